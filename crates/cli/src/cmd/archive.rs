@@ -0,0 +1,392 @@
+use crate::http::http_options;
+use clap::Clap;
+use etwin_core::clock::VirtualClock;
+use etwin_core::core::Secret;
+use etwin_core::dinoparc::{DinoparcServer, DinoparcStore, DinoparcUserId, DinoparcUsername};
+use etwin_core::dinorpg::DinorpgStore;
+use etwin_core::hammerfest::HammerfestStore;
+use etwin_core::popotamo::PopotamoStore;
+use etwin_core::twinoid::{TwinoidContactList, TwinoidProfile, TwinoidStore};
+use etwin_core::types::EtwinError;
+use etwin_core::uuid::Uuid4Generator;
+use etwin_dinoparc_client::http::reparse::DinoparcArchivedPage;
+use etwin_dinoparc_store::pg::PgDinoparcStore;
+use etwin_dinorpg_client::http::reparse::DinorpgArchivedPage;
+use etwin_dinorpg_store::pg::PgDinorpgStore;
+use etwin_hammerfest_client::HammerfestArchivedPage;
+use etwin_hammerfest_store::pg::PgHammerfestStore;
+use etwin_http::archive::{read_warc, WarcRecord};
+use etwin_http::{HttpOptions, HttpResponse};
+use etwin_popotamo_client::http::reparse::PopotamoArchivedPage;
+use etwin_popotamo_store::pg::PgPopotamoStore;
+use etwin_twinoid_client::http::reparse::TwinoidArchivedPage;
+use etwin_twinoid_store::pg::PgTwinoidStore;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Arguments to the `archive` task.
+#[derive(Debug, Clap)]
+pub struct ArchiveArgs {
+  #[clap(subcommand)]
+  command: ArchiveCommand,
+}
+
+#[derive(Debug, Clap)]
+pub enum ArchiveCommand {
+  /// Parse the pages of WARC archives again and update the stores
+  #[clap(name = "reparse")]
+  Reparse(ReparseArgs),
+}
+
+/// Arguments to the `archive reparse` task.
+#[derive(Debug, Clap)]
+pub struct ReparseArgs {
+  /// WARC files to reparse
+  #[clap(required = true)]
+  files: Vec<PathBuf>,
+}
+
+pub async fn run(args: &ArchiveArgs) -> Result<(), EtwinError> {
+  match &args.command {
+    ArchiveCommand::Reparse(ref args) => reparse(args).await,
+  }
+}
+
+struct ArchiveStores {
+  dinoparc: Arc<dyn DinoparcStore>,
+  dinorpg: Arc<dyn DinorpgStore>,
+  hammerfest: Arc<dyn HammerfestStore>,
+  popotamo: Arc<dyn PopotamoStore>,
+  twinoid: Arc<dyn TwinoidStore>,
+}
+
+async fn reparse(args: &ReparseArgs) -> Result<(), EtwinError> {
+  let mut records: Vec<WarcRecord> = Vec::new();
+  for file in args.files.iter() {
+    let file_records = read_warc(file)?;
+    eprintln!("Read {} records from {}", file_records.len(), file.display());
+    records.extend(file_records);
+  }
+  // The stores use their clock as the archival time: replay the pages in fetch order
+  records.sort_by_key(|record| record.time);
+  let start = match records.first() {
+    Some(record) => record.time,
+    None => {
+      eprintln!("No records to reparse");
+      return Ok(());
+    }
+  };
+
+  let config = etwin_config::find_config(env::current_dir()?).map_err(|e| format!("failed to read config: {:?}", e))?;
+  let database: PgPool = PgPoolOptions::new()
+    .max_connections(5)
+    .connect_with(
+      PgConnectOptions::new()
+        .host(&config.db.host)
+        .port(config.db.port)
+        .database(&config.db.name)
+        .username(&config.db.user)
+        .password(&config.db.password),
+    )
+    .await?;
+  let database = Arc::new(database);
+  let clock = Arc::new(VirtualClock::new(start));
+  let box_error = |e: Box<dyn std::error::Error + Send>| -> EtwinError { e.to_string().into() };
+  let stores = ArchiveStores {
+    dinoparc: Arc::new(
+      PgDinoparcStore::new(Arc::clone(&clock), Arc::clone(&database), Uuid4Generator)
+        .await
+        .map_err(box_error)?,
+    ),
+    dinorpg: Arc::new(
+      PgDinorpgStore::new(Arc::clone(&clock), Arc::clone(&database))
+        .await
+        .map_err(box_error)?,
+    ),
+    hammerfest: Arc::new(
+      PgHammerfestStore::new(
+        Arc::clone(&clock),
+        Arc::clone(&database),
+        Secret::new(config.etwin.secret.clone()),
+        Uuid4Generator,
+      )
+      .await
+      .map_err(box_error)?,
+    ),
+    popotamo: Arc::new(
+      PgPopotamoStore::new(Arc::clone(&clock), Arc::clone(&database))
+        .await
+        .map_err(box_error)?,
+    ),
+    twinoid: Arc::new(PgTwinoidStore::new(Arc::clone(&clock), Arc::clone(&database))),
+  };
+  // Pages fetched through a mirror are archived with the mirror URL
+  let options = config.http.as_ref().map(http_options).unwrap_or_default();
+  let mut reparser = Reparser::new(stores, options);
+
+  let (mut touched, mut skipped, mut failed) = (0usize, 0usize, 0usize);
+  for record in records {
+    clock.advance_to(record.time);
+    let res = &record.response;
+    if !res.status().is_success() {
+      skipped += 1;
+      continue;
+    }
+    match reparser.touch_page(res).await {
+      Ok(true) => touched += 1,
+      Ok(false) => skipped += 1,
+      Err(e) => {
+        failed += 1;
+        eprintln!("Failed to reparse {} (fetched at {}): {}", res.url(), record.time, e);
+      }
+    }
+  }
+  eprintln!("Touched: {}, skipped: {}, failed: {}", touched, skipped, failed);
+  if failed > 0 {
+    return Err(format!("failed to reparse {} pages", failed).into());
+  }
+  Ok(())
+}
+
+struct Reparser {
+  stores: ArchiveStores,
+  options: HttpOptions,
+  /// Ids of the Dinoparc users seen so far, to resolve the session user of the pages requiring a session
+  dinoparc_users: HashMap<(DinoparcServer, DinoparcUsername), DinoparcUserId>,
+}
+
+impl Reparser {
+  fn new(stores: ArchiveStores, options: HttpOptions) -> Self {
+    Self {
+      stores,
+      options,
+      dinoparc_users: HashMap::new(),
+    }
+  }
+
+  /// Parses the page with the matching scraper and touches the corresponding store.
+  ///
+  /// Returns `false` if there is no scraper for this page.
+  async fn touch_page(&mut self, res: &HttpResponse) -> Result<bool, EtwinError> {
+    let (stores, url) = (&self.stores, res.url());
+    if let Some(page) =
+      etwin_twinoid_client::http::reparse::reparse_page(&self.options, url, res.status(), res.bytes())?
+    {
+      match page {
+        TwinoidArchivedPage::User(user) => {
+          // Pages fetched at login (`UserQuery::archive()`) contain both the profile and the contacts
+          let (has_contacts, has_sites) = (user.contacts.is_some(), user.sites.is_some());
+          if has_contacts {
+            stores
+              .twinoid
+              .touch_contacts(&TwinoidContactList::try_from(user.clone())?)
+              .await?;
+          }
+          if has_sites {
+            stores.twinoid.touch_profile(&TwinoidProfile::try_from(user)?).await?;
+          } else if !has_contacts {
+            let profile = TwinoidProfile::try_from(user)?;
+            stores.twinoid.touch_short_user(&profile.user).await?;
+          }
+        }
+      }
+      return Ok(true);
+    }
+
+    let html = res.text();
+    if let Some(page) = etwin_hammerfest_client::reparse_page(&self.options, url, &html)? {
+      match page {
+        HammerfestArchivedPage::Profile(response) => stores.hammerfest.touch_profile(&response).await?,
+        HammerfestArchivedPage::Inventory(response) => stores.hammerfest.touch_inventory(&response).await?,
+        HammerfestArchivedPage::Shop(response) => stores.hammerfest.touch_shop(&response).await?,
+        HammerfestArchivedPage::Godchildren(response) => stores.hammerfest.touch_godchildren(&response).await?,
+        HammerfestArchivedPage::ForumTheme(response) => stores.hammerfest.touch_theme_page(&response).await?,
+        HammerfestArchivedPage::ForumThread(response) => stores.hammerfest.touch_thread_page(&response).await?,
+      }
+      return Ok(true);
+    }
+    let dinoparc_users = &self.dinoparc_users;
+    let session_user_id =
+      |server: DinoparcServer, username: &DinoparcUsername| dinoparc_users.get(&(server, username.clone())).copied();
+    if let Some(page) = etwin_dinoparc_client::http::reparse::reparse_page(&self.options, url, &html, &session_user_id)?
+    {
+      let user = match page {
+        DinoparcArchivedPage::Profile(profile) => {
+          stores.dinoparc.touch_short_user(&profile.user).await?;
+          Some(profile.user)
+        }
+        DinoparcArchivedPage::Bank(user) => {
+          stores.dinoparc.touch_short_user(&user).await?;
+          Some(user)
+        }
+        DinoparcArchivedPage::Inventory(response) => {
+          stores.dinoparc.touch_inventory(&response).await?;
+          None
+        }
+        DinoparcArchivedPage::Collection(response) => {
+          stores.dinoparc.touch_collection(&response).await?;
+          None
+        }
+        DinoparcArchivedPage::Dinoz(response) => {
+          stores.dinoparc.touch_dinoz(&response).await?;
+          None
+        }
+        DinoparcArchivedPage::ExchangeWith(response) => {
+          stores.dinoparc.touch_exchange_with(&response).await?;
+          None
+        }
+      };
+      if let Some(user) = user {
+        self.dinoparc_users.insert((user.server, user.username), user.id);
+      }
+      return Ok(true);
+    }
+    if let Some(page) = etwin_dinorpg_client::http::reparse::reparse_page(url, &html)? {
+      match page {
+        // DinoRPG pages do not contain usernames: the user must already be archived
+        DinorpgArchivedPage::Profile(response) => stores.dinorpg.touch_profile(&response).await?,
+      }
+      return Ok(true);
+    }
+    if let Some(page) = etwin_popotamo_client::http::reparse::reparse_page(url, &html)? {
+      match page {
+        PopotamoArchivedPage::Profile(response) => stores.popotamo.touch_profile(&response).await?,
+      }
+      return Ok(true);
+    }
+    Ok(false)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{ArchiveStores, Reparser};
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::dinoparc::{DinoparcServer, DinoparcStore, DinoparcUserId, GetDinoparcUserOptions};
+  use etwin_core::twinoid::{GetTwinoidUserOptions, TwinoidStore, TwinoidUserId};
+  use etwin_dinoparc_store::mem::MemDinoparcStore;
+  use etwin_dinorpg_store::mem::MemDinorpgStore;
+  use etwin_hammerfest_store::mem::MemHammerfestStore;
+  use etwin_http::reqwest::header::HeaderMap;
+  use etwin_http::reqwest::{StatusCode, Url};
+  use etwin_http::{HttpOptions, HttpResponse};
+  use etwin_popotamo_store::mem::MemPopotamoStore;
+  use etwin_twinoid_store::mem::MemTwinoidStore;
+  use std::str::FromStr;
+  use std::sync::Arc;
+
+  fn page(url: &str, body: Vec<u8>) -> HttpResponse {
+    HttpResponse {
+      url: Url::parse(url).unwrap(),
+      status: StatusCode::OK,
+      headers: HeaderMap::new(),
+      body,
+    }
+  }
+
+  #[tokio::test]
+  async fn reparse_mirror_session_pages() {
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)));
+    let dinoparc: Arc<dyn DinoparcStore> = Arc::new(MemDinoparcStore::new(Arc::clone(&clock)));
+    let twinoid: Arc<dyn TwinoidStore> = Arc::new(MemTwinoidStore::new(Arc::clone(&clock)));
+    let stores = ArchiveStores {
+      dinoparc: Arc::clone(&dinoparc),
+      dinorpg: Arc::new(MemDinorpgStore::new(Arc::clone(&clock))),
+      hammerfest: Arc::new(MemHammerfestStore::new(Arc::clone(&clock))),
+      popotamo: Arc::new(MemPopotamoStore::new(Arc::clone(&clock))),
+      twinoid: Arc::clone(&twinoid),
+    };
+    let options = HttpOptions {
+      base_urls: vec![(
+        String::from("dinoparc.com"),
+        Url::parse("http://127.0.0.1:8080/dinoparc.com/").unwrap(),
+      )]
+      .into_iter()
+      .collect(),
+      ..HttpOptions::default()
+    };
+    let mut reparser = Reparser::new(stores, options);
+
+    let dir = "../../test-resources/scraping/dinoparc";
+    let inventory = std::fs::read(format!("{}/inventory/fr.user2480723/main.utf8.html", dir)).unwrap();
+    let inventory = page("http://127.0.0.1:8080/dinoparc.com/?a=inventory", inventory);
+    // The session user is unknown until its bank page is reparsed
+    assert!(reparser.touch_page(&inventory).await.is_err());
+    let bank = std::fs::read(format!("{}/bank/fr.user2480723/main.utf8.html", dir)).unwrap();
+    let bank = page("http://127.0.0.1:8080/dinoparc.com/?a=bank", bank);
+    assert!(reparser.touch_page(&bank).await.unwrap());
+    assert!(reparser.touch_page(&inventory).await.unwrap());
+    let options = GetDinoparcUserOptions {
+      server: DinoparcServer::DinoparcCom,
+      id: DinoparcUserId::from_str("2480723").unwrap(),
+      time: None,
+    };
+    assert!(dinoparc.get_inventory(&options).await.unwrap().is_some());
+
+    let me = page(
+      "https://twinoid.com/graph/me?fields=id,name",
+      br#"{"id":38,"name":"alice"}"#.to_vec(),
+    );
+    assert!(reparser.touch_page(&me).await.unwrap());
+    let options = GetTwinoidUserOptions {
+      id: TwinoidUserId::from_str("38").unwrap(),
+      time: None,
+    };
+    assert!(twinoid.get_short_user(&options).await.unwrap().is_some());
+
+    let unknown = page("http://www.example.com/", Vec::new());
+    assert!(!reparser.touch_page(&unknown).await.unwrap());
+  }
+
+  #[tokio::test]
+  async fn reparse_twinoid_login_archive() {
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)));
+    let twinoid: Arc<dyn TwinoidStore> = Arc::new(MemTwinoidStore::new(Arc::clone(&clock)));
+    let stores = ArchiveStores {
+      dinoparc: Arc::new(MemDinoparcStore::new(Arc::clone(&clock))),
+      dinorpg: Arc::new(MemDinorpgStore::new(Arc::clone(&clock))),
+      hammerfest: Arc::new(MemHammerfestStore::new(Arc::clone(&clock))),
+      popotamo: Arc::new(MemPopotamoStore::new(Arc::clone(&clock))),
+      twinoid: Arc::clone(&twinoid),
+    };
+    let mut reparser = Reparser::new(stores, HttpOptions::default());
+
+    // Response to `UserQuery::archive()`, as recorded by the login archival
+    let me = page(
+      "https://twinoid.com/graph/me?access_token=REDACTED&fields=id,name,title,picture,locale,gender,city,sites.fields(site.fields(id),stats,achievements.fields(id,points)),contacts.fields(user.fields(id,name),friend)",
+      br#"{
+        "id": 38,
+        "name": "alice",
+        "title": "Explorer",
+        "picture": null,
+        "locale": "fr",
+        "gender": "female",
+        "city": null,
+        "sites": [{"site": {"id": 1}, "stats": [{"id": "score", "score": 100}], "achievements": [{"id": "first_game", "points": 10}]}],
+        "contacts": [{"user": {"id": 39, "name": "bob"}, "friend": true}]
+      }"#
+      .to_vec(),
+    );
+    assert!(reparser.touch_page(&me).await.unwrap());
+
+    let options = GetTwinoidUserOptions {
+      id: TwinoidUserId::from_str("38").unwrap(),
+      time: None,
+    };
+    let user = twinoid.get_user(&options).await.unwrap().unwrap();
+    assert_eq!(user.details.unwrap().latest.value.title.as_deref(), Some("Explorer"));
+    assert_eq!(user.stats.len(), 1);
+    assert_eq!(user.achievements.len(), 1);
+    let contacts = twinoid.get_contacts(&options).await.unwrap().unwrap();
+    let contacts = contacts.latest.value;
+    assert_eq!(contacts.len(), 1);
+    assert_eq!(contacts[0].user.display_name.as_str(), "bob");
+    assert!(contacts[0].friend);
+  }
+}
@@ -1,10 +1,10 @@
+use crate::http::load_http_options;
 use clap::Clap;
 use dialoguer::{theme::ColorfulTheme, Input, Password, Select};
 use etwin_core::clock::SystemClock;
 use etwin_core::dinoparc::{DinoparcClient, DinoparcCredentials, DinoparcPassword, DinoparcServer, DinoparcUsername};
 use etwin_core::types::EtwinError;
-use etwin_dinoparc_client::http::HttpDinoparcClient;
-use etwin_log::NoopLogger;
+use etwin_dinoparc_client::http::{new_logger, HttpDinoparcClient};
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -49,7 +49,9 @@ pub async fn run(_args: &DinoparcArgs) -> Result<(), EtwinError> {
   };
 
   let clock = SystemClock;
-  let dinoparc_client = HttpDinoparcClient::new(clock, NoopLogger).unwrap();
+  let http_options = load_http_options()?;
+  let dinoparc_client =
+    HttpDinoparcClient::with_options(clock, new_logger(http_options.logger), &http_options).unwrap();
   let session = dinoparc_client.create_session(&credentials).await.unwrap();
 
   eprintln!("AcquiredSession:");
@@ -0,0 +1,66 @@
+use crate::http::load_http_options;
+use clap::Clap;
+use etwin_core::clock::SystemClock;
+use etwin_core::types::EtwinError;
+use etwin_dinoparc_client::http::HttpDinoparcClient;
+use etwin_hammerfest_client::HttpHammerfestClient;
+use etwin_http::event::new_logger;
+use etwin_scraper_tools::drift::DriftReport;
+use std::path::PathBuf;
+
+/// Arguments to the `scraper` task.
+#[derive(Debug, Clap)]
+pub struct ScraperArgs {
+  #[clap(subcommand)]
+  command: ScraperCommand,
+}
+
+#[derive(Debug, Clap)]
+pub enum ScraperCommand {
+  /// Check the Hammerfest and Dinoparc scrapers against recorded pages and live canaries
+  #[clap(name = "check")]
+  Check(CheckArgs),
+}
+
+/// Arguments to the `scraper check` task.
+#[derive(Debug, Clap)]
+pub struct CheckArgs {
+  /// Directory containing the recorded pages, organized like the scraping test resources
+  #[clap(long, default_value = "test-resources/scraping")]
+  catalog: PathBuf,
+  /// Only check the recorded pages, without fetching the canaries
+  #[clap(long)]
+  offline: bool,
+}
+
+pub async fn run(args: &ScraperArgs) -> Result<(), EtwinError> {
+  match &args.command {
+    ScraperCommand::Check(ref args) => check(args).await,
+  }
+}
+
+async fn check(args: &CheckArgs) -> Result<(), EtwinError> {
+  let mut pages = etwin_hammerfest_client::check_catalog(&args.catalog.join("hammerfest"))?;
+  pages.extend(etwin_dinoparc_client::http::selfcheck::check_catalog(
+    &args.catalog.join("dinoparc"),
+  )?);
+
+  if !args.offline {
+    let http_options = load_http_options()?;
+    let hammerfest = HttpHammerfestClient::with_options(SystemClock, new_logger(http_options.logger), &http_options)?;
+    pages.extend(hammerfest.check_canaries().await);
+    let dinoparc = HttpDinoparcClient::with_options(
+      SystemClock,
+      etwin_dinoparc_client::http::new_logger(http_options.logger),
+      &http_options,
+    )?;
+    pages.extend(dinoparc.check_canaries().await);
+  }
+
+  let report = DriftReport::new(pages);
+  print!("{}", report);
+  if !report.is_ok() {
+    return Err("scraper drift detected".into());
+  }
+  Ok(())
+}
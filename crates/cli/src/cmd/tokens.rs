@@ -0,0 +1,109 @@
+use chrono::Duration;
+use clap::Clap;
+use etwin_config::Config;
+use etwin_core::clock::SystemClock;
+use etwin_core::core::SecretKeyring;
+use etwin_core::token::{SweepTokensOptions, TokenStore};
+use etwin_core::types::EtwinError;
+use etwin_token_store::pg::{token_keyring, PgTokenStore};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+
+/// Arguments to the `tokens` task.
+#[derive(Debug, Clap)]
+pub struct TokensArgs {
+  #[clap(subcommand)]
+  command: TokensCommand,
+}
+
+#[derive(Debug, Clap)]
+pub enum TokensCommand {
+  /// Re-encrypt the stored game sessions and OAuth tokens with the current key
+  #[clap(name = "rotate")]
+  Rotate(RotateArgs),
+  /// Delete the expired OAuth tokens and the game sessions unused for too long
+  #[clap(name = "gc")]
+  Gc(GcArgs),
+}
+
+/// Arguments to the `tokens rotate` task.
+#[derive(Debug, Clap)]
+pub struct RotateArgs {}
+
+/// Arguments to the `tokens gc` task.
+#[derive(Debug, Clap)]
+pub struct GcArgs {
+  /// Delete the Twinoid refresh tokens unused for this number of days
+  #[clap(long, default_value = "365")]
+  max_refresh_token_age_days: i64,
+  /// Delete the game sessions unused for this number of days
+  #[clap(long, default_value = "30")]
+  max_session_age_days: i64,
+}
+
+pub async fn run(args: &TokensArgs) -> Result<(), EtwinError> {
+  match &args.command {
+    TokensCommand::Rotate(ref args) => rotate(args).await,
+    TokensCommand::Gc(ref args) => gc(args).await,
+  }
+}
+
+async fn create_token_store(
+  config: &Config,
+  keyring: SecretKeyring,
+) -> Result<PgTokenStore<SystemClock, Arc<PgPool>>, EtwinError> {
+  let database: PgPool = PgPoolOptions::new()
+    .max_connections(5)
+    .connect_with(
+      PgConnectOptions::new()
+        .host(&config.db.host)
+        .port(config.db.port)
+        .database(&config.db.name)
+        .username(&config.db.user)
+        .password(&config.db.password),
+    )
+    .await?;
+  PgTokenStore::with_keyring(SystemClock, Arc::new(database), keyring)
+    .await
+    .map_err(|e| -> EtwinError { e.to_string().into() })
+}
+
+async fn rotate(_args: &RotateArgs) -> Result<(), EtwinError> {
+  let config = etwin_config::find_config(env::current_dir()?).map_err(|e| format!("failed to read config: {:?}", e))?;
+  let keyring = token_keyring(&config)?;
+  let token_store = create_token_store(&config, keyring.clone()).await?;
+
+  eprintln!("Current key: {}", keyring.current().id);
+  let rotation = token_store.rotate_keys().await?;
+  eprintln!("Rotated: {}, unknown key: {}", rotation.rotated, rotation.unknown_key);
+  if rotation.unknown_key > 0 {
+    return Err(
+      format!(
+        "{} secrets use a key missing from the config, they can no longer be decrypted",
+        rotation.unknown_key
+      )
+      .into(),
+    );
+  }
+  Ok(())
+}
+
+async fn gc(args: &GcArgs) -> Result<(), EtwinError> {
+  let config = etwin_config::find_config(env::current_dir()?).map_err(|e| format!("failed to read config: {:?}", e))?;
+  let token_store = create_token_store(&config, token_keyring(&config)?).await?;
+
+  let sweep = token_store
+    .sweep(&SweepTokensOptions {
+      max_refresh_token_age: Duration::days(args.max_refresh_token_age_days),
+      max_session_age: Duration::days(args.max_session_age_days),
+    })
+    .await?;
+  eprintln!("Deleted Twinoid access tokens: {}", sweep.twinoid_access_tokens);
+  eprintln!("Deleted Twinoid refresh tokens: {}", sweep.twinoid_refresh_tokens);
+  eprintln!("Deleted Dinoparc sessions: {}", sweep.dinoparc_sessions);
+  eprintln!("Deleted Hammerfest sessions: {}", sweep.hammerfest_sessions);
+  eprintln!("Deleted Popotamo sessions: {}", sweep.popotamo_sessions);
+  Ok(())
+}
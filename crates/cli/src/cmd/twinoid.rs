@@ -1,10 +1,12 @@
+use crate::http::load_http_options;
 use clap::Clap;
 use dialoguer::{theme::ColorfulTheme, Input};
 use etwin_core::clock::SystemClock;
 use etwin_core::oauth::RfcOauthAccessTokenKey;
-use etwin_core::twinoid::api::ConstUserQuery;
+use etwin_core::twinoid::api::UserQuery;
 use etwin_core::twinoid::{TwinoidApiAuth, TwinoidClient};
 use etwin_core::types::EtwinError;
+use etwin_http::event::new_logger;
 use etwin_twinoid_client::http::HttpTwinoidClient;
 
 /// Arguments to the `twinoid` task.
@@ -22,10 +24,13 @@ pub async fn run(_args: &TwinoidArgs) -> Result<(), EtwinError> {
   let auth = TwinoidApiAuth::Token(token);
 
   let clock = SystemClock;
-  let twinoid_client = HttpTwinoidClient::new(clock).unwrap();
+  let http_options = load_http_options()?;
+  let twinoid_client = HttpTwinoidClient::with_options(clock, new_logger(http_options.logger), &http_options).unwrap();
 
   eprintln!("Fetching `me`");
-  let me = twinoid_client.get_me(auth, &ConstUserQuery::<true, true>).await?;
+  let me = twinoid_client
+    .get_me(auth, &UserQuery::new().name().title().picture().locale())
+    .await?;
   eprintln!("Fetched `me`:");
   eprintln!("{:#?}", &me);
 
@@ -0,0 +1,42 @@
+use etwin_config::{FindConfigError, HttpConfig, HttpLoggerConfig};
+use etwin_core::types::EtwinError;
+use etwin_http::policy::{RateLimit, RetryPolicy};
+use etwin_http::{HttpLogger, HttpOptions};
+use std::time::Duration;
+
+/// Reads the options of the game HTTP clients from `etwin.toml`, using the defaults if there is no config file.
+pub fn load_http_options() -> Result<HttpOptions, EtwinError> {
+  let working_dir = std::env::current_dir()?;
+  match etwin_config::find_config(working_dir) {
+    Ok(config) => Ok(config.http.as_ref().map(http_options).unwrap_or_default()),
+    Err(FindConfigError::NotFound(_)) => Ok(HttpOptions::default()),
+    Err(e) => Err(format!("failed to read config: {:?}", e).into()),
+  }
+}
+
+pub fn http_options(config: &HttpConfig) -> HttpOptions {
+  HttpOptions {
+    timeout: Duration::from_millis(config.timeout_ms),
+    rate_limit: RateLimit {
+      period: Duration::from_millis(config.rate_limit.period_ms),
+      burst: config.rate_limit.burst,
+    },
+    retry: RetryPolicy {
+      max_retries: config.retry.max_retries,
+      base_delay: Duration::from_millis(config.retry.base_delay_ms),
+      max_delay: Duration::from_millis(config.retry.max_delay_ms),
+    },
+    archive: config.archive.clone(),
+    proxy: config.proxy.clone(),
+    base_urls: config.base_urls.clone(),
+    logger: http_logger(config.logger),
+    ..HttpOptions::default()
+  }
+}
+
+pub fn http_logger(config: HttpLoggerConfig) -> HttpLogger {
+  match config {
+    HttpLoggerConfig::None => HttpLogger::Noop,
+    HttpLoggerConfig::Stderr => HttpLogger::Stderr,
+  }
+}
@@ -3,10 +3,14 @@ use clap::Clap;
 use etwin_core::types::EtwinError;
 
 pub mod cmd {
+  pub mod archive;
   pub mod dinoparc;
   pub mod dump;
+  pub mod scraper;
+  pub mod tokens;
   pub mod twinoid;
 }
+pub mod http;
 pub mod rest;
 
 #[derive(Debug, Clap)]
@@ -18,6 +22,9 @@ pub struct CliArgs {
 
 #[derive(Debug, Clap)]
 pub enum CliCommand {
+  /// Manage the raw page archives
+  #[clap(name = "archive")]
+  Archive(cmd::archive::ArchiveArgs),
   /// Run the Dinoparc client demo
   #[clap(name = "dinoparc")]
   Dinoparc(cmd::dinoparc::DinoparcArgs),
@@ -27,6 +34,12 @@ pub enum CliCommand {
   /// Start REST server
   #[clap(name = "rest")]
   Rest(RestArgs),
+  /// Check the scrapers for markup drift
+  #[clap(name = "scraper")]
+  Scraper(cmd::scraper::ScraperArgs),
+  /// Manage the stored game sessions and OAuth tokens
+  #[clap(name = "tokens")]
+  Tokens(cmd::tokens::TokensArgs),
   /// Run the Twinoid client demo
   #[clap(name = "twinoid")]
   Twinoid(cmd::twinoid::TwinoidArgs),
@@ -34,9 +47,12 @@ pub enum CliCommand {
 
 pub async fn run(args: &CliArgs) -> Result<(), EtwinError> {
   match &args.command {
+    CliCommand::Archive(ref args) => cmd::archive::run(args).await,
     CliCommand::Dinoparc(ref args) => cmd::dinoparc::run(args).await,
     CliCommand::Dump(ref args) => cmd::dump::run(args).await,
     CliCommand::Rest(ref args) => crate::rest::run(args).await,
+    CliCommand::Scraper(ref args) => cmd::scraper::run(args).await,
+    CliCommand::Tokens(ref args) => cmd::tokens::run(args).await,
     CliCommand::Twinoid(ref args) => cmd::twinoid::run(args).await,
   }
 }
@@ -1,15 +1,19 @@
+use crate::http::load_http_options;
 use chrono::{TimeZone, Utc};
 use clap::Clap;
 use etwin_core::clock::VirtualClock;
-use etwin_core::dinoparc::DinoparcStore;
+use etwin_core::dinoparc::{DinoparcClient, DinoparcStore};
 use etwin_core::hammerfest::{HammerfestClient, HammerfestStore};
 use etwin_core::link::LinkStore;
 use etwin_core::types::EtwinError;
 use etwin_core::user::UserStore;
 use etwin_core::uuid::Uuid4Generator;
+use etwin_dinoparc_client::http::HttpDinoparcClient;
 use etwin_dinoparc_store::mem::MemDinoparcStore;
 use etwin_hammerfest_client::HttpHammerfestClient;
 use etwin_hammerfest_store::mem::MemHammerfestStore;
+use etwin_http::event::new_logger;
+use etwin_http::HttpOptions;
 use etwin_link_store::mem::MemLinkStore;
 use etwin_rest::{create_rest_filter, RouterApi};
 use etwin_services::dinoparc::DinoparcService;
@@ -22,15 +26,26 @@ use std::sync::Arc;
 #[derive(Debug, Clap)]
 pub struct RestArgs {}
 
-fn create_api() -> RouterApi {
+fn create_api(http_options: &HttpOptions) -> RouterApi {
   let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
-  let hammerfest_client: Arc<dyn HammerfestClient> = Arc::new(HttpHammerfestClient::new(Arc::clone(&clock)).unwrap());
+  let hammerfest_client: Arc<dyn HammerfestClient> = Arc::new(
+    HttpHammerfestClient::with_options(Arc::clone(&clock), new_logger(http_options.logger), http_options).unwrap(),
+  );
   let hammerfest_store: Arc<dyn HammerfestStore> = Arc::new(MemHammerfestStore::new(Arc::clone(&clock)));
+  let dinoparc_client: Arc<dyn DinoparcClient> = Arc::new(
+    HttpDinoparcClient::with_options(
+      Arc::clone(&clock),
+      etwin_dinoparc_client::http::new_logger(http_options.logger),
+      http_options,
+    )
+    .unwrap(),
+  );
   let dinoparc_store: Arc<dyn DinoparcStore> = Arc::new(MemDinoparcStore::new(Arc::clone(&clock)));
   let link_store: Arc<dyn LinkStore> = Arc::new(MemLinkStore::new(Arc::clone(&clock)));
   let user_store: Arc<dyn UserStore> = Arc::new(MemUserStore::new(Arc::clone(&clock), Uuid4Generator));
 
   let dinoparc = Arc::new(DinoparcService::new(
+    dinoparc_client,
     dinoparc_store,
     Arc::clone(&link_store),
     Arc::clone(&user_store),
@@ -47,7 +62,7 @@ fn create_api() -> RouterApi {
 }
 
 pub async fn run(_args: &RestArgs) -> Result<(), EtwinError> {
-  let api = create_api();
+  let api = create_api(&load_http_options()?);
   let routes = create_rest_filter(api);
 
   eprintln!("Started at http://localhost:3030");
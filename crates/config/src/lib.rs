@@ -1,5 +1,6 @@
 use once_cell::sync::Lazy;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -10,10 +11,12 @@ pub struct Config {
   pub etwin: EtwinConfig,
   pub db: DbConfig,
   pub mailer: Option<MailerConfig>,
+  pub http: Option<HttpConfig>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
 pub struct EtwinConfig {
+  pub secret: String,
   pub http_port: u16,
   pub external_uri: Url,
 }
@@ -27,6 +30,11 @@ pub struct DbConfig {
   pub admin_password: String,
   pub user: String,
   pub password: String,
+  /// Id of the key encrypting the stored game sessions and OAuth tokens, defaults to the main secret (`etwin.secret`)
+  pub token_key: Option<String>,
+  /// Keys encrypting the stored game sessions and OAuth tokens, keyed by id
+  #[serde(default)]
+  pub token_keys: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
@@ -44,6 +52,47 @@ pub struct MailerHeader {
   pub value: String,
 }
 
+/// Configuration of the HTTP clients used to reach the game servers.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub struct HttpConfig {
+  pub timeout_ms: u64,
+  pub rate_limit: HttpRateLimitConfig,
+  pub retry: HttpRetryConfig,
+  /// Directory where the fetched pages are archived as WARC files
+  pub archive: Option<PathBuf>,
+  /// Proxy used for all the outgoing requests
+  pub proxy: Option<Url>,
+  /// Base URL replacing the official host of a server, keyed by server name
+  #[serde(default)]
+  pub base_urls: BTreeMap<String, Url>,
+  /// Destination of the events of the HTTP clients (requests, responses and failures)
+  #[serde(default)]
+  pub logger: HttpLoggerConfig,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpLoggerConfig {
+  /// Discard the events
+  #[default]
+  None,
+  /// Print the events to the standard error
+  Stderr,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub struct HttpRateLimitConfig {
+  pub period_ms: u64,
+  pub burst: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub struct HttpRetryConfig {
+  pub max_retries: u32,
+  pub base_delay_ms: u64,
+  pub max_delay_ms: u64,
+}
+
 #[derive(Debug)]
 pub enum FindConfigFileError {
   NotFound(PathBuf),
@@ -87,6 +136,7 @@ pub fn find_config(dir: PathBuf) -> Result<Config, FindConfigError> {
 
 pub static DEFAULT: Lazy<Config> = Lazy::new(|| Config {
   etwin: EtwinConfig {
+    secret: "dev_secret".to_string(),
     http_port: 50320,
     external_uri: Url::parse("http://localhost:50320/").unwrap(),
   },
@@ -98,18 +148,24 @@ pub static DEFAULT: Lazy<Config> = Lazy::new(|| Config {
     admin_password: "dev".to_string(),
     user: "etwin.dev.admin".to_string(),
     password: "dev".to_string(),
+    token_key: None,
+    token_keys: BTreeMap::new(),
   },
   mailer: None,
+  http: None,
 });
 
 #[cfg(test)]
 mod test {
-  use crate::{parse_config, DEFAULT};
+  use crate::{parse_config, DbConfig, HttpConfig, HttpLoggerConfig, HttpRateLimitConfig, HttpRetryConfig, DEFAULT};
+  use std::path::PathBuf;
+  use url::Url;
 
   #[test]
   fn test_default_config() {
     const INPUT: &str = r#"
 [etwin]
+secret = "dev_secret"
 http_port = 50320
 external_uri = "http://localhost:50320"
 
@@ -127,4 +183,102 @@ password = "dev"
     let expected = Ok(DEFAULT.clone());
     assert_eq!(actual, expected);
   }
+
+  #[test]
+  fn test_http_config() {
+    const INPUT: &str = r#"
+[etwin]
+secret = "dev_secret"
+http_port = 50320
+external_uri = "http://localhost:50320"
+
+[db]
+host = "localhost"
+port = 5432
+name = "etwin.dev"
+admin_user = "etwin.dev.admin"
+admin_password = "dev"
+user = "etwin.dev.admin"
+password = "dev"
+
+[http]
+timeout_ms = 10000
+archive = "./archive"
+proxy = "http://localhost:3128"
+logger = "stderr"
+
+[http.base_urls]
+"hammerfest.fr" = "http://localhost:50321/hammerfest.fr/"
+
+[http.rate_limit]
+period_ms = 1000
+burst = 2
+
+[http.retry]
+max_retries = 5
+base_delay_ms = 200
+max_delay_ms = 60000
+    "#;
+    let path = std::env::current_dir().unwrap().join("etwin.toml");
+    let actual = parse_config(&path, INPUT).unwrap();
+    let expected = Some(HttpConfig {
+      timeout_ms: 10000,
+      rate_limit: HttpRateLimitConfig {
+        period_ms: 1000,
+        burst: 2,
+      },
+      retry: HttpRetryConfig {
+        max_retries: 5,
+        base_delay_ms: 200,
+        max_delay_ms: 60000,
+      },
+      archive: Some(PathBuf::from("./archive")),
+      proxy: Some(Url::parse("http://localhost:3128").unwrap()),
+      base_urls: vec![(
+        String::from("hammerfest.fr"),
+        Url::parse("http://localhost:50321/hammerfest.fr/").unwrap(),
+      )]
+      .into_iter()
+      .collect(),
+      logger: HttpLoggerConfig::Stderr,
+    });
+    assert_eq!(actual.http, expected);
+  }
+
+  #[test]
+  fn test_db_token_keys() {
+    const INPUT: &str = r#"
+[etwin]
+secret = "dev_secret"
+http_port = 50320
+external_uri = "http://localhost:50320"
+
+[db]
+host = "localhost"
+port = 5432
+name = "etwin.dev"
+admin_user = "etwin.dev.admin"
+admin_password = "dev"
+user = "etwin.dev.admin"
+password = "dev"
+token_key = "2021-06"
+
+[db.token_keys]
+"2021-01" = "old_secret"
+"2021-06" = "new_secret"
+    "#;
+    let path = std::env::current_dir().unwrap().join("etwin.toml");
+    let actual = parse_config(&path, INPUT).unwrap();
+    let expected = DbConfig {
+      token_key: Some(String::from("2021-06")),
+      token_keys: vec![
+        (String::from("2021-01"), String::from("old_secret")),
+        (String::from("2021-06"), String::from("new_secret")),
+      ]
+      .into_iter()
+      .collect(),
+      ..DEFAULT.db.clone()
+    };
+    assert_eq!(actual.db, expected);
+  }
 }
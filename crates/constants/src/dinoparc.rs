@@ -1,4 +1,8 @@
-use etwin_core::dinoparc::{DinoparcLocationId, DinoparcSkill};
+use crate::localized_name;
+use etwin_core::core::LocaleId;
+use etwin_core::dinoparc::{DinoparcItemId, DinoparcLocationId, DinoparcRewardId, DinoparcSkill};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
 
 pub const MAX_SIDEBAR_DINOZ_COUNT: u8 = 150;
 
@@ -25,6 +29,10 @@ impl DinoparcLocation {
       name_es,
     }
   }
+
+  pub fn name(&self, locale: LocaleId) -> Option<&'static str> {
+    localized_name(locale, self.name_fr, self.name_en, self.name_es)
+  }
 }
 
 #[rustfmt::skip]
@@ -73,6 +81,10 @@ impl DinoparcSkillMeta {
       name_es,
     }
   }
+
+  pub fn name(&self, locale: LocaleId) -> Option<&'static str> {
+    localized_name(locale, self.name_fr, self.name_en, self.name_es)
+  }
 }
 
 #[rustfmt::skip]
@@ -112,3 +124,226 @@ pub const SKILLS: [DinoparcSkillMeta; 34] = [
   DinoparcSkillMeta::new(DinoparcSkill::Spy, "Espion", "", "Espía"),
   DinoparcSkillMeta::new(DinoparcSkill::Mercenary, "Mercenaire", "", "Mercenario"),
 ];
+
+/// Represents an item in the official Dinoparc game
+///
+/// The names are the ones displayed in the inventory. An empty string means
+/// that the translation is not known yet.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, PartialEq, Eq, Hash)]
+pub struct DinoparcItem {
+  pub id: DinoparcItemId,
+  pub name_fr: &'static str,
+  pub name_en: &'static str,
+  pub name_es: &'static str,
+}
+
+impl DinoparcItem {
+  const unsafe fn new_unchecked(id: u32, name_fr: &'static str, name_en: &'static str, name_es: &'static str) -> Self {
+    Self {
+      id: DinoparcItemId::new_unchecked(id),
+      name_fr,
+      name_en,
+      name_es,
+    }
+  }
+
+  pub fn name(&self, locale: LocaleId) -> Option<&'static str> {
+    localized_name(locale, self.name_fr, self.name_en, self.name_es)
+  }
+}
+
+#[rustfmt::skip]
+pub const ITEMS: [DinoparcItem; 19] = unsafe {
+  [
+    DinoparcItem::new_unchecked(1, "Potion de Madame Irma", "Sparky's Potion", "Poción de la Bruja Lola"),
+    DinoparcItem::new_unchecked(2, "Potion d'Ange", "Angel Potion", ""),
+    DinoparcItem::new_unchecked(3, "Nuage-Burger", "Cloudburger", "Nube-Hamburguesa"),
+    DinoparcItem::new_unchecked(4, "Pain chaud 'Authentique'", "", ""),
+    DinoparcItem::new_unchecked(5, "Liqueur de Pruniac", "Blackberry Drink", ""),
+    DinoparcItem::new_unchecked(6, "Tarte à la viande", "", ""),
+    DinoparcItem::new_unchecked(7, "", "Chocolate Medal", ""),
+    DinoparcItem::new_unchecked(8, "Charme Incandescent", "", ""),
+    DinoparcItem::new_unchecked(10, "Charme Volvik", "", ""),
+    DinoparcItem::new_unchecked(11, "Charme 220V", "", ""),
+    DinoparcItem::new_unchecked(12, "Charme des Vents", "", ""),
+    DinoparcItem::new_unchecked(13, "Griffes Empoisonnées", "", ""),
+    DinoparcItem::new_unchecked(14, "Bave de Loupi", "", ""),
+    DinoparcItem::new_unchecked(18, "Focus de la Nature", "", ""),
+    DinoparcItem::new_unchecked(19, "Focus d'aggressivité", "", ""),
+    DinoparcItem::new_unchecked(20, "Focus des Sirains", "", ""),
+    DinoparcItem::new_unchecked(52, "Dent des Ancetres", "", ""),
+    DinoparcItem::new_unchecked(53, "Crin de Licorne", "", ""),
+    DinoparcItem::new_unchecked(54, "Bois Magique", "", ""),
+  ]
+};
+
+pub static ITEMS_BY_ID: Lazy<HashMap<DinoparcItemId, &'static DinoparcItem>> = Lazy::new(|| {
+  let mut map: HashMap<DinoparcItemId, &'static DinoparcItem> = HashMap::new();
+  for item in ITEMS.iter() {
+    map.insert(item.id, item);
+  }
+  map
+});
+
+/// Represents a regular reward from the collection
+///
+/// The id is the 1-based position of the reward in the collection page.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, PartialEq, Eq, Hash)]
+pub struct DinoparcReward {
+  pub id: DinoparcRewardId,
+  pub name_fr: &'static str,
+  pub name_en: &'static str,
+  pub name_es: &'static str,
+}
+
+impl DinoparcReward {
+  const unsafe fn new_unchecked(id: u8, name_fr: &'static str, name_en: &'static str, name_es: &'static str) -> Self {
+    Self {
+      id: DinoparcRewardId::new_unchecked(id),
+      name_fr,
+      name_en,
+      name_es,
+    }
+  }
+
+  pub fn name(&self, locale: LocaleId) -> Option<&'static str> {
+    localized_name(locale, self.name_fr, self.name_en, self.name_es)
+  }
+}
+
+#[rustfmt::skip]
+pub const REWARDS: [DinoparcReward; 49] = unsafe {
+  [
+    DinoparcReward::new_unchecked(1, "Médaille d'Or de Dinoplage", "", "Medalla de Oro de Dinoplaya"),
+    DinoparcReward::new_unchecked(2, "Médaille d'Argent de Dinoplage", "", "Medalla de Plata de Dinoplaya"),
+    DinoparcReward::new_unchecked(3, "Médaille de Bronze de Dinoplage", "Dinobeach Bronze Medal", "Medalla de Bronce de Dinoplaya"),
+    DinoparcReward::new_unchecked(4, "Médaille d'Or du Mont Dino", "", "Medalla de Oro del Monte Dino"),
+    DinoparcReward::new_unchecked(5, "Médaille d'Argent du Mont Dino", "", "Medalla de Plata del Monte Dino"),
+    DinoparcReward::new_unchecked(6, "Médaille de Bronze du Mont Dino", "Mount Dino Bronze Medal", "Medalla de Bronce del Monte Dino"),
+    DinoparcReward::new_unchecked(7, "Médaille d'Or du Tournoi Zen", "", "Medalla de Oro del Torneo Zen"),
+    DinoparcReward::new_unchecked(8, "Médaille d'Argent du Tournoi Zen", "", "Medalla de Plata del Torneo Zen"),
+    DinoparcReward::new_unchecked(9, "Médaille de Bronze du Tournoi Zen", "", "Medalla de Bronce del Torneo Zen"),
+    DinoparcReward::new_unchecked(10, "Médaille d'Or des Mayincas", "", "Medalla de Oro de los Mayincas"),
+    DinoparcReward::new_unchecked(11, "Médaille d'Argent des Mayincas", "", "Medalla de Plata de los Mayincas"),
+    DinoparcReward::new_unchecked(12, "Médaille de Bronze des Mayincas", "", "Medalla de Bronce de los Mayincas"),
+    DinoparcReward::new_unchecked(13, "Médaille d'Or de Dinoville", "", "Medalla de Oro de Dinovilla"),
+    DinoparcReward::new_unchecked(14, "Médaille d'Argent de Dinoville", "", "Medalla de Plata de Dinovilla"),
+    DinoparcReward::new_unchecked(15, "Médaille de Bronze de Dinoville", "", "Medalla de Bronce de Dinovilla"),
+    DinoparcReward::new_unchecked(16, "Gruyère fossilisé", "Fossilized Cheese", "Queso fosilizado"),
+    DinoparcReward::new_unchecked(17, "Pendentif Aphrodisiaque", "Aphrodisiac Pendent", "Colgante Afrodisíaco"),
+    DinoparcReward::new_unchecked(18, "Cocoffre en bois", "Wooden Cocoffer", "Cocofre de madera"),
+    DinoparcReward::new_unchecked(19, "Griffe d'escargot", "Snail's Claw", "Pincho de caracol"),
+    DinoparcReward::new_unchecked(20, "Trèfle à 4 feuilles", "4 Leafed Clover", "Trébol de 4 hojas"),
+    DinoparcReward::new_unchecked(21, "1 dineuro d'or", "1 Gold Dineuro", "1 dineuro de oro"),
+    DinoparcReward::new_unchecked(22, "1 dineuro d'argent", "1 Silver Dineuro", "1 dineuro de plata"),
+    DinoparcReward::new_unchecked(23, "Flamme d'Anne-Laure", "The Mordor Flame", "Llama de Mordor"),
+    DinoparcReward::new_unchecked(24, "Cacahuètes", "Peanuts", "Cacahuetes"),
+    DinoparcReward::new_unchecked(25, "Gemme verte", "Green Gem", "Gema verde"),
+    DinoparcReward::new_unchecked(26, "Gant en fonte serti de plomb", "Lead Glove", "Guante de plomo"),
+    DinoparcReward::new_unchecked(27, "Pacifique", "", "Pacífica"),
+    DinoparcReward::new_unchecked(28, "Perle volcanique", "Volcanic Pearl", "Perla volcánica"),
+    DinoparcReward::new_unchecked(29, "Poudre de limace", "Slaver Dust", "Pólvora de Babosa"),
+    DinoparcReward::new_unchecked(30, "La carte au trésor", "The Treasure Map", "El mapa del tesoro"),
+    DinoparcReward::new_unchecked(31, "Pétale sucré", "Sweet Petal", "Pétalo dulce"),
+    DinoparcReward::new_unchecked(32, "Dent de Socrate", "Socrates Tooth", "Diente de Sócrates"),
+    DinoparcReward::new_unchecked(33, "Hamburger", "Hamburger", "Hamburguesa"),
+    DinoparcReward::new_unchecked(34, "L'oeil mystérieux", "The Mysterious Eye", "El Ojo Misterioso"),
+    DinoparcReward::new_unchecked(35, "Le crochet doré", "The Golden Hook", "El Garfio Dorado"),
+    DinoparcReward::new_unchecked(36, "Etoile Laba", "A Real Peruvian Hat", "Bola Laba"),
+    DinoparcReward::new_unchecked(37, "Etoile du Fond", "Depth Ball", "Bola del Fondo"),
+    DinoparcReward::new_unchecked(38, "Etoile des Neiges", "", "Bola de las Nieves"),
+    DinoparcReward::new_unchecked(39, "Etoile de Mer", "Sea Ball", "Bola de Mar"),
+    DinoparcReward::new_unchecked(40, "Etoile d'Araignée", "Spider Ball", "Bola de la Araña"),
+    DinoparcReward::new_unchecked(41, "Fimalin", "Fimacling Spider's Web", "Tela de araña fimalín"),
+    DinoparcReward::new_unchecked(42, "Mini-Edmond", "Mini-Toby", "Mini-Emilio"),
+    DinoparcReward::new_unchecked(43, "Buze moussue", "Manurex with moss", "Esdiérkol con musgo"),
+    DinoparcReward::new_unchecked(44, "Anneau des admins", "The Administrator's Ring", "Anillo de administrador"),
+    DinoparcReward::new_unchecked(45, "Para-tonnerre de poche", "Pocket Lightening Conductor", "Pararrayos de bolsillo"),
+    DinoparcReward::new_unchecked(46, "Valve de cocotte-minute", "Pressure cooker Valve", "Válvula de olla a presión"),
+    DinoparcReward::new_unchecked(47, "Bébé nuage", "Baby Cloud", "Bebé de nube"),
+    DinoparcReward::new_unchecked(48, "Charme vierge", "Virgin Spell", "Encantamiento virgen"),
+    DinoparcReward::new_unchecked(49, "Pioupiou séché", "Dissecated Piopi", "Piopio disecado"),
+  ]
+};
+
+pub static REWARDS_BY_ID: Lazy<HashMap<DinoparcRewardId, &'static DinoparcReward>> = Lazy::new(|| {
+  let mut map: HashMap<DinoparcRewardId, &'static DinoparcReward> = HashMap::new();
+  for reward in REWARDS.iter() {
+    map.insert(reward.id, reward);
+  }
+  map
+});
+
+/// Represents an epic reward from the collection
+///
+/// The key is the name of the reward image (without extension), it matches
+/// `DinoparcEpicRewardKey`.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, PartialEq, Eq, Hash)]
+pub struct DinoparcEpicReward {
+  pub key: &'static str,
+  pub name_fr: &'static str,
+  pub name_en: &'static str,
+  pub name_es: &'static str,
+}
+
+impl DinoparcEpicReward {
+  const fn new(key: &'static str, name_fr: &'static str, name_en: &'static str, name_es: &'static str) -> Self {
+    Self {
+      key,
+      name_fr,
+      name_en,
+      name_es,
+    }
+  }
+
+  pub fn name(&self, locale: LocaleId) -> Option<&'static str> {
+    localized_name(locale, self.name_fr, self.name_en, self.name_es)
+  }
+}
+
+#[rustfmt::skip]
+pub const EPIC_REWARDS: [DinoparcEpicReward; 35] = [
+  DinoparcEpicReward::new("adn", "Genne-et-Tique", "", "Liana de vísceras"),
+  DinoparcEpicReward::new("beer", "Bière Fraternelle", "", ""),
+  DinoparcEpicReward::new("coincoin", "Canard Magique", "", ""),
+  DinoparcEpicReward::new("globe_argent", "Globe d'Argent", "", "Globo de Plata"),
+  DinoparcEpicReward::new("globe_bronze", "Globe de Bronze", "", "Globo de Bronce"),
+  DinoparcEpicReward::new("globe_charbon", "Globe de Charbon", "Coal Globe", "Globo de Carbón"),
+  DinoparcEpicReward::new("globe_or", "Globe d'Or", "", "Globo de Oro"),
+  DinoparcEpicReward::new("kabuki", "Trophée des Kabukis", "Kabuki Prize", "Trofeo de los Kabukis"),
+  DinoparcEpicReward::new("katana10", "Epée Cosmik", "", ""),
+  DinoparcEpicReward::new("katana9", "Lames jumelles du Père Nicieux", "", ""),
+  DinoparcEpicReward::new("letter", "Lettre du Futur", "", ""),
+  DinoparcEpicReward::new("scroll2", "Recette Miracle", "", ""),
+  DinoparcEpicReward::new("star_full", "Etoile de jazz", "", "Bola de jazz"),
+  DinoparcEpicReward::new("war17b", "Rûne Bleue", "", ""),
+  DinoparcEpicReward::new("war18b", "Casque de guerre", "", ""),
+  DinoparcEpicReward::new("war19a", "Anneau Précieux", "", ""),
+  DinoparcEpicReward::new("war19b", "Anneau Terne", "", ""),
+  DinoparcEpicReward::new("war20b", "Molaire de Dorogon", "", ""),
+  DinoparcEpicReward::new("war22b", "Samouraï Fantôme", "", ""),
+  DinoparcEpicReward::new("war23b", "Pierre de Magite", "", ""),
+  DinoparcEpicReward::new("war24a", "Statue de Djinn", "", ""),
+  DinoparcEpicReward::new("war24b", "Statue d'Yggdra", "", ""),
+  DinoparcEpicReward::new("war28b", "Coupe de Bronze", "", ""),
+  DinoparcEpicReward::new("war29b", "Trophée d'argent", "", ""),
+  DinoparcEpicReward::new("war30b", "Obélisque d'argent", "", ""),
+  DinoparcEpicReward::new("war31a", "Dinocolada sunrise", "", ""),
+  DinoparcEpicReward::new("war31b", "Dinococtail", "", ""),
+  DinoparcEpicReward::new("war32a", "Tartedino", "", ""),
+  DinoparcEpicReward::new("war32b", "Pistagâteau", "", ""),
+  DinoparcEpicReward::new("war_stone10", "Monstroplancton", "", ""),
+  DinoparcEpicReward::new("war_stone11", "Soda Vivant", "", ""),
+  DinoparcEpicReward::new("war_stone6", "Pendentif-Bombe", "", ""),
+  DinoparcEpicReward::new("war_stone7", "Pierre Dinosophale", "", ""),
+  DinoparcEpicReward::new("war_stone8", "La Hache Kyri", "", ""),
+  DinoparcEpicReward::new("war_stone9", "Pacte brisé", "", ""),
+];
+
+pub static EPIC_REWARDS_BY_KEY: Lazy<HashMap<&'static str, &'static DinoparcEpicReward>> = Lazy::new(|| {
+  let mut map: HashMap<&'static str, &'static DinoparcEpicReward> = HashMap::new();
+  for reward in EPIC_REWARDS.iter() {
+    map.insert(reward.key, reward);
+  }
+  map
+});
@@ -1,3 +1,5 @@
+use crate::localized_name;
+use etwin_core::core::LocaleId;
 use etwin_core::hammerfest::{
   HammerfestForumThemeId, HammerfestForumThemeIdRef, HammerfestItemId, HammerfestQuestId, HammerfestServer,
 };
@@ -20,49 +22,397 @@ pub const PUBLIC_FORUM_THEMES: [HammerfestForumThemeIdRef; 7] = unsafe {
   ]
 };
 
+/// Represents an item in the official Hammerfest game
+///
+/// Only the names displayed in the inventory ("frigo") of the french server
+/// are known. An empty string means that the name is not known: the game
+/// itself only displays the "-Nom inconnu-" placeholder.
 pub struct HammerfestItem {
   pub id: HammerfestItemId,
   pub is_hidden: bool,
+  pub name_fr: &'static str,
 }
 
 impl HammerfestItem {
-  const unsafe fn new_unchecked(id: u16) -> Self {
+  const unsafe fn new_unchecked(id: u16, name_fr: &'static str) -> Self {
     let is_hidden = matches!(id, 1186 | 1187 | 1188 | 1189);
     Self {
       id: HammerfestItemId::new_unchecked(id),
       is_hidden,
+      name_fr,
     }
   }
-}
 
-macro_rules! unchecked_hammerfest_item_list {
-  ($($item:literal),* $(,)?) => {
-    [ $(HammerfestItem::new_unchecked($item)),* ]
+  /// Returns `None` for the other locales, the english and spanish names are not known.
+  pub fn name(&self, locale: LocaleId) -> Option<&'static str> {
+    match locale {
+      LocaleId::FrFr if !self.name_fr.is_empty() => Some(self.name_fr),
+      _ => None,
+    }
   }
 }
 
 #[rustfmt::skip]
-pub const ITEMS: [HammerfestItem; 357] = unsafe {
-  unchecked_hammerfest_item_list! [
-    1000, 1003, 1008, 1013, 1017, 1027, 1041, 1043, 1047, 1048, 1049, 1050, 1051, 1169, 1185, 1022, 1024, 1025, 1026, 1028,
-    1040, 1069, 1070, 1071, 1073, 1074, 1075, 1077, 1078, 1079, 1080, 1081, 1082, 1002, 1004, 1005, 1006, 1007, 1012, 1014,
-    1015, 1016, 1018, 1019, 1023, 1045, 1046, 1009, 1010, 1011, 1001, 1021, 1042, 1055, 1056, 1057, 1142, 1149, 1166, 1020,
-    1039, 1044, 1060, 1061, 1062, 1076, 1161, 1162, 1163, 1167, 1029, 1030, 1031, 1032, 1033, 1034, 1035, 1036, 1037, 1038,
-    1164, 1052, 1053, 1054, 1091, 1092, 1093, 1094, 1095, 1096, 1097, 1098, 1099, 1063, 1064, 1065, 1066, 1067, 1068, 1083,
-    1084, 1085, 1086, 1087, 1088, 1089, 1090, 1106, 1107, 1108, 1109, 1110, 1111, 1100, 1101, 1102, 1103, 1104, 1105, 1168,
-    1112, 1113, 1114, 1115, 1116, 1117, 1118, 1119, 1120, 1121, 1122, 1123, 1124, 1125, 1126, 1127, 1128, 1129, 1130, 1131,
-    1132, 1133, 1134, 1135, 1136, 1141, 1143, 1137, 1138, 1139, 1140, 1144, 1145, 1146, 1147, 1148, 1150, 1151, 1152, 1153,
-    1154, 1155, 1159, 1160, 1058, 1059, 1072, 1156, 1157, 1158, 1165, 1170, 1171, 1172, 1173, 1174, 1175, 1176, 1177, 1178,
-    1179, 1180, 1181, 1182, 1183, 1184, 1190, 1191, 1194, 1197, 1198, 1199, 1200, 1193, 1196, 1192, 1195, 1201, 1202, 1203,
-    1204, 1205, 1206, 1207, 1208, 1209, 1210, 1211, 1212, 1213, 1214, 1215, 1216, 1217, 1218, 1219, 1220, 1225, 1226, 1227,
-    1237, 1238, 1221, 1222, 1223, 1224, 1228, 1236, 1229, 1230, 1231, 1232, 1233, 1234, 1235, 1186, 1187, 1188, 1189,    0,
-       3,    4,    7,   13,   21,   24,   64,   74,   77,   84,  102,  113,  115,  116,  117,    1,    5,    8,   11,   18,
-      22,   23,   25,   27,   28,   38,   39,   65,   66,   69,   71,   82,  106,  107,    2,    6,    9,   12,   19,   30,
-      31,   36,   70,   72,   73,   75,   76,   80,   81,   86,   87,   88,   89,   90,   93,   95,   96,   99,  101,  112,
-      14,   15,   16,   17,   32,   33,   34,   35,   40,   41,   42,   43,   44,   45,   46,   47,   48,   49,   50,   51,
-      52,   53,   54,   55,   56,   57,   58,   59,   60,   61,   62,   63,  103,  104,  105,   20,   78,   79,   91,   94,
-      83,   97,   98,  100,  108,   29,   37,   67,   85,   92,   68,   26,  109,  110,  111,   10,  114,
-   ]
+pub static ITEMS: [HammerfestItem; 357] = unsafe {
+  [
+    HammerfestItem::new_unchecked(1000, "Cristaux d'Hammerfest"),
+    HammerfestItem::new_unchecked(1003, "Bonbon Berlinmauve"),
+    HammerfestItem::new_unchecked(1008, "Diamant Oune-difaïned"),
+    HammerfestItem::new_unchecked(1013, "Muffin aux cailloux"),
+    HammerfestItem::new_unchecked(1017, "Pierres du Changement"),
+    HammerfestItem::new_unchecked(1027, "Réglisse rouge"),
+    HammerfestItem::new_unchecked(1041, "Sucette acidulée"),
+    HammerfestItem::new_unchecked(1043, "Sorbet au plastique"),
+    HammerfestItem::new_unchecked(1047, "Bleuet"),
+    HammerfestItem::new_unchecked(1048, "Rougeoyant"),
+    HammerfestItem::new_unchecked(1049, "Verdifiant"),
+    HammerfestItem::new_unchecked(1050, "KassDent"),
+    HammerfestItem::new_unchecked(1051, "Pièce d'or secrète"),
+    HammerfestItem::new_unchecked(1169, "Etoile Toulaho"),
+    HammerfestItem::new_unchecked(1185, "Anneau hérissé"),
+    HammerfestItem::new_unchecked(1022, "Liquide bizarre"),
+    HammerfestItem::new_unchecked(1024, "Liquide étrange"),
+    HammerfestItem::new_unchecked(1025, "Oeuf cru"),
+    HammerfestItem::new_unchecked(1026, "Gland gnan-gnan"),
+    HammerfestItem::new_unchecked(1028, "Oeuf Au Plat"),
+    HammerfestItem::new_unchecked(1040, "Sushi thon"),
+    HammerfestItem::new_unchecked(1069, "Cacahuete secrète"),
+    HammerfestItem::new_unchecked(1070, "P'tit fantome"),
+    HammerfestItem::new_unchecked(1071, "Cookie deshydraté"),
+    HammerfestItem::new_unchecked(1073, "Piment farceur"),
+    HammerfestItem::new_unchecked(1074, "Soja Max IceCream"),
+    HammerfestItem::new_unchecked(1075, "Bouquet de steack"),
+    HammerfestItem::new_unchecked(1077, "Graine de tournesol"),
+    HammerfestItem::new_unchecked(1078, ""),
+    HammerfestItem::new_unchecked(1079, "Haricot paresseux"),
+    HammerfestItem::new_unchecked(1080, "Lapin-choco"),
+    HammerfestItem::new_unchecked(1081, "Biloo"),
+    HammerfestItem::new_unchecked(1082, "Graine de pechume en gelée"),
+    HammerfestItem::new_unchecked(1002, "Canne de Bobble"),
+    HammerfestItem::new_unchecked(1004, "Bonbon Chamagros"),
+    HammerfestItem::new_unchecked(1005, "Bonbon rosamelle-praline"),
+    HammerfestItem::new_unchecked(1006, "Sucette aux fruits bleus"),
+    HammerfestItem::new_unchecked(1007, "Sucette chlorophylle"),
+    HammerfestItem::new_unchecked(1012, "Surprise de Cerises"),
+    HammerfestItem::new_unchecked(1014, "Gelée des bois"),
+    HammerfestItem::new_unchecked(1015, "Suprême aux framboises"),
+    HammerfestItem::new_unchecked(1016, "Petit pétillant"),
+    HammerfestItem::new_unchecked(1018, "Glace Fraise"),
+    HammerfestItem::new_unchecked(1019, "Coupe Glacée"),
+    HammerfestItem::new_unchecked(1023, "Gros acidulé"),
+    HammerfestItem::new_unchecked(1045, "Pétale mystérieuse"),
+    HammerfestItem::new_unchecked(1046, "Gump"),
+    HammerfestItem::new_unchecked(1009, "Oeil de tigre"),
+    HammerfestItem::new_unchecked(1010, "Jade de 12kg"),
+    HammerfestItem::new_unchecked(1011, "Reflet-de-lune"),
+    HammerfestItem::new_unchecked(1001, "Pain à la viande"),
+    HammerfestItem::new_unchecked(1021, "Poulet Surgelé"),
+    HammerfestItem::new_unchecked(1042, ""),
+    HammerfestItem::new_unchecked(1055, "Boisson kipik"),
+    HammerfestItem::new_unchecked(1056, "Doigts-de-tuberculoz"),
+    HammerfestItem::new_unchecked(1057, "Pizza de Donatello"),
+    HammerfestItem::new_unchecked(1142, "Café de fin de projet"),
+    HammerfestItem::new_unchecked(1149, "Noodles crus"),
+    HammerfestItem::new_unchecked(1166, ""),
+    HammerfestItem::new_unchecked(1020, "Noodles"),
+    HammerfestItem::new_unchecked(1039, "Monsieur radis"),
+    HammerfestItem::new_unchecked(1044, ""),
+    HammerfestItem::new_unchecked(1060, "Bleuette rouge"),
+    HammerfestItem::new_unchecked(1061, "Perroquet décapité en sauce"),
+    HammerfestItem::new_unchecked(1062, "Morvo-morphe"),
+    HammerfestItem::new_unchecked(1076, ""),
+    HammerfestItem::new_unchecked(1161, "Nem aux anchois"),
+    HammerfestItem::new_unchecked(1162, "Surimi pamplemousse"),
+    HammerfestItem::new_unchecked(1163, ""),
+    HammerfestItem::new_unchecked(1167, ""),
+    HammerfestItem::new_unchecked(1029, "Saucisse piquée"),
+    HammerfestItem::new_unchecked(1030, "Cerise-apéro confite"),
+    HammerfestItem::new_unchecked(1031, "Fromage piqué"),
+    HammerfestItem::new_unchecked(1032, "Olive pas mûre"),
+    HammerfestItem::new_unchecked(1033, "Olive noire"),
+    HammerfestItem::new_unchecked(1034, "Oeil de pomme"),
+    HammerfestItem::new_unchecked(1035, "Blob intrusif"),
+    HammerfestItem::new_unchecked(1036, "Gouda mou"),
+    HammerfestItem::new_unchecked(1037, "Poulpi empalé"),
+    HammerfestItem::new_unchecked(1038, "Olive oubliée"),
+    HammerfestItem::new_unchecked(1164, ""),
+    HammerfestItem::new_unchecked(1052, "Sou d'argent"),
+    HammerfestItem::new_unchecked(1053, "Sou d'or"),
+    HammerfestItem::new_unchecked(1054, "Gros tas de sous"),
+    HammerfestItem::new_unchecked(1091, "Jambon de Bayonne"),
+    HammerfestItem::new_unchecked(1092, "Saucisson entamé"),
+    HammerfestItem::new_unchecked(1093, "Raide red reste"),
+    HammerfestItem::new_unchecked(1094, "Torchon madrangeais au sirop d'érable"),
+    HammerfestItem::new_unchecked(1095, "Saucisson de marcassin sauvage"),
+    HammerfestItem::new_unchecked(1096, "Tranches de Jaret de Kangourou"),
+    HammerfestItem::new_unchecked(1097, "Saucissaille de St-Morgelet"),
+    HammerfestItem::new_unchecked(1098, "Paté d'ongles au truffes"),
+    HammerfestItem::new_unchecked(1099, "Saucisson maudit scellé"),
+    HammerfestItem::new_unchecked(1063, "Fraise Tagada"),
+    HammerfestItem::new_unchecked(1064, "Car-En-Sac"),
+    HammerfestItem::new_unchecked(1065, "Dragibus"),
+    HammerfestItem::new_unchecked(1066, "Krokodile"),
+    HammerfestItem::new_unchecked(1067, "Demi Cocobats"),
+    HammerfestItem::new_unchecked(1068, "Happy Cola"),
+    HammerfestItem::new_unchecked(1083, "Sombrino aux amandes"),
+    HammerfestItem::new_unchecked(1084, "Emi-Praline"),
+    HammerfestItem::new_unchecked(1085, "Frogmaliet aux pepites de chocolat"),
+    HammerfestItem::new_unchecked(1086, "Yumi au café"),
+    HammerfestItem::new_unchecked(1087, "Bouchée mielleuse nappée au gel de Vodka"),
+    HammerfestItem::new_unchecked(1088, "Escargot au chocolat persillé"),
+    HammerfestItem::new_unchecked(1089, "Fossile de cacao marbré au fois gras"),
+    HammerfestItem::new_unchecked(1090, "Cerisot mariné a la bière"),
+    HammerfestItem::new_unchecked(1106, "Camembert"),
+    HammerfestItem::new_unchecked(1107, "Emmental"),
+    HammerfestItem::new_unchecked(1108, "Fromage verni"),
+    HammerfestItem::new_unchecked(1109, "Roquefort"),
+    HammerfestItem::new_unchecked(1110, "Fromage frais vigné"),
+    HammerfestItem::new_unchecked(1111, "Pâte dessert fromagée"),
+    HammerfestItem::new_unchecked(1100, "Manquereau-sauce-au-citron"),
+    HammerfestItem::new_unchecked(1101, "Mini-saucisses en boite"),
+    HammerfestItem::new_unchecked(1102, "Haricots blanc"),
+    HammerfestItem::new_unchecked(1103, "Lychees premier prix"),
+    HammerfestItem::new_unchecked(1104, "Zion's Calamar"),
+    HammerfestItem::new_unchecked(1105, "Aubergines au sirop"),
+    HammerfestItem::new_unchecked(1168, "Sardines"),
+    HammerfestItem::new_unchecked(1112, "Saladou"),
+    HammerfestItem::new_unchecked(1113, "Poire d'eau"),
+    HammerfestItem::new_unchecked(1114, "Cacahuète mauve et juteuse"),
+    HammerfestItem::new_unchecked(1115, "Pommes de pierre"),
+    HammerfestItem::new_unchecked(1116, "Patates douces"),
+    HammerfestItem::new_unchecked(1117, "Matraque bio"),
+    HammerfestItem::new_unchecked(1118, "Tomate pacifique"),
+    HammerfestItem::new_unchecked(1119, "Radix"),
+    HammerfestItem::new_unchecked(1120, "Haricots verts"),
+    HammerfestItem::new_unchecked(1121, "Pomme Sapik"),
+    HammerfestItem::new_unchecked(1122, "Pomme Sapu"),
+    HammerfestItem::new_unchecked(1123, ""),
+    HammerfestItem::new_unchecked(1124, "Sel rieur"),
+    HammerfestItem::new_unchecked(1125, "Poivron vert"),
+    HammerfestItem::new_unchecked(1126, "Poivron jaune"),
+    HammerfestItem::new_unchecked(1127, "Poivron rouge"),
+    HammerfestItem::new_unchecked(1128, "Brocolis digérés"),
+    HammerfestItem::new_unchecked(1129, "Grappe de Radix"),
+    HammerfestItem::new_unchecked(1130, "Lance-poix"),
+    HammerfestItem::new_unchecked(1131, ""),
+    HammerfestItem::new_unchecked(1132, "Poires d'eau en grappe"),
+    HammerfestItem::new_unchecked(1133, "Artifroid"),
+    HammerfestItem::new_unchecked(1134, ""),
+    HammerfestItem::new_unchecked(1135, "Chourou"),
+    HammerfestItem::new_unchecked(1136, "Pom pom pom.."),
+    HammerfestItem::new_unchecked(1141, ""),
+    HammerfestItem::new_unchecked(1143, ""),
+    HammerfestItem::new_unchecked(1137, "Hollandais"),
+    HammerfestItem::new_unchecked(1138, "Fondant XXL au choco-beurre"),
+    HammerfestItem::new_unchecked(1139, "Pétillante"),
+    HammerfestItem::new_unchecked(1140, "Warpoquiche"),
+    HammerfestItem::new_unchecked(1144, "Smiley croquant"),
+    HammerfestItem::new_unchecked(1145, "Barquette de lave"),
+    HammerfestItem::new_unchecked(1146, "Nonoix"),
+    HammerfestItem::new_unchecked(1147, "Amande croquante"),
+    HammerfestItem::new_unchecked(1148, "Noisette"),
+    HammerfestItem::new_unchecked(1150, "Brioche vapeur"),
+    HammerfestItem::new_unchecked(1151, "Tartine chocolat-noisette"),
+    HammerfestItem::new_unchecked(1152, "Tartine hémoglobine"),
+    HammerfestItem::new_unchecked(1153, "Tartine à l'orange collante"),
+    HammerfestItem::new_unchecked(1154, "Tartine au miel"),
+    HammerfestItem::new_unchecked(1155, "Lombric nature"),
+    HammerfestItem::new_unchecked(1159, ""),
+    HammerfestItem::new_unchecked(1160, "Bonbon Hélène-fraiche"),
+    HammerfestItem::new_unchecked(1058, "Canelé du sud-ouest"),
+    HammerfestItem::new_unchecked(1059, "Eclair noisette choco caramel et sucre"),
+    HammerfestItem::new_unchecked(1072, "Arbuche de noël"),
+    HammerfestItem::new_unchecked(1156, "Grenade de chantilly"),
+    HammerfestItem::new_unchecked(1157, "Profies très drôles"),
+    HammerfestItem::new_unchecked(1158, "Chouchocos"),
+    HammerfestItem::new_unchecked(1165, "Tartelette framboise"),
+    HammerfestItem::new_unchecked(1170, "Transformer en sucre"),
+    HammerfestItem::new_unchecked(1171, "Kitchissime"),
+    HammerfestItem::new_unchecked(1172, "Igorocop"),
+    HammerfestItem::new_unchecked(1173, "Tidouli didi"),
+    HammerfestItem::new_unchecked(1174, "Dalek ! Exterminate !"),
+    HammerfestItem::new_unchecked(1175, "Robo-malin"),
+    HammerfestItem::new_unchecked(1176, "Johnny 6"),
+    HammerfestItem::new_unchecked(1177, "Biscuit Transformer"),
+    HammerfestItem::new_unchecked(1178, "Statue: Citron Sorbex"),
+    HammerfestItem::new_unchecked(1179, "Statue: Bombino"),
+    HammerfestItem::new_unchecked(1180, "Statue: Poire Melbombe"),
+    HammerfestItem::new_unchecked(1181, "Statue: Tagada"),
+    HammerfestItem::new_unchecked(1182, "Statue: Sapeur-kiwi"),
+    HammerfestItem::new_unchecked(1183, "Statue: Bondissante"),
+    HammerfestItem::new_unchecked(1184, "Statue: Ananargeddon"),
+    HammerfestItem::new_unchecked(1190, "Passe-partout en bois"),
+    HammerfestItem::new_unchecked(1191, "Clé de Rigor Dangerous"),
+    HammerfestItem::new_unchecked(1194, "Furtok Glaciale"),
+    HammerfestItem::new_unchecked(1197, "Clé des Mondes Ardus"),
+    HammerfestItem::new_unchecked(1198, "Clé piquante"),
+    HammerfestItem::new_unchecked(1199, "Passe-partout de Tuberculoz"),
+    HammerfestItem::new_unchecked(1200, "Clé des cauchemars"),
+    HammerfestItem::new_unchecked(1193, "Clé du Bourru"),
+    HammerfestItem::new_unchecked(1196, "Autorisation du Bois-Joli"),
+    HammerfestItem::new_unchecked(1192, ""),
+    HammerfestItem::new_unchecked(1195, "Vieille clé rouillée"),
+    HammerfestItem::new_unchecked(1201, "Pad Sounie"),
+    HammerfestItem::new_unchecked(1202, ""),
+    HammerfestItem::new_unchecked(1203, "Pad Game-Pyramid"),
+    HammerfestItem::new_unchecked(1204, "Pad Sey-Ga"),
+    HammerfestItem::new_unchecked(1205, "Pad Super Frusion"),
+    HammerfestItem::new_unchecked(1206, "Pad du Système Maitre"),
+    HammerfestItem::new_unchecked(1207, "Pad Frusion Entertainment System"),
+    HammerfestItem::new_unchecked(1208, "Manette S-Téhéf"),
+    HammerfestItem::new_unchecked(1209, "Canette Express"),
+    HammerfestItem::new_unchecked(1210, "Bouteille aux 2064 bulles"),
+    HammerfestItem::new_unchecked(1211, "Mousse volante"),
+    HammerfestItem::new_unchecked(1212, "Vin Merveilleux"),
+    HammerfestItem::new_unchecked(1213, ""),
+    HammerfestItem::new_unchecked(1214, "Tampon MT"),
+    HammerfestItem::new_unchecked(1215, "Facture gratuite"),
+    HammerfestItem::new_unchecked(1216, "Post-It de François"),
+    HammerfestItem::new_unchecked(1217, "Pot à crayon solitaire"),
+    HammerfestItem::new_unchecked(1218, ""),
+    HammerfestItem::new_unchecked(1219, "Miroir bancal"),
+    HammerfestItem::new_unchecked(1220, "Etoile du Diable"),
+    HammerfestItem::new_unchecked(1225, "Miroir des Sables"),
+    HammerfestItem::new_unchecked(1226, "Etoile des Diables Jumeaux"),
+    HammerfestItem::new_unchecked(1227, "Sceau d'amitié"),
+    HammerfestItem::new_unchecked(1237, ""),
+    HammerfestItem::new_unchecked(1238, "Pass-Pyramide"),
+    HammerfestItem::new_unchecked(1221, "Poudre de plage magique"),
+    HammerfestItem::new_unchecked(1222, "Matériel d'architecte"),
+    HammerfestItem::new_unchecked(1223, "Maquette en sable"),
+    HammerfestItem::new_unchecked(1224, "Winkel"),
+    HammerfestItem::new_unchecked(1228, "Insigne de l'ordre des Ninjas"),
+    HammerfestItem::new_unchecked(1236, "Insigne du Mérite"),
+    HammerfestItem::new_unchecked(1229, "Couteau suisse japonais"),
+    HammerfestItem::new_unchecked(1230, "Shuriken de second rang"),
+    HammerfestItem::new_unchecked(1231, "Shuriken d'entraînement"),
+    HammerfestItem::new_unchecked(1232, "Najinata"),
+    HammerfestItem::new_unchecked(1233, "Lance-boulettes de Précision"),
+    HammerfestItem::new_unchecked(1234, "Ocarina chantant"),
+    HammerfestItem::new_unchecked(1235, "Armure de la nuit"),
+    HammerfestItem::new_unchecked(1186, ""),
+    HammerfestItem::new_unchecked(1187, ""),
+    HammerfestItem::new_unchecked(1188, ""),
+    HammerfestItem::new_unchecked(1189, ""),
+    HammerfestItem::new_unchecked(0, "Alphabet Cristallin"),
+    HammerfestItem::new_unchecked(3, "Ballon de banquise"),
+    HammerfestItem::new_unchecked(4, "Lampe Fétvoveu"),
+    HammerfestItem::new_unchecked(7, "Basket IcePump"),
+    HammerfestItem::new_unchecked(13, "Cass-Tet"),
+    HammerfestItem::new_unchecked(21, "Enceinte Bessel-Son"),
+    HammerfestItem::new_unchecked(24, "Hippo-flocon"),
+    HammerfestItem::new_unchecked(64, "Arc-en-miel"),
+    HammerfestItem::new_unchecked(74, "Esprit de l'orange"),
+    HammerfestItem::new_unchecked(77, "Lucidjané à crête bleue"),
+    HammerfestItem::new_unchecked(84, "Talisman scorpide"),
+    HammerfestItem::new_unchecked(102, "La Carotte d'Igor"),
+    HammerfestItem::new_unchecked(113, "Cape de Tuberculoz"),
+    HammerfestItem::new_unchecked(115, ""),
+    HammerfestItem::new_unchecked(116, "Joyau d'Ankhel"),
+    HammerfestItem::new_unchecked(117, "Clé de Gordon"),
+    HammerfestItem::new_unchecked(1, "Bouclidur en or"),
+    HammerfestItem::new_unchecked(5, "Lampe Léveussonfé"),
+    HammerfestItem::new_unchecked(8, "Etoile des neiges"),
+    HammerfestItem::new_unchecked(11, "Parapluie rouge"),
+    HammerfestItem::new_unchecked(18, "Pissenlit tropical"),
+    HammerfestItem::new_unchecked(22, "Vieille chaussure trouée"),
+    HammerfestItem::new_unchecked(23, "Boule cristalline"),
+    HammerfestItem::new_unchecked(25, "Flamme froide"),
+    HammerfestItem::new_unchecked(27, "Porte-grenouilles"),
+    HammerfestItem::new_unchecked(28, "Bibelot en argent"),
+    HammerfestItem::new_unchecked(38, "Totem des dinoz"),
+    HammerfestItem::new_unchecked(39, "Tête de granit lestée de plomb"),
+    HammerfestItem::new_unchecked(65, "Bouée canard"),
+    HammerfestItem::new_unchecked(66, "Branche de Kipik"),
+    HammerfestItem::new_unchecked(69, "Koulraoule des îles"),
+    HammerfestItem::new_unchecked(71, "Chaud devant !"),
+    HammerfestItem::new_unchecked(82, "Jugement avant-dernier"),
+    HammerfestItem::new_unchecked(106, "Livre des champignons"),
+    HammerfestItem::new_unchecked(107, "Livre des étoiles"),
+    HammerfestItem::new_unchecked(2, "Bouclidur argenté"),
+    HammerfestItem::new_unchecked(6, "Paix intérieure"),
+    HammerfestItem::new_unchecked(9, "Mauvais-oeil"),
+    HammerfestItem::new_unchecked(12, "Parapluie bleu"),
+    HammerfestItem::new_unchecked(19, "Tournelune"),
+    HammerfestItem::new_unchecked(30, "Lunettes tournantes bleues"),
+    HammerfestItem::new_unchecked(31, "Lunettes renversantes rouges"),
+    HammerfestItem::new_unchecked(36, "Ig'or"),
+    HammerfestItem::new_unchecked(70, "Trêfle commun"),
+    HammerfestItem::new_unchecked(72, "Chapeau de Mage-Gris"),
+    HammerfestItem::new_unchecked(73, "Feuille de sinopée"),
+    HammerfestItem::new_unchecked(75, "Esprit de la pluie"),
+    HammerfestItem::new_unchecked(76, "Esprit des arbres"),
+    HammerfestItem::new_unchecked(80, "Escargot Poussépa"),
+    HammerfestItem::new_unchecked(81, "Perle nacrée des murlocs"),
+    HammerfestItem::new_unchecked(86, "Surprise de paille"),
+    HammerfestItem::new_unchecked(87, "Larve d'oenopterius"),
+    HammerfestItem::new_unchecked(88, "Pokuté"),
+    HammerfestItem::new_unchecked(89, "Oeuf de Tzongre"),
+    HammerfestItem::new_unchecked(90, "Fulguro pieds-en-mousse"),
+    HammerfestItem::new_unchecked(93, "Boit'a'messages"),
+    HammerfestItem::new_unchecked(95, ""),
+    HammerfestItem::new_unchecked(96, "Perle flamboyante"),
+    HammerfestItem::new_unchecked(99, "Poils de Chourou"),
+    HammerfestItem::new_unchecked(101, "Colis surprise"),
+    HammerfestItem::new_unchecked(112, ""),
+    HammerfestItem::new_unchecked(14, "Délice hallucinogène bleu"),
+    HammerfestItem::new_unchecked(15, "Champignon rigolo rouge"),
+    HammerfestItem::new_unchecked(16, ""),
+    HammerfestItem::new_unchecked(17, ""),
+    HammerfestItem::new_unchecked(32, "As de pique"),
+    HammerfestItem::new_unchecked(33, "As de trêfle"),
+    HammerfestItem::new_unchecked(34, ""),
+    HammerfestItem::new_unchecked(35, ""),
+    HammerfestItem::new_unchecked(40, "Sagittaire"),
+    HammerfestItem::new_unchecked(41, "Capricorne"),
+    HammerfestItem::new_unchecked(42, "Lion"),
+    HammerfestItem::new_unchecked(43, "Taureau"),
+    HammerfestItem::new_unchecked(44, "Balance"),
+    HammerfestItem::new_unchecked(45, "Bélier"),
+    HammerfestItem::new_unchecked(46, "Scorpion"),
+    HammerfestItem::new_unchecked(47, "Cancer"),
+    HammerfestItem::new_unchecked(48, "Verseau"),
+    HammerfestItem::new_unchecked(49, "Gémeaux"),
+    HammerfestItem::new_unchecked(50, "Poisson"),
+    HammerfestItem::new_unchecked(51, "Vierge"),
+    HammerfestItem::new_unchecked(52, "Elixir du Sagittaire"),
+    HammerfestItem::new_unchecked(53, "Elixir du Capricorne"),
+    HammerfestItem::new_unchecked(54, "Elixir du Lion"),
+    HammerfestItem::new_unchecked(55, "Elixir du Taureau"),
+    HammerfestItem::new_unchecked(56, "Elixir de la Balance"),
+    HammerfestItem::new_unchecked(57, "Elixir du Bélier"),
+    HammerfestItem::new_unchecked(58, "Elixir du Scorpion"),
+    HammerfestItem::new_unchecked(59, "Elixir du Cancer"),
+    HammerfestItem::new_unchecked(60, "Elixir du Verseau"),
+    HammerfestItem::new_unchecked(61, "Elixir des Gémeaux"),
+    HammerfestItem::new_unchecked(62, "Elixir du Poisson"),
+    HammerfestItem::new_unchecked(63, "Elixir de la Vierge"),
+    HammerfestItem::new_unchecked(103, "Vie palpitante"),
+    HammerfestItem::new_unchecked(104, "Vie aventureuse"),
+    HammerfestItem::new_unchecked(105, "Vie épique"),
+    HammerfestItem::new_unchecked(20, "Coffre d'Anarchipel"),
+    HammerfestItem::new_unchecked(78, "Filandreux rougeoyant"),
+    HammerfestItem::new_unchecked(79, "Poisson empereur"),
+    HammerfestItem::new_unchecked(91, "Couvre-chef de Luffy"),
+    HammerfestItem::new_unchecked(94, "Anneau Antok"),
+    HammerfestItem::new_unchecked(83, "Jugement dernier"),
+    HammerfestItem::new_unchecked(97, ""),
+    HammerfestItem::new_unchecked(98, ""),
+    HammerfestItem::new_unchecked(100, "Pocket-Guu"),
+    HammerfestItem::new_unchecked(108, "Parapluie Frutiparc"),
+    HammerfestItem::new_unchecked(29, "Bague 'Thermostat 8'"),
+    HammerfestItem::new_unchecked(37, "Collier rafraîchissant"),
+    HammerfestItem::new_unchecked(67, "Anneau de Guillaume Tell"),
+    HammerfestItem::new_unchecked(85, "Baton tonnerre"),
+    HammerfestItem::new_unchecked(92, "Chapeau violin"),
+    HammerfestItem::new_unchecked(68, "Bougie"),
+    HammerfestItem::new_unchecked(26, "Ampoule 30 watts"),
+    HammerfestItem::new_unchecked(109, "Flocon simple"),
+    HammerfestItem::new_unchecked(110, "Flocon bizarre"),
+    HammerfestItem::new_unchecked(111, "Flocon ENORME !"),
+    HammerfestItem::new_unchecked(10, "Téléphone-phone-phone"),
+    HammerfestItem::new_unchecked(114, ""),
+  ]
 };
 
 pub static ITEMS_BY_ID: Lazy<HashMap<HammerfestItemId, &'static HammerfestItem>> = Lazy::new(|| {
@@ -94,6 +444,10 @@ impl HammerfestQuest {
       title_es,
     }
   }
+
+  pub fn title(&self, locale: LocaleId) -> Option<&'static str> {
+    localized_name(locale, self.title_fr, self.title_en, self.title_es)
+  }
 }
 
 #[rustfmt::skip]
@@ -177,3 +531,34 @@ pub const QUESTS: [HammerfestQuest; 76] = unsafe {
     HammerfestQuest::new_unchecked(75, "Tombeau de Tuberculoz", "Tuber's tomb", "Tumba de Tubérculo"),
   ]
 };
+
+#[cfg(test)]
+mod test {
+  use super::{ITEMS, ITEMS_BY_ID};
+  use etwin_core::core::LocaleId;
+
+  #[test]
+  fn items_by_id_contains_every_item() {
+    assert_eq!(ITEMS_BY_ID.len(), ITEMS.len());
+    let crystals = ITEMS_BY_ID[&"1000".parse().unwrap()];
+    assert_eq!(crystals.name_fr, "Cristaux d'Hammerfest");
+    assert!(!crystals.is_hidden);
+    assert!(ITEMS_BY_ID[&"1186".parse().unwrap()].is_hidden);
+    assert!(ITEMS_BY_ID.get(&"9999".parse().unwrap()).is_none());
+  }
+
+  #[test]
+  fn item_name() {
+    let crystals = ITEMS_BY_ID[&"1000".parse().unwrap()];
+    assert_eq!(crystals.name(LocaleId::FrFr), Some("Cristaux d'Hammerfest"));
+    assert_eq!(crystals.name(LocaleId::EnUs), None);
+    assert_eq!(crystals.name(LocaleId::EsSp), None);
+    assert_eq!(crystals.name(LocaleId::DeDe), None);
+  }
+
+  #[test]
+  fn unknown_item_name() {
+    let unknown = ITEMS_BY_ID[&"114".parse().unwrap()];
+    assert_eq!(unknown.name(LocaleId::FrFr), None);
+  }
+}
@@ -1,2 +1,58 @@
+use etwin_core::core::LocaleId;
+
 pub mod dinoparc;
 pub mod hammerfest;
+
+/// Select the translation matching `locale` among the names used by the
+/// french, english and spanish game servers.
+///
+/// Returns `None` if there is no server for this locale or if the translation
+/// is unknown (empty).
+fn localized_name(locale: LocaleId, fr: &'static str, en: &'static str, es: &'static str) -> Option<&'static str> {
+  let name = match locale {
+    LocaleId::FrFr => fr,
+    LocaleId::EnUs => en,
+    LocaleId::EsSp => es,
+    LocaleId::DeDe | LocaleId::Eo => return None,
+  };
+  if name.is_empty() {
+    None
+  } else {
+    Some(name)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::localized_name;
+  use etwin_core::core::LocaleId;
+
+  #[test]
+  fn localized_name_per_server() {
+    let name = |locale| localized_name(locale, "Dinoville", "Dinotown", "Dinovilla");
+    assert_eq!(name(LocaleId::FrFr), Some("Dinoville"));
+    assert_eq!(name(LocaleId::EnUs), Some("Dinotown"));
+    assert_eq!(name(LocaleId::EsSp), Some("Dinovilla"));
+  }
+
+  #[test]
+  fn localized_name_without_server() {
+    assert_eq!(
+      localized_name(LocaleId::DeDe, "Dinoville", "Dinotown", "Dinovilla"),
+      None
+    );
+    assert_eq!(localized_name(LocaleId::Eo, "Dinoville", "Dinotown", "Dinovilla"), None);
+  }
+
+  #[test]
+  fn localized_name_unknown_translation() {
+    assert_eq!(
+      localized_name(LocaleId::EnUs, "Caverne d'Irma", "", "Caverna de Lola"),
+      None
+    );
+    assert_eq!(
+      localized_name(LocaleId::EsSp, "Caverne d'Irma", "", "Caverna de Lola"),
+      Some("Caverna de Lola")
+    );
+  }
+}
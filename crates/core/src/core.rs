@@ -274,6 +274,53 @@ impl Secret {
   }
 }
 
+/// Secret used for encryption, with the id stored next to the encrypted data.
+#[derive(Clone)]
+pub struct SecretKey {
+  pub id: String,
+  pub secret: Secret,
+}
+
+/// Versioned encryption keys.
+///
+/// New data is encrypted with the current key. The previous keys are only kept to decrypt the data written before the
+/// last rotations.
+#[derive(Clone)]
+pub struct SecretKeyring {
+  current: SecretKey,
+  previous: Vec<SecretKey>,
+}
+
+impl SecretKeyring {
+  pub fn new(current: SecretKey) -> Self {
+    Self {
+      current,
+      previous: Vec::new(),
+    }
+  }
+
+  /// Adds a key used for decryption only. Keys with an id already in the keyring are ignored.
+  pub fn with_previous(mut self, key: SecretKey) -> Self {
+    if self.get(&key.id).is_none() {
+      self.previous.push(key);
+    }
+    self
+  }
+
+  pub fn current(&self) -> &SecretKey {
+    &self.current
+  }
+
+  pub fn get(&self, id: &str) -> Option<&SecretKey> {
+    self.keys().find(|key| key.id == id)
+  }
+
+  /// All the keys, starting with the current one.
+  pub fn keys(&self) -> impl Iterator<Item = &SecretKey> {
+    std::iter::once(&self.current).chain(self.previous.iter())
+  }
+}
+
 declare_new_int! {
   /// A percentage value between 0 and 100 (inclusive) supporting only integer
   /// values.
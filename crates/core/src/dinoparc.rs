@@ -1,6 +1,6 @@
 use crate::core::{Instant, IntPercentage};
 use crate::link::VersionedEtwinLink;
-use crate::temporal::LatestTemporal;
+use crate::temporal::{ForeignSnapshot, LatestTemporal};
 use crate::types::EtwinError;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
@@ -34,6 +34,13 @@ pub struct GetDinoparcDinozOptions {
   pub time: Option<Instant>,
 }
 
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GetDinoparcDinozHistoryOptions {
+  pub server: DinoparcServer,
+  pub id: DinoparcDinozId,
+}
+
 // TODO: Use `new_enum` macro
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, IntoEnumIterator)]
@@ -248,10 +255,15 @@ pub struct EtwinDinoparcUser {
   #[cfg_attr(feature = "_serde", serde(serialize_with = "serialize_instant"))]
   pub archived_at: Instant,
   pub username: DinoparcUsername,
+  /// Only provided to the Eternaltwin user linked to this Dinoparc user
   pub coins: Option<LatestTemporal<u32>>,
+  /// Only provided to the Eternaltwin user linked to this Dinoparc user
+  pub bills: Option<LatestTemporal<u32>>,
   pub dinoz: Option<LatestTemporal<Vec<DinoparcDinozIdRef>>>,
+  /// Only provided to the Eternaltwin user linked to this Dinoparc user
   #[cfg_attr(feature = "_serde", serde(serialize_with = "serialize_ordered_opt_temporal_map"))]
   pub inventory: Option<LatestTemporal<HashMap<DinoparcItemId, u32>>>,
+  /// Only provided to the Eternaltwin user linked to this Dinoparc user
   pub collection: Option<LatestTemporal<DinoparcCollection>>,
   pub etwin: VersionedEtwinLink,
 }
@@ -284,6 +296,18 @@ declare_decimal_id! {
   const SQL_NAME = "dinoparc_item_id";
 }
 
+declare_decimal_id! {
+  pub struct DinoparcClanId(u32);
+  pub type ParseError = DinoparcClanIdParseError;
+  const BOUNDS = 0..1_000_000_000;
+}
+
+declare_new_string! {
+  pub struct DinoparcClanName(String);
+  pub type ParseError = DinoparcClanNameParseError;
+  const PATTERN = r"^.{1,50}$";
+}
+
 declare_new_string! {
   pub struct DinoparcDinozName(String);
   pub type ParseError = DinoparcDinozNameParseError;
@@ -438,6 +462,32 @@ pub struct ArchivedDinoparcDinoz {
 
 pub type EtwinDinoparcDinoz = ArchivedDinoparcDinoz;
 
+/// Full timeline of an archived dinoz.
+///
+/// Each property is the list of its snapshots, sorted by start time.
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinoparcDinozHistory"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinoparcDinozHistory {
+  pub server: DinoparcServer,
+  pub id: DinoparcDinozId,
+  #[cfg_attr(feature = "_serde", serde(serialize_with = "serialize_instant"))]
+  pub archived_at: Instant,
+  pub name: Vec<ForeignSnapshot<Option<DinoparcDinozName>>>,
+  pub owner: Vec<ForeignSnapshot<ShortDinoparcUser>>,
+  pub location: Vec<ForeignSnapshot<DinoparcLocationId>>,
+  pub race: Vec<ForeignSnapshot<DinoparcDinozRace>>,
+  pub skin: Vec<ForeignSnapshot<DinoparcDinozSkin>>,
+  pub life: Vec<ForeignSnapshot<IntPercentage>>,
+  pub level: Vec<ForeignSnapshot<u16>>,
+  pub experience: Vec<ForeignSnapshot<IntPercentage>>,
+  pub danger: Vec<ForeignSnapshot<i16>>,
+  pub in_tournament: Vec<ForeignSnapshot<bool>>,
+  pub elements: Vec<ForeignSnapshot<DinoparcDinozElements>>,
+  #[cfg_attr(feature = "_serde", serde(serialize_with = "serialize_ordered_snapshot_maps"))]
+  pub skills: Vec<ForeignSnapshot<HashMap<DinoparcSkill, DinoparcSkillLevel>>>,
+}
+
 declare_new_int! {
   pub struct DinoparcSkillLevel(u8);
   pub type RangeError = DinoparcSkillLevelRangeError;
@@ -706,6 +756,62 @@ pub struct DinoparcDinozResponse<U = ShortDinoparcUser> {
   pub dinoz: DinoparcDinoz,
 }
 
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinoparcClan"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortDinoparcClan {
+  pub server: DinoparcServer,
+  pub id: DinoparcClanId,
+  pub name: DinoparcClanName,
+}
+
+/// Public profile of a Dinoparc user, as visible without a session
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinoparcProfile {
+  pub user: ShortDinoparcUser,
+  /// Position in the player ranking, `None` if the user is not ranked
+  pub rank: Option<u32>,
+  pub clan: Option<ShortDinoparcClan>,
+  pub dinoz: Vec<ShortDinoparcDinozWithLevel>,
+}
+
+/// Single page retrieved when archiving a Dinoparc account
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DinoparcArchiveStep {
+  Inventory,
+  Collection,
+  ExchangeWith { recipient: DinoparcUserId },
+  Dinoz { id: DinoparcDinozId },
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinoparcArchiveFailure {
+  pub step: DinoparcArchiveStep,
+  pub error: String,
+}
+
+/// Summary of a full account archival.
+///
+/// Failed steps do not abort the archival: they are reported in `failures`
+/// and the remaining steps are still attempted.
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinoparcArchiveReport {
+  pub user: ShortDinoparcUser,
+  pub archived: Vec<DinoparcArchiveStep>,
+  pub failures: Vec<DinoparcArchiveFailure>,
+}
+
+impl DinoparcArchiveReport {
+  pub fn is_complete(&self) -> bool {
+    self.failures.is_empty()
+  }
+}
+
 #[async_trait]
 #[auto_impl(&, Arc)]
 pub trait DinoparcClient: Send + Sync {
@@ -737,6 +843,15 @@ pub trait DinoparcClient: Send + Sync {
   async fn get_inventory(&self, session: &DinoparcSession) -> Result<DinoparcInventoryResponse, EtwinError>;
 
   async fn get_collection(&self, session: &DinoparcSession) -> Result<DinoparcCollectionResponse, EtwinError>;
+
+  /// Retrieves the public profile of a user, without a session.
+  ///
+  /// Returns `None` if the user does not exist.
+  async fn get_user_profile(
+    &self,
+    server: DinoparcServer,
+    id: DinoparcUserId,
+  ) -> Result<Option<DinoparcProfile>, EtwinError>;
 }
 
 #[async_trait]
@@ -754,7 +869,26 @@ pub trait DinoparcStore: Send + Sync {
 
   async fn get_dinoz(&self, options: &GetDinoparcDinozOptions) -> Result<Option<ArchivedDinoparcDinoz>, EtwinError>;
 
+  async fn get_dinoz_history(
+    &self,
+    options: &GetDinoparcDinozHistoryOptions,
+  ) -> Result<Option<DinoparcDinozHistory>, EtwinError>;
+
   async fn get_user(&self, options: &GetDinoparcUserOptions) -> Result<Option<ArchivedDinoparcUser>, EtwinError>;
+
+  async fn get_coins(&self, options: &GetDinoparcUserOptions) -> Result<Option<LatestTemporal<u32>>, EtwinError>;
+
+  async fn get_bills(&self, options: &GetDinoparcUserOptions) -> Result<Option<LatestTemporal<u32>>, EtwinError>;
+
+  async fn get_inventory(
+    &self,
+    options: &GetDinoparcUserOptions,
+  ) -> Result<Option<LatestTemporal<HashMap<DinoparcItemId, u32>>>, EtwinError>;
+
+  async fn get_collection(
+    &self,
+    options: &GetDinoparcUserOptions,
+  ) -> Result<Option<LatestTemporal<DinoparcCollection>>, EtwinError>;
 }
 
 #[derive(Debug, Error)]
@@ -778,6 +912,22 @@ pub fn serialize_ordered_opt_temporal_map<K: Ord + Serialize, V: Serialize, S: S
     .serialize(serializer)
 }
 
+// TODO: Move to serde_tools
+#[cfg(feature = "_serde")]
+pub fn serialize_ordered_snapshot_maps<K: Ord + Serialize, V: Serialize, S: Serializer>(
+  value: &[ForeignSnapshot<HashMap<K, V>>],
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  value
+    .iter()
+    .map(|s| {
+      s.as_ref()
+        .map(|m| m.iter().collect::<std::collections::BTreeMap<_, _>>())
+    })
+    .collect::<Vec<_>>()
+    .serialize(serializer)
+}
+
 #[cfg(test)]
 mod test {
   use crate::core::{IntPercentage, PeriodLower};
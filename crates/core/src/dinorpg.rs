@@ -1,10 +1,27 @@
-use crate::twinoid::TwinoidUserDisplayName;
+use crate::core::Instant;
+use crate::temporal::LatestTemporal;
+use crate::twinoid::{TwinoidLogin, TwinoidPassword, TwinoidUserDisplayName};
 use crate::types::EtwinError;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use enum_iterator::IntoEnumIterator;
 #[cfg(feature = "_serde")]
-use etwin_serde_tools::{Deserialize, Serialize};
+use etwin_serde_tools::{serialize_instant, Deserialize, Serialize};
+#[cfg(feature = "sqlx")]
+use sqlx::{database, postgres, Database, Postgres};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::str::FromStr;
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GetDinorpgUserOptions {
+  pub server: DinorpgServer,
+  pub id: DinorpgUserId,
+  pub time: Option<Instant>,
+}
 
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, IntoEnumIterator)]
@@ -17,6 +34,78 @@ pub enum DinorpgServer {
   EsDinorpgCom,
 }
 
+impl DinorpgServer {
+  pub const fn as_str(&self) -> &'static str {
+    match self {
+      Self::DinorpgCom => "www.dinorpg.com",
+      Self::EnDinorpgCom => "en.dinorpg.com",
+      Self::EsDinorpgCom => "es.dinorpg.com",
+    }
+  }
+
+  pub fn iter() -> impl ExactSizeIterator<Item = Self> + FusedIterator + Copy {
+    Self::into_enum_iter()
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DinorpgServerParseError;
+
+impl fmt::Display for DinorpgServerParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "DinorpgServerParseError")
+  }
+}
+
+impl Error for DinorpgServerParseError {}
+
+impl FromStr for DinorpgServer {
+  type Err = DinorpgServerParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "www.dinorpg.com" => Ok(Self::DinorpgCom),
+      "en.dinorpg.com" => Ok(Self::EnDinorpgCom),
+      "es.dinorpg.com" => Ok(Self::EsDinorpgCom),
+      _ => Err(DinorpgServerParseError),
+    }
+  }
+}
+
+#[cfg(feature = "sqlx")]
+impl sqlx::Type<Postgres> for DinorpgServer {
+  fn type_info() -> postgres::PgTypeInfo {
+    postgres::PgTypeInfo::with_name("dinorpg_server")
+  }
+
+  fn compatible(ty: &postgres::PgTypeInfo) -> bool {
+    *ty == Self::type_info() || <&str as sqlx::Type<Postgres>>::compatible(ty)
+  }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, Db: Database> sqlx::Decode<'r, Db> for DinorpgServer
+where
+  &'r str: sqlx::Decode<'r, Db>,
+{
+  fn decode(
+    value: <Db as database::HasValueRef<'r>>::ValueRef,
+  ) -> Result<DinorpgServer, Box<dyn Error + 'static + Send + Sync>> {
+    let value: &str = <&str as sqlx::Decode<Db>>::decode(value)?;
+    Ok(value.parse()?)
+  }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'q, Db: Database> sqlx::Encode<'q, Db> for DinorpgServer
+where
+  &'q str: sqlx::Encode<'q, Db>,
+{
+  fn encode_by_ref(&self, buf: &mut <Db as database::HasArguments<'q>>::ArgumentBuffer) -> sqlx::encode::IsNull {
+    self.as_str().encode(buf)
+  }
+}
+
 declare_decimal_id! {
   pub struct DinorpgUserId(u32);
   pub type ParseError = DinorpgUserIdParseError;
@@ -24,6 +113,12 @@ declare_decimal_id! {
   const SQL_NAME = "dinorpg_user_id";
 }
 
+impl DinorpgUserId {
+  pub const fn and_server(&self, server: DinorpgServer) -> DinorpgUserIdRef {
+    DinorpgUserIdRef { server, id: *self }
+  }
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgUser"))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -32,6 +127,53 @@ pub struct DinorpgUserIdRef {
   pub id: DinorpgUserId,
 }
 
+/// Credentials used to create a DinoRPG session.
+///
+/// DinoRPG accounts are Twinoid accounts: the Twinoid login and password
+/// are submitted through the game login form. An existing `sid` cookie
+/// can be used instead.
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DinorpgCredentials {
+  Twinoid {
+    server: DinorpgServer,
+    login: TwinoidLogin,
+    password: TwinoidPassword,
+  },
+  SessionKey {
+    server: DinorpgServer,
+    key: DinorpgSessionKey,
+  },
+}
+
+impl DinorpgCredentials {
+  pub const fn server(&self) -> DinorpgServer {
+    match self {
+      Self::Twinoid { server, .. } => *server,
+      Self::SessionKey { server, .. } => *server,
+    }
+  }
+}
+
+declare_new_string! {
+  /// A DinoRPG session key.
+  ///
+  /// It correspond to the value of the `sid` cookie.
+  pub struct DinorpgSessionKey(String);
+  pub type ParseError = DinorpgSessionKeyParseError;
+  const PATTERN = r"^[0-9a-zA-Z]{32}$";
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DinorpgSession {
+  pub ctime: Instant,
+  pub atime: Instant,
+  pub key: DinorpgSessionKey,
+  pub user: DinorpgUserIdRef,
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgUser"))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -50,24 +192,344 @@ impl ShortDinorpgUser {
   }
 }
 
+impl From<ArchivedDinorpgUser> for ShortDinorpgUser {
+  fn from(value: ArchivedDinorpgUser) -> Self {
+    Self {
+      server: value.server,
+      id: value.id,
+      display_name: value.display_name,
+    }
+  }
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgUser"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArchivedDinorpgUser {
+  pub server: DinorpgServer,
+  pub id: DinorpgUserId,
+  #[cfg_attr(feature = "_serde", serde(serialize_with = "serialize_instant"))]
+  pub archived_at: Instant,
+  pub display_name: TwinoidUserDisplayName,
+}
+
+declare_decimal_id! {
+  pub struct DinorpgDinozId(u32);
+  pub type ParseError = DinorpgDinozIdParseError;
+  const BOUNDS = 0..1_000_000_000;
+  const SQL_NAME = "dinorpg_dinoz_id";
+}
+
+impl DinorpgDinozId {
+  pub const fn and_server(&self, server: DinorpgServer) -> DinorpgDinozIdRef {
+    DinorpgDinozIdRef { server, id: *self }
+  }
+}
+
+declare_new_string! {
+  pub struct DinorpgDinozName(String);
+  pub type ParseError = DinorpgDinozNameParseError;
+  const PATTERN = r"^.{1,50}$";
+  const SQL_NAME = "dinorpg_dinoz_name";
+}
+
+declare_new_string! {
+  /// Raw skin code, as passed to the Flash dinoz renderer.
+  ///
+  /// - `09irKpZLMLRoI000`
+  pub struct DinorpgDinozSkin(String);
+  pub type ParseError = DinorpgDinozSkinParseError;
+  const PATTERN = r"^[0-9A-Za-z]{1,30}$";
+  const SQL_NAME = "dinorpg_dinoz_skin";
+}
+
+impl DinorpgDinozSkin {
+  /// Race encoded by the first character of the skin code
+  pub fn race(&self) -> Option<DinorpgDinozRace> {
+    self.as_str().chars().next().and_then(DinorpgDinozRace::from_skin_code)
+  }
+}
+
+declare_new_enum!(
+  #[derive(IntoEnumIterator)]
+  pub enum DinorpgDinozRace {
+    #[str("Moueffe")]
+    Moueffe,
+    #[str("Pigmou")]
+    Pigmou,
+    #[str("Winks")]
+    Winks,
+    #[str("Planaille")]
+    Planaille,
+    #[str("Castivore")]
+    Castivore,
+    #[str("Rocky")]
+    Rocky,
+    #[str("Pteroz")]
+    Pteroz,
+    #[str("Nuagoz")]
+    Nuagoz,
+    #[str("Sirain")]
+    Sirain,
+    #[str("Hippoclamp")]
+    Hippoclamp,
+    #[str("Gorilloz")]
+    Gorilloz,
+    #[str("Wanwan")]
+    Wanwan,
+    #[str("Santaz")]
+    Santaz,
+    #[str("Feross")]
+    Feross,
+    #[str("Kabuki")]
+    Kabuki,
+    #[str("Mahamuti")]
+    Mahamuti,
+    #[str("Toufufu")]
+    Toufufu,
+    #[str("Quetzu")]
+    Quetzu,
+    #[str("Smog")]
+    Smog,
+    #[str("Triceragon")]
+    Triceragon,
+  }
+  pub type ParseError = DinorpgDinozRaceParseError;
+  const SQL_NAME = "dinorpg_dinoz_race";
+);
+
+impl DinorpgDinozRace {
+  /// Resolve the race from the first character of a skin code
+  pub fn from_skin_code(code: char) -> Option<Self> {
+    let index = code.to_digit(36)?;
+    Self::into_enum_iter().nth(usize::try_from(index).ok()?)
+  }
+}
+
+declare_decimal_id! {
+  pub struct DinorpgClanId(u32);
+  pub type ParseError = DinorpgClanIdParseError;
+  const BOUNDS = 0..1_000_000_000;
+  const SQL_NAME = "dinorpg_clan_id";
+}
+
+declare_new_string! {
+  pub struct DinorpgClanName(String);
+  pub type ParseError = DinorpgClanNameParseError;
+  const PATTERN = r"^.{1,50}$";
+  const SQL_NAME = "dinorpg_clan_name";
+}
+
+declare_new_string! {
+  /// Key of a collection reward, as used in its icon name.
+  ///
+  /// - `perle`
+  /// - `chgx4a`
+  pub struct DinorpgRewardKey(String);
+  pub type ParseError = DinorpgRewardKeyParseError;
+  const PATTERN = r"^[a-z0-9_]{1,30}$";
+  const SQL_NAME = "dinorpg_reward_key";
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgDinoz"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DinorpgDinozIdRef {
+  pub server: DinorpgServer,
+  pub id: DinorpgDinozId,
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgDinoz"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortDinorpgDinoz {
+  pub server: DinorpgServer,
+  pub id: DinorpgDinozId,
+  pub name: Option<DinorpgDinozName>,
+}
+
+impl ShortDinorpgDinoz {
+  pub const fn as_ref(&self) -> DinorpgDinozIdRef {
+    DinorpgDinozIdRef {
+      server: self.server,
+      id: self.id,
+    }
+  }
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgDinoz"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortDinorpgDinozWithLevel {
+  pub server: DinorpgServer,
+  pub id: DinorpgDinozId,
+  pub name: Option<DinorpgDinozName>,
+  pub race: DinorpgDinozRace,
+  /// Raw skin code
+  pub skin: DinorpgDinozSkin,
+  pub level: u16,
+}
+
+impl ShortDinorpgDinozWithLevel {
+  pub const fn as_ref(&self) -> DinorpgDinozIdRef {
+    DinorpgDinozIdRef {
+      server: self.server,
+      id: self.id,
+    }
+  }
+}
+
+declare_new_string! {
+  /// Display name of a location, as shown on the dinoz page (localized).
+  pub struct DinorpgLocationName(String);
+  pub type ParseError = DinorpgLocationNameParseError;
+  const PATTERN = r"^.{1,100}$";
+}
+
+declare_new_string! {
+  /// Key of a dinoz skill, as used in its icon name.
+  pub struct DinorpgSkillKey(String);
+  pub type ParseError = DinorpgSkillKeyParseError;
+  const PATTERN = r"^[a-z0-9_]{1,30}$";
+}
+
+declare_new_string! {
+  /// Key of a dinoz status effect, as used in its icon name.
+  ///
+  /// - `rasca`
+  /// - `bouee`
+  pub struct DinorpgStatusKey(String);
+  pub type ParseError = DinorpgStatusKeyParseError;
+  const PATTERN = r"^[a-z0-9_]{1,30}$";
+}
+
+declare_new_string! {
+  /// Key of an item, as used in its icon name.
+  pub struct DinorpgItemKey(String);
+  pub type ParseError = DinorpgItemKeyParseError;
+  const PATTERN = r"^[a-z0-9_]{1,30}$";
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgDinoz"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinorpgDinoz {
+  pub server: DinorpgServer,
+  pub id: DinorpgDinozId,
+  pub name: Option<DinorpgDinozName>,
+  pub race: DinorpgDinozRace,
+  /// Raw skin code
+  pub skin: DinorpgDinozSkin,
+  pub level: u16,
+  pub location: DinorpgLocationName,
+  /// Learnt skills, in display order
+  pub skills: Vec<DinorpgSkillKey>,
+  /// Active status effects, in display order
+  pub status: Vec<DinorpgStatusKey>,
+  /// Equipped items, in display order
+  pub equipment: Vec<DinorpgItemKey>,
+}
+
+impl DinorpgDinoz {
+  pub const fn as_ref(&self) -> DinorpgDinozIdRef {
+    DinorpgDinozIdRef {
+      server: self.server,
+      id: self.id,
+    }
+  }
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinorpgDinozResponse {
+  pub session_user: DinorpgSessionUser,
+  pub dinoz: DinorpgDinoz,
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "DinorpgClan"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortDinorpgClan {
+  pub server: DinorpgServer,
+  pub id: DinorpgClanId,
+  pub name: DinorpgClanName,
+}
+
+/// Session bar shown on every page when logged in
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DinorpgSessionUser {
+  pub user: DinorpgUserIdRef,
+  pub dinoz: Vec<ShortDinorpgDinoz>,
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DinorpgProfileResponse {
-  // pub session_user: Option<todo!()>,
+  pub session_user: Option<DinorpgSessionUser>,
   pub profile: DinorpgProfile,
 }
 
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DinorpgRanking {
+  pub rank: u32,
+  pub points: u32,
+}
+
+/// Public profile of a DinoRPG user.
+///
+/// The display name comes from Twinoid and is not part of the page, so
+/// only the user reference is available. The inventory is private and
+/// not visible on the profile either.
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DinorpgProfile {
-  pub user: ShortDinorpgUser,
-  // pub dinoz_count: ...,
-  // pub clan: ...,
-  // pub items: ...,
+  pub user: DinorpgUserIdRef,
+  pub dinoz_count: u32,
+  /// Position in the player ranking, `None` if the user is not ranked
+  pub ranking: Option<DinorpgRanking>,
+  pub clan: Option<ShortDinorpgClan>,
+  /// Collection rewards, in display order
+  pub rewards: Vec<DinorpgRewardKey>,
+  pub dinoz: Vec<ShortDinorpgDinozWithLevel>,
 }
 
 #[async_trait]
 #[auto_impl(&, Arc)]
 pub trait DinorpgClient: Send + Sync {
+  async fn create_session(&self, credentials: &DinorpgCredentials) -> Result<DinorpgSession, EtwinError>;
+
+  /// Checks if the session key is still valid.
+  ///
+  /// Returns `None` if the key is expired or unknown.
+  async fn test_session(
+    &self,
+    server: DinorpgServer,
+    key: &DinorpgSessionKey,
+  ) -> Result<Option<DinorpgSession>, EtwinError>;
+
   async fn get_profile(&self, id: DinorpgUserIdRef) -> Result<DinorpgProfileResponse, EtwinError>;
+
+  async fn get_dinoz(&self, session: &DinorpgSession, id: DinorpgDinozId) -> Result<DinorpgDinozResponse, EtwinError>;
+}
+
+#[async_trait]
+#[auto_impl(&, Arc)]
+pub trait DinorpgStore: Send + Sync {
+  async fn touch_short_user(&self, short: &ShortDinorpgUser) -> Result<ArchivedDinorpgUser, EtwinError>;
+
+  /// Archives a profile page.
+  ///
+  /// The page does not include the display name of the user: the user must already be archived.
+  async fn touch_profile(&self, response: &DinorpgProfileResponse) -> Result<(), EtwinError>;
+
+  async fn get_short_user(&self, options: &GetDinorpgUserOptions) -> Result<Option<ShortDinorpgUser>, EtwinError>;
+
+  async fn get_user(&self, options: &GetDinorpgUserOptions) -> Result<Option<ArchivedDinorpgUser>, EtwinError>;
+
+  async fn get_profile(
+    &self,
+    options: &GetDinorpgUserOptions,
+  ) -> Result<Option<LatestTemporal<DinorpgProfile>>, EtwinError>;
 }
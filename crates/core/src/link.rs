@@ -1,6 +1,7 @@
 use crate::core::{Instant, RawUserDot, UserDot};
 use crate::dinoparc::DinoparcUserIdRef;
 use crate::hammerfest::HammerfestUserIdRef;
+use crate::popotamo::PopotamoUserIdRef;
 use crate::twinoid::TwinoidUserIdRef;
 use crate::types::EtwinError;
 use crate::user::{ShortUser, UserIdRef};
@@ -19,6 +20,7 @@ pub trait RemoteUserIdRef: Clone + PartialEq + Eq + fmt::Debug {}
 
 impl RemoteUserIdRef for DinoparcUserIdRef {}
 impl RemoteUserIdRef for HammerfestUserIdRef {}
+impl RemoteUserIdRef for PopotamoUserIdRef {}
 impl RemoteUserIdRef for TwinoidUserIdRef {}
 
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
@@ -64,9 +66,11 @@ impl<T: RemoteUserIdRef> Default for VersionedRawLink<T> {
 pub struct VersionedRawLinks {
   pub dinoparc_com: VersionedRawLink<DinoparcUserIdRef>,
   pub en_dinoparc_com: VersionedRawLink<DinoparcUserIdRef>,
+  pub en_popotamo_com: VersionedRawLink<PopotamoUserIdRef>,
   pub hammerfest_es: VersionedRawLink<HammerfestUserIdRef>,
   pub hammerfest_fr: VersionedRawLink<HammerfestUserIdRef>,
   pub hfest_net: VersionedRawLink<HammerfestUserIdRef>,
+  pub popotamo_com: VersionedRawLink<PopotamoUserIdRef>,
   pub sp_dinoparc_com: VersionedRawLink<DinoparcUserIdRef>,
   pub twinoid: VersionedRawLink<TwinoidUserIdRef>,
 }
@@ -82,6 +86,10 @@ impl Default for VersionedRawLinks {
         current: None,
         old: vec![],
       },
+      en_popotamo_com: VersionedRawLink {
+        current: None,
+        old: vec![],
+      },
       hammerfest_es: VersionedRawLink {
         current: None,
         old: vec![],
@@ -94,6 +102,10 @@ impl Default for VersionedRawLinks {
         current: None,
         old: vec![],
       },
+      popotamo_com: VersionedRawLink {
+        current: None,
+        old: vec![],
+      },
       sp_dinoparc_com: VersionedRawLink {
         current: None,
         old: vec![],
@@ -164,6 +176,13 @@ pub struct GetLinksFromEtwinOptions {
   pub time: Option<Instant>,
 }
 
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GetLinksFromTwinoidOptions {
+  pub remotes: Vec<TwinoidUserIdRef>,
+  pub time: Option<Instant>,
+}
+
 #[derive(Error, Debug)]
 pub enum TouchLinkError<T: RemoteUserIdRef> {
   #[error("cannot link as the remote user is already linked to the etwin user {0:?}")]
@@ -209,6 +228,11 @@ pub trait LinkStore: Send + Sync {
     options: &TouchLinkOptions<HammerfestUserIdRef>,
   ) -> Result<VersionedRawLink<HammerfestUserIdRef>, TouchLinkError<HammerfestUserIdRef>>;
 
+  async fn touch_popotamo_link(
+    &self,
+    options: &TouchLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, TouchLinkError<PopotamoUserIdRef>>;
+
   async fn touch_twinoid_link(
     &self,
     options: &TouchLinkOptions<TwinoidUserIdRef>,
@@ -224,6 +248,11 @@ pub trait LinkStore: Send + Sync {
     options: &DeleteLinkOptions<HammerfestUserIdRef>,
   ) -> Result<VersionedRawLink<HammerfestUserIdRef>, DeleteLinkError<HammerfestUserIdRef>>;
 
+  async fn delete_popotamo_link(
+    &self,
+    options: &DeleteLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, DeleteLinkError<PopotamoUserIdRef>>;
+
   async fn delete_twinoid_link(
     &self,
     options: &DeleteLinkOptions<TwinoidUserIdRef>,
@@ -239,10 +268,23 @@ pub trait LinkStore: Send + Sync {
     options: &GetLinkOptions<HammerfestUserIdRef>,
   ) -> Result<VersionedRawLink<HammerfestUserIdRef>, EtwinError>;
 
+  async fn get_link_from_popotamo(
+    &self,
+    options: &GetLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, EtwinError>;
+
   async fn get_link_from_twinoid(
     &self,
     options: &GetLinkOptions<TwinoidUserIdRef>,
   ) -> Result<VersionedRawLink<TwinoidUserIdRef>, EtwinError>;
 
+  /// Retrieve the current links of many Twinoid users at once, in the order of `options.remotes`.
+  ///
+  /// Users without a current link are skipped.
+  async fn get_links_from_twinoid(
+    &self,
+    options: &GetLinksFromTwinoidOptions,
+  ) -> Result<Vec<RawLink<TwinoidUserIdRef>>, EtwinError>;
+
   async fn get_links_from_etwin(&self, options: &GetLinksFromEtwinOptions) -> Result<VersionedRawLinks, EtwinError>;
 }
@@ -1,17 +1,102 @@
+use crate::core::Instant;
 use crate::types::EtwinError;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use enum_iterator::IntoEnumIterator;
 #[cfg(feature = "_serde")]
-use etwin_serde_tools::{Deserialize, Serialize};
+use etwin_serde_tools::{serialize_instant, Deserialize, Serialize};
+#[cfg(feature = "sqlx")]
+use sqlx::{database, postgres, Database, Postgres};
+use std::error::Error;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::str::FromStr;
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GetPopotamoUserOptions {
+  pub server: PopotamoServer,
+  pub id: PopotamoUserId,
+  pub time: Option<Instant>,
+}
 
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, IntoEnumIterator)]
 pub enum PopotamoServer {
   #[cfg_attr(feature = "_serde", serde(rename = "popotamo.com"))]
   PopotamoCom,
-  // #[cfg_attr(feature = "_serde", serde(rename = "en.popotamo.com"))]
-  // EnPopotamoCom,
+  #[cfg_attr(feature = "_serde", serde(rename = "en.popotamo.com"))]
+  EnPopotamoCom,
+}
+
+impl PopotamoServer {
+  pub const fn as_str(&self) -> &'static str {
+    match self {
+      Self::PopotamoCom => "popotamo.com",
+      Self::EnPopotamoCom => "en.popotamo.com",
+    }
+  }
+
+  pub fn iter() -> impl ExactSizeIterator<Item = Self> + FusedIterator + Copy {
+    Self::into_enum_iter()
+  }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PopotamoServerParseError;
+
+impl fmt::Display for PopotamoServerParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "PopotamoServerParseError")
+  }
+}
+
+impl Error for PopotamoServerParseError {}
+
+impl FromStr for PopotamoServer {
+  type Err = PopotamoServerParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "popotamo.com" => Ok(Self::PopotamoCom),
+      "en.popotamo.com" => Ok(Self::EnPopotamoCom),
+      _ => Err(PopotamoServerParseError),
+    }
+  }
+}
+
+#[cfg(feature = "sqlx")]
+impl sqlx::Type<Postgres> for PopotamoServer {
+  fn type_info() -> postgres::PgTypeInfo {
+    postgres::PgTypeInfo::with_name("popotamo_server")
+  }
+
+  fn compatible(ty: &postgres::PgTypeInfo) -> bool {
+    *ty == Self::type_info() || <&str as sqlx::Type<Postgres>>::compatible(ty)
+  }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, Db: Database> sqlx::Decode<'r, Db> for PopotamoServer
+where
+  &'r str: sqlx::Decode<'r, Db>,
+{
+  fn decode(
+    value: <Db as database::HasValueRef<'r>>::ValueRef,
+  ) -> Result<PopotamoServer, Box<dyn Error + 'static + Send + Sync>> {
+    let value: &str = <&str as sqlx::Decode<Db>>::decode(value)?;
+    Ok(value.parse()?)
+  }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'q, Db: Database> sqlx::Encode<'q, Db> for PopotamoServer
+where
+  &'q str: sqlx::Encode<'q, Db>,
+{
+  fn encode_by_ref(&self, buf: &mut <Db as database::HasArguments<'q>>::ArgumentBuffer) -> sqlx::encode::IsNull {
+    self.as_str().encode(buf)
+  }
 }
 
 declare_decimal_id! {
@@ -21,6 +106,12 @@ declare_decimal_id! {
   const SQL_NAME = "popotamo_user_id";
 }
 
+impl PopotamoUserId {
+  pub const fn and_server(&self, server: PopotamoServer) -> PopotamoUserIdRef {
+    PopotamoUserIdRef { server, id: *self }
+  }
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "_serde", serde(tag = "type", rename = "PopotamoUser"))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -32,8 +123,7 @@ pub struct PopotamoUserIdRef {
 declare_new_string! {
   pub struct PopotamoUsername(String);
   pub type ParseError = PopotamoUsernameParseError;
-  // TODO: Pattern
-  const PATTERN = r"^[0-9A-Za-z_-]{1,8}$";
+  const PATTERN = r"^[0-9A-Za-z_-]{1,20}$";
   const SQL_NAME = "popotamo_username";
 }
 
@@ -54,10 +144,36 @@ impl PopotamoPassword {
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PopotamoCredentials {
+  pub server: PopotamoServer,
   pub username: PopotamoUsername,
   pub password: PopotamoPassword,
 }
 
+declare_new_string! {
+  pub struct PopotamoSessionKey(String);
+  pub type ParseError = PopotamoSessionKeyParseError;
+  const PATTERN = r"^[0-9a-zA-Z]{32}$";
+  const SQL_NAME = "popotamo_session_key";
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PopotamoSession {
+  pub key: PopotamoSessionKey,
+  pub user: ShortPopotamoUser,
+  pub ctime: Instant,
+  pub atime: Instant,
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StoredPopotamoSession {
+  pub key: PopotamoSessionKey,
+  pub user: PopotamoUserIdRef,
+  pub ctime: Instant,
+  pub atime: Instant,
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "_serde", serde(tag = "type", rename = "PopotamoUser"))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -76,12 +192,77 @@ impl ShortPopotamoUser {
   }
 }
 
+impl From<ArchivedPopotamoUser> for ShortPopotamoUser {
+  fn from(value: ArchivedPopotamoUser) -> Self {
+    Self {
+      server: value.server,
+      id: value.id,
+      username: value.username,
+    }
+  }
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "_serde", serde(tag = "type", rename = "PopotamoUser"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArchivedPopotamoUser {
+  pub server: PopotamoServer,
+  pub id: PopotamoUserId,
+  #[cfg_attr(feature = "_serde", serde(serialize_with = "serialize_instant"))]
+  pub archived_at: Instant,
+  pub username: PopotamoUsername,
+}
+
+declare_decimal_id! {
+  /// League of a player, displayed as `/gfx/leagues/<id>.gif`
+  pub struct PopotamoLeagueId(u8);
+  pub type ParseError = PopotamoLeagueIdParseError;
+  const BOUNDS = 0..100;
+}
+
+declare_decimal_id! {
+  /// A player has one sub-profile per game mode, they are identified by this id
+  pub struct PopotamoSubProfileId(u32);
+  pub type ParseError = PopotamoSubProfileIdParseError;
+  const BOUNDS = 0..1_000_000_000;
+}
+
+declare_new_string! {
+  /// Key of a reward, as used in its image name.
+  ///
+  /// - `poutruche`
+  /// - `heartwin`
+  pub struct PopotamoRewardKey(String);
+  pub type ParseError = PopotamoRewardKeyParseError;
+  const PATTERN = r"^[a-z0-9_]{1,30}$";
+}
+
+declare_new_string! {
+  /// Key of a game option (item bought in the Poposhop), as used in its image name.
+  ///
+  /// - `vowel`
+  /// - `telescope`
+  pub struct PopotamoItemKey(String);
+  pub type ParseError = PopotamoItemKeyParseError;
+  const PATTERN = r"^[a-z0-9_]{1,30}$";
+}
+
+/// Rewards displayed in the session box
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PopotamoSessionRewards {
+  pub league: PopotamoLeagueId,
+  pub tokens: u32,
+  pub quids: u32,
+  pub cups: u32,
+}
+
 /// Data in the top right for logged-in users
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PopotamoSessionUser {
   pub user: ShortPopotamoUser,
-  // pub rewards: ...,
+  pub rewards: PopotamoSessionRewards,
 }
 
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
@@ -91,16 +272,63 @@ pub struct PopotamoProfileResponse {
   pub profile: PopotamoProfile,
 }
 
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PopotamoUserSkills {
+  pub speed: u32,
+  pub creativity: u32,
+  pub wisdom: u32,
+}
+
+/// Game statistics for a sub-profile
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PopotamoSubProfile {
+  pub id: PopotamoSubProfileId,
+  pub games_played: u32,
+  /// Number of games finished at each position, starting with the first place
+  pub finishes: Vec<u32>,
+  pub skills: PopotamoUserSkills,
+  /// Options available during games, in display order
+  pub items: Vec<PopotamoItemKey>,
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PopotamoProfile {
   pub user: ShortPopotamoUser,
-  // pub rewards: ...,
-  // pub items: ...,
+  pub score: u32,
+  pub rank: u32,
+  pub league: PopotamoLeagueId,
+  pub cups: u32,
+  pub is_moderator: bool,
+  pub games_played_this_month: u32,
+  pub rewards: Vec<PopotamoRewardKey>,
+  pub sub_profiles: Vec<PopotamoSubProfile>,
 }
 
 #[async_trait]
 #[auto_impl(&, Arc)]
 pub trait PopotamoClient: Send + Sync {
+  async fn create_session(&self, credentials: &PopotamoCredentials) -> Result<PopotamoSession, EtwinError>;
+
+  async fn test_session(
+    &self,
+    server: PopotamoServer,
+    key: &PopotamoSessionKey,
+  ) -> Result<Option<PopotamoSession>, EtwinError>;
+
   async fn get_profile(&self, id: PopotamoUserIdRef) -> Result<PopotamoProfileResponse, EtwinError>;
 }
+
+#[async_trait]
+#[auto_impl(&, Arc)]
+pub trait PopotamoStore: Send + Sync {
+  async fn touch_short_user(&self, short: &ShortPopotamoUser) -> Result<ArchivedPopotamoUser, EtwinError>;
+
+  async fn touch_profile(&self, response: &PopotamoProfileResponse) -> Result<(), EtwinError>;
+
+  async fn get_short_user(&self, options: &GetPopotamoUserOptions) -> Result<Option<ShortPopotamoUser>, EtwinError>;
+
+  async fn get_user(&self, options: &GetPopotamoUserOptions) -> Result<Option<ArchivedPopotamoUser>, EtwinError>;
+}
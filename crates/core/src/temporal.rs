@@ -345,6 +345,74 @@ impl<T: Eq> SnapshotLog<T> {
       value,
     })
   }
+
+  /// Get the snapshot valid at the provided time.
+  ///
+  /// The period covers the full run of identical values containing `time`: it
+  /// ends when a different value was first observed.
+  pub fn at(&self, time: Instant) -> Option<ForeignSnapshot<&T>> {
+    let (&current, value) = self.snapshots.range(..=time).next_back()?;
+    let mut start = current;
+    for (t, v) in self.snapshots.range(..current).rev() {
+      if v != value {
+        break;
+      }
+      start = *t;
+    }
+    let mut latest = current;
+    let mut end: Option<Instant> = None;
+    for (t, v) in self.snapshots.range(current..) {
+      if v != value {
+        end = Some(*t);
+        break;
+      }
+      latest = *t;
+    }
+    Some(ForeignSnapshot {
+      period: PeriodLower::new(start, end),
+      retrieved: ForeignRetrieved { latest },
+      value,
+    })
+  }
+
+  /// Get the full history, as a list of snapshots sorted by start time.
+  pub fn history(&self) -> Vec<ForeignSnapshot<&T>> {
+    let mut history: Vec<ForeignSnapshot<&T>> = Vec::new();
+    for (t, v) in self.snapshots.iter() {
+      match history.last_mut() {
+        Some(last) if last.value == v => last.retrieved.latest = *t,
+        last => {
+          if let Some(last) = last {
+            last.period = PeriodLower::bounded(last.start_time(), *t);
+          }
+          history.push(ForeignSnapshot {
+            period: PeriodLower::unbounded(*t),
+            retrieved: ForeignRetrieved { latest: *t },
+            value: v,
+          });
+        }
+      }
+    }
+    history
+  }
+}
+
+/// Merge consecutive snapshots with equal values.
+///
+/// This is useful when a single store row holds multiple properties: a change
+/// to one property splits the periods of all the others.
+pub fn coalesce_snapshots<T: Eq>(snapshots: impl IntoIterator<Item = ForeignSnapshot<T>>) -> Vec<ForeignSnapshot<T>> {
+  let mut coalesced: Vec<ForeignSnapshot<T>> = Vec::new();
+  for snapshot in snapshots {
+    match coalesced.last_mut() {
+      Some(last) if last.value == snapshot.value && last.end_time() == Some(snapshot.start_time()) => {
+        last.period = PeriodLower::new(last.start_time(), snapshot.end_time());
+        last.retrieved = snapshot.retrieved;
+      }
+      _ => coalesced.push(snapshot),
+    }
+  }
+  coalesced
 }
 
 /// Third-party time-varying data history with indirect invalidation support
@@ -2,10 +2,12 @@ use crate::core::Instant;
 use crate::dinoparc::{DinoparcServer, DinoparcSessionKey, DinoparcUserIdRef, StoredDinoparcSession};
 use crate::hammerfest::{HammerfestServer, HammerfestSessionKey, HammerfestUserIdRef, StoredHammerfestSession};
 use crate::oauth::{RfcOauthAccessTokenKey, RfcOauthRefreshTokenKey, TwinoidAccessToken, TwinoidRefreshToken};
+use crate::popotamo::{PopotamoServer, PopotamoSessionKey, PopotamoUserIdRef, StoredPopotamoSession};
 use crate::twinoid::{TwinoidUserId, TwinoidUserIdRef};
 use crate::types::EtwinError;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
+use chrono::Duration;
 #[cfg(feature = "_serde")]
 use etwin_serde_tools::{Deserialize, Serialize};
 
@@ -29,6 +31,28 @@ pub struct TwinoidOauth {
   pub refresh_token: Option<TwinoidRefreshToken>,
 }
 
+/// Limits used to delete the stale tokens and sessions.
+///
+/// Expired Twinoid access tokens are always deleted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SweepTokensOptions {
+  /// Twinoid refresh tokens unused for longer than this are deleted
+  pub max_refresh_token_age: Duration,
+  /// Game sessions unused for longer than this are deleted
+  pub max_session_age: Duration,
+}
+
+/// Number of tokens and sessions deleted by a sweep, including the revoked ones.
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenSweep {
+  pub twinoid_access_tokens: u64,
+  pub twinoid_refresh_tokens: u64,
+  pub dinoparc_sessions: u64,
+  pub hammerfest_sessions: u64,
+  pub popotamo_sessions: u64,
+}
+
 #[async_trait]
 #[auto_impl(&, Arc)]
 pub trait TokenStore: Send + Sync {
@@ -36,6 +60,13 @@ pub trait TokenStore: Send + Sync {
   async fn revoke_twinoid_access_token(&self, options: &RfcOauthAccessTokenKey) -> Result<(), EtwinError>;
   async fn revoke_twinoid_refresh_token(&self, options: &RfcOauthRefreshTokenKey) -> Result<(), EtwinError>;
   async fn get_twinoid_oauth(&self, options: TwinoidUserIdRef) -> Result<TwinoidOauth, EtwinError>;
+  /// Retrieve the access token with this key, even if it expired.
+  ///
+  /// Returns `None` if the token is unknown or was revoked.
+  async fn get_twinoid_access_token(
+    &self,
+    key: &RfcOauthAccessTokenKey,
+  ) -> Result<Option<TwinoidAccessToken>, EtwinError>;
   async fn touch_dinoparc(
     &self,
     user: DinoparcUserIdRef,
@@ -50,4 +81,13 @@ pub trait TokenStore: Send + Sync {
   ) -> Result<StoredHammerfestSession, EtwinError>;
   async fn revoke_hammerfest(&self, server: HammerfestServer, key: &HammerfestSessionKey) -> Result<(), EtwinError>;
   async fn get_hammerfest(&self, user: HammerfestUserIdRef) -> Result<Option<StoredHammerfestSession>, EtwinError>;
+  async fn touch_popotamo(
+    &self,
+    user: PopotamoUserIdRef,
+    key: &PopotamoSessionKey,
+  ) -> Result<StoredPopotamoSession, EtwinError>;
+  async fn revoke_popotamo(&self, server: PopotamoServer, key: &PopotamoSessionKey) -> Result<(), EtwinError>;
+  async fn get_popotamo(&self, user: PopotamoUserIdRef) -> Result<Option<StoredPopotamoSession>, EtwinError>;
+  /// Deletes the expired Twinoid access tokens, and the refresh tokens and sessions unused for too long.
+  async fn sweep(&self, options: &SweepTokensOptions) -> Result<TokenSweep, EtwinError>;
 }
@@ -1,12 +1,14 @@
 use crate::core::{HtmlFragment, Instant};
 use crate::oauth::RfcOauthAccessTokenKey;
-use crate::twinoid::api::{User, UserQuery};
+use crate::temporal::LatestTemporal;
 use crate::types::EtwinError;
+use crate::user::ShortUser;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 #[cfg(feature = "_serde")]
 use etwin_serde_tools::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::convert::TryFrom;
+use url::Url;
 
 declare_decimal_id! {
   pub struct TwinoidUserId(u32);
@@ -28,6 +30,35 @@ declare_new_string! {
   const SQL_NAME = "twinoid_user_display_name";
 }
 
+/// Login used to authenticate on Twinoid: either an email or a display name.
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TwinoidLogin(String);
+
+impl TwinoidLogin {
+  pub fn new(raw: String) -> Self {
+    Self(raw)
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TwinoidPassword(String);
+
+impl TwinoidPassword {
+  pub fn new(raw: String) -> Self {
+    Self(raw)
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "_serde", serde(tag = "type", rename = "TwinoidUser"))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -60,11 +91,213 @@ impl From<TwinoidUserId> for TwinoidUserIdRef {
 
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "_serde", serde(tag = "type", rename = "TwinoidUser"))]
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ArchivedTwinoidUser {
   pub id: TwinoidUserId,
   pub archived_at: Instant,
   pub display_name: TwinoidUserDisplayName,
+  pub details: Option<LatestTemporal<TwinoidProfileDetails>>,
+  pub stats: Vec<ArchivedTwinoidStat>,
+  pub achievements: Vec<ArchivedTwinoidAchievement>,
+}
+
+declare_decimal_id! {
+  pub struct TwinoidSiteId(u32);
+  pub type ParseError = TwinoidSiteIdParseError;
+  const BOUNDS = 1..1_000_000;
+  const SQL_NAME = "twinoid_site_id";
+}
+
+declare_new_string! {
+  pub struct TwinoidStatKey(String);
+  pub type ParseError = TwinoidStatKeyParseError;
+  const PATTERN = r"^.{1,100}$";
+  const SQL_NAME = "twinoid_stat_key";
+}
+
+declare_new_string! {
+  pub struct TwinoidAchievementKey(String);
+  pub type ParseError = TwinoidAchievementKeyParseError;
+  const PATTERN = r"^.{1,100}$";
+  const SQL_NAME = "twinoid_achievement_key";
+}
+
+/// Public information displayed on the Twinoid profile page
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidProfileDetails {
+  pub title: Option<HtmlFragment>,
+  pub picture: Option<String>,
+  pub locale: Option<String>,
+  pub gender: Option<String>,
+  pub city: Option<String>,
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidStat {
+  pub key: TwinoidStatKey,
+  pub score: i64,
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidAchievement {
+  pub key: TwinoidAchievementKey,
+  /// `None` when the Graph API did not return the points of the achievement
+  pub points: Option<u32>,
+}
+
+/// Stats and achievements of a user on a single Twinoid site
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidSiteProfile {
+  pub site: TwinoidSiteId,
+  pub stats: Vec<TwinoidStat>,
+  pub achievements: Vec<TwinoidAchievement>,
+}
+
+/// Full Twinoid profile, retrieved with `api::UserQuery::profile()`
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidProfile {
+  pub user: ShortTwinoidUser,
+  pub details: TwinoidProfileDetails,
+  pub sites: Vec<TwinoidSiteProfile>,
+}
+
+impl TryFrom<api::User> for TwinoidProfile {
+  type Error = TwinoidApiError;
+
+  fn try_from(user: api::User) -> Result<Self, Self::Error> {
+    fn malformed(e: impl std::error::Error) -> TwinoidApiError {
+      TwinoidApiError::MalformedBody(e.to_string())
+    }
+
+    let sites = user
+      .sites
+      .unwrap_or_default()
+      .into_iter()
+      .map(|site| {
+        Ok(TwinoidSiteProfile {
+          site: TwinoidSiteId::new(site.site.id).map_err(malformed)?,
+          stats: site
+            .stats
+            .unwrap_or_default()
+            .into_iter()
+            .map(|stat| {
+              Ok(TwinoidStat {
+                key: stat.id.parse().map_err(malformed)?,
+                score: stat.score,
+              })
+            })
+            .collect::<Result<_, TwinoidApiError>>()?,
+          achievements: site
+            .achievements
+            .unwrap_or_default()
+            .into_iter()
+            .map(|achievement| {
+              Ok(TwinoidAchievement {
+                key: achievement.id.parse().map_err(malformed)?,
+                points: achievement.points,
+              })
+            })
+            .collect::<Result<_, TwinoidApiError>>()?,
+        })
+      })
+      .collect::<Result<_, TwinoidApiError>>()?;
+
+    Ok(Self {
+      user: ShortTwinoidUser {
+        id: TwinoidUserId::new(user.id).map_err(malformed)?,
+        display_name: user
+          .name
+          .ok_or_else(|| TwinoidApiError::MalformedBody(String::from("missing user name")))?,
+      },
+      details: TwinoidProfileDetails {
+        title: user.title,
+        picture: user.picture.map(|p| p.url),
+        locale: user.locale,
+        gender: user.gender,
+        city: user.city,
+      },
+      sites,
+    })
+  }
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArchivedTwinoidStat {
+  pub site: TwinoidSiteId,
+  pub key: TwinoidStatKey,
+  pub score: LatestTemporal<i64>,
+}
+
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArchivedTwinoidAchievement {
+  pub site: TwinoidSiteId,
+  pub key: TwinoidAchievementKey,
+  pub points: LatestTemporal<Option<u32>>,
+}
+
+/// Contact of a Twinoid user, as returned by the Graph API
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidContact {
+  pub user: ShortTwinoidUser,
+  pub friend: bool,
+}
+
+/// Contacts of a Twinoid user, retrieved with `api::UserQuery::contact_list()`
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidContactList {
+  pub user: ShortTwinoidUser,
+  pub contacts: Vec<TwinoidContact>,
+}
+
+impl TryFrom<api::User> for TwinoidContactList {
+  type Error = TwinoidApiError;
+
+  fn try_from(mut user: api::User) -> Result<Self, Self::Error> {
+    fn short(user: api::User) -> Result<ShortTwinoidUser, TwinoidApiError> {
+      Ok(ShortTwinoidUser {
+        id: TwinoidUserId::new(user.id).map_err(|e| TwinoidApiError::MalformedBody(e.to_string()))?,
+        display_name: user
+          .name
+          .ok_or_else(|| TwinoidApiError::MalformedBody(String::from("missing user name")))?,
+      })
+    }
+
+    let contacts = user
+      .contacts
+      .take()
+      .unwrap_or_default()
+      .into_iter()
+      .map(|contact| {
+        Ok(TwinoidContact {
+          user: short(contact.user)?,
+          friend: contact.friend.unwrap_or(false),
+        })
+      })
+      .collect::<Result<_, TwinoidApiError>>()?;
+
+    Ok(Self {
+      user: short(user)?,
+      contacts,
+    })
+  }
+}
+
+/// Twinoid contact who also has an Eternaltwin account
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EtwinTwinoidContact {
+  pub twinoid: ShortTwinoidUser,
+  pub friend: bool,
+  pub etwin: ShortUser,
 }
 
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
@@ -82,6 +315,18 @@ pub trait TwinoidStore: Send + Sync {
   async fn get_user(&self, options: &GetTwinoidUserOptions) -> Result<Option<ArchivedTwinoidUser>, EtwinError>;
 
   async fn touch_short_user(&self, options: &ShortTwinoidUser) -> Result<ArchivedTwinoidUser, EtwinError>;
+
+  async fn touch_profile(&self, profile: &TwinoidProfile) -> Result<(), EtwinError>;
+
+  /// Retrieve the contact list of a user as it was at `options.time`, in the order returned by Twinoid.
+  ///
+  /// Returns `None` if no contact list of this user was archived at this time.
+  async fn get_contacts(
+    &self,
+    options: &GetTwinoidUserOptions,
+  ) -> Result<Option<LatestTemporal<Vec<TwinoidContact>>>, EtwinError>;
+
+  async fn touch_contacts(&self, contacts: &TwinoidContactList) -> Result<(), EtwinError>;
 }
 
 // #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
@@ -91,108 +336,641 @@ pub enum TwinoidApiAuth {
   Token(RfcOauthAccessTokenKey),
 }
 
+/// Failure of a Twinoid Graph API call
+#[derive(Debug, thiserror::Error)]
+pub enum TwinoidApiError {
+  #[error("Twinoid rejected the access token as invalid")]
+  InvalidToken,
+  #[error("Twinoid rejected the access token as expired")]
+  ExpiredToken,
+  #[error("Twinoid API rate limit reached")]
+  RateLimited,
+  #[error("Twinoid resource not found")]
+  NotFound,
+  #[error("Twinoid server error with status {0}")]
+  ServerError(u16),
+  #[error("Malformed Twinoid API response: {0}")]
+  MalformedBody(String),
+  #[error(transparent)]
+  Other(EtwinError),
+}
+
+impl TwinoidApiError {
+  pub fn other<E: std::error::Error + Send + Sync + 'static>(e: E) -> Self {
+    Self::Other(Box::new(e))
+  }
+}
+
 pub mod api {
+  //! Typed field selection for the Twinoid Graph API.
+  //!
+  //! The Graph API only returns the fields listed in the `fields` query parameter, for example
+  //! `id,name,contacts.fields(user.fields(id,name))`. Queries are plain values built field by field so they
+  //! can be passed through `dyn TwinoidClient`. Fields that were not requested are `None` in the output.
+  //!
+  //! Reference: <https://twinoid.com/developers/doc>
+
   use crate::core::HtmlFragment;
   use crate::twinoid::TwinoidUserDisplayName;
   #[cfg(feature = "_serde")]
   use etwin_serde_tools::{Deserialize, Serialize};
-  #[cfg(feature = "_serde")]
-  use serde::de::DeserializeOwned;
 
-  #[cfg(not(feature = "_serde"))]
-  pub trait UserLike {}
+  /// Field selection for a Twinoid user. The `id` is always selected.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+  pub struct UserQuery {
+    name: bool,
+    title: bool,
+    picture: bool,
+    locale: bool,
+    gender: bool,
+    city: bool,
+    contacts: Option<Box<ContactQuery>>,
+    sites: Option<SiteUserQuery>,
+  }
 
-  #[cfg(feature = "_serde")]
-  pub trait UserLike: DeserializeOwned {}
+  impl UserQuery {
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    /// Query for the contacts archived as a `TwinoidContactList`
+    pub fn contact_list() -> Self {
+      Self::new()
+        .name()
+        .contacts(ContactQuery::new().user(UserQuery::new().name()).friend())
+    }
 
-  pub trait UserQuery: Send + Sync {
-    type Output: UserLike;
-    type Fields: AsRef<str>;
-    // https://twinoid.com/graph/user/38?fields=id,name,picture,title,like,contacts.fields(user.fields(name,contacts))
-    fn to_fields(&self) -> Self::Fields;
+    /// Query for all the fields archived as a `TwinoidProfile`
+    pub fn profile() -> Self {
+      Self::new().name().title().picture().locale().gender().city().sites(
+        SiteUserQuery::new()
+          .stats(StatQuery::new())
+          .achievements(AchievementQuery::new().points()),
+      )
+    }
+
+    /// Query for all the fields archived at login: the `TwinoidProfile` and the `TwinoidContactList`
+    pub fn archive() -> Self {
+      Self::profile().contacts(ContactQuery::new().user(UserQuery::new().name()).friend())
+    }
+
+    pub fn name(self) -> Self {
+      Self { name: true, ..self }
+    }
+
+    pub fn title(self) -> Self {
+      Self { title: true, ..self }
+    }
+
+    pub fn picture(self) -> Self {
+      Self { picture: true, ..self }
+    }
+
+    pub fn locale(self) -> Self {
+      Self { locale: true, ..self }
+    }
+
+    pub fn gender(self) -> Self {
+      Self { gender: true, ..self }
+    }
+
+    pub fn city(self) -> Self {
+      Self { city: true, ..self }
+    }
+
+    pub fn contacts(self, query: ContactQuery) -> Self {
+      Self {
+        contacts: Some(Box::new(query)),
+        ..self
+      }
+    }
+
+    pub fn sites(self, query: SiteUserQuery) -> Self {
+      Self {
+        sites: Some(query),
+        ..self
+      }
+    }
+
+    /// Value of the `fields` query parameter
+    pub fn to_fields(&self) -> String {
+      let mut fields = vec![String::from("id")];
+      push_flag(&mut fields, self.name, "name");
+      push_flag(&mut fields, self.title, "title");
+      push_flag(&mut fields, self.picture, "picture");
+      push_flag(&mut fields, self.locale, "locale");
+      push_flag(&mut fields, self.gender, "gender");
+      push_flag(&mut fields, self.city, "city");
+      if let Some(contacts) = &self.contacts {
+        fields.push(nested("contacts", &contacts.to_fields()));
+      }
+      if let Some(sites) = &self.sites {
+        fields.push(nested("sites", &sites.to_fields()));
+      }
+      fields.join(",")
+    }
   }
 
-  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
-  #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-  pub struct User<Name, Title> {
-    pub id: u32,
-    pub name: Name,
-    pub title: Title,
+  /// Field selection for the contacts of a user. The contact user is always selected.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+  pub struct ContactQuery {
+    user: UserQuery,
+    friend: bool,
   }
 
-  #[cfg(not(feature = "_serde"))]
-  impl<Name, Title> UserLike for User<Name, Title> {}
+  impl ContactQuery {
+    pub fn new() -> Self {
+      Self::default()
+    }
 
-  #[cfg(feature = "_serde")]
-  impl<Name: DeserializeOwned, Title: DeserializeOwned> UserLike for User<Name, Title> {}
+    pub fn user(self, query: UserQuery) -> Self {
+      Self { user: query, ..self }
+    }
 
-  #[derive(Debug)]
-  pub struct ConstUserQuery<const NAME: bool, const TITLE: bool>;
+    pub fn friend(self) -> Self {
+      Self { friend: true, ..self }
+    }
 
-  impl UserQuery for ConstUserQuery<false, false> {
-    type Output = User<(), ()>;
-    type Fields = &'static str;
+    pub fn to_fields(&self) -> String {
+      let mut fields = vec![nested("user", &self.user.to_fields())];
+      push_flag(&mut fields, self.friend, "friend");
+      fields.join(",")
+    }
+  }
+
+  /// Field selection for the per-site data of a user. The site is always selected.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+  pub struct SiteUserQuery {
+    site: SiteQuery,
+    real_id: bool,
+    link: bool,
+    points: bool,
+    npoints: bool,
+    stats: Option<StatQuery>,
+    achievements: Option<AchievementQuery>,
+  }
+
+  impl SiteUserQuery {
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    pub fn site(self, query: SiteQuery) -> Self {
+      Self { site: query, ..self }
+    }
+
+    pub fn real_id(self) -> Self {
+      Self { real_id: true, ..self }
+    }
+
+    pub fn link(self) -> Self {
+      Self { link: true, ..self }
+    }
+
+    pub fn points(self) -> Self {
+      Self { points: true, ..self }
+    }
+
+    pub fn npoints(self) -> Self {
+      Self { npoints: true, ..self }
+    }
+
+    pub fn stats(self, query: StatQuery) -> Self {
+      Self {
+        stats: Some(query),
+        ..self
+      }
+    }
 
-    fn to_fields(&self) -> Self::Fields {
-      "id"
+    pub fn achievements(self, query: AchievementQuery) -> Self {
+      Self {
+        achievements: Some(query),
+        ..self
+      }
     }
+
+    pub fn to_fields(&self) -> String {
+      let mut fields = vec![nested("site", &self.site.to_fields())];
+      push_flag(&mut fields, self.real_id, "realId");
+      push_flag(&mut fields, self.link, "link");
+      push_flag(&mut fields, self.points, "points");
+      push_flag(&mut fields, self.npoints, "npoints");
+      if let Some(stats) = &self.stats {
+        fields.push(nested("stats", &stats.to_fields()));
+      }
+      if let Some(achievements) = &self.achievements {
+        fields.push(nested("achievements", &achievements.to_fields()));
+      }
+      fields.join(",")
+    }
+  }
+
+  /// Field selection for a Twinoid site. The `id` is always selected.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+  pub struct SiteQuery {
+    name: bool,
+    host: bool,
+    icon: bool,
+    lang: bool,
   }
 
-  impl UserQuery for ConstUserQuery<false, true> {
-    type Output = User<(), HtmlFragment>;
-    type Fields = &'static str;
+  impl SiteQuery {
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    pub fn name(self) -> Self {
+      Self { name: true, ..self }
+    }
+
+    pub fn host(self) -> Self {
+      Self { host: true, ..self }
+    }
+
+    pub fn icon(self) -> Self {
+      Self { icon: true, ..self }
+    }
+
+    pub fn lang(self) -> Self {
+      Self { lang: true, ..self }
+    }
 
-    fn to_fields(&self) -> Self::Fields {
-      "id,title"
+    pub fn to_fields(&self) -> String {
+      let mut fields = vec![String::from("id")];
+      push_flag(&mut fields, self.name, "name");
+      push_flag(&mut fields, self.host, "host");
+      push_flag(&mut fields, self.icon, "icon");
+      push_flag(&mut fields, self.lang, "lang");
+      fields.join(",")
     }
   }
 
-  impl UserQuery for ConstUserQuery<true, false> {
-    type Output = User<TwinoidUserDisplayName, ()>;
-    type Fields = &'static str;
+  /// Field selection for a site stat. The `id` and `score` are always selected.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+  pub struct StatQuery {
+    name: bool,
+    icon: bool,
+    description: bool,
+    rare: bool,
+    social: bool,
+  }
+
+  impl StatQuery {
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    pub fn name(self) -> Self {
+      Self { name: true, ..self }
+    }
+
+    pub fn icon(self) -> Self {
+      Self { icon: true, ..self }
+    }
+
+    pub fn description(self) -> Self {
+      Self {
+        description: true,
+        ..self
+      }
+    }
+
+    pub fn rare(self) -> Self {
+      Self { rare: true, ..self }
+    }
+
+    pub fn social(self) -> Self {
+      Self { social: true, ..self }
+    }
 
-    fn to_fields(&self) -> Self::Fields {
-      "id,name"
+    pub fn to_fields(&self) -> String {
+      let mut fields = vec![String::from("id"), String::from("score")];
+      push_flag(&mut fields, self.name, "name");
+      push_flag(&mut fields, self.icon, "icon");
+      push_flag(&mut fields, self.description, "description");
+      push_flag(&mut fields, self.rare, "rare");
+      push_flag(&mut fields, self.social, "social");
+      fields.join(",")
     }
   }
 
-  impl UserQuery for ConstUserQuery<true, true> {
-    type Output = User<TwinoidUserDisplayName, HtmlFragment>;
-    type Fields = &'static str;
+  /// Field selection for a site achievement. The `id` is always selected.
+  #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+  pub struct AchievementQuery {
+    name: bool,
+    stat: bool,
+    score: bool,
+    points: bool,
+    npoints: bool,
+    description: bool,
+    date: bool,
+  }
+
+  impl AchievementQuery {
+    pub fn new() -> Self {
+      Self::default()
+    }
+
+    pub fn name(self) -> Self {
+      Self { name: true, ..self }
+    }
+
+    pub fn stat(self) -> Self {
+      Self { stat: true, ..self }
+    }
+
+    pub fn score(self) -> Self {
+      Self { score: true, ..self }
+    }
+
+    pub fn points(self) -> Self {
+      Self { points: true, ..self }
+    }
+
+    pub fn npoints(self) -> Self {
+      Self { npoints: true, ..self }
+    }
+
+    pub fn description(self) -> Self {
+      Self {
+        description: true,
+        ..self
+      }
+    }
+
+    pub fn date(self) -> Self {
+      Self { date: true, ..self }
+    }
+
+    pub fn to_fields(&self) -> String {
+      let mut fields = vec![String::from("id")];
+      push_flag(&mut fields, self.name, "name");
+      push_flag(&mut fields, self.stat, "stat");
+      push_flag(&mut fields, self.score, "score");
+      push_flag(&mut fields, self.points, "points");
+      push_flag(&mut fields, self.npoints, "npoints");
+      push_flag(&mut fields, self.description, "description");
+      push_flag(&mut fields, self.date, "date");
+      fields.join(",")
+    }
+  }
 
-    fn to_fields(&self) -> Self::Fields {
-      "id,name,title"
+  fn push_flag(fields: &mut Vec<String>, selected: bool, name: &str) {
+    if selected {
+      fields.push(String::from(name));
     }
   }
+
+  fn nested(name: &str, fields: &str) -> String {
+    format!("{}.fields({})", name, fields)
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq)]
+  pub struct User {
+    pub id: u32,
+    pub name: Option<TwinoidUserDisplayName>,
+    pub title: Option<HtmlFragment>,
+    pub picture: Option<Picture>,
+    pub locale: Option<String>,
+    pub gender: Option<String>,
+    pub city: Option<String>,
+    pub contacts: Option<Vec<Contact>>,
+    pub sites: Option<Vec<SiteUser>>,
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+  pub struct Picture {
+    pub url: String,
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq)]
+  pub struct Contact {
+    pub user: User,
+    pub friend: Option<bool>,
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq)]
+  pub struct SiteUser {
+    pub site: Site,
+    #[cfg_attr(feature = "_serde", serde(rename = "realId"))]
+    pub real_id: Option<u32>,
+    pub link: Option<String>,
+    pub points: Option<u32>,
+    pub npoints: Option<f64>,
+    pub stats: Option<Vec<Stat>>,
+    pub achievements: Option<Vec<Achievement>>,
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+  pub struct Site {
+    pub id: u32,
+    pub name: Option<String>,
+    pub host: Option<String>,
+    pub icon: Option<String>,
+    pub lang: Option<String>,
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+  pub struct Stat {
+    pub id: String,
+    pub score: i64,
+    pub name: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<HtmlFragment>,
+    pub rare: Option<i32>,
+    pub social: Option<bool>,
+  }
+
+  #[cfg_attr(feature = "_serde", derive(Deserialize, Serialize))]
+  #[derive(Clone, Debug, PartialEq)]
+  pub struct Achievement {
+    pub id: String,
+    pub name: Option<String>,
+    pub stat: Option<String>,
+    pub score: Option<i64>,
+    pub points: Option<u32>,
+    pub npoints: Option<f64>,
+    pub description: Option<HtmlFragment>,
+    pub date: Option<String>,
+  }
 }
 
 #[async_trait]
-#[auto_impl(&)]
+#[auto_impl(&, Arc)]
 pub trait TwinoidClient: Send + Sync {
-  async fn get_me<Query: api::UserQuery>(
-    &self,
-    auth: TwinoidApiAuth,
-    query: &Query,
-  ) -> Result<Query::Output, EtwinError>
-  where
-    Self: Sized;
+  /// Retrieve the user authenticated by `auth`.
+  async fn get_me(&self, auth: TwinoidApiAuth, query: &api::UserQuery) -> Result<api::User, TwinoidApiError>;
 
-  async fn get_me_short(
+  /// Retrieve any user by id, returns `None` if the user does not exist.
+  async fn get_user(
     &self,
     auth: TwinoidApiAuth,
-  ) -> Result<api::User<TwinoidUserDisplayName, HtmlFragment>, EtwinError>;
+    id: TwinoidUserId,
+    query: &api::UserQuery,
+  ) -> Result<Option<api::User>, TwinoidApiError>;
+
+  async fn get_me_short(&self, auth: TwinoidApiAuth) -> Result<ShortTwinoidUser, TwinoidApiError> {
+    let user = self.get_me(auth, &api::UserQuery::new().name()).await?;
+    Ok(ShortTwinoidUser {
+      id: TwinoidUserId::new(user.id).map_err(|e| TwinoidApiError::MalformedBody(e.to_string()))?,
+      display_name: user
+        .name
+        .ok_or_else(|| TwinoidApiError::MalformedBody(String::from("missing user name")))?,
+    })
+  }
+}
+
+/// Result of a successful authorization code exchange
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidOauthGrant {
+  pub user: ShortTwinoidUser,
+  pub access_token: RfcOauthAccessTokenKey,
 }
 
+/// Client of the Twinoid OAuth token endpoint, the tokens it receives are persisted in the token store.
 #[async_trait]
-impl<T: TwinoidClient + ?Sized> TwinoidClient for Arc<T> {
-  async fn get_me<Query: UserQuery>(&self, _auth: TwinoidApiAuth, _query: &Query) -> Result<Query::Output, EtwinError>
-  where
-    Self: Sized,
-  {
-    todo!()
+#[auto_impl(&, Arc)]
+pub trait TwinoidOauthClient: Send + Sync {
+  /// URI where the user should be redirected to grant access to Eternal-Twin.
+  fn authorization_uri(&self, scope: &str, state: &str) -> Url;
+
+  /// Exchange an authorization code and persist the resulting tokens.
+  async fn exchange_code(&self, code: &str) -> Result<TwinoidOauthGrant, EtwinError>;
+
+  /// Use the stored refresh token of `user` to get a new access token.
+  async fn refresh(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError>;
+
+  /// Get a valid access token for `user`, refreshing it if it expired.
+  async fn get_access_token(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError>;
+
+  /// Retrieve the Twinoid user `user` using their stored tokens.
+  ///
+  /// If Twinoid rejects the stored access token, it is revoked and the request is retried once with a refreshed
+  /// token.
+  async fn get_me(&self, user: TwinoidUserIdRef, query: &api::UserQuery) -> Result<api::User, EtwinError>;
+}
+
+#[cfg(test)]
+mod test {
+  use crate::twinoid::api::{AchievementQuery, ContactQuery, SiteQuery, SiteUserQuery, StatQuery, UserQuery};
+
+  #[test]
+  fn user_query_id_only() {
+    assert_eq!(UserQuery::new().to_fields(), "id");
+  }
+
+  #[test]
+  fn user_query_nested() {
+    let query = UserQuery::new()
+      .name()
+      .picture()
+      .locale()
+      .contacts(ContactQuery::new().friend().user(UserQuery::new().name()))
+      .sites(
+        SiteUserQuery::new()
+          .site(SiteQuery::new().name())
+          .real_id()
+          .stats(StatQuery::new().name())
+          .achievements(AchievementQuery::new().name().date()),
+      );
+    assert_eq!(
+      query.to_fields(),
+      "id,name,picture,locale,contacts.fields(user.fields(id,name),friend),sites.fields(site.fields(id,name),realId,stats.fields(id,score,name),achievements.fields(id,name,date))"
+    );
+  }
+
+  #[cfg(feature = "_serde")]
+  #[test]
+  fn read_partial_user() {
+    use crate::twinoid::api::{Contact, User};
+
+    let actual: User = serde_json::from_str(
+      r#"{"id":38,"name":"Demurgos","contacts":[{"user":{"id":1,"name":"alice"},"friend":true}]}"#,
+    )
+    .unwrap();
+    let expected = User {
+      id: 38,
+      name: Some("Demurgos".parse().unwrap()),
+      title: None,
+      picture: None,
+      locale: None,
+      gender: None,
+      city: None,
+      contacts: Some(vec![Contact {
+        user: User {
+          id: 1,
+          name: Some("alice".parse().unwrap()),
+          title: None,
+          picture: None,
+          locale: None,
+          gender: None,
+          city: None,
+          contacts: None,
+          sites: None,
+        },
+        friend: Some(true),
+      }]),
+      sites: None,
+    };
+    assert_eq!(actual, expected);
+  }
+
+  #[test]
+  fn user_query_contact_list() {
+    assert_eq!(
+      UserQuery::contact_list().to_fields(),
+      "id,name,contacts.fields(user.fields(id,name),friend)"
+    );
   }
 
-  async fn get_me_short(&self, auth: TwinoidApiAuth) -> Result<User<TwinoidUserDisplayName, HtmlFragment>, EtwinError> {
-    (**self).get_me_short(auth).await
+  #[test]
+  fn user_query_archive() {
+    assert_eq!(
+      UserQuery::archive().to_fields(),
+      "id,name,title,picture,locale,gender,city,contacts.fields(user.fields(id,name),friend),sites.fields(site.fields(id),stats.fields(id,score),achievements.fields(id,points))"
+    );
+  }
+
+  #[cfg(feature = "_serde")]
+  #[test]
+  fn read_contact_list() {
+    use crate::twinoid::api::User;
+    use crate::twinoid::{ShortTwinoidUser, TwinoidContact, TwinoidContactList};
+    use std::convert::TryFrom;
+
+    let user: User = serde_json::from_str(
+      r#"{"id":38,"name":"Demurgos","contacts":[{"user":{"id":1,"name":"alice"},"friend":true},{"user":{"id":2,"name":"bob"}}]}"#,
+    )
+    .unwrap();
+    let actual = TwinoidContactList::try_from(user).unwrap();
+    let expected = TwinoidContactList {
+      user: ShortTwinoidUser {
+        id: "38".parse().unwrap(),
+        display_name: "Demurgos".parse().unwrap(),
+      },
+      contacts: vec![
+        TwinoidContact {
+          user: ShortTwinoidUser {
+            id: "1".parse().unwrap(),
+            display_name: "alice".parse().unwrap(),
+          },
+          friend: true,
+        },
+        TwinoidContact {
+          user: ShortTwinoidUser {
+            id: "2".parse().unwrap(),
+            display_name: "bob".parse().unwrap(),
+          },
+          friend: false,
+        },
+      ],
+    };
+    assert_eq!(actual, expected);
   }
 }
@@ -51,6 +51,11 @@ macro_rules! declare_decimal_id {
       $struct_vis const unsafe fn new_unchecked(id: $struct_ty) -> Self {
         Self(id)
       }
+
+      /// Returns the inner value as a primitive type
+      $struct_vis const fn get(self) -> $struct_ty {
+        self.0
+      }
     }
 
     impl ::std::fmt::Display for $struct_name {
@@ -87,6 +87,13 @@ pub struct GetShortUserOptions {
   pub time: Option<Instant>,
 }
 
+#[cfg_attr(feature = "_serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GetShortUsersOptions {
+  pub ids: Vec<UserIdRef>,
+  pub time: Option<Instant>,
+}
+
 #[cfg_attr(feature = "_serde", derive(Serialize, Deserialize), serde(untagged))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum GetUserResult {
@@ -315,6 +322,11 @@ pub trait UserStore: Send + Sync {
 
   async fn get_short_user(&self, options: &GetShortUserOptions) -> Result<Option<ShortUser>, EtwinError>;
 
+  /// Retrieve many users at once, in the order of `options.ids`.
+  ///
+  /// Unknown users are skipped.
+  async fn get_short_users(&self, options: &GetShortUsersOptions) -> Result<Vec<ShortUser>, EtwinError>;
+
   async fn get_user_with_password(&self, options: &GetUserOptions)
     -> Result<Option<ShortUserWithPassword>, EtwinError>;
 
@@ -1,6 +1,8 @@
 mod errors;
 mod locale;
+pub mod reparse;
 mod scraper;
+pub mod selfcheck;
 mod url;
 
 use self::errors::ScraperError;
@@ -12,26 +14,33 @@ use erased_serde::Serialize as ErasedSerialize;
 use etwin_core::clock::Clock;
 use etwin_core::dinoparc::{
   DinoparcClient, DinoparcCollectionResponse, DinoparcCredentials, DinoparcDinozId, DinoparcDinozResponse,
-  DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcMachineId, DinoparcServer, DinoparcSession,
-  DinoparcSessionKey, DinoparcSessionUser, DinoparcUserId, DinoparcUsername, GetExchangeWithError, ShortDinoparcUser,
+  DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcMachineId, DinoparcProfile, DinoparcServer,
+  DinoparcSession, DinoparcSessionKey, DinoparcSessionUser, DinoparcUserId, DinoparcUsername, GetExchangeWithError,
+  ShortDinoparcUser,
 };
 use etwin_core::types::EtwinError;
-use etwin_log::Logger;
+use etwin_http::archive::PageArchive;
+use etwin_http::event::RedactedUrl;
+use etwin_http::{
+  new_archive, new_transport, HttpError, HttpLogger, HttpOptions, HttpRequest, HttpResponse, HttpTransport,
+};
+use etwin_log::{Logger, NoopLogger};
+use etwin_scraper_tools::drift::{PageCheck, PageSource};
 use etwin_serde_tools::{serialize_header_map, serialize_status_code, serialize_url};
 use md5::{Digest, Md5};
 use reqwest::header::HeaderMap;
-use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use reqwest::StatusCode;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::fmt::Debug;
 use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
+use std::sync::Arc;
 
 const USER_AGENT: &str = "EtwinDinoparcScraper";
-const TIMEOUT: Duration = Duration::from_millis(5000);
 
 struct StderrLogger;
 
@@ -102,6 +111,7 @@ fn take_bytesu64<'a>(bytes: Option<&'a [u8]>, sink: &mut Vec<&'a [u8]>) -> Optio
 #[derive(Copy, Clone, Debug)]
 pub enum HttpDinoparcClientEvent<'a, Html: 'a> {
   CreateSession(CreateSessionEvent<'a, Html>),
+  ArchiveError(ArchiveErrorEvent<'a>),
 }
 
 impl<'a, Html: 'a> From<CreateSessionEvent<'a, Html>> for HttpDinoparcClientEvent<'a, Html> {
@@ -110,6 +120,27 @@ impl<'a, Html: 'a> From<CreateSessionEvent<'a, Html>> for HttpDinoparcClientEven
   }
 }
 
+impl<'a, Html: 'a> From<ArchiveErrorEvent<'a>> for HttpDinoparcClientEvent<'a, Html> {
+  fn from(ev: ArchiveErrorEvent<'a>) -> Self {
+    Self::ArchiveError(ev)
+  }
+}
+
+fn is_archive_error(ev: &HttpDinoparcClientEvent<'_, &[u8]>) -> bool {
+  matches!(ev, HttpDinoparcClientEvent::ArchiveError(_))
+}
+
+/// Creates the logger selected by `logger`.
+///
+/// The standard error only receives the archive errors: the other events carry the raw pages, which may contain the
+/// session.
+pub fn new_logger(logger: HttpLogger) -> Arc<dyn for<'r> Logger<HttpDinoparcClientEvent<'r, &'r [u8]>>> {
+  match logger {
+    HttpLogger::Noop => Arc::new(NoopLogger),
+    HttpLogger::Stderr => Arc::new(etwin_log::StderrLogger.filter(is_archive_error)),
+  }
+}
+
 impl HttpDinoparcClientEvent<'_, &[u8]> {
   pub fn filter_map<'a>(ev: HttpDinoparcClientEvent<'a, &'a [u8]>) -> Option<HttpEvent<'a>> {
     Some(ev.into())
@@ -120,6 +151,7 @@ impl<'a> From<HttpDinoparcClientEvent<'a, &'a [u8]>> for HttpEvent<'a> {
   fn from(ev: HttpDinoparcClientEvent<'a, &'a [u8]>) -> Self {
     match ev {
       HttpDinoparcClientEvent::CreateSession(ev) => ev.into(),
+      HttpDinoparcClientEvent::ArchiveError(ev) => ev.into(),
     }
   }
 }
@@ -134,8 +166,8 @@ pub struct HttpResponseMeta {
   headers: HeaderMap,
 }
 
-impl<'a> From<&'a Response> for HttpResponseMeta {
-  fn from(response: &'a Response) -> Self {
+impl<'a> From<&'a HttpResponse> for HttpResponseMeta {
+  fn from(response: &'a HttpResponse) -> Self {
     Self {
       uri: response.url().clone(),
       status: response.status(),
@@ -189,22 +221,41 @@ impl<'a> From<CreateSessionEvent<'a, &'a [u8]>> for HttpEvent<'a> {
   }
 }
 
+/// A fetched page could not be written to the page archive.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct ArchiveErrorEvent<'a> {
+  url: &'a str,
+  error: &'a str,
+}
+
+impl<'a> From<ArchiveErrorEvent<'a>> for HttpEvent<'a> {
+  fn from(ev: ArchiveErrorEvent<'a>) -> Self {
+    HttpEvent {
+      target: "archive_error",
+      html: Vec::new(),
+      meta: Box::new(ev),
+    }
+  }
+}
+
 pub struct HttpDinoparcClient<TyClock, TyLogger> {
-  client: Client,
+  transport: Arc<dyn HttpTransport>,
+  archive: Option<Arc<dyn PageArchive>>,
+  /// Base URLs replacing the official hosts, keyed by server name
+  base_urls: BTreeMap<String, Url>,
   clock: TyClock,
   logger: TyLogger,
 }
 
-trait RequestBuilderExt {
-  fn with_session(self, key: Option<DinoparcSessionKey>) -> RequestBuilder;
+trait HttpRequestExt {
+  fn with_session(self, key: Option<&DinoparcSessionKey>) -> HttpRequest;
 }
 
-impl RequestBuilderExt for RequestBuilder {
-  fn with_session(self, key: Option<DinoparcSessionKey>) -> RequestBuilder {
+impl HttpRequestExt for HttpRequest {
+  fn with_session(self, key: Option<&DinoparcSessionKey>) -> HttpRequest {
     if let Some(key) = key {
       // No need to escape, per DinoparcSessionKey invariants.
-      let session_cookie = "sid=".to_owned() + key.as_str();
-      self.header(reqwest::header::COOKIE, session_cookie)
+      self.cookie("sid", key.as_str())
     } else {
       self
     }
@@ -217,30 +268,91 @@ where
   TyLogger: for<'r> Logger<HttpDinoparcClientEvent<'r, &'r [u8]>>,
 {
   pub fn new(clock: TyClock, logger: TyLogger) -> Result<Self, EtwinError> {
+    Self::with_options(clock, logger, &HttpOptions::default())
+  }
+
+  /// Creates a client using the provided transport options (mode, timeout, rate limit, retries, archive, proxy and
+  /// base URLs).
+  pub fn with_options(clock: TyClock, logger: TyLogger, options: &HttpOptions) -> Result<Self, EtwinError> {
     Ok(Self {
-      client: Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(TIMEOUT)
-        .redirect(reqwest::redirect::Policy::none())
-        .build()?,
+      archive: new_archive(options, USER_AGENT)?,
+      base_urls: options.base_urls.clone(),
+      ..Self::with_transport(clock, logger, new_transport(options, USER_AGENT)?)
+    })
+  }
+
+  pub fn with_transport(clock: TyClock, logger: TyLogger, transport: Arc<dyn HttpTransport>) -> Self {
+    Self {
+      transport,
+      archive: None,
+      base_urls: BTreeMap::new(),
       clock,
       logger,
-    })
+    }
   }
 
-  async fn get_html(&self, url: reqwest::Url, session: Option<&DinoparcSessionKey>) -> reqwest::Result<Html> {
-    let mut builder = self.client.get(url);
+  /// URLs of `server`, using its configured base URL if any.
+  fn urls(&self, server: DinoparcServer) -> DinoparcUrls {
+    match self.base_urls.get(server.as_str()) {
+      Some(root) => DinoparcUrls::with_root(root.clone()),
+      None => DinoparcUrls::new(server),
+    }
+  }
 
-    if let Some(key) = session {
-      // No need to escape, per DinoparcSessionKey invariants.
-      let session_cookie = "sid=".to_owned() + key.as_str();
-      builder = builder.header(reqwest::header::COOKIE, session_cookie);
+  /// Sends `req`, archiving the response if a page archive is configured.
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let res = self.transport.send(req).await?;
+    if let Some(archive) = &self.archive {
+      // The archive is only a copy of the pages: failing to write it must not fail the scrape
+      if let Err(e) = archive.archive(self.clock.now(), &res) {
+        let url = RedactedUrl::new(res.url());
+        let error = url.redact_error(res.url(), &e);
+        self.logger.log(
+          ArchiveErrorEvent {
+            url: url.as_str(),
+            error: &error,
+          }
+          .into(),
+        );
+      }
     }
+    Ok(res)
+  }
 
-    let resp = builder.send().await?;
-    let text = resp.error_for_status()?.text().await?;
+  async fn get_html(&self, url: reqwest::Url, session: Option<&DinoparcSessionKey>) -> Result<Html, HttpError> {
+    let req = HttpRequest::get(url).with_session(session);
+    let resp = self.send(req).await?;
+    let text = resp.error_for_status()?.text();
     Ok(Html::parse_document(&text))
   }
+
+  /// Fetches the canary pages as a guest and checks them against the scrapers, see [selfcheck::check_catalog].
+  pub async fn check_canaries(&self) -> Vec<PageCheck> {
+    let mut checks = Vec::new();
+    for (url, dir) in selfcheck::canaries(|server| self.urls(server)) {
+      let res = self
+        .send(HttpRequest::get(url.clone()))
+        .await
+        .and_then(|res| res.error_for_status());
+      let check = match res {
+        Ok(res) => selfcheck::check_html(
+          dir,
+          url.to_string(),
+          PageSource::Canary,
+          &Html::parse_document(&res.text()),
+        ),
+        Err(e) => PageCheck {
+          kind: selfcheck::page_kind(dir),
+          page: url.to_string(),
+          source: PageSource::Canary,
+          error: Some(e.to_string()),
+          selectors: Vec::new(),
+        },
+      };
+      checks.push(check);
+    }
+    checks
+  }
 }
 
 fn derive_machine_id(username: &DinoparcUsername) -> DinoparcMachineId {
@@ -284,19 +396,16 @@ where
       login: &'a str,
       pass: &'a str,
     }
-    let urls = DinoparcUrls::new(options.server);
+    let urls = self.urls(options.server);
 
     let now = self.clock.now();
-    let res = self
-      .client
-      .post(urls.login())
+    let req = HttpRequest::post(urls.login())
       .form(&LoginForm {
         login: options.username.as_str(),
         pass: options.password.as_str(),
       })
-      .send()
-      .await
       .log_on_err(event, logger)?;
+    let res = self.send(req).await.log_on_err(event, logger)?;
     event.state = "login_response";
     let login_res_meta = HttpResponseMeta::from(&res);
     event.login_response = Some(&login_res_meta);
@@ -307,31 +416,24 @@ where
     event.state = "login_response_ok";
 
     let session_key = res
-      .cookies()
-      .find(|cookie| cookie.name() == "sid")
-      .map(|cookie| cookie.value().to_owned())
+      .cookie("sid")
       .ok_or(ScraperError::MissingSessionCookie)
       .log_on_err(event, logger)?;
     event.state = "login_session_key_found";
-    let session_key = DinoparcSessionKey::from_str(&session_key)
+    let session_key = DinoparcSessionKey::from_str(session_key)
       .map_err(|_| ScraperError::InvalidSessionCookie)
       .log_on_err(event, logger)?;
     event.state = "login_session_key_ok";
 
     {
-      touch_ad_tracking(&self.client, session_key.clone(), options.server, &options.username).await?;
+      touch_ad_tracking(&*self.transport, &session_key, &urls, &options.username).await?;
       event.state = "login_touched_ad_tracking";
-      confirm_login(&self.client, session_key.clone(), options.server).await?;
+      confirm_login(&*self.transport, &session_key, &urls).await?;
       event.state = "login_confirm_login";
     }
 
-    let mut builder = self.client.get(urls.bank());
-
-    // No need to escape, per DinoparcSessionKey invariants.
-    let session_cookie = "sid=".to_owned() + session_key.as_str();
-    builder = builder.header(reqwest::header::COOKIE, session_cookie);
-
-    let resp = builder.send().await.log_on_err(event, logger)?;
+    let req = HttpRequest::get(urls.bank()).with_session(Some(&session_key));
+    let resp = self.send(req).await.log_on_err(event, logger)?;
     event.state = "login_bank_response";
     let bank_res_meta = HttpResponseMeta::from(&resp);
     event.bank_response = Some(&bank_res_meta);
@@ -340,7 +442,7 @@ where
       return Err(ScraperError::InvalidCredentials(options.server, options.username.clone()).into())
         .log_on_err(event, logger);
     }
-    let text = resp.error_for_status()?.text().await.log_on_err(event, logger)?;
+    let text = resp.error_for_status().log_on_err(event, logger)?.text();
     event.bank_html = Some(text.as_bytes());
     let html = Html::parse_document(&text);
     event.state = "login_bank_html";
@@ -365,9 +467,13 @@ where
     session_key: &DinoparcSessionKey,
   ) -> Result<Option<DinoparcSession>, EtwinError> {
     let now = self.clock.now();
-    let html = self
-      .get_html(DinoparcUrls::new(server).bank(), Some(session_key))
-      .await?;
+    let req = HttpRequest::get(self.urls(server).bank()).with_session(Some(session_key));
+    let resp = self.send(req).await?;
+    if resp.status() == StatusCode::FOUND {
+      // Redirected: the session is no longer valid
+      return Ok(None);
+    }
+    let html = Html::parse_document(&resp.error_for_status()?.text());
     let user = scraper::scrape_bank(&html)?;
     Ok(Some(DinoparcSession {
       ctime: now,
@@ -387,7 +493,7 @@ where
     id: DinoparcDinozId,
   ) -> Result<DinoparcDinozResponse, EtwinError> {
     let html = self
-      .get_html(DinoparcUrls::new(session.user.server).dinoz(id), Some(&session.key))
+      .get_html(self.urls(session.user.server).dinoz(id), Some(&session.key))
       .await?;
     let response = scraper::scrape_dinoz(&html)?;
     // TODO: Assert username matches
@@ -416,7 +522,7 @@ where
 
     let html = self
       .get_html(
-        DinoparcUrls::new(session.user.server).exchange_with(other_user),
+        self.urls(session.user.server).exchange_with(other_user),
         Some(&session.key),
       )
       .await?;
@@ -440,17 +546,11 @@ where
   }
 
   async fn get_inventory(&self, session: &DinoparcSession) -> Result<DinoparcInventoryResponse, EtwinError> {
-    let uri = DinoparcUrls::new(session.user.server).inventory();
+    let uri = self.urls(session.user.server).inventory();
 
-    let mut builder = self.client.get(uri.clone());
-    {
-      // No need to escape, per DinoparcSessionKey invariants.
-      let session_cookie = "sid=".to_owned() + session.key.as_str();
-      builder = builder.header(reqwest::header::COOKIE, session_cookie);
-    }
-
-    let resp = builder.send().await?;
-    let text = resp.error_for_status()?.text().await?;
+    let req = HttpRequest::get(uri).with_session(Some(&session.key));
+    let resp = self.send(req).await?;
+    let text = resp.error_for_status()?.text();
     let text = text.as_str();
     let html = Html::parse_document(text);
     let result = scraper::scrape_inventory(&html).map(|response| DinoparcInventoryResponse {
@@ -475,7 +575,7 @@ where
 
   async fn get_collection(&self, session: &DinoparcSession) -> Result<DinoparcCollectionResponse, EtwinError> {
     let html = self
-      .get_html(DinoparcUrls::new(session.user.server).collection(), Some(&session.key))
+      .get_html(self.urls(session.user.server).collection(), Some(&session.key))
       .await?;
     let response = scraper::scrape_collection(&html)?;
     // TODO: Assert username matches
@@ -492,22 +592,35 @@ where
       collection: response.collection,
     })
   }
+
+  async fn get_user_profile(
+    &self,
+    server: DinoparcServer,
+    id: DinoparcUserId,
+  ) -> Result<Option<DinoparcProfile>, EtwinError> {
+    let resp = self.send(HttpRequest::get(self.urls(server).user(id))).await?;
+    if resp.status() == StatusCode::FOUND {
+      // Redirected: the user does not exist
+      return Ok(None);
+    }
+    let text = resp.error_for_status()?.text();
+    let html = Html::parse_document(&text);
+    let profile = scraper::scrape_user_profile(&html)?;
+    Ok(Some(profile.into_profile(id)))
+  }
 }
 
 async fn touch_ad_tracking(
-  client: &Client,
-  session: DinoparcSessionKey,
-  server: DinoparcServer,
+  transport: &dyn HttpTransport,
+  session: &DinoparcSessionKey,
+  urls: &DinoparcUrls,
   username: &DinoparcUsername,
 ) -> Result<(), ScraperError> {
   let mid = derive_machine_id(username);
-  let res = client
-    .get(DinoparcUrls::new(server).ad_tracking(mid))
-    .with_session(Some(session))
-    .send()
-    .await?;
+  let req = HttpRequest::get(urls.ad_tracking(mid)).with_session(Some(session));
+  let res = transport.send(req).await?;
 
-  if res.status() == StatusCode::OK && res.text().await? == "OK" {
+  if res.status() == StatusCode::OK && res.text() == "OK" {
     Ok(())
   } else {
     Err(ScraperError::UnexpectedAdTrackingResponse)
@@ -515,15 +628,12 @@ async fn touch_ad_tracking(
 }
 
 async fn confirm_login(
-  client: &Client,
-  session: DinoparcSessionKey,
-  server: DinoparcServer,
+  transport: &dyn HttpTransport,
+  session: &DinoparcSessionKey,
+  urls: &DinoparcUrls,
 ) -> Result<(), ScraperError> {
-  let res = client
-    .get(DinoparcUrls::new(server).login())
-    .with_session(Some(session))
-    .send()
-    .await?;
+  let req = HttpRequest::get(urls.login()).with_session(Some(session));
+  let res = transport.send(req).await?;
 
   let status = res.status();
   if status == StatusCode::OK || status == StatusCode::FOUND {
@@ -207,6 +207,26 @@ pub enum ScraperError {
   NonUniquePlacePane,
   #[error("Zero or many place link, exactly one was expected")]
   NonUniquePlaceLink,
+  #[error("Zero or many user profile, exactly one was expected")]
+  NonUniqueUserProfile,
+  #[error("Zero or many user profile name, exactly one was expected")]
+  NonUniqueUserProfileName,
+  #[error("2 or more user rank rows, zero or one expected")]
+  NonUniqueUserRank,
+  #[error("Invalid user rank {:?}", .0)]
+  InvalidUserRank(String),
+  #[error("2 or more clan links, zero or one expected")]
+  NonUniqueClanLink,
+  #[error("Zero or many clan id in link, exactly one was expected")]
+  NonUniqueClanIdInLink,
+  #[error("Invalid clan id {:?}", .0)]
+  InvalidClanId(String),
+  #[error("Invalid clan name {:?}", .0)]
+  InvalidClanName(String),
+  #[error("2 or more profile dinoz lists, zero or one expected")]
+  NonUniqueProfileDinozList,
+  #[error("Zero or many profile dinoz link, exactly one was expected")]
+  NonUniqueProfileDinozLink,
   #[error("HTTP Error")]
-  HttpError(#[from] reqwest::Error),
+  HttpError(#[from] etwin_http::HttpError),
 }
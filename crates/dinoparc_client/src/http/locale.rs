@@ -7,6 +7,8 @@ use regex::Regex;
 
 pub struct ScraperLocale {
   pub in_tournament_pattern: Regex,
+  /// Header of the rank row on user profiles
+  pub rank_label: &'static str,
   pub location_names: HashMap<&'static str, DinoparcLocationId>,
   pub skill_names: HashMap<&'static str, DinoparcSkill>,
 }
@@ -23,6 +25,7 @@ impl ScraperLocale {
 
 static SCRAPER_LOCALE_FR: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
   in_tournament_pattern: Regex::new(r#"participe actuellement au Tournoi de ce lieu"#).unwrap(),
+  rank_label: "Classement",
   location_names: std::array::IntoIter::new(LOCATIONS)
     .map(|l| (l.name_fr, l.id))
     .collect(),
@@ -39,6 +42,7 @@ static SCRAPER_LOCALE_FR: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
 
 static SCRAPER_LOCALE_ES: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
   in_tournament_pattern: Regex::new(r#"participa actualmente en el Torneo de este lugar"#).unwrap(),
+  rank_label: "Clasificación",
   location_names: std::array::IntoIter::new(LOCATIONS)
     .map(|l| (l.name_es, l.id))
     .collect(),
@@ -55,6 +59,7 @@ static SCRAPER_LOCALE_ES: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
 
 static SCRAPER_LOCALE_EN: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
   in_tournament_pattern: Regex::new(r#"is currently participating in the Tournament in this location"#).unwrap(),
+  rank_label: "Ranking",
   location_names: std::array::IntoIter::new(LOCATIONS)
     .map(|l| (l.name_en, l.id))
     .collect(),
@@ -0,0 +1,174 @@
+use super::scraper;
+use super::url::DinoparcUrls;
+use ::scraper::Html;
+use ::url::Url;
+use etwin_core::dinoparc::{
+  DinoparcCollectionResponse, DinoparcDinozResponse, DinoparcExchangeWithResponse, DinoparcInventoryResponse,
+  DinoparcProfile, DinoparcServer, DinoparcSessionUser, DinoparcUserId, DinoparcUsername, ShortDinoparcUser,
+};
+use etwin_core::types::EtwinError;
+use etwin_http::{path_below, HttpOptions};
+use std::str::FromStr;
+
+/// Archived page, parsed with the scraper matching its URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DinoparcArchivedPage {
+  Profile(DinoparcProfile),
+  /// Bank of the session user, the only page with the id of the session user
+  Bank(ShortDinoparcUser),
+  Inventory(DinoparcInventoryResponse),
+  Collection(DinoparcCollectionResponse),
+  Dinoz(DinoparcDinozResponse),
+  ExchangeWith(DinoparcExchangeWithResponse),
+}
+
+/// Parses an archived page fetched from `url`, on the official host or on the base URL configured in `options`.
+///
+/// Pages requiring a session only contain the username of the session user: `session_user_id` must return its id,
+/// usually from a [DinoparcArchivedPage::Bank] page reparsed before.
+///
+/// Returns `None` if `url` is not a Dinoparc page with a supported scraper.
+pub fn reparse_page(
+  options: &HttpOptions,
+  url: &Url,
+  html: &str,
+  session_user_id: &dyn Fn(DinoparcServer, &DinoparcUsername) -> Option<DinoparcUserId>,
+) -> Result<Option<DinoparcArchivedPage>, EtwinError> {
+  let server = DinoparcServer::iter().find(|server| {
+    let mirror = options.base_url(server.as_str()).cloned().map(DinoparcUrls::with_root);
+    std::iter::once(DinoparcUrls::new(*server))
+      .chain(mirror)
+      .any(|urls| path_below(&urls.root(), url).is_some())
+  });
+  let server = match server {
+    Some(server) => server,
+    None => return Ok(None),
+  };
+  let param = |name: &str| url.query_pairs().find(|(k, _)| k == name).map(|(_, v)| v.into_owned());
+  let session_user = |user: DinoparcSessionUser<DinoparcUsername>| -> Result<DinoparcSessionUser, EtwinError> {
+    let id = session_user_id(server, &user.user).ok_or_else(|| {
+      format!(
+        "unknown id for the session user {}: reparse its bank page first",
+        user.user.as_str()
+      )
+    })?;
+    Ok(DinoparcSessionUser {
+      user: ShortDinoparcUser {
+        server,
+        id,
+        username: user.user,
+      },
+      coins: user.coins,
+      dinoz: user.dinoz,
+    })
+  };
+  let html = Html::parse_document(html);
+  let page = match param("a").as_deref() {
+    Some("user") => {
+      let id = match param("id") {
+        Some(id) => DinoparcUserId::from_str(&id)?,
+        None => return Ok(None),
+      };
+      DinoparcArchivedPage::Profile(scraper::scrape_user_profile(&html)?.into_profile(id))
+    }
+    Some("bank") => {
+      let bank = scraper::scrape_bank(&html)?;
+      DinoparcArchivedPage::Bank(ShortDinoparcUser {
+        server,
+        id: bank.user_id,
+        username: bank.context.auth.username,
+      })
+    }
+    Some("inventory") => {
+      let response = scraper::scrape_inventory(&html)?;
+      DinoparcArchivedPage::Inventory(DinoparcInventoryResponse {
+        session_user: session_user(response.session_user)?,
+        inventory: response.inventory,
+      })
+    }
+    Some("collection") => {
+      let response = scraper::scrape_collection(&html)?;
+      DinoparcArchivedPage::Collection(DinoparcCollectionResponse {
+        session_user: session_user(response.session_user)?,
+        collection: response.collection,
+      })
+    }
+    Some("dino") => {
+      let response = scraper::scrape_dinoz(&html)?;
+      DinoparcArchivedPage::Dinoz(DinoparcDinozResponse {
+        session_user: session_user(response.session_user)?,
+        dinoz: response.dinoz,
+      })
+    }
+    Some("bill") if param("uid").is_some() => {
+      let response = scraper::scrape_exchange_with(&html)?;
+      DinoparcArchivedPage::ExchangeWith(DinoparcExchangeWithResponse {
+        session_user: session_user(response.session_user)?,
+        own_bills: response.own_bills,
+        own_dinoz: response.own_dinoz,
+        other_user: response.other_user,
+        other_dinoz: response.other_dinoz,
+      })
+    }
+    _ => return Ok(None),
+  };
+  Ok(Some(page))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{reparse_page, DinoparcArchivedPage};
+  use etwin_core::dinoparc::{DinoparcServer, DinoparcUserId, DinoparcUsername};
+  use etwin_http::HttpOptions;
+  use std::str::FromStr;
+  use url::Url;
+
+  fn no_session_user(_: DinoparcServer, _: &DinoparcUsername) -> Option<DinoparcUserId> {
+    None
+  }
+
+  #[test]
+  fn reparse_profile() {
+    let html =
+      std::fs::read_to_string("../../test-resources/scraping/dinoparc/user/fr.user497785/main.utf8.html").unwrap();
+    let url = Url::parse("http://www.dinoparc.com/?a=user&id=497785").unwrap();
+    let profile = match reparse_page(&HttpOptions::default(), &url, &html, &no_session_user).unwrap() {
+      Some(DinoparcArchivedPage::Profile(profile)) => profile,
+      page => panic!("expected a profile page, got {:?}", page),
+    };
+    assert_eq!(profile.user.server, DinoparcServer::DinoparcCom);
+    assert_eq!(profile.user.id, DinoparcUserId::from_str("497785").unwrap());
+    assert_eq!(profile.user.username.as_str(), "Yukora1");
+  }
+
+  #[test]
+  fn reparse_session_pages() {
+    let dir = "../../test-resources/scraping/dinoparc";
+    let mirror = Url::parse("http://127.0.0.1:8080/dinoparc.com/").unwrap();
+    let options = HttpOptions {
+      base_urls: vec![(String::from("dinoparc.com"), mirror)].into_iter().collect(),
+      ..HttpOptions::default()
+    };
+
+    let html = std::fs::read_to_string(format!("{}/bank/fr.user2480723/main.utf8.html", dir)).unwrap();
+    let url = Url::parse("http://127.0.0.1:8080/dinoparc.com/?a=bank").unwrap();
+    let user = match reparse_page(&options, &url, &html, &no_session_user).unwrap() {
+      Some(DinoparcArchivedPage::Bank(user)) => user,
+      page => panic!("expected a bank page, got {:?}", page),
+    };
+    assert_eq!(user.server, DinoparcServer::DinoparcCom);
+    assert_eq!(user.id, DinoparcUserId::from_str("2480723").unwrap());
+
+    let html = std::fs::read_to_string(format!("{}/inventory/fr.user2480723/main.utf8.html", dir)).unwrap();
+    let url = Url::parse("http://127.0.0.1:8080/dinoparc.com/?a=inventory").unwrap();
+    assert!(reparse_page(&options, &url, &html, &no_session_user).is_err());
+    let session_user_id = |server: DinoparcServer, username: &DinoparcUsername| {
+      Some(user.id).filter(|_| server == user.server && *username == user.username)
+    };
+    let inventory = match reparse_page(&options, &url, &html, &session_user_id).unwrap() {
+      Some(DinoparcArchivedPage::Inventory(inventory)) => inventory,
+      page => panic!("expected an inventory page, got {:?}", page),
+    };
+    assert_eq!(inventory.session_user.user, user);
+  }
+}
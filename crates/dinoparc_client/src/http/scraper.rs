@@ -5,11 +5,11 @@ use etwin_core::core::IntPercentage;
 use etwin_core::dinoparc::{
   DinoparcCollection, DinoparcCollectionResponse, DinoparcDinoz, DinoparcDinozElements, DinoparcDinozId,
   DinoparcDinozName, DinoparcDinozRace, DinoparcDinozResponse, DinoparcEpicRewardKey, DinoparcExchangeWithResponse,
-  DinoparcInventoryResponse, DinoparcItemId, DinoparcRewardId, DinoparcServer, DinoparcSessionUser, DinoparcSkill,
-  DinoparcSkillLevel, DinoparcUserId, DinoparcUsername, NamedDinoparcDinozFields, ShortDinoparcDinozWithLevel,
-  ShortDinoparcDinozWithLocation, ShortDinoparcUser,
+  DinoparcInventoryResponse, DinoparcItemId, DinoparcProfile, DinoparcRewardId, DinoparcServer, DinoparcSessionUser,
+  DinoparcSkill, DinoparcSkillLevel, DinoparcUserId, DinoparcUsername, NamedDinoparcDinozFields, ShortDinoparcClan,
+  ShortDinoparcDinozWithLevel, ShortDinoparcDinozWithLocation, ShortDinoparcUser,
 };
-use etwin_scraper_tools::{ElementRefExt, FlashVars};
+use etwin_scraper_tools::{selector, ElementRefExt, FlashVars};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use percent_encoding::percent_decode_str;
@@ -70,9 +70,9 @@ pub(crate) fn scrape_bank(doc: &Html) -> Result<BankScraping, ScraperError> {
 
   let context = scrape_context(root)?;
 
-  let selector = Selector::parse("script[type=\"text/javascript\"]").unwrap();
+  let selector = selector!("script[type=\"text/javascript\"]");
   let mut ids = root
-    .select(&selector)
+    .select(selector)
     .filter_map(|e| match e.get_one_text() {
       Ok(t) => CASH_FRAME_RE.captures(t),
       Err(_) => None,
@@ -135,30 +135,29 @@ fn scrape_context(doc: ElementRef) -> Result<ContextScraping, ScraperError> {
     _ => return Err(ScraperError::NonUniqueHtml),
   };
 
-  let server = match html.value().attr("lang") {
-    Some("en") => DinoparcServer::EnDinoparcCom,
-    Some("es") => DinoparcServer::SpDinoparcCom,
-    Some("fr") => DinoparcServer::DinoparcCom,
-    _ => return Err(ScraperError::ServerDetectionFailure),
-  };
+  let server = scrape_server(html)?;
 
   let auth = scrape_sidebar(doc)?;
 
   Ok(ContextScraping { server, auth })
 }
 
+fn scrape_server(html: ElementRef) -> Result<DinoparcServer, ScraperError> {
+  match html.value().attr("lang") {
+    Some("en") => Ok(DinoparcServer::EnDinoparcCom),
+    Some("es") => Ok(DinoparcServer::SpDinoparcCom),
+    Some("fr") => Ok(DinoparcServer::DinoparcCom),
+    _ => Err(ScraperError::ServerDetectionFailure),
+  }
+}
+
 fn scrape_sidebar(doc: ElementRef) -> Result<SelfScraping, ScraperError> {
-  let menu = match doc
-    .select(&Selector::parse("td.leftPane>div.menu").unwrap())
-    .exactly_one()
-  {
+  let menu = match doc.select(selector!("td.leftPane>div.menu")).exactly_one() {
     Ok(menu) => menu,
     Err(_) => return Err(ScraperError::NonUniqueMenu),
   };
 
-  let titles = menu
-    .select(&Selector::parse(":scope > div.title").unwrap())
-    .collect_vec();
+  let titles = menu.select(selector!(":scope > div.title")).collect_vec();
 
   let username = match titles.first() {
     Some(e) => *e,
@@ -181,16 +180,16 @@ pub(crate) fn scrape_inventory(doc: &Html) -> Result<DinoparcInventoryResponse<D
   let session_user = scrape_session_user(context.server, root)?;
 
   let inventory_table = root
-    .select(&Selector::parse(".siteContent .contentPane .inventory table").unwrap())
+    .select(selector!(".siteContent .contentPane .inventory table"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueInventory)?;
 
   let inventory: Result<HashMap<DinoparcItemId, u32>, ScraperError> = inventory_table
-    .select(&Selector::parse(":scope tr").unwrap())
+    .select(selector!(":scope tr"))
     .skip(1) // Table header
     .map(|row| -> Result<(DinoparcItemId, u32), ScraperError> {
       let help_link = row
-        .select(&Selector::parse(":scope > td:nth-child(2) a.helpLink").unwrap())
+        .select(selector!(":scope > td:nth-child(2) a.helpLink"))
         .exactly_one()
         .map_err(|_| ScraperError::NonUniqueItemHelpLink)?;
 
@@ -212,7 +211,7 @@ pub(crate) fn scrape_inventory(doc: &Html) -> Result<DinoparcInventoryResponse<D
       let id = DinoparcItemId::from_str(id).map_err(|_| ScraperError::InvalidItemId(id.to_string()))?;
 
       let count = row
-        .select(&Selector::parse(":scope > td:nth-child(3) > strong").unwrap())
+        .select(selector!(":scope > td:nth-child(3) > strong"))
         .exactly_one()
         .map_err(|_| ScraperError::NonUniqueItemCount)?;
       let count = count.get_one_text().map_err(|_| ScraperError::NonUniqueItemCountText)?;
@@ -241,22 +240,15 @@ pub(crate) fn scrape_collection(doc: &Html) -> Result<DinoparcCollectionResponse
   let mut rewards: Option<HashSet<DinoparcRewardId>> = None;
   let mut epic_rewards: Option<HashSet<DinoparcEpicRewardKey>> = None;
 
-  for reward_box in root.select(&Selector::parse(".siteContent .contentPane div.rewardBox").unwrap()) {
-    if reward_box
-      .select(&Selector::parse(":scope > div.header").unwrap())
-      .next()
-      .is_some()
-    {
+  for reward_box in root.select(selector!(".siteContent .contentPane div.rewardBox")) {
+    if reward_box.select(selector!(":scope > div.header")).next().is_some() {
       // Regular
       if rewards.is_some() {
         return Err(ScraperError::DuplicateRegularRewardBox);
       }
       let mut new_rewards: HashSet<DinoparcRewardId> = HashSet::new();
-      for (i, cell) in reward_box
-        .select(&Selector::parse(":scope table.rewards td").unwrap())
-        .enumerate()
-      {
-        let is_unlocked = cell.select(&Selector::parse(":scope a").unwrap()).any(|_| true);
+      for (i, cell) in reward_box.select(selector!(":scope table.rewards td")).enumerate() {
+        let is_unlocked = cell.select(selector!(":scope a")).any(|_| true);
         if is_unlocked {
           let id = u64::try_from(i).unwrap() + 1;
           let id =
@@ -265,19 +257,15 @@ pub(crate) fn scrape_collection(doc: &Html) -> Result<DinoparcCollectionResponse
         }
       }
       rewards = Some(new_rewards);
-    } else if reward_box
-      .select(&Selector::parse(":scope > div.epicHeader").unwrap())
-      .next()
-      .is_some()
-    {
+    } else if reward_box.select(selector!(":scope > div.epicHeader")).next().is_some() {
       // Epic
       if epic_rewards.is_some() {
         return Err(ScraperError::DuplicateEpicRewardBox);
       }
       let mut new_epic_rewards: HashSet<DinoparcEpicRewardKey> = HashSet::new();
-      for cell in reward_box.select(&Selector::parse(":scope table.rewards td").unwrap()) {
+      for cell in reward_box.select(selector!(":scope table.rewards td")) {
         let reward = cell
-          .select(&Selector::parse(":scope img").unwrap())
+          .select(selector!(":scope img"))
           .at_most_one()
           .map_err(|_| ScraperError::MultipleEpicRewardImages)?;
         let reward = if let Some(r) = reward { r } else { continue };
@@ -316,13 +304,11 @@ pub(crate) fn scrape_exchange_with(doc: &Html) -> Result<DinoparcExchangeWithRes
   let session_user = scrape_session_user(context.server, root)?;
 
   let exchange_table = root
-    .select(&Selector::parse(".siteContent .contentPane form > table").unwrap())
+    .select(selector!(".siteContent .contentPane form > table"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueExchangeTable)?;
 
-  let rows: Vec<ElementRef> = exchange_table
-    .select(&Selector::parse(":scope > tbody > tr").unwrap())
-    .collect();
+  let rows: Vec<ElementRef> = exchange_table.select(selector!(":scope > tbody > tr")).collect();
 
   if rows.len() != 6 {
     return Err(ScraperError::UnexpectedExchangeTableLayout);
@@ -330,7 +316,7 @@ pub(crate) fn scrape_exchange_with(doc: &Html) -> Result<DinoparcExchangeWithRes
 
   let other_user: ShortDinoparcUser = {
     let other_user = rows[0]
-      .select(&Selector::parse(":scope > th > a").unwrap())
+      .select(selector!(":scope > th > a"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueExchangeTarget)?;
 
@@ -368,7 +354,7 @@ pub(crate) fn scrape_exchange_with(doc: &Html) -> Result<DinoparcExchangeWithRes
 
   let own_bills: u32 = {
     let max_bills = rows[1]
-      .select(&Selector::parse(":scope > td > span.bill").unwrap())
+      .select(selector!(":scope > td > span.bill"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueExchangeTarget)?;
 
@@ -399,12 +385,12 @@ fn scrape_exchange_dinoz_list(
   row: ElementRef,
 ) -> Result<Vec<ShortDinoparcDinozWithLevel>, ScraperError> {
   let dinoz = row
-    .select(&Selector::parse(":scope > td > select").unwrap())
+    .select(selector!(":scope > td > select"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueExchangeDinozList)?;
 
   dinoz
-    .select(&Selector::parse(":scope > option").unwrap())
+    .select(selector!(":scope > option"))
     .enumerate()
     .filter_map(|(i, dinoz)| if i == 0 { None } else { Some(dinoz) })
     .map(|dinoz| -> Result<ShortDinoparcDinozWithLevel, ScraperError> {
@@ -452,6 +438,191 @@ fn scrape_exchange_dinoz_list(
     .collect()
 }
 
+#[cfg_attr(test, derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct UserProfileScraping {
+  pub server: DinoparcServer,
+  pub username: DinoparcUsername,
+  pub rank: Option<u32>,
+  pub clan: Option<ShortDinoparcClan>,
+  pub dinoz: Vec<ShortDinoparcDinozWithLevel>,
+}
+
+impl UserProfileScraping {
+  /// Completes the scraped profile with the user id, which is only present in the page URL.
+  pub(crate) fn into_profile(self, id: DinoparcUserId) -> DinoparcProfile {
+    DinoparcProfile {
+      user: ShortDinoparcUser {
+        server: self.server,
+        id,
+        username: self.username,
+      },
+      rank: self.rank,
+      clan: self.clan,
+      dinoz: self.dinoz,
+    }
+  }
+}
+
+/// Scrape the public profile page of a user (`?a=user;id=...`)
+///
+/// This page is available to guests: the sidebar is not read.
+pub(crate) fn scrape_user_profile(doc: &Html) -> Result<UserProfileScraping, ScraperError> {
+  let root = doc.root_element();
+  if root.value().name() != "html" {
+    return Err(ScraperError::NonUniqueHtml);
+  }
+  let server = scrape_server(root)?;
+  let locale = ScraperLocale::get(server);
+
+  let profile = root
+    .select(selector!(".siteContent .contentPane div.user"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueUserProfile)?;
+
+  let username = {
+    let username = profile
+      .select(selector!(":scope > h1"))
+      .exactly_one()
+      .map_err(|_| ScraperError::NonUniqueUserProfileName)?;
+    let username = username
+      .get_one_text()
+      .map_err(|_| ScraperError::NonUniqueUsernameText)?
+      .trim();
+    DinoparcUsername::from_str(username).map_err(|e| ScraperError::InvalidUsername(username.to_string(), e))?
+  };
+
+  let rank: Option<u32> = {
+    let rank = profile
+      .select(selector!(":scope table.profile tr"))
+      .filter(|row| {
+        row
+          .select(selector!(":scope > th"))
+          .any(|th| th.text().collect::<String>().trim() == locale.rank_label)
+      })
+      .at_most_one()
+      .map_err(|_| ScraperError::NonUniqueUserRank)?;
+    match rank {
+      Some(row) => {
+        let text: String = row.select(selector!(":scope > td")).flat_map(|td| td.text()).collect();
+        let rank = DECIMAL_RE
+          .find(&text)
+          .ok_or_else(|| ScraperError::InvalidUserRank(text.clone()))?
+          .as_str();
+        Some(
+          rank
+            .parse()
+            .map_err(|_| ScraperError::InvalidUserRank(rank.to_string()))?,
+        )
+      }
+      None => None,
+    }
+  };
+
+  let clan: Option<ShortDinoparcClan> = {
+    let link = profile
+      .select(selector!(r#":scope table.profile a[href*="a=clan_show"]"#))
+      .at_most_one()
+      .map_err(|_| ScraperError::NonUniqueClanLink)?;
+    match link {
+      Some(link) => {
+        let id = get_link_param(server, link, "id")?.ok_or(ScraperError::NonUniqueClanIdInLink)?;
+        let id = id.parse().map_err(|_| ScraperError::InvalidClanId(id.to_string()))?;
+        let name = link.text().collect::<String>();
+        let name = name.trim();
+        let name = name
+          .parse()
+          .map_err(|_| ScraperError::InvalidClanName(name.to_string()))?;
+        Some(ShortDinoparcClan { server, id, name })
+      }
+      None => None,
+    }
+  };
+
+  let dinoz_list = profile
+    .select(selector!(":scope table.dinozList"))
+    .at_most_one()
+    .map_err(|_| ScraperError::NonUniqueProfileDinozList)?;
+  let dinoz: Vec<ShortDinoparcDinozWithLevel> = match dinoz_list {
+    Some(list) => list
+      .select(selector!(":scope tr"))
+      .skip(1) // Table header
+      .map(|row| scrape_profile_dinoz(server, row))
+      .collect::<Result<_, _>>()?,
+    None => Vec::new(),
+  };
+
+  Ok(UserProfileScraping {
+    server,
+    username,
+    rank,
+    clan,
+    dinoz,
+  })
+}
+
+fn scrape_profile_dinoz(server: DinoparcServer, row: ElementRef) -> Result<ShortDinoparcDinozWithLevel, ScraperError> {
+  let link = row
+    .select(selector!(":scope > td:nth-child(1) > a"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueProfileDinozLink)?;
+  let id = get_link_param(server, link, "id")?.ok_or(ScraperError::NonUniqueDinozIdInLink)?;
+  let id: DinoparcDinozId = id.parse().map_err(|_| ScraperError::InvalidDinozId(id.to_string()))?;
+
+  let name = link
+    .get_one_text()
+    .map_err(|_| ScraperError::NonUniqueDinozNameText)?
+    .trim();
+  let name: Option<DinoparcDinozName> = if name == "null" {
+    None
+  } else {
+    Some(
+      name
+        .parse()
+        .map_err(|_| ScraperError::InvalidDinozName(name.to_string()))?,
+    )
+  };
+
+  let level = row
+    .select(selector!(":scope > td:nth-child(2)"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozLevel)?;
+  let level = level.text().collect::<String>();
+  let level = level.trim();
+  let level: u16 = level
+    .parse()
+    .map_err(|_| ScraperError::InvalidDinozLevel(level.to_string()))?;
+
+  Ok(ShortDinoparcDinozWithLevel {
+    server,
+    id,
+    name,
+    level,
+  })
+}
+
+/// Read a parameter from the Dinoparc request (`r` query parameter) of a link
+///
+/// Returns `None` if the parameter is missing or duplicated.
+fn get_link_param(server: DinoparcServer, link: ElementRef, key: &str) -> Result<Option<String>, ScraperError> {
+  let href = link.value().attr("href").ok_or(ScraperError::MissingLinkHref)?;
+  let href = DinoparcUrls::new(server)
+    .parse_from_root(href)
+    .map_err(|_| ScraperError::InvalidLinkHref(href.to_string()))?;
+  let req = href
+    .query_pairs()
+    .filter_map(|(k, v)| if k.as_ref() == "r" { Some(v) } else { None })
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinoparcRequest)?;
+  let req = DinoparcRequest::new(req.as_ref());
+  let value = req
+    .pairs()
+    .filter_map(|(k, v)| if k == key { Some(v) } else { None })
+    .exactly_one()
+    .ok();
+  Ok(value.map(str::to_string))
+}
+
 pub(crate) fn scrape_dinoz(doc: &Html) -> Result<DinoparcDinozResponse<DinoparcUsername>, ScraperError> {
   let root = doc.root_element();
 
@@ -459,19 +630,19 @@ pub(crate) fn scrape_dinoz(doc: &Html) -> Result<DinoparcDinozResponse<DinoparcU
   let session_user = scrape_session_user(context.server, root)?;
 
   let content_pane = root
-    .select(&Selector::parse(".siteContent .contentPane").unwrap())
+    .select(selector!(".siteContent .contentPane"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueContentPane)?;
 
   let dinoz_view = content_pane
-    .select(&Selector::parse(":scope > table.dinoView").unwrap())
+    .select(selector!(":scope > table.dinoView"))
     .at_most_one()
     .map_err(|_| ScraperError::NonUniqueDinozView)?;
 
   let dinoz = if let Some(dinoz_view) = dinoz_view {
     let name = {
       let name = content_pane
-        .select(&Selector::parse(":scope > h1").unwrap())
+        .select(selector!(":scope > h1"))
         .exactly_one()
         .map_err(|_| ScraperError::NonUniqueDinozName)?;
       let name = name.text().next().ok_or(ScraperError::NonUniqueDinozNameText)?;
@@ -484,7 +655,7 @@ pub(crate) fn scrape_dinoz(doc: &Html) -> Result<DinoparcDinozResponse<DinoparcU
     scrape_named_dinoz(context.server, name, dinoz_view)?
   } else {
     let dinoz_sheet = content_pane
-      .select(&Selector::parse(":scope > table.dinoSheet").unwrap())
+      .select(selector!(":scope > table.dinoSheet"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozView)?;
     scrape_unnamed_dinoz(context.server, dinoz_sheet)?
@@ -499,13 +670,13 @@ fn scrape_named_dinoz(
   dinoz_view: ElementRef,
 ) -> Result<DinoparcDinoz, ScraperError> {
   let dinoz_pane = dinoz_view
-    .select(&Selector::parse(":scope div.dino").unwrap())
+    .select(selector!(":scope div.dino"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueDinozPane)?;
 
   let skin = {
     let skin = dinoz_pane
-      .select(&Selector::parse(":scope > div.pic.center object > param[name=FlashVars]").unwrap())
+      .select(selector!(":scope > div.pic.center object > param[name=FlashVars]"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozSkinFlashVars)?;
     let skin = skin.value().attr("value").ok_or(ScraperError::MissingFlashVarsValue)?;
@@ -518,13 +689,13 @@ fn scrape_named_dinoz(
   };
 
   let def = dinoz_pane
-    .select(&Selector::parse(":scope > table.def").unwrap())
+    .select(selector!(":scope > table.def"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueDinozDefTable)?;
 
   let life = {
     let life = def
-      .select(&Selector::parse(":scope tr:nth-child(2) td div.value").unwrap())
+      .select(selector!(":scope tr:nth-child(2) td div.value"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozLifeValue)?;
     let life = life
@@ -534,7 +705,7 @@ fn scrape_named_dinoz(
   };
   let level = {
     let level = def
-      .select(&Selector::parse(":scope tr:nth-child(3) td").unwrap())
+      .select(selector!(":scope tr:nth-child(3) td"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozLevel)?;
 
@@ -549,20 +720,17 @@ fn scrape_named_dinoz(
   };
   let experience = {
     let experience = def
-      .select(&Selector::parse(":scope tr:nth-child(4) td").unwrap())
+      .select(selector!(":scope tr:nth-child(4) td"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozExperience)?;
 
-    let can_level_up = experience
-      .select(&Selector::parse(":scope > a").unwrap())
-      .next()
-      .is_some();
+    let can_level_up = experience.select(selector!(":scope > a")).next().is_some();
 
     if can_level_up {
       IntPercentage::new(100).unwrap()
     } else {
       let experience = experience
-        .select(&Selector::parse(":scope div.value").unwrap())
+        .select(selector!(":scope div.value"))
         .exactly_one()
         .map_err(|_| ScraperError::NonUniqueDinozExperienceValue)?;
       let experience = experience
@@ -573,7 +741,7 @@ fn scrape_named_dinoz(
   };
   let danger = {
     let danger = def
-      .select(&Selector::parse(":scope tr:nth-child(5) td").unwrap())
+      .select(selector!(":scope tr:nth-child(5) td"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozDanger)?;
     let danger = danger.text().next().ok_or(ScraperError::MissingDinozDangerText)?;
@@ -586,12 +754,12 @@ fn scrape_named_dinoz(
 
   let elements: DinoparcDinozElements = {
     let elements = dinoz_pane
-      .select(&Selector::parse(":scope > ul.elements").unwrap())
+      .select(selector!(":scope > ul.elements"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozElementList)?;
 
     let elements: Result<Vec<u16>, ScraperError> = elements
-      .select(&Selector::parse(":scope > li > div").unwrap())
+      .select(selector!(":scope > li > div"))
       .map(|element| -> Result<u16, ScraperError> {
         let element = element
           .get_one_text()
@@ -624,15 +792,15 @@ fn scrape_named_dinoz(
 
   let skills: HashMap<DinoparcSkill, DinoparcSkillLevel> = {
     let skills = dinoz_pane
-      .select(&Selector::parse(":scope > ul.skills").unwrap())
+      .select(selector!(":scope > ul.skills"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozSkillList)?;
 
     let skills: Result<HashMap<DinoparcSkill, DinoparcSkillLevel>, ScraperError> = skills
-      .select(&Selector::parse(":scope > li").unwrap())
+      .select(selector!(":scope > li"))
       .map(|skill| -> Result<(DinoparcSkill, DinoparcSkillLevel), ScraperError> {
         let name = skill
-          .select(&Selector::parse(":scope > div.name").unwrap())
+          .select(selector!(":scope > div.name"))
           .exactly_one()
           .map_err(|_| ScraperError::NonUniqueDinozSkillName)?;
         let name = name
@@ -645,7 +813,7 @@ fn scrape_named_dinoz(
           .ok_or_else(|| ScraperError::InvalidSkillName(name.to_string()))?;
 
         let level = skill
-          .select(&Selector::parse(":scope > div.level > img").unwrap())
+          .select(selector!(":scope > div.level > img"))
           .exactly_one()
           .map_err(|_| ScraperError::NonUniqueDinozSkillLevel)?;
 
@@ -667,28 +835,26 @@ fn scrape_named_dinoz(
   };
 
   let actions = dinoz_view
-    .select(&Selector::parse(":scope div.actions").unwrap())
+    .select(selector!(":scope div.actions"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueActionsPane)?;
 
   let in_tournament = {
-    actions
-      .select(&Selector::parse(":scope > div.important").unwrap())
-      .any(|important| {
-        important
-          .text()
-          .any(|t| ScraperLocale::get(server).in_tournament_pattern.is_match(t))
-      })
+    actions.select(selector!(":scope > div.important")).any(|important| {
+      important
+        .text()
+        .any(|t| ScraperLocale::get(server).in_tournament_pattern.is_match(t))
+    })
   };
 
   let place = dinoz_view
-    .select(&Selector::parse(":scope div.place").unwrap())
+    .select(selector!(":scope div.place"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniquePlacePane)?;
 
   let location = {
     let location = place
-      .select(&Selector::parse(":scope > div.title").unwrap())
+      .select(selector!(":scope > div.title"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueLocationName)?;
     let location = location
@@ -703,7 +869,7 @@ fn scrape_named_dinoz(
 
   let id = {
     let place_link = place
-      .select(&Selector::parse(":scope > div.link > a").unwrap())
+      .select(selector!(":scope > div.link > a"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniquePlaceLink)?;
     let place_link = place_link.value().attr("href").ok_or(ScraperError::MissingLinkHref)?;
@@ -748,7 +914,7 @@ fn scrape_named_dinoz(
 fn scrape_unnamed_dinoz(server: DinoparcServer, dinoz_sheet: ElementRef) -> Result<DinoparcDinoz, ScraperError> {
   let skin = {
     let skin = dinoz_sheet
-      .select(&Selector::parse(":scope td.picBox object > param[name=FlashVars]").unwrap())
+      .select(selector!(":scope td.picBox object > param[name=FlashVars]"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozSkinFlashVars)?;
     let skin = skin.value().attr("value").ok_or(ScraperError::MissingFlashVarsValue)?;
@@ -761,7 +927,7 @@ fn scrape_unnamed_dinoz(server: DinoparcServer, dinoz_sheet: ElementRef) -> Resu
   };
 
   let form = dinoz_sheet
-    .select(&Selector::parse(":scope form").unwrap())
+    .select(selector!(":scope form"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueDinozNameForm)?;
 
@@ -785,13 +951,13 @@ fn scrape_unnamed_dinoz(server: DinoparcServer, dinoz_sheet: ElementRef) -> Resu
   };
 
   let def = form
-    .select(&Selector::parse(":scope > table.def").unwrap())
+    .select(selector!(":scope > table.def"))
     .exactly_one()
     .map_err(|_| ScraperError::NonUniqueDinozDefTable)?;
 
   let level = {
     let level = def
-      .select(&Selector::parse(":scope tr:nth-child(1) td").unwrap())
+      .select(selector!(":scope tr:nth-child(1) td"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozLevel)?;
 
@@ -836,17 +1002,12 @@ fn scrape_session_user(
   server: DinoparcServer,
   doc: ElementRef,
 ) -> Result<DinoparcSessionUser<DinoparcUsername>, ScraperError> {
-  let menu = match doc
-    .select(&Selector::parse("td.leftPane>div.menu").unwrap())
-    .exactly_one()
-  {
+  let menu = match doc.select(selector!("td.leftPane>div.menu")).exactly_one() {
     Ok(menu) => menu,
     Err(_) => return Err(ScraperError::NonUniqueMenu),
   };
 
-  let titles = menu
-    .select(&Selector::parse(":scope > div.title").unwrap())
-    .collect_vec();
+  let titles = menu.select(selector!(":scope > div.title")).collect_vec();
 
   let username = match titles.first() {
     Some(e) => *e,
@@ -861,7 +1022,7 @@ fn scrape_session_user(
 
   let coins = {
     let coins = menu
-      .select(&Selector::parse(":scope span.money").unwrap())
+      .select(selector!(":scope span.money"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueCoinSpan)?;
     let coin_text = coins.get_one_text().map_err(|_| ScraperError::NonUniqueCoinText)?;
@@ -874,17 +1035,17 @@ fn scrape_session_user(
 
   let dinoz = {
     let block = menu
-      .select(&Selector::parse(":scope > #dinozListBlock").unwrap())
+      .select(selector!(":scope > #dinozListBlock"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozListBlock)?;
 
     let dinoz_list = block
-      .select(&Selector::parse(":scope > ul.dinoList").unwrap())
+      .select(selector!(":scope > ul.dinoList"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozList)?;
 
     let dinoz: Result<Vec<ShortDinoparcDinozWithLocation>, ScraperError> = dinoz_list
-      .select(&Selector::parse(":scope > li").unwrap())
+      .select(selector!(":scope > li"))
       .map(|e| scrape_sidebar_dinoz(server, e))
       .collect();
     dinoz?
@@ -903,7 +1064,7 @@ fn scrape_sidebar_dinoz(
 ) -> Result<ShortDinoparcDinozWithLocation, ScraperError> {
   let id = {
     let link: ElementRef = dinoz
-      .select(&Selector::parse(":scope > a").unwrap())
+      .select(selector!(":scope > a"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueDinozLink)?;
     let link = link.value().attr("href").ok_or(ScraperError::MissingLinkHref)?;
@@ -925,18 +1086,14 @@ fn scrape_sidebar_dinoz(
   };
   let name = {
     let name = dinoz
-      .select(&Selector::parse(":scope p.name").unwrap())
+      .select(selector!(":scope p.name"))
       .at_most_one()
       .map_err(|_| ScraperError::NonUniqueDinozName)?;
     if let Some(name) = name {
       let name = name.get_one_text().map_err(|_| ScraperError::NonUniqueDinozNameText)?;
       // No trimming: already trimmed
       Some(DinoparcDinozName::from_str(name).map_err(|_| ScraperError::InvalidDinozName(name.to_string()))?)
-    } else if dinoz
-      .select(&Selector::parse(":scope p.notify").unwrap())
-      .next()
-      .is_some()
-    {
+    } else if dinoz.select(selector!(":scope p.notify")).next().is_some() {
       // Unnamed dinoz, the `p.notify` is the "Pick a name" prompt
       None
     } else {
@@ -946,7 +1103,7 @@ fn scrape_sidebar_dinoz(
   };
   let location = if name.is_some() {
     let location = dinoz
-      .select(&Selector::parse(":scope p.placeName").unwrap())
+      .select(selector!(":scope p.placeName"))
       .exactly_one()
       .map_err(|_| ScraperError::NonUniqueLocationName)?;
     let location = location
@@ -973,7 +1130,8 @@ fn scrape_sidebar_dinoz(
 #[cfg(test)]
 mod test {
   use crate::http::scraper::{
-    scrape_bank, scrape_collection, scrape_dinoz, scrape_exchange_with, scrape_inventory, BankScraping,
+    scrape_bank, scrape_collection, scrape_dinoz, scrape_exchange_with, scrape_inventory, scrape_user_profile,
+    BankScraping, UserProfileScraping,
   };
   use etwin_core::dinoparc::{
     DinoparcCollectionResponse, DinoparcDinozResponse, DinoparcExchangeWithResponse, DinoparcInventoryResponse,
@@ -1091,4 +1249,25 @@ mod test {
 
     assert_eq!(actual, expected);
   }
+
+  #[test_resources("./test-resources/scraping/dinoparc/user/*/")]
+  fn test_scrape_user_profile(path: &str) {
+    let path: PathBuf = Path::join(Path::new("../.."), path);
+    let value_path = path.join("value.json");
+    let html_path = path.join("main.utf8.html");
+    let actual_path = path.join("rs.actual.json");
+
+    let raw_html = ::std::fs::read_to_string(html_path).expect("Failed to read html file");
+
+    let html = Html::parse_document(&raw_html);
+
+    let actual = scrape_user_profile(&html).unwrap();
+    let actual_json = serde_json::to_string_pretty(&actual).unwrap();
+    ::std::fs::write(actual_path, format!("{}\n", actual_json)).expect("Failed to write actual file");
+
+    let value_json = ::std::fs::read_to_string(value_path).expect("Failed to read value file");
+    let expected = serde_json::from_str::<UserProfileScraping>(&value_json).expect("Failed to parse value file");
+
+    assert_eq!(actual, expected);
+  }
 }
@@ -0,0 +1,102 @@
+//! Self-check of the scrapers against recorded pages and live canaries, see [etwin_scraper_tools::drift].
+
+use crate::http::scraper;
+use crate::http::url::DinoparcUrls;
+use ::scraper::Html;
+use etwin_core::dinoparc::{DinoparcServer, DinoparcUserId};
+use etwin_core::types::EtwinError;
+use etwin_scraper_tools::drift::{check_page, PageCheck, PageSource};
+use reqwest::Url;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Pages with a scraper, named after their directory in the scraping test resources.
+const PAGES: [&str; 6] = ["bank", "collection", "dinoz", "exchange-with", "inventory", "user"];
+
+pub(crate) fn page_kind(dir: &str) -> &'static str {
+  match dir {
+    "bank" => "dinoparc/bank",
+    "collection" => "dinoparc/collection",
+    "dinoz" => "dinoparc/dinoz",
+    "exchange-with" => "dinoparc/exchange-with",
+    "inventory" => "dinoparc/inventory",
+    "user" => "dinoparc/user",
+    _ => unreachable!("unknown Dinoparc page {:?}", dir),
+  }
+}
+
+pub(crate) fn check_html(dir: &str, page: String, source: PageSource, html: &Html) -> PageCheck {
+  let kind = page_kind(dir);
+  match dir {
+    "bank" => check_page(kind, page, source, html, scraper::scrape_bank),
+    "collection" => check_page(kind, page, source, html, scraper::scrape_collection),
+    "dinoz" => check_page(kind, page, source, html, scraper::scrape_dinoz),
+    "exchange-with" => check_page(kind, page, source, html, scraper::scrape_exchange_with),
+    "inventory" => check_page(kind, page, source, html, scraper::scrape_inventory),
+    _ => check_page(kind, page, source, html, scraper::scrape_user_profile),
+  }
+}
+
+/// Public pages fetched as a guest to check the live markup: profiles with a recorded page.
+///
+/// `urls` resolves the server hosts.
+pub(crate) fn canaries(urls: impl Fn(DinoparcServer) -> DinoparcUrls) -> Vec<(Url, &'static str)> {
+  [
+    (DinoparcServer::DinoparcCom, "497785"),
+    (DinoparcServer::EnDinoparcCom, "681579"),
+  ]
+  .iter()
+  .map(|(server, user_id)| {
+    let user_id = DinoparcUserId::from_str(user_id).unwrap();
+    (urls(*server).user(user_id), "user")
+  })
+  .collect()
+}
+
+/// Runs the scrapers over the recorded pages of the scraping test resources (`test-resources/scraping/dinoparc`).
+///
+/// Each page is a directory containing `main.utf8.html`.
+pub fn check_catalog(root: &Path) -> Result<Vec<PageCheck>, EtwinError> {
+  let mut checks = Vec::new();
+  for dir in PAGES.iter() {
+    let mut pages: Vec<_> = fs::read_dir(root.join(dir))?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<_, _>>()?;
+    pages.sort();
+    for page in pages {
+      let input = page.join("main.utf8.html");
+      if !input.is_file() {
+        continue;
+      }
+      let html = Html::parse_document(&fs::read_to_string(&input)?);
+      checks.push(check_html(dir, page.display().to_string(), PageSource::Recorded, &html));
+    }
+  }
+  Ok(checks)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::http::selfcheck::check_catalog;
+  use etwin_scraper_tools::drift::{DriftReport, PageCheck, PageSource};
+  use std::path::Path;
+
+  #[test]
+  fn check_recorded_pages() {
+    let mut checks = check_catalog(Path::new("../../test-resources/scraping/dinoparc")).unwrap();
+    assert!(checks.iter().any(|check| check.kind == "dinoparc/dinoz"));
+    // The recorded pages are supported: using them as canaries must not report anything
+    let canaries: Vec<PageCheck> = checks
+      .iter()
+      .cloned()
+      .map(|check| PageCheck {
+        source: PageSource::Canary,
+        ..check
+      })
+      .collect();
+    checks.extend(canaries);
+    let report = DriftReport::new(checks);
+    assert!(report.is_ok(), "{}", report);
+  }
+}
@@ -6,15 +6,19 @@ pub struct DinoparcUrls {
 }
 
 impl DinoparcUrls {
+  /// URLs of the official host of `server`.
   pub fn new(server: DinoparcServer) -> Self {
     let root = match server {
       DinoparcServer::DinoparcCom => "http://www.dinoparc.com/",
       DinoparcServer::EnDinoparcCom => "http://en.dinoparc.com/",
       DinoparcServer::SpDinoparcCom => "http://sp.dinoparc.com/",
     };
-    Self {
-      root: Url::parse(root).expect("failed to parse dinoparc root URL"),
-    }
+    Self::with_root(Url::parse(root).expect("failed to parse dinoparc root URL"))
+  }
+
+  /// URLs relative to `root`, for mirrors or local test servers.
+  pub fn with_root(root: Url) -> Self {
+    Self { root }
   }
 
   fn make_url(&self, action: &'static str, params: &[(&str, &str)]) -> Url {
@@ -30,9 +34,9 @@ impl DinoparcUrls {
     url
   }
 
-  // pub fn root(&self) -> Url {
-  //   self.root.clone()
-  // }
+  pub fn root(&self) -> Url {
+    self.root.clone()
+  }
 
   pub fn bank(&self) -> Url {
     self.make_url("bank", &[])
@@ -58,6 +62,10 @@ impl DinoparcUrls {
     user_id.with_str(|user| self.make_url("bill", &[("uid", user)]))
   }
 
+  pub fn user(&self, user_id: DinoparcUserId) -> Url {
+    user_id.with_str(|user| self.make_url("user", &[("id", user)]))
+  }
+
   pub fn dinoz(&self, dinoz_id: DinoparcDinozId) -> Url {
     dinoz_id.with_str(|dinoz| self.make_url("dino", &[("id", dinoz)]))
   }
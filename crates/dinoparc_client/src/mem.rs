@@ -2,13 +2,14 @@ use async_trait::async_trait;
 use etwin_core::clock::Clock;
 use etwin_core::core::Instant;
 use etwin_core::dinoparc::{
-  DinoparcClient, DinoparcCollectionResponse, DinoparcCredentials, DinoparcDinozId, DinoparcDinozResponse,
-  DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcPassword, DinoparcServer, DinoparcSession,
-  DinoparcSessionKey, DinoparcUserId, DinoparcUsername, ShortDinoparcUser,
+  DinoparcClient, DinoparcCollection, DinoparcCollectionResponse, DinoparcCredentials, DinoparcDinozId,
+  DinoparcDinozResponse, DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcPassword, DinoparcProfile,
+  DinoparcServer, DinoparcSession, DinoparcSessionKey, DinoparcSessionUser, DinoparcUserId, DinoparcUsername,
+  ShortDinoparcUser,
 };
 use etwin_core::types::EtwinError;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::RwLock;
 use thiserror::Error;
@@ -21,6 +22,10 @@ pub enum Error {
   ServerNotFound(DinoparcServer),
   #[error("Invalid session")]
   InvalidSession,
+  #[error("User not found: {:?}", .0)]
+  UserNotFound(DinoparcUserId),
+  #[error("Dinoz not found: {:?}", .0)]
+  DinozNotFound(DinoparcDinozId),
 }
 
 #[derive(Clone, Debug)]
@@ -84,6 +89,20 @@ impl MemServer {
     self.users.get(id)
   }
 
+  fn get_session_user(&self, server: DinoparcServer, key: &DinoparcSessionKey) -> Result<DinoparcSessionUser, Error> {
+    let session = self.get_session_by_key(key).ok_or(Error::InvalidSession)?;
+    let user = self.get_user_by_id(&session.user_id).unwrap();
+    Ok(DinoparcSessionUser {
+      user: ShortDinoparcUser {
+        server,
+        id: user.id,
+        username: user.username.clone(),
+      },
+      coins: 0,
+      dinoz: Vec::new(),
+    })
+  }
+
   fn create_session(
     &mut self,
     time: Instant,
@@ -143,6 +162,17 @@ impl<TyClock> MemDinoparcClient<TyClock> {
     }
   }
 
+  fn get_session_user(&self, session: &DinoparcSession) -> Result<DinoparcSessionUser, Error> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for dinoparc client state");
+    let srv = state
+      .get(&session.user.server)
+      .ok_or(Error::ServerNotFound(session.user.server))?;
+    srv.get_session_user(session.user.server, &session.key)
+  }
+
   pub fn create_user(
     &self,
     server: DinoparcServer,
@@ -164,7 +194,7 @@ where
   TyClock: Clock,
 {
   async fn get_preferred_exchange_with(&self, _server: DinoparcServer) -> [DinoparcUserId; 2] {
-    todo!()
+    ["1".parse().unwrap(), "2".parse().unwrap()]
   }
 
   async fn create_session(&self, options: &DinoparcCredentials) -> Result<DinoparcSession, EtwinError> {
@@ -225,38 +255,90 @@ where
 
   async fn get_dinoz(
     &self,
-    _session: &DinoparcSession,
-    _id: DinoparcDinozId,
+    session: &DinoparcSession,
+    id: DinoparcDinozId,
   ) -> Result<DinoparcDinozResponse, EtwinError> {
-    todo!()
+    // Mem users do not own any dinoz
+    self.get_session_user(session)?;
+    Err(Box::new(Error::DinozNotFound(id)))
   }
 
   async fn get_exchange_with(
     &self,
-    _session: &DinoparcSession,
-    _other_user: DinoparcUserId,
+    session: &DinoparcSession,
+    other_user: DinoparcUserId,
   ) -> Result<DinoparcExchangeWithResponse, EtwinError> {
-    todo!()
+    let session_user = self.get_session_user(session)?;
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for dinoparc client state");
+    let srv = state.get(&session.user.server).unwrap();
+    let other = srv.get_user_by_id(&other_user).ok_or(Error::UserNotFound(other_user))?;
+    Ok(DinoparcExchangeWithResponse {
+      session_user,
+      own_bills: 0,
+      own_dinoz: Vec::new(),
+      other_user: ShortDinoparcUser {
+        server: session.user.server,
+        id: other.id,
+        username: other.username.clone(),
+      },
+      other_dinoz: Vec::new(),
+    })
+  }
+
+  async fn get_inventory(&self, session: &DinoparcSession) -> Result<DinoparcInventoryResponse, EtwinError> {
+    let session_user = self.get_session_user(session)?;
+    Ok(DinoparcInventoryResponse {
+      session_user,
+      inventory: HashMap::new(),
+    })
   }
 
-  async fn get_inventory(&self, _session: &DinoparcSession) -> Result<DinoparcInventoryResponse, EtwinError> {
-    todo!()
+  async fn get_collection(&self, session: &DinoparcSession) -> Result<DinoparcCollectionResponse, EtwinError> {
+    let session_user = self.get_session_user(session)?;
+    Ok(DinoparcCollectionResponse {
+      session_user,
+      collection: DinoparcCollection {
+        rewards: HashSet::new(),
+        epic_rewards: HashSet::new(),
+      },
+    })
   }
 
-  async fn get_collection(&self, _session: &DinoparcSession) -> Result<DinoparcCollectionResponse, EtwinError> {
-    todo!()
+  async fn get_user_profile(
+    &self,
+    server: DinoparcServer,
+    id: DinoparcUserId,
+  ) -> Result<Option<DinoparcProfile>, EtwinError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for dinoparc client state");
+    let srv = state.get(&server).ok_or(Error::ServerNotFound(server))?;
+    Ok(srv.get_user_by_id(&id).map(|user| DinoparcProfile {
+      user: ShortDinoparcUser {
+        server,
+        id: user.id,
+        username: user.username.clone(),
+      },
+      rank: None,
+      clan: None,
+      dinoz: Vec::new(),
+    }))
   }
 }
 
 fn make_session_key() -> DinoparcSessionKey {
   use rand::seq::SliceRandom;
 
-  const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+  const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
   let mut rng = rand::thread_rng();
 
   let key: String = std::iter::from_fn(|| CHARS.choose(&mut rng).copied())
     .map(char::from)
-    .take(26)
+    .take(32)
     .collect();
 
   DinoparcSessionKey::from_str(&key).expect("invalid session key")
@@ -4,12 +4,13 @@ use etwin_core::clock::Clock;
 use etwin_core::core::{Instant, IntPercentage};
 use etwin_core::dinoparc::{
   ArchivedDinoparcDinoz, ArchivedDinoparcUser, DinoparcCollection, DinoparcCollectionResponse, DinoparcDinozElements,
-  DinoparcDinozId, DinoparcDinozIdRef, DinoparcDinozName, DinoparcDinozRace, DinoparcDinozResponse, DinoparcDinozSkin,
-  DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcItemId, DinoparcLocationId, DinoparcServer,
-  DinoparcSessionUser, DinoparcSkill, DinoparcSkillLevel, DinoparcStore, DinoparcUserId, DinoparcUserIdRef,
-  DinoparcUsername, GetDinoparcDinozOptions, GetDinoparcUserOptions, ShortDinoparcDinozWithLevel, ShortDinoparcUser,
+  DinoparcDinozHistory, DinoparcDinozId, DinoparcDinozIdRef, DinoparcDinozName, DinoparcDinozRace,
+  DinoparcDinozResponse, DinoparcDinozSkin, DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcItemId,
+  DinoparcLocationId, DinoparcServer, DinoparcSessionUser, DinoparcSkill, DinoparcSkillLevel, DinoparcStore,
+  DinoparcUserId, DinoparcUserIdRef, DinoparcUsername, GetDinoparcDinozHistoryOptions, GetDinoparcDinozOptions,
+  GetDinoparcUserOptions, ShortDinoparcDinozWithLevel, ShortDinoparcUser,
 };
-use etwin_core::temporal::{CheckedSnapshotLog, LatestTemporal, SnapshotLog};
+use etwin_core::temporal::{CheckedSnapshotLog, ForeignSnapshot, LatestTemporal, SnapshotLog};
 use etwin_core::types::EtwinError;
 use std::collections::HashMap;
 use std::sync::RwLock;
@@ -178,6 +179,47 @@ impl StoreState {
     }
   }
 
+  fn to_dinoz_history(&self, dinoz: &StoreDinoz) -> DinoparcDinozHistory {
+    fn cloned<T: Clone + Eq>(log: &SnapshotLog<T>) -> Vec<ForeignSnapshot<T>> {
+      log.history().iter().map(ForeignSnapshot::cloned).collect()
+    }
+
+    DinoparcDinozHistory {
+      server: dinoz.server,
+      id: dinoz.id,
+      archived_at: dinoz.archived_at,
+      name: cloned(&dinoz.name),
+      owner: dinoz
+        .owner
+        .history()
+        .into_iter()
+        .map(|snapshot| {
+          snapshot.map(|owner| {
+            let owner = self
+              .users
+              .get(&owner.and_server(dinoz.server))
+              .expect("OwnerMustBeStored");
+            ShortDinoparcUser {
+              server: owner.server,
+              id: owner.id,
+              username: owner.username.clone(),
+            }
+          })
+        })
+        .collect(),
+      location: cloned(&dinoz.location),
+      race: cloned(&dinoz.race),
+      skin: cloned(&dinoz.skin),
+      life: cloned(&dinoz.life),
+      level: cloned(&dinoz.level),
+      experience: cloned(&dinoz.experience),
+      danger: cloned(&dinoz.danger),
+      in_tournament: cloned(&dinoz.in_tournament),
+      elements: cloned(&dinoz.elements),
+      skills: cloned(&dinoz.skills),
+    }
+  }
+
   fn touch_user(&mut self, time: Instant, user: ShortDinoparcUser) -> &mut StoreUser {
     self.users.entry(user.as_ref()).or_insert_with(|| StoreUser {
       server: user.server,
@@ -352,10 +394,80 @@ where
     )
   }
 
+  async fn get_dinoz_history(
+    &self,
+    options: &GetDinoparcDinozHistoryOptions,
+  ) -> Result<Option<DinoparcDinozHistory>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(
+      state
+        .get_dinoz(&options.id.and_server(options.server))
+        .map(|d| state.to_dinoz_history(d)),
+    )
+  }
+
   async fn get_user(&self, options: &GetDinoparcUserOptions) -> Result<Option<ArchivedDinoparcUser>, EtwinError> {
     let state = self.state.read().unwrap();
     Ok(state.get_user(&options.id.and_server(options.server)).map(|u| u.into()))
   }
+
+  async fn get_coins(&self, options: &GetDinoparcUserOptions) -> Result<Option<LatestTemporal<u32>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    Ok(
+      state
+        .get_user(&options.id.and_server(options.server))
+        .and_then(|u| u.coins.at(time))
+        .map(|latest| LatestTemporal {
+          latest: latest.cloned(),
+        }),
+    )
+  }
+
+  async fn get_bills(&self, options: &GetDinoparcUserOptions) -> Result<Option<LatestTemporal<u32>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    Ok(
+      state
+        .get_user(&options.id.and_server(options.server))
+        .and_then(|u| u.bills.at(time))
+        .map(|latest| LatestTemporal {
+          latest: latest.cloned(),
+        }),
+    )
+  }
+
+  async fn get_inventory(
+    &self,
+    options: &GetDinoparcUserOptions,
+  ) -> Result<Option<LatestTemporal<HashMap<DinoparcItemId, u32>>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    Ok(
+      state
+        .get_user(&options.id.and_server(options.server))
+        .and_then(|u| u.inventory.at(time))
+        .map(|latest| LatestTemporal {
+          latest: latest.cloned(),
+        }),
+    )
+  }
+
+  async fn get_collection(
+    &self,
+    options: &GetDinoparcUserOptions,
+  ) -> Result<Option<LatestTemporal<DinoparcCollection>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    Ok(
+      state
+        .get_user(&options.id.and_server(options.server))
+        .and_then(|u| u.collection.at(time))
+        .map(|latest| LatestTemporal {
+          latest: latest.cloned(),
+        }),
+    )
+  }
 }
 
 #[cfg(feature = "neon")]
@@ -5,14 +5,15 @@ use etwin_core::clock::Clock;
 use etwin_core::core::{Instant, IntPercentage, PeriodLower};
 use etwin_core::dinoparc::{
   ArchivedDinoparcDinoz, ArchivedDinoparcUser, DinoparcCollection, DinoparcCollectionResponse, DinoparcDinozElements,
-  DinoparcDinozId, DinoparcDinozIdRef, DinoparcDinozName, DinoparcDinozRace, DinoparcDinozResponse, DinoparcDinozSkin,
-  DinoparcEpicRewardKey, DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcItemId, DinoparcLocationId,
-  DinoparcRewardId, DinoparcServer, DinoparcSessionUser, DinoparcSkill, DinoparcSkillLevel, DinoparcStore,
-  DinoparcUserId, DinoparcUserIdRef, DinoparcUsername, GetDinoparcDinozOptions, GetDinoparcUserOptions,
-  ShortDinoparcDinozWithLevel, ShortDinoparcUser,
+  DinoparcDinozHistory, DinoparcDinozId, DinoparcDinozIdRef, DinoparcDinozName, DinoparcDinozRace,
+  DinoparcDinozResponse, DinoparcDinozSkin, DinoparcEpicRewardKey, DinoparcExchangeWithResponse,
+  DinoparcInventoryResponse, DinoparcItemId, DinoparcLocationId, DinoparcRewardId, DinoparcServer, DinoparcSessionUser,
+  DinoparcSkill, DinoparcSkillLevel, DinoparcStore, DinoparcUserId, DinoparcUserIdRef, DinoparcUsername,
+  GetDinoparcDinozHistoryOptions, GetDinoparcDinozOptions, GetDinoparcUserOptions, ShortDinoparcDinozWithLevel,
+  ShortDinoparcUser,
 };
 use etwin_core::pg_num::{PgU16, PgU32};
-use etwin_core::temporal::{ForeignRetrieved, ForeignSnapshot, LatestTemporal};
+use etwin_core::temporal::{coalesce_snapshots, ForeignRetrieved, ForeignSnapshot, LatestTemporal};
 use etwin_core::types::EtwinError;
 use etwin_core::uuid::UuidGenerator;
 use etwin_populate::dinoparc::populate_dinoparc;
@@ -21,6 +22,7 @@ use sha3::{Digest, Sha3_256};
 use sqlx::postgres::PgQueryResult;
 use sqlx::types::Uuid;
 use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::error::Error;
@@ -305,26 +307,7 @@ where
     };
 
     let skills = if let Some(skills) = row.profile_skills {
-      #[derive(Debug, sqlx::FromRow)]
-      struct Row {
-        dinoparc_skill: DinoparcSkill,
-        level: DinoparcSkillLevel,
-      }
-
-      let rows: Vec<Row> = sqlx::query_as::<_, Row>(
-        r"
-      SELECT dinoparc_skill, level
-      FROM dinoparc_skill_level_map_items
-      WHERE dinoparc_skill_level_map_id = $1::DINOPARC_SKILL_LEVEL_MAP_ID;
-    ",
-      )
-      .bind(skills)
-      .fetch_all(&mut tx)
-      .await?;
-
-      let skills: HashMap<DinoparcSkill, DinoparcSkillLevel> =
-        rows.iter().map(|r| (r.dinoparc_skill, r.level)).collect();
-      Some(skills)
+      Some(get_dinoparc_skill_levels(&mut tx, skills).await?)
     } else {
       None
     };
@@ -371,6 +354,241 @@ where
     }))
   }
 
+  async fn get_dinoz_history(
+    &self,
+    options: &GetDinoparcDinozHistoryOptions,
+  ) -> Result<Option<DinoparcDinozHistory>, EtwinError> {
+    let mut tx = self.database.as_ref().begin().await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct DinozRow {
+      archived_at: Instant,
+    }
+
+    let row: Option<DinozRow> = sqlx::query_as::<_, DinozRow>(
+      r"
+      SELECT archived_at
+      FROM dinoparc_dinoz
+      WHERE dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID;
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let archived_at = match row {
+      Some(row) => row.archived_at,
+      None => return Ok(None),
+    };
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct NameRow {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      name: Option<DinoparcDinozName>,
+    }
+
+    let names: Vec<NameRow> = sqlx::query_as::<_, NameRow>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, name
+      FROM dinoparc_dinoz_names
+      WHERE dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID
+      ORDER BY lower(period);
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_all(&mut tx)
+    .await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct OwnerRow {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      owner: DinoparcUserId,
+      username: DinoparcUsername,
+    }
+
+    let owners: Vec<OwnerRow> = sqlx::query_as::<_, OwnerRow>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, owner, username
+      FROM dinoparc_dinoz_owners
+        INNER JOIN dinoparc_users ON (dinoparc_users.dinoparc_server = dinoparc_dinoz_owners.dinoparc_server AND dinoparc_users.dinoparc_user_id = dinoparc_dinoz_owners.owner)
+      WHERE dinoparc_dinoz_owners.dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID
+      ORDER BY lower(period);
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_all(&mut tx)
+    .await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct LocationRow {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      location: DinoparcLocationId,
+    }
+
+    let locations: Vec<LocationRow> = sqlx::query_as::<_, LocationRow>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, location
+      FROM dinoparc_dinoz_locations
+      WHERE dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID
+      ORDER BY lower(period);
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_all(&mut tx)
+    .await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct LevelRow {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      level: PgU16,
+    }
+
+    let levels: Vec<LevelRow> = sqlx::query_as::<_, LevelRow>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, level
+      FROM dinoparc_dinoz_levels
+      WHERE dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID
+      ORDER BY lower(period);
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_all(&mut tx)
+    .await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct SkinRow {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      race: DinoparcDinozRace,
+      skin: DinoparcDinozSkin,
+    }
+
+    let skins: Vec<SkinRow> = sqlx::query_as::<_, SkinRow>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, race, skin
+      FROM dinoparc_dinoz_skins
+      WHERE dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID
+      ORDER BY lower(period);
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_all(&mut tx)
+    .await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct ProfileRow {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      life: IntPercentage,
+      experience: IntPercentage,
+      danger: i16,
+      in_tournament: bool,
+      elements: DinoparcDinozElements,
+      skills: Uuid,
+    }
+
+    let profiles: Vec<ProfileRow> = sqlx::query_as::<_, ProfileRow>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest,
+        life, experience, danger, in_tournament, elements, skills
+      FROM dinoparc_dinoz_profiles
+      WHERE dinoparc_server = $1::DINOPARC_SERVER AND dinoparc_dinoz_id = $2::DINOPARC_DINOZ_ID
+      ORDER BY lower(period);
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_all(&mut tx)
+    .await?;
+
+    let mut skill_maps: HashMap<Uuid, HashMap<DinoparcSkill, DinoparcSkillLevel>> = HashMap::new();
+    for profile in profiles.iter() {
+      if let Entry::Vacant(entry) = skill_maps.entry(profile.skills) {
+        entry.insert(get_dinoparc_skill_levels(&mut tx, profile.skills).await?);
+      }
+    }
+
+    tx.commit().await?;
+
+    fn snapshot<T>(period: PeriodLower, latest: Instant, value: T) -> ForeignSnapshot<T> {
+      ForeignSnapshot {
+        period,
+        retrieved: ForeignRetrieved { latest },
+        value,
+      }
+    }
+
+    Ok(Some(DinoparcDinozHistory {
+      server: options.server,
+      id: options.id,
+      archived_at,
+      name: coalesce_snapshots(
+        names
+          .into_iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.name)),
+      ),
+      owner: coalesce_snapshots(owners.into_iter().map(|r| {
+        let owner = ShortDinoparcUser {
+          server: options.server,
+          id: r.owner,
+          username: r.username,
+        };
+        snapshot(r.period, r.retrieved_latest, owner)
+      })),
+      location: coalesce_snapshots(
+        locations
+          .into_iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.location)),
+      ),
+      race: coalesce_snapshots(skins.iter().map(|r| snapshot(r.period, r.retrieved_latest, r.race))),
+      skin: coalesce_snapshots(
+        skins
+          .into_iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.skin)),
+      ),
+      life: coalesce_snapshots(profiles.iter().map(|r| snapshot(r.period, r.retrieved_latest, r.life))),
+      level: coalesce_snapshots(
+        levels
+          .into_iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, u16::from(r.level))),
+      ),
+      experience: coalesce_snapshots(
+        profiles
+          .iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.experience)),
+      ),
+      danger: coalesce_snapshots(
+        profiles
+          .iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.danger)),
+      ),
+      in_tournament: coalesce_snapshots(
+        profiles
+          .iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.in_tournament)),
+      ),
+      elements: coalesce_snapshots(
+        profiles
+          .iter()
+          .map(|r| snapshot(r.period, r.retrieved_latest, r.elements)),
+      ),
+      skills: coalesce_snapshots(profiles.iter().map(|r| {
+        let skills = skill_maps.get(&r.skills).cloned().unwrap_or_default();
+        snapshot(r.period, r.retrieved_latest, skills)
+      })),
+    }))
+  }
+
   async fn get_user(&self, options: &GetDinoparcUserOptions) -> Result<Option<ArchivedDinoparcUser>, EtwinError> {
     let time = options.time.unwrap_or_else(|| self.clock.now());
     let mut tx = self.database.as_ref().begin().await?;
@@ -462,72 +680,13 @@ where
       None => return Ok(None),
     };
 
-    let inventory = if let Some(inventory) = row.inventory_value {
-      #[derive(Debug, sqlx::FromRow)]
-      struct Row {
-        dinoparc_item_id: DinoparcItemId,
-        count: PgU32,
-      }
-
-      let rows: Vec<Row> = sqlx::query_as::<_, Row>(
-        r"
-      SELECT dinoparc_item_id, count
-      FROM dinoparc_item_count_map_items
-      WHERE dinoparc_item_count_map_id = $1::DINOPARC_ITEM_COUNT_MAP_ID;
-    ",
-      )
-      .bind(inventory)
-      .fetch_all(&mut tx)
-      .await?;
-
-      let inventory: HashMap<DinoparcItemId, u32> = rows.iter().map(|r| (r.dinoparc_item_id, r.count.into())).collect();
-      Some(inventory)
-    } else {
-      None
+    let inventory = match row.inventory_value {
+      Some(inventory) => Some(get_dinoparc_item_counts(&mut tx, inventory).await?),
+      None => None,
     };
 
     let collection = match (row.collection_rewards, row.collection_epic_rewards) {
-      (Some(rewards), Some(epic_rewards)) => {
-        let rewards: HashSet<DinoparcRewardId> = {
-          #[derive(Debug, sqlx::FromRow)]
-          struct Row {
-            dinoparc_reward_id: DinoparcRewardId,
-          }
-
-          let rows: Vec<Row> = sqlx::query_as::<_, Row>(
-            r"
-            SELECT dinoparc_reward_id
-            FROM dinoparc_reward_set_items
-            WHERE dinoparc_reward_set_id = $1::DINOPARC_REWARD_SET_ID;
-          ",
-          )
-          .bind(rewards)
-          .fetch_all(&mut tx)
-          .await?;
-
-          rows.into_iter().map(|r| r.dinoparc_reward_id).collect()
-        };
-        let epic_rewards: HashSet<DinoparcEpicRewardKey> = {
-          #[derive(Debug, sqlx::FromRow)]
-          struct Row {
-            dinoparc_epic_reward_key: DinoparcEpicRewardKey,
-          }
-
-          let rows: Vec<Row> = sqlx::query_as::<_, Row>(
-            r"
-            SELECT dinoparc_epic_reward_key
-            FROM dinoparc_epic_reward_set_items
-            WHERE dinoparc_epic_reward_set_id = $1::DINOPARC_EPIC_REWARD_SET_ID;
-          ",
-          )
-          .bind(epic_rewards)
-          .fetch_all(&mut tx)
-          .await?;
-
-          rows.into_iter().map(|r| r.dinoparc_epic_reward_key).collect()
-        };
-        Some(DinoparcCollection { rewards, epic_rewards })
-      }
+      (Some(rewards), Some(epic_rewards)) => Some(get_dinoparc_collection(&mut tx, rewards, epic_rewards).await?),
       (None, None) => None,
       _ => unreachable!(),
     };
@@ -594,6 +753,281 @@ where
       dinoz,
     }))
   }
+
+  async fn get_coins(&self, options: &GetDinoparcUserOptions) -> Result<Option<LatestTemporal<u32>>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      coins: PgU32,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, coins
+      FROM dinoparc_coins
+      WHERE
+        dinoparc_server = $1::DINOPARC_SERVER
+        AND dinoparc_user_id = $2::DINOPARC_USER_ID
+        AND lower(period) <= $3::INSTANT
+      ORDER BY lower(period) DESC
+      LIMIT 1;
+    ",
+    )
+    .bind(&options.server)
+    .bind(&options.id)
+    .bind(time)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    Ok(row.map(|row| LatestTemporal {
+      latest: ForeignSnapshot {
+        period: row.period,
+        retrieved: ForeignRetrieved {
+          latest: row.retrieved_latest,
+        },
+        value: row.coins.into(),
+      },
+    }))
+  }
+
+  async fn get_bills(&self, options: &GetDinoparcUserOptions) -> Result<Option<LatestTemporal<u32>>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      bills: PgU32,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, bills
+      FROM dinoparc_bills
+      WHERE
+        dinoparc_server = $1::DINOPARC_SERVER
+        AND dinoparc_user_id = $2::DINOPARC_USER_ID
+        AND lower(period) <= $3::INSTANT
+      ORDER BY lower(period) DESC
+      LIMIT 1;
+    ",
+    )
+    .bind(&options.server)
+    .bind(&options.id)
+    .bind(time)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    Ok(row.map(|row| LatestTemporal {
+      latest: ForeignSnapshot {
+        period: row.period,
+        retrieved: ForeignRetrieved {
+          latest: row.retrieved_latest,
+        },
+        value: row.bills.into(),
+      },
+    }))
+  }
+
+  async fn get_inventory(
+    &self,
+    options: &GetDinoparcUserOptions,
+  ) -> Result<Option<LatestTemporal<HashMap<DinoparcItemId, u32>>>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    let mut tx = self.database.as_ref().begin().await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      item_counts: Uuid,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, item_counts
+      FROM dinoparc_inventories
+      WHERE
+        dinoparc_server = $1::DINOPARC_SERVER
+        AND dinoparc_user_id = $2::DINOPARC_USER_ID
+        AND lower(period) <= $3::INSTANT
+      ORDER BY lower(period) DESC
+      LIMIT 1;
+    ",
+    )
+    .bind(&options.server)
+    .bind(&options.id)
+    .bind(time)
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let row = match row {
+      Some(row) => row,
+      None => return Ok(None),
+    };
+    let inventory = get_dinoparc_item_counts(&mut tx, row.item_counts).await?;
+    tx.commit().await?;
+
+    Ok(Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: row.period,
+        retrieved: ForeignRetrieved {
+          latest: row.retrieved_latest,
+        },
+        value: inventory,
+      },
+    }))
+  }
+
+  async fn get_collection(
+    &self,
+    options: &GetDinoparcUserOptions,
+  ) -> Result<Option<LatestTemporal<DinoparcCollection>>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    let mut tx = self.database.as_ref().begin().await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      period: PeriodLower,
+      retrieved_latest: Instant,
+      dinoparc_reward_set_id: Uuid,
+      dinoparc_epic_reward_set_id: Uuid,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest,
+        dinoparc_reward_set_id, dinoparc_epic_reward_set_id
+      FROM dinoparc_collections
+      WHERE
+        dinoparc_server = $1::DINOPARC_SERVER
+        AND dinoparc_user_id = $2::DINOPARC_USER_ID
+        AND lower(period) <= $3::INSTANT
+      ORDER BY lower(period) DESC
+      LIMIT 1;
+    ",
+    )
+    .bind(&options.server)
+    .bind(&options.id)
+    .bind(time)
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let row = match row {
+      Some(row) => row,
+      None => return Ok(None),
+    };
+    let collection =
+      get_dinoparc_collection(&mut tx, row.dinoparc_reward_set_id, row.dinoparc_epic_reward_set_id).await?;
+    tx.commit().await?;
+
+    Ok(Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: row.period,
+        retrieved: ForeignRetrieved {
+          latest: row.retrieved_latest,
+        },
+        value: collection,
+      },
+    }))
+  }
+}
+
+async fn get_dinoparc_skill_levels(
+  tx: &mut Transaction<'_, Postgres>,
+  skill_levels: Uuid,
+) -> Result<HashMap<DinoparcSkill, DinoparcSkillLevel>, EtwinError> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    dinoparc_skill: DinoparcSkill,
+    level: DinoparcSkillLevel,
+  }
+
+  let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+    r"
+    SELECT dinoparc_skill, level
+    FROM dinoparc_skill_level_map_items
+    WHERE dinoparc_skill_level_map_id = $1::DINOPARC_SKILL_LEVEL_MAP_ID;
+  ",
+  )
+  .bind(skill_levels)
+  .fetch_all(&mut *tx)
+  .await?;
+
+  Ok(rows.iter().map(|r| (r.dinoparc_skill, r.level)).collect())
+}
+
+async fn get_dinoparc_item_counts(
+  tx: &mut Transaction<'_, Postgres>,
+  item_counts: Uuid,
+) -> Result<HashMap<DinoparcItemId, u32>, EtwinError> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    dinoparc_item_id: DinoparcItemId,
+    count: PgU32,
+  }
+
+  let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+    r"
+    SELECT dinoparc_item_id, count
+    FROM dinoparc_item_count_map_items
+    WHERE dinoparc_item_count_map_id = $1::DINOPARC_ITEM_COUNT_MAP_ID;
+  ",
+  )
+  .bind(item_counts)
+  .fetch_all(&mut *tx)
+  .await?;
+
+  Ok(rows.iter().map(|r| (r.dinoparc_item_id, r.count.into())).collect())
+}
+
+async fn get_dinoparc_collection(
+  tx: &mut Transaction<'_, Postgres>,
+  rewards: Uuid,
+  epic_rewards: Uuid,
+) -> Result<DinoparcCollection, EtwinError> {
+  let rewards: HashSet<DinoparcRewardId> = {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      dinoparc_reward_id: DinoparcRewardId,
+    }
+
+    let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT dinoparc_reward_id
+      FROM dinoparc_reward_set_items
+      WHERE dinoparc_reward_set_id = $1::DINOPARC_REWARD_SET_ID;
+    ",
+    )
+    .bind(rewards)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    rows.into_iter().map(|r| r.dinoparc_reward_id).collect()
+  };
+  let epic_rewards: HashSet<DinoparcEpicRewardKey> = {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      dinoparc_epic_reward_key: DinoparcEpicRewardKey,
+    }
+
+    let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT dinoparc_epic_reward_key
+      FROM dinoparc_epic_reward_set_items
+      WHERE dinoparc_epic_reward_set_id = $1::DINOPARC_EPIC_REWARD_SET_ID;
+    ",
+    )
+    .bind(epic_rewards)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    rows.into_iter().map(|r| r.dinoparc_epic_reward_key).collect()
+  };
+  Ok(DinoparcCollection { rewards, epic_rewards })
 }
 
 fn to_latest_temporal<T>(
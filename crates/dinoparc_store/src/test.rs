@@ -4,10 +4,11 @@ use etwin_core::clock::VirtualClock;
 use etwin_core::core::{IntPercentage, PeriodLower};
 use etwin_core::dinoparc::{
   ArchivedDinoparcDinoz, ArchivedDinoparcUser, DinoparcCollection, DinoparcCollectionResponse, DinoparcDinoz,
-  DinoparcDinozElements, DinoparcDinozIdRef, DinoparcDinozRace, DinoparcDinozResponse, DinoparcExchangeWithResponse,
-  DinoparcInventoryResponse, DinoparcServer, DinoparcSessionUser, DinoparcSkill, DinoparcSkillLevel, DinoparcStore,
-  GetDinoparcDinozOptions, GetDinoparcUserOptions, NamedDinoparcDinozFields, ShortDinoparcDinozWithLevel,
-  ShortDinoparcDinozWithLocation, ShortDinoparcUser,
+  DinoparcDinozElements, DinoparcDinozHistory, DinoparcDinozIdRef, DinoparcDinozRace, DinoparcDinozResponse,
+  DinoparcExchangeWithResponse, DinoparcInventoryResponse, DinoparcItemId, DinoparcServer, DinoparcSessionUser,
+  DinoparcSkill, DinoparcSkillLevel, DinoparcStore, GetDinoparcDinozHistoryOptions, GetDinoparcDinozOptions,
+  GetDinoparcUserOptions, NamedDinoparcDinozFields, ShortDinoparcDinozWithLevel, ShortDinoparcDinozWithLocation,
+  ShortDinoparcUser,
 };
 use etwin_core::temporal::{ForeignRetrieved, ForeignSnapshot, LatestTemporal};
 use std::collections::{HashMap, HashSet};
@@ -27,6 +28,10 @@ macro_rules! test_dinoparc_store {
     register_test!($(#[$meta])*, $api, test_touch_exchange_with_none_admin);
     register_test!($(#[$meta])*, $api, test_touch_exchange_with_extra);
     register_test!($(#[$meta])*, $api, test_touch_exchange_with_extra_then_drop_some);
+    register_test!($(#[$meta])*, $api, test_get_inventory_history);
+    register_test!($(#[$meta])*, $api, test_get_collection_history);
+    register_test!($(#[$meta])*, $api, test_get_coins_and_bills_history);
+    register_test!($(#[$meta])*, $api, test_get_dinoz_history);
   };
 }
 
@@ -1659,3 +1664,491 @@ pub(crate) async fn test_touch_exchange_with_extra_then_drop_some<TyClock, TyDin
     assert_eq!(actual, expected);
   }
 }
+
+pub(crate) async fn test_get_inventory_history<TyClock, TyDinoparcStore>(api: TestApi<TyClock, TyDinoparcStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+{
+  let alice = DinoparcSessionUser {
+    user: ShortDinoparcUser {
+      server: DinoparcServer::DinoparcCom,
+      id: "1".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    },
+    coins: 10000,
+    dinoz: Vec::new(),
+  };
+  let old_inventory: HashMap<DinoparcItemId, u32> = {
+    let mut inventory = HashMap::new();
+    inventory.insert("1".parse().unwrap(), 10);
+    inventory
+  };
+  let new_inventory: HashMap<DinoparcItemId, u32> = {
+    let mut inventory = HashMap::new();
+    inventory.insert("1".parse().unwrap(), 9);
+    inventory.insert("4".parse().unwrap(), 1);
+    inventory
+  };
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_inventory(&DinoparcInventoryResponse {
+        session_user: alice.clone(),
+        inventory: old_inventory.clone(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_inventory(&DinoparcInventoryResponse {
+        session_user: alice.clone(),
+        inventory: old_inventory.clone(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_inventory(&DinoparcInventoryResponse {
+        session_user: alice.clone(),
+        inventory: new_inventory.clone(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 3));
+  {
+    let actual = api
+      .dinoparc_store
+      .get_inventory(&GetDinoparcUserOptions {
+        server: DinoparcServer::DinoparcCom,
+        id: "1".parse().unwrap(),
+        time: Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1)),
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::bounded(
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+        ),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        },
+        value: old_inventory,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinoparc_store
+      .get_inventory(&GetDinoparcUserOptions {
+        server: DinoparcServer::DinoparcCom,
+        id: "1".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+        },
+        value: new_inventory,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinoparc_store
+      .get_inventory(&GetDinoparcUserOptions {
+        server: DinoparcServer::DinoparcCom,
+        id: "1".parse().unwrap(),
+        time: Some(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)),
+      })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+}
+
+pub(crate) async fn test_get_collection_history<TyClock, TyDinoparcStore>(api: TestApi<TyClock, TyDinoparcStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+{
+  let alice = DinoparcSessionUser {
+    user: ShortDinoparcUser {
+      server: DinoparcServer::DinoparcCom,
+      id: "1".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    },
+    coins: 10000,
+    dinoz: Vec::new(),
+  };
+  let old_collection = DinoparcCollection {
+    rewards: {
+      let mut rewards = HashSet::new();
+      rewards.insert("3".parse().unwrap());
+      rewards
+    },
+    epic_rewards: HashSet::new(),
+  };
+  let new_collection = DinoparcCollection {
+    rewards: {
+      let mut rewards = HashSet::new();
+      rewards.insert("2".parse().unwrap());
+      rewards.insert("3".parse().unwrap());
+      rewards
+    },
+    epic_rewards: {
+      let mut epic_rewards = HashSet::new();
+      epic_rewards.insert("kabuki".parse().unwrap());
+      epic_rewards
+    },
+  };
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_collection(&DinoparcCollectionResponse {
+        session_user: alice.clone(),
+        collection: old_collection.clone(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_collection(&DinoparcCollectionResponse {
+        session_user: alice.clone(),
+        collection: new_collection.clone(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2));
+  {
+    let actual = api
+      .dinoparc_store
+      .get_collection(&GetDinoparcUserOptions {
+        server: DinoparcServer::DinoparcCom,
+        id: "1".parse().unwrap(),
+        time: Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::bounded(
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        ),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+        },
+        value: old_collection,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinoparc_store
+      .get_collection(&GetDinoparcUserOptions {
+        server: DinoparcServer::DinoparcCom,
+        id: "1".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        },
+        value: new_collection,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_get_coins_and_bills_history<TyClock, TyDinoparcStore>(api: TestApi<TyClock, TyDinoparcStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+{
+  let alice = ShortDinoparcUser {
+    server: DinoparcServer::DinoparcCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  let bob = ShortDinoparcUser {
+    server: DinoparcServer::DinoparcCom,
+    id: "2".parse().unwrap(),
+    username: "bob".parse().unwrap(),
+  };
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_exchange_with(&DinoparcExchangeWithResponse {
+        session_user: DinoparcSessionUser {
+          user: alice.clone(),
+          coins: 10000,
+          dinoz: Vec::new(),
+        },
+        own_bills: 2,
+        own_dinoz: Vec::new(),
+        other_user: bob.clone(),
+        other_dinoz: Vec::new(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1));
+  {
+    let actual = api
+      .dinoparc_store
+      .touch_exchange_with(&DinoparcExchangeWithResponse {
+        session_user: DinoparcSessionUser {
+          user: alice.clone(),
+          coins: 7000,
+          dinoz: Vec::new(),
+        },
+        own_bills: 2,
+        own_dinoz: Vec::new(),
+        other_user: bob.clone(),
+        other_dinoz: Vec::new(),
+      })
+      .await;
+    assert_ok!(actual);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2));
+  let at_start = GetDinoparcUserOptions {
+    server: DinoparcServer::DinoparcCom,
+    id: "1".parse().unwrap(),
+    time: Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+  };
+  let now = GetDinoparcUserOptions {
+    server: DinoparcServer::DinoparcCom,
+    id: "1".parse().unwrap(),
+    time: None,
+  };
+  {
+    let actual = api.dinoparc_store.get_coins(&at_start).await.unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::bounded(
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        ),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+        },
+        value: 10000,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api.dinoparc_store.get_coins(&now).await.unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        },
+        value: 7000,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api.dinoparc_store.get_bills(&at_start).await.unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        },
+        value: 2,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinoparc_store
+      .get_bills(&GetDinoparcUserOptions {
+        server: DinoparcServer::DinoparcCom,
+        id: "2".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+}
+
+pub(crate) async fn test_get_dinoz_history<TyClock, TyDinoparcStore>(api: TestApi<TyClock, TyDinoparcStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+{
+  fn yasumi_response(location: &str, level: u16, life: u8) -> DinoparcDinozResponse {
+    DinoparcDinozResponse {
+      session_user: DinoparcSessionUser {
+        user: ShortDinoparcUser {
+          server: DinoparcServer::EnDinoparcCom,
+          id: "681579".parse().unwrap(),
+          username: "Kapox".parse().unwrap(),
+        },
+        coins: 527051,
+        dinoz: vec![ShortDinoparcDinozWithLocation {
+          server: DinoparcServer::EnDinoparcCom,
+          id: "765483".parse().unwrap(),
+          name: Some("Yasumi".parse().unwrap()),
+          location: Some(location.parse().unwrap()),
+        }],
+      },
+      dinoz: DinoparcDinoz {
+        server: DinoparcServer::EnDinoparcCom,
+        id: "765483".parse().unwrap(),
+        race: DinoparcDinozRace::Wanwan,
+        skin: "Ac9OrgxOWu1pd7Fp".parse().unwrap(),
+        level,
+        named: Some(NamedDinoparcDinozFields {
+          name: "Yasumi".parse().unwrap(),
+          location: location.parse().unwrap(),
+          life: IntPercentage::new(life).unwrap(),
+          experience: IntPercentage::new(13).unwrap(),
+          danger: 116,
+          in_tournament: false,
+          elements: DinoparcDinozElements {
+            fire: 10,
+            earth: 0,
+            water: 0,
+            thunder: 7,
+            air: 2,
+          },
+          skills: {
+            let mut skills = HashMap::new();
+            skills.insert(DinoparcSkill::Dexterity, DinoparcSkillLevel::new(2).unwrap());
+            skills.insert(DinoparcSkill::Strength, DinoparcSkillLevel::new(5).unwrap());
+            skills
+          },
+        }),
+      },
+    }
+  }
+
+  fn snapshot<T>(start: u32, end: Option<u32>, latest: u32, value: T) -> ForeignSnapshot<T> {
+    ForeignSnapshot {
+      period: PeriodLower::new(
+        Utc.ymd(2021, 1, 1).and_hms(0, 0, start),
+        end.map(|end| Utc.ymd(2021, 1, 1).and_hms(0, 0, end)),
+      ),
+      retrieved: ForeignRetrieved {
+        latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, latest),
+      },
+      value,
+    }
+  }
+
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  assert_ok!(api.dinoparc_store.touch_dinoz(&yasumi_response("0", 12, 30)).await);
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1));
+  assert_ok!(api.dinoparc_store.touch_dinoz(&yasumi_response("0", 12, 30)).await);
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2));
+  assert_ok!(api.dinoparc_store.touch_dinoz(&yasumi_response("0", 13, 100)).await);
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 3));
+  assert_ok!(api.dinoparc_store.touch_dinoz(&yasumi_response("4", 13, 90)).await);
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 4));
+  {
+    let actual = api
+      .dinoparc_store
+      .get_dinoz_history(&GetDinoparcDinozHistoryOptions {
+        server: DinoparcServer::EnDinoparcCom,
+        id: "765483".parse().unwrap(),
+      })
+      .await
+      .unwrap();
+    let expected = Some(DinoparcDinozHistory {
+      server: DinoparcServer::EnDinoparcCom,
+      id: "765483".parse().unwrap(),
+      archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      name: vec![snapshot(0, None, 3, Some("Yasumi".parse().unwrap()))],
+      owner: vec![snapshot(
+        0,
+        None,
+        3,
+        ShortDinoparcUser {
+          server: DinoparcServer::EnDinoparcCom,
+          id: "681579".parse().unwrap(),
+          username: "Kapox".parse().unwrap(),
+        },
+      )],
+      location: vec![
+        snapshot(0, Some(3), 2, "0".parse().unwrap()),
+        snapshot(3, None, 3, "4".parse().unwrap()),
+      ],
+      race: vec![snapshot(0, None, 3, DinoparcDinozRace::Wanwan)],
+      skin: vec![snapshot(0, None, 3, "Ac9OrgxOWu1pd7Fp".parse().unwrap())],
+      life: vec![
+        snapshot(0, Some(2), 1, IntPercentage::new(30).unwrap()),
+        snapshot(2, Some(3), 2, IntPercentage::new(100).unwrap()),
+        snapshot(3, None, 3, IntPercentage::new(90).unwrap()),
+      ],
+      level: vec![snapshot(0, Some(2), 1, 12), snapshot(2, None, 3, 13)],
+      experience: vec![snapshot(0, None, 3, IntPercentage::new(13).unwrap())],
+      danger: vec![snapshot(0, None, 3, 116)],
+      in_tournament: vec![snapshot(0, None, 3, false)],
+      elements: vec![snapshot(
+        0,
+        None,
+        3,
+        DinoparcDinozElements {
+          fire: 10,
+          earth: 0,
+          water: 0,
+          thunder: 7,
+          air: 2,
+        },
+      )],
+      skills: vec![snapshot(0, None, 3, {
+        let mut skills = HashMap::new();
+        skills.insert(DinoparcSkill::Dexterity, DinoparcSkillLevel::new(2).unwrap());
+        skills.insert(DinoparcSkill::Strength, DinoparcSkillLevel::new(5).unwrap());
+        skills
+      })],
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinoparc_store
+      .get_dinoz_history(&GetDinoparcDinozHistoryOptions {
+        server: DinoparcServer::EnDinoparcCom,
+        id: "765484".parse().unwrap(),
+      })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+}
@@ -1,7 +1,78 @@
+use etwin_core::dinorpg::{
+  DinorpgClanIdParseError, DinorpgClanNameParseError, DinorpgDinozIdParseError, DinorpgDinozNameParseError,
+  DinorpgDinozSkinParseError, DinorpgItemKeyParseError, DinorpgLocationNameParseError, DinorpgRewardKeyParseError,
+  DinorpgServer, DinorpgSkillKeyParseError, DinorpgStatusKeyParseError, DinorpgUserIdParseError,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ScraperError {
   #[error("HTTP Error")]
-  HttpError(#[from] reqwest::Error),
+  HttpError(#[from] etwin_http::HttpError),
+  #[error("Failed to login due to unexpected login response: {:?}", .0)]
+  UnexpectedLoginResponse(reqwest::StatusCode),
+  #[error("Missing DinoRPG session cookie from response")]
+  MissingSessionCookie,
+  #[error("DinoRPG session cookie is invalid or malformed")]
+  InvalidSessionCookie,
+  #[error("Invalid Twinoid credentials on {}", .0.as_str())]
+  InvalidCredentials(DinorpgServer),
+  #[error("Invalid or expired session key on {}", .0.as_str())]
+  InvalidSessionKey(DinorpgServer),
+  #[error("Expected a logged-in page, but the session bar is missing")]
+  MissingSessionUser,
+  #[error("Failed to detect server: missing or unknown Twinoid `lang`")]
+  ServerDetectionFailure,
+  #[error("Zero or many session dinoz lists, exactly one was expected")]
+  NonUniqueDinozList,
+  #[error("Zero or many profile cards, exactly one was expected")]
+  NonUniqueCard,
+  #[error("Missing profile card entry {:?}", .0)]
+  MissingCardEntry(&'static str),
+  #[error("Invalid dinoz count {:?}", .0)]
+  InvalidDinozCount(String),
+  #[error("Invalid ranking {:?}", .0)]
+  InvalidRanking(String),
+  #[error("Invalid DinoRPG user id {:?}", .0)]
+  InvalidUserId(String, #[source] DinorpgUserIdParseError),
+  #[error("Invalid DinoRPG clan id {:?}", .0)]
+  InvalidClanId(String, #[source] DinorpgClanIdParseError),
+  #[error("Invalid DinoRPG clan name {:?}", .0)]
+  InvalidClanName(String, #[source] DinorpgClanNameParseError),
+  #[error("Invalid DinoRPG reward icon {:?}", .0)]
+  InvalidRewardIcon(String),
+  #[error("Invalid DinoRPG reward key {:?}", .0)]
+  InvalidRewardKey(String, #[source] DinorpgRewardKeyParseError),
+  #[error("Missing dinoz Flash container")]
+  MissingDinozSwf,
+  #[error("Invalid DinoRPG dinoz id {:?}", .0)]
+  InvalidDinozId(String, #[source] DinorpgDinozIdParseError),
+  #[error("Invalid DinoRPG dinoz name {:?}", .0)]
+  InvalidDinozName(String, #[source] DinorpgDinozNameParseError),
+  #[error("Missing dinoz skin FlashVars")]
+  MissingDinozSkin,
+  #[error("Invalid DinoRPG dinoz skin {:?}", .0)]
+  InvalidDinozSkin(String, #[source] DinorpgDinozSkinParseError),
+  #[error("Unknown race for DinoRPG dinoz skin {:?}", .0)]
+  UnknownDinozRace(String),
+  #[error("Zero or many dinoz names, exactly one was expected")]
+  NonUniqueDinozName,
+  #[error("Zero or many dinoz levels, exactly one was expected")]
+  NonUniqueDinozLevel,
+  #[error("Invalid dinoz level {:?}", .0)]
+  InvalidDinozLevel(String),
+  #[error("Zero or many dinoz sheets, exactly one was expected")]
+  NonUniqueDinozSheet,
+  #[error("Zero or many dinoz locations, exactly one was expected")]
+  NonUniqueDinozLocation,
+  #[error("Invalid DinoRPG location name {:?}", .0)]
+  InvalidLocationName(String, #[source] DinorpgLocationNameParseError),
+  #[error("Invalid DinoRPG icon {:?}", .0)]
+  InvalidIcon(String),
+  #[error("Invalid DinoRPG skill key {:?}", .0)]
+  InvalidSkillKey(String, #[source] DinorpgSkillKeyParseError),
+  #[error("Invalid DinoRPG status key {:?}", .0)]
+  InvalidStatusKey(String, #[source] DinorpgStatusKeyParseError),
+  #[error("Invalid DinoRPG item key {:?}", .0)]
+  InvalidItemKey(String, #[source] DinorpgItemKeyParseError),
 }
@@ -2,11 +2,13 @@ use etwin_core::dinorpg::DinorpgServer;
 use once_cell::sync::Lazy;
 
 pub struct ScraperLocale {
-  // TODO
+  /// Label of the dinoz count entry on the profile card
+  pub dinoz_count_label: &'static str,
+  /// Label of the ranking entry on the profile card
+  pub ranking_label: &'static str,
 }
 
 impl ScraperLocale {
-  #[allow(unused)]
   pub fn get(server: DinorpgServer) -> &'static Self {
     match server {
       DinorpgServer::DinorpgCom => &SCRAPER_LOCALE_FR,
@@ -16,11 +18,17 @@ impl ScraperLocale {
   }
 }
 
-#[allow(dead_code)]
-static SCRAPER_LOCALE_FR: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {});
+static SCRAPER_LOCALE_FR: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
+  dinoz_count_label: "Dinoz",
+  ranking_label: "Classement",
+});
 
-#[allow(dead_code)]
-static SCRAPER_LOCALE_ES: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {});
+static SCRAPER_LOCALE_ES: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
+  dinoz_count_label: "Dinoz",
+  ranking_label: "Clasificación",
+});
 
-#[allow(dead_code)]
-static SCRAPER_LOCALE_EN: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {});
+static SCRAPER_LOCALE_EN: Lazy<ScraperLocale> = Lazy::new(|| ScraperLocale {
+  dinoz_count_label: "Dinoz",
+  ranking_label: "Ranking",
+});
@@ -1,61 +1,189 @@
 mod errors;
 mod locale;
+pub mod reparse;
 mod scraper;
 mod url;
 
+use crate::http::errors::ScraperError;
 use crate::http::url::DinorpgUrls;
 use ::scraper::Html;
 use async_trait::async_trait;
 use etwin_core::clock::Clock;
-use etwin_core::dinorpg::{DinorpgClient, DinorpgProfileResponse, DinorpgUserIdRef};
+use etwin_core::dinorpg::{
+  DinorpgClient, DinorpgCredentials, DinorpgDinozId, DinorpgDinozResponse, DinorpgProfileResponse, DinorpgServer,
+  DinorpgSession, DinorpgSessionKey, DinorpgUserIdRef,
+};
 use etwin_core::types::EtwinError;
-use reqwest::Client;
-use std::time::Duration;
+use etwin_http::archive::PageArchive;
+use etwin_http::event::{archive_logged, log_parse_failure, send_logged, HttpClientEvent};
+use etwin_http::{new_archive, new_transport, HttpError, HttpOptions, HttpRequest, HttpResponse, HttpTransport};
+use etwin_log::Logger;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
 
 const USER_AGENT: &str = "EtwinDinorpgScraper";
-const TIMEOUT: Duration = Duration::from_millis(5000);
 
-pub struct HttpDinorpgClient<TyClock> {
-  client: Client,
-  #[allow(unused)]
+pub struct HttpDinorpgClient<TyClock, TyLogger> {
+  transport: Arc<dyn HttpTransport>,
+  archive: Option<Arc<dyn PageArchive>>,
   clock: TyClock,
+  logger: TyLogger,
 }
 
-impl<TyClock> HttpDinorpgClient<TyClock>
+impl<TyClock, TyLogger> HttpDinorpgClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
-  pub fn new(clock: TyClock) -> Result<Self, EtwinError> {
+  pub fn new(clock: TyClock, logger: TyLogger) -> Result<Self, EtwinError> {
+    Self::with_options(clock, logger, &HttpOptions::default())
+  }
+
+  /// Creates a client using the provided transport options (mode, timeout, rate limit, retries and archive).
+  pub fn with_options(clock: TyClock, logger: TyLogger, options: &HttpOptions) -> Result<Self, EtwinError> {
     Ok(Self {
-      client: Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(TIMEOUT)
-        .redirect(reqwest::redirect::Policy::none())
-        .build()?,
-      clock,
+      archive: new_archive(options, USER_AGENT)?,
+      ..Self::with_transport(clock, logger, new_transport(options, USER_AGENT)?)
     })
   }
 
-  async fn get_html(&self, url: reqwest::Url) -> reqwest::Result<Html> {
-    let builder = self.client.get(url);
-    let resp = builder.send().await?;
-    let text = resp.error_for_status()?.text().await?;
-    Ok(Html::parse_document(&text))
+  pub fn with_transport(clock: TyClock, logger: TyLogger, transport: Arc<dyn HttpTransport>) -> Self {
+    Self {
+      transport,
+      archive: None,
+      clock,
+      logger,
+    }
+  }
+
+  /// Sends `req`, archiving the response if a page archive is configured.
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let res = send_logged(&*self.transport, &self.logger, USER_AGENT, req).await?;
+    if let Some(archive) = &self.archive {
+      archive_logged(&**archive, &self.logger, USER_AGENT, self.clock.now(), &res);
+    }
+    Ok(res)
+  }
+
+  async fn get_text(&self, url: reqwest::Url, session: Option<&DinorpgSessionKey>) -> Result<String, HttpError> {
+    let mut req = HttpRequest::get(url);
+
+    if let Some(key) = session {
+      // No need to escape, per DinorpgSessionKey invariants.
+      req = req.cookie("sid", key.as_str());
+    }
+
+    let resp = self.send(req).await?;
+    Ok(resp.error_for_status()?.text())
+  }
+
+  /// Fetches the page at `url` and reads it with `scrape`, logging a snippet of the page if it can't be read.
+  async fn scrape<T>(
+    &self,
+    url: reqwest::Url,
+    session: Option<&DinorpgSessionKey>,
+    scrape: impl FnOnce(&Html) -> Result<T, ScraperError> + Send,
+  ) -> Result<T, EtwinError> {
+    let text = self.get_text(url.clone(), session).await?;
+    let html = Html::parse_document(&text);
+    Ok(log_parse_failure(&self.logger, USER_AGENT, &url, &text, scrape(&html))?)
   }
 }
 
 #[async_trait]
-impl<TyClock> DinorpgClient for HttpDinorpgClient<TyClock>
+impl<TyClock, TyLogger> DinorpgClient for HttpDinorpgClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
+  async fn create_session(&self, credentials: &DinorpgCredentials) -> Result<DinorpgSession, EtwinError> {
+    let (server, login, password) = match credentials {
+      DinorpgCredentials::Twinoid {
+        server,
+        login,
+        password,
+      } => (*server, login, password),
+      DinorpgCredentials::SessionKey { server, key } => {
+        return match self.test_session(*server, key).await? {
+          Some(session) => Ok(session),
+          None => Err(ScraperError::InvalidSessionKey(*server).into()),
+        };
+      }
+    };
+
+    #[derive(Serialize)]
+    struct LoginForm<'a> {
+      login: &'a str,
+      pass: &'a str,
+    }
+
+    // This is the form used by the Twinoid widget embedded in every page
+    let req = HttpRequest::post(DinorpgUrls::new(server).login()).form(&LoginForm {
+      login: login.as_str(),
+      pass: password.as_str(),
+    })?;
+    let res = self.send(req).await?;
+
+    if !(res.status() == StatusCode::OK || res.status().is_redirection()) {
+      return Err(ScraperError::UnexpectedLoginResponse(res.status()).into());
+    }
+
+    let session_key = res.cookie("sid").ok_or(ScraperError::MissingSessionCookie)?;
+    let session_key = DinorpgSessionKey::from_str(session_key).map_err(|_| ScraperError::InvalidSessionCookie)?;
+
+    match self.test_session(server, &session_key).await? {
+      Some(session) => Ok(session),
+      None => Err(ScraperError::InvalidCredentials(server).into()),
+    }
+  }
+
+  async fn test_session(
+    &self,
+    server: DinorpgServer,
+    key: &DinorpgSessionKey,
+  ) -> Result<Option<DinorpgSession>, EtwinError> {
+    let now = self.clock.now();
+    let session_user = self
+      .scrape(DinorpgUrls::new(server).root(), Some(key), scraper::scrape_session)
+      .await?;
+    Ok(session_user.map(|session_user| DinorpgSession {
+      ctime: now,
+      atime: now,
+      key: key.clone(),
+      user: session_user.user,
+    }))
+  }
+
   async fn get_profile(&self, user: DinorpgUserIdRef) -> Result<DinorpgProfileResponse, EtwinError> {
-    let html = self.get_html(DinorpgUrls::new(user.server).user(user.id)).await?;
-    let response = scraper::scrape_profile(&html)?;
-    // TODO: Assert username matches
+    let response = self
+      .scrape(DinorpgUrls::new(user.server).user(user.id), None, |html| {
+        scraper::scrape_profile(html, user.id)
+      })
+      .await?;
+    Ok(response)
+  }
+
+  async fn get_dinoz(&self, session: &DinorpgSession, id: DinorpgDinozId) -> Result<DinorpgDinozResponse, EtwinError> {
+    let response = self
+      .scrape(
+        DinorpgUrls::new(session.user.server).dinoz(id),
+        Some(&session.key),
+        scraper::scrape_dinoz,
+      )
+      .await?;
+    if response.session_user.user != session.user {
+      return Err(ScraperError::InvalidSessionKey(session.user.server).into());
+    }
     Ok(response)
   }
 }
 
 #[cfg(feature = "neon")]
-impl<TyClock> neon::prelude::Finalize for HttpDinorpgClient<TyClock> where TyClock: Clock {}
+impl<TyClock, TyLogger> neon::prelude::Finalize for HttpDinorpgClient<TyClock, TyLogger>
+where
+  TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
+{
+}
@@ -0,0 +1,56 @@
+use super::scraper;
+use super::url::DinorpgUrls;
+use ::scraper::Html;
+use etwin_core::dinorpg::{DinorpgProfileResponse, DinorpgServer, DinorpgUserId};
+use etwin_core::types::EtwinError;
+use reqwest::Url;
+use std::str::FromStr;
+
+/// Archived page, parsed with the scraper matching its URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DinorpgArchivedPage {
+  Profile(DinorpgProfileResponse),
+}
+
+/// Parses an archived page fetched from `url`.
+///
+/// Returns `None` if `url` is not a DinoRPG page with a scraper.
+pub fn reparse_page(url: &Url, html: &str) -> Result<Option<DinorpgArchivedPage>, EtwinError> {
+  if !DinorpgServer::iter().any(|server| DinorpgUrls::new(server).root().host() == url.host()) {
+    return Ok(None);
+  }
+  let segments: Vec<&str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+  let page = match segments.as_slice() {
+    ["user", id] if *id != "login" => {
+      let id = DinorpgUserId::from_str(id)?;
+      DinorpgArchivedPage::Profile(scraper::scrape_profile(&Html::parse_document(html), id)?)
+    }
+    _ => return Ok(None),
+  };
+  Ok(Some(page))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{reparse_page, DinorpgArchivedPage};
+  use etwin_core::dinorpg::DinorpgProfileResponse;
+  use reqwest::Url;
+
+  #[test]
+  fn reparse_profile() {
+    let dir = "../../test-resources/scraping/dinorpg/user/fr.1171056.guest";
+    let html = std::fs::read_to_string(format!("{}/main.html", dir)).unwrap();
+    let expected: DinorpgProfileResponse =
+      serde_json::from_str(&std::fs::read_to_string(format!("{}/value.json", dir)).unwrap()).unwrap();
+
+    let url = Url::parse("http://www.dinorpg.com/user/1171056").unwrap();
+    let actual = reparse_page(&url, &html).unwrap();
+    assert_eq!(actual, Some(DinorpgArchivedPage::Profile(expected)));
+  }
+
+  #[test]
+  fn ignore_login_form() {
+    let url = Url::parse("http://www.dinorpg.com/user/login").unwrap();
+    assert_eq!(reparse_page(&url, "").unwrap(), None);
+  }
+}
@@ -1,39 +1,371 @@
 use crate::http::errors::ScraperError;
-use etwin_core::dinorpg::{DinorpgProfile, DinorpgProfileResponse, DinorpgServer, ShortDinorpgUser};
-use scraper::{ElementRef, Html};
+use crate::http::locale::ScraperLocale;
+use etwin_core::dinorpg::{
+  DinorpgClanId, DinorpgClanName, DinorpgDinoz, DinorpgDinozId, DinorpgDinozName, DinorpgDinozRace,
+  DinorpgDinozResponse, DinorpgDinozSkin, DinorpgItemKey, DinorpgLocationName, DinorpgProfile, DinorpgProfileResponse,
+  DinorpgRanking, DinorpgRewardKey, DinorpgServer, DinorpgSessionUser, DinorpgSkillKey, DinorpgStatusKey,
+  DinorpgUserId, DinorpgUserIdRef, ShortDinorpgClan, ShortDinorpgDinoz, ShortDinorpgDinozWithLevel,
+};
+use etwin_scraper_tools::{selector, FlashVars};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::str::FromStr;
+
+/// Regular expression for the Twinoid bootstrap configuration.
+/// Matches `__tid = {lang:'fr',...}`
+static TID_LANG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"__tid\s*=\s*\{\s*lang\s*:\s*'([a-z]{2})'"#).unwrap());
+
+/// Regular expression for the FlashVars parameter of a SWFObject.
+/// Matches `so.addParam("FlashVars","...")`
+static FLASH_VARS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"addParam\("FlashVars",\s*"([^"]*)"\)"#).unwrap());
+
+/// Regular expression for the ranking entry of the profile card.
+/// Matches `31113 ème (133 points)`
+static RANKING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^(\d+)\D*\((\d+)\D*\)$"#).unwrap());
+
+/// Regular expression for the collection reward icons.
+/// Matches `http://data.dinorpg.com/img/icons/collec_perle.gif`
+static REWARD_ICON_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"/collec_([^/.]+)\.gif$"#).unwrap());
+
+/// Regular expression for the icons of skills, status effects and items.
+/// Matches `http://data.dinorpg.com/img/icons/fx_rasca.gif`
+static ICON_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"/([a-z]+)_([a-z0-9_]+)\.gif$"#).unwrap());
+
+/// Regular expression for a trailing decimal number (dinoz level).
+static TRAILING_DECIMAL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(0|[1-9]\d*)$"#).unwrap());
 
 #[derive(Debug)]
 struct ScraperContext {
   server: DinorpgServer,
-  // session: Option<todo!()>,
+  session: Option<DinorpgSessionUser>,
 }
 
-fn scrape_context(_doc: ElementRef) -> Result<ScraperContext, ScraperError> {
-  // TODO: Detect the server
-  let server = DinorpgServer::DinorpgCom;
-  Ok(ScraperContext { server })
+fn scrape_context(doc: ElementRef) -> Result<ScraperContext, ScraperError> {
+  let server = scrape_server(doc)?;
+  let session = scrape_session_user(server, doc)?;
+  Ok(ScraperContext { server, session })
 }
 
-pub(crate) fn scrape_profile(doc: &Html) -> Result<DinorpgProfileResponse, ScraperError> {
+fn scrape_server(doc: ElementRef) -> Result<DinorpgServer, ScraperError> {
+  let lang = doc
+    .select(selector!("script"))
+    .flat_map(|script| script.text())
+    .find_map(|text| TID_LANG_RE.captures(text))
+    .and_then(|caps| caps.get(1))
+    .map(|lang| lang.as_str());
+
+  match lang {
+    Some("fr") => Ok(DinorpgServer::DinorpgCom),
+    Some("en") => Ok(DinorpgServer::EnDinorpgCom),
+    Some("es") => Ok(DinorpgServer::EsDinorpgCom),
+    _ => Err(ScraperError::ServerDetectionFailure),
+  }
+}
+
+/// Scrape the session bar (`#dinozList`), it is empty for guests.
+fn scrape_session_user(server: DinorpgServer, doc: ElementRef) -> Result<Option<DinorpgSessionUser>, ScraperError> {
+  let dinoz_list = doc
+    .select(selector!("#dinozList"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozList)?;
+
+  let dinoz = dinoz_list
+    .select(selector!("a[href^=\"/dino/\"]"))
+    .map(|link| {
+      let href = link.value().attr("href").unwrap_or_default();
+      let id = href.trim_start_matches("/dino/");
+      let id = DinorpgDinozId::from_str(id).map_err(|e| ScraperError::InvalidDinozId(id.to_string(), e))?;
+      let name = scrape_dinoz_name(&link.text().collect::<String>())?;
+      Ok(ShortDinorpgDinoz { server, id, name })
+    })
+    .collect::<Result<Vec<_>, ScraperError>>()?;
+
+  let user = doc
+    .select(selector!("#menu a[href^=\"/user/\"]"))
+    .next()
+    .and_then(|link| link.value().attr("href"));
+
+  let user = match user {
+    Some(href) => {
+      let id = href.trim_start_matches("/user/");
+      let id = DinorpgUserId::from_str(id).map_err(|e| ScraperError::InvalidUserId(id.to_string(), e))?;
+      id.and_server(server)
+    }
+    None => return Ok(None),
+  };
+
+  Ok(Some(DinorpgSessionUser { user, dinoz }))
+}
+
+/// Unnamed dinoz are displayed as `--`
+fn scrape_dinoz_name(text: &str) -> Result<Option<DinorpgDinozName>, ScraperError> {
+  match text.trim() {
+    "--" => Ok(None),
+    name => DinorpgDinozName::from_str(name)
+      .map(Some)
+      .map_err(|e| ScraperError::InvalidDinozName(name.to_string(), e)),
+  }
+}
+
+pub(crate) fn scrape_profile(doc: &Html, user: DinorpgUserId) -> Result<DinorpgProfileResponse, ScraperError> {
   let root = doc.root_element();
 
-  let ScraperContext { server } = scrape_context(root)?;
+  let ScraperContext { server, session } = scrape_context(root)?;
+  let locale = ScraperLocale::get(server);
+
+  let card = root
+    .select(selector!("div.card > dl"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueCard)?;
+
+  let card_entry = |label: &'static str| -> Result<String, ScraperError> {
+    card
+      .select(selector!(":scope > dt"))
+      .zip(card.select(selector!(":scope > dd")))
+      .find(|(dt, _)| dt.text().collect::<String>().trim() == label)
+      .map(|(_, dd)| dd.text().map(str::trim).filter(|t| !t.is_empty()).join(" "))
+      .ok_or(ScraperError::MissingCardEntry(label))
+  };
+
+  let dinoz_count = card_entry(locale.dinoz_count_label)?;
+  let dinoz_count = u32::from_str(&dinoz_count).map_err(|_| ScraperError::InvalidDinozCount(dinoz_count))?;
+
+  let ranking = card_entry(locale.ranking_label)?;
+  let ranking = if ranking.chars().any(|c| c.is_ascii_digit()) {
+    let caps = RANKING_RE
+      .captures(&ranking)
+      .ok_or_else(|| ScraperError::InvalidRanking(ranking.clone()))?;
+    Some(DinorpgRanking {
+      rank: caps[1]
+        .parse()
+        .map_err(|_| ScraperError::InvalidRanking(ranking.clone()))?,
+      points: caps[2]
+        .parse()
+        .map_err(|_| ScraperError::InvalidRanking(ranking.clone()))?,
+    })
+  } else {
+    None
+  };
+
+  let clan = card
+    .select(selector!(":scope > dd > a[href^=\"/clan/\"]"))
+    .next()
+    .map(|link| -> Result<ShortDinorpgClan, ScraperError> {
+      let id = link
+        .value()
+        .attr("href")
+        .unwrap_or_default()
+        .trim_start_matches("/clan/");
+      let id = DinorpgClanId::from_str(id).map_err(|e| ScraperError::InvalidClanId(id.to_string(), e))?;
+      let name = link.text().collect::<String>();
+      let name = DinorpgClanName::from_str(name.trim()).map_err(|e| ScraperError::InvalidClanName(name.clone(), e))?;
+      Ok(ShortDinorpgClan { server, id, name })
+    })
+    .transpose()?;
+
+  let rewards = root
+    .select(selector!("div.reward div.collection img"))
+    .map(|img| {
+      let src = img.value().attr("src").unwrap_or_default();
+      let key = REWARD_ICON_RE
+        .captures(src)
+        .and_then(|caps| caps.get(1))
+        .ok_or_else(|| ScraperError::InvalidRewardIcon(src.to_string()))?
+        .as_str();
+      DinorpgRewardKey::from_str(key).map_err(|e| ScraperError::InvalidRewardKey(key.to_string(), e))
+    })
+    .collect::<Result<Vec<_>, ScraperError>>()?;
+
+  let dinoz = root
+    .select(selector!("#profilBox1 ul.dinList > li"))
+    .map(|li| scrape_profile_dinoz(server, li))
+    .collect::<Result<Vec<_>, ScraperError>>()?;
 
   let profile = DinorpgProfile {
-    user: ShortDinorpgUser {
-      server,
-      id: "0".parse().unwrap(),
-      display_name: "todo".parse().unwrap(),
-    },
+    user: DinorpgUserIdRef { server, id: user },
+    dinoz_count,
+    ranking,
+    clan,
+    rewards,
+    dinoz,
   };
 
-  Ok(DinorpgProfileResponse { profile })
+  Ok(DinorpgProfileResponse {
+    session_user: session,
+    profile,
+  })
+}
+
+fn scrape_profile_dinoz(server: DinorpgServer, li: ElementRef) -> Result<ShortDinorpgDinozWithLevel, ScraperError> {
+  let id = scrape_dinoz_id(li)?;
+  let (skin, race) = scrape_dinoz_skin(li)?;
+
+  let name = li
+    .select(selector!("div.name"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozName)?;
+  let name = scrape_dinoz_name(&name.text().collect::<String>())?;
+
+  let level = li
+    .select(selector!("div.misc"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozLevel)?;
+  let level = scrape_dinoz_level(level)?;
+
+  Ok(ShortDinorpgDinozWithLevel {
+    server,
+    id,
+    name,
+    race,
+    skin,
+    level,
+  })
+}
+
+/// Read the dinoz id from its Flash container (`#swf_dino_<id>`)
+fn scrape_dinoz_id(parent: ElementRef) -> Result<DinorpgDinozId, ScraperError> {
+  let id = parent
+    .select(selector!("div.swf[id^=\"swf_dino_\"]"))
+    .next()
+    .and_then(|swf| swf.value().id())
+    .ok_or(ScraperError::MissingDinozSwf)?
+    .trim_start_matches("swf_dino_");
+  DinorpgDinozId::from_str(id).map_err(|e| ScraperError::InvalidDinozId(id.to_string(), e))
+}
+
+/// Read the skin from the `data` FlashVar of the dinoz renderer, and derive the race from it
+fn scrape_dinoz_skin(parent: ElementRef) -> Result<(DinorpgDinozSkin, DinorpgDinozRace), ScraperError> {
+  let skin = parent
+    .select(selector!("script"))
+    .flat_map(|script| script.text())
+    .find_map(|text| FLASH_VARS_RE.captures(text))
+    .and_then(|caps| caps.get(1))
+    .and_then(|vars| {
+      FlashVars::new(vars.as_str())
+        .into_iter()
+        .find(|(key, _)| *key == "data")
+        .map(|(_, value)| value)
+    })
+    .ok_or(ScraperError::MissingDinozSkin)?;
+  let skin = DinorpgDinozSkin::from_str(skin).map_err(|e| ScraperError::InvalidDinozSkin(skin.to_string(), e))?;
+  let race = skin
+    .race()
+    .ok_or_else(|| ScraperError::UnknownDinozRace(skin.as_str().to_string()))?;
+  Ok((skin, race))
+}
+
+/// The level is the trailing number of the text (`Moueffe niveau 25`, `Niveau 25`)
+fn scrape_dinoz_level(node: ElementRef) -> Result<u16, ScraperError> {
+  let level = node.text().collect::<String>();
+  TRAILING_DECIMAL_RE
+    .find(level.trim())
+    .and_then(|level| level.as_str().parse().ok())
+    .ok_or(ScraperError::InvalidDinozLevel(level))
+}
+
+/// Collect the keys of the `<prefix>_<key>.gif` icons found in `parent`
+fn scrape_icon_keys<'a, T>(
+  parent: ElementRef<'a>,
+  prefix: &str,
+  parse: impl Fn(&'a str) -> Result<T, ScraperError>,
+) -> Result<Vec<T>, ScraperError> {
+  parent
+    .select(selector!("img"))
+    .filter_map(|img| img.value().attr("src"))
+    .map(|src| match ICON_RE.captures(src) {
+      Some(caps) if &caps[1] == prefix => Ok(caps.get(2).map(|key| key.as_str())),
+      Some(_) => Ok(None),
+      None => Err(ScraperError::InvalidIcon(src.to_string())),
+    })
+    .filter_map(Result::transpose)
+    .map(|key| key.and_then(&parse))
+    .collect()
+}
+
+/// Session info for a logged-in page, `None` for guests
+pub(crate) fn scrape_session(doc: &Html) -> Result<Option<DinorpgSessionUser>, ScraperError> {
+  Ok(scrape_context(doc.root_element())?.session)
+}
+
+pub(crate) fn scrape_dinoz(doc: &Html) -> Result<DinorpgDinozResponse, ScraperError> {
+  let root = doc.root_element();
+
+  let ScraperContext { server, session } = scrape_context(root)?;
+  let session_user = session.ok_or(ScraperError::MissingSessionUser)?;
+
+  let sheet = root
+    .select(selector!("div.dinoSheet"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozSheet)?;
+
+  let id = scrape_dinoz_id(sheet)?;
+  let (skin, race) = scrape_dinoz_skin(sheet)?;
+
+  let name = sheet
+    .select(selector!("h1.name"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozName)?;
+  let name = scrape_dinoz_name(&name.text().collect::<String>())?;
+
+  let level = sheet
+    .select(selector!(".level"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozLevel)?;
+  let level = scrape_dinoz_level(level)?;
+
+  let location = sheet
+    .select(selector!(".place strong"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueDinozLocation)?
+    .text()
+    .collect::<String>();
+  let location = DinorpgLocationName::from_str(location.trim())
+    .map_err(|e| ScraperError::InvalidLocationName(location.clone(), e))?;
+
+  let mut skills = Vec::new();
+  for list in sheet.select(selector!(".skills")) {
+    skills.extend(scrape_icon_keys(list, "skill", |key| {
+      DinorpgSkillKey::from_str(key).map_err(|e| ScraperError::InvalidSkillKey(key.to_string(), e))
+    })?);
+  }
+
+  let mut status = Vec::new();
+  for list in sheet.select(selector!(".status")) {
+    status.extend(scrape_icon_keys(list, "fx", |key| {
+      DinorpgStatusKey::from_str(key).map_err(|e| ScraperError::InvalidStatusKey(key.to_string(), e))
+    })?);
+  }
+
+  let mut equipment = Vec::new();
+  for list in sheet.select(selector!(".equip")) {
+    equipment.extend(scrape_icon_keys(list, "obj", |key| {
+      DinorpgItemKey::from_str(key).map_err(|e| ScraperError::InvalidItemKey(key.to_string(), e))
+    })?);
+  }
+
+  Ok(DinorpgDinozResponse {
+    session_user,
+    dinoz: DinorpgDinoz {
+      server,
+      id,
+      name,
+      race,
+      skin,
+      level,
+      location,
+      skills,
+      status,
+      equipment,
+    },
+  })
 }
 
 #[cfg(test)]
 mod test {
-  use crate::http::scraper::scrape_profile;
-  use etwin_core::dinorpg::DinorpgProfileResponse;
+  use crate::http::scraper::{scrape_dinoz, scrape_profile};
+  use etwin_core::dinorpg::{DinorpgDinozResponse, DinorpgProfileResponse, DinorpgUserId};
   use scraper::Html;
   use std::path::{Path, PathBuf};
   use test_generator::test_resources;
@@ -45,11 +377,20 @@ mod test {
     let html_path = path.join("main.html");
     let actual_path = path.join("rs.actual.json");
 
+    // Directory names are `<lang>.<user_id>.<description>`
+    let user_id: DinorpgUserId = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .and_then(|name| name.split('.').nth(1))
+      .expect("Failed to read user id from directory name")
+      .parse()
+      .expect("Invalid user id in directory name");
+
     let raw_html = ::std::fs::read_to_string(html_path).expect("Failed to read html file");
 
     let html = Html::parse_document(&raw_html);
 
-    let actual = scrape_profile(&html).unwrap();
+    let actual = scrape_profile(&html, user_id).unwrap();
     let actual_json = serde_json::to_string_pretty(&actual).unwrap();
     ::std::fs::write(actual_path, format!("{}\n", actual_json)).expect("Failed to write actual file");
 
@@ -58,4 +399,25 @@ mod test {
 
     assert_eq!(actual, expected);
   }
+
+  #[test_resources("./test-resources/scraping/dinorpg/dinoz/*/")]
+  fn test_scrape_dinoz(path: &str) {
+    let path: PathBuf = Path::join(Path::new("../.."), path);
+    let value_path = path.join("value.json");
+    let html_path = path.join("main.html");
+    let actual_path = path.join("rs.actual.json");
+
+    let raw_html = ::std::fs::read_to_string(html_path).expect("Failed to read html file");
+
+    let html = Html::parse_document(&raw_html);
+
+    let actual = scrape_dinoz(&html).unwrap();
+    let actual_json = serde_json::to_string_pretty(&actual).unwrap();
+    ::std::fs::write(actual_path, format!("{}\n", actual_json)).expect("Failed to write actual file");
+
+    let value_json = ::std::fs::read_to_string(value_path).expect("Failed to read value file");
+    let expected = serde_json::from_str::<DinorpgDinozResponse>(&value_json).expect("Failed to parse value file");
+
+    assert_eq!(actual, expected);
+  }
 }
@@ -1,4 +1,4 @@
-use etwin_core::dinorpg::{DinorpgServer, DinorpgUserId};
+use etwin_core::dinorpg::{DinorpgDinozId, DinorpgServer, DinorpgUserId};
 use reqwest::Url;
 
 pub struct DinorpgUrls {
@@ -13,7 +13,7 @@ impl DinorpgUrls {
       DinorpgServer::EsDinorpgCom => "http://es.dinorpg.com/",
     };
     Self {
-      root: Url::parse(root).expect("failed to parse popotamo root URL"),
+      root: Url::parse(root).expect("failed to parse dinorpg root URL"),
     }
   }
 
@@ -23,6 +23,19 @@ impl DinorpgUrls {
     url
   }
 
+  pub fn root(&self) -> Url {
+    self.root.clone()
+  }
+
+  /// Login form, shared with the Twinoid widget
+  pub fn login(&self) -> Url {
+    self.make_url(&["user", "login"])
+  }
+
+  pub fn dinoz(&self, dinoz_id: DinorpgDinozId) -> Url {
+    dinoz_id.with_str(|dinoz_id| self.make_url(&["dino", dinoz_id]))
+  }
+
   pub fn user(&self, user_id: DinorpgUserId) -> Url {
     user_id.with_str(|user_id| self.make_url(&["user", user_id]))
   }
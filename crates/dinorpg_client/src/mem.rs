@@ -1 +1,410 @@
-// TODO: MemPopotamoClient
+use async_trait::async_trait;
+use etwin_core::clock::Clock;
+use etwin_core::core::Instant;
+use etwin_core::dinorpg::{
+  DinorpgClient, DinorpgCredentials, DinorpgDinoz, DinorpgDinozId, DinorpgDinozResponse, DinorpgProfile,
+  DinorpgProfileResponse, DinorpgServer, DinorpgSession, DinorpgSessionKey, DinorpgSessionUser, DinorpgUserId,
+  DinorpgUserIdRef, ShortDinorpgDinoz, ShortDinorpgDinozWithLevel,
+};
+use etwin_core::twinoid::{TwinoidLogin, TwinoidPassword};
+use etwin_core::types::EtwinError;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use std::sync::RwLock;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error("Invalid credentials")]
+  InvalidCredentials,
+  #[error("Invalid session key for server: {:?}", .0)]
+  InvalidSessionKey(DinorpgServer),
+  #[error("Server not found: {:?}", .0)]
+  ServerNotFound(DinorpgServer),
+  #[error("User not found: {:?}", .0)]
+  UserNotFound(DinorpgUserId),
+  #[error("Dinoz not found: {:?}", .0)]
+  DinozNotFound(DinorpgDinozId),
+}
+
+#[derive(Clone, Debug)]
+struct MemSession {
+  key: DinorpgSessionKey,
+  created_at: Instant,
+  user_id: DinorpgUserId,
+}
+
+#[derive(Clone, Debug)]
+struct MemUser {
+  id: DinorpgUserId,
+  login: TwinoidLogin,
+  password: TwinoidPassword,
+  /// Dinoz owned by the user, in display order
+  dinoz: Vec<DinorpgDinozId>,
+}
+
+#[derive(Clone, Debug)]
+struct MemServer {
+  users: HashMap<DinorpgUserId, MemUser>,
+  dinoz: HashMap<DinorpgDinozId, DinorpgDinoz>,
+  sessions: HashMap<DinorpgSessionKey, MemSession>,
+  sessions_by_user_id: HashMap<DinorpgUserId, DinorpgSessionKey>,
+}
+
+impl MemServer {
+  fn new() -> Self {
+    Self {
+      users: HashMap::new(),
+      dinoz: HashMap::new(),
+      sessions: HashMap::new(),
+      sessions_by_user_id: HashMap::new(),
+    }
+  }
+
+  fn create_user(&mut self, id: DinorpgUserId, login: TwinoidLogin, password: TwinoidPassword) {
+    if self.users.values().any(|u| u.login == login) {
+      panic!("TwinoidLogin conflict");
+    }
+    match self.users.entry(id) {
+      Entry::Occupied(_) => panic!("DinorpgUserId conflict"),
+      Entry::Vacant(e) => e.insert(MemUser {
+        id,
+        login,
+        password,
+        dinoz: Vec::new(),
+      }),
+    };
+  }
+
+  fn create_dinoz(&mut self, owner: DinorpgUserId, dinoz: DinorpgDinoz) {
+    let user = self.users.get_mut(&owner).expect("DinorpgUserId not found");
+    match self.dinoz.entry(dinoz.id) {
+      Entry::Occupied(_) => panic!("DinorpgDinozId conflict"),
+      Entry::Vacant(e) => {
+        user.dinoz.push(dinoz.id);
+        e.insert(dinoz);
+      }
+    };
+  }
+
+  fn create_session(
+    &mut self,
+    time: Instant,
+    login: &TwinoidLogin,
+    password: &TwinoidPassword,
+  ) -> Result<MemSession, Error> {
+    let user_id = self
+      .users
+      .values()
+      .find(|u| &u.login == login && &u.password == password)
+      .map(|u| u.id)
+      .ok_or(Error::InvalidCredentials)?;
+
+    if let Some(old_session_key) = self.sessions_by_user_id.remove(&user_id) {
+      self.sessions.remove(&old_session_key);
+    }
+
+    let session = MemSession {
+      key: make_session_key(),
+      created_at: time,
+      user_id,
+    };
+    self.sessions.insert(session.key.clone(), session.clone());
+    self.sessions_by_user_id.insert(user_id, session.key.clone());
+    Ok(session)
+  }
+
+  fn to_session_user(&self, server: DinorpgServer, user: &MemUser) -> DinorpgSessionUser {
+    DinorpgSessionUser {
+      user: user.id.and_server(server),
+      dinoz: user
+        .dinoz
+        .iter()
+        .map(|id| {
+          let dinoz = &self.dinoz[id];
+          ShortDinorpgDinoz {
+            server,
+            id: dinoz.id,
+            name: dinoz.name.clone(),
+          }
+        })
+        .collect(),
+    }
+  }
+}
+
+pub struct MemDinorpgClient<TyClock> {
+  clock: TyClock,
+  state: RwLock<HashMap<DinorpgServer, MemServer>>,
+}
+
+impl<TyClock> MemDinorpgClient<TyClock> {
+  pub fn new(clock: TyClock) -> Self
+  where
+    TyClock: Clock,
+  {
+    let mut servers = HashMap::new();
+    for server in DinorpgServer::iter() {
+      servers.insert(server, MemServer::new());
+    }
+    Self {
+      clock,
+      state: RwLock::new(servers),
+    }
+  }
+
+  pub fn create_user(&self, server: DinorpgServer, id: DinorpgUserId, login: TwinoidLogin, password: TwinoidPassword) {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for dinorpg client state");
+    state.get_mut(&server).unwrap().create_user(id, login, password)
+  }
+
+  /// Adds a dinoz to the dinoz list of `owner`.
+  pub fn create_dinoz(&self, owner: DinorpgUserIdRef, dinoz: DinorpgDinoz) {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for dinorpg client state");
+    state.get_mut(&owner.server).unwrap().create_dinoz(owner.id, dinoz)
+  }
+}
+
+#[async_trait]
+impl<TyClock> DinorpgClient for MemDinorpgClient<TyClock>
+where
+  TyClock: Clock,
+{
+  async fn create_session(&self, credentials: &DinorpgCredentials) -> Result<DinorpgSession, EtwinError> {
+    let (server, login, password) = match credentials {
+      DinorpgCredentials::Twinoid {
+        server,
+        login,
+        password,
+      } => (*server, login, password),
+      DinorpgCredentials::SessionKey { server, key } => {
+        return match self.test_session(*server, key).await? {
+          Some(session) => Ok(session),
+          None => Err(Error::InvalidSessionKey(*server).into()),
+        };
+      }
+    };
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for dinorpg client state");
+    let srv = state.get_mut(&server).ok_or(Error::ServerNotFound(server))?;
+    let session = srv.create_session(self.clock.now(), login, password)?;
+    Ok(DinorpgSession {
+      key: session.key,
+      user: session.user_id.and_server(server),
+      ctime: session.created_at,
+      atime: session.created_at,
+    })
+  }
+
+  async fn test_session(
+    &self,
+    server: DinorpgServer,
+    key: &DinorpgSessionKey,
+  ) -> Result<Option<DinorpgSession>, EtwinError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for dinorpg client state");
+    let srv = state.get(&server).ok_or(Error::ServerNotFound(server))?;
+    Ok(srv.sessions.get(key).map(|session| DinorpgSession {
+      key: session.key.clone(),
+      user: session.user_id.and_server(server),
+      ctime: session.created_at,
+      atime: self.clock.now(),
+    }))
+  }
+
+  async fn get_profile(&self, user: DinorpgUserIdRef) -> Result<DinorpgProfileResponse, EtwinError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for dinorpg client state");
+    let srv = state.get(&user.server).ok_or(Error::ServerNotFound(user.server))?;
+    let mem_user = srv.users.get(&user.id).ok_or(Error::UserNotFound(user.id))?;
+    let dinoz: Vec<_> = mem_user
+      .dinoz
+      .iter()
+      .map(|id| {
+        let dinoz = &srv.dinoz[id];
+        ShortDinorpgDinozWithLevel {
+          server: dinoz.server,
+          id: dinoz.id,
+          name: dinoz.name.clone(),
+          race: dinoz.race,
+          skin: dinoz.skin.clone(),
+          level: dinoz.level,
+        }
+      })
+      .collect();
+    Ok(DinorpgProfileResponse {
+      session_user: None,
+      profile: DinorpgProfile {
+        user,
+        dinoz_count: u32::try_from(dinoz.len()).unwrap(),
+        ranking: None,
+        clan: None,
+        rewards: Vec::new(),
+        dinoz,
+      },
+    })
+  }
+
+  async fn get_dinoz(&self, session: &DinorpgSession, id: DinorpgDinozId) -> Result<DinorpgDinozResponse, EtwinError> {
+    let server = session.user.server;
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for dinorpg client state");
+    let srv = state.get(&server).ok_or(Error::ServerNotFound(server))?;
+    let mem_session = srv.sessions.get(&session.key).ok_or(Error::InvalidSessionKey(server))?;
+    let user = &srv.users[&mem_session.user_id];
+    // Only the dinoz of the session user can be viewed
+    if !user.dinoz.contains(&id) {
+      return Err(Error::DinozNotFound(id).into());
+    }
+    Ok(DinorpgDinozResponse {
+      session_user: srv.to_session_user(server, user),
+      dinoz: srv.dinoz[&id].clone(),
+    })
+  }
+}
+
+fn make_session_key() -> DinorpgSessionKey {
+  use rand::seq::SliceRandom;
+
+  const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+  let mut rng = rand::thread_rng();
+
+  let key: String = std::iter::from_fn(|| CHARS.choose(&mut rng).copied())
+    .map(char::from)
+    .take(32)
+    .collect();
+
+  DinorpgSessionKey::from_str(&key).expect("invalid session key")
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock> neon::prelude::Finalize for MemDinorpgClient<TyClock> where TyClock: Clock {}
+
+#[cfg(test)]
+mod test {
+  use super::MemDinorpgClient;
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::dinorpg::{DinorpgClient, DinorpgCredentials, DinorpgServer, DinorpgSessionKey, DinorpgUserId};
+  use etwin_core::twinoid::{TwinoidLogin, TwinoidPassword};
+  use std::str::FromStr;
+
+  fn make_client() -> MemDinorpgClient<VirtualClock> {
+    let client = MemDinorpgClient::new(VirtualClock::new(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)));
+    client.create_user(
+      DinorpgServer::DinorpgCom,
+      DinorpgUserId::from_str("123").unwrap(),
+      TwinoidLogin::new(String::from("alice")),
+      TwinoidPassword::new(String::from("aaaaa")),
+    );
+    client
+  }
+
+  #[tokio::test]
+  async fn create_and_test_session() {
+    let client = make_client();
+    let session = client
+      .create_session(&DinorpgCredentials::Twinoid {
+        server: DinorpgServer::DinorpgCom,
+        login: TwinoidLogin::new(String::from("alice")),
+        password: TwinoidPassword::new(String::from("aaaaa")),
+      })
+      .await
+      .unwrap();
+    assert_eq!(
+      session.user,
+      DinorpgUserId::from_str("123")
+        .unwrap()
+        .and_server(DinorpgServer::DinorpgCom)
+    );
+
+    let tested = client
+      .test_session(DinorpgServer::DinorpgCom, &session.key)
+      .await
+      .unwrap();
+    assert_eq!(tested, Some(session.clone()));
+    let resumed = client
+      .create_session(&DinorpgCredentials::SessionKey {
+        server: DinorpgServer::DinorpgCom,
+        key: session.key.clone(),
+      })
+      .await
+      .unwrap();
+    assert_eq!(resumed, session);
+    // Sessions are per server
+    let other_server = client
+      .test_session(DinorpgServer::EnDinorpgCom, &session.key)
+      .await
+      .unwrap();
+    assert_eq!(other_server, None);
+  }
+
+  #[tokio::test]
+  async fn reject_invalid_credentials() {
+    let client = make_client();
+    let wrong_password = client
+      .create_session(&DinorpgCredentials::Twinoid {
+        server: DinorpgServer::DinorpgCom,
+        login: TwinoidLogin::new(String::from("alice")),
+        password: TwinoidPassword::new(String::from("bbbbb")),
+      })
+      .await;
+    assert!(wrong_password.is_err());
+    let unknown_key = DinorpgSessionKey::from_str("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa").unwrap();
+    assert_eq!(
+      client
+        .test_session(DinorpgServer::DinorpgCom, &unknown_key)
+        .await
+        .unwrap(),
+      None
+    );
+    let unknown_key = client
+      .create_session(&DinorpgCredentials::SessionKey {
+        server: DinorpgServer::DinorpgCom,
+        key: unknown_key,
+      })
+      .await;
+    assert!(unknown_key.is_err());
+  }
+
+  #[tokio::test]
+  async fn new_session_revokes_previous_one() {
+    let client = make_client();
+    let credentials = DinorpgCredentials::Twinoid {
+      server: DinorpgServer::DinorpgCom,
+      login: TwinoidLogin::new(String::from("alice")),
+      password: TwinoidPassword::new(String::from("aaaaa")),
+    };
+    let first = client.create_session(&credentials).await.unwrap();
+    let second = client.create_session(&credentials).await.unwrap();
+    assert_ne!(first.key, second.key);
+    assert_eq!(
+      client
+        .test_session(DinorpgServer::DinorpgCom, &first.key)
+        .await
+        .unwrap(),
+      None
+    );
+    assert!(client
+      .test_session(DinorpgServer::DinorpgCom, &second.key)
+      .await
+      .unwrap()
+      .is_some());
+  }
+}
@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use etwin_core::clock::Clock;
+use etwin_core::core::Instant;
+use etwin_core::dinorpg::{
+  ArchivedDinorpgUser, DinorpgProfile, DinorpgProfileResponse, DinorpgStore, DinorpgUserIdRef, GetDinorpgUserOptions,
+  ShortDinorpgUser,
+};
+use etwin_core::temporal::{LatestTemporal, SnapshotLog};
+use etwin_core::types::EtwinError;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+struct StoreState {
+  users: HashMap<DinorpgUserIdRef, ArchivedDinorpgUser>,
+  profiles: HashMap<DinorpgUserIdRef, SnapshotLog<DinorpgProfile>>,
+}
+
+impl StoreState {
+  fn new() -> Self {
+    Self {
+      users: HashMap::new(),
+      profiles: HashMap::new(),
+    }
+  }
+
+  fn get_user(&self, id: &DinorpgUserIdRef) -> Option<&ArchivedDinorpgUser> {
+    self.users.get(id)
+  }
+
+  fn touch_user(&mut self, time: Instant, short: &ShortDinorpgUser) -> ArchivedDinorpgUser {
+    let user = self.users.entry(short.as_ref()).or_insert_with(|| ArchivedDinorpgUser {
+      server: short.server,
+      id: short.id,
+      archived_at: time,
+      display_name: short.display_name.clone(),
+    });
+    user.display_name = short.display_name.clone();
+    user.clone()
+  }
+
+  fn touch_profile(&mut self, time: Instant, profile: &DinorpgProfile) -> Result<(), EtwinError> {
+    if !self.users.contains_key(&profile.user) {
+      return Err("DinorpgUserNotFound".into());
+    }
+    self
+      .profiles
+      .entry(profile.user)
+      .or_insert_with(SnapshotLog::new)
+      .snapshot(time, profile.clone());
+    Ok(())
+  }
+}
+
+pub struct MemDinorpgStore<TyClock: Clock> {
+  clock: TyClock,
+  state: RwLock<StoreState>,
+}
+
+impl<TyClock> MemDinorpgStore<TyClock>
+where
+  TyClock: Clock,
+{
+  pub fn new(clock: TyClock) -> Self {
+    Self {
+      clock,
+      state: RwLock::new(StoreState::new()),
+    }
+  }
+}
+
+#[async_trait]
+impl<TyClock> DinorpgStore for MemDinorpgStore<TyClock>
+where
+  TyClock: Clock,
+{
+  async fn touch_short_user(&self, short: &ShortDinorpgUser) -> Result<ArchivedDinorpgUser, EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    Ok(state.touch_user(now, short))
+  }
+
+  async fn touch_profile(&self, response: &DinorpgProfileResponse) -> Result<(), EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    state.touch_profile(now, &response.profile)
+  }
+
+  async fn get_short_user(&self, options: &GetDinorpgUserOptions) -> Result<Option<ShortDinorpgUser>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(
+      state
+        .get_user(&options.id.and_server(options.server))
+        .cloned()
+        .map(From::from),
+    )
+  }
+
+  async fn get_user(&self, options: &GetDinorpgUserOptions) -> Result<Option<ArchivedDinorpgUser>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(state.get_user(&options.id.and_server(options.server)).cloned())
+  }
+
+  async fn get_profile(
+    &self,
+    options: &GetDinorpgUserOptions,
+  ) -> Result<Option<LatestTemporal<DinorpgProfile>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    Ok(
+      state
+        .profiles
+        .get(&options.id.and_server(options.server))
+        .and_then(|profiles| profiles.at(time))
+        .map(|latest| LatestTemporal {
+          latest: latest.cloned(),
+        }),
+    )
+  }
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock> neon::prelude::Finalize for MemDinorpgStore<TyClock> where TyClock: Clock {}
+
+#[cfg(test)]
+mod test {
+  use crate::mem::MemDinorpgStore;
+  use crate::test::TestApi;
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::dinorpg::DinorpgStore;
+  use std::sync::Arc;
+
+  fn make_test_api() -> TestApi<Arc<VirtualClock>, Arc<dyn DinorpgStore>> {
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
+    let dinorpg_store: Arc<dyn DinorpgStore> = Arc::new(MemDinorpgStore::new(Arc::clone(&clock)));
+
+    TestApi { clock, dinorpg_store }
+  }
+
+  #[tokio::test]
+  async fn test_empty() {
+    crate::test::test_empty(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_touch_user() {
+    crate::test::test_touch_user(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_get_missing_user() {
+    crate::test::test_get_missing_user(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_touch_profile() {
+    crate::test::test_touch_profile(make_test_api()).await;
+  }
+}
@@ -0,0 +1,580 @@
+use async_trait::async_trait;
+use etwin_core::api::ApiRef;
+use etwin_core::clock::Clock;
+use etwin_core::core::{Instant, PeriodLower};
+use etwin_core::dinorpg::{
+  ArchivedDinorpgUser, DinorpgClanId, DinorpgClanName, DinorpgDinozId, DinorpgDinozName, DinorpgDinozRace,
+  DinorpgDinozSkin, DinorpgProfile, DinorpgProfileResponse, DinorpgRanking, DinorpgRewardKey, DinorpgServer,
+  DinorpgStore, DinorpgUserId, DinorpgUserIdRef, GetDinorpgUserOptions, ShortDinorpgClan, ShortDinorpgDinozWithLevel,
+  ShortDinorpgUser,
+};
+use etwin_core::pg_num::{PgU16, PgU32};
+use etwin_core::temporal::{ForeignRetrieved, ForeignSnapshot, LatestTemporal};
+use etwin_core::twinoid::TwinoidUserDisplayName;
+use etwin_core::types::EtwinError;
+use etwin_populate::dinorpg::populate_dinorpg;
+use etwin_postgres_tools::upsert_archive_query;
+use sqlx::postgres::PgQueryResult;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
+use std::error::Error;
+
+fn box_sqlx_error(e: sqlx::Error) -> Box<dyn Error + Send> {
+  Box::new(e)
+}
+
+pub struct PgDinorpgStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+  clock: TyClock,
+  database: TyDatabase,
+}
+
+impl<TyClock, TyDatabase> PgDinorpgStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+  pub async fn new(clock: TyClock, database: TyDatabase) -> Result<Self, Box<dyn Error + Send>> {
+    let mut tx = database.as_ref().begin().await.map_err(box_sqlx_error)?;
+    populate_dinorpg(&mut tx).await?;
+    tx.commit().await.map_err(box_sqlx_error)?;
+    Ok(Self { clock, database })
+  }
+}
+
+#[async_trait]
+impl<TyClock, TyDatabase> DinorpgStore for PgDinorpgStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+  async fn touch_short_user(&self, short: &ShortDinorpgUser) -> Result<ArchivedDinorpgUser, EtwinError> {
+    let now = self.clock.now();
+    let mut tx = self.database.as_ref().begin().await?;
+    let archived_at = touch_dinorpg_user(&mut tx, now, short).await?;
+    tx.commit().await?;
+    Ok(ArchivedDinorpgUser {
+      server: short.server,
+      id: short.id,
+      archived_at,
+      display_name: short.display_name.clone(),
+    })
+  }
+
+  async fn touch_profile(&self, response: &DinorpgProfileResponse) -> Result<(), EtwinError> {
+    let now = self.clock.now();
+    let profile = &response.profile;
+    let mut tx = self.database.as_ref().begin().await?;
+    if !has_dinorpg_user(&mut tx, profile.user).await? {
+      return Err("DinorpgUserNotFound".into());
+    }
+    if let Some(clan) = profile.clan.as_ref() {
+      touch_dinorpg_clan(&mut tx, now, clan).await?;
+    }
+    for dinoz in profile.dinoz.iter() {
+      touch_dinorpg_dinoz(&mut tx, now, dinoz).await?;
+    }
+    touch_dinorpg_user_profile(&mut tx, now, profile).await?;
+    for (offset, reward) in profile.rewards.iter().enumerate() {
+      let offset = u32::try_from(offset).unwrap();
+      touch_dinorpg_user_reward(&mut tx, now, profile.user, offset, reward).await?;
+    }
+    for (offset, dinoz) in profile.dinoz.iter().enumerate() {
+      let offset = u32::try_from(offset).unwrap();
+      touch_dinorpg_user_dinoz_item(&mut tx, now, profile.user, offset, dinoz).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn get_short_user(&self, options: &GetDinorpgUserOptions) -> Result<Option<ShortDinorpgUser>, EtwinError> {
+    Ok(self.get_user(options).await?.map(From::from))
+  }
+
+  async fn get_user(&self, options: &GetDinorpgUserOptions) -> Result<Option<ArchivedDinorpgUser>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      dinorpg_server: DinorpgServer,
+      dinorpg_user_id: DinorpgUserId,
+      archived_at: Instant,
+      display_name: TwinoidUserDisplayName,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT dinorpg_server, dinorpg_user_id, archived_at, display_name
+      FROM dinorpg_users
+      WHERE dinorpg_server = $1::DINORPG_SERVER AND dinorpg_user_id = $2::DINORPG_USER_ID;
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    Ok(row.map(|r| ArchivedDinorpgUser {
+      server: r.dinorpg_server,
+      id: r.dinorpg_user_id,
+      archived_at: r.archived_at,
+      display_name: r.display_name,
+    }))
+  }
+
+  async fn get_profile(
+    &self,
+    options: &GetDinorpgUserOptions,
+  ) -> Result<Option<LatestTemporal<DinorpgProfile>>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    let mut tx = self.database.as_ref().begin().await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      period_start: Instant,
+      period_end: Option<Instant>,
+      retrieved_at: Vec<Instant>,
+      dinoz_count: PgU32,
+      ranking_rank: Option<PgU32>,
+      ranking_points: Option<PgU32>,
+      dinorpg_clan_id: Option<DinorpgClanId>,
+      clan_name: Option<DinorpgClanName>,
+      reward_count: PgU32,
+      dinoz_list_count: PgU32,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT lower(period) AS period_start, upper(period) AS period_end, retrieved_at::TIMESTAMPTZ[] AS retrieved_at,
+        dinoz_count, ranking_rank, ranking_points, dinorpg_clan_id, dinorpg_clans.name AS clan_name,
+        reward_count, dinoz_list_count
+      FROM dinorpg_user_profiles
+        LEFT OUTER JOIN dinorpg_clans USING (dinorpg_server, dinorpg_clan_id)
+      WHERE
+        dinorpg_server = $1::DINORPG_SERVER
+        AND dinorpg_user_id = $2::DINORPG_USER_ID
+        AND lower(period) <= $3::INSTANT
+      ORDER BY lower(period) DESC
+      LIMIT 1;
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .bind(time)
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let row = match row {
+      Some(row) => row,
+      None => return Ok(None),
+    };
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct RewardRow {
+      period_start: Instant,
+      period_end: Option<Instant>,
+      reward_key: DinorpgRewardKey,
+    }
+
+    let reward_rows: Vec<RewardRow> = sqlx::query_as::<_, RewardRow>(
+      r"
+      SELECT DISTINCT ON (offset_in_list) lower(period) AS period_start, upper(period) AS period_end, reward_key
+      FROM dinorpg_user_rewards
+      WHERE
+        dinorpg_server = $1::DINORPG_SERVER
+        AND dinorpg_user_id = $2::DINORPG_USER_ID
+        AND lower(period) <= $3::INSTANT
+        AND offset_in_list < $4::U32
+      ORDER BY offset_in_list, lower(period) DESC;
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .bind(time)
+    .bind(row.reward_count)
+    .fetch_all(&mut tx)
+    .await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct DinozRow {
+      period_start: Instant,
+      period_end: Option<Instant>,
+      dinorpg_dinoz_id: DinorpgDinozId,
+      name: Option<DinorpgDinozName>,
+      race: DinorpgDinozRace,
+      skin: DinorpgDinozSkin,
+      level: PgU16,
+    }
+
+    let dinoz_rows: Vec<DinozRow> = sqlx::query_as::<_, DinozRow>(
+      r"
+      SELECT DISTINCT ON (offset_in_list) lower(period) AS period_start, upper(period) AS period_end,
+        dinorpg_dinoz_id, name, race, skin, level
+      FROM dinorpg_user_dinoz
+      WHERE
+        dinorpg_server = $1::DINORPG_SERVER
+        AND dinorpg_user_id = $2::DINORPG_USER_ID
+        AND lower(period) <= $3::INSTANT
+        AND offset_in_list < $4::U32
+      ORDER BY offset_in_list, lower(period) DESC;
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .bind(time)
+    .bind(row.dinoz_list_count)
+    .fetch_all(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // The profile is split across multiple tables: it is valid while all of its rows are valid.
+    let mut start = row.period_start;
+    let mut end = row.period_end;
+    let list_periods = reward_rows
+      .iter()
+      .map(|r| (r.period_start, r.period_end))
+      .chain(dinoz_rows.iter().map(|r| (r.period_start, r.period_end)));
+    for (row_start, row_end) in list_periods {
+      start = Instant::max(start, row_start);
+      end = match (end, row_end) {
+        (Some(end), Some(row_end)) => Some(Instant::min(end, row_end)),
+        (end, row_end) => end.or(row_end),
+      };
+    }
+    let latest = row
+      .retrieved_at
+      .iter()
+      .copied()
+      .filter(|t| end.is_none_or(|end| *t < end))
+      .max()
+      .expect("the profile must be retrieved during its period");
+
+    let ranking = match (row.ranking_rank, row.ranking_points) {
+      (Some(rank), Some(points)) => Some(DinorpgRanking {
+        rank: rank.into(),
+        points: points.into(),
+      }),
+      (None, None) => None,
+      _ => unreachable!(),
+    };
+    let clan = match (row.dinorpg_clan_id, row.clan_name) {
+      (Some(id), Some(name)) => Some(ShortDinorpgClan {
+        server: options.server,
+        id,
+        name,
+      }),
+      (None, None) => None,
+      _ => unreachable!(),
+    };
+
+    Ok(Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::new(start, end),
+        retrieved: ForeignRetrieved { latest },
+        value: DinorpgProfile {
+          user: options.id.and_server(options.server),
+          dinoz_count: row.dinoz_count.into(),
+          ranking,
+          clan,
+          rewards: reward_rows.into_iter().map(|r| r.reward_key).collect(),
+          dinoz: dinoz_rows
+            .into_iter()
+            .map(|r| ShortDinorpgDinozWithLevel {
+              server: options.server,
+              id: r.dinorpg_dinoz_id,
+              name: r.name,
+              race: r.race,
+              skin: r.skin,
+              level: r.level.into(),
+            })
+            .collect(),
+        },
+      },
+    }))
+  }
+}
+
+/// Inserts or updates a DinoRPG user, returns the time when it was first archived.
+async fn touch_dinorpg_user(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  short: &ShortDinorpgUser,
+) -> Result<Instant, EtwinError> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    archived_at: Instant,
+  }
+
+  let row: Row = sqlx::query_as::<_, Row>(
+    r"
+    INSERT INTO dinorpg_users(dinorpg_server, dinorpg_user_id, display_name, archived_at)
+    VALUES ($1::DINORPG_SERVER, $2::DINORPG_USER_ID, $3::TWINOID_USER_DISPLAY_NAME, $4::INSTANT)
+      ON CONFLICT (dinorpg_server, dinorpg_user_id)
+        DO UPDATE SET display_name = $3::TWINOID_USER_DISPLAY_NAME
+    RETURNING archived_at;
+  ",
+  )
+  .bind(short.server)
+  .bind(short.id)
+  .bind(&short.display_name)
+  .bind(now)
+  .fetch_one(tx)
+  .await?;
+
+  Ok(row.archived_at)
+}
+
+async fn has_dinorpg_user(tx: &mut Transaction<'_, Postgres>, user: DinorpgUserIdRef) -> Result<bool, EtwinError> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    present: bool,
+  }
+
+  let row: Row = sqlx::query_as::<_, Row>(
+    r"
+    SELECT EXISTS(
+      SELECT 1 FROM dinorpg_users WHERE dinorpg_server = $1::DINORPG_SERVER AND dinorpg_user_id = $2::DINORPG_USER_ID
+    ) AS present;
+  ",
+  )
+  .bind(user.server)
+  .bind(user.id)
+  .fetch_one(tx)
+  .await?;
+
+  Ok(row.present)
+}
+
+async fn touch_dinorpg_clan(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  clan: &ShortDinorpgClan,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(
+    r"
+    INSERT INTO dinorpg_clans(dinorpg_server, dinorpg_clan_id, name, archived_at)
+    VALUES ($1::DINORPG_SERVER, $2::DINORPG_CLAN_ID, $3::DINORPG_CLAN_NAME, $4::INSTANT)
+      ON CONFLICT (dinorpg_server, dinorpg_clan_id)
+        DO UPDATE SET name = $3::DINORPG_CLAN_NAME;
+  ",
+  )
+  .bind(clan.server)
+  .bind(clan.id)
+  .bind(&clan.name)
+  .bind(now)
+  .execute(tx)
+  .await?;
+  assert_eq!(res.rows_affected(), 1);
+  Ok(())
+}
+
+async fn touch_dinorpg_dinoz(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  dinoz: &ShortDinorpgDinozWithLevel,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(
+    r"
+    INSERT INTO dinorpg_dinoz(dinorpg_server, dinorpg_dinoz_id, archived_at)
+    VALUES ($1::DINORPG_SERVER, $2::DINORPG_DINOZ_ID, $3::INSTANT)
+      ON CONFLICT (dinorpg_server, dinorpg_dinoz_id) DO NOTHING;
+  ",
+  )
+  .bind(dinoz.server)
+  .bind(dinoz.id)
+  .bind(now)
+  .execute(tx)
+  .await?;
+  assert!((0..=1u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_dinorpg_user_profile(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  profile: &DinorpgProfile,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    dinorpg_user_profiles(
+      time($1 period, retrieved_at),
+      primary($2 dinorpg_server::DINORPG_SERVER, $3 dinorpg_user_id::DINORPG_USER_ID),
+      data(
+        $4 dinoz_count::U32, $5 ranking_rank::U32?, $6 ranking_points::U32?, $7 dinorpg_clan_id::DINORPG_CLAN_ID?,
+        $8 reward_count::U32, $9 dinoz_list_count::U32
+      ),
+    )
+  ))
+  .bind(now)
+  .bind(profile.user.server)
+  .bind(profile.user.id)
+  .bind(PgU32::from(profile.dinoz_count))
+  .bind(profile.ranking.map(|r| PgU32::from(r.rank)))
+  .bind(profile.ranking.map(|r| PgU32::from(r.points)))
+  .bind(profile.clan.as_ref().map(|c| c.id))
+  .bind(PgU32::from(u32::try_from(profile.rewards.len()).unwrap()))
+  .bind(PgU32::from(u32::try_from(profile.dinoz.len()).unwrap()))
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary)
+  assert!((1..=2u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_dinorpg_user_reward(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: DinorpgUserIdRef,
+  offset: u32,
+  reward: &DinorpgRewardKey,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    dinorpg_user_rewards(
+      time($1 period, retrieved_at),
+      primary($2 dinorpg_server::DINORPG_SERVER, $3 dinorpg_user_id::DINORPG_USER_ID, $4 offset_in_list::U32),
+      data($5 reward_key::DINORPG_REWARD_KEY),
+    )
+  ))
+  .bind(now)
+  .bind(user.server)
+  .bind(user.id)
+  .bind(PgU32::from(offset))
+  .bind(reward)
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary)
+  assert!((1..=2u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_dinorpg_user_dinoz_item(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: DinorpgUserIdRef,
+  offset: u32,
+  dinoz: &ShortDinorpgDinozWithLevel,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    dinorpg_user_dinoz(
+      time($1 period, retrieved_at),
+      primary($2 dinorpg_server::DINORPG_SERVER, $3 dinorpg_user_id::DINORPG_USER_ID, $4 offset_in_list::U32),
+      data(
+        $5 dinorpg_dinoz_id::DINORPG_DINOZ_ID, $6 name::DINORPG_DINOZ_NAME?, $7 race::DINORPG_DINOZ_RACE,
+        $8 skin::DINORPG_DINOZ_SKIN, $9 level::U16
+      ),
+      unique(dinoz(dinorpg_server, dinorpg_dinoz_id)),
+    )
+  ))
+  .bind(now)
+  .bind(user.server)
+  .bind(user.id)
+  .bind(PgU32::from(offset))
+  .bind(dinoz.id)
+  .bind(&dinoz.name)
+  .bind(dinoz.race)
+  .bind(&dinoz.skin)
+  .bind(PgU16::from(dinoz.level))
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary + dinoz_id)
+  // 3 : 1 inserted (data change), 1 invalidated (primary), 1 invalidated (dinoz_id)
+  assert!((1..=3u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock, TyDatabase> neon::prelude::Finalize for PgDinorpgStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+}
+
+#[cfg(test)]
+mod test {
+  use super::PgDinorpgStore;
+  use crate::test::TestApi;
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::dinorpg::DinorpgStore;
+  use etwin_db_schema::force_create_latest;
+  use serial_test::serial;
+  use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+  use sqlx::PgPool;
+  use std::sync::Arc;
+
+  async fn make_test_api() -> TestApi<Arc<VirtualClock>, Arc<dyn DinorpgStore>> {
+    let config = etwin_config::find_config(std::env::current_dir().unwrap()).unwrap();
+    let admin_database: PgPool = PgPoolOptions::new()
+      .max_connections(5)
+      .connect_with(
+        PgConnectOptions::new()
+          .host(&config.db.host)
+          .port(config.db.port)
+          .database(&config.db.name)
+          .username(&config.db.admin_user)
+          .password(&config.db.admin_password),
+      )
+      .await
+      .unwrap();
+    force_create_latest(&admin_database, true).await.unwrap();
+    admin_database.close().await;
+
+    let database: PgPool = PgPoolOptions::new()
+      .max_connections(5)
+      .connect_with(
+        PgConnectOptions::new()
+          .host(&config.db.host)
+          .port(config.db.port)
+          .database(&config.db.name)
+          .username(&config.db.user)
+          .password(&config.db.password),
+      )
+      .await
+      .unwrap();
+    let database = Arc::new(database);
+
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
+    let dinorpg_store: Arc<dyn DinorpgStore> = Arc::new(
+      PgDinorpgStore::new(Arc::clone(&clock), Arc::clone(&database))
+        .await
+        .unwrap(),
+    );
+
+    TestApi { clock, dinorpg_store }
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_empty() {
+    crate::test::test_empty(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_user() {
+    crate::test::test_touch_user(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_get_missing_user() {
+    crate::test::test_get_missing_user(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_profile() {
+    crate::test::test_touch_profile(make_test_api().await).await;
+  }
+}
@@ -0,0 +1,283 @@
+use chrono::{Duration, TimeZone, Utc};
+use etwin_core::api::ApiRef;
+use etwin_core::clock::VirtualClock;
+use etwin_core::core::PeriodLower;
+use etwin_core::dinorpg::{
+  ArchivedDinorpgUser, DinorpgDinozRace, DinorpgProfile, DinorpgProfileResponse, DinorpgRanking, DinorpgServer,
+  DinorpgStore, DinorpgUserIdRef, GetDinorpgUserOptions, ShortDinorpgClan, ShortDinorpgDinozWithLevel,
+  ShortDinorpgUser,
+};
+use etwin_core::temporal::{ForeignRetrieved, ForeignSnapshot, LatestTemporal};
+
+pub(crate) struct TestApi<TyClock, TyDinorpgStore>
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinorpgStore: DinorpgStore,
+{
+  pub(crate) clock: TyClock,
+  pub(crate) dinorpg_store: TyDinorpgStore,
+}
+
+pub(crate) async fn test_empty<TyClock, TyDinorpgStore>(api: TestApi<TyClock, TyDinorpgStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinorpgStore: DinorpgStore,
+{
+  let options = GetDinorpgUserOptions {
+    server: DinorpgServer::DinorpgCom,
+    id: "123".parse().unwrap(),
+    time: None,
+  };
+  let actual = api.dinorpg_store.get_short_user(&options).await.unwrap();
+  let expected = None;
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_touch_user<TyClock, TyDinorpgStore>(api: TestApi<TyClock, TyDinorpgStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinorpgStore: DinorpgStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  {
+    let actual = api
+      .dinorpg_store
+      .touch_short_user(&ShortDinorpgUser {
+        server: DinorpgServer::DinorpgCom,
+        id: "123".parse().unwrap(),
+        display_name: "alice".parse().unwrap(),
+      })
+      .await
+      .unwrap();
+    let expected = ArchivedDinorpgUser {
+      server: DinorpgServer::DinorpgCom,
+      id: "123".parse().unwrap(),
+      archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      display_name: "alice".parse().unwrap(),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .dinorpg_store
+      .get_short_user(&GetDinorpgUserOptions {
+        server: DinorpgServer::DinorpgCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(ShortDinorpgUser {
+      server: DinorpgServer::DinorpgCom,
+      id: "123".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinorpg_store
+      .get_user(&GetDinorpgUserOptions {
+        server: DinorpgServer::DinorpgCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(ArchivedDinorpgUser {
+      server: DinorpgServer::DinorpgCom,
+      id: "123".parse().unwrap(),
+      archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      display_name: "alice".parse().unwrap(),
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_get_missing_user<TyClock, TyDinorpgStore>(api: TestApi<TyClock, TyDinorpgStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinorpgStore: DinorpgStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .dinorpg_store
+    .touch_short_user(&ShortDinorpgUser {
+      server: DinorpgServer::DinorpgCom,
+      id: "123".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  {
+    let actual = api
+      .dinorpg_store
+      .get_user(&GetDinorpgUserOptions {
+        server: DinorpgServer::EnDinorpgCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = None;
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_touch_profile<TyClock, TyDinorpgStore>(api: TestApi<TyClock, TyDinorpgStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinorpgStore: DinorpgStore,
+{
+  let alice = DinorpgUserIdRef {
+    server: DinorpgServer::DinorpgCom,
+    id: "123".parse().unwrap(),
+  };
+  let first_profile = DinorpgProfile {
+    user: alice,
+    dinoz_count: 2,
+    ranking: Some(DinorpgRanking { rank: 42, points: 1000 }),
+    clan: Some(ShortDinorpgClan {
+      server: DinorpgServer::DinorpgCom,
+      id: "7".parse().unwrap(),
+      name: "Les Dinoz".parse().unwrap(),
+    }),
+    rewards: vec!["trophy_1".parse().unwrap(), "fire_brooch".parse().unwrap()],
+    dinoz: vec![
+      ShortDinorpgDinozWithLevel {
+        server: DinorpgServer::DinorpgCom,
+        id: "1001".parse().unwrap(),
+        name: Some("Balboa".parse().unwrap()),
+        race: DinorpgDinozRace::Moueffe,
+        skin: "0AbCdEf".parse().unwrap(),
+        level: 12,
+      },
+      ShortDinorpgDinozWithLevel {
+        server: DinorpgServer::DinorpgCom,
+        id: "1002".parse().unwrap(),
+        name: None,
+        race: DinorpgDinozRace::Winks,
+        skin: "2XyZ".parse().unwrap(),
+        level: 1,
+      },
+    ],
+  };
+
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  {
+    let actual = api
+      .dinorpg_store
+      .touch_profile(&DinorpgProfileResponse {
+        session_user: None,
+        profile: first_profile.clone(),
+      })
+      .await;
+    assert!(actual.is_err(), "touching the profile of an unknown user must fail");
+  }
+  api
+    .dinorpg_store
+    .touch_short_user(&ShortDinorpgUser {
+      server: DinorpgServer::DinorpgCom,
+      id: "123".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  api
+    .dinorpg_store
+    .touch_profile(&DinorpgProfileResponse {
+      session_user: None,
+      profile: first_profile.clone(),
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .dinorpg_store
+    .touch_profile(&DinorpgProfileResponse {
+      session_user: None,
+      profile: first_profile.clone(),
+    })
+    .await
+    .unwrap();
+
+  let second_profile = DinorpgProfile {
+    ranking: None,
+    clan: None,
+    rewards: vec!["trophy_1".parse().unwrap()],
+    dinoz: vec![ShortDinorpgDinozWithLevel {
+      level: 13,
+      ..first_profile.dinoz[0].clone()
+    }],
+    dinoz_count: 1,
+    ..first_profile.clone()
+  };
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 2).and_hms(0, 0, 0));
+  api
+    .dinorpg_store
+    .touch_profile(&DinorpgProfileResponse {
+      session_user: None,
+      profile: second_profile.clone(),
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .dinorpg_store
+      .get_profile(&GetDinorpgUserOptions {
+        server: DinorpgServer::DinorpgCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 2).and_hms(0, 0, 0)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+        },
+        value: second_profile,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinorpg_store
+      .get_profile(&GetDinorpgUserOptions {
+        server: DinorpgServer::DinorpgCom,
+        id: "123".parse().unwrap(),
+        time: Some(Utc.ymd(2021, 1, 1).and_hms(12, 0, 0)),
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::bounded(
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+          Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+        ),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        },
+        value: first_profile,
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .dinorpg_store
+      .get_profile(&GetDinorpgUserOptions {
+        server: DinorpgServer::DinorpgCom,
+        id: "123".parse().unwrap(),
+        time: Some(Utc.ymd(2020, 12, 31).and_hms(0, 0, 0)),
+      })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+}
@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Recorded pages of a game, one directory per page named after the request and the session user
+/// (e.g. `profile/fr-user127-guest`).
+pub(crate) struct Catalog<'a> {
+  root: &'a Path,
+  /// Name of the page file in each directory
+  file: &'static str,
+  /// Separator between the parts of a directory name
+  sep: char,
+}
+
+impl<'a> Catalog<'a> {
+  pub fn new(root: &'a Path, file: &'static str, sep: char) -> Self {
+    Self { root, file, sep }
+  }
+
+  /// Finds the page of `kind` in the directory named `name`, or in the first directory with a name starting with
+  /// `name` followed by the separator.
+  pub fn find(&self, kind: &str, name: &str) -> Option<PathBuf> {
+    let dir = self.root.join(kind);
+    let exact = dir.join(name).join(self.file);
+    if exact.is_file() {
+      return Some(exact);
+    }
+    let prefix = format!("{}{}", name, self.sep);
+    let mut pages: Vec<PathBuf> = fs::read_dir(&dir)
+      .ok()?
+      .filter_map(|entry| entry.ok())
+      .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+      .map(|entry| entry.path().join(self.file))
+      .filter(|page| page.is_file())
+      .collect();
+    pages.sort();
+    pages.into_iter().next()
+  }
+}
@@ -0,0 +1,87 @@
+use crate::catalog::Catalog;
+use crate::{found, not_found, page, text, with_cookie, FakeRequest, FakeSession, FakeState};
+use etwin_core::dinoparc::DinoparcServer;
+use warp::http::{Method, Response};
+
+const SESSION_COOKIE: &str = "sid";
+const SESSION_KEY_LEN: usize = 32;
+
+/// Prefix of the page directories of `server`.
+fn lang(server: DinoparcServer) -> &'static str {
+  match server {
+    DinoparcServer::DinoparcCom => "fr",
+    DinoparcServer::EnDinoparcCom => "en",
+    DinoparcServer::SpDinoparcCom => "es",
+  }
+}
+
+/// Dinoparc serves all its pages from the root, the action is selected by the `a` query parameter.
+pub(crate) fn handle(state: &FakeState, server: DinoparcServer, req: &FakeRequest) -> Response<Vec<u8>> {
+  if !req.path.is_empty() {
+    return not_found(&format!("unexpected Dinoparc path {:?}", req.path));
+  }
+  let root = state.game_catalog("dinoparc");
+  let catalog = Catalog::new(&root, "main.utf8.html", '.');
+  let lang = lang(server);
+  let user = state.session_user(server.as_str(), req, SESSION_COOKIE);
+  let action = req.query("a").unwrap_or_default();
+
+  match (&req.method, action) {
+    (&Method::POST, "login") => login(state, server, req),
+    // Login confirmation
+    (&Method::GET, "login") => found("?a=news"),
+    (&Method::GET, "adtk") => text("OK"),
+    (&Method::GET, "user") => {
+      let id = req.query("id").unwrap_or_default();
+      match catalog.find("user", &format!("{}.user{}", lang, id)) {
+        Some(page_path) => page(&page_path),
+        // Dinoparc redirects to the home page for unknown users
+        None => found("?a=news"),
+      }
+    }
+    (&Method::GET, _) => {
+      // Other pages require an authenticated session
+      let user = match user {
+        Some(user) => user,
+        None => return found("?a=news"),
+      };
+      let own = format!("{}.user{}", lang, user);
+      let page_path = match action {
+        "bank" => catalog.find("bank", &own),
+        "inventory" => catalog.find("inventory", &own),
+        "collection" => catalog.find("collection", &own),
+        "dino" => catalog.find(
+          "dinoz",
+          &format!("{}.dinoz{}", own, req.query("id").unwrap_or_default()),
+        ),
+        "bill" => catalog.find(
+          "exchange-with",
+          &format!("{}.user{}", own, req.query("uid").unwrap_or_default()),
+        ),
+        _ => None,
+      };
+      match page_path {
+        Some(page_path) => page(&page_path),
+        None => not_found(&format!("no recorded page for action {:?} and user {}", action, user)),
+      }
+    }
+    _ => not_found(&format!("unexpected Dinoparc request {} {:?}", req.method, action)),
+  }
+}
+
+/// Always opens a session, which is only authenticated if the credentials are valid.
+fn login(state: &FakeState, server: DinoparcServer, req: &FakeRequest) -> Response<Vec<u8>> {
+  let account = state.dinoparc_accounts.iter().find(|account| {
+    account.server == server
+      && account.username.as_str() == req.form("login")
+      && account.password.as_str() == req.form("pass")
+  });
+  let key = state.create_session(
+    SESSION_KEY_LEN,
+    FakeSession {
+      server: server.as_str().to_string(),
+      user_id: account.map(|account| account.user_id.to_string()),
+    },
+  );
+  with_cookie(text(""), SESSION_COOKIE, &key)
+}
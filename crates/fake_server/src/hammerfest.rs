@@ -0,0 +1,101 @@
+use crate::catalog::Catalog;
+use crate::{found, not_found, page, with_cookie, FakeRequest, FakeSession, FakeState};
+use etwin_core::hammerfest::HammerfestServer;
+use std::path::PathBuf;
+use warp::http::{Method, Response};
+
+const SESSION_COOKIE: &str = "SID";
+const SESSION_KEY_LEN: usize = 26;
+
+/// Prefix of the page directories of `server`.
+fn lang(server: HammerfestServer) -> &'static str {
+  match server {
+    HammerfestServer::HammerfestFr => "fr",
+    HammerfestServer::HammerfestEs => "es",
+    HammerfestServer::HfestNet => "en",
+  }
+}
+
+pub(crate) fn handle(state: &FakeState, server: HammerfestServer, req: &FakeRequest) -> Response<Vec<u8>> {
+  let root = state.game_catalog("hammerfest");
+  let catalog = Catalog::new(&root, "input.html", '-');
+  let lang = lang(server);
+  let user = state.session_user(server.as_str(), req, SESSION_COOKIE);
+  let serve = |page_path: Option<PathBuf>| match page_path {
+    Some(page_path) => page(&page_path),
+    None => not_found(&format!("no recorded page for {:?}", req.path)),
+  };
+
+  match (&req.method, req.path().as_slice()) {
+    (&Method::GET, []) => serve(match &user {
+      Some(user) => catalog.find("home", &format!("{}-user{}", lang, user)),
+      None => catalog.find("login", &format!("{}-guest-default", lang)),
+    }),
+    (&Method::POST, ["login.html"]) => login(state, server, req, &catalog),
+    (&Method::GET, ["user.html", "inventory"]) => own_page(&catalog, "inventory", lang, user.as_deref()),
+    (&Method::GET, ["user.html", "godChildren"]) => own_page(&catalog, "godchildren", lang, user.as_deref()),
+    (&Method::GET, ["shop.html"]) => own_page(&catalog, "shop", lang, user.as_deref()),
+    (&Method::GET, ["user.html", id]) => {
+      let profile = format!("{}-user{}", lang, id);
+      serve(
+        user
+          .and_then(|user| catalog.find("profile", &format!("{}-user{}", profile, user)))
+          .or_else(|| catalog.find("profile", &format!("{}-guest", profile)))
+          .or_else(|| catalog.find("profile", &profile))
+          // Page displayed by Hammerfest for unknown users
+          .or_else(|| catalog.find("profile", &format!("{}-user9999999", lang))),
+      )
+    }
+    (&Method::GET, ["forum.html"]) => serve(
+      user
+        .and_then(|user| catalog.find("forum-home", &format!("{}-user{}", lang, user)))
+        .or_else(|| catalog.find("forum-home", &format!("{}-guest", lang)))
+        .or_else(|| catalog.find("forum-home", lang)),
+    ),
+    (&Method::GET, ["forum.html", "theme", id]) => {
+      let page1 = req.query("page").unwrap_or("1");
+      serve(catalog.find("forum-theme", &format!("{}-theme{}-p{}", lang, id, page1)))
+    }
+    (&Method::GET, ["forum.html", "thread", id]) => {
+      let page1 = req.query("page").unwrap_or("1");
+      serve(catalog.find("forum-thread", &format!("{}-thread{}-p{}", lang, id, page1)))
+    }
+    _ => not_found(&format!("unexpected Hammerfest request {} {:?}", req.method, req.path)),
+  }
+}
+
+fn login(state: &FakeState, server: HammerfestServer, req: &FakeRequest, catalog: &Catalog) -> Response<Vec<u8>> {
+  let account = state.hammerfest_accounts.iter().find(|account| {
+    account.server == server
+      && account.username.as_str() == req.form("login")
+      && account.password.as_str() == req.form("pass")
+  });
+  match account {
+    Some(account) => {
+      let key = state.create_session(
+        SESSION_KEY_LEN,
+        FakeSession {
+          server: server.as_str().to_string(),
+          user_id: Some(account.user_id.to_string()),
+        },
+      );
+      with_cookie(found(&format!("/{}/", server.as_str())), SESSION_COOKIE, &key)
+    }
+    None => match catalog.find("login", &format!("{}-guest-error", lang(server))) {
+      Some(error_page) => page(&error_page),
+      None => not_found("no recorded login error page"),
+    },
+  }
+}
+
+/// Page of the logged-in user, redirects guests to the login page.
+fn own_page(catalog: &Catalog, kind: &str, lang: &str, user: Option<&str>) -> Response<Vec<u8>> {
+  let user = match user {
+    Some(user) => user,
+    None => return found("login.html"),
+  };
+  match catalog.find(kind, &format!("{}-user{}", lang, user)) {
+    Some(page_path) => page(&page_path),
+    None => not_found(&format!("no recorded {} page for user {}", kind, user)),
+  }
+}
@@ -0,0 +1,280 @@
+//! Fake Hammerfest and Dinoparc servers serving the recorded pages of the scraping test resources.
+//!
+//! A single [FakeServer] hosts every server under a path prefix named after the server (e.g. `/hammerfest.fr/`), use
+//! [FakeServer::http_options] to point the game clients to it. The pages are selected from the catalog using the
+//! naming conventions of `test-resources/scraping`: requesting the inventory of the Hammerfest user `127` on
+//! `hammerfest.fr` serves `hammerfest/inventory/fr-user127/input.html`. Requests without a matching page get a
+//! `404 Not Found` response.
+
+mod catalog;
+mod dinoparc;
+mod hammerfest;
+
+use etwin_core::dinoparc::{DinoparcPassword, DinoparcServer, DinoparcUserId, DinoparcUsername};
+use etwin_core::hammerfest::{HammerfestPassword, HammerfestServer, HammerfestUserId, HammerfestUsername};
+use etwin_core::types::EtwinError;
+use etwin_http::policy::{RateLimit, RetryPolicy};
+use etwin_http::HttpOptions;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use url::{form_urlencoded, Url};
+use warp::http::header::{CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE};
+use warp::http::{Method, Response, StatusCode};
+use warp::hyper::body::Bytes;
+use warp::path::FullPath;
+use warp::Filter;
+
+/// Hammerfest account accepted by the login form.
+#[derive(Clone, Debug)]
+pub struct HammerfestAccount {
+  pub server: HammerfestServer,
+  pub username: HammerfestUsername,
+  pub password: HammerfestPassword,
+  /// Used to select the pages of the logged-in user
+  pub user_id: HammerfestUserId,
+}
+
+/// Dinoparc account accepted by the login form.
+#[derive(Clone, Debug)]
+pub struct DinoparcAccount {
+  pub server: DinoparcServer,
+  pub username: DinoparcUsername,
+  pub password: DinoparcPassword,
+  /// Used to select the pages of the logged-in user
+  pub user_id: DinoparcUserId,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FakeServerOptions {
+  /// Root of the recorded pages, organized like `test-resources/scraping`
+  pub catalog: PathBuf,
+  pub hammerfest_accounts: Vec<HammerfestAccount>,
+  pub dinoparc_accounts: Vec<DinoparcAccount>,
+}
+
+/// Running fake server, stopped when dropped.
+pub struct FakeServer {
+  addr: SocketAddr,
+  shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeServer {
+  /// Starts the server on a random local port, in the current Tokio runtime.
+  pub fn start(options: FakeServerOptions) -> Result<Self, EtwinError> {
+    let state = Arc::new(FakeState::new(options));
+    let routes = warp::method()
+      .and(warp::path::full())
+      .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+      .and(warp::header::optional::<String>(COOKIE.as_str()))
+      .and(warp::body::bytes())
+      .map(
+        move |method: Method, path: FullPath, query: String, cookies: Option<String>, body: Bytes| {
+          state.handle(&FakeRequest::new(
+            method,
+            path.as_str(),
+            &query,
+            cookies.as_deref(),
+            &body,
+          ))
+        },
+      );
+    let (shutdown, on_shutdown) = oneshot::channel::<()>();
+    let (addr, server) =
+      warp::serve(routes).try_bind_with_graceful_shutdown(SocketAddr::from(([127, 0, 0, 1], 0)), async {
+        on_shutdown.await.ok();
+      })?;
+    tokio::spawn(server);
+    Ok(Self {
+      addr,
+      shutdown: Some(shutdown),
+    })
+  }
+
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+
+  /// Base URL of the fake server named `server` (e.g. `hammerfest.fr` or `dinoparc.com`).
+  pub fn base_url(&self, server: &str) -> Url {
+    Url::parse(&format!("http://{}/{}/", self.addr, server)).expect("invalid fake server URL")
+  }
+
+  /// Client options sending all the Hammerfest and Dinoparc requests to this server, without rate limit or retries.
+  pub fn http_options(&self) -> HttpOptions {
+    let servers = HammerfestServer::iter()
+      .map(|server| server.as_str())
+      .chain(DinoparcServer::iter().map(|server| server.as_str()));
+    HttpOptions {
+      rate_limit: RateLimit {
+        period: Duration::from_millis(1),
+        burst: 1000,
+      },
+      retry: RetryPolicy {
+        max_retries: 0,
+        ..RetryPolicy::default()
+      },
+      base_urls: servers
+        .map(|server| (server.to_string(), self.base_url(server)))
+        .collect::<BTreeMap<_, _>>(),
+      ..HttpOptions::default()
+    }
+  }
+}
+
+impl Drop for FakeServer {
+  fn drop(&mut self) {
+    if let Some(shutdown) = self.shutdown.take() {
+      shutdown.send(()).ok();
+    }
+  }
+}
+
+/// Request received by the fake server, with its path relative to the server prefix.
+pub(crate) struct FakeRequest {
+  pub method: Method,
+  /// Name of the targeted server, first segment of the path
+  pub server: String,
+  pub path: Vec<String>,
+  pub query: HashMap<String, String>,
+  pub cookies: HashMap<String, String>,
+  pub form: HashMap<String, String>,
+}
+
+impl FakeRequest {
+  fn new(method: Method, path: &str, query: &str, cookies: Option<&str>, body: &[u8]) -> Self {
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty()).map(String::from);
+    let server = segments.next().unwrap_or_default();
+    let cookies = cookies
+      .unwrap_or_default()
+      .split(';')
+      .filter_map(|cookie| cookie.split_once('='))
+      .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+      .collect();
+    Self {
+      method,
+      server,
+      path: segments.collect(),
+      query: form_urlencoded::parse(query.as_bytes()).into_owned().collect(),
+      cookies,
+      form: form_urlencoded::parse(body).into_owned().collect(),
+    }
+  }
+
+  pub fn path(&self) -> Vec<&str> {
+    self.path.iter().map(String::as_str).collect()
+  }
+
+  pub fn query(&self, name: &str) -> Option<&str> {
+    self.query.get(name).map(String::as_str)
+  }
+
+  pub fn form(&self, name: &str) -> &str {
+    self.form.get(name).map(String::as_str).unwrap_or_default()
+  }
+}
+
+/// Session created by a login form.
+#[derive(Clone, Debug)]
+pub(crate) struct FakeSession {
+  pub server: String,
+  /// Authenticated user, if the credentials were valid
+  pub user_id: Option<String>,
+}
+
+pub(crate) struct FakeState {
+  pub catalog: PathBuf,
+  pub hammerfest_accounts: Vec<HammerfestAccount>,
+  pub dinoparc_accounts: Vec<DinoparcAccount>,
+  sessions: Mutex<HashMap<String, FakeSession>>,
+  next_session: AtomicU64,
+}
+
+impl FakeState {
+  fn new(options: FakeServerOptions) -> Self {
+    Self {
+      catalog: options.catalog,
+      hammerfest_accounts: options.hammerfest_accounts,
+      dinoparc_accounts: options.dinoparc_accounts,
+      sessions: Mutex::new(HashMap::new()),
+      next_session: AtomicU64::new(1),
+    }
+  }
+
+  fn handle(&self, req: &FakeRequest) -> Response<Vec<u8>> {
+    if let Ok(server) = HammerfestServer::from_str(&req.server) {
+      hammerfest::handle(self, server, req)
+    } else if let Ok(server) = DinoparcServer::from_str(&req.server) {
+      dinoparc::handle(self, server, req)
+    } else {
+      not_found(&format!("unknown server {:?}", req.server))
+    }
+  }
+
+  pub fn game_catalog(&self, game: &str) -> PathBuf {
+    self.catalog.join(game)
+  }
+
+  /// Creates a session key of `len` digits, valid for both Hammerfest and Dinoparc.
+  pub fn create_session(&self, len: usize, session: FakeSession) -> String {
+    let id = self.next_session.fetch_add(1, Ordering::SeqCst);
+    let key = format!("{:0width$}", id, width = len);
+    self.sessions.lock().unwrap().insert(key.clone(), session);
+    key
+  }
+
+  /// User authenticated by the session cookie `cookie` of `req` on `server`.
+  pub fn session_user(&self, server: &str, req: &FakeRequest, cookie: &str) -> Option<String> {
+    let key = req.cookies.get(cookie)?;
+    let sessions = self.sessions.lock().unwrap();
+    let session = sessions.get(key).filter(|session| session.server == server)?;
+    session.user_id.clone()
+  }
+}
+
+/// Serves the page stored at `path`.
+pub(crate) fn page(path: &Path) -> Response<Vec<u8>> {
+  match std::fs::read(path) {
+    Ok(body) => Response::builder()
+      .header(CONTENT_TYPE, "text/html; charset=utf-8")
+      .body(body)
+      .unwrap(),
+    Err(e) => not_found(&format!("failed to read {}: {}", path.display(), e)),
+  }
+}
+
+pub(crate) fn found(location: &str) -> Response<Vec<u8>> {
+  Response::builder()
+    .status(StatusCode::FOUND)
+    .header(LOCATION, location)
+    .body(Vec::new())
+    .unwrap()
+}
+
+pub(crate) fn with_cookie(mut res: Response<Vec<u8>>, name: &str, value: &str) -> Response<Vec<u8>> {
+  let cookie = format!("{}={}; Path=/", name, value);
+  res
+    .headers_mut()
+    .append(SET_COOKIE, cookie.parse().expect("invalid cookie"));
+  res
+}
+
+pub(crate) fn text(body: &str) -> Response<Vec<u8>> {
+  Response::builder()
+    .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+    .body(body.as_bytes().to_vec())
+    .unwrap()
+}
+
+pub(crate) fn not_found(reason: &str) -> Response<Vec<u8>> {
+  Response::builder()
+    .status(StatusCode::NOT_FOUND)
+    .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+    .body(reason.as_bytes().to_vec())
+    .unwrap()
+}
@@ -0,0 +1,93 @@
+use etwin_core::clock::SystemClock;
+use etwin_core::dinoparc::{
+  DinoparcClient, DinoparcCredentials, DinoparcPassword, DinoparcServer, DinoparcSessionKey, DinoparcUserId,
+  DinoparcUsername,
+};
+use etwin_dinoparc_client::http::HttpDinoparcClient;
+use etwin_fake_server::{DinoparcAccount, FakeServer, FakeServerOptions};
+use etwin_log::NoopLogger;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+fn start_server() -> FakeServer {
+  FakeServer::start(FakeServerOptions {
+    catalog: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test-resources/scraping"),
+    hammerfest_accounts: Vec::new(),
+    dinoparc_accounts: vec![DinoparcAccount {
+      server: DinoparcServer::DinoparcCom,
+      username: DinoparcUsername::from_str("demurgos").unwrap(),
+      password: DinoparcPassword::new(String::from("secret")),
+      user_id: DinoparcUserId::from_str("2480723").unwrap(),
+    }],
+  })
+  .unwrap()
+}
+
+fn credentials(password: &str) -> DinoparcCredentials {
+  DinoparcCredentials {
+    server: DinoparcServer::DinoparcCom,
+    username: DinoparcUsername::from_str("demurgos").unwrap(),
+    password: DinoparcPassword::new(String::from(password)),
+  }
+}
+
+#[tokio::test]
+async fn test_session_pages() {
+  let server = start_server();
+  let client = HttpDinoparcClient::with_options(SystemClock, NoopLogger, &server.http_options()).unwrap();
+  let user_id = DinoparcUserId::from_str("2480723").unwrap();
+
+  let session = client.create_session(&credentials("secret")).await.unwrap();
+  assert_eq!(session.user.id, user_id);
+  assert_eq!(session.user.username.as_str(), "demurgos");
+
+  let inventory = client.get_inventory(&session).await.unwrap();
+  assert_eq!(inventory.session_user.user.id, user_id);
+  assert!(!inventory.inventory.is_empty());
+
+  let tested = client
+    .test_session(DinoparcServer::DinoparcCom, &session.key)
+    .await
+    .unwrap();
+  assert_eq!(tested.map(|session| session.user.id), Some(user_id));
+  let unknown_key = DinoparcSessionKey::from_str(&"a".repeat(32)).unwrap();
+  let expired = client
+    .test_session(DinoparcServer::DinoparcCom, &unknown_key)
+    .await
+    .unwrap();
+  assert_eq!(expired, None);
+
+  let other_user = DinoparcUserId::from_str("71").unwrap();
+  let exchange = client.get_exchange_with(&session, other_user).await.unwrap();
+  assert_eq!(exchange.other_user.id, other_user);
+}
+
+#[tokio::test]
+async fn test_invalid_credentials() {
+  let server = start_server();
+  let client = HttpDinoparcClient::with_options(SystemClock, NoopLogger, &server.http_options()).unwrap();
+
+  let err = client.create_session(&credentials("wrong")).await.unwrap_err();
+  assert!(err.to_string().starts_with("Invalid credentials"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_user_profile() {
+  let server = start_server();
+  let client = HttpDinoparcClient::with_options(SystemClock, NoopLogger, &server.http_options()).unwrap();
+
+  let profile = client
+    .get_user_profile(DinoparcServer::DinoparcCom, DinoparcUserId::from_str("497785").unwrap())
+    .await
+    .unwrap();
+  assert_eq!(
+    profile.map(|profile| profile.user.username.as_str().to_string()),
+    Some(String::from("Yukora1"))
+  );
+
+  let missing = client
+    .get_user_profile(DinoparcServer::DinoparcCom, DinoparcUserId::from_str("1").unwrap())
+    .await
+    .unwrap();
+  assert_eq!(missing, None);
+}
@@ -0,0 +1,106 @@
+use etwin_core::clock::SystemClock;
+use etwin_core::hammerfest::{
+  HammerfestClient, HammerfestCredentials, HammerfestForumThreadId, HammerfestGetProfileByIdOptions,
+  HammerfestPassword, HammerfestServer, HammerfestUserId, HammerfestUsername,
+};
+use etwin_fake_server::{FakeServer, FakeServerOptions, HammerfestAccount};
+use etwin_hammerfest_client::HttpHammerfestClient;
+use etwin_log::NoopLogger;
+use std::num::NonZeroU16;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+fn start_server() -> FakeServer {
+  FakeServer::start(FakeServerOptions {
+    catalog: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test-resources/scraping"),
+    hammerfest_accounts: vec![HammerfestAccount {
+      server: HammerfestServer::HammerfestFr,
+      username: HammerfestUsername::from_str("Elseabora").unwrap(),
+      password: HammerfestPassword::new(String::from("secret")),
+      user_id: HammerfestUserId::from_str("127").unwrap(),
+    }],
+    dinoparc_accounts: Vec::new(),
+  })
+  .unwrap()
+}
+
+fn credentials(password: &str) -> HammerfestCredentials {
+  HammerfestCredentials {
+    server: HammerfestServer::HammerfestFr,
+    username: HammerfestUsername::from_str("Elseabora").unwrap(),
+    password: HammerfestPassword::new(String::from(password)),
+  }
+}
+
+#[tokio::test]
+async fn test_session_pages() {
+  let server = start_server();
+  let client = HttpHammerfestClient::with_options(SystemClock, NoopLogger, &server.http_options()).unwrap();
+  let user_id = HammerfestUserId::from_str("127").unwrap();
+
+  let session = client.create_session(&credentials("secret")).await.unwrap();
+  assert_eq!(session.user.id, user_id);
+  assert_eq!(session.user.username.as_str(), "Elseabora");
+
+  let tested = client
+    .test_session(HammerfestServer::HammerfestFr, &session.key)
+    .await
+    .unwrap();
+  assert_eq!(tested.map(|session| session.user.id), Some(user_id));
+
+  let inventory = client.get_own_items(&session).await.unwrap();
+  assert_eq!(inventory.session.user.id, user_id);
+  assert!(!inventory.inventory.is_empty());
+
+  let profile = client
+    .get_profile_by_id(
+      None,
+      &HammerfestGetProfileByIdOptions {
+        server: HammerfestServer::HammerfestFr,
+        user_id,
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(profile.session, None);
+  assert_eq!(profile.profile.map(|profile| profile.user.id), Some(user_id));
+}
+
+#[tokio::test]
+async fn test_invalid_credentials() {
+  let server = start_server();
+  let client = HttpHammerfestClient::with_options(SystemClock, NoopLogger, &server.http_options()).unwrap();
+
+  let err = client.create_session(&credentials("wrong")).await.unwrap_err();
+  assert!(err.to_string().starts_with("Invalid credentials"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_forum_pagination() {
+  let server = start_server();
+  let client = HttpHammerfestClient::with_options(SystemClock, NoopLogger, &server.http_options()).unwrap();
+  let thread_id = HammerfestForumThreadId::from_str("486800").unwrap();
+
+  let page = client
+    .get_forum_thread_page(
+      None,
+      HammerfestServer::HammerfestFr,
+      thread_id,
+      NonZeroU16::new(9).unwrap(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.page.posts.page1.get(), 9);
+  assert_eq!(page.page.posts.pages.get(), 10);
+
+  // There is no recorded first page
+  let first = client
+    .get_forum_thread_page(
+      None,
+      HammerfestServer::HammerfestFr,
+      thread_id,
+      NonZeroU16::new(1).unwrap(),
+    )
+    .await;
+  assert!(first.is_err());
+}
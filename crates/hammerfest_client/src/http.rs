@@ -1,5 +1,7 @@
 mod errors;
+pub(crate) mod reparse;
 mod scraper;
+pub(crate) mod selfcheck;
 #[cfg(test)]
 mod tests;
 mod url;
@@ -10,60 +12,134 @@ use async_trait::async_trait;
 use etwin_core::clock::Clock;
 use etwin_core::hammerfest::*;
 use etwin_core::types::EtwinError;
-use reqwest::{Client, StatusCode};
+use etwin_http::archive::PageArchive;
+use etwin_http::event::{archive_logged, log_parse_failure, send_logged, HttpClientEvent};
+use etwin_http::{new_archive, new_transport, HttpError, HttpOptions, HttpRequest, HttpResponse, HttpTransport};
+use etwin_log::Logger;
+use etwin_scraper_tools::drift::{PageCheck, PageSource};
+use reqwest::{StatusCode, Url};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::num::NonZeroU16;
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::Arc;
 
 type Result<T> = std::result::Result<T, EtwinError>;
 
 const USER_AGENT: &str = "EtwinHammerfestScraper";
-const TIMEOUT: Duration = Duration::from_millis(5000);
 
-pub struct HttpHammerfestClient<TyClock> {
-  client: Client,
+pub struct HttpHammerfestClient<TyClock, TyLogger> {
+  transport: Arc<dyn HttpTransport>,
+  archive: Option<Arc<dyn PageArchive>>,
+  /// Base URLs replacing the official hosts, keyed by server name
+  base_urls: BTreeMap<String, Url>,
   clock: TyClock,
+  logger: TyLogger,
 }
 
-impl<TyClock> HttpHammerfestClient<TyClock>
+impl<TyClock, TyLogger> HttpHammerfestClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
-  pub fn new(clock: TyClock) -> Result<Self> {
+  pub fn new(clock: TyClock, logger: TyLogger) -> Result<Self> {
+    Self::with_options(clock, logger, &HttpOptions::default())
+  }
+
+  /// Creates a client using the provided transport options (mode, timeout, rate limit, retries, archive, proxy and
+  /// base URLs).
+  pub fn with_options(clock: TyClock, logger: TyLogger, options: &HttpOptions) -> Result<Self> {
     Ok(Self {
-      client: Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(TIMEOUT)
-        .redirect(reqwest::redirect::Policy::none())
-        .build()?,
-      clock,
+      archive: new_archive(options, USER_AGENT)?,
+      base_urls: options.base_urls.clone(),
+      ..Self::with_transport(clock, logger, new_transport(options, USER_AGENT)?)
     })
   }
 
-  async fn get_html(
+  pub fn with_transport(clock: TyClock, logger: TyLogger, transport: Arc<dyn HttpTransport>) -> Self {
+    Self {
+      transport,
+      archive: None,
+      base_urls: BTreeMap::new(),
+      clock,
+      logger,
+    }
+  }
+
+  /// URLs of `server`, using its configured base URL if any.
+  fn urls(&self, server: HammerfestServer) -> HammerfestUrls {
+    match self.base_urls.get(server.as_str()) {
+      Some(root) => HammerfestUrls::with_root(root.clone()),
+      None => HammerfestUrls::new(server),
+    }
+  }
+
+  /// Sends `req`, archiving the response if a page archive is configured.
+  async fn send(&self, req: HttpRequest) -> std::result::Result<HttpResponse, HttpError> {
+    let res = send_logged(&*self.transport, &self.logger, USER_AGENT, req).await?;
+    if let Some(archive) = &self.archive {
+      archive_logged(&**archive, &self.logger, USER_AGENT, self.clock.now(), &res);
+    }
+    Ok(res)
+  }
+
+  async fn get_text(
     &self,
     url: reqwest::Url,
     session: Option<&HammerfestSessionKey>,
-  ) -> reqwest::Result<scraper::Html> {
-    let mut builder = self.client.get(url);
+  ) -> std::result::Result<String, HttpError> {
+    let mut req = HttpRequest::get(url);
 
     if let Some(key) = session {
       // No need to escape, per HammerfestSessionKey invariants.
-      let session_cookie = "SID=".to_owned() + key.as_str();
-      builder = builder.header(reqwest::header::COOKIE, session_cookie);
+      req = req.cookie("SID", key.as_str());
     }
 
-    let resp = builder.send().await?;
-    let text = resp.error_for_status()?.text().await?;
-    Ok(scraper::Html::parse_document(&text))
+    let resp = self.send(req).await?;
+    Ok(resp.error_for_status()?.text())
+  }
+
+  /// Fetches the canary pages as a guest and checks them against the scrapers, see [selfcheck::check_catalog].
+  pub async fn check_canaries(&self) -> Vec<PageCheck> {
+    let mut checks = Vec::new();
+    for (url, page) in selfcheck::canaries(|server| self.urls(server)) {
+      let check = match self.get_text(url.clone(), None).await {
+        Ok(text) => page.check(
+          url.to_string(),
+          PageSource::Canary,
+          &scraper::Html::parse_document(&text),
+        ),
+        Err(e) => PageCheck {
+          kind: page.kind(),
+          page: url.to_string(),
+          source: PageSource::Canary,
+          error: Some(e.to_string()),
+          selectors: Vec::new(),
+        },
+      };
+      checks.push(check);
+    }
+    checks
+  }
+
+  /// Fetches the page at `url` and reads it with `scrape`, logging a snippet of the page if it can't be read.
+  async fn scrape<T>(
+    &self,
+    url: reqwest::Url,
+    session: Option<&HammerfestSessionKey>,
+    scrape: impl FnOnce(&scraper::Html) -> std::result::Result<T, ScraperError> + Send,
+  ) -> Result<T> {
+    let text = self.get_text(url.clone(), session).await?;
+    let html = scraper::Html::parse_document(&text);
+    Ok(log_parse_failure(&self.logger, USER_AGENT, &url, &text, scrape(&html))?)
   }
 }
 
 #[async_trait]
-impl<TyClock> HammerfestClient for HttpHammerfestClient<TyClock>
+impl<TyClock, TyLogger> HammerfestClient for HttpHammerfestClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
   async fn create_session(&self, options: &HammerfestCredentials) -> Result<HammerfestSession> {
     #[derive(Serialize)]
@@ -72,21 +148,17 @@ where
       pass: &'a str,
     }
 
-    let urls = HammerfestUrls::new(options.server);
+    let urls = self.urls(options.server);
 
     let now = self.clock.now();
-    let resp = self
-      .client
-      .post(urls.login())
-      .form(&LoginForm {
-        login: options.username.as_str(),
-        pass: options.password.as_str(),
-      })
-      .send()
-      .await?;
+    let req = HttpRequest::post(urls.login()).form(&LoginForm {
+      login: options.username.as_str(),
+      pass: options.password.as_str(),
+    })?;
+    let resp = self.send(req).await?;
 
     if resp.status() != StatusCode::FOUND {
-      let text = resp.error_for_status()?.text().await?;
+      let text = resp.error_for_status()?.text();
       let html = scraper::Html::parse_document(&text);
       return Err(
         if scraper::is_login_page_error(&html) {
@@ -98,16 +170,15 @@ where
       );
     }
 
-    let session_key = resp
-      .cookies()
-      .find(|cookie| cookie.name() == "SID")
-      .map(|cookie| cookie.value().to_owned())
-      .ok_or(ScraperError::MissingSessionCookie)?;
-    let session_key = HammerfestSessionKey::from_str(&session_key).map_err(|_| ScraperError::InvalidSessionCookie)?;
+    let session_key = resp.cookie("SID").ok_or(ScraperError::MissingSessionCookie)?;
+    let session_key = HammerfestSessionKey::from_str(session_key).map_err(|_| ScraperError::InvalidSessionCookie)?;
 
-    let html = self.get_html(urls.root(), Some(&session_key)).await?;
-    let session =
-      scraper::scrape_session(html.root_element(), options.server)?.ok_or(ScraperError::LoginSessionRevoked)?;
+    let session = self
+      .scrape(urls.root(), Some(&session_key), |html| {
+        scraper::scrape_session(html.root_element(), options.server)
+      })
+      .await?
+      .ok_or(ScraperError::LoginSessionRevoked)?;
     Ok(HammerfestSession {
       ctime: now,
       atime: now,
@@ -121,10 +192,13 @@ where
     server: HammerfestServer,
     key: &HammerfestSessionKey,
   ) -> Result<Option<HammerfestSession>> {
-    let urls = HammerfestUrls::new(server);
+    let urls = self.urls(server);
     let now = self.clock.now();
-    let html = self.get_html(urls.root(), Some(key)).await?;
-    let session = scraper::scrape_session(html.root_element(), server)?;
+    let session = self
+      .scrape(urls.root(), Some(key), |html| {
+        scraper::scrape_session(html.root_element(), server)
+      })
+      .await?;
     Ok(session.map(|s| HammerfestSession {
       ctime: now,
       atime: now,
@@ -138,30 +212,36 @@ where
     session: Option<&HammerfestSession>,
     options: &HammerfestGetProfileByIdOptions,
   ) -> Result<HammerfestProfileResponse> {
-    let urls = HammerfestUrls::new(options.server);
-    let html = self
-      .get_html(urls.user(&options.user_id), session.map(|sess| &sess.key))
-      .await?;
-    Ok(scraper::scrape_user_profile(options.server, options.user_id, &html)?)
+    let urls = self.urls(options.server);
+    self
+      .scrape(urls.user(&options.user_id), session.map(|sess| &sess.key), |html| {
+        scraper::scrape_user_profile(options.server, options.user_id, html)
+      })
+      .await
   }
 
   async fn get_own_items(&self, session: &HammerfestSession) -> Result<HammerfestInventoryResponse> {
-    let urls = HammerfestUrls::new(session.user.server);
-    let html = self.get_html(urls.inventory(), Some(&session.key)).await?;
-    Ok(scraper::scrape_user_inventory(&html)?)
+    let urls = self.urls(session.user.server);
+    self
+      .scrape(urls.inventory(), Some(&session.key), scraper::scrape_user_inventory)
+      .await
   }
 
   async fn get_own_godchildren(&self, session: &HammerfestSession) -> Result<HammerfestGodchildrenResponse> {
     let server = session.user.server;
-    let urls = HammerfestUrls::new(server);
-    let html = self.get_html(urls.god_children(), Some(&session.key)).await?;
-    Ok(scraper::scrape_user_god_children(server, &html)?)
+    let urls = self.urls(server);
+    self
+      .scrape(urls.god_children(), Some(&session.key), |html| {
+        scraper::scrape_user_god_children(server, html)
+      })
+      .await
   }
 
   async fn get_own_shop(&self, session: &HammerfestSession) -> Result<HammerfestShopResponse> {
-    let urls = HammerfestUrls::new(session.user.server);
-    let html = self.get_html(urls.shop(), Some(&session.key)).await?;
-    Ok(scraper::scrape_user_shop(&html)?)
+    let urls = self.urls(session.user.server);
+    self
+      .scrape(urls.shop(), Some(&session.key), scraper::scrape_user_shop)
+      .await
   }
 
   async fn get_forum_themes(
@@ -169,9 +249,12 @@ where
     session: Option<&HammerfestSession>,
     server: HammerfestServer,
   ) -> Result<HammerfestForumHomeResponse> {
-    let urls = HammerfestUrls::new(server);
-    let html = self.get_html(urls.forum_home(), session.map(|sess| &sess.key)).await?;
-    Ok(scraper::scrape_forum_home(server, &html)?)
+    let urls = self.urls(server);
+    self
+      .scrape(urls.forum_home(), session.map(|sess| &sess.key), |html| {
+        scraper::scrape_forum_home(server, html)
+      })
+      .await
   }
 
   async fn get_forum_theme_page(
@@ -181,11 +264,14 @@ where
     theme_id: HammerfestForumThemeId,
     page1: NonZeroU16,
   ) -> Result<HammerfestForumThemePageResponse> {
-    let urls = HammerfestUrls::new(server);
-    let html = self
-      .get_html(urls.forum_theme(theme_id, page1), session.map(|sess| &sess.key))
-      .await?;
-    Ok(scraper::scrape_forum_theme(server, &html)?)
+    let urls = self.urls(server);
+    self
+      .scrape(
+        urls.forum_theme(theme_id, page1),
+        session.map(|sess| &sess.key),
+        |html| scraper::scrape_forum_theme(server, html),
+      )
+      .await
   }
 
   async fn get_forum_thread_page(
@@ -195,13 +281,21 @@ where
     thread_id: HammerfestForumThreadId,
     page1: NonZeroU16,
   ) -> Result<HammerfestForumThreadPageResponse> {
-    let urls = HammerfestUrls::new(server);
-    let html = self
-      .get_html(urls.forum_thread(thread_id, page1), session.map(|sess| &sess.key))
-      .await?;
-    Ok(scraper::scrape_forum_thread(server, thread_id, &html)?)
+    let urls = self.urls(server);
+    self
+      .scrape(
+        urls.forum_thread(thread_id, page1),
+        session.map(|sess| &sess.key),
+        |html| scraper::scrape_forum_thread(server, thread_id, html),
+      )
+      .await
   }
 }
 
 #[cfg(feature = "neon")]
-impl<TyClock> neon::prelude::Finalize for HttpHammerfestClient<TyClock> where TyClock: Clock {}
+impl<TyClock, TyLogger> neon::prelude::Finalize for HttpHammerfestClient<TyClock, TyLogger>
+where
+  TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
+{
+}
@@ -0,0 +1,100 @@
+use super::scraper;
+use super::url::HammerfestUrls;
+use etwin_core::hammerfest::*;
+use etwin_core::types::EtwinError;
+use etwin_http::{path_below, HttpOptions};
+use reqwest::Url;
+use std::str::FromStr;
+
+/// Archived page, parsed with the scraper matching its URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HammerfestArchivedPage {
+  Profile(HammerfestProfileResponse),
+  Inventory(HammerfestInventoryResponse),
+  Shop(HammerfestShopResponse),
+  Godchildren(HammerfestGodchildrenResponse),
+  ForumTheme(HammerfestForumThemePageResponse),
+  ForumThread(HammerfestForumThreadPageResponse),
+}
+
+/// Parses an archived page fetched from `url`, on the official host or on the base URL configured in `options`.
+///
+/// Returns `None` if `url` is not a Hammerfest page with a scraper.
+pub fn reparse_page(
+  options: &HttpOptions,
+  url: &Url,
+  html: &str,
+) -> Result<Option<HammerfestArchivedPage>, EtwinError> {
+  let found = HammerfestServer::iter().find_map(|server| {
+    let mirror = options
+      .base_url(server.as_str())
+      .cloned()
+      .map(HammerfestUrls::with_root);
+    std::iter::once(HammerfestUrls::new(server))
+      .chain(mirror)
+      .find_map(|urls| path_below(&urls.root(), url))
+      .map(|segments| (server, segments))
+  });
+  let (server, segments) = match found {
+    Some(found) => found,
+    None => return Ok(None),
+  };
+  let html = scraper::Html::parse_document(html);
+  let page = match segments.as_slice() {
+    ["user.html", "inventory"] => HammerfestArchivedPage::Inventory(scraper::scrape_user_inventory(&html)?),
+    ["user.html", "godChildren"] => {
+      HammerfestArchivedPage::Godchildren(scraper::scrape_user_god_children(server, &html)?)
+    }
+    ["user.html", id] => {
+      let id = HammerfestUserId::from_str(id)?;
+      HammerfestArchivedPage::Profile(scraper::scrape_user_profile(server, id, &html)?)
+    }
+    ["shop.html"] => HammerfestArchivedPage::Shop(scraper::scrape_user_shop(&html)?),
+    ["forum.html", "theme", _] => HammerfestArchivedPage::ForumTheme(scraper::scrape_forum_theme(server, &html)?),
+    ["forum.html", "thread", id] => {
+      let id = HammerfestForumThreadId::from_str(id)?;
+      HammerfestArchivedPage::ForumThread(scraper::scrape_forum_thread(server, id, &html)?)
+    }
+    _ => return Ok(None),
+  };
+  Ok(Some(page))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{reparse_page, HammerfestArchivedPage};
+  use etwin_core::hammerfest::HammerfestProfileResponse;
+  use etwin_http::HttpOptions;
+  use reqwest::Url;
+  use std::path::PathBuf;
+
+  #[test]
+  fn reparse_profile() {
+    let path = PathBuf::from("../../test-resources/scraping/hammerfest/profile/fr-user127-guest");
+    let html = std::fs::read_to_string(path.join("input.html")).unwrap();
+    let expected: HammerfestProfileResponse =
+      serde_json::from_str(&std::fs::read_to_string(path.join("expected.json")).unwrap()).unwrap();
+
+    let url = Url::parse("http://www.hammerfest.fr/user.html/127").unwrap();
+    let actual = reparse_page(&HttpOptions::default(), &url, &html).unwrap();
+    assert_eq!(actual, Some(HammerfestArchivedPage::Profile(expected.clone())));
+
+    let mirror = Url::parse("http://127.0.0.1:8080/hammerfest.fr/").unwrap();
+    let options = HttpOptions {
+      base_urls: vec![(String::from("hammerfest.fr"), mirror)].into_iter().collect(),
+      ..HttpOptions::default()
+    };
+    let url = Url::parse("http://127.0.0.1:8080/hammerfest.fr/user.html/127").unwrap();
+    assert_eq!(reparse_page(&HttpOptions::default(), &url, &html).unwrap(), None);
+    let actual = reparse_page(&options, &url, &html).unwrap();
+    assert_eq!(actual, Some(HammerfestArchivedPage::Profile(expected)));
+  }
+
+  #[test]
+  fn ignore_unknown_page() {
+    let url = Url::parse("http://www.hammerfest.fr/forum.html").unwrap();
+    assert_eq!(reparse_page(&HttpOptions::default(), &url, "").unwrap(), None);
+    let url = Url::parse("http://www.dinoparc.com/?a=user;id=1").unwrap();
+    assert_eq!(reparse_page(&HttpOptions::default(), &url, "").unwrap(), None);
+  }
+}
@@ -0,0 +1,161 @@
+//! Self-check of the scrapers against recorded pages and live canaries, see [etwin_scraper_tools::drift].
+
+use super::scraper;
+use super::url::HammerfestUrls;
+use etwin_core::hammerfest::{HammerfestForumThreadId, HammerfestServer, HammerfestUserId};
+use etwin_core::types::EtwinError;
+use etwin_scraper_tools::drift::{check_page, PageCheck, PageSource};
+use reqwest::Url;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Page read by one of the scrapers, with the context the scraper needs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CheckedPage {
+  Session(HammerfestServer),
+  Profile(HammerfestServer, HammerfestUserId),
+  Inventory,
+  Shop,
+  Godchildren(HammerfestServer),
+  ForumHome(HammerfestServer),
+  ForumTheme(HammerfestServer),
+  ForumThread(HammerfestServer, HammerfestForumThreadId),
+}
+
+impl CheckedPage {
+  pub(crate) fn kind(&self) -> &'static str {
+    match self {
+      Self::Session(_) => "hammerfest/session",
+      Self::Profile(..) => "hammerfest/profile",
+      Self::Inventory => "hammerfest/inventory",
+      Self::Shop => "hammerfest/shop",
+      Self::Godchildren(_) => "hammerfest/godchildren",
+      Self::ForumHome(_) => "hammerfest/forum-home",
+      Self::ForumTheme(_) => "hammerfest/forum-theme",
+      Self::ForumThread(..) => "hammerfest/forum-thread",
+    }
+  }
+
+  pub(crate) fn check(&self, page: String, source: PageSource, html: &scraper::Html) -> PageCheck {
+    let kind = self.kind();
+    match *self {
+      Self::Session(server) => check_page(kind, page, source, html, |html| {
+        scraper::scrape_session(html.root_element(), server)
+      }),
+      Self::Profile(server, user_id) => check_page(kind, page, source, html, |html| {
+        scraper::scrape_user_profile(server, user_id, html)
+      }),
+      Self::Inventory => check_page(kind, page, source, html, scraper::scrape_user_inventory),
+      Self::Shop => check_page(kind, page, source, html, scraper::scrape_user_shop),
+      Self::Godchildren(server) => check_page(kind, page, source, html, |html| {
+        scraper::scrape_user_god_children(server, html)
+      }),
+      Self::ForumHome(server) => check_page(kind, page, source, html, |html| {
+        scraper::scrape_forum_home(server, html)
+      }),
+      Self::ForumTheme(server) => check_page(kind, page, source, html, |html| {
+        scraper::scrape_forum_theme(server, html)
+      }),
+      Self::ForumThread(server, thread_id) => check_page(kind, page, source, html, |html| {
+        scraper::scrape_forum_thread(server, thread_id, html)
+      }),
+    }
+  }
+}
+
+/// Public pages fetched as a guest to check the live markup, using `urls` to resolve the server hosts.
+pub(crate) fn canaries(urls: impl Fn(HammerfestServer) -> HammerfestUrls) -> Vec<(Url, CheckedPage)> {
+  let mut canaries = Vec::new();
+  for server in HammerfestServer::iter() {
+    let urls = urls(server);
+    canaries.push((urls.root(), CheckedPage::Session(server)));
+    canaries.push((urls.forum_home(), CheckedPage::ForumHome(server)));
+  }
+  // Profiles with a recorded guest page
+  for (server, user_id) in [
+    (HammerfestServer::HammerfestFr, "127"),
+    (HammerfestServer::HfestNet, "180098"),
+    (HammerfestServer::HammerfestEs, "248939"),
+  ] {
+    let user_id = HammerfestUserId::from_str(user_id).unwrap();
+    canaries.push((urls(server).user(&user_id), CheckedPage::Profile(server, user_id)));
+  }
+  canaries
+}
+
+/// Runs the scrapers over the recorded pages of the scraping test resources (`test-resources/scraping/hammerfest`).
+///
+/// Each page is a directory containing `input.html` and the scraper options in `options.json`.
+pub fn check_catalog(root: &Path) -> Result<Vec<PageCheck>, EtwinError> {
+  let mut checks = Vec::new();
+  for (dir, kind) in [
+    ("login", "session"),
+    ("home", "session"),
+    ("profile", "profile"),
+    ("inventory", "inventory"),
+    ("shop", "shop"),
+    ("godchildren", "godchildren"),
+    ("forum-home", "forum-home"),
+    ("forum-theme", "forum-theme"),
+    ("forum-thread", "forum-thread"),
+  ] {
+    let mut pages: Vec<_> = fs::read_dir(root.join(dir))?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<_, _>>()?;
+    pages.sort();
+    for page in pages {
+      let input = page.join("input.html");
+      if !input.is_file() {
+        continue;
+      }
+      let options: serde_json::Value = serde_json::from_str(&fs::read_to_string(page.join("options.json"))?)?;
+      let option = |name: &str| -> Result<&str, EtwinError> {
+        options
+          .get(name)
+          .and_then(|value| value.as_str())
+          .ok_or_else(|| format!("missing option {:?} for {}", name, page.display()).into())
+      };
+      let checked = match kind {
+        "session" => CheckedPage::Session(option("server")?.parse()?),
+        "profile" => CheckedPage::Profile(option("server")?.parse()?, option("user_id")?.parse()?),
+        "inventory" => CheckedPage::Inventory,
+        "shop" => CheckedPage::Shop,
+        "godchildren" => CheckedPage::Godchildren(option("server")?.parse()?),
+        "forum-home" => CheckedPage::ForumHome(option("server")?.parse()?),
+        "forum-theme" => CheckedPage::ForumTheme(option("server")?.parse()?),
+        _ => CheckedPage::ForumThread(option("server")?.parse()?, option("thread_id")?.parse()?),
+      };
+      let html = scraper::Html::parse_document(&fs::read_to_string(&input)?);
+      checks.push(checked.check(page.display().to_string(), PageSource::Recorded, &html));
+    }
+  }
+  Ok(checks)
+}
+
+#[cfg(test)]
+mod test {
+  use super::check_catalog;
+  use etwin_scraper_tools::drift::{DriftReport, PageCheck, PageSource};
+  use std::path::PathBuf;
+
+  #[test]
+  fn check_recorded_pages() {
+    let root =
+      PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("../../test-resources/scraping/hammerfest");
+    let mut checks = check_catalog(&root).unwrap();
+    assert!(checks.iter().any(|check| check.kind == "hammerfest/forum-thread"));
+    // The recorded pages are supported: using them as canaries must not report anything
+    let canaries: Vec<PageCheck> = checks
+      .iter()
+      .cloned()
+      .map(|check| PageCheck {
+        source: PageSource::Canary,
+        ..check
+      })
+      .collect();
+    checks.extend(canaries);
+    let report = DriftReport::new(checks);
+    assert!(report.is_ok(), "{}", report);
+  }
+}
@@ -3,23 +3,32 @@ use reqwest::Url;
 use std::num::NonZeroU16;
 
 pub struct HammerfestUrls {
-  root: &'static str,
+  root: Url,
 }
 
 impl HammerfestUrls {
+  /// URLs of the official host of `server`.
   pub fn new(server: HammerfestServer) -> Self {
-    Self {
-      root: match server {
-        HammerfestServer::HammerfestFr => "http://www.hammerfest.fr",
-        HammerfestServer::HammerfestEs => "http://www.hammerfest.es",
-        HammerfestServer::HfestNet => "http://www.hfest.net",
-      },
-    }
+    let root = match server {
+      HammerfestServer::HammerfestFr => "http://www.hammerfest.fr",
+      HammerfestServer::HammerfestEs => "http://www.hammerfest.es",
+      HammerfestServer::HfestNet => "http://www.hfest.net",
+    };
+    Self::with_root(Url::parse(root).expect("invalid root url"))
+  }
+
+  /// URLs relative to `root`, for mirrors or local test servers.
+  pub fn with_root(root: Url) -> Self {
+    Self { root }
   }
 
   fn make_url(&self, segments: &[&str]) -> Url {
-    let mut url = Url::parse(self.root).expect("invalid root url");
-    url.path_segments_mut().expect("invalid root url").extend(segments);
+    let mut url = self.root.clone();
+    url
+      .path_segments_mut()
+      .expect("invalid root url")
+      .pop_if_empty()
+      .extend(segments);
     url
   }
 
@@ -63,3 +72,29 @@ impl HammerfestUrls {
     url
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::HammerfestUrls;
+  use etwin_core::hammerfest::{HammerfestServer, HammerfestUserId};
+  use reqwest::Url;
+  use std::str::FromStr;
+
+  #[test]
+  fn official_root() {
+    let urls = HammerfestUrls::new(HammerfestServer::HammerfestFr);
+    assert_eq!(urls.root().as_str(), "http://www.hammerfest.fr/");
+    assert_eq!(urls.login().as_str(), "http://www.hammerfest.fr/login.html");
+  }
+
+  #[test]
+  fn custom_root() {
+    let urls = HammerfestUrls::with_root(Url::parse("http://localhost:8080/hammerfest.fr/").unwrap());
+    let user_id = HammerfestUserId::from_str("127").unwrap();
+    assert_eq!(urls.root().as_str(), "http://localhost:8080/hammerfest.fr");
+    assert_eq!(
+      urls.user(&user_id).as_str(),
+      "http://localhost:8080/hammerfest.fr/user.html/127"
+    );
+  }
+}
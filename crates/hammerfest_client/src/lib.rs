@@ -3,6 +3,10 @@ mod http;
 #[cfg(feature = "mem")]
 mod mem;
 
+#[cfg(feature = "http")]
+pub use http::reparse::{reparse_page, HammerfestArchivedPage};
+#[cfg(feature = "http")]
+pub use http::selfcheck::check_catalog;
 #[cfg(feature = "http")]
 pub use http::HttpHammerfestClient;
 #[cfg(feature = "mem")]
@@ -3,7 +3,7 @@ use etwin_core::clock::Clock;
 use etwin_core::hammerfest::{
   GetHammerfestUserOptions, HammerfestForumThemePageResponse, HammerfestForumThreadPageResponse,
   HammerfestGodchildrenResponse, HammerfestInventoryResponse, HammerfestProfileResponse, HammerfestShopResponse,
-  HammerfestStore, HammerfestUserId, ShortHammerfestUser, StoredHammerfestUser,
+  HammerfestStore, HammerfestUserId, ShortHammerfestUser, StoredHammerfestItems, StoredHammerfestUser,
 };
 use etwin_core::types::EtwinError;
 use std::collections::HashMap;
@@ -87,8 +87,33 @@ where
     Ok(())
   }
 
-  async fn touch_inventory(&self, _respone: &HammerfestInventoryResponse) -> Result<(), EtwinError> {
-    eprintln!("Stub: Incomplete `MemHammerfestSore::touch_inventory` implementation");
+  async fn touch_inventory(&self, response: &HammerfestInventoryResponse) -> Result<(), EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    let short = &response.session.user;
+    let (archived_at, profile, items) = match state.get_user(&short.id) {
+      Some(user) => (user.archived_at, user.profile.clone(), user.items.clone()),
+      None => (now, None, None),
+    };
+    let items = match items {
+      Some(items) if items.items == response.inventory => StoredHammerfestItems {
+        last_archived_at: now,
+        ..items
+      },
+      _ => StoredHammerfestItems {
+        first_archived_at: now,
+        last_archived_at: now,
+        items: response.inventory.clone(),
+      },
+    };
+    state.touch_user(StoredHammerfestUser {
+      server: short.server,
+      id: short.id,
+      username: short.username.clone(),
+      archived_at,
+      profile,
+      items: Some(items),
+    });
     Ok(())
   }
 
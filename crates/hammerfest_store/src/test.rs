@@ -20,6 +20,8 @@ macro_rules! test_hammerfest_store {
     register_test!($(#[$meta])*, $api, test_empty);
     register_test!($(#[$meta])*, $api, test_touch_user);
     register_test!($(#[$meta])*, $api, test_get_missing_user);
+    register_test!($(#[$meta])*, $api, test_touch_hammerfest_inventory);
+    register_test!($(#[$meta])*, $api, test_touch_hammerfest_inventory_twice);
   };
 }
 
@@ -40,8 +42,6 @@ macro_rules! test_hammerfest_store_pg {
     register_test!($(#[$meta])*, $api, test_touch_forum_thread_page_as_moderator);
     register_test!($(#[$meta])*, $api, test_touch_godchildren);
     register_test!($(#[$meta])*, $api, test_touch_hammerfest_shop);
-    register_test!($(#[$meta])*, $api, test_touch_hammerfest_inventory);
-    register_test!($(#[$meta])*, $api, test_touch_hammerfest_inventory_twice);
   };
 }
 
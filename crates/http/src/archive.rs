@@ -0,0 +1,267 @@
+//! Raw page archival in the [WARC](https://iipc.github.io/warc-specifications/specifications/warc-format/warc-1.0/)
+//! format.
+//!
+//! Only `response` records are written: each record contains the target URL, the fetch time and the full HTTP
+//! response (status line, headers and body). Headers carrying session state (`Set-Cookie`) and secret query
+//! parameters (such as `access_token`) are not archived.
+
+use crate::capture::redact_url;
+use crate::{HttpError, HttpResponse};
+use auto_impl::auto_impl;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, SET_COOKIE, TRANSFER_ENCODING};
+use reqwest::{StatusCode, Url};
+use std::convert::TryFrom;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Sink receiving every page fetched by a client.
+#[auto_impl(&, Arc, Box)]
+pub trait PageArchive: Send + Sync {
+  fn archive(&self, time: DateTime<Utc>, res: &HttpResponse) -> Result<(), HttpError>;
+}
+
+/// Archive appending the pages to a WARC file.
+pub struct WarcWriter {
+  path: PathBuf,
+  file: Mutex<File>,
+}
+
+impl WarcWriter {
+  /// Opens `{dir}/{name}.warc` in append mode, creating the directory if needed.
+  pub fn create(dir: &Path, name: &str) -> Result<Self, HttpError> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.warc", name));
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    Ok(Self {
+      path,
+      file: Mutex::new(file),
+    })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+}
+
+impl PageArchive for WarcWriter {
+  fn archive(&self, time: DateTime<Utc>, res: &HttpResponse) -> Result<(), HttpError> {
+    let record = write_record(time, res);
+    // Write the record with a single call so concurrent writers never interleave
+    let mut file = self.file.lock().unwrap();
+    file.write_all(&record)?;
+    file.flush()?;
+    Ok(())
+  }
+}
+
+/// Response read back from a WARC file.
+#[derive(Clone, Debug)]
+pub struct WarcRecord {
+  pub time: DateTime<Utc>,
+  pub response: HttpResponse,
+}
+
+fn write_record(time: DateTime<Utc>, res: &HttpResponse) -> Vec<u8> {
+  let mut block: Vec<u8> = Vec::new();
+  block.extend_from_slice(
+    format!(
+      "HTTP/1.1 {} {}\r\n",
+      res.status.as_u16(),
+      res.status.canonical_reason().unwrap_or_default()
+    )
+    .as_bytes(),
+  );
+  for (name, value) in res.headers.iter() {
+    // The body is stored decoded, and the session must not leak into the archive
+    if name == SET_COOKIE || name == TRANSFER_ENCODING {
+      continue;
+    }
+    block.extend_from_slice(name.as_str().as_bytes());
+    block.extend_from_slice(b": ");
+    block.extend_from_slice(value.as_bytes());
+    block.extend_from_slice(b"\r\n");
+  }
+  block.extend_from_slice(b"\r\n");
+  block.extend_from_slice(&res.body);
+
+  let mut record: Vec<u8> = Vec::new();
+  record.extend_from_slice(b"WARC/1.0\r\n");
+  record.extend_from_slice(b"WARC-Type: response\r\n");
+  record.extend_from_slice(format!("WARC-Record-ID: <urn:uuid:{}>\r\n", uuid::Uuid::new_v4()).as_bytes());
+  record.extend_from_slice(format!("WARC-Date: {}\r\n", time.to_rfc3339_opts(SecondsFormat::Millis, true)).as_bytes());
+  record.extend_from_slice(format!("WARC-Target-URI: {}\r\n", redact_url(&res.url)).as_bytes());
+  record.extend_from_slice(b"Content-Type: application/http;msgtype=response\r\n");
+  record.extend_from_slice(format!("Content-Length: {}\r\n", block.len()).as_bytes());
+  record.extend_from_slice(b"\r\n");
+  record.extend_from_slice(&block);
+  record.extend_from_slice(b"\r\n\r\n");
+  record
+}
+
+/// Reads the `response` records of a WARC file, other record types are skipped.
+pub fn read_warc(path: &Path) -> Result<Vec<WarcRecord>, HttpError> {
+  let data = fs::read(path)?;
+  let invalid = |msg: &str| HttpError::InvalidCapture(format!("{}: {}", path.display(), msg));
+  let mut records = Vec::new();
+  let mut input: &[u8] = &data;
+  loop {
+    input = trim_start_newlines(input);
+    if input.is_empty() {
+      break;
+    }
+    let (version, rest) = split_line(input).ok_or_else(|| invalid("truncated record"))?;
+    if !version.starts_with("WARC/") {
+      return Err(invalid("missing WARC version line"));
+    }
+    let (fields, rest) = read_fields(rest).ok_or_else(|| invalid("invalid record header"))?;
+    let field = |name: &str| {
+      fields
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+    };
+    let len: usize = field("Content-Length")
+      .and_then(|len| len.parse().ok())
+      .ok_or_else(|| invalid("missing Content-Length"))?;
+    if rest.len() < len {
+      return Err(invalid("truncated record block"));
+    }
+    let (block, rest) = rest.split_at(len);
+    input = rest;
+    if field("WARC-Type") != Some("response") {
+      continue;
+    }
+    let time = field("WARC-Date")
+      .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+      .ok_or_else(|| invalid("invalid WARC-Date"))?
+      .with_timezone(&Utc);
+    let url = field("WARC-Target-URI")
+      .and_then(|url| Url::parse(url.trim_start_matches('<').trim_end_matches('>')).ok())
+      .ok_or_else(|| invalid("invalid WARC-Target-URI"))?;
+    let response = read_http_response(url, block).ok_or_else(|| invalid("invalid HTTP response"))?;
+    records.push(WarcRecord { time, response });
+  }
+  Ok(records)
+}
+
+fn read_http_response(url: Url, block: &[u8]) -> Option<HttpResponse> {
+  let (status_line, rest) = split_line(block)?;
+  let status = status_line.split(' ').nth(1)?.parse::<u16>().ok()?;
+  let status = StatusCode::from_u16(status).ok()?;
+  let (fields, body) = read_fields(rest)?;
+  let mut headers = HeaderMap::new();
+  for (name, value) in fields {
+    headers.append(
+      HeaderName::try_from(name.as_str()).ok()?,
+      HeaderValue::try_from(value.as_str()).ok()?,
+    );
+  }
+  Some(HttpResponse {
+    url,
+    status,
+    headers,
+    body: body.to_vec(),
+  })
+}
+
+/// Reads `Name: value` lines until an empty line.
+fn read_fields(mut input: &[u8]) -> Option<(Vec<(String, String)>, &[u8])> {
+  let mut fields = Vec::new();
+  loop {
+    let (line, rest) = split_line(input)?;
+    input = rest;
+    if line.is_empty() {
+      return Some((fields, input));
+    }
+    let (name, value) = line.split_once(':')?;
+    fields.push((name.trim().to_string(), value.trim().to_string()));
+  }
+}
+
+fn split_line(input: &[u8]) -> Option<(&str, &[u8])> {
+  let end = input.iter().position(|b| *b == b'\n')?;
+  let line = std::str::from_utf8(&input[..end]).ok()?;
+  Some((line.trim_end_matches('\r'), &input[end + 1..]))
+}
+
+fn trim_start_newlines(mut input: &[u8]) -> &[u8] {
+  while let [b'\r' | b'\n', rest @ ..] = input {
+    input = rest;
+  }
+  input
+}
+
+#[cfg(test)]
+mod test {
+  use crate::archive::{read_warc, PageArchive, WarcWriter};
+  use crate::HttpResponse;
+  use chrono::{TimeZone, Utc};
+  use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, SET_COOKIE};
+  use reqwest::{StatusCode, Url};
+
+  #[test]
+  fn write_then_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = WarcWriter::create(dir.path(), "EtwinHammerfestScraper").unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=UTF-8"));
+    headers.insert(SET_COOKIE, HeaderValue::from_static("SID=secret; Path=/"));
+    let profile = HttpResponse {
+      url: Url::parse("http://www.hammerfest.fr/user.html/127").unwrap(),
+      status: StatusCode::OK,
+      headers,
+      body: b"<html>\r\n\r\n<body>WARC/1.0</body></html>".to_vec(),
+    };
+    let missing = HttpResponse {
+      url: Url::parse("http://www.hammerfest.fr/forum.html/thread/1?page=1").unwrap(),
+      status: StatusCode::NOT_FOUND,
+      headers: HeaderMap::new(),
+      body: Vec::new(),
+    };
+    writer
+      .archive(Utc.ymd(2021, 1, 1).and_hms_milli(10, 20, 30, 400), &profile)
+      .unwrap();
+    writer.archive(Utc.ymd(2021, 1, 2).and_hms(0, 0, 0), &missing).unwrap();
+
+    let records = read_warc(writer.path()).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].time, Utc.ymd(2021, 1, 1).and_hms_milli(10, 20, 30, 400));
+    assert_eq!(records[0].response.url, profile.url);
+    assert_eq!(records[0].response.status, StatusCode::OK);
+    assert_eq!(records[0].response.body, profile.body);
+    assert_eq!(
+      records[0].response.headers.get(CONTENT_TYPE).unwrap(),
+      "text/html; charset=UTF-8"
+    );
+    assert!(records[0].response.headers.get(SET_COOKIE).is_none());
+    assert_eq!(records[1].time, Utc.ymd(2021, 1, 2).and_hms(0, 0, 0));
+    assert_eq!(records[1].response.url, missing.url);
+    assert_eq!(records[1].response.status, StatusCode::NOT_FOUND);
+    assert!(records[1].response.body.is_empty());
+  }
+
+  #[test]
+  fn redact_target_uri() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = WarcWriter::create(dir.path(), "EtwinTwinoidClient").unwrap();
+    let me = HttpResponse {
+      url: Url::parse("https://twinoid.com/graph/me?access_token=secret&fields=id").unwrap(),
+      status: StatusCode::OK,
+      headers: HeaderMap::new(),
+      body: b"{}".to_vec(),
+    };
+    writer.archive(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0), &me).unwrap();
+
+    let raw = std::fs::read_to_string(writer.path()).unwrap();
+    assert!(!raw.contains("secret"));
+    let records = read_warc(writer.path()).unwrap();
+    assert_eq!(
+      records[0].response.url.as_str(),
+      "https://twinoid.com/graph/me?access_token=REDACTED&fields=id"
+    );
+  }
+}
@@ -0,0 +1,199 @@
+//! On-disk format of recorded exchanges.
+//!
+//! Each exchange `n` is stored as `{n:06}.json` (metadata) and `{n:06}.body` (raw response body).
+
+use crate::{HttpError, HttpRequest, HttpResponse};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Query parameters whose value is never written to disk.
+const SECRET_PARAMS: [&str; 6] = [
+  "access_token",
+  "client_secret",
+  "code",
+  "pass",
+  "password",
+  "refresh_token",
+];
+
+const REDACTED: &str = "REDACTED";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CapturedRequest {
+  pub method: String,
+  /// Request URL, with secret query parameters redacted
+  pub url: String,
+  /// Hex-encoded SHA-256 digest of the request body
+  pub body_sha256: String,
+}
+
+impl CapturedRequest {
+  pub fn new(req: &HttpRequest) -> Self {
+    Self {
+      method: req.method.to_string(),
+      url: redact_url(&req.url),
+      body_sha256: hex::encode(Sha256::digest(&req.body)),
+    }
+  }
+
+  /// Request URL without the query, used as a fallback key when replaying
+  pub fn path(&self) -> &str {
+    self.url.split('?').next().unwrap_or_default()
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CapturedResponse {
+  pub url: String,
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CapturedExchange {
+  pub request: CapturedRequest,
+  pub response: CapturedResponse,
+}
+
+pub(crate) fn meta_path(dir: &Path, id: u64) -> PathBuf {
+  dir.join(format!("{:06}.json", id))
+}
+
+pub(crate) fn body_path(dir: &Path, id: u64) -> PathBuf {
+  dir.join(format!("{:06}.body", id))
+}
+
+pub(crate) fn write_exchange(dir: &Path, id: u64, req: &HttpRequest, res: &HttpResponse) -> Result<(), HttpError> {
+  let exchange = CapturedExchange {
+    request: CapturedRequest::new(req),
+    response: CapturedResponse {
+      // Redirects are not followed: the response URL is the request URL, with the same secrets
+      url: redact_url(&res.url),
+      status: res.status.as_u16(),
+      headers: res
+        .headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+        .collect(),
+    },
+  };
+  let meta = serde_json::to_vec_pretty(&exchange).map_err(|e| HttpError::InvalidCapture(e.to_string()))?;
+  fs::write(meta_path(dir, id), meta)?;
+  fs::write(body_path(dir, id), &res.body)?;
+  Ok(())
+}
+
+/// Reads all the exchanges of a capture directory, sorted by id.
+pub(crate) fn read_exchanges(dir: &Path) -> Result<Vec<(CapturedRequest, HttpResponse)>, HttpError> {
+  let mut ids: Vec<u64> = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+      continue;
+    }
+    let id = path
+      .file_stem()
+      .and_then(|stem| stem.to_str())
+      .and_then(|stem| stem.parse::<u64>().ok())
+      .ok_or_else(|| HttpError::InvalidCapture(path.display().to_string()))?;
+    ids.push(id);
+  }
+  ids.sort_unstable();
+
+  let mut exchanges = Vec::with_capacity(ids.len());
+  for id in ids {
+    let path = meta_path(dir, id);
+    let invalid = |e: &dyn std::fmt::Display| HttpError::InvalidCapture(format!("{}: {}", path.display(), e));
+    let exchange: CapturedExchange = serde_json::from_slice(&fs::read(&path)?).map_err(|e| invalid(&e))?;
+    let mut headers = HeaderMap::new();
+    for (name, value) in exchange.response.headers.iter() {
+      let name = HeaderName::try_from(name.as_str()).map_err(|e| invalid(&e))?;
+      let value = HeaderValue::try_from(value.as_str()).map_err(|e| invalid(&e))?;
+      headers.append(name, value);
+    }
+    let response = HttpResponse {
+      url: Url::parse(&exchange.response.url).map_err(|e| invalid(&e))?,
+      status: StatusCode::from_u16(exchange.response.status).map_err(|e| invalid(&e))?,
+      headers,
+      body: fs::read(body_path(dir, id))?,
+    };
+    exchanges.push((exchange.request, response));
+  }
+  Ok(exchanges)
+}
+
+pub(crate) fn method_eq(method: &Method, captured: &CapturedRequest) -> bool {
+  method.as_str() == captured.method
+}
+
+/// Returns `url` with the values of the secret query parameters replaced.
+pub(crate) fn redact_url(url: &Url) -> String {
+  if !url
+    .query_pairs()
+    .any(|(name, _)| SECRET_PARAMS.contains(&name.as_ref()))
+  {
+    return url.to_string();
+  }
+  let pairs: Vec<(String, String)> = url
+    .query_pairs()
+    .map(|(name, value)| {
+      let value = if SECRET_PARAMS.contains(&name.as_ref()) {
+        String::from(REDACTED)
+      } else {
+        value.into_owned()
+      };
+      (name.into_owned(), value)
+    })
+    .collect();
+  let mut url = url.clone();
+  url.query_pairs_mut().clear().extend_pairs(pairs);
+  url.to_string()
+}
+
+#[cfg(test)]
+mod test {
+  use super::{body_path, meta_path, redact_url, write_exchange};
+  use crate::{HttpRequest, HttpResponse};
+  use reqwest::header::HeaderMap;
+  use reqwest::{StatusCode, Url};
+  use std::fs;
+
+  #[test]
+  fn redact_access_token() {
+    let url = Url::parse("https://twinoid.com/graph/me?access_token=secret&fields=id,name").unwrap();
+    assert_eq!(
+      redact_url(&url),
+      "https://twinoid.com/graph/me?access_token=REDACTED&fields=id%2Cname"
+    );
+  }
+
+  #[test]
+  fn keep_public_url() {
+    let url = Url::parse("http://www.popotamo.com/user/480534?lang=fr").unwrap();
+    assert_eq!(redact_url(&url), "http://www.popotamo.com/user/480534?lang=fr");
+  }
+
+  #[test]
+  fn redact_written_exchange() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = Url::parse("https://twinoid.com/graph/me?access_token=secret_token&fields=id").unwrap();
+    let req = HttpRequest::get(url.clone());
+    let res = HttpResponse {
+      url,
+      status: StatusCode::OK,
+      headers: HeaderMap::new(),
+      body: br#"{"id":38}"#.to_vec(),
+    };
+    write_exchange(dir.path(), 0, &req, &res).unwrap();
+    let meta = fs::read_to_string(meta_path(dir.path(), 0)).unwrap();
+    let body = fs::read_to_string(body_path(dir.path(), 0)).unwrap();
+    assert!(!meta.contains("secret_token"), "token written to the capture: {}", meta);
+    assert!(!body.contains("secret_token"));
+    assert!(meta.contains("access_token=REDACTED"));
+  }
+}
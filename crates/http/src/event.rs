@@ -0,0 +1,409 @@
+//! Structured events emitted by the game clients around their HTTP exchanges.
+//!
+//! Clients are generic over an [etwin_log::Logger] receiving [HttpClientEvent]s. Each event carries the name of
+//! the client (its user agent) so a single logger can be shared by several clients.
+//!
+//! Events never contain secrets: URLs are logged as [RedactedUrl]s and the error messages mentioning them are redacted
+//! the same way.
+
+use crate::archive::PageArchive;
+use crate::capture::redact_url;
+use crate::{HttpError, HttpLogger, HttpRequest, HttpResponse, HttpTransport};
+use chrono::{DateTime, Utc};
+use etwin_log::{Logger, NoopLogger, StderrLogger};
+use reqwest::{Method, StatusCode, Url};
+use std::fmt;
+use std::sync::Arc;
+
+/// Maximum length of the page snippet attached to parse failures, in bytes.
+pub const SNIPPET_LEN: usize = 512;
+
+/// URL safe to log: the values of its secret query parameters (such as `access_token`) are redacted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedactedUrl(String);
+
+impl RedactedUrl {
+  pub fn new(url: &Url) -> Self {
+    Self(redact_url(url))
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+
+  /// Returns the message of `error`, where the occurrences of the raw `url` are replaced by this redacted URL.
+  pub fn redact_error(&self, url: &Url, error: &dyn fmt::Display) -> String {
+    error.to_string().replace(url.as_str(), self.as_str())
+  }
+}
+
+impl fmt::Display for RedactedUrl {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum HttpClientEvent<'a> {
+  RequestStart(RequestStartEvent<'a>),
+  ResponseStatus(ResponseStatusEvent<'a>),
+  RequestError(RequestErrorEvent<'a>),
+  ParseFailure(ParseFailureEvent<'a>),
+  ArchiveError(ArchiveErrorEvent<'a>),
+}
+
+/// The client is about to send a request.
+#[derive(Copy, Clone, Debug)]
+pub struct RequestStartEvent<'a> {
+  pub client: &'static str,
+  pub method: &'a Method,
+  pub url: &'a RedactedUrl,
+}
+
+/// The client received a response, whatever its status.
+#[derive(Copy, Clone, Debug)]
+pub struct ResponseStatusEvent<'a> {
+  pub client: &'static str,
+  pub method: &'a Method,
+  pub url: &'a RedactedUrl,
+  pub status: StatusCode,
+}
+
+/// The request failed without a response (network error, timeout, replay miss, ...).
+#[derive(Copy, Clone, Debug)]
+pub struct RequestErrorEvent<'a> {
+  pub client: &'static str,
+  pub method: &'a Method,
+  pub url: &'a RedactedUrl,
+  pub error: &'a str,
+}
+
+/// The response was received but its content could not be read.
+#[derive(Copy, Clone, Debug)]
+pub struct ParseFailureEvent<'a> {
+  pub client: &'static str,
+  pub url: &'a RedactedUrl,
+  pub error: &'a str,
+  /// Start of the page, truncated to [SNIPPET_LEN] bytes
+  pub snippet: &'a str,
+}
+
+/// The response was received but could not be written to the page archive.
+#[derive(Copy, Clone, Debug)]
+pub struct ArchiveErrorEvent<'a> {
+  pub client: &'static str,
+  pub url: &'a RedactedUrl,
+  pub error: &'a str,
+}
+
+impl<'a> From<RequestStartEvent<'a>> for HttpClientEvent<'a> {
+  fn from(ev: RequestStartEvent<'a>) -> Self {
+    Self::RequestStart(ev)
+  }
+}
+
+impl<'a> From<ResponseStatusEvent<'a>> for HttpClientEvent<'a> {
+  fn from(ev: ResponseStatusEvent<'a>) -> Self {
+    Self::ResponseStatus(ev)
+  }
+}
+
+impl<'a> From<RequestErrorEvent<'a>> for HttpClientEvent<'a> {
+  fn from(ev: RequestErrorEvent<'a>) -> Self {
+    Self::RequestError(ev)
+  }
+}
+
+impl<'a> From<ParseFailureEvent<'a>> for HttpClientEvent<'a> {
+  fn from(ev: ParseFailureEvent<'a>) -> Self {
+    Self::ParseFailure(ev)
+  }
+}
+
+impl<'a> From<ArchiveErrorEvent<'a>> for HttpClientEvent<'a> {
+  fn from(ev: ArchiveErrorEvent<'a>) -> Self {
+    Self::ArchiveError(ev)
+  }
+}
+
+/// Creates the logger selected by `logger`.
+pub fn new_logger(logger: HttpLogger) -> Arc<dyn for<'r> Logger<HttpClientEvent<'r>>> {
+  match logger {
+    HttpLogger::Noop => Arc::new(NoopLogger),
+    HttpLogger::Stderr => Arc::new(StderrLogger),
+  }
+}
+
+/// Returns the start of `text`, truncated to [SNIPPET_LEN] bytes on a char boundary.
+pub fn snippet(text: &str) -> &str {
+  if text.len() <= SNIPPET_LEN {
+    return text;
+  }
+  let mut end = SNIPPET_LEN;
+  while !text.is_char_boundary(end) {
+    end -= 1;
+  }
+  &text[..end]
+}
+
+/// Sends `req` through `transport`, logging the start of the request and its outcome.
+pub async fn send_logged<TyLogger>(
+  transport: &dyn HttpTransport,
+  logger: &TyLogger,
+  client: &'static str,
+  req: HttpRequest,
+) -> Result<HttpResponse, HttpError>
+where
+  TyLogger: ?Sized + for<'r> Logger<HttpClientEvent<'r>>,
+{
+  let method = req.method.clone();
+  let raw_url = req.url.clone();
+  let url = RedactedUrl::new(&raw_url);
+  logger.log(
+    RequestStartEvent {
+      client,
+      method: &method,
+      url: &url,
+    }
+    .into(),
+  );
+  let result = transport.send(req).await;
+  match &result {
+    Ok(res) => logger.log(
+      ResponseStatusEvent {
+        client,
+        method: &method,
+        url: &url,
+        status: res.status(),
+      }
+      .into(),
+    ),
+    Err(error) => logger.log(
+      RequestErrorEvent {
+        client,
+        method: &method,
+        url: &url,
+        error: &url.redact_error(&raw_url, error),
+      }
+      .into(),
+    ),
+  }
+  result
+}
+
+/// Writes `res` to `archive`, logging the failure if it can't be written.
+///
+/// The archive is a side copy of the fetched pages: failing to write it must not fail the request.
+pub fn archive_logged<TyLogger>(
+  archive: &dyn PageArchive,
+  logger: &TyLogger,
+  client: &'static str,
+  time: DateTime<Utc>,
+  res: &HttpResponse,
+) where
+  TyLogger: ?Sized + for<'r> Logger<HttpClientEvent<'r>>,
+{
+  if let Err(error) = archive.archive(time, res) {
+    let url = RedactedUrl::new(&res.url);
+    logger.log(
+      ArchiveErrorEvent {
+        client,
+        url: &url,
+        error: &url.redact_error(&res.url, &error),
+      }
+      .into(),
+    );
+  }
+}
+
+/// Logs a parse failure for the page at `url` if `result` is an error.
+pub fn log_parse_failure<T, E, TyLogger>(
+  logger: &TyLogger,
+  client: &'static str,
+  url: &Url,
+  text: &str,
+  result: Result<T, E>,
+) -> Result<T, E>
+where
+  E: std::fmt::Display,
+  TyLogger: ?Sized + for<'r> Logger<HttpClientEvent<'r>>,
+{
+  if let Err(e) = &result {
+    let redacted_url = RedactedUrl::new(url);
+    let error = redacted_url.redact_error(url, e);
+    logger.log(
+      ParseFailureEvent {
+        client,
+        url: &redacted_url,
+        error: &error,
+        snippet: snippet(text),
+      }
+      .into(),
+    );
+  }
+  result
+}
+
+#[cfg(test)]
+mod test {
+  use crate::archive::PageArchive;
+  use crate::event::{archive_logged, log_parse_failure, send_logged, snippet, HttpClientEvent, SNIPPET_LEN};
+  use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+  use async_trait::async_trait;
+  use chrono::{DateTime, TimeZone, Utc};
+  use etwin_log::Logger;
+  use reqwest::header::HeaderMap;
+  use reqwest::{Method, StatusCode, Url};
+  use std::sync::Mutex;
+
+  /// Logger keeping a textual summary of each event.
+  #[derive(Default)]
+  struct MemLogger(Mutex<Vec<String>>);
+
+  impl<'a> Logger<HttpClientEvent<'a>> for MemLogger {
+    fn log(&self, ev: HttpClientEvent<'a>) {
+      let summary = match ev {
+        HttpClientEvent::RequestStart(ev) => format!("start {} {} {}", ev.client, ev.method, ev.url),
+        HttpClientEvent::ResponseStatus(ev) => format!("status {} {}", ev.url, ev.status.as_u16()),
+        HttpClientEvent::RequestError(ev) => format!("error {} {}", ev.url, ev.error),
+        HttpClientEvent::ParseFailure(ev) => format!("parse {} {}: {}", ev.url, ev.error, ev.snippet),
+        HttpClientEvent::ArchiveError(ev) => format!("archive {} {}", ev.url, ev.error),
+      };
+      self.0.lock().unwrap().push(summary);
+    }
+  }
+
+  struct StatusTransport(Option<StatusCode>);
+
+  #[async_trait]
+  impl HttpTransport for StatusTransport {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+      match self.0 {
+        Some(status) => Ok(HttpResponse {
+          url: req.url,
+          status,
+          headers: HeaderMap::new(),
+          body: Vec::new(),
+        }),
+        None => Err(HttpError::ReplayMiss(req.method, req.url.to_string())),
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn log_response_status() {
+    let logger = MemLogger::default();
+    let req = HttpRequest::get(Url::parse("http://www.hammerfest.fr/").unwrap());
+    send_logged(&StatusTransport(Some(StatusCode::NOT_FOUND)), &logger, "Test", req)
+      .await
+      .unwrap();
+    assert_eq!(
+      logger.0.into_inner().unwrap(),
+      vec![
+        String::from("start Test GET http://www.hammerfest.fr/"),
+        String::from("status http://www.hammerfest.fr/ 404"),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn log_request_error() {
+    let logger = MemLogger::default();
+    let req = HttpRequest::new(Method::POST, Url::parse("http://www.hammerfest.fr/login.html").unwrap());
+    let res = send_logged(&StatusTransport(None), &logger, "Test", req).await;
+    assert!(res.is_err());
+    assert_eq!(
+      logger.0.into_inner().unwrap(),
+      vec![
+        String::from("start Test POST http://www.hammerfest.fr/login.html"),
+        String::from(
+          "error http://www.hammerfest.fr/login.html no recorded response for POST http://www.hammerfest.fr/login.html"
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn log_parse_failure_snippet() {
+    let logger = MemLogger::default();
+    let url = Url::parse("http://www.hammerfest.fr/user.html/127").unwrap();
+    let ok: Result<u8, &str> = log_parse_failure(&logger, "Test", &url, "<html>", Ok(1));
+    assert_eq!(ok, Ok(1));
+    let err: Result<u8, &str> = log_parse_failure(&logger, "Test", &url, "<html>", Err("missing user"));
+    assert_eq!(err, Err("missing user"));
+    assert_eq!(
+      logger.0.into_inner().unwrap(),
+      vec![String::from(
+        "parse http://www.hammerfest.fr/user.html/127 missing user: <html>"
+      )]
+    );
+  }
+
+  struct FailingArchive;
+
+  impl PageArchive for FailingArchive {
+    fn archive(&self, _time: DateTime<Utc>, _res: &HttpResponse) -> Result<(), HttpError> {
+      Err(HttpError::InvalidCapture(String::from("disk full")))
+    }
+  }
+
+  #[test]
+  fn log_archive_error() {
+    let logger = MemLogger::default();
+    let res = HttpResponse {
+      url: Url::parse("http://www.hammerfest.fr/user.html/127").unwrap(),
+      status: StatusCode::OK,
+      headers: HeaderMap::new(),
+      body: Vec::new(),
+    };
+    archive_logged(
+      &FailingArchive,
+      &logger,
+      "Test",
+      Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      &res,
+    );
+    assert_eq!(
+      logger.0.into_inner().unwrap(),
+      vec![String::from(
+        "archive http://www.hammerfest.fr/user.html/127 invalid HTTP capture disk full"
+      )]
+    );
+  }
+
+  #[tokio::test]
+  async fn redact_secrets_from_events() {
+    let logger = MemLogger::default();
+    let url = Url::parse("https://twinoid.com/graph/me?access_token=secret_token&fields=id").unwrap();
+    let res = send_logged(&StatusTransport(None), &logger, "Test", HttpRequest::get(url.clone())).await;
+    assert!(res.is_err());
+    let err: Result<u8, String> = Err(format!("unexpected page {}", url));
+    let _ = log_parse_failure(&logger, "Test", &url, "<html>", err);
+    let res = HttpResponse {
+      url,
+      status: StatusCode::OK,
+      headers: HeaderMap::new(),
+      body: Vec::new(),
+    };
+    archive_logged(
+      &FailingArchive,
+      &logger,
+      "Test",
+      Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      &res,
+    );
+    let logged = logger.0.into_inner().unwrap();
+    assert_eq!(logged.len(), 4);
+    for line in logged {
+      assert!(!line.contains("secret_token"), "token leaked to the logger: {}", line);
+      assert!(line.contains("access_token=REDACTED"), "missing redacted URL: {}", line);
+    }
+  }
+
+  #[test]
+  fn truncate_snippet_on_char_boundary() {
+    let text = format!("{}é", "a".repeat(SNIPPET_LEN - 1));
+    assert_eq!(snippet(&text), "a".repeat(SNIPPET_LEN - 1));
+    assert_eq!(snippet("short"), "short");
+  }
+}
@@ -0,0 +1,353 @@
+//! HTTP transport shared by the game clients.
+//!
+//! Clients build an [HttpRequest] and send it through a [HttpTransport]. The transport is either
+//! [live::ReqwestTransport] (network), [record::RecordTransport] (network + capture to a directory) or
+//! [replay::ReplayTransport] (offline, from a capture directory). Requests sent to the network go through
+//! [policy::PolicyTransport], which applies the rate limit and retry policy.
+//!
+//! Independently of the transport, clients can archive the raw pages they fetch through an
+//! [archive::PageArchive], and report each exchange to a logger through [event::HttpClientEvent].
+
+pub mod archive;
+mod capture;
+pub mod event;
+pub mod live;
+pub mod policy;
+pub mod record;
+pub mod replay;
+
+use archive::{PageArchive, WarcWriter};
+use async_trait::async_trait;
+use auto_impl::auto_impl;
+use policy::{PolicyTransport, RateLimit, RetryPolicy};
+use reqwest::header::{HeaderMap, HeaderName, COOKIE, SET_COOKIE};
+use reqwest::{Method, StatusCode, Url};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use reqwest;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HttpError {
+  #[error("HTTP transport error")]
+  Transport(#[from] reqwest::Error),
+  #[error("unexpected HTTP status {1} for {0}")]
+  Status(Url, StatusCode),
+  #[error("failed to encode request form")]
+  Form(#[from] serde_urlencoded::ser::Error),
+  #[error("failed to access HTTP capture")]
+  CaptureIo(#[from] std::io::Error),
+  #[error("invalid HTTP capture {0}")]
+  InvalidCapture(String),
+  #[error("no recorded response for {0} {1}")]
+  ReplayMiss(Method, String),
+  #[error("server is under maintenance: {0}")]
+  Maintenance(Url),
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+  pub method: Method,
+  pub url: Url,
+  pub headers: Vec<(HeaderName, String)>,
+  pub body: Vec<u8>,
+  /// Whether sending the request several times has the same effect as sending it once.
+  ///
+  /// Only idempotent requests are retried after a server error or a timeout. It defaults to the idempotency of the
+  /// method: use [HttpRequest::idempotent] to opt in for a `POST` only reading data.
+  pub idempotent: bool,
+}
+
+impl HttpRequest {
+  pub fn new(method: Method, url: Url) -> Self {
+    Self {
+      idempotent: method.is_idempotent(),
+      method,
+      url,
+      headers: Vec::new(),
+      body: Vec::new(),
+    }
+  }
+
+  pub fn get(url: Url) -> Self {
+    Self::new(Method::GET, url)
+  }
+
+  pub fn post(url: Url) -> Self {
+    Self::new(Method::POST, url)
+  }
+
+  pub fn header(mut self, name: HeaderName, value: String) -> Self {
+    self.headers.push((name, value));
+    self
+  }
+
+  /// Marks the request as safe to retry, whatever its method.
+  pub fn idempotent(mut self) -> Self {
+    self.idempotent = true;
+    self
+  }
+
+  /// Adds a `Cookie` header, the caller is responsible for escaping.
+  pub fn cookie(self, name: &str, value: &str) -> Self {
+    self.header(COOKIE, format!("{}={}", name, value))
+  }
+
+  /// Sets an `application/x-www-form-urlencoded` body.
+  pub fn form<T: Serialize + ?Sized>(self, form: &T) -> Result<Self, HttpError> {
+    let body = serde_urlencoded::to_string(form)?;
+    let mut req = self.header(
+      reqwest::header::CONTENT_TYPE,
+      String::from("application/x-www-form-urlencoded"),
+    );
+    req.body = body.into_bytes();
+    Ok(req)
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+  pub url: Url,
+  pub status: StatusCode,
+  pub headers: HeaderMap,
+  pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+  pub fn status(&self) -> StatusCode {
+    self.status
+  }
+
+  pub fn url(&self) -> &Url {
+    &self.url
+  }
+
+  pub fn headers(&self) -> &HeaderMap {
+    &self.headers
+  }
+
+  pub fn bytes(&self) -> &[u8] {
+    &self.body
+  }
+
+  /// Returns an error if the status is a client or server error.
+  pub fn error_for_status(self) -> Result<Self, HttpError> {
+    if self.status.is_client_error() || self.status.is_server_error() {
+      Err(HttpError::Status(self.url, self.status))
+    } else {
+      Ok(self)
+    }
+  }
+
+  /// Decodes the body using the charset from the `Content-Type` header, defaulting to UTF-8.
+  pub fn text(&self) -> String {
+    let charset = self
+      .headers
+      .get(reqwest::header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| {
+        value
+          .split(';')
+          .filter_map(|param| param.trim().split_once('='))
+          .find(|(name, _)| name.eq_ignore_ascii_case("charset"))
+          .map(|(_, charset)| charset.trim_matches('"'))
+      });
+    let encoding = charset
+      .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+      .unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(&self.body);
+    text.into_owned()
+  }
+
+  /// Value of the cookie `name` set by this response.
+  pub fn cookie(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .get_all(SET_COOKIE)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .filter_map(|value| value.split(';').next()?.split_once('='))
+      .find(|(cookie_name, _)| cookie_name.trim() == name)
+      .map(|(_, value)| value.trim())
+  }
+}
+
+#[async_trait]
+#[auto_impl(&, Arc, Box)]
+pub trait HttpTransport: Send + Sync {
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError>;
+}
+
+/// How a client reaches the remote server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum HttpMode {
+  #[default]
+  Live,
+  /// Send requests to the remote server and persist the exchanges to the provided directory.
+  Record(PathBuf),
+  /// Serve the exchanges persisted in the provided directory.
+  Replay(PathBuf),
+}
+
+/// Destination of the events of a client, see [event::new_logger].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HttpLogger {
+  /// Discard the events
+  #[default]
+  Noop,
+  /// Print the events to the standard error
+  Stderr,
+}
+
+/// Options shared by all the game clients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpOptions {
+  pub mode: HttpMode,
+  /// Timeout for a single attempt
+  pub timeout: Duration,
+  /// Rate limit applied to each server, ignored when replaying
+  pub rate_limit: RateLimit,
+  /// Retry policy for transient failures, ignored when replaying
+  pub retry: RetryPolicy,
+  /// Directory where the fetched pages are archived as WARC files
+  pub archive: Option<PathBuf>,
+  /// Proxy used for all the requests sent to the network
+  pub proxy: Option<Url>,
+  /// Base URL replacing the official host of a server, keyed by server name (e.g. `hammerfest.fr`)
+  pub base_urls: BTreeMap<String, Url>,
+  /// Logger receiving the events of the clients
+  pub logger: HttpLogger,
+}
+
+impl HttpOptions {
+  /// Base URL configured for `server`, if it does not use its official host.
+  pub fn base_url(&self, server: &str) -> Option<&Url> {
+    self.base_urls.get(server)
+  }
+}
+
+impl Default for HttpOptions {
+  fn default() -> Self {
+    Self {
+      mode: HttpMode::Live,
+      timeout: Duration::from_millis(5000),
+      rate_limit: RateLimit::default(),
+      retry: RetryPolicy::default(),
+      archive: None,
+      proxy: None,
+      base_urls: BTreeMap::new(),
+      logger: HttpLogger::Noop,
+    }
+  }
+}
+
+/// Creates the transport described by `options`. Requests to the network use the provided user agent.
+pub fn new_transport(options: &HttpOptions, user_agent: &'static str) -> Result<Arc<dyn HttpTransport>, HttpError> {
+  let network = || -> Result<_, HttpError> {
+    Ok(PolicyTransport::new(
+      live::ReqwestTransport::new(user_agent, options.timeout, options.proxy.as_ref())?,
+      options.rate_limit,
+      options.retry,
+    ))
+  };
+  Ok(match &options.mode {
+    HttpMode::Live => Arc::new(network()?),
+    HttpMode::Record(dir) => Arc::new(record::RecordTransport::new(network()?, dir.clone())?),
+    HttpMode::Replay(dir) => Arc::new(replay::ReplayTransport::open(dir)?),
+  })
+}
+
+/// Path segments of `url` below `root`, or `None` if `url` is not a page of `root`.
+///
+/// Used to find the server of an archived page, whether it was fetched from the official host or from a mirror
+/// configured in [HttpOptions::base_urls].
+pub fn path_below<'u>(root: &Url, url: &'u Url) -> Option<Vec<&'u str>> {
+  if root.scheme() != url.scheme()
+    || root.host() != url.host()
+    || root.port_or_known_default() != url.port_or_known_default()
+  {
+    return None;
+  }
+  let mut root_segments: Vec<&str> = root.path_segments().map(|s| s.collect()).unwrap_or_default();
+  if root_segments.last() == Some(&"") {
+    root_segments.pop();
+  }
+  let segments: Vec<&'u str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+  if !segments.starts_with(&root_segments) {
+    return None;
+  }
+  Some(segments[root_segments.len()..].to_vec())
+}
+
+/// Creates the page archive described by `options`, if any. Pages are written to `{archive}/{name}.warc`.
+pub fn new_archive(options: &HttpOptions, name: &str) -> Result<Option<Arc<dyn PageArchive>>, HttpError> {
+  match &options.archive {
+    Some(dir) => Ok(Some(Arc::new(WarcWriter::create(dir, name)?))),
+    None => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{path_below, HttpRequest, HttpResponse};
+  use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, SET_COOKIE};
+  use reqwest::{StatusCode, Url};
+
+  fn response(headers: HeaderMap, body: &[u8]) -> HttpResponse {
+    HttpResponse {
+      url: Url::parse("http://www.popotamo.com/").unwrap(),
+      status: StatusCode::OK,
+      headers,
+      body: body.to_vec(),
+    }
+  }
+
+  #[test]
+  fn read_cookie() {
+    let mut headers = HeaderMap::new();
+    headers.append(SET_COOKIE, HeaderValue::from_static("lang=fr; Path=/"));
+    headers.append(SET_COOKIE, HeaderValue::from_static("sid=abc123; Path=/; HttpOnly"));
+    let res = response(headers, b"");
+    assert_eq!(res.cookie("sid"), Some("abc123"));
+    assert_eq!(res.cookie("lang"), Some("fr"));
+    assert_eq!(res.cookie("missing"), None);
+  }
+
+  #[test]
+  fn read_latin1_text() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=ISO-8859-1"));
+    let res = response(headers, b"caf\xe9");
+    assert_eq!(res.text(), "café");
+  }
+
+  #[test]
+  fn read_default_utf8_text() {
+    let res = response(HeaderMap::new(), "café".as_bytes());
+    assert_eq!(res.text(), "café");
+  }
+
+  #[test]
+  fn encode_form() {
+    let req = HttpRequest::post(Url::parse("http://www.popotamo.com/user/login").unwrap())
+      .form(&[("login", "alice"), ("pass", "a&b")])
+      .unwrap();
+    assert_eq!(req.body, b"login=alice&pass=a%26b");
+  }
+
+  #[test]
+  fn match_path_below_root() {
+    let official = Url::parse("http://www.hammerfest.fr/").unwrap();
+    let mirror = Url::parse("http://127.0.0.1:8080/hammerfest.fr/").unwrap();
+    let page = Url::parse("http://127.0.0.1:8080/hammerfest.fr/user.html/127").unwrap();
+    assert_eq!(path_below(&mirror, &page), Some(vec!["user.html", "127"]));
+    assert_eq!(path_below(&official, &page), None);
+    let other = Url::parse("http://127.0.0.1:8080/dinoparc.com/?a=user&id=1").unwrap();
+    assert_eq!(path_below(&mirror, &other), None);
+    let page = Url::parse("http://www.hammerfest.fr/user.html/127").unwrap();
+    assert_eq!(path_below(&official, &page), Some(vec!["user.html", "127"]));
+  }
+}
@@ -0,0 +1,51 @@
+use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+use async_trait::async_trait;
+use reqwest::{Client, Proxy, Url};
+use std::time::Duration;
+
+/// Transport sending the requests over the network.
+///
+/// Redirections are never followed: the clients inspect them to detect authentication failures.
+pub struct ReqwestTransport {
+  client: Client,
+}
+
+impl ReqwestTransport {
+  /// Creates a transport sending the requests directly, or through `proxy` if provided.
+  pub fn new(user_agent: &'static str, timeout: Duration, proxy: Option<&Url>) -> Result<Self, HttpError> {
+    let mut builder = Client::builder()
+      .user_agent(user_agent)
+      .timeout(timeout)
+      .redirect(reqwest::redirect::Policy::none());
+    if let Some(proxy) = proxy {
+      builder = builder.proxy(Proxy::all(proxy.clone())?);
+    }
+    Ok(Self {
+      client: builder.build()?,
+    })
+  }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let mut builder = self.client.request(req.method, req.url);
+    for (name, value) in req.headers {
+      builder = builder.header(name, value);
+    }
+    if !req.body.is_empty() {
+      builder = builder.body(req.body);
+    }
+    let res = builder.send().await?;
+    let url = res.url().clone();
+    let status = res.status();
+    let headers = res.headers().clone();
+    let body = res.bytes().await?.to_vec();
+    Ok(HttpResponse {
+      url,
+      status,
+      headers,
+      body,
+    })
+  }
+}
@@ -0,0 +1,425 @@
+use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+use async_trait::async_trait;
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// Token bucket limiting the requests sent to a single server.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+  /// Time to refill one token
+  pub period: Duration,
+  /// Maximum number of tokens, i.e. requests that can be sent without waiting
+  pub burst: u32,
+}
+
+impl Default for RateLimit {
+  fn default() -> Self {
+    Self {
+      period: Duration::from_millis(250),
+      burst: 4,
+    }
+  }
+}
+
+/// Exponential backoff applied to transient failures.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+  /// Maximum number of retries after the first attempt
+  pub max_retries: u32,
+  /// Delay before the first retry, doubled for each subsequent retry
+  pub base_delay: Duration,
+  /// Upper bound for the delay between two attempts
+  pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_retries: 3,
+      base_delay: Duration::from_millis(500),
+      max_delay: Duration::from_secs(30),
+    }
+  }
+}
+
+impl RetryPolicy {
+  fn backoff(&self, attempt: u32) -> Duration {
+    let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+    self
+      .base_delay
+      .checked_mul(factor)
+      .unwrap_or(self.max_delay)
+      .min(self.max_delay)
+  }
+}
+
+/// Phrases identifying the maintenance pages of the games, matched against the lowercase page title.
+///
+/// The games announce their maintenance with a regular HTML page, often with a `200` status.
+pub const MAINTENANCE_TITLES: [&str; 5] = [
+  "en maintenance",
+  "under maintenance",
+  "maintenance en cours",
+  "en mantenimiento",
+  "maintenance in progress",
+];
+
+/// Classification of the result of an attempt.
+enum Outcome {
+  /// Final result, returned to the caller
+  Done,
+  /// Server error (`5xx`), retried with backoff
+  ServerError,
+  /// The request timed out, retried with backoff
+  Timeout,
+  /// The connection failed: the request did not reach the server, retried with backoff
+  Connect,
+  /// Maintenance (`503` or maintenance page), retried after the delay requested by the server if any
+  Maintenance(Option<Duration>),
+}
+
+impl Outcome {
+  /// Whether the attempt can be retried: requests that may have reached the server are only retried if they are
+  /// idempotent.
+  fn is_retryable(&self, req: &HttpRequest) -> bool {
+    match self {
+      Self::Done => false,
+      Self::Connect => true,
+      Self::ServerError | Self::Timeout | Self::Maintenance(_) => req.idempotent,
+    }
+  }
+}
+
+fn classify(result: &Result<HttpResponse, HttpError>) -> Outcome {
+  match result {
+    Ok(res) if res.status() == StatusCode::SERVICE_UNAVAILABLE => Outcome::Maintenance(retry_after(res)),
+    Ok(res) if res.status().is_server_error() => Outcome::ServerError,
+    Ok(res) if is_maintenance_page(res) => Outcome::Maintenance(retry_after(res)),
+    Err(HttpError::Transport(e)) if e.is_connect() => Outcome::Connect,
+    Err(HttpError::Transport(e)) if e.is_timeout() => Outcome::Timeout,
+    _ => Outcome::Done,
+  }
+}
+
+/// Checks if the title of the HTML page `res` announces a maintenance, see [MAINTENANCE_TITLES].
+fn is_maintenance_page(res: &HttpResponse) -> bool {
+  let is_html = res
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.contains("html"));
+  if !is_html {
+    return false;
+  }
+  let text = res.text().to_lowercase();
+  let title = text
+    .find("<title")
+    .and_then(|start| text[start..].find('>').map(|end| start + end + 1))
+    .and_then(|start| text[start..].find("</title>").map(|end| &text[start..start + end]));
+  match title {
+    Some(title) => MAINTENANCE_TITLES.iter().any(|phrase| title.contains(phrase)),
+    None => false,
+  }
+}
+
+/// Reads the `Retry-After` header, only the delay-seconds form is supported.
+fn retry_after(res: &HttpResponse) -> Option<Duration> {
+  let value = res.headers().get(RETRY_AFTER)?.to_str().ok()?;
+  value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+struct TokenBucket {
+  /// Available tokens, negative when requests are waiting for a token
+  tokens: f64,
+  updated_at: Instant,
+}
+
+/// Transport applying a per-server rate limit and retrying transient failures of the inner transport.
+///
+/// Failed connections are always retried, other failures only if the request is idempotent (see
+/// [HttpRequest::idempotent]): a login form is never sent twice.
+///
+/// Servers are identified by the host and port of the request URL: each game server (`HammerfestServer`,
+/// `DinoparcServer`, ...) has its own bucket.
+pub struct PolicyTransport<TyInner: HttpTransport> {
+  inner: TyInner,
+  rate_limit: RateLimit,
+  retry: RetryPolicy,
+  buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl<TyInner: HttpTransport> PolicyTransport<TyInner> {
+  pub fn new(inner: TyInner, rate_limit: RateLimit, retry: RetryPolicy) -> Self {
+    Self {
+      inner,
+      rate_limit,
+      retry,
+      buckets: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// Reserves a token for `server`, returns how long to wait before using it.
+  fn reserve(&self, server: String) -> Duration {
+    let now = Instant::now();
+    let burst = f64::from(self.rate_limit.burst.max(1));
+    let period = self.rate_limit.period.as_secs_f64();
+    let mut buckets = self.buckets.lock().unwrap();
+    let bucket = buckets.entry(server).or_insert(TokenBucket {
+      tokens: burst,
+      updated_at: now,
+    });
+    if period > 0.0 {
+      let refill = now.duration_since(bucket.updated_at).as_secs_f64() / period;
+      bucket.tokens = (bucket.tokens + refill).min(burst);
+    } else {
+      bucket.tokens = burst;
+    }
+    bucket.updated_at = now;
+    bucket.tokens -= 1.0;
+    if bucket.tokens >= 0.0 {
+      Duration::from_secs(0)
+    } else {
+      Duration::from_secs_f64(-bucket.tokens * period)
+    }
+  }
+
+  async fn acquire(&self, req: &HttpRequest) {
+    let server = format!(
+      "{}:{}",
+      req.url.host_str().unwrap_or_default(),
+      req.url.port_or_known_default().unwrap_or_default()
+    );
+    let wait = self.reserve(server);
+    if wait > Duration::from_secs(0) {
+      sleep(wait).await;
+    }
+  }
+}
+
+#[async_trait]
+impl<TyInner: HttpTransport> HttpTransport for PolicyTransport<TyInner> {
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let mut attempt: u32 = 0;
+    loop {
+      self.acquire(&req).await;
+      let result = self.inner.send(req.clone()).await;
+      let outcome = classify(&result);
+      let delay = match outcome {
+        Outcome::Done => return result,
+        Outcome::ServerError | Outcome::Timeout | Outcome::Connect => self.retry.backoff(attempt),
+        Outcome::Maintenance(retry_after) => retry_after
+          .map(|d| d.min(self.retry.max_delay))
+          .unwrap_or_else(|| self.retry.backoff(attempt)),
+      };
+      if attempt >= self.retry.max_retries || !outcome.is_retryable(&req) {
+        return match outcome {
+          Outcome::Maintenance(_) => Err(HttpError::Maintenance(req.url)),
+          _ => result,
+        };
+      }
+      sleep(delay).await;
+      attempt += 1;
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::policy::{PolicyTransport, RateLimit, RetryPolicy};
+  use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+  use async_trait::async_trait;
+  use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+  use reqwest::{StatusCode, Url};
+  use std::sync::Mutex;
+  use std::time::Duration;
+  use tokio::time::Instant;
+
+  /// Transport answering with the provided statuses, in order, and recording when each request was received.
+  struct ScriptedTransport {
+    statuses: Mutex<Vec<(StatusCode, Option<&'static str>)>>,
+    body: Option<&'static str>,
+    received: Mutex<Vec<Instant>>,
+  }
+
+  impl ScriptedTransport {
+    fn new(statuses: Vec<(StatusCode, Option<&'static str>)>) -> Self {
+      Self {
+        statuses: Mutex::new(statuses),
+        body: None,
+        received: Mutex::new(Vec::new()),
+      }
+    }
+
+    /// Answers with the HTML page `body`.
+    fn with_html(statuses: Vec<(StatusCode, Option<&'static str>)>, body: &'static str) -> Self {
+      Self {
+        body: Some(body),
+        ..Self::new(statuses)
+      }
+    }
+
+    fn received(&self) -> Vec<Instant> {
+      self.received.lock().unwrap().clone()
+    }
+  }
+
+  #[async_trait]
+  impl HttpTransport for ScriptedTransport {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+      self.received.lock().unwrap().push(Instant::now());
+      let (status, retry_after) = self.statuses.lock().unwrap().remove(0);
+      let mut headers = HeaderMap::new();
+      if let Some(retry_after) = retry_after {
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(retry_after));
+      }
+      if self.body.is_some() {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=UTF-8"));
+      }
+      Ok(HttpResponse {
+        url: req.url,
+        status,
+        headers,
+        body: self.body.unwrap_or_default().as_bytes().to_vec(),
+      })
+    }
+  }
+
+  fn get(url: &str) -> HttpRequest {
+    HttpRequest::get(Url::parse(url).unwrap())
+  }
+
+  fn retry() -> RetryPolicy {
+    RetryPolicy {
+      max_retries: 2,
+      base_delay: Duration::from_secs(1),
+      max_delay: Duration::from_secs(10),
+    }
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn retry_server_error_with_backoff() {
+    let inner = ScriptedTransport::new(vec![
+      (StatusCode::BAD_GATEWAY, None),
+      (StatusCode::INTERNAL_SERVER_ERROR, None),
+      (StatusCode::OK, None),
+    ]);
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let start = Instant::now();
+    let res = transport.send(get("http://www.hammerfest.fr/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let received: Vec<Duration> = inner.received().iter().map(|t| t.duration_since(start)).collect();
+    assert_eq!(
+      received,
+      vec![Duration::from_secs(0), Duration::from_secs(1), Duration::from_secs(3)]
+    );
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn return_last_server_error() {
+    let inner = ScriptedTransport::new(vec![(StatusCode::BAD_GATEWAY, None); 3]);
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let res = transport.send(get("http://www.hammerfest.fr/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(inner.received().len(), 3);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn do_not_retry_client_error() {
+    let inner = ScriptedTransport::new(vec![(StatusCode::NOT_FOUND, None)]);
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let res = transport.send(get("http://www.hammerfest.fr/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(inner.received().len(), 1);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn maintenance_uses_retry_after() {
+    let inner = ScriptedTransport::new(vec![(StatusCode::SERVICE_UNAVAILABLE, Some("7")); 3]);
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let start = Instant::now();
+    let res = transport.send(get("http://www.dinoparc.com/")).await;
+    assert!(matches!(res, Err(HttpError::Maintenance(_))));
+    let received: Vec<Duration> = inner.received().iter().map(|t| t.duration_since(start)).collect();
+    assert_eq!(
+      received,
+      vec![Duration::from_secs(0), Duration::from_secs(7), Duration::from_secs(14)]
+    );
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn do_not_retry_non_idempotent_request() {
+    let inner = ScriptedTransport::new(vec![(StatusCode::BAD_GATEWAY, None), (StatusCode::OK, None)]);
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let req = HttpRequest::post(Url::parse("http://www.hammerfest.fr/login.html").unwrap());
+    let res = transport.send(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(inner.received().len(), 1);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn retry_idempotent_post() {
+    let inner = ScriptedTransport::new(vec![(StatusCode::BAD_GATEWAY, None), (StatusCode::OK, None)]);
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let req = HttpRequest::post(Url::parse("http://www.hammerfest.fr/forum.html").unwrap()).idempotent();
+    let res = transport.send(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(inner.received().len(), 2);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn detect_maintenance_page() {
+    let inner = ScriptedTransport::with_html(
+      vec![(StatusCode::OK, None); 3],
+      "<html><head><title>Dinoparc - Site en maintenance</title></head><body>Revenez plus tard</body></html>",
+    );
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let res = transport.send(get("http://www.dinoparc.com/")).await;
+    assert!(matches!(res, Err(HttpError::Maintenance(_))));
+    assert_eq!(inner.received().len(), 3);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn ignore_maintenance_in_page_body() {
+    let inner = ScriptedTransport::with_html(
+      vec![(StatusCode::OK, None)],
+      "<html><head><title>Forum</title></head><body>Le site sera en maintenance demain</body></html>",
+    );
+    let transport = PolicyTransport::new(&inner, RateLimit::default(), retry());
+    let res = transport.send(get("http://www.dinoparc.com/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(inner.received().len(), 1);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn rate_limit_per_server() {
+    let inner = ScriptedTransport::new(vec![(StatusCode::OK, None); 5]);
+    let rate_limit = RateLimit {
+      period: Duration::from_secs(1),
+      burst: 2,
+    };
+    let transport = PolicyTransport::new(&inner, rate_limit, retry());
+    let start = Instant::now();
+    for _ in 0..3 {
+      transport.send(get("http://www.hammerfest.fr/")).await.unwrap();
+    }
+    // Other server, with its own bucket
+    for _ in 0..2 {
+      transport.send(get("http://www.hammerfest.es/")).await.unwrap();
+    }
+    let received: Vec<Duration> = inner.received().iter().map(|t| t.duration_since(start)).collect();
+    assert_eq!(
+      received,
+      vec![
+        Duration::from_secs(0),
+        Duration::from_secs(0),
+        Duration::from_secs(1),
+        Duration::from_secs(1),
+        Duration::from_secs(1),
+      ]
+    );
+  }
+}
@@ -0,0 +1,48 @@
+use crate::capture::write_exchange;
+use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+use async_trait::async_trait;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Transport persisting every successful exchange of the inner transport to a directory.
+///
+/// The directory can then be served by [crate::replay::ReplayTransport].
+pub struct RecordTransport<TyInner: HttpTransport> {
+  inner: TyInner,
+  dir: PathBuf,
+  next_id: AtomicU64,
+}
+
+impl<TyInner: HttpTransport> RecordTransport<TyInner> {
+  /// Creates the directory if needed. Exchanges are appended after the ones already present.
+  pub fn new(inner: TyInner, dir: PathBuf) -> Result<Self, HttpError> {
+    fs::create_dir_all(&dir)?;
+    let mut next_id: u64 = 0;
+    for entry in fs::read_dir(&dir)? {
+      let path = entry?.path();
+      if let Some(id) = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.parse::<u64>().ok())
+      {
+        next_id = next_id.max(id + 1);
+      }
+    }
+    Ok(Self {
+      inner,
+      dir,
+      next_id: AtomicU64::new(next_id),
+    })
+  }
+}
+
+#[async_trait]
+impl<TyInner: HttpTransport> HttpTransport for RecordTransport<TyInner> {
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let res = self.inner.send(req.clone()).await?;
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+    write_exchange(&self.dir, id, &req, &res)?;
+    Ok(res)
+  }
+}
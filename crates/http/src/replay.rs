@@ -0,0 +1,155 @@
+use crate::capture::{method_eq, read_exchanges, CapturedRequest};
+use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Mutex;
+
+struct ReplayEntry {
+  request: CapturedRequest,
+  response: HttpResponse,
+  used: bool,
+}
+
+/// Transport serving the exchanges recorded by [crate::record::RecordTransport], without network access.
+///
+/// Each recorded exchange is served once, in recording order. A request matches an exchange with the same method,
+/// URL and body; if there is none, it falls back to an exchange with the same method and URL ignoring the query
+/// string and body (so requests with varying tokens or nonces can still be replayed).
+pub struct ReplayTransport {
+  entries: Mutex<Vec<ReplayEntry>>,
+}
+
+impl ReplayTransport {
+  pub fn open(dir: &Path) -> Result<Self, HttpError> {
+    let entries = read_exchanges(dir)?
+      .into_iter()
+      .map(|(request, response)| ReplayEntry {
+        request,
+        response,
+        used: false,
+      })
+      .collect();
+    Ok(Self {
+      entries: Mutex::new(entries),
+    })
+  }
+}
+
+#[async_trait]
+impl HttpTransport for ReplayTransport {
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let key = CapturedRequest::new(&req);
+    let mut entries = self.entries.lock().unwrap();
+    let unused = || entries.iter().enumerate().filter(|(_, e)| !e.used);
+    let found = unused()
+      .find(|(_, e)| e.request == key)
+      .or_else(|| unused().find(|(_, e)| method_eq(&req.method, &e.request) && e.request.path() == key.path()))
+      .map(|(i, _)| i);
+    match found {
+      Some(i) => {
+        let entry = &mut entries[i];
+        entry.used = true;
+        Ok(entry.response.clone())
+      }
+      None => Err(HttpError::ReplayMiss(req.method, key.url)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::record::RecordTransport;
+  use crate::replay::ReplayTransport;
+  use crate::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+  use async_trait::async_trait;
+  use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+  use reqwest::{StatusCode, Url};
+  use std::sync::atomic::{AtomicU64, Ordering};
+
+  /// Transport answering with the request URL and a counter
+  struct EchoTransport(AtomicU64);
+
+  #[async_trait]
+  impl HttpTransport for EchoTransport {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+      let count = self.0.fetch_add(1, Ordering::Relaxed);
+      let mut headers = HeaderMap::new();
+      headers.insert(SET_COOKIE, HeaderValue::from_static("sid=abc; Path=/"));
+      Ok(HttpResponse {
+        url: req.url.clone(),
+        status: StatusCode::OK,
+        headers,
+        body: format!("{} {}", req.url.path(), count).into_bytes(),
+      })
+    }
+  }
+
+  fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+  }
+
+  #[tokio::test]
+  async fn record_then_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+      let recorder = RecordTransport::new(EchoTransport(AtomicU64::new(0)), dir.path().to_path_buf()).unwrap();
+      let login = HttpRequest::post(url("http://www.popotamo.com/user/login"))
+        .form(&[("login", "alice"), ("pass", "secret")])
+        .unwrap();
+      recorder.send(login).await.unwrap();
+      recorder
+        .send(HttpRequest::get(url("http://www.popotamo.com/")).cookie("sid", "abc"))
+        .await
+        .unwrap();
+      recorder
+        .send(HttpRequest::get(url("http://www.popotamo.com/")))
+        .await
+        .unwrap();
+    }
+
+    let raw = std::fs::read_to_string(dir.path().join("000000.json")).unwrap();
+    assert!(!raw.contains("secret"));
+
+    let replay = ReplayTransport::open(dir.path()).unwrap();
+    let login = HttpRequest::post(url("http://www.popotamo.com/user/login"))
+      .form(&[("login", "alice"), ("pass", "secret")])
+      .unwrap();
+    let res = replay.send(login).await.unwrap();
+    assert_eq!(res.cookie("sid"), Some("abc"));
+    assert_eq!(res.bytes(), b"/user/login 0");
+    let res = replay
+      .send(HttpRequest::get(url("http://www.popotamo.com/")))
+      .await
+      .unwrap();
+    assert_eq!(res.bytes(), b"/ 1");
+    let res = replay
+      .send(HttpRequest::get(url("http://www.popotamo.com/")))
+      .await
+      .unwrap();
+    assert_eq!(res.bytes(), b"/ 2");
+    let res = replay.send(HttpRequest::get(url("http://www.popotamo.com/"))).await;
+    assert!(matches!(res, Err(HttpError::ReplayMiss(_, _))));
+  }
+
+  #[tokio::test]
+  async fn replay_ignores_query_as_fallback() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+      let recorder = RecordTransport::new(EchoTransport(AtomicU64::new(0)), dir.path().to_path_buf()).unwrap();
+      recorder
+        .send(HttpRequest::get(url(
+          "https://twinoid.com/graph/me?access_token=a&fields=id",
+        )))
+        .await
+        .unwrap();
+    }
+    let replay = ReplayTransport::open(dir.path()).unwrap();
+    let res = replay
+      .send(HttpRequest::get(url(
+        "https://twinoid.com/graph/me?access_token=b&fields=id,name",
+      )))
+      .await
+      .unwrap();
+    assert_eq!(res.bytes(), b"/graph/me 0");
+  }
+}
@@ -4,9 +4,10 @@ use etwin_core::core::RawUserDot;
 use etwin_core::dinoparc::{DinoparcServer, DinoparcUserId, DinoparcUserIdRef};
 use etwin_core::hammerfest::{HammerfestServer, HammerfestUserId, HammerfestUserIdRef};
 use etwin_core::link::{
-  DeleteLinkError, DeleteLinkOptions, GetLinkOptions, GetLinksFromEtwinOptions, LinkStore, OldRawLink, RawLink,
-  RemoteUserIdRef, TouchLinkError, TouchLinkOptions, VersionedRawLink, VersionedRawLinks,
+  DeleteLinkError, DeleteLinkOptions, GetLinkOptions, GetLinksFromEtwinOptions, GetLinksFromTwinoidOptions, LinkStore,
+  OldRawLink, RawLink, RemoteUserIdRef, TouchLinkError, TouchLinkOptions, VersionedRawLink, VersionedRawLinks,
 };
+use etwin_core::popotamo::{PopotamoServer, PopotamoUserId, PopotamoUserIdRef};
 use etwin_core::twinoid::{TwinoidUserId, TwinoidUserIdRef};
 use etwin_core::types::EtwinError;
 use etwin_core::user::UserId;
@@ -32,6 +33,8 @@ struct StoreState {
   to_dinoparc: HashMap<(UserId, DinoparcServer), RawLinkHistory<DinoparcUserIdRef>>,
   from_hammerfest: HashMap<(HammerfestServer, HammerfestUserId), RawLinkHistory<HammerfestUserIdRef>>,
   to_hammerfest: HashMap<(UserId, HammerfestServer), RawLinkHistory<HammerfestUserIdRef>>,
+  from_popotamo: HashMap<(PopotamoServer, PopotamoUserId), RawLinkHistory<PopotamoUserIdRef>>,
+  to_popotamo: HashMap<(UserId, PopotamoServer), RawLinkHistory<PopotamoUserIdRef>>,
   from_twinoid: HashMap<TwinoidUserId, RawLinkHistory<TwinoidUserIdRef>>,
   to_twinoid: HashMap<UserId, RawLinkHistory<TwinoidUserIdRef>>,
 }
@@ -43,6 +46,8 @@ impl StoreState {
       to_dinoparc: HashMap::new(),
       from_hammerfest: HashMap::new(),
       to_hammerfest: HashMap::new(),
+      from_popotamo: HashMap::new(),
+      to_popotamo: HashMap::new(),
       from_twinoid: HashMap::new(),
       to_twinoid: HashMap::new(),
     }
@@ -125,6 +130,33 @@ where
     )
   }
 
+  async fn touch_popotamo_link(
+    &self,
+    options: &TouchLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, TouchLinkError<PopotamoUserIdRef>> {
+    let mut state = self.state.write().unwrap();
+    let state: &mut StoreState = &mut state;
+    touch_link(
+      &mut state.from_popotamo,
+      &mut state.to_popotamo,
+      (options.remote.server, options.remote.id),
+      (options.etwin.id, options.remote.server),
+      || {
+        let now = self.clock.now();
+        let link: RawLink<PopotamoUserIdRef> = RawLink {
+          link: RawUserDot {
+            time: now,
+            user: options.linked_by,
+          },
+          unlink: (),
+          etwin: options.etwin,
+          remote: options.remote,
+        };
+        link
+      },
+    )
+  }
+
   async fn touch_twinoid_link(
     &self,
     options: &TouchLinkOptions<TwinoidUserIdRef>,
@@ -210,6 +242,35 @@ where
     .map(|_| Default::default())
   }
 
+  async fn delete_popotamo_link(
+    &self,
+    options: &DeleteLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, DeleteLinkError<PopotamoUserIdRef>> {
+    let mut state = self.state.write().unwrap();
+    let state: &mut StoreState = &mut state;
+    delete_link(
+      &mut state.from_popotamo,
+      &mut state.to_popotamo,
+      (options.remote.server, options.remote.id),
+      (options.etwin.id, options.remote.server),
+      |start| {
+        let now = self.clock.now();
+        let link: OldRawLink<PopotamoUserIdRef> = OldRawLink {
+          link: start.link,
+          unlink: RawUserDot {
+            time: now,
+            user: options.unlinked_by,
+          },
+          etwin: options.etwin,
+          remote: options.remote,
+        };
+        link
+      },
+      || DeleteLinkError::NotFound(options.etwin, options.remote),
+    )
+    .map(|_| Default::default())
+  }
+
   async fn delete_twinoid_link(
     &self,
     options: &DeleteLinkOptions<TwinoidUserIdRef>,
@@ -285,6 +346,29 @@ where
     }
   }
 
+  async fn get_link_from_popotamo(
+    &self,
+    options: &GetLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, EtwinError> {
+    // assert!(options.time.is_none());
+    let state = self.state.read().unwrap();
+    let link = state.from_popotamo.get(&(options.remote.server, options.remote.id));
+
+    match link {
+      None => Ok(VersionedRawLink {
+        current: None,
+        old: vec![],
+      }),
+      Some(link) => {
+        let link: VersionedRawLink<PopotamoUserIdRef> = VersionedRawLink {
+          current: link.current.clone(),
+          old: vec![],
+        };
+        Ok(link)
+      }
+    }
+  }
+
   async fn get_link_from_twinoid(
     &self,
     options: &GetLinkOptions<TwinoidUserIdRef>,
@@ -308,6 +392,21 @@ where
     }
   }
 
+  async fn get_links_from_twinoid(
+    &self,
+    options: &GetLinksFromTwinoidOptions,
+  ) -> Result<Vec<RawLink<TwinoidUserIdRef>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(
+      options
+        .remotes
+        .iter()
+        .filter_map(|remote| state.from_twinoid.get(&remote.id))
+        .filter_map(|link| link.current.clone())
+        .collect(),
+    )
+  }
+
   async fn get_links_from_etwin(&self, options: &GetLinksFromEtwinOptions) -> Result<VersionedRawLinks, EtwinError> {
     let state = self.state.read().unwrap();
     let mut links = VersionedRawLinks::default();
@@ -332,6 +431,15 @@ where
       }
     }
 
+    for srv in PopotamoServer::iter() {
+      let empty = RawLinkHistory::<PopotamoUserIdRef>::default();
+      let link = state.to_popotamo.get(&(options.etwin.id, srv)).unwrap_or(&empty);
+      match srv {
+        PopotamoServer::PopotamoCom => links.popotamo_com.current = link.current.clone(),
+        PopotamoServer::EnPopotamoCom => links.en_popotamo_com.current = link.current.clone(),
+      }
+    }
+
     {
       let empty = RawLinkHistory::<TwinoidUserIdRef>::default();
       let link = state.to_twinoid.get(&options.etwin.id).unwrap_or(&empty);
@@ -5,13 +5,15 @@ use etwin_core::core::{Instant, RawUserDot};
 use etwin_core::dinoparc::{DinoparcServer, DinoparcUserId, DinoparcUserIdRef};
 use etwin_core::hammerfest::{HammerfestServer, HammerfestUserId, HammerfestUserIdRef};
 use etwin_core::link::{
-  DeleteLinkError, DeleteLinkOptions, GetLinkOptions, GetLinksFromEtwinOptions, LinkStore, OldRawLink, RawLink,
-  TouchLinkError, TouchLinkOptions, VersionedRawLink, VersionedRawLinks,
+  DeleteLinkError, DeleteLinkOptions, GetLinkOptions, GetLinksFromEtwinOptions, GetLinksFromTwinoidOptions, LinkStore,
+  OldRawLink, RawLink, TouchLinkError, TouchLinkOptions, VersionedRawLink, VersionedRawLinks,
 };
+use etwin_core::popotamo::{PopotamoServer, PopotamoUserId, PopotamoUserIdRef};
 use etwin_core::twinoid::{TwinoidUserId, TwinoidUserIdRef};
 use etwin_core::types::EtwinError;
 use etwin_core::user::{UserId, UserIdRef};
 use sqlx::PgPool;
+use std::collections::HashMap;
 
 pub struct PgLinkStore<TyClock, TyDatabase>
 where
@@ -140,6 +142,57 @@ where
     }
   }
 
+  async fn touch_popotamo_link(
+    &self,
+    options: &TouchLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, TouchLinkError<PopotamoUserIdRef>> {
+    let now = self.clock.now();
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      linked_at: Instant,
+      linked_by: UserId,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+        INSERT INTO popotamo_user_links(user_id, popotamo_server, popotamo_user_id, period, linked_by, unlinked_by)
+        VALUES ($1::USER_ID, $2::POPOTAMO_SERVER, $3::POPOTAMO_USER_ID, PERIOD($4::INSTANT, NULL), $5::USER_ID, NULL)
+        RETURNING lower(period) AS linked_at, linked_by;
+    ",
+    )
+    .bind(&options.etwin.id)
+    .bind(&options.remote.server)
+    .bind(&options.remote.id)
+    .bind(&now)
+    .bind(&options.linked_by.id)
+    .fetch_optional(self.database.as_ref())
+    .await
+    .map_err(TouchLinkError::other)?;
+
+    match row {
+      None => Ok(VersionedRawLink {
+        current: None,
+        old: vec![],
+      }),
+      Some(row) => {
+        let link: VersionedRawLink<PopotamoUserIdRef> = VersionedRawLink {
+          current: Some(RawLink {
+            link: RawUserDot {
+              time: row.linked_at,
+              user: UserIdRef { id: row.linked_by },
+            },
+            unlink: (),
+            etwin: options.etwin,
+            remote: options.remote,
+          }),
+          old: vec![],
+        };
+        Ok(link)
+      }
+    }
+  }
+
   async fn touch_twinoid_link(
     &self,
     options: &TouchLinkOptions<TwinoidUserIdRef>,
@@ -290,6 +343,56 @@ where
     Ok(link)
   }
 
+  async fn delete_popotamo_link(
+    &self,
+    options: &DeleteLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, DeleteLinkError<PopotamoUserIdRef>> {
+    let now = self.clock.now();
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      linked_at: Instant,
+      unlinked_at: Instant,
+      linked_by: UserId,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+        UPDATE popotamo_user_links
+        SET period = PERIOD(lower(period), $1::INSTANT), unlinked_by = $2::USER_ID
+        WHERE user_id = $3::USER_ID AND popotamo_server = $4::POPOTAMO_SERVER AND popotamo_user_id = $5::POPOTAMO_USER_ID AND upper_inf(period)
+        RETURNING lower(period) AS linked_at, upper(period) AS unlinked_at, linked_by;
+    ",
+    )
+      .bind(now)
+      .bind(&options.unlinked_by.id)
+      .bind(&options.etwin.id)
+      .bind(&options.remote.server)
+      .bind(&options.remote.id)
+      .fetch_optional(self.database.as_ref())
+      .await
+      .map_err(DeleteLinkError::other)?;
+
+    let row = row.ok_or(DeleteLinkError::NotFound(options.etwin, options.remote))?;
+
+    let link: VersionedRawLink<PopotamoUserIdRef> = VersionedRawLink {
+      current: None,
+      old: vec![OldRawLink {
+        link: RawUserDot {
+          time: row.linked_at,
+          user: UserIdRef { id: row.linked_by },
+        },
+        unlink: RawUserDot {
+          time: row.unlinked_at,
+          user: options.unlinked_by,
+        },
+        etwin: options.etwin,
+        remote: options.remote,
+      }],
+    };
+    Ok(link)
+  }
+
   async fn delete_twinoid_link(
     &self,
     options: &DeleteLinkOptions<TwinoidUserIdRef>,
@@ -435,6 +538,54 @@ where
     }
   }
 
+  async fn get_link_from_popotamo(
+    &self,
+    options: &GetLinkOptions<PopotamoUserIdRef>,
+  ) -> Result<VersionedRawLink<PopotamoUserIdRef>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      linked_at: Instant,
+      linked_by: UserId,
+      user_id: UserId,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+        SELECT lower(period) AS linked_at, linked_by, user_id
+        FROM popotamo_user_links
+        WHERE popotamo_server = $1::POPOTAMO_SERVER
+          AND popotamo_user_id = $2::POPOTAMO_USER_ID
+          AND upper_inf(period);
+    ",
+    )
+    .bind(&options.remote.server)
+    .bind(&options.remote.id)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    match row {
+      None => Ok(VersionedRawLink {
+        current: None,
+        old: vec![],
+      }),
+      Some(row) => {
+        let link: VersionedRawLink<PopotamoUserIdRef> = VersionedRawLink {
+          current: Some(RawLink {
+            link: RawUserDot {
+              time: row.linked_at,
+              user: UserIdRef { id: row.linked_by },
+            },
+            unlink: (),
+            etwin: UserIdRef { id: row.user_id },
+            remote: options.remote,
+          }),
+          old: vec![],
+        };
+        Ok(link)
+      }
+    }
+  }
+
   async fn get_link_from_twinoid(
     &self,
     options: &GetLinkOptions<TwinoidUserIdRef>,
@@ -480,6 +631,50 @@ where
     }
   }
 
+  async fn get_links_from_twinoid(
+    &self,
+    options: &GetLinksFromTwinoidOptions,
+  ) -> Result<Vec<RawLink<TwinoidUserIdRef>>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      twinoid_user_id: TwinoidUserId,
+      linked_at: Instant,
+      linked_by: UserId,
+      user_id: UserId,
+    }
+
+    let remotes: Vec<String> = options.remotes.iter().map(|r| r.id.to_string()).collect();
+    let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+      r"
+        SELECT twinoid_user_id, lower(period) AS linked_at, linked_by, user_id
+        FROM twinoid_user_links
+        WHERE twinoid_user_id = ANY($1::TEXT[]) AND upper_inf(period);
+    ",
+    )
+    .bind(remotes)
+    .fetch_all(self.database.as_ref())
+    .await?;
+
+    let rows: HashMap<TwinoidUserId, Row> = rows.into_iter().map(|r| (r.twinoid_user_id, r)).collect();
+    Ok(
+      options
+        .remotes
+        .iter()
+        .filter_map(|remote| {
+          rows.get(&remote.id).map(|row| RawLink {
+            link: RawUserDot {
+              time: row.linked_at,
+              user: UserIdRef { id: row.linked_by },
+            },
+            unlink: (),
+            etwin: UserIdRef { id: row.user_id },
+            remote: *remote,
+          })
+        })
+        .collect(),
+    )
+  }
+
   async fn get_links_from_etwin(&self, options: &GetLinksFromEtwinOptions) -> Result<VersionedRawLinks, EtwinError> {
     let mut links = VersionedRawLinks::default();
 
@@ -563,6 +758,45 @@ where
         }
       }
     }
+    {
+      #[derive(Debug, sqlx::FromRow)]
+      struct Row {
+        popotamo_server: PopotamoServer,
+        popotamo_user_id: PopotamoUserId,
+        linked_at: Instant,
+        linked_by: UserId,
+      }
+
+      let rows = sqlx::query_as::<_, Row>(
+        r"
+          SELECT popotamo_server, popotamo_user_id, lower(period) AS linked_at, linked_by
+          FROM popotamo_user_links
+          WHERE popotamo_user_links.user_id = $1::UUID AND upper_inf(period);
+    ",
+      )
+      .bind(&options.etwin.id)
+      .fetch_all(self.database.as_ref())
+      .await?;
+
+      for row in rows.into_iter() {
+        let link: RawLink<PopotamoUserIdRef> = RawLink {
+          link: RawUserDot {
+            time: row.linked_at,
+            user: UserIdRef { id: row.linked_by },
+          },
+          unlink: (),
+          etwin: options.etwin,
+          remote: PopotamoUserIdRef {
+            server: row.popotamo_server,
+            id: row.popotamo_user_id,
+          },
+        };
+        match link.remote.server {
+          PopotamoServer::PopotamoCom => links.popotamo_com.current = Some(link),
+          PopotamoServer::EnPopotamoCom => links.en_popotamo_com.current = Some(link),
+        }
+      }
+    }
     {
       #[derive(Debug, sqlx::FromRow)]
       struct Row {
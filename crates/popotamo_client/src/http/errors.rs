@@ -1,7 +1,10 @@
+use etwin_core::popotamo::PopotamoServer;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ScraperError {
+  #[error("Failed to detect server: missing or unknown html[lang]")]
+  ServerDetectionFailure,
   #[error("Duplicate session box, expected zero or one")]
   DuplicateSessionBox,
   #[error("Non-unique session-user rewards, expected exactly one")]
@@ -10,6 +13,8 @@ pub enum ScraperError {
   MissingSessionUserLink,
   #[error("Non-unique session user link text node, expected exactly one")]
   NonUniqueSessionUserLinkText,
+  #[error("Missing session-user counter {:?}", .0)]
+  MissingSessionUserCounter(&'static str),
   #[error("Invalid user id {:?}", .0)]
   InvalidUserId(String),
   #[error("Invalid username {:?}", .0)]
@@ -18,6 +23,30 @@ pub enum ScraperError {
   MissingLinkHref,
   #[error("Invalid user link {:?}", .0)]
   InvalidUserLink(String),
+  #[error("Non-unique profile header, expected exactly one")]
+  NonUniqueProfileHeader,
+  #[error("Missing profile field {:?}", .0)]
+  MissingProfileField(&'static str),
+  #[error("Invalid integer {:?}", .0)]
+  InvalidInteger(String),
+  #[error("Missing league icon")]
+  MissingLeague,
+  #[error("Invalid league icon {:?}", .0)]
+  InvalidLeague(String),
+  #[error("Invalid reward icon {:?}", .0)]
+  InvalidReward(String),
+  #[error("Invalid item icon {:?}", .0)]
+  InvalidItem(String),
+  #[error("Invalid sub-profile id {:?}", .0)]
+  InvalidSubProfileId(String),
+  #[error("Failed to login due to unexpected login response: {:?}", .0)]
+  UnexpectedLoginResponse(reqwest::StatusCode),
+  #[error("Missing Popotamo session cookie from response")]
+  MissingSessionCookie,
+  #[error("Popotamo session cookie is invalid or malformed")]
+  InvalidSessionCookie,
+  #[error("Invalid Popotamo credentials on {}", .0.as_str())]
+  InvalidCredentials(PopotamoServer),
   #[error("HTTP Error")]
-  HttpError(#[from] reqwest::Error),
+  HttpError(#[from] etwin_http::HttpError),
 }
@@ -1,61 +1,204 @@
 mod errors;
 mod locale;
+pub mod reparse;
 mod scraper;
 mod url;
 
+use crate::http::errors::ScraperError;
 use crate::http::url::PopotamoUrls;
 use ::scraper::Html;
 use async_trait::async_trait;
 use etwin_core::clock::Clock;
-use etwin_core::popotamo::{PopotamoClient, PopotamoProfileResponse, PopotamoUserIdRef};
+use etwin_core::popotamo::{
+  PopotamoClient, PopotamoCredentials, PopotamoProfileResponse, PopotamoServer, PopotamoSession, PopotamoSessionKey,
+  PopotamoUserIdRef,
+};
 use etwin_core::types::EtwinError;
-use reqwest::Client;
-use std::time::Duration;
+use etwin_http::archive::PageArchive;
+use etwin_http::event::{archive_logged, log_parse_failure, send_logged, HttpClientEvent};
+use etwin_http::{new_archive, new_transport, HttpError, HttpOptions, HttpRequest, HttpResponse, HttpTransport};
+use etwin_log::Logger;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
 
 const USER_AGENT: &str = "EtwinPopotamoScraper";
-const TIMEOUT: Duration = Duration::from_millis(5000);
 
-pub struct HttpPopotamoClient<TyClock> {
-  client: Client,
-  #[allow(unused)]
+pub struct HttpPopotamoClient<TyClock, TyLogger> {
+  transport: Arc<dyn HttpTransport>,
+  archive: Option<Arc<dyn PageArchive>>,
   clock: TyClock,
+  logger: TyLogger,
 }
 
-impl<TyClock> HttpPopotamoClient<TyClock>
+impl<TyClock, TyLogger> HttpPopotamoClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
-  pub fn new(clock: TyClock) -> Result<Self, EtwinError> {
+  pub fn new(clock: TyClock, logger: TyLogger) -> Result<Self, EtwinError> {
+    Self::with_options(clock, logger, &HttpOptions::default())
+  }
+
+  /// Creates a client using the provided transport options (mode, timeout, rate limit, retries and archive).
+  pub fn with_options(clock: TyClock, logger: TyLogger, options: &HttpOptions) -> Result<Self, EtwinError> {
     Ok(Self {
-      client: Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(TIMEOUT)
-        .redirect(reqwest::redirect::Policy::none())
-        .build()?,
-      clock,
+      archive: new_archive(options, USER_AGENT)?,
+      ..Self::with_transport(clock, logger, new_transport(options, USER_AGENT)?)
     })
   }
 
-  async fn get_html(&self, url: reqwest::Url) -> reqwest::Result<Html> {
-    let builder = self.client.get(url);
-    let resp = builder.send().await?;
-    let text = resp.error_for_status()?.text().await?;
-    Ok(Html::parse_document(&text))
+  pub fn with_transport(clock: TyClock, logger: TyLogger, transport: Arc<dyn HttpTransport>) -> Self {
+    Self {
+      transport,
+      archive: None,
+      clock,
+      logger,
+    }
+  }
+
+  /// Sends `req`, archiving the response if a page archive is configured.
+  async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let res = send_logged(&*self.transport, &self.logger, USER_AGENT, req).await?;
+    if let Some(archive) = &self.archive {
+      archive_logged(&**archive, &self.logger, USER_AGENT, self.clock.now(), &res);
+    }
+    Ok(res)
+  }
+
+  async fn get_text(&self, url: reqwest::Url, session: Option<&PopotamoSessionKey>) -> Result<String, HttpError> {
+    let mut req = HttpRequest::get(url);
+
+    if let Some(key) = session {
+      // No need to escape, per PopotamoSessionKey invariants.
+      req = req.cookie("sid", key.as_str());
+    }
+
+    let resp = self.send(req).await?;
+    Ok(resp.error_for_status()?.text())
+  }
+
+  /// Fetches the page at `url` and reads it with `scrape`, logging a snippet of the page if it can't be read.
+  async fn scrape<T>(
+    &self,
+    url: reqwest::Url,
+    session: Option<&PopotamoSessionKey>,
+    scrape: impl FnOnce(&Html) -> Result<T, ScraperError> + Send,
+  ) -> Result<T, EtwinError> {
+    let text = self.get_text(url.clone(), session).await?;
+    let html = Html::parse_document(&text);
+    Ok(log_parse_failure(&self.logger, USER_AGENT, &url, &text, scrape(&html))?)
   }
 }
 
 #[async_trait]
-impl<TyClock> PopotamoClient for HttpPopotamoClient<TyClock>
+impl<TyClock, TyLogger> PopotamoClient for HttpPopotamoClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
+  async fn create_session(&self, credentials: &PopotamoCredentials) -> Result<PopotamoSession, EtwinError> {
+    #[derive(Serialize)]
+    struct LoginForm<'a> {
+      login: &'a str,
+      pass: &'a str,
+    }
+
+    let server = credentials.server;
+    let req = HttpRequest::post(PopotamoUrls::new(server).login()).form(&LoginForm {
+      login: credentials.username.as_str(),
+      pass: credentials.password.as_str(),
+    })?;
+    let res = self.send(req).await?;
+
+    if !(res.status() == StatusCode::OK || res.status().is_redirection()) {
+      return Err(ScraperError::UnexpectedLoginResponse(res.status()).into());
+    }
+
+    let session_key = res.cookie("sid").ok_or(ScraperError::MissingSessionCookie)?;
+    let session_key = PopotamoSessionKey::from_str(session_key).map_err(|_| ScraperError::InvalidSessionCookie)?;
+
+    match self.test_session(server, &session_key).await? {
+      Some(session) => Ok(session),
+      None => Err(ScraperError::InvalidCredentials(server).into()),
+    }
+  }
+
+  async fn test_session(
+    &self,
+    server: PopotamoServer,
+    key: &PopotamoSessionKey,
+  ) -> Result<Option<PopotamoSession>, EtwinError> {
+    let now = self.clock.now();
+    let session_user = self
+      .scrape(PopotamoUrls::new(server).root(), Some(key), scraper::scrape_session)
+      .await?;
+    Ok(session_user.map(|session_user| PopotamoSession {
+      ctime: now,
+      atime: now,
+      key: key.clone(),
+      user: session_user.user,
+    }))
+  }
+
   async fn get_profile(&self, user: PopotamoUserIdRef) -> Result<PopotamoProfileResponse, EtwinError> {
-    let html = self.get_html(PopotamoUrls::new(user.server).user(user.id)).await?;
-    let response = scraper::scrape_profile(&html)?;
+    let response = self
+      .scrape(
+        PopotamoUrls::new(user.server).user(user.id),
+        None,
+        scraper::scrape_profile,
+      )
+      .await?;
     // TODO: Assert username matches
     Ok(response)
   }
 }
 
 #[cfg(feature = "neon")]
-impl<TyClock> neon::prelude::Finalize for HttpPopotamoClient<TyClock> where TyClock: Clock {}
+impl<TyClock, TyLogger> neon::prelude::Finalize for HttpPopotamoClient<TyClock, TyLogger>
+where
+  TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
+{
+}
+
+#[cfg(test)]
+mod test {
+  use crate::http::HttpPopotamoClient;
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::popotamo::{PopotamoClient, PopotamoProfileResponse, PopotamoServer, PopotamoUserIdRef};
+  use etwin_http::{HttpMode, HttpOptions};
+  use etwin_log::NoopLogger;
+  use std::path::PathBuf;
+  use std::str::FromStr;
+
+  #[tokio::test]
+  async fn replay_get_profile() {
+    let clock = VirtualClock::new(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+    let dir = PathBuf::from("../../test-resources/http-replay/popotamo/get_profile");
+    let client = HttpPopotamoClient::with_options(
+      clock,
+      NoopLogger,
+      &HttpOptions {
+        mode: HttpMode::Replay(dir),
+        ..HttpOptions::default()
+      },
+    )
+    .unwrap();
+
+    let actual = client
+      .get_profile(PopotamoUserIdRef {
+        server: PopotamoServer::PopotamoCom,
+        id: FromStr::from_str("480534").unwrap(),
+      })
+      .await
+      .unwrap();
+
+    let value_json = ::std::fs::read_to_string("../../test-resources/scraping/popotamo/user/480534.guest/value.json")
+      .expect("Failed to read value file");
+    let expected = serde_json::from_str::<PopotamoProfileResponse>(&value_json).expect("Failed to parse value file");
+    assert_eq!(actual, expected);
+  }
+}
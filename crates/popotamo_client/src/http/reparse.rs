@@ -0,0 +1,46 @@
+use super::scraper;
+use super::url::PopotamoUrls;
+use ::scraper::Html;
+use etwin_core::popotamo::{PopotamoProfileResponse, PopotamoServer};
+use etwin_core::types::EtwinError;
+use reqwest::Url;
+
+/// Archived page, parsed with the scraper matching its URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PopotamoArchivedPage {
+  Profile(PopotamoProfileResponse),
+}
+
+/// Parses an archived page fetched from `url`.
+///
+/// Returns `None` if `url` is not a Popotamo page with a scraper.
+pub fn reparse_page(url: &Url, html: &str) -> Result<Option<PopotamoArchivedPage>, EtwinError> {
+  if !PopotamoServer::iter().any(|server| PopotamoUrls::new(server).root().host() == url.host()) {
+    return Ok(None);
+  }
+  let segments: Vec<&str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+  let page = match segments.as_slice() {
+    ["user", _] => PopotamoArchivedPage::Profile(scraper::scrape_profile(&Html::parse_document(html))?),
+    _ => return Ok(None),
+  };
+  Ok(Some(page))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{reparse_page, PopotamoArchivedPage};
+  use etwin_core::popotamo::PopotamoProfileResponse;
+  use reqwest::Url;
+
+  #[test]
+  fn reparse_profile() {
+    let dir = "../../test-resources/scraping/popotamo/user/480534.guest";
+    let html = std::fs::read_to_string(format!("{}/main.html", dir)).unwrap();
+    let expected: PopotamoProfileResponse =
+      serde_json::from_str(&std::fs::read_to_string(format!("{}/value.json", dir)).unwrap()).unwrap();
+
+    let url = Url::parse("http://www.popotamo.com/user/480534").unwrap();
+    let actual = reparse_page(&url, &html).unwrap();
+    assert_eq!(actual, Some(PopotamoArchivedPage::Profile(expected)));
+  }
+}
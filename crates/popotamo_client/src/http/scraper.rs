@@ -1,24 +1,66 @@
 use crate::http::errors::ScraperError;
 use crate::http::url::PopotamoUrls;
 use etwin_core::popotamo::{
-  PopotamoProfile, PopotamoProfileResponse, PopotamoServer, PopotamoSessionUser, PopotamoUserId, PopotamoUsername,
-  ShortPopotamoUser,
+  PopotamoItemKey, PopotamoLeagueId, PopotamoProfile, PopotamoProfileResponse, PopotamoRewardKey, PopotamoServer,
+  PopotamoSessionRewards, PopotamoSessionUser, PopotamoSubProfile, PopotamoSubProfileId, PopotamoUserId,
+  PopotamoUserSkills, PopotamoUsername, ShortPopotamoUser,
 };
 use etwin_scraper_tools::selector;
 use etwin_scraper_tools::ElementRefExt;
 use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use scraper::{ElementRef, Html, Selector};
 use std::str::FromStr;
 
+static DECIMAL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"0|[1-9]\d*"#).unwrap());
+
+/// Matches `/gfx/leagues/4.gif`
+static LEAGUE_ICON_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^/gfx/leagues/(\d+)\.gif$"#).unwrap());
+
+/// Matches `/gfx/rewards/poutruche.png`
+static REWARD_ICON_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^/gfx/rewards/([a-z0-9_]+)\.(?:png|gif)$"#).unwrap());
+
+/// Matches `/gfx/options/vowel.gif`
+static ITEM_ICON_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^/gfx/options/([a-z0-9_]+)\.gif$"#).unwrap());
+
 #[derive(Debug)]
 struct ScraperContext {
   server: PopotamoServer,
   session: Option<PopotamoSessionUser>,
 }
 
+/// Read the first decimal number in the text content of `node`
+fn scrape_decimal(node: ElementRef) -> Result<u32, ScraperError> {
+  let text = node.text().collect::<String>();
+  DECIMAL_RE
+    .find(&text)
+    .and_then(|d| d.as_str().parse().ok())
+    .ok_or(ScraperError::InvalidInteger(text))
+}
+
+fn scrape_league(parent: ElementRef) -> Result<PopotamoLeagueId, ScraperError> {
+  let src = parent
+    .select(selector!("img[src^=\"/gfx/leagues/\"]"))
+    .next()
+    .and_then(|img| img.value().attr("src"))
+    .ok_or(ScraperError::MissingLeague)?;
+  LEAGUE_ICON_RE
+    .captures(src)
+    .and_then(|caps| caps[1].parse().ok())
+    .ok_or_else(|| ScraperError::InvalidLeague(src.to_string()))
+}
+
+fn scrape_server(doc: ElementRef) -> Result<PopotamoServer, ScraperError> {
+  match doc.value().attr("lang") {
+    Some("fr") => Ok(PopotamoServer::PopotamoCom),
+    Some("en") => Ok(PopotamoServer::EnPopotamoCom),
+    _ => Err(ScraperError::ServerDetectionFailure),
+  }
+}
+
 fn scrape_context(doc: ElementRef) -> Result<ScraperContext, ScraperError> {
-  // Only the french server is supported
-  let server = PopotamoServer::PopotamoCom;
+  let server = scrape_server(doc)?;
 
   let session = doc
     .select(selector!("#menu table#sheet"))
@@ -54,12 +96,38 @@ fn scrape_context(doc: ElementRef) -> Result<ScraperContext, ScraperError> {
       .parse()
       .map_err(|_| ScraperError::InvalidUsername(username.to_string()))?;
 
+    let league = scrape_league(rewards)?;
+
+    let tokens = session
+      .select(selector!(":scope div.token"))
+      .next()
+      .ok_or(ScraperError::MissingSessionUserCounter("tokens"))?;
+    let tokens = scrape_decimal(tokens)?;
+
+    // The counters are labelled in the page language, use their position instead
+    let cups = session
+      .select(selector!(":scope > tbody > tr:nth-child(2) > td:nth-child(1)"))
+      .next()
+      .ok_or(ScraperError::MissingSessionUserCounter("cups"))?;
+    let cups = scrape_decimal(cups)?;
+    let quids = session
+      .select(selector!(":scope > tbody > tr:nth-child(3) > td:nth-child(1)"))
+      .next()
+      .ok_or(ScraperError::MissingSessionUserCounter("quids"))?;
+    let quids = scrape_decimal(quids)?;
+
     Some(PopotamoSessionUser {
       user: ShortPopotamoUser {
         server,
         id: user_id,
         username,
       },
+      rewards: PopotamoSessionRewards {
+        league,
+        tokens,
+        quids,
+        cups,
+      },
     })
   } else {
     None
@@ -68,17 +136,105 @@ fn scrape_context(doc: ElementRef) -> Result<ScraperContext, ScraperError> {
   Ok(ScraperContext { server, session })
 }
 
+pub(crate) fn scrape_session(doc: &Html) -> Result<Option<PopotamoSessionUser>, ScraperError> {
+  Ok(scrape_context(doc.root_element())?.session)
+}
+
 pub(crate) fn scrape_profile(doc: &Html) -> Result<PopotamoProfileResponse, ScraperError> {
   let root = doc.root_element();
 
   let ScraperContext { server, session } = scrape_context(root)?;
 
+  let header = root
+    .select(selector!("#content h2.mainsheet"))
+    .exactly_one()
+    .map_err(|_| ScraperError::NonUniqueProfileHeader)?;
+
+  let score = header
+    .select(selector!(":scope > span.score"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("score"))?;
+  let score = scrape_decimal(score)?;
+
+  let position = header
+    .select(selector!(":scope > a.position"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("position"))?;
+  let rank = scrape_decimal(position)?;
+
+  // The ranking link is centered on the user: `/user?hispos=<user_id>`
+  let user_id_href = position.value().attr("href").ok_or(ScraperError::MissingLinkHref)?;
+  let user_id = PopotamoUrls::new(server)
+    .parse_from_root(user_id_href)
+    .map_err(|_| ScraperError::InvalidUserLink(user_id_href.to_string()))?;
+  let user_id = user_id
+    .query_pairs()
+    .find(|(key, _)| key == "hispos")
+    .map(|(_, value)| value.into_owned())
+    .ok_or_else(|| ScraperError::InvalidUserLink(user_id_href.to_string()))?;
+  let user_id = PopotamoUserId::from_str(&user_id).map_err(|_| ScraperError::InvalidUserId(user_id.clone()))?;
+
+  let username = header
+    .children()
+    .filter_map(|child| child.value().as_text())
+    .map(|text| text.trim())
+    .filter(|text| !text.is_empty())
+    .exactly_one()
+    .map_err(|_| ScraperError::MissingProfileField("username"))?;
+  let username: PopotamoUsername = username
+    .parse()
+    .map_err(|_| ScraperError::InvalidUsername(username.to_string()))?;
+
+  let league = scrape_league(header)?;
+
+  let is_moderator = header
+    .select(selector!(":scope > img[src=\"/gfx/forum/modestar.gif\"]"))
+    .next()
+    .is_some();
+
+  let cups = match root.select(selector!("#content div.pricesheet")).next() {
+    Some(prices) => scrape_decimal(prices)?,
+    None => 0,
+  };
+
+  let games_played_this_month = root
+    .select(selector!("#content > ul:not(#profiles) > li"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("games_played_this_month"))?;
+  let games_played_this_month = scrape_decimal(games_played_this_month)?;
+
+  let rewards = root
+    .select(selector!(
+      "#content .rewardsLeft img, #content .rewardsRight img, #content .extraRewardsLeft img, #content .extraRewardsRight img"
+    ))
+    .map(|img| {
+      let src = img.value().attr("src").unwrap_or_default();
+      REWARD_ICON_RE
+        .captures(src)
+        .and_then(|caps| PopotamoRewardKey::from_str(&caps[1]).ok())
+        .ok_or_else(|| ScraperError::InvalidReward(src.to_string()))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let sub_profiles = root
+    .select(selector!("#content div[id^=\"profile_\"]"))
+    .map(scrape_sub_profile)
+    .collect::<Result<Vec<_>, _>>()?;
+
   let profile = PopotamoProfile {
     user: ShortPopotamoUser {
       server,
-      id: "0".parse().unwrap(),
-      username: "todo".parse().unwrap(),
+      id: user_id,
+      username,
     },
+    score,
+    rank,
+    league,
+    cups,
+    is_moderator,
+    games_played_this_month,
+    rewards,
+    sub_profiles,
   };
 
   Ok(PopotamoProfileResponse {
@@ -87,6 +243,59 @@ pub(crate) fn scrape_profile(doc: &Html) -> Result<PopotamoProfileResponse, Scra
   })
 }
 
+fn scrape_sub_profile(node: ElementRef) -> Result<PopotamoSubProfile, ScraperError> {
+  let id = node.value().id().unwrap_or_default().trim_start_matches("profile_");
+  let id = PopotamoSubProfileId::from_str(id).map_err(|_| ScraperError::InvalidSubProfileId(id.to_string()))?;
+
+  let games_played = node
+    .select(selector!("td.gpl"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("games_played"))?;
+  let games_played = scrape_decimal(games_played)?;
+
+  let finishes = node
+    .select(selector!("table.priz td.numb"))
+    .map(scrape_decimal)
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let speed = node
+    .select(selector!(".skills .nmb"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("speed"))?;
+  let creativity = node
+    .select(selector!(".skills .nmbc"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("creativity"))?;
+  let wisdom = node
+    .select(selector!(".skills .nmbw"))
+    .next()
+    .ok_or(ScraperError::MissingProfileField("wisdom"))?;
+  let skills = PopotamoUserSkills {
+    speed: scrape_decimal(speed)?,
+    creativity: scrape_decimal(creativity)?,
+    wisdom: scrape_decimal(wisdom)?,
+  };
+
+  let items = node
+    .select(selector!("td.opt img"))
+    .map(|img| {
+      let src = img.value().attr("src").unwrap_or_default();
+      ITEM_ICON_RE
+        .captures(src)
+        .and_then(|caps| PopotamoItemKey::from_str(&caps[1]).ok())
+        .ok_or_else(|| ScraperError::InvalidItem(src.to_string()))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  Ok(PopotamoSubProfile {
+    id,
+    games_played,
+    finishes,
+    skills,
+    items,
+  })
+}
+
 #[cfg(test)]
 mod test {
   use crate::http::scraper::scrape_profile;
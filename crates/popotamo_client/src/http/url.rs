@@ -9,7 +9,7 @@ impl PopotamoUrls {
   pub fn new(server: PopotamoServer) -> Self {
     let root = match server {
       PopotamoServer::PopotamoCom => "http://www.popotamo.com/",
-      // PopotamoServer::EnDinoparcCom => "http://en.dinoparc.com/",
+      PopotamoServer::EnPopotamoCom => "http://en.popotamo.com/",
     };
     Self {
       root: Url::parse(root).expect("failed to parse popotamo root URL"),
@@ -22,6 +22,14 @@ impl PopotamoUrls {
     url
   }
 
+  pub fn root(&self) -> Url {
+    self.root.clone()
+  }
+
+  pub fn login(&self) -> Url {
+    self.make_url(&["user", "login"])
+  }
+
   pub fn user(&self, user_id: PopotamoUserId) -> Url {
     user_id.with_str(|user_id| self.make_url(&["user", user_id]))
   }
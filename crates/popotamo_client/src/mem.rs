@@ -1 +1,218 @@
-// TODO: MemPopotamoClient
+use async_trait::async_trait;
+use etwin_core::clock::Clock;
+use etwin_core::core::Instant;
+use etwin_core::popotamo::{
+  PopotamoClient, PopotamoCredentials, PopotamoPassword, PopotamoProfile, PopotamoProfileResponse, PopotamoServer,
+  PopotamoSession, PopotamoSessionKey, PopotamoUserId, PopotamoUserIdRef, PopotamoUsername, ShortPopotamoUser,
+};
+use etwin_core::types::EtwinError;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error("Invalid credentials")]
+  InvalidCredentials,
+  #[error("Server not found: {:?}", .0)]
+  ServerNotFound(PopotamoServer),
+  #[error("User not found: {:?}", .0)]
+  UserNotFound(PopotamoUserId),
+}
+
+#[derive(Clone, Debug)]
+struct MemSession {
+  key: PopotamoSessionKey,
+  created_at: Instant,
+  user_id: PopotamoUserId,
+}
+
+#[derive(Clone, Debug)]
+struct MemUser {
+  id: PopotamoUserId,
+  username: PopotamoUsername,
+  password: PopotamoPassword,
+}
+
+#[derive(Clone, Debug)]
+struct MemServer {
+  users: HashMap<PopotamoUserId, MemUser>,
+  sessions: HashMap<PopotamoSessionKey, MemSession>,
+  sessions_by_user_id: HashMap<PopotamoUserId, PopotamoSessionKey>,
+}
+
+impl MemServer {
+  fn new() -> Self {
+    Self {
+      users: HashMap::new(),
+      sessions: HashMap::new(),
+      sessions_by_user_id: HashMap::new(),
+    }
+  }
+
+  fn create_user(&mut self, id: PopotamoUserId, username: PopotamoUsername, password: PopotamoPassword) {
+    if self.users.values().any(|u| u.username == username) {
+      panic!("PopotamoUsername conflict");
+    }
+    match self.users.entry(id) {
+      Entry::Occupied(_) => panic!("PopotamoUserId conflict"),
+      Entry::Vacant(e) => e.insert(MemUser { id, username, password }),
+    };
+  }
+
+  fn create_session(
+    &mut self,
+    time: Instant,
+    username: &PopotamoUsername,
+    password: &PopotamoPassword,
+  ) -> Result<MemSession, Error> {
+    let user_id = self
+      .users
+      .values()
+      .find(|u| &u.username == username && &u.password == password)
+      .map(|u| u.id)
+      .ok_or(Error::InvalidCredentials)?;
+
+    if let Some(old_session_key) = self.sessions_by_user_id.remove(&user_id) {
+      self.sessions.remove(&old_session_key);
+    }
+
+    let session = MemSession {
+      key: make_session_key(),
+      created_at: time,
+      user_id,
+    };
+    self.sessions.insert(session.key.clone(), session.clone());
+    self.sessions_by_user_id.insert(user_id, session.key.clone());
+    Ok(session)
+  }
+
+  fn to_short_user(&self, server: PopotamoServer, id: PopotamoUserId) -> Option<ShortPopotamoUser> {
+    self.users.get(&id).map(|user| ShortPopotamoUser {
+      server,
+      id: user.id,
+      username: user.username.clone(),
+    })
+  }
+}
+
+pub struct MemPopotamoClient<TyClock> {
+  clock: TyClock,
+  state: RwLock<HashMap<PopotamoServer, MemServer>>,
+}
+
+impl<TyClock> MemPopotamoClient<TyClock> {
+  pub fn new(clock: TyClock) -> Self
+  where
+    TyClock: Clock,
+  {
+    let mut servers = HashMap::new();
+    for server in PopotamoServer::iter() {
+      servers.insert(server, MemServer::new());
+    }
+    Self {
+      clock,
+      state: RwLock::new(servers),
+    }
+  }
+
+  pub fn create_user(
+    &self,
+    server: PopotamoServer,
+    id: PopotamoUserId,
+    username: PopotamoUsername,
+    password: PopotamoPassword,
+  ) {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for popotamo client state");
+    state.get_mut(&server).unwrap().create_user(id, username, password)
+  }
+}
+
+#[async_trait]
+impl<TyClock> PopotamoClient for MemPopotamoClient<TyClock>
+where
+  TyClock: Clock,
+{
+  async fn create_session(&self, credentials: &PopotamoCredentials) -> Result<PopotamoSession, EtwinError> {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for popotamo client state");
+    let srv = state
+      .get_mut(&credentials.server)
+      .ok_or(Error::ServerNotFound(credentials.server))?;
+    let session = srv.create_session(self.clock.now(), &credentials.username, &credentials.password)?;
+    let user = srv.to_short_user(credentials.server, session.user_id).unwrap();
+    Ok(PopotamoSession {
+      key: session.key,
+      user,
+      ctime: session.created_at,
+      atime: session.created_at,
+    })
+  }
+
+  async fn test_session(
+    &self,
+    server: PopotamoServer,
+    key: &PopotamoSessionKey,
+  ) -> Result<Option<PopotamoSession>, EtwinError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for popotamo client state");
+    let srv = state.get(&server).ok_or(Error::ServerNotFound(server))?;
+    Ok(srv.sessions.get(key).map(|session| PopotamoSession {
+      key: session.key.clone(),
+      user: srv.to_short_user(server, session.user_id).unwrap(),
+      ctime: session.created_at,
+      atime: self.clock.now(),
+    }))
+  }
+
+  async fn get_profile(&self, user: PopotamoUserIdRef) -> Result<PopotamoProfileResponse, EtwinError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for popotamo client state");
+    let srv = state.get(&user.server).ok_or(Error::ServerNotFound(user.server))?;
+    let short = srv
+      .to_short_user(user.server, user.id)
+      .ok_or(Error::UserNotFound(user.id))?;
+    Ok(PopotamoProfileResponse {
+      session_user: None,
+      profile: PopotamoProfile {
+        user: short,
+        score: 0,
+        rank: 0,
+        league: "1".parse().unwrap(),
+        cups: 0,
+        is_moderator: false,
+        games_played_this_month: 0,
+        rewards: Vec::new(),
+        sub_profiles: Vec::new(),
+      },
+    })
+  }
+}
+
+fn make_session_key() -> PopotamoSessionKey {
+  use rand::seq::SliceRandom;
+
+  const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+  let mut rng = rand::thread_rng();
+
+  let key: String = std::iter::from_fn(|| CHARS.choose(&mut rng).copied())
+    .map(char::from)
+    .take(32)
+    .collect();
+
+  PopotamoSessionKey::from_str(&key).expect("invalid session key")
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock> neon::prelude::Finalize for MemPopotamoClient<TyClock> where TyClock: Clock {}
@@ -0,0 +1,4 @@
+pub mod mem;
+pub mod pg;
+#[cfg(test)]
+pub(crate) mod test;
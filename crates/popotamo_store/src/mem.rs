@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use etwin_core::clock::Clock;
+use etwin_core::core::Instant;
+use etwin_core::popotamo::{
+  ArchivedPopotamoUser, GetPopotamoUserOptions, PopotamoProfileResponse, PopotamoStore, PopotamoUserIdRef,
+  ShortPopotamoUser,
+};
+use etwin_core::types::EtwinError;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+struct StoreState {
+  users: HashMap<PopotamoUserIdRef, ArchivedPopotamoUser>,
+}
+
+impl StoreState {
+  fn new() -> Self {
+    Self { users: HashMap::new() }
+  }
+
+  fn get_user(&self, id: &PopotamoUserIdRef) -> Option<&ArchivedPopotamoUser> {
+    self.users.get(id)
+  }
+
+  fn touch_user(&mut self, time: Instant, short: &ShortPopotamoUser) -> ArchivedPopotamoUser {
+    let user = self
+      .users
+      .entry(short.as_ref())
+      .or_insert_with(|| ArchivedPopotamoUser {
+        server: short.server,
+        id: short.id,
+        archived_at: time,
+        username: short.username.clone(),
+      });
+    user.username = short.username.clone();
+    user.clone()
+  }
+}
+
+pub struct MemPopotamoStore<TyClock: Clock> {
+  clock: TyClock,
+  state: RwLock<StoreState>,
+}
+
+impl<TyClock> MemPopotamoStore<TyClock>
+where
+  TyClock: Clock,
+{
+  pub fn new(clock: TyClock) -> Self {
+    Self {
+      clock,
+      state: RwLock::new(StoreState::new()),
+    }
+  }
+}
+
+#[async_trait]
+impl<TyClock> PopotamoStore for MemPopotamoStore<TyClock>
+where
+  TyClock: Clock,
+{
+  async fn touch_short_user(&self, short: &ShortPopotamoUser) -> Result<ArchivedPopotamoUser, EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    Ok(state.touch_user(now, short))
+  }
+
+  async fn touch_profile(&self, response: &PopotamoProfileResponse) -> Result<(), EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    if let Some(session_user) = &response.session_user {
+      state.touch_user(now, &session_user.user);
+    }
+    state.touch_user(now, &response.profile.user);
+    Ok(())
+  }
+
+  async fn get_short_user(&self, options: &GetPopotamoUserOptions) -> Result<Option<ShortPopotamoUser>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(
+      state
+        .get_user(&options.id.and_server(options.server))
+        .cloned()
+        .map(From::from),
+    )
+  }
+
+  async fn get_user(&self, options: &GetPopotamoUserOptions) -> Result<Option<ArchivedPopotamoUser>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(state.get_user(&options.id.and_server(options.server)).cloned())
+  }
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock> neon::prelude::Finalize for MemPopotamoStore<TyClock> where TyClock: Clock {}
+
+#[cfg(test)]
+mod test {
+  use crate::mem::MemPopotamoStore;
+  use crate::test::TestApi;
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::popotamo::PopotamoStore;
+  use std::sync::Arc;
+
+  fn make_test_api() -> TestApi<Arc<VirtualClock>, Arc<dyn PopotamoStore>> {
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
+    let popotamo_store: Arc<dyn PopotamoStore> = Arc::new(MemPopotamoStore::new(Arc::clone(&clock)));
+
+    TestApi { clock, popotamo_store }
+  }
+
+  #[tokio::test]
+  async fn test_empty() {
+    crate::test::test_empty(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_touch_user() {
+    crate::test::test_touch_user(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_get_missing_user() {
+    crate::test::test_get_missing_user(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_touch_profile() {
+    crate::test::test_touch_profile(make_test_api()).await;
+  }
+}
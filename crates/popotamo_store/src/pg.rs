@@ -0,0 +1,219 @@
+use async_trait::async_trait;
+use etwin_core::api::ApiRef;
+use etwin_core::clock::Clock;
+use etwin_core::core::Instant;
+use etwin_core::popotamo::{
+  ArchivedPopotamoUser, GetPopotamoUserOptions, PopotamoProfileResponse, PopotamoServer, PopotamoStore, PopotamoUserId,
+  PopotamoUsername, ShortPopotamoUser,
+};
+use etwin_core::types::EtwinError;
+use etwin_populate::popotamo::populate_popotamo;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::error::Error;
+
+fn box_sqlx_error(e: sqlx::Error) -> Box<dyn Error + Send> {
+  Box::new(e)
+}
+
+pub struct PgPopotamoStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+  clock: TyClock,
+  database: TyDatabase,
+}
+
+impl<TyClock, TyDatabase> PgPopotamoStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+  pub async fn new(clock: TyClock, database: TyDatabase) -> Result<Self, Box<dyn Error + Send>> {
+    let mut tx = database.as_ref().begin().await.map_err(box_sqlx_error)?;
+    populate_popotamo(&mut tx).await?;
+    tx.commit().await.map_err(box_sqlx_error)?;
+    Ok(Self { clock, database })
+  }
+}
+
+#[async_trait]
+impl<TyClock, TyDatabase> PopotamoStore for PgPopotamoStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+  async fn touch_short_user(&self, short: &ShortPopotamoUser) -> Result<ArchivedPopotamoUser, EtwinError> {
+    let now = self.clock.now();
+    let mut tx = self.database.as_ref().begin().await?;
+    let archived_at = touch_popotamo_user(&mut tx, now, short).await?;
+    tx.commit().await?;
+    Ok(ArchivedPopotamoUser {
+      server: short.server,
+      id: short.id,
+      archived_at,
+      username: short.username.clone(),
+    })
+  }
+
+  async fn touch_profile(&self, response: &PopotamoProfileResponse) -> Result<(), EtwinError> {
+    let now = self.clock.now();
+    let mut tx = self.database.as_ref().begin().await?;
+    if let Some(session_user) = &response.session_user {
+      touch_popotamo_user(&mut tx, now, &session_user.user).await?;
+    }
+    touch_popotamo_user(&mut tx, now, &response.profile.user).await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn get_short_user(&self, options: &GetPopotamoUserOptions) -> Result<Option<ShortPopotamoUser>, EtwinError> {
+    Ok(self.get_user(options).await?.map(From::from))
+  }
+
+  async fn get_user(&self, options: &GetPopotamoUserOptions) -> Result<Option<ArchivedPopotamoUser>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      popotamo_server: PopotamoServer,
+      popotamo_user_id: PopotamoUserId,
+      archived_at: Instant,
+      username: PopotamoUsername,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT popotamo_server, popotamo_user_id, archived_at, username
+      FROM popotamo_users
+      WHERE popotamo_server = $1::POPOTAMO_SERVER AND popotamo_user_id = $2::POPOTAMO_USER_ID;
+    ",
+    )
+    .bind(options.server)
+    .bind(options.id)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    Ok(row.map(|r| ArchivedPopotamoUser {
+      server: r.popotamo_server,
+      id: r.popotamo_user_id,
+      archived_at: r.archived_at,
+      username: r.username,
+    }))
+  }
+}
+
+/// Inserts or updates a Popotamo user, returns the time when it was first archived.
+async fn touch_popotamo_user(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  short: &ShortPopotamoUser,
+) -> Result<Instant, EtwinError> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    archived_at: Instant,
+  }
+
+  let row: Row = sqlx::query_as::<_, Row>(
+    r"
+    INSERT INTO popotamo_users(popotamo_server, popotamo_user_id, username, archived_at)
+    VALUES ($1::POPOTAMO_SERVER, $2::POPOTAMO_USER_ID, $3::POPOTAMO_USERNAME, $4::INSTANT)
+      ON CONFLICT (popotamo_server, popotamo_user_id)
+        DO UPDATE SET username = $3::POPOTAMO_USERNAME
+    RETURNING archived_at;
+  ",
+  )
+  .bind(short.server)
+  .bind(short.id)
+  .bind(&short.username)
+  .bind(now)
+  .fetch_one(tx)
+  .await?;
+
+  Ok(row.archived_at)
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock, TyDatabase> neon::prelude::Finalize for PgPopotamoStore<TyClock, TyDatabase>
+where
+  TyClock: Clock,
+  TyDatabase: ApiRef<PgPool>,
+{
+}
+
+#[cfg(test)]
+mod test {
+  use super::PgPopotamoStore;
+  use crate::test::TestApi;
+  use chrono::{TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::popotamo::PopotamoStore;
+  use etwin_db_schema::force_create_latest;
+  use serial_test::serial;
+  use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+  use sqlx::PgPool;
+  use std::sync::Arc;
+
+  async fn make_test_api() -> TestApi<Arc<VirtualClock>, Arc<dyn PopotamoStore>> {
+    let config = etwin_config::find_config(std::env::current_dir().unwrap()).unwrap();
+    let admin_database: PgPool = PgPoolOptions::new()
+      .max_connections(5)
+      .connect_with(
+        PgConnectOptions::new()
+          .host(&config.db.host)
+          .port(config.db.port)
+          .database(&config.db.name)
+          .username(&config.db.admin_user)
+          .password(&config.db.admin_password),
+      )
+      .await
+      .unwrap();
+    force_create_latest(&admin_database, true).await.unwrap();
+    admin_database.close().await;
+
+    let database: PgPool = PgPoolOptions::new()
+      .max_connections(5)
+      .connect_with(
+        PgConnectOptions::new()
+          .host(&config.db.host)
+          .port(config.db.port)
+          .database(&config.db.name)
+          .username(&config.db.user)
+          .password(&config.db.password),
+      )
+      .await
+      .unwrap();
+    let database = Arc::new(database);
+
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
+    let popotamo_store: Arc<dyn PopotamoStore> = Arc::new(
+      PgPopotamoStore::new(Arc::clone(&clock), Arc::clone(&database))
+        .await
+        .unwrap(),
+    );
+
+    TestApi { clock, popotamo_store }
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_empty() {
+    crate::test::test_empty(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_user() {
+    crate::test::test_touch_user(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_get_missing_user() {
+    crate::test::test_get_missing_user(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_profile() {
+    crate::test::test_touch_profile(make_test_api().await).await;
+  }
+}
@@ -0,0 +1,200 @@
+use chrono::{Duration, TimeZone, Utc};
+use etwin_core::api::ApiRef;
+use etwin_core::clock::VirtualClock;
+use etwin_core::popotamo::{
+  ArchivedPopotamoUser, GetPopotamoUserOptions, PopotamoProfile, PopotamoProfileResponse, PopotamoServer,
+  PopotamoSessionRewards, PopotamoSessionUser, PopotamoStore, ShortPopotamoUser,
+};
+
+pub(crate) struct TestApi<TyClock, TyPopotamoStore>
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyPopotamoStore: PopotamoStore,
+{
+  pub(crate) clock: TyClock,
+  pub(crate) popotamo_store: TyPopotamoStore,
+}
+
+pub(crate) async fn test_empty<TyClock, TyPopotamoStore>(api: TestApi<TyClock, TyPopotamoStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyPopotamoStore: PopotamoStore,
+{
+  let options = GetPopotamoUserOptions {
+    server: PopotamoServer::PopotamoCom,
+    id: "123".parse().unwrap(),
+    time: None,
+  };
+  let actual = api.popotamo_store.get_short_user(&options).await.unwrap();
+  let expected = None;
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_touch_user<TyClock, TyPopotamoStore>(api: TestApi<TyClock, TyPopotamoStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyPopotamoStore: PopotamoStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  {
+    let actual = api
+      .popotamo_store
+      .touch_short_user(&ShortPopotamoUser {
+        server: PopotamoServer::PopotamoCom,
+        id: "123".parse().unwrap(),
+        username: "alice".parse().unwrap(),
+      })
+      .await
+      .unwrap();
+    let expected = ArchivedPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "123".parse().unwrap(),
+      archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      username: "alice".parse().unwrap(),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .popotamo_store
+      .get_short_user(&GetPopotamoUserOptions {
+        server: PopotamoServer::PopotamoCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(ShortPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "123".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .popotamo_store
+      .get_user(&GetPopotamoUserOptions {
+        server: PopotamoServer::PopotamoCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(ArchivedPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "123".parse().unwrap(),
+      archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      username: "alice".parse().unwrap(),
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_get_missing_user<TyClock, TyPopotamoStore>(api: TestApi<TyClock, TyPopotamoStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyPopotamoStore: PopotamoStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .popotamo_store
+    .touch_short_user(&ShortPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "123".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  {
+    let actual = api
+      .popotamo_store
+      .get_user(&GetPopotamoUserOptions {
+        server: PopotamoServer::EnPopotamoCom,
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = None;
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_touch_profile<TyClock, TyPopotamoStore>(api: TestApi<TyClock, TyPopotamoStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyPopotamoStore: PopotamoStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .popotamo_store
+    .touch_profile(&PopotamoProfileResponse {
+      session_user: Some(PopotamoSessionUser {
+        user: ShortPopotamoUser {
+          server: PopotamoServer::PopotamoCom,
+          id: "592161".parse().unwrap(),
+          username: "demurgos".parse().unwrap(),
+        },
+        rewards: PopotamoSessionRewards {
+          league: "1".parse().unwrap(),
+          tokens: 0,
+          quids: 0,
+          cups: 0,
+        },
+      }),
+      profile: PopotamoProfile {
+        user: ShortPopotamoUser {
+          server: PopotamoServer::PopotamoCom,
+          id: "480534".parse().unwrap(),
+          username: "bouillegri".parse().unwrap(),
+        },
+        score: 0,
+        rank: 878,
+        league: "4".parse().unwrap(),
+        cups: 1,
+        is_moderator: true,
+        games_played_this_month: 0,
+        rewards: vec![],
+        sub_profiles: vec![],
+      },
+    })
+    .await
+    .unwrap();
+  {
+    let actual = api
+      .popotamo_store
+      .get_user(&GetPopotamoUserOptions {
+        server: PopotamoServer::PopotamoCom,
+        id: "480534".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(ArchivedPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "480534".parse().unwrap(),
+      archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      username: "bouillegri".parse().unwrap(),
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .popotamo_store
+      .get_short_user(&GetPopotamoUserOptions {
+        server: PopotamoServer::PopotamoCom,
+        id: "592161".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(ShortPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "592161".parse().unwrap(),
+      username: "demurgos".parse().unwrap(),
+    });
+    assert_eq!(actual, expected);
+  }
+}
@@ -0,0 +1,71 @@
+use etwin_core::dinorpg::DinorpgServer;
+use sqlx::postgres::PgQueryResult;
+use sqlx::{Postgres, Transaction};
+use std::collections::BTreeSet;
+use std::error::Error;
+
+fn box_sqlx_error(e: sqlx::Error) -> Box<dyn Error + Send> {
+  Box::new(e)
+}
+
+pub async fn populate_dinorpg(tx: &mut Transaction<'_, Postgres>) -> Result<(), Box<dyn Error + Send>> {
+  populate_dinorpg_servers(tx).await?;
+  Ok(())
+}
+
+async fn populate_dinorpg_servers(tx: &mut Transaction<'_, Postgres>) -> Result<(), Box<dyn Error + Send>> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    dinorpg_server: DinorpgServer,
+  }
+
+  let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+    r"
+      SELECT dinorpg_server
+      FROM dinorpg_servers;
+    ",
+  )
+  .fetch_all(&mut *tx)
+  .await
+  .map_err(box_sqlx_error)?;
+
+  let actual: BTreeSet<_> = rows.iter().map(|r| r.dinorpg_server).collect();
+  let expected: BTreeSet<_> = DinorpgServer::iter().collect();
+
+  if actual == expected {
+    return Ok(());
+  }
+
+  for extra in actual.difference(&expected) {
+    let res: PgQueryResult = sqlx::query(
+      r"
+      DELETE
+      FROM dinorpg_servers
+      WHERE dinorpg_server = $1::dinorpg_server;
+    ",
+    )
+    .bind(extra)
+    .execute(&mut *tx)
+    .await
+    .map_err(box_sqlx_error)?;
+    assert_eq!(res.rows_affected(), 1);
+  }
+
+  for value in expected {
+    let res: PgQueryResult = sqlx::query(
+      r"
+      INSERT
+      INTO dinorpg_servers(dinorpg_server)
+      VALUES ($1::dinorpg_server)
+      ON CONFLICT (dinorpg_server) DO NOTHING;
+    ",
+    )
+    .bind(value)
+    .execute(&mut *tx)
+    .await
+    .map_err(box_sqlx_error)?;
+    assert!((0..=1u64).contains(&res.rows_affected()));
+  }
+
+  Ok(())
+}
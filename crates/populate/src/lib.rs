@@ -1,2 +1,4 @@
 pub mod dinoparc;
+pub mod dinorpg;
 pub mod hammerfest;
+pub mod popotamo;
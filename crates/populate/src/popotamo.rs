@@ -0,0 +1,71 @@
+use etwin_core::popotamo::PopotamoServer;
+use sqlx::postgres::PgQueryResult;
+use sqlx::{Postgres, Transaction};
+use std::collections::BTreeSet;
+use std::error::Error;
+
+fn box_sqlx_error(e: sqlx::Error) -> Box<dyn Error + Send> {
+  Box::new(e)
+}
+
+pub async fn populate_popotamo(tx: &mut Transaction<'_, Postgres>) -> Result<(), Box<dyn Error + Send>> {
+  populate_popotamo_servers(tx).await?;
+  Ok(())
+}
+
+async fn populate_popotamo_servers(tx: &mut Transaction<'_, Postgres>) -> Result<(), Box<dyn Error + Send>> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    popotamo_server: PopotamoServer,
+  }
+
+  let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+    r"
+      SELECT popotamo_server
+      FROM popotamo_servers;
+    ",
+  )
+  .fetch_all(&mut *tx)
+  .await
+  .map_err(box_sqlx_error)?;
+
+  let actual: BTreeSet<_> = rows.iter().map(|r| r.popotamo_server).collect();
+  let expected: BTreeSet<_> = PopotamoServer::iter().collect();
+
+  if actual == expected {
+    return Ok(());
+  }
+
+  for extra in actual.difference(&expected) {
+    let res: PgQueryResult = sqlx::query(
+      r"
+      DELETE
+      FROM popotamo_servers
+      WHERE popotamo_server = $1::popotamo_server;
+    ",
+    )
+    .bind(extra)
+    .execute(&mut *tx)
+    .await
+    .map_err(box_sqlx_error)?;
+    assert_eq!(res.rows_affected(), 1);
+  }
+
+  for value in expected {
+    let res: PgQueryResult = sqlx::query(
+      r"
+      INSERT
+      INTO popotamo_servers(popotamo_server)
+      VALUES ($1::popotamo_server)
+      ON CONFLICT (popotamo_server) DO NOTHING;
+    ",
+    )
+    .bind(value)
+    .execute(&mut *tx)
+    .await
+    .map_err(box_sqlx_error)?;
+    assert!((0..=1u64).contains(&res.rows_affected()));
+  }
+
+  Ok(())
+}
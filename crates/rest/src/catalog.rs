@@ -0,0 +1,102 @@
+use etwin_core::core::LocaleId;
+use etwin_core::dinoparc::{DinoparcItemId, DinoparcRewardId, EtwinDinoparcUser};
+use etwin_core::hammerfest::{HammerfestItemId, HammerfestUser};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Query parameters accepted by the archive routes.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct CatalogQuery {
+  /// If set, embed the metadata of the game objects referenced by the
+  /// response, localized for this locale.
+  pub catalog: Option<LocaleId>,
+}
+
+/// Archive response with the catalog metadata of the ids it references.
+#[derive(Clone, Debug, Serialize)]
+pub struct WithCatalog<T, C> {
+  #[serde(flatten)]
+  pub value: T,
+  pub catalog: C,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HammerfestCatalog {
+  pub items: BTreeMap<HammerfestItemId, HammerfestItemMeta>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HammerfestItemMeta {
+  /// Only the french names are known: always `None` for the other locales
+  pub name: Option<&'static str>,
+  pub is_hidden: bool,
+}
+
+impl HammerfestCatalog {
+  pub fn for_user(user: &HammerfestUser, locale: LocaleId) -> Self {
+    let profile_items = user.profile.iter().flat_map(|p| p.items.keys());
+    let inventory_items = user.items.iter().flat_map(|i| i.items.keys());
+    let items = profile_items
+      .chain(inventory_items)
+      .filter_map(|id| etwin_constants::hammerfest::ITEMS_BY_ID.get(id))
+      .map(|item| {
+        let meta = HammerfestItemMeta {
+          name: item.name(locale),
+          is_hidden: item.is_hidden,
+        };
+        (item.id, meta)
+      })
+      .collect();
+    Self { items }
+  }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DinoparcCatalog {
+  pub items: BTreeMap<DinoparcItemId, CatalogMeta>,
+  pub rewards: BTreeMap<DinoparcRewardId, CatalogMeta>,
+  pub epic_rewards: BTreeMap<&'static str, CatalogMeta>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CatalogMeta {
+  pub name: Option<&'static str>,
+}
+
+impl CatalogMeta {
+  fn new(name: Option<&'static str>) -> Self {
+    Self { name }
+  }
+}
+
+impl DinoparcCatalog {
+  pub fn for_user(user: &EtwinDinoparcUser, locale: LocaleId) -> Self {
+    use etwin_constants::dinoparc::{EPIC_REWARDS_BY_KEY, ITEMS_BY_ID, REWARDS_BY_ID};
+
+    let items = user
+      .inventory
+      .iter()
+      .flat_map(|inventory| inventory.latest.value.keys())
+      .filter_map(|id| ITEMS_BY_ID.get(id))
+      .map(|item| (item.id, CatalogMeta::new(item.name(locale))))
+      .collect();
+    let collection = user.collection.as_ref().map(|c| &c.latest.value);
+    let rewards = collection
+      .iter()
+      .flat_map(|c| c.rewards.iter())
+      .filter_map(|id| REWARDS_BY_ID.get(id))
+      .map(|reward| (reward.id, CatalogMeta::new(reward.name(locale))))
+      .collect();
+    let epic_rewards = collection
+      .iter()
+      .flat_map(|c| c.epic_rewards.iter())
+      .filter_map(|key| EPIC_REWARDS_BY_KEY.get(key.as_str()))
+      .map(|reward| (reward.key, CatalogMeta::new(reward.name(locale))))
+      .collect();
+    Self {
+      items,
+      rewards,
+      epic_rewards,
+    }
+  }
+}
@@ -1,7 +1,8 @@
+use crate::catalog::{CatalogQuery, DinoparcCatalog, HammerfestCatalog, WithCatalog};
 use etwin_core::auth::{AuthContext, AuthScope, GuestAuthContext};
 use etwin_core::dinoparc::{
-  DinoparcDinozId, DinoparcServer, DinoparcUserId, EtwinDinoparcDinoz, EtwinDinoparcUser, GetDinoparcDinozOptions,
-  GetDinoparcUserOptions,
+  DinoparcDinozHistory, DinoparcDinozId, DinoparcServer, DinoparcUserId, EtwinDinoparcDinoz, EtwinDinoparcUser,
+  GetDinoparcDinozHistoryOptions, GetDinoparcDinozOptions, GetDinoparcUserOptions,
 };
 use etwin_core::hammerfest::{GetHammerfestUserOptions, HammerfestServer, HammerfestUser, HammerfestUserId};
 use etwin_core::types::EtwinError;
@@ -15,6 +16,8 @@ use warp::reject::Reject;
 use warp::reply::{Json, WithStatus};
 use warp::{Filter, Rejection};
 
+pub mod catalog;
+
 #[derive(Debug)]
 struct ServerError(EtwinError);
 
@@ -76,13 +79,19 @@ pub fn create_archive_dinoparc_filter(api: RouterApi) -> RestFilter {
 
     let api = api.clone();
     warp::path!(DinoparcServer / "users" / DinoparcUserId)
-      .and_then(move |server: DinoparcServer, id: DinoparcUserId| {
+      .and(warp::query::<CatalogQuery>())
+      .and_then(move |server: DinoparcServer, id: DinoparcUserId, query: CatalogQuery| {
         let dinoparc = Arc::clone(&api.dinoparc);
         async move {
           let res = handle_get_user(&dinoparc, server, id).await;
-          let reply = match res {
-            Ok(user) => warp::reply::with_status(warp::reply::json(&user), StatusCode::OK),
-            Err(e) => warp::reply::with_status(warp::reply::json(&e), e.get_status_code()),
+          let reply = match (res, query.catalog) {
+            (Ok(user), Some(locale)) => {
+              let catalog = DinoparcCatalog::for_user(&user, locale);
+              let user = WithCatalog { value: user, catalog };
+              warp::reply::with_status(warp::reply::json(&user), StatusCode::OK)
+            }
+            (Ok(user), None) => warp::reply::with_status(warp::reply::json(&user), StatusCode::OK),
+            (Err(e), _) => warp::reply::with_status(warp::reply::json(&e), e.get_status_code()),
           };
           Ok::<_, Rejection>(reply)
         }
@@ -125,7 +134,7 @@ pub fn create_archive_dinoparc_filter(api: RouterApi) -> RestFilter {
       }
     }
 
-    // let api = api.clone();
+    let api = api.clone();
     warp::path!(DinoparcServer / "dinoz" / DinoparcDinozId)
       .and_then(move |server: DinoparcServer, id: DinoparcDinozId| {
         let dinoparc = Arc::clone(&api.dinoparc);
@@ -141,7 +150,57 @@ pub fn create_archive_dinoparc_filter(api: RouterApi) -> RestFilter {
       .boxed()
   };
 
-  get_user.or(get_dinoz).unify().boxed()
+  let get_dinoz_history = {
+    #[derive(Copy, Clone, Debug, Serialize)]
+    #[serde(tag = "error")]
+    enum GetDinoparcDinozHistoryError {
+      DinoparcDinozNotFound,
+      InternalServerError,
+    }
+
+    impl GetDinoparcDinozHistoryError {
+      pub fn get_status_code(self) -> StatusCode {
+        match self {
+          Self::DinoparcDinozNotFound => StatusCode::NOT_FOUND,
+          Self::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+      }
+    }
+
+    async fn handle_get_dinoz_history(
+      dinoparc: &DynDinoparcService,
+      server: DinoparcServer,
+      id: DinoparcDinozId,
+    ) -> Result<DinoparcDinozHistory, GetDinoparcDinozHistoryError> {
+      let acx = AuthContext::Guest(GuestAuthContext {
+        scope: AuthScope::Default,
+      });
+      match dinoparc
+        .get_dinoz_history(&acx, &GetDinoparcDinozHistoryOptions { server, id })
+        .await
+      {
+        Ok(Some(history)) => Ok(history),
+        Ok(None) => Err(GetDinoparcDinozHistoryError::DinoparcDinozNotFound),
+        Err(_) => Err(GetDinoparcDinozHistoryError::InternalServerError),
+      }
+    }
+
+    warp::path!(DinoparcServer / "dinoz" / DinoparcDinozId / "history")
+      .and_then(move |server: DinoparcServer, id: DinoparcDinozId| {
+        let dinoparc = Arc::clone(&api.dinoparc);
+        async move {
+          let res = handle_get_dinoz_history(&dinoparc, server, id).await;
+          let reply = match res {
+            Ok(history) => warp::reply::with_status(warp::reply::json(&history), StatusCode::OK),
+            Err(e) => warp::reply::with_status(warp::reply::json(&e), e.get_status_code()),
+          };
+          Ok::<_, Rejection>(reply)
+        }
+      })
+      .boxed()
+  };
+
+  get_user.or(get_dinoz).unify().or(get_dinoz_history).unify().boxed()
 }
 
 pub fn create_archive_hammerfest_filter(api: RouterApi) -> RestFilter {
@@ -182,17 +241,25 @@ pub fn create_archive_hammerfest_filter(api: RouterApi) -> RestFilter {
 
     // let api = api.clone();
     warp::path!(HammerfestServer / "users" / HammerfestUserId)
-      .and_then(move |server: HammerfestServer, id: HammerfestUserId| {
-        let hammerfest = Arc::clone(&api.hammerfest);
-        async move {
-          let res = handle_get_user(&hammerfest, server, id).await;
-          let reply = match res {
-            Ok(user) => warp::reply::with_status(warp::reply::json(&user), StatusCode::OK),
-            Err(e) => warp::reply::with_status(warp::reply::json(&e), e.get_status_code()),
-          };
-          Ok::<_, Rejection>(reply)
-        }
-      })
+      .and(warp::query::<CatalogQuery>())
+      .and_then(
+        move |server: HammerfestServer, id: HammerfestUserId, query: CatalogQuery| {
+          let hammerfest = Arc::clone(&api.hammerfest);
+          async move {
+            let res = handle_get_user(&hammerfest, server, id).await;
+            let reply = match (res, query.catalog) {
+              (Ok(user), Some(locale)) => {
+                let catalog = HammerfestCatalog::for_user(&user, locale);
+                let user = WithCatalog { value: user, catalog };
+                warp::reply::with_status(warp::reply::json(&user), StatusCode::OK)
+              }
+              (Ok(user), None) => warp::reply::with_status(warp::reply::json(&user), StatusCode::OK),
+              (Err(e), _) => warp::reply::with_status(warp::reply::json(&e), e.get_status_code()),
+            };
+            Ok::<_, Rejection>(reply)
+          }
+        },
+      )
       .boxed()
   };
 
@@ -201,14 +268,24 @@ pub fn create_archive_hammerfest_filter(api: RouterApi) -> RestFilter {
 
 #[cfg(test)]
 mod test {
+  use crate::catalog::DinoparcCatalog;
   use crate::{create_archive_dinoparc_filter, create_rest_filter, RouterApi};
   use chrono::{TimeZone, Utc};
+  use etwin_core::auth::{AuthContext, AuthScope, UserAuthContext};
   use etwin_core::clock::VirtualClock;
-  use etwin_core::dinoparc::DinoparcStore;
-  use etwin_core::hammerfest::{HammerfestClient, HammerfestStore};
-  use etwin_core::link::LinkStore;
-  use etwin_core::user::UserStore;
+  use etwin_core::dinoparc::{
+    DinoparcClient, DinoparcCollection, DinoparcCollectionResponse, DinoparcInventoryResponse, DinoparcItemId,
+    DinoparcRewardId, DinoparcServer, DinoparcSessionUser, DinoparcStore, GetDinoparcUserOptions,
+    ShortDinoparcDinozWithLocation, ShortDinoparcUser,
+  };
+  use etwin_core::hammerfest::{
+    HammerfestClient, HammerfestInventoryResponse, HammerfestItemId, HammerfestServer, HammerfestSessionUser,
+    HammerfestStore, ShortHammerfestUser,
+  };
+  use etwin_core::link::{LinkStore, TouchLinkOptions};
+  use etwin_core::user::{CreateUserOptions, ShortUser, UserIdRef, UserStore};
   use etwin_core::uuid::Uuid4Generator;
+  use etwin_dinoparc_client::mem::MemDinoparcClient;
   use etwin_dinoparc_store::mem::MemDinoparcStore;
   use etwin_hammerfest_client::MemHammerfestClient;
   use etwin_hammerfest_store::mem::MemHammerfestStore;
@@ -216,30 +293,51 @@ mod test {
   use etwin_services::dinoparc::DinoparcService;
   use etwin_services::hammerfest::HammerfestService;
   use etwin_user_store::mem::MemUserStore;
+  use std::collections::HashMap;
   use std::sync::Arc;
 
+  struct TestApi {
+    api: RouterApi,
+    dinoparc_store: Arc<dyn DinoparcStore>,
+    hammerfest_store: Arc<dyn HammerfestStore>,
+    link_store: Arc<dyn LinkStore>,
+    user_store: Arc<dyn UserStore>,
+  }
+
   fn create_api() -> RouterApi {
+    create_test_api().api
+  }
+
+  fn create_test_api() -> TestApi {
     let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
     let hammerfest_client: Arc<dyn HammerfestClient> = Arc::new(MemHammerfestClient::new(Arc::clone(&clock)));
     let hammerfest_store: Arc<dyn HammerfestStore> = Arc::new(MemHammerfestStore::new(Arc::clone(&clock)));
+    let dinoparc_client: Arc<dyn DinoparcClient> = Arc::new(MemDinoparcClient::new(Arc::clone(&clock)));
     let dinoparc_store: Arc<dyn DinoparcStore> = Arc::new(MemDinoparcStore::new(Arc::clone(&clock)));
     let link_store: Arc<dyn LinkStore> = Arc::new(MemLinkStore::new(Arc::clone(&clock)));
     let user_store: Arc<dyn UserStore> = Arc::new(MemUserStore::new(Arc::clone(&clock), Uuid4Generator));
 
     let dinoparc = Arc::new(DinoparcService::new(
-      dinoparc_store,
+      dinoparc_client,
+      Arc::clone(&dinoparc_store),
       Arc::clone(&link_store),
       Arc::clone(&user_store),
     ));
 
     let hammerfest = Arc::new(HammerfestService::new(
       hammerfest_client,
-      hammerfest_store,
+      Arc::clone(&hammerfest_store),
       Arc::clone(&link_store),
       Arc::clone(&user_store),
     ));
 
-    RouterApi { dinoparc, hammerfest }
+    TestApi {
+      api: RouterApi { dinoparc, hammerfest },
+      dinoparc_store,
+      hammerfest_store,
+      link_store,
+      user_store,
+    }
   }
 
   #[tokio::test]
@@ -284,4 +382,212 @@ mod test {
     let body: &str = std::str::from_utf8(res.body()).unwrap();
     assert_eq!(body, "{\"error\":\"DinoparcDinozNotFound\"}");
   }
+
+  #[tokio::test]
+  async fn test_hammerfest_user_with_catalog() {
+    let api = create_test_api();
+    api
+      .hammerfest_store
+      .touch_inventory(&HammerfestInventoryResponse {
+        session: HammerfestSessionUser {
+          user: ShortHammerfestUser {
+            server: HammerfestServer::HammerfestFr,
+            id: "123".parse().unwrap(),
+            username: "alice".parse().unwrap(),
+          },
+          tokens: 50,
+        },
+        inventory: {
+          let mut inventory: HashMap<HammerfestItemId, u32> = HashMap::new();
+          inventory.insert("1000".parse().unwrap(), 10);
+          inventory.insert("1186".parse().unwrap(), 1);
+          inventory
+        },
+      })
+      .await
+      .unwrap();
+    let router = create_rest_filter(api.api);
+
+    let res: warp::http::Response<warp::hyper::body::Bytes> = warp::test::request()
+      .path("/archive/hammerfest/hammerfest.fr/users/123?catalog=fr-FR")
+      .reply(&router)
+      .await;
+    assert_eq!(res.status(), 200);
+    let body: &str = std::str::from_utf8(res.body()).unwrap();
+    assert!(body.ends_with(concat!(
+      ",\"catalog\":{\"items\":{",
+      "\"1000\":{\"name\":\"Cristaux d'Hammerfest\",\"is_hidden\":false},",
+      "\"1186\":{\"name\":null,\"is_hidden\":true}",
+      "}}}"
+    )));
+
+    // Only the french item names are known
+    let res: warp::http::Response<warp::hyper::body::Bytes> = warp::test::request()
+      .path("/archive/hammerfest/hammerfest.fr/users/123?catalog=en-US")
+      .reply(&router)
+      .await;
+    assert_eq!(res.status(), 200);
+    let body: &str = std::str::from_utf8(res.body()).unwrap();
+    assert!(body.contains("\"1000\":{\"name\":null,\"is_hidden\":false}"));
+
+    let res: warp::http::Response<warp::hyper::body::Bytes> = warp::test::request()
+      .path("/archive/hammerfest/hammerfest.fr/users/123")
+      .reply(&router)
+      .await;
+    assert_eq!(res.status(), 200);
+    let body: &str = std::str::from_utf8(res.body()).unwrap();
+    assert!(!body.contains("\"catalog\""));
+  }
+
+  #[tokio::test]
+  async fn test_dinoparc_user_with_catalog() {
+    let api = create_test_api();
+    let alice = ShortDinoparcUser {
+      server: DinoparcServer::DinoparcCom,
+      id: "123".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    };
+    let session_user = DinoparcSessionUser {
+      user: alice.clone(),
+      coins: 10000,
+      dinoz: vec![],
+    };
+    api
+      .dinoparc_store
+      .touch_inventory(&DinoparcInventoryResponse {
+        session_user: session_user.clone(),
+        inventory: {
+          let mut inventory: HashMap<DinoparcItemId, u32> = HashMap::new();
+          inventory.insert("1".parse().unwrap(), 3);
+          inventory
+        },
+      })
+      .await
+      .unwrap();
+    api
+      .dinoparc_store
+      .touch_collection(&DinoparcCollectionResponse {
+        session_user,
+        collection: DinoparcCollection {
+          rewards: vec!["3".parse().unwrap()].into_iter().collect(),
+          epic_rewards: vec!["adn".parse().unwrap()].into_iter().collect(),
+        },
+      })
+      .await
+      .unwrap();
+    let etwin_alice = api
+      .user_store
+      .create_user(&CreateUserOptions {
+        display_name: "Alice".parse().unwrap(),
+        email: None,
+        username: Some("alice".parse().unwrap()),
+        password: None,
+      })
+      .await
+      .unwrap();
+    api
+      .link_store
+      .touch_dinoparc_link(&TouchLinkOptions {
+        etwin: UserIdRef { id: etwin_alice.id },
+        remote: alice.as_ref(),
+        linked_by: UserIdRef { id: etwin_alice.id },
+      })
+      .await
+      .unwrap();
+    let router = create_archive_dinoparc_filter(api.api.clone());
+
+    // The inventory is only provided to the linked owner: guests get an empty catalog.
+    let res: warp::http::Response<warp::hyper::body::Bytes> = warp::test::request()
+      .path("/dinoparc.com/users/123?catalog=fr-FR")
+      .reply(&router)
+      .await;
+    assert_eq!(res.status(), 200);
+    let body: &str = std::str::from_utf8(res.body()).unwrap();
+    assert!(body.contains("\"inventory\":null"));
+    assert!(body.ends_with(",\"catalog\":{\"items\":{},\"rewards\":{},\"epic_rewards\":{}}}"));
+
+    let options = GetDinoparcUserOptions {
+      server: DinoparcServer::DinoparcCom,
+      id: "123".parse().unwrap(),
+      time: None,
+    };
+    let owner = AuthContext::User(UserAuthContext {
+      scope: AuthScope::Default,
+      user: ShortUser::from(etwin_alice),
+      is_administrator: false,
+    });
+    let user = api.api.dinoparc.get_user(&owner, &options).await.unwrap().unwrap();
+
+    let catalog = DinoparcCatalog::for_user(&user, "fr-FR".parse().unwrap());
+    let item: DinoparcItemId = "1".parse().unwrap();
+    let reward: DinoparcRewardId = "3".parse().unwrap();
+    assert_eq!(catalog.items.len(), 1);
+    assert_eq!(catalog.items[&item].name, Some("Potion de Madame Irma"));
+    assert_eq!(catalog.rewards.len(), 1);
+    assert_eq!(catalog.rewards[&reward].name, Some("Médaille de Bronze de Dinoplage"));
+    assert_eq!(catalog.epic_rewards.len(), 1);
+    assert_eq!(catalog.epic_rewards["adn"].name, Some("Genne-et-Tique"));
+
+    // Missing translations are `None`
+    let catalog = DinoparcCatalog::for_user(&user, "en-US".parse().unwrap());
+    assert_eq!(catalog.items[&item].name, Some("Sparky's Potion"));
+    assert_eq!(catalog.rewards[&reward].name, Some("Dinobeach Bronze Medal"));
+    assert_eq!(catalog.epic_rewards["adn"].name, None);
+  }
+
+  #[tokio::test]
+  async fn test_empty_dinoparc_dinoz_history() {
+    let api = create_api();
+    let router = create_archive_dinoparc_filter(api);
+
+    let res: warp::http::Response<warp::hyper::body::Bytes> = warp::test::request()
+      .path("/dinoparc.com/dinoz/123/history")
+      .reply(&router)
+      .await;
+    assert_eq!(res.status(), 404);
+    let body: &str = std::str::from_utf8(res.body()).unwrap();
+    assert_eq!(body, "{\"error\":\"DinoparcDinozNotFound\"}");
+  }
+
+  #[tokio::test]
+  async fn test_dinoparc_dinoz_history() {
+    let api = create_test_api();
+    api
+      .dinoparc_store
+      .touch_inventory(&DinoparcInventoryResponse {
+        session_user: DinoparcSessionUser {
+          user: ShortDinoparcUser {
+            server: DinoparcServer::DinoparcCom,
+            id: "123".parse().unwrap(),
+            username: "alice".parse().unwrap(),
+          },
+          coins: 10000,
+          dinoz: vec![ShortDinoparcDinozWithLocation {
+            server: DinoparcServer::DinoparcCom,
+            id: "456".parse().unwrap(),
+            name: Some("Balboa".parse().unwrap()),
+            location: Some("0".parse().unwrap()),
+          }],
+        },
+        inventory: HashMap::new(),
+      })
+      .await
+      .unwrap();
+    let router = create_archive_dinoparc_filter(api.api);
+
+    let res: warp::http::Response<warp::hyper::body::Bytes> = warp::test::request()
+      .path("/dinoparc.com/dinoz/456/history")
+      .reply(&router)
+      .await;
+    assert_eq!(res.status(), 200);
+    let body: &str = std::str::from_utf8(res.body()).unwrap();
+    let expected = concat!(
+      "{\"type\":\"DinoparcDinozHistory\",\"server\":\"dinoparc.com\",\"id\":\"456\",\"archived_at\":\"2020-01-01T00:00:00.000Z\",",
+      "\"name\":[{\"period\":{\"start\":\"2020-01-01T00:00:00.000Z\",\"end\":null},\"retrieved\":{\"latest\":\"2020-01-01T00:00:00.000Z\"},\"value\":\"Balboa\"}],",
+      "\"owner\":[{\"period\":{\"start\":\"2020-01-01T00:00:00.000Z\",\"end\":null},\"retrieved\":{\"latest\":\"2020-01-01T00:00:00.000Z\"},\"value\":{\"type\":\"DinoparcUser\",\"server\":\"dinoparc.com\",\"id\":\"123\",\"username\":\"alice\"}}],",
+      "\"location\":[{\"period\":{\"start\":\"2020-01-01T00:00:00.000Z\",\"end\":null},\"retrieved\":{\"latest\":\"2020-01-01T00:00:00.000Z\"},\"value\":\"0\"}],",
+      "\"race\":[],\"skin\":[],\"life\":[],\"level\":[],\"experience\":[],\"danger\":[],\"in_tournament\":[],\"elements\":[],\"skills\":[]}"
+    );
+    assert_eq!(body, expected);
+  }
 }
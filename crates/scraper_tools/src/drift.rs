@@ -0,0 +1,299 @@
+//! Scraper drift detection.
+//!
+//! Every [selector!](crate::selector) evaluation is recorded while a scraper runs inside [trace_selectors]. The
+//! recorded selectors are then matched against the page to find which ones no longer match anything. Comparing
+//! recorded pages (known to be supported) with freshly fetched canaries highlights selectors broken by a markup
+//! change before the corresponding `ScraperError` reaches users.
+
+use scraper::{Html, Selector};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A `selector!` invocation in the scraper source.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SelectorSite {
+  pub selector: &'static str,
+  pub file: &'static str,
+  pub line: u32,
+}
+
+impl fmt::Display for SelectorSite {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "`{}` ({}:{})", self.selector, self.file, self.line)
+  }
+}
+
+thread_local! {
+  static TRACE: RefCell<Option<Vec<SelectorSite>>> = const { RefCell::new(None) };
+}
+
+/// Records the evaluation of a selector, used by the `selector!` macro.
+#[doc(hidden)]
+pub fn record_selector(site: SelectorSite) {
+  TRACE.with(|trace| {
+    if let Some(sites) = trace.borrow_mut().as_mut() {
+      if !sites.contains(&site) {
+        sites.push(site);
+      }
+    }
+  })
+}
+
+/// Runs `f` and returns the selectors it evaluated, in order of first use.
+pub fn trace_selectors<T>(f: impl FnOnce() -> T) -> (T, Vec<SelectorSite>) {
+  let outer = TRACE.with(|trace| trace.replace(Some(Vec::new())));
+  let result = f();
+  let sites = TRACE.with(|trace| trace.replace(outer)).unwrap_or_default();
+  (result, sites)
+}
+
+/// Origin of a checked page.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PageSource {
+  /// Page from the catalog of recorded pages, supported by the scrapers
+  Recorded,
+  /// Page freshly fetched from the live server
+  Canary,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SelectorCheck {
+  pub site: SelectorSite,
+  /// Number of elements matched in the whole page
+  pub matches: usize,
+}
+
+/// Result of running a scraper over a single page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageCheck {
+  /// Scraper name, pages are only compared with pages of the same kind
+  pub kind: &'static str,
+  /// Path or URL of the page
+  pub page: String,
+  pub source: PageSource,
+  /// Scraper error, if any
+  pub error: Option<String>,
+  pub selectors: Vec<SelectorCheck>,
+}
+
+/// Runs `scrape` over `html` and checks which of the selectors it used still match the page.
+pub fn check_page<T, E: fmt::Display>(
+  kind: &'static str,
+  page: String,
+  source: PageSource,
+  html: &Html,
+  scrape: impl FnOnce(&Html) -> Result<T, E>,
+) -> PageCheck {
+  let (result, sites) = trace_selectors(|| scrape(html));
+  let selectors = sites
+    .into_iter()
+    .map(|site| SelectorCheck {
+      site,
+      matches: count_matches(html, site.selector),
+    })
+    .collect();
+  PageCheck {
+    kind,
+    page,
+    source,
+    error: result.err().map(|e| e.to_string()),
+    selectors,
+  }
+}
+
+/// Counts the elements matching `selector` anywhere in the page.
+///
+/// Scrapers often apply relative selectors (`:scope > ...`) to a parent element: they are checked against the whole
+/// document instead, this only tells if the selected structure still exists somewhere in the page.
+fn count_matches(html: &Html, selector: &str) -> usize {
+  let selector = match selector.strip_prefix(":scope") {
+    Some(relative) => relative.trim_start().trim_start_matches('>').trim_start(),
+    None => selector,
+  };
+  match Selector::parse(selector) {
+    Ok(selector) => html.select(&selector).count(),
+    Err(_) => 0,
+  }
+}
+
+/// Selector matching recorded pages but none of the canaries of the same kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectorDrift {
+  pub kind: &'static str,
+  pub site: SelectorSite,
+  /// Number of recorded pages where the selector matches
+  pub recorded_matches: usize,
+  /// Canaries where the selector was used without matching
+  pub canaries: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DriftReport {
+  pub pages: Vec<PageCheck>,
+}
+
+impl DriftReport {
+  pub fn new(pages: Vec<PageCheck>) -> Self {
+    Self { pages }
+  }
+
+  fn with_source(&self, source: PageSource) -> impl Iterator<Item = &PageCheck> {
+    self.pages.iter().filter(move |page| page.source == source)
+  }
+
+  /// Canaries the scrapers failed to read.
+  pub fn failed_canaries(&self) -> impl Iterator<Item = &PageCheck> {
+    self.with_source(PageSource::Canary).filter(|page| page.error.is_some())
+  }
+
+  /// Selectors which match recorded pages but no longer match the canaries.
+  pub fn drift(&self) -> Vec<SelectorDrift> {
+    let mut recorded: BTreeMap<(&'static str, SelectorSite), usize> = BTreeMap::new();
+    for page in self.with_source(PageSource::Recorded) {
+      for check in page.selectors.iter().filter(|check| check.matches > 0) {
+        *recorded.entry((page.kind, check.site)).or_default() += 1;
+      }
+    }
+    let mut canaries: BTreeMap<(&'static str, SelectorSite), Vec<String>> = BTreeMap::new();
+    let mut matched: Vec<(&'static str, SelectorSite)> = Vec::new();
+    for page in self.with_source(PageSource::Canary) {
+      for check in page.selectors.iter() {
+        if check.matches > 0 {
+          matched.push((page.kind, check.site));
+        } else {
+          canaries
+            .entry((page.kind, check.site))
+            .or_default()
+            .push(page.page.clone());
+        }
+      }
+    }
+    canaries
+      .into_iter()
+      .filter(|(key, _)| !matched.contains(key))
+      .filter_map(|((kind, site), canaries)| {
+        recorded.get(&(kind, site)).map(|&recorded_matches| SelectorDrift {
+          kind,
+          site,
+          recorded_matches,
+          canaries,
+        })
+      })
+      .collect()
+  }
+
+  /// Returns `true` if all the canaries were read and no selector drifted.
+  pub fn is_ok(&self) -> bool {
+    self.failed_canaries().next().is_none() && self.drift().is_empty()
+  }
+}
+
+impl fmt::Display for DriftReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(
+      f,
+      "Checked {} recorded pages and {} canaries",
+      self.with_source(PageSource::Recorded).count(),
+      self.with_source(PageSource::Canary).count()
+    )?;
+    let failed: Vec<&PageCheck> = self.failed_canaries().collect();
+    if !failed.is_empty() {
+      writeln!(f, "Failed canaries:")?;
+      for page in failed {
+        writeln!(
+          f,
+          "  [{}] {}: {}",
+          page.kind,
+          page.page,
+          page.error.as_deref().unwrap_or_default()
+        )?;
+      }
+    }
+    let drift = self.drift();
+    if !drift.is_empty() {
+      writeln!(f, "Drifted selectors:")?;
+      for drift in drift {
+        writeln!(
+          f,
+          "  [{}] {}: matches {} recorded pages, no match in {}",
+          drift.kind,
+          drift.site,
+          drift.recorded_matches,
+          drift.canaries.join(", ")
+        )?;
+      }
+    }
+    if self.is_ok() {
+      writeln!(f, "No drift detected")?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::drift::{check_page, trace_selectors, DriftReport, PageSource};
+  use crate::selector;
+  use scraper::{Html, Selector};
+
+  fn scrape_title(html: &Html) -> Result<String, &'static str> {
+    let title = html
+      .select(selector!("div.profile > h1"))
+      .next()
+      .ok_or("missing title")?;
+    Ok(title.text().collect())
+  }
+
+  #[test]
+  fn trace_nested() {
+    let html = Html::parse_document("<div class=\"profile\"><h1>Alice</h1></div>");
+    let (_, outer) = trace_selectors(|| {
+      let (title, inner) = trace_selectors(|| scrape_title(&html));
+      assert_eq!(title, Ok(String::from("Alice")));
+      assert_eq!(inner.len(), 1);
+      html.select(selector!("div")).count()
+    });
+    assert_eq!(outer.iter().map(|site| site.selector).collect::<Vec<_>>(), vec!["div"]);
+  }
+
+  #[test]
+  fn detect_drift() {
+    let recorded = Html::parse_document("<div class=\"profile\"><h1>Alice</h1></div>");
+    let canary = Html::parse_document("<section class=\"profile\"><h1>Alice</h1></section>");
+    let report = DriftReport::new(vec![
+      check_page(
+        "profile",
+        String::from("alice.html"),
+        PageSource::Recorded,
+        &recorded,
+        scrape_title,
+      ),
+      check_page(
+        "profile",
+        String::from("http://example.com/alice"),
+        PageSource::Canary,
+        &canary,
+        scrape_title,
+      ),
+    ]);
+    assert!(!report.is_ok());
+    assert_eq!(report.failed_canaries().count(), 1);
+    let drift = report.drift();
+    assert_eq!(drift.len(), 1);
+    assert_eq!(drift[0].site.selector, "div.profile > h1");
+    assert_eq!(drift[0].recorded_matches, 1);
+    assert_eq!(drift[0].canaries, vec![String::from("http://example.com/alice")]);
+  }
+
+  #[test]
+  fn relative_selectors_match_whole_page() {
+    let html = Html::parse_document("<table><tr><td>1</td></tr></table>");
+    let check = check_page("table", String::from("table.html"), PageSource::Canary, &html, |html| {
+      let table = html.select(selector!("table")).next().ok_or("missing table")?;
+      Ok::<usize, &str>(table.select(selector!(":scope > tbody > tr")).count())
+    });
+    assert_eq!(check.error, None);
+    assert!(check.selectors.iter().all(|check| check.matches > 0));
+  }
+}
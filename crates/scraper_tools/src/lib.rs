@@ -1,3 +1,5 @@
+pub mod drift;
+
 use scraper::ElementRef;
 use thiserror::Error;
 
@@ -68,9 +70,15 @@ impl<'a> Iterator for FlashVarsIter<'a> {
   }
 }
 
+/// Returns a lazily parsed `&'static Selector`, recording its use for [drift] detection.
 #[macro_export]
 macro_rules! selector {
   ($selector:literal $(,)?) => {{
+    $crate::drift::record_selector($crate::drift::SelectorSite {
+      selector: $selector,
+      file: file!(),
+      line: line!(),
+    });
     static SELECTOR: ::once_cell::race::OnceBox<::scraper::Selector> = ::once_cell::race::OnceBox::new();
     SELECTOR.get_or_init(|| match Selector::parse($selector) {
       Ok(selector) => Box::new(selector),
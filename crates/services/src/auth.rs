@@ -1,3 +1,5 @@
+use crate::dinoparc::archive_dinoparc_account;
+use crate::twinoid::archive_twinoid_account;
 use chrono::{NaiveDateTime, Utc};
 use etwin_core::auth::{
   AuthContext, AuthScope, AuthStore, CreateSessionOptions, CreateValidatedEmailVerificationOptions, Credentials, Login,
@@ -6,7 +8,7 @@ use etwin_core::auth::{
 };
 use etwin_core::clock::Clock;
 use etwin_core::core::{Instant, LocaleId};
-use etwin_core::dinoparc::{DinoparcClient, DinoparcCredentials, DinoparcStore, ShortDinoparcUser};
+use etwin_core::dinoparc::{DinoparcClient, DinoparcCredentials, DinoparcStore, DinoparcUserIdRef, ShortDinoparcUser};
 use etwin_core::email::{EmailAddress, EmailFormatter, Mailer, VerifyRegistrationEmail};
 use etwin_core::hammerfest::{HammerfestClient, HammerfestCredentials, HammerfestStore, ShortHammerfestUser};
 use etwin_core::link::{GetLinkOptions, LinkStore, TouchLinkOptions};
@@ -14,15 +16,20 @@ use etwin_core::oauth::{
   GetOauthClientOptions, OauthClientId, OauthClientRef, OauthProviderStore, RfcOauthAccessTokenKey, SimpleOauthClient,
 };
 use etwin_core::password::{Password, PasswordService};
+use etwin_core::popotamo::{PopotamoClient, PopotamoCredentials, PopotamoStore, ShortPopotamoUser};
+use etwin_core::token::TokenStore;
+use etwin_core::twinoid::api::UserQuery;
 use etwin_core::twinoid::{
-  ShortTwinoidUser, TwinoidApiAuth, TwinoidClient, TwinoidStore, TwinoidUserId, TwinoidUserIdRef,
+  ShortTwinoidUser, TwinoidApiAuth, TwinoidApiError, TwinoidClient, TwinoidOauthClient, TwinoidStore, TwinoidUserIdRef,
 };
 use etwin_core::types::EtwinError;
 use etwin_core::user::{
   CreateUserOptions, GetShortUserOptions, GetUserOptions, GetUserResult, SimpleUser, UserDisplayName, UserEmailRef,
   UserFields, UserId, UserIdRef, UserRef, UserStore, UserUsernameRef,
 };
+use etwin_log::Logger;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -36,6 +43,48 @@ struct EmailJwtClaims {
   email: EmailAddress,
 }
 
+/// Reason why `register_or_login_with_twinoid_oauth` failed to identify the Twinoid user
+#[derive(Debug, thiserror::Error)]
+pub enum TwinoidOauthLoginError {
+  #[error("Twinoid rejected the access token, it was revoked")]
+  InvalidToken,
+  #[error("Twinoid access token expired, login again through Twinoid")]
+  ExpiredToken,
+  #[error("Twinoid is rate limiting requests, retry later")]
+  RateLimited,
+  #[error("failed to retrieve the Twinoid user")]
+  Unavailable(#[source] TwinoidApiError),
+}
+
+/// Remote account archived in the background after a login
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoginArchiveTarget {
+  Dinoparc(DinoparcUserIdRef),
+  Twinoid(TwinoidUserIdRef),
+}
+
+/// Failure of the archival following a login.
+///
+/// The login does not wait for the archival: its failures are only reported to the logger.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoginArchiveError {
+  pub target: LoginArchiveTarget,
+  pub error: String,
+}
+
+/// Runs `archive` in the background and logs each of the failures it returns.
+fn spawn_login_archive<TyLogger, F>(logger: TyLogger, target: LoginArchiveTarget, archive: F)
+where
+  TyLogger: Logger<LoginArchiveError> + 'static,
+  F: Future<Output = Vec<String>> + Send + 'static,
+{
+  tokio::spawn(async move {
+    for error in archive.await {
+      logger.log(LoginArchiveError { target, error });
+    }
+  });
+}
+
 pub struct AuthService<
   TyAuthStore,
   TyClock,
@@ -45,10 +94,15 @@ pub struct AuthService<
   TyHammerfestClient,
   TyHammerfestStore,
   TyLinkStore,
+  TyLogger,
   TyMailer,
   TyOauthProviderStore,
   TyPasswordService,
+  TyPopotamoClient,
+  TyPopotamoStore,
+  TyTokenStore,
   TyTwinoidClient,
+  TyTwinoidOauthClient,
   TyTwinoidStore,
   TyUserStore,
 > where
@@ -60,10 +114,15 @@ pub struct AuthService<
   TyHammerfestClient: HammerfestClient,
   TyHammerfestStore: HammerfestStore,
   TyLinkStore: LinkStore,
+  TyLogger: Logger<LoginArchiveError>,
   TyMailer: Mailer,
   TyOauthProviderStore: OauthProviderStore,
   TyPasswordService: PasswordService,
+  TyPopotamoClient: PopotamoClient,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
   TyTwinoidClient: TwinoidClient,
+  TyTwinoidOauthClient: TwinoidOauthClient,
   TyTwinoidStore: TwinoidStore,
   TyUserStore: UserStore,
 {
@@ -75,11 +134,16 @@ pub struct AuthService<
   hammerfest_client: TyHammerfestClient,
   hammerfest_store: TyHammerfestStore,
   link_store: TyLinkStore,
+  logger: TyLogger,
   mailer: TyMailer,
   oauth_provider_store: TyOauthProviderStore,
   password_service: TyPasswordService,
+  popotamo_client: TyPopotamoClient,
+  popotamo_store: TyPopotamoStore,
+  token_store: TyTokenStore,
   user_store: TyUserStore,
   twinoid_client: TyTwinoidClient,
+  twinoid_oauth_client: TyTwinoidOauthClient,
   twinoid_store: TyTwinoidStore,
   jwt_secret_key: Vec<u8>,
   default_locale: LocaleId,
@@ -94,10 +158,15 @@ pub type DynAuthService = AuthService<
   Arc<dyn HammerfestClient>,
   Arc<dyn HammerfestStore>,
   Arc<dyn LinkStore>,
+  Arc<dyn Logger<LoginArchiveError>>,
   Arc<dyn Mailer>,
   Arc<dyn OauthProviderStore>,
   Arc<dyn PasswordService>,
+  Arc<dyn PopotamoClient>,
+  Arc<dyn PopotamoStore>,
+  Arc<dyn TokenStore>,
   Arc<dyn TwinoidClient>,
+  Arc<dyn TwinoidOauthClient>,
   Arc<dyn TwinoidStore>,
   Arc<dyn UserStore>,
 >;
@@ -111,10 +180,15 @@ impl<
     TyHammerfestClient,
     TyHammerfestStore,
     TyLinkStore,
+    TyLogger,
     TyMailer,
     TyOauthProviderStore,
     TyPasswordService,
+    TyPopotamoClient,
+    TyPopotamoStore,
+    TyTokenStore,
     TyTwinoidClient,
+    TyTwinoidOauthClient,
     TyTwinoidStore,
     TyUserStore,
   >
@@ -127,10 +201,15 @@ impl<
     TyHammerfestClient,
     TyHammerfestStore,
     TyLinkStore,
+    TyLogger,
     TyMailer,
     TyOauthProviderStore,
     TyPasswordService,
+    TyPopotamoClient,
+    TyPopotamoStore,
+    TyTokenStore,
     TyTwinoidClient,
+    TyTwinoidOauthClient,
     TyTwinoidStore,
     TyUserStore,
   >
@@ -143,10 +222,15 @@ where
   TyHammerfestClient: HammerfestClient,
   TyHammerfestStore: HammerfestStore,
   TyLinkStore: LinkStore,
+  TyLogger: Logger<LoginArchiveError>,
   TyMailer: Mailer,
   TyOauthProviderStore: OauthProviderStore,
   TyPasswordService: PasswordService,
+  TyPopotamoClient: PopotamoClient,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
   TyTwinoidClient: TwinoidClient,
+  TyTwinoidOauthClient: TwinoidOauthClient,
   TyTwinoidStore: TwinoidStore,
   TyUserStore: UserStore,
 {
@@ -160,11 +244,16 @@ where
     hammerfest_client: TyHammerfestClient,
     hammerfest_store: TyHammerfestStore,
     link_store: TyLinkStore,
+    logger: TyLogger,
     mailer: TyMailer,
     oauth_provider_store: TyOauthProviderStore,
     password_service: TyPasswordService,
+    popotamo_client: TyPopotamoClient,
+    popotamo_store: TyPopotamoStore,
+    token_store: TyTokenStore,
     user_store: TyUserStore,
     twinoid_client: TyTwinoidClient,
+    twinoid_oauth_client: TyTwinoidOauthClient,
     twinoid_store: TyTwinoidStore,
     secret: Vec<u8>,
   ) -> Self {
@@ -177,10 +266,15 @@ where
       hammerfest_client,
       hammerfest_store,
       link_store,
+      logger,
       mailer,
       oauth_provider_store,
       password_service,
+      popotamo_client,
+      popotamo_store,
+      token_store,
       twinoid_client,
+      twinoid_oauth_client,
       twinoid_store,
       user_store,
       jwt_secret_key: secret,
@@ -338,7 +432,12 @@ where
   pub async fn register_or_login_with_dinoparc(
     &self,
     credentials: &DinoparcCredentials,
-  ) -> Result<UserAndSession, EtwinError> {
+  ) -> Result<UserAndSession, EtwinError>
+  where
+    TyDinoparcClient: Clone + 'static,
+    TyDinoparcStore: Clone + 'static,
+    TyLogger: Clone + 'static,
+  {
     let dparc_session = self.dinoparc_client.create_session(credentials).await?;
     let link = self
       .link_store
@@ -394,6 +493,26 @@ where
       .await?
       .into_session(user.display_name.clone());
 
+    // Only start the archival once the login succeeded
+    {
+      let dinoparc_client = self.dinoparc_client.clone();
+      let dinoparc_store = self.dinoparc_store.clone();
+      spawn_login_archive(
+        self.logger.clone(),
+        LoginArchiveTarget::Dinoparc(dparc_session.user.as_ref()),
+        async move {
+          match archive_dinoparc_account(&dinoparc_client, &dinoparc_store, &dparc_session).await {
+            Ok(report) => report
+              .failures
+              .into_iter()
+              .map(|failure| format!("{:?}: {}", failure.step, failure.error))
+              .collect(),
+            Err(e) => vec![e.to_string()],
+          }
+        },
+      );
+    }
+
     let is_administrator = user.is_administrator;
 
     Ok(UserAndSession {
@@ -471,18 +590,174 @@ where
     })
   }
 
+  pub async fn register_or_login_with_popotamo(
+    &self,
+    credentials: &PopotamoCredentials,
+  ) -> Result<UserAndSession, EtwinError> {
+    let popo_session = self.popotamo_client.create_session(credentials).await?;
+    let link = self
+      .link_store
+      .get_link_from_popotamo(&GetLinkOptions {
+        remote: popo_session.user.as_ref(),
+        time: None,
+      })
+      .await?;
+    let user_id = if let Some(link) = link.current {
+      link.etwin.id
+    } else {
+      let display_name: UserDisplayName = popo_session.user.derive_user_display_name();
+      let user = self
+        .user_store
+        .create_user(&CreateUserOptions {
+          display_name,
+          email: None,
+          username: None,
+          password: None,
+        })
+        .await?;
+      self.popotamo_store.touch_short_user(&popo_session.user).await?;
+      self
+        .link_store
+        .touch_popotamo_link(&TouchLinkOptions {
+          etwin: user.id.into(),
+          remote: popo_session.user.as_ref(),
+          linked_by: user.id.into(),
+        })
+        .await?;
+      user.id
+    };
+    // Keep the Popotamo session so it can be reused without asking the user to log in again
+    self
+      .token_store
+      .touch_popotamo(popo_session.user.as_ref(), &popo_session.key)
+      .await?;
+
+    let user = self
+      .user_store
+      .get_user(&GetUserOptions {
+        r#ref: UserRef::Id(UserIdRef { id: user_id }),
+        fields: UserFields::Default,
+        time: None,
+      })
+      .await?
+      .unwrap();
+
+    let user: SimpleUser = match user {
+      GetUserResult::Complete(u) => u.into(),
+      GetUserResult::Default(u) => u,
+      GetUserResult::Short(_) => unreachable!("AssertionError: Requested `UserFields::Default` but got short response"),
+    };
+
+    let session = self
+      .auth_store
+      .create_session(&CreateSessionOptions { user: user.id.into() })
+      .await?
+      .into_session(user.display_name.clone());
+
+    let is_administrator = user.is_administrator;
+
+    Ok(UserAndSession {
+      user: user.into(),
+      is_administrator,
+      session,
+    })
+  }
+
   pub async fn register_or_login_with_twinoid_oauth(
     &self,
     token: &RfcOauthAccessTokenKey,
-  ) -> Result<UserAndSession, EtwinError> {
-    let tid_user = self
+  ) -> Result<UserAndSession, EtwinError>
+  where
+    TyTwinoidClient: Clone + 'static,
+    TyTwinoidOauthClient: Clone + 'static,
+    TyTwinoidStore: Clone + 'static,
+    TyLogger: Clone + 'static,
+  {
+    let (tid_user, token) = match self
       .twinoid_client
       .get_me_short(TwinoidApiAuth::Token(token.clone()))
-      .await?;
-    let tid_user = ShortTwinoidUser {
-      id: TwinoidUserId::new(tid_user.id)?,
-      display_name: tid_user.name,
+      .await
+    {
+      Ok(tid_user) => (tid_user, token.clone()),
+      Err(TwinoidApiError::InvalidToken) => {
+        self
+          .refresh_rejected_twinoid_token(token, TwinoidOauthLoginError::InvalidToken)
+          .await?
+      }
+      Err(TwinoidApiError::ExpiredToken) => {
+        self
+          .refresh_rejected_twinoid_token(token, TwinoidOauthLoginError::ExpiredToken)
+          .await?
+      }
+      Err(TwinoidApiError::RateLimited) => return Err(TwinoidOauthLoginError::RateLimited.into()),
+      Err(e) => return Err(TwinoidOauthLoginError::Unavailable(e).into()),
     };
+    self.login_with_twinoid_user(&tid_user, &token).await
+  }
+
+  /// Revokes an access token rejected by Twinoid, then identifies its owner with their stored refresh token.
+  ///
+  /// Fails with `error` if the token was not stored or cannot be refreshed.
+  async fn refresh_rejected_twinoid_token(
+    &self,
+    token: &RfcOauthAccessTokenKey,
+    error: TwinoidOauthLoginError,
+  ) -> Result<(ShortTwinoidUser, RfcOauthAccessTokenKey), EtwinError> {
+    let stored = self.token_store.get_twinoid_access_token(token).await?;
+    self.token_store.revoke_twinoid_access_token(token).await?;
+    let user = match stored {
+      Some(stored) => TwinoidUserIdRef {
+        id: stored.twinoid_user_id,
+      },
+      None => return Err(error.into()),
+    };
+    let me = match self.twinoid_oauth_client.get_me(user, &UserQuery::new().name()).await {
+      Ok(me) => me,
+      Err(_) => return Err(error.into()),
+    };
+    let display_name = me.name.ok_or_else(|| {
+      TwinoidOauthLoginError::Unavailable(TwinoidApiError::MalformedBody(String::from("missing user name")))
+    })?;
+    let token = self.twinoid_oauth_client.get_access_token(user).await?;
+    Ok((
+      ShortTwinoidUser {
+        id: user.id,
+        display_name,
+      },
+      token,
+    ))
+  }
+
+  /// Exchanges the authorization code returned by Twinoid, then registers or logs in the Twinoid user.
+  ///
+  /// The tokens from the grant are stored so the Twinoid account can be retrieved again once the access token expires.
+  pub async fn register_or_login_with_twinoid_code(&self, code: &str) -> Result<UserAndSession, EtwinError>
+  where
+    TyTwinoidClient: Clone + 'static,
+    TyTwinoidOauthClient: Clone + 'static,
+    TyTwinoidStore: Clone + 'static,
+    TyLogger: Clone + 'static,
+  {
+    let grant = self.twinoid_oauth_client.exchange_code(code).await?;
+    self.login_with_twinoid_user(&grant.user, &grant.access_token).await
+  }
+
+  /// Returns a valid access token for the Twinoid user, refreshed with the stored refresh token if it expired.
+  pub async fn get_twinoid_access_token(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    self.twinoid_oauth_client.get_access_token(user).await
+  }
+
+  async fn login_with_twinoid_user(
+    &self,
+    tid_user: &ShortTwinoidUser,
+    token: &RfcOauthAccessTokenKey,
+  ) -> Result<UserAndSession, EtwinError>
+  where
+    TyTwinoidClient: Clone + 'static,
+    TyTwinoidOauthClient: Clone + 'static,
+    TyTwinoidStore: Clone + 'static,
+    TyLogger: Clone + 'static,
+  {
     let tid_user_ref = TwinoidUserIdRef { id: tid_user.id };
     let link = self
       .link_store
@@ -504,7 +779,7 @@ where
           password: None,
         })
         .await?;
-      self.twinoid_store.touch_short_user(&tid_user).await?;
+      self.twinoid_store.touch_short_user(tid_user).await?;
       self
         .link_store
         .touch_twinoid_link(&TouchLinkOptions {
@@ -515,6 +790,29 @@ where
         .await?;
       user.id
     };
+    {
+      let twinoid_client = self.twinoid_client.clone();
+      let twinoid_oauth_client = self.twinoid_oauth_client.clone();
+      let twinoid_store = self.twinoid_store.clone();
+      let token = token.clone();
+      spawn_login_archive(
+        self.logger.clone(),
+        LoginArchiveTarget::Twinoid(tid_user_ref),
+        async move {
+          archive_twinoid_account(
+            &twinoid_client,
+            &twinoid_oauth_client,
+            &twinoid_store,
+            tid_user_ref,
+            &token,
+          )
+          .await
+          .into_iter()
+          .map(|e| e.to_string())
+          .collect()
+        },
+      );
+    }
 
     let user = self
       .user_store
@@ -773,10 +1071,15 @@ impl<
     TyHammerfestClient,
     TyHammerfestStore,
     TyLinkStore,
+    TyLogger,
     TyMailer,
     TyOauthProviderStore,
     TyPasswordService,
+    TyPopotamoClient,
+    TyPopotamoStore,
+    TyTokenStore,
     TyTwinoidClient,
+    TyTwinoidOauthClient,
     TyTwinoidStore,
     TyUserStore,
   > neon::prelude::Finalize
@@ -789,10 +1092,15 @@ impl<
     TyHammerfestClient,
     TyHammerfestStore,
     TyLinkStore,
+    TyLogger,
     TyMailer,
     TyOauthProviderStore,
     TyPasswordService,
+    TyPopotamoClient,
+    TyPopotamoStore,
+    TyTokenStore,
     TyTwinoidClient,
+    TyTwinoidOauthClient,
     TyTwinoidStore,
     TyUserStore,
   >
@@ -805,10 +1113,15 @@ where
   TyHammerfestClient: HammerfestClient,
   TyHammerfestStore: HammerfestStore,
   TyLinkStore: LinkStore,
+  TyLogger: Logger<LoginArchiveError>,
   TyMailer: Mailer,
   TyOauthProviderStore: OauthProviderStore,
   TyPasswordService: PasswordService,
+  TyPopotamoClient: PopotamoClient,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
   TyTwinoidClient: TwinoidClient,
+  TyTwinoidOauthClient: TwinoidOauthClient,
   TyTwinoidStore: TwinoidStore,
   TyUserStore: UserStore,
 {
@@ -848,6 +1161,21 @@ impl DeriverUserDisplayName for ShortHammerfestUser {
   }
 }
 
+impl DeriverUserDisplayName for ShortPopotamoUser {
+  fn derive_user_display_name(&self) -> UserDisplayName {
+    if let Ok(name) = UserDisplayName::from_str(self.username.as_str()) {
+      return name;
+    }
+    if let Ok(name) = UserDisplayName::from_str(format!("popo_{}", &self.username).as_str()) {
+      return name;
+    }
+    if let Ok(name) = UserDisplayName::from_str(format!("popo_{}", &self.id).as_str()) {
+      return name;
+    }
+    UserDisplayName::from_str("popoPlayer").expect("`popoPlayer` should be a valid `UserDisplayName`")
+  }
+}
+
 impl DeriverUserDisplayName for ShortTwinoidUser {
   fn derive_user_display_name(&self) -> UserDisplayName {
     if let Ok(name) = UserDisplayName::from_str(self.display_name.as_str()) {
@@ -1,44 +1,64 @@
 use etwin_core::auth::AuthContext;
 use etwin_core::core::UserDot;
 use etwin_core::dinoparc::{
-  ArchivedDinoparcDinoz, ArchivedDinoparcUser, DinoparcStore, DinoparcUserIdRef, EtwinDinoparcDinoz, EtwinDinoparcUser,
-  GetDinoparcDinozOptions, GetDinoparcUserOptions,
+  ArchivedDinoparcDinoz, ArchivedDinoparcUser, DinoparcArchiveFailure, DinoparcArchiveReport, DinoparcArchiveStep,
+  DinoparcClient, DinoparcDinozHistory, DinoparcDinozId, DinoparcSession, DinoparcStore, DinoparcUserIdRef,
+  EtwinDinoparcDinoz, EtwinDinoparcUser, GetDinoparcDinozHistoryOptions, GetDinoparcDinozOptions,
+  GetDinoparcUserOptions,
 };
 use etwin_core::link::{EtwinLink, GetLinkOptions, LinkStore, VersionedEtwinLink, VersionedRawLink};
+use etwin_core::types::EtwinError;
 use etwin_core::user::{GetShortUserOptions, ShortUser, UserRef, UserStore};
+use std::collections::HashSet;
 use std::error::Error;
 use std::sync::Arc;
 
-pub struct DinoparcService<TyDinoparcStore, TyLinkStore, TyUserStore>
+pub struct DinoparcService<TyDinoparcClient, TyDinoparcStore, TyLinkStore, TyUserStore>
 where
+  TyDinoparcClient: DinoparcClient,
   TyDinoparcStore: DinoparcStore,
   TyLinkStore: LinkStore,
   TyUserStore: UserStore,
 {
+  dinoparc_client: TyDinoparcClient,
   dinoparc_store: TyDinoparcStore,
   link_store: TyLinkStore,
   user_store: TyUserStore,
 }
 
-pub type DynDinoparcService = DinoparcService<Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>;
+pub type DynDinoparcService =
+  DinoparcService<Arc<dyn DinoparcClient>, Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>;
 
-impl<TyDinoparcStore, TyLinkStore, TyUserStore> DinoparcService<TyDinoparcStore, TyLinkStore, TyUserStore>
+impl<TyDinoparcClient, TyDinoparcStore, TyLinkStore, TyUserStore>
+  DinoparcService<TyDinoparcClient, TyDinoparcStore, TyLinkStore, TyUserStore>
 where
+  TyDinoparcClient: DinoparcClient,
   TyDinoparcStore: DinoparcStore,
   TyLinkStore: LinkStore,
   TyUserStore: UserStore,
 {
-  pub fn new(dinoparc_store: TyDinoparcStore, link_store: TyLinkStore, user_store: TyUserStore) -> Self {
+  pub fn new(
+    dinoparc_client: TyDinoparcClient,
+    dinoparc_store: TyDinoparcStore,
+    link_store: TyLinkStore,
+    user_store: TyUserStore,
+  ) -> Self {
     Self {
+      dinoparc_client,
       dinoparc_store,
       link_store,
       user_store,
     }
   }
 
+  /// Retrieves and stores all the pages available to the session user.
+  pub async fn archive_account(&self, session: &DinoparcSession) -> Result<DinoparcArchiveReport, EtwinError> {
+    archive_dinoparc_account(&self.dinoparc_client, &self.dinoparc_store, session).await
+  }
+
   pub async fn get_user(
     &self,
-    _acx: &AuthContext,
+    acx: &AuthContext,
     options: &GetDinoparcUserOptions,
   ) -> Result<Option<EtwinDinoparcUser>, Box<dyn Error + Send + Sync + 'static>> {
     let user: Option<ArchivedDinoparcUser> = self.dinoparc_store.get_user(options).await?;
@@ -88,15 +108,30 @@ where
       };
       VersionedEtwinLink { current, old: vec![] }
     };
+    let is_owner = match (acx, etwin_link.current.as_ref()) {
+      (AuthContext::User(acx), Some(link)) => acx.user.id == link.etwin.id,
+      _ => false,
+    };
+    let (coins, bills, inventory, collection) = if is_owner {
+      (
+        self.dinoparc_store.get_coins(options).await?,
+        self.dinoparc_store.get_bills(options).await?,
+        self.dinoparc_store.get_inventory(options).await?,
+        self.dinoparc_store.get_collection(options).await?,
+      )
+    } else {
+      (None, None, None, None)
+    };
     let dparc_user = EtwinDinoparcUser {
       server: user.server,
       id: user.id,
       archived_at: user.archived_at,
       username: user.username,
-      coins: user.coins,
+      coins,
+      bills,
       dinoz: user.dinoz,
-      inventory: user.inventory,
-      collection: user.collection,
+      inventory,
+      collection,
       etwin: etwin_link,
     };
     Ok(Some(dparc_user))
@@ -111,12 +146,128 @@ where
     // TODO: Map owner data to include etwin ref
     Ok(dinoz)
   }
+
+  pub async fn get_dinoz_history(
+    &self,
+    _acx: &AuthContext,
+    options: &GetDinoparcDinozHistoryOptions,
+  ) -> Result<Option<DinoparcDinozHistory>, Box<dyn Error + Send + Sync + 'static>> {
+    let history: Option<DinoparcDinozHistory> = self.dinoparc_store.get_dinoz_history(options).await?;
+    Ok(history)
+  }
+}
+
+/// Archives the inventory, collection and dinoz of the session user.
+///
+/// The full dinoz list is only available from the exchange page, so an exchange
+/// with one of the preferred users is also retrieved. Only the failure to store
+/// the user itself is returned as an error: other failures are collected in the
+/// report.
+pub(crate) async fn archive_dinoparc_account<TyDinoparcClient, TyDinoparcStore>(
+  dinoparc_client: &TyDinoparcClient,
+  dinoparc_store: &TyDinoparcStore,
+  session: &DinoparcSession,
+) -> Result<DinoparcArchiveReport, EtwinError>
+where
+  TyDinoparcClient: DinoparcClient,
+  TyDinoparcStore: DinoparcStore,
+{
+  dinoparc_store.touch_short_user(&session.user).await?;
+
+  let mut report = DinoparcArchiveReport {
+    user: session.user.clone(),
+    archived: Vec::new(),
+    failures: Vec::new(),
+  };
+  let mut dinoz: Vec<DinoparcDinozId> = Vec::new();
+  let mut known_dinoz: HashSet<DinoparcDinozId> = HashSet::new();
+
+  let [first, second] = dinoparc_client.get_preferred_exchange_with(session.user.server).await;
+  let recipient = if first == session.user.id { second } else { first };
+  let exchange_with = async {
+    let response = dinoparc_client.get_exchange_with(session, recipient).await?;
+    dinoparc_store.touch_exchange_with(&response).await?;
+    Ok::<_, EtwinError>(response.own_dinoz.into_iter().map(|d| d.id).collect::<Vec<_>>())
+  };
+  let step = DinoparcArchiveStep::ExchangeWith { recipient };
+  if let Some(own_dinoz) = record_step(&mut report, step, exchange_with.await) {
+    for id in own_dinoz {
+      if known_dinoz.insert(id) {
+        dinoz.push(id);
+      }
+    }
+  }
+
+  let inventory = async {
+    let response = dinoparc_client.get_inventory(session).await?;
+    dinoparc_store.touch_inventory(&response).await?;
+    Ok::<_, EtwinError>(
+      response
+        .session_user
+        .dinoz
+        .into_iter()
+        .map(|d| d.id)
+        .collect::<Vec<_>>(),
+    )
+  };
+  let collection = async {
+    let response = dinoparc_client.get_collection(session).await?;
+    dinoparc_store.touch_collection(&response).await?;
+    Ok::<_, EtwinError>(
+      response
+        .session_user
+        .dinoz
+        .into_iter()
+        .map(|d| d.id)
+        .collect::<Vec<_>>(),
+    )
+  };
+  let inventory_dinoz = record_step(&mut report, DinoparcArchiveStep::Inventory, inventory.await);
+  let collection_dinoz = record_step(&mut report, DinoparcArchiveStep::Collection, collection.await);
+  // The side bar lists the dinoz of the user: use it as a fallback if the exchange failed
+  for id in inventory_dinoz.into_iter().chain(collection_dinoz).flatten() {
+    if known_dinoz.insert(id) {
+      dinoz.push(id);
+    }
+  }
+
+  for id in dinoz {
+    let res = async {
+      let response = dinoparc_client.get_dinoz(session, id).await?;
+      dinoparc_store.touch_dinoz(&response).await?;
+      Ok::<_, EtwinError>(())
+    };
+    record_step(&mut report, DinoparcArchiveStep::Dinoz { id }, res.await);
+  }
+
+  Ok(report)
+}
+
+fn record_step<T>(
+  report: &mut DinoparcArchiveReport,
+  step: DinoparcArchiveStep,
+  result: Result<T, EtwinError>,
+) -> Option<T> {
+  match result {
+    Ok(value) => {
+      report.archived.push(step);
+      Some(value)
+    }
+    Err(e) => {
+      report.failures.push(DinoparcArchiveFailure {
+        step,
+        error: e.to_string(),
+      });
+      None
+    }
+  }
 }
 
 #[cfg(feature = "neon")]
-impl<TyDinoparcStore, TyLinkStore, TyUserStore> neon::prelude::Finalize
-  for DinoparcService<TyDinoparcStore, TyLinkStore, TyUserStore>
+impl<TyDinoparcClient, TyDinoparcStore, TyLinkStore, TyUserStore> neon::prelude::Finalize
+  for DinoparcService<TyDinoparcClient, TyDinoparcStore, TyLinkStore, TyUserStore>
 where
+  TyDinoparcClient: DinoparcClient,
   TyDinoparcStore: DinoparcStore,
   TyLinkStore: LinkStore,
   TyUserStore: UserStore,
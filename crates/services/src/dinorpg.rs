@@ -0,0 +1,35 @@
+use etwin_core::auth::AuthContext;
+use etwin_core::dinorpg::{ArchivedDinorpgUser, DinorpgStore, GetDinorpgUserOptions};
+use std::error::Error;
+use std::sync::Arc;
+
+pub struct DinorpgService<TyDinorpgStore>
+where
+  TyDinorpgStore: DinorpgStore,
+{
+  dinorpg_store: TyDinorpgStore,
+}
+
+pub type DynDinorpgService = DinorpgService<Arc<dyn DinorpgStore>>;
+
+impl<TyDinorpgStore> DinorpgService<TyDinorpgStore>
+where
+  TyDinorpgStore: DinorpgStore,
+{
+  pub fn new(dinorpg_store: TyDinorpgStore) -> Self {
+    Self { dinorpg_store }
+  }
+
+  pub async fn get_user(
+    &self,
+    _acx: &AuthContext,
+    options: &GetDinorpgUserOptions,
+  ) -> Result<Option<ArchivedDinorpgUser>, Box<dyn Error + Send + Sync + 'static>> {
+    let user: Option<ArchivedDinorpgUser> = self.dinorpg_store.get_user(options).await?;
+    // TODO: Add the etwin link once DinoRPG users can be linked
+    Ok(user)
+  }
+}
+
+#[cfg(feature = "neon")]
+impl<TyDinorpgStore> neon::prelude::Finalize for DinorpgService<TyDinorpgStore> where TyDinorpgStore: DinorpgStore {}
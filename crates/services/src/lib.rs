@@ -1,3 +1,7 @@
 pub mod auth;
 pub mod dinoparc;
+pub mod dinorpg;
 pub mod hammerfest;
+pub mod popotamo;
+pub mod session;
+pub mod twinoid;
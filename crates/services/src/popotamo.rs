@@ -0,0 +1,35 @@
+use etwin_core::auth::AuthContext;
+use etwin_core::popotamo::{ArchivedPopotamoUser, GetPopotamoUserOptions, PopotamoStore};
+use std::error::Error;
+use std::sync::Arc;
+
+pub struct PopotamoService<TyPopotamoStore>
+where
+  TyPopotamoStore: PopotamoStore,
+{
+  popotamo_store: TyPopotamoStore,
+}
+
+pub type DynPopotamoService = PopotamoService<Arc<dyn PopotamoStore>>;
+
+impl<TyPopotamoStore> PopotamoService<TyPopotamoStore>
+where
+  TyPopotamoStore: PopotamoStore,
+{
+  pub fn new(popotamo_store: TyPopotamoStore) -> Self {
+    Self { popotamo_store }
+  }
+
+  pub async fn get_user(
+    &self,
+    _acx: &AuthContext,
+    options: &GetPopotamoUserOptions,
+  ) -> Result<Option<ArchivedPopotamoUser>, Box<dyn Error + Send + Sync + 'static>> {
+    let user: Option<ArchivedPopotamoUser> = self.popotamo_store.get_user(options).await?;
+    // TODO: Add the etwin link once Popotamo users can be linked
+    Ok(user)
+  }
+}
+
+#[cfg(feature = "neon")]
+impl<TyPopotamoStore> neon::prelude::Finalize for PopotamoService<TyPopotamoStore> where TyPopotamoStore: PopotamoStore {}
@@ -0,0 +1,128 @@
+use etwin_core::dinoparc::{DinoparcClient, DinoparcCredentials, DinoparcSession, DinoparcUserIdRef};
+use etwin_core::hammerfest::{HammerfestClient, HammerfestCredentials, HammerfestSession, HammerfestUserIdRef};
+use etwin_core::token::TokenStore;
+use etwin_core::types::EtwinError;
+use std::sync::Arc;
+
+/// Reuses the game sessions kept in the token store, so archive jobs can run without asking the user to log in again.
+pub struct SessionService<TyDinoparcClient, TyHammerfestClient, TyTokenStore>
+where
+  TyDinoparcClient: DinoparcClient,
+  TyHammerfestClient: HammerfestClient,
+  TyTokenStore: TokenStore,
+{
+  dinoparc_client: TyDinoparcClient,
+  hammerfest_client: TyHammerfestClient,
+  token_store: TyTokenStore,
+}
+
+pub type DynSessionService = SessionService<Arc<dyn DinoparcClient>, Arc<dyn HammerfestClient>, Arc<dyn TokenStore>>;
+
+impl<TyDinoparcClient, TyHammerfestClient, TyTokenStore>
+  SessionService<TyDinoparcClient, TyHammerfestClient, TyTokenStore>
+where
+  TyDinoparcClient: DinoparcClient,
+  TyHammerfestClient: HammerfestClient,
+  TyTokenStore: TokenStore,
+{
+  pub fn new(
+    dinoparc_client: TyDinoparcClient,
+    hammerfest_client: TyHammerfestClient,
+    token_store: TyTokenStore,
+  ) -> Self {
+    Self {
+      dinoparc_client,
+      hammerfest_client,
+      token_store,
+    }
+  }
+
+  /// Logs in to Dinoparc and stores the session for later reuse.
+  pub async fn create_dinoparc_session(
+    &self,
+    credentials: &DinoparcCredentials,
+  ) -> Result<DinoparcSession, EtwinError> {
+    let session = self.dinoparc_client.create_session(credentials).await?;
+    self
+      .token_store
+      .touch_dinoparc(session.user.as_ref(), &session.key)
+      .await?;
+    Ok(session)
+  }
+
+  /// Returns a live Dinoparc session for `user`, using its stored session.
+  ///
+  /// The stored session is revoked if Dinoparc no longer accepts it. Returns `None` if there is no valid stored
+  /// session: the user has to log in again.
+  pub async fn get_dinoparc_session(&self, user: DinoparcUserIdRef) -> Result<Option<DinoparcSession>, EtwinError> {
+    let stored = match self.token_store.get_dinoparc(user).await? {
+      Some(stored) => stored,
+      None => return Ok(None),
+    };
+    match self.dinoparc_client.test_session(user.server, &stored.key).await? {
+      Some(session) if session.user.id == user.id => {
+        let stored = self.token_store.touch_dinoparc(user, &stored.key).await?;
+        Ok(Some(DinoparcSession {
+          ctime: stored.ctime,
+          atime: stored.atime,
+          ..session
+        }))
+      }
+      _ => {
+        self.token_store.revoke_dinoparc(user.server, &stored.key).await?;
+        Ok(None)
+      }
+    }
+  }
+
+  /// Logs in to Hammerfest and stores the session for later reuse.
+  pub async fn create_hammerfest_session(
+    &self,
+    credentials: &HammerfestCredentials,
+  ) -> Result<HammerfestSession, EtwinError> {
+    let session = self.hammerfest_client.create_session(credentials).await?;
+    self
+      .token_store
+      .touch_hammerfest(session.user.as_ref(), &session.key)
+      .await?;
+    Ok(session)
+  }
+
+  /// Returns a live Hammerfest session for `user`, using its stored session.
+  ///
+  /// The stored session is revoked if Hammerfest no longer accepts it. Returns `None` if there is no valid stored
+  /// session: the user has to log in again.
+  pub async fn get_hammerfest_session(
+    &self,
+    user: HammerfestUserIdRef,
+  ) -> Result<Option<HammerfestSession>, EtwinError> {
+    let stored = match self.token_store.get_hammerfest(user).await? {
+      Some(stored) => stored,
+      None => return Ok(None),
+    };
+    match self.hammerfest_client.test_session(user.server, &stored.key).await? {
+      Some(session) if session.user.id == user.id => {
+        let stored = self.token_store.touch_hammerfest(user, &stored.key).await?;
+        Ok(Some(HammerfestSession {
+          ctime: stored.ctime,
+          atime: stored.atime,
+          ..session
+        }))
+      }
+      _ => {
+        self.token_store.revoke_hammerfest(user.server, &stored.key).await?;
+        Ok(None)
+      }
+    }
+  }
+}
+
+#[cfg(feature = "neon")]
+impl<TyDinoparcClient, TyHammerfestClient, TyTokenStore> neon::prelude::Finalize
+  for SessionService<TyDinoparcClient, TyHammerfestClient, TyTokenStore>
+where
+  TyDinoparcClient: DinoparcClient,
+  TyHammerfestClient: HammerfestClient,
+  TyTokenStore: TokenStore,
+{
+}
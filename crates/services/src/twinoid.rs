@@ -0,0 +1,166 @@
+use etwin_core::auth::AuthContext;
+use etwin_core::link::{GetLinksFromEtwinOptions, GetLinksFromTwinoidOptions, LinkStore, RawLink};
+use etwin_core::oauth::RfcOauthAccessTokenKey;
+use etwin_core::twinoid::api::UserQuery;
+use etwin_core::twinoid::{
+  EtwinTwinoidContact, GetTwinoidUserOptions, TwinoidApiAuth, TwinoidApiError, TwinoidClient, TwinoidContact,
+  TwinoidContactList, TwinoidOauthClient, TwinoidProfile, TwinoidStore, TwinoidUserId, TwinoidUserIdRef,
+};
+use etwin_core::types::EtwinError;
+use etwin_core::user::{GetShortUsersOptions, ShortUser, UserId, UserIdRef, UserStore};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::sync::Arc;
+
+pub struct TwinoidService<TyLinkStore, TyTwinoidStore, TyUserStore>
+where
+  TyLinkStore: LinkStore,
+  TyTwinoidStore: TwinoidStore,
+  TyUserStore: UserStore,
+{
+  link_store: TyLinkStore,
+  twinoid_store: TyTwinoidStore,
+  user_store: TyUserStore,
+}
+
+pub type DynTwinoidService = TwinoidService<Arc<dyn LinkStore>, Arc<dyn TwinoidStore>, Arc<dyn UserStore>>;
+
+impl<TyLinkStore, TyTwinoidStore, TyUserStore> TwinoidService<TyLinkStore, TyTwinoidStore, TyUserStore>
+where
+  TyLinkStore: LinkStore,
+  TyTwinoidStore: TwinoidStore,
+  TyUserStore: UserStore,
+{
+  pub fn new(link_store: TyLinkStore, twinoid_store: TyTwinoidStore, user_store: TyUserStore) -> Self {
+    Self {
+      link_store,
+      twinoid_store,
+      user_store,
+    }
+  }
+
+  /// Lists the archived Twinoid contacts of `user` who are linked to an Eternaltwin account.
+  ///
+  /// Returns an empty list if `user` is not linked to a Twinoid account.
+  pub async fn get_linked_contacts(
+    &self,
+    _acx: &AuthContext,
+    user: UserIdRef,
+  ) -> Result<Vec<EtwinTwinoidContact>, Box<dyn Error + Send + Sync + 'static>> {
+    let links = self
+      .link_store
+      .get_links_from_etwin(&GetLinksFromEtwinOptions {
+        etwin: user,
+        time: None,
+      })
+      .await?;
+    let tid_user = match links.twinoid.current {
+      Some(link) => link.remote,
+      None => return Ok(Vec::new()),
+    };
+    let contacts: Vec<TwinoidContact> = match self
+      .twinoid_store
+      .get_contacts(&GetTwinoidUserOptions {
+        id: tid_user.id,
+        time: None,
+      })
+      .await?
+    {
+      Some(contacts) => contacts.latest.value,
+      None => return Ok(Vec::new()),
+    };
+
+    let links: Vec<RawLink<TwinoidUserIdRef>> = self
+      .link_store
+      .get_links_from_twinoid(&GetLinksFromTwinoidOptions {
+        remotes: contacts.iter().map(|contact| contact.user.id.as_ref()).collect(),
+        time: None,
+      })
+      .await?;
+    let users: Vec<ShortUser> = self
+      .user_store
+      .get_short_users(&GetShortUsersOptions {
+        ids: links.iter().map(|link| link.etwin).collect(),
+        time: None,
+      })
+      .await?;
+    let links: HashMap<TwinoidUserId, UserId> = links.into_iter().map(|link| (link.remote.id, link.etwin.id)).collect();
+    let users: HashMap<UserId, ShortUser> = users.into_iter().map(|user| (user.id, user)).collect();
+
+    let mut linked: Vec<EtwinTwinoidContact> = Vec::new();
+    for contact in contacts {
+      // Skip contacts without a link, or linked to a user which no longer exists
+      let etwin = match links.get(&contact.user.id).and_then(|etwin| users.get(etwin)) {
+        Some(etwin) => etwin.clone(),
+        None => continue,
+      };
+      linked.push(EtwinTwinoidContact {
+        twinoid: contact.user,
+        friend: contact.friend,
+        etwin,
+      });
+    }
+    Ok(linked)
+  }
+}
+
+/// Retrieves and stores the profile and contacts of the Twinoid user `user`.
+///
+/// `token` is used first: if Twinoid rejects it, the stored tokens of the user are used instead, refreshed if needed.
+/// The profile and the contacts are stored independently: the returned list contains the failure of each part.
+pub(crate) async fn archive_twinoid_account<TyTwinoidClient, TyTwinoidOauthClient, TyTwinoidStore>(
+  twinoid_client: &TyTwinoidClient,
+  twinoid_oauth_client: &TyTwinoidOauthClient,
+  twinoid_store: &TyTwinoidStore,
+  user: TwinoidUserIdRef,
+  token: &RfcOauthAccessTokenKey,
+) -> Vec<EtwinError>
+where
+  TyTwinoidClient: TwinoidClient,
+  TyTwinoidOauthClient: TwinoidOauthClient,
+  TyTwinoidStore: TwinoidStore,
+{
+  let query = UserQuery::archive();
+  let user = match twinoid_client
+    .get_me(TwinoidApiAuth::Token(token.clone()), &query)
+    .await
+  {
+    Ok(user) => user,
+    Err(TwinoidApiError::InvalidToken) | Err(TwinoidApiError::ExpiredToken) => {
+      match twinoid_oauth_client.get_me(user, &query).await {
+        Ok(user) => user,
+        Err(e) => return vec![e],
+      }
+    }
+    Err(e) => return vec![Box::new(e)],
+  };
+  let mut errors: Vec<EtwinError> = Vec::new();
+  match TwinoidProfile::try_from(user.clone()) {
+    Ok(profile) => {
+      if let Err(e) = twinoid_store.touch_profile(&profile).await {
+        errors.push(e);
+      }
+    }
+    Err(e) => errors.push(Box::new(e)),
+  }
+  match TwinoidContactList::try_from(user) {
+    Ok(contacts) => {
+      if let Err(e) = twinoid_store.touch_contacts(&contacts).await {
+        errors.push(e);
+      }
+    }
+    Err(e) => errors.push(Box::new(e)),
+  }
+  errors
+}
+
+#[cfg(feature = "neon")]
+impl<TyLinkStore, TyTwinoidStore, TyUserStore> neon::prelude::Finalize
+  for TwinoidService<TyLinkStore, TyTwinoidStore, TyUserStore>
+where
+  TyLinkStore: LinkStore,
+  TyTwinoidStore: TwinoidStore,
+  TyUserStore: UserStore,
+{
+}
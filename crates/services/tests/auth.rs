@@ -16,26 +16,39 @@ use etwin_user_store::pg::PgUserStore;
 use serial_test::serial;
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::PgPool;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use etwin_auth_store::pg::PgAuthStore;
 use etwin_core::auth::{
   AuthStore, RawUserCredentials, RegisterOrLoginWithEmailOptions, RegisterWithUsernameOptions,
   RegisterWithVerifiedEmailOptions, Session, UserAndSession,
 };
-use etwin_core::dinoparc::{DinoparcClient, DinoparcStore};
+use etwin_core::dinoparc::{
+  DinoparcClient, DinoparcCredentials, DinoparcPassword, DinoparcServer, DinoparcStore, DinoparcUserIdRef,
+  ShortDinoparcUser,
+};
 use etwin_core::email::{EmailAddress, EmailFormatter, Mailer, VerifyRegistrationEmail};
 use etwin_core::oauth::OauthProviderStore;
 use etwin_core::password::{Password, PasswordService};
-use etwin_core::twinoid::{TwinoidClient, TwinoidStore};
+use etwin_core::popotamo::{
+  PopotamoClient, PopotamoCredentials, PopotamoPassword, PopotamoServer, PopotamoStore, PopotamoUserIdRef,
+};
+use etwin_core::token::TokenStore;
+use etwin_core::twinoid::{TwinoidClient, TwinoidOauthClient, TwinoidStore, TwinoidUserId};
 use etwin_dinoparc_client::mem::MemDinoparcClient;
 use etwin_dinoparc_store::pg::PgDinoparcStore;
 use etwin_email_formatter::json::{JsonBody, JsonEmailFormatter};
+use etwin_log::Logger;
 use etwin_mailer::mem::MemMailer;
 use etwin_oauth_provider_store::pg::PgOauthProviderStore;
 use etwin_password::scrypt::ScryptPasswordService;
-use etwin_services::auth::{AuthService, DynAuthService};
-use etwin_twinoid_client::mem::MemTwinoidClient;
+use etwin_popotamo_client::mem::MemPopotamoClient;
+use etwin_popotamo_store::pg::PgPopotamoStore;
+use etwin_services::auth::{
+  AuthService, DynAuthService, LoginArchiveError, LoginArchiveTarget, TwinoidOauthLoginError,
+};
+use etwin_token_store::pg::PgTokenStore;
+use etwin_twinoid_client::mem::{MemTwinoidClient, MemTwinoidOauthClient};
 use etwin_twinoid_store::pg::PgTwinoidStore;
 
 macro_rules! assert_ok {
@@ -49,8 +62,14 @@ macro_rules! assert_ok {
   }};
 }
 
-async fn make_test_api(
-) -> TestApi<Arc<DynAuthService>, Arc<VirtualClock>, Arc<MemHammerfestClient<Arc<VirtualClock>>>, Arc<MemMailer>> {
+async fn make_test_api() -> TestApi<
+  Arc<DynAuthService>,
+  Arc<VirtualClock>,
+  Arc<MemHammerfestClient<Arc<VirtualClock>>>,
+  Arc<MemMailer>,
+  Arc<MemPopotamoClient<Arc<VirtualClock>>>,
+  Arc<MemTwinoidClient>,
+> {
   let config = etwin_config::find_config(std::env::current_dir().unwrap()).unwrap();
   let admin_database: PgPool = PgPoolOptions::new()
     .max_connections(5)
@@ -88,8 +107,9 @@ async fn make_test_api(
 
   let hammerfest_client: Arc<MemHammerfestClient<Arc<VirtualClock>>> =
     Arc::new(MemHammerfestClient::new(Arc::clone(&clock)));
-  let dinoparc_client: Arc<dyn DinoparcClient> = Arc::new(MemDinoparcClient::new(Arc::clone(&clock)));
-  let twinoid_client: Arc<dyn TwinoidClient> = Arc::new(MemTwinoidClient);
+  let dinoparc_client: Arc<MemDinoparcClient<Arc<VirtualClock>>> = Arc::new(MemDinoparcClient::new(Arc::clone(&clock)));
+  let popotamo_client: Arc<MemPopotamoClient<Arc<VirtualClock>>> = Arc::new(MemPopotamoClient::new(Arc::clone(&clock)));
+  let twinoid_client: Arc<MemTwinoidClient> = Arc::new(MemTwinoidClient::new());
 
   let hammerfest_store: Arc<dyn HammerfestStore> = Arc::new(
     PgHammerfestStore::new(
@@ -106,7 +126,26 @@ async fn make_test_api(
       .await
       .unwrap(),
   );
+  let popotamo_store: Arc<dyn PopotamoStore> = Arc::new(
+    PgPopotamoStore::new(Arc::clone(&clock), Arc::clone(&database))
+      .await
+      .unwrap(),
+  );
   let twinoid_store: Arc<dyn TwinoidStore> = Arc::new(PgTwinoidStore::new(Arc::clone(&clock), Arc::clone(&database)));
+  let token_store: Arc<dyn TokenStore> = Arc::new(
+    PgTokenStore::new(Arc::clone(&clock), Arc::clone(&database), database_secret.clone())
+      .await
+      .unwrap(),
+  );
+
+  let twinoid_oauth_client: Arc<
+    MemTwinoidOauthClient<Arc<VirtualClock>, Arc<dyn TokenStore>, Arc<MemTwinoidClient>, Arc<dyn TwinoidStore>>,
+  > = Arc::new(MemTwinoidOauthClient::new(
+    Arc::clone(&clock),
+    Arc::clone(&token_store),
+    Arc::clone(&twinoid_client),
+    Arc::clone(&twinoid_store),
+  ));
 
   let link_store: Arc<dyn LinkStore> = Arc::new(PgLinkStore::new(Arc::clone(&clock), Arc::clone(&database)));
   let user_store: Arc<dyn UserStore> = Arc::new(PgUserStore::new(
@@ -135,20 +174,27 @@ async fn make_test_api(
     database_secret.clone(),
   ));
 
+  let logger = Arc::new(MemLogger::default());
+
   let auth: Arc<DynAuthService> = Arc::new(AuthService::new(
     Arc::clone(&auth_store),
     Arc::clone(&clock) as Arc<dyn Clock>,
-    Arc::clone(&dinoparc_client),
+    Arc::clone(&dinoparc_client) as Arc<dyn DinoparcClient>,
     Arc::clone(&dinoparc_store),
     Arc::clone(&email_formatter) as Arc<dyn EmailFormatter>,
     Arc::clone(&hammerfest_client) as Arc<dyn HammerfestClient>,
     Arc::clone(&hammerfest_store),
     Arc::clone(&link_store),
+    Arc::clone(&logger) as Arc<dyn Logger<LoginArchiveError>>,
     Arc::clone(&mailer) as Arc<dyn Mailer>,
     Arc::clone(&oauth_provider_store),
     Arc::clone(&password_service) as Arc<dyn PasswordService>,
+    Arc::clone(&popotamo_client) as Arc<dyn PopotamoClient>,
+    Arc::clone(&popotamo_store),
+    Arc::clone(&token_store),
     Arc::clone(&user_store),
-    Arc::clone(&twinoid_client),
+    Arc::clone(&twinoid_client) as Arc<dyn TwinoidClient>,
+    Arc::clone(&twinoid_oauth_client) as Arc<dyn TwinoidOauthClient>,
     Arc::clone(&twinoid_store),
     auth_secret,
   ));
@@ -156,22 +202,49 @@ async fn make_test_api(
   TestApi {
     auth,
     clock,
+    dinoparc_client,
+    dinoparc_store,
     hammerfest_client,
+    logger,
     mailer,
+    popotamo_client,
+    twinoid_client,
+    twinoid_oauth_client,
+    token_store,
   }
 }
 
-struct TestApi<TyAuth, TyClock, TyHammerfest, TyMailer>
+struct TestApi<TyAuth, TyClock, TyHammerfest, TyMailer, TyPopotamo, TyTwinoid>
 where
   TyAuth: ApiRef<DynAuthService>,
   TyClock: ApiRef<VirtualClock>,
   TyHammerfest: ApiRef<MemHammerfestClient<TyClock>>,
   TyMailer: ApiRef<MemMailer>,
+  TyPopotamo: ApiRef<MemPopotamoClient<TyClock>>,
+  TyTwinoid: ApiRef<MemTwinoidClient>,
 {
   pub(crate) auth: TyAuth,
   pub(crate) clock: TyClock,
+  pub(crate) dinoparc_client: Arc<MemDinoparcClient<Arc<VirtualClock>>>,
+  pub(crate) dinoparc_store: Arc<dyn DinoparcStore>,
   pub(crate) hammerfest_client: TyHammerfest,
+  pub(crate) logger: Arc<MemLogger>,
   pub(crate) mailer: TyMailer,
+  pub(crate) popotamo_client: TyPopotamo,
+  pub(crate) twinoid_client: TyTwinoid,
+  pub(crate) twinoid_oauth_client:
+    Arc<MemTwinoidOauthClient<Arc<VirtualClock>, Arc<dyn TokenStore>, Arc<MemTwinoidClient>, Arc<dyn TwinoidStore>>>,
+  pub(crate) token_store: Arc<dyn TokenStore>,
+}
+
+/// Logger keeping the login archival failures, to check them after the login
+#[derive(Default)]
+struct MemLogger(Mutex<Vec<LoginArchiveError>>);
+
+impl Logger<LoginArchiveError> for MemLogger {
+  fn log(&self, ev: LoginArchiveError) {
+    self.0.lock().unwrap().push(ev);
+  }
 }
 
 #[tokio::test]
@@ -198,8 +271,57 @@ async fn test_register_user_with_hammerfest() {
   register_user_with_hammerfest(make_test_api().await).await;
 }
 
+#[tokio::test]
+#[serial]
+async fn test_register_user_with_dinoparc_and_failed_archival() {
+  register_user_with_dinoparc_and_failed_archival(make_test_api().await).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_failed_login_with_dinoparc_does_not_archive() {
+  failed_login_with_dinoparc_does_not_archive(make_test_api().await).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_register_user_with_popotamo() {
+  register_user_with_popotamo(make_test_api().await).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_register_user_with_twinoid_oauth() {
+  register_user_with_twinoid_oauth(make_test_api().await).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_register_user_with_twinoid_code() {
+  register_user_with_twinoid_code(make_test_api().await).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_register_user_with_expired_twinoid_token() {
+  register_user_with_expired_twinoid_token(make_test_api().await).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_register_user_with_invalid_twinoid_token() {
+  register_user_with_invalid_twinoid_token(make_test_api().await).await;
+}
+
 async fn register_user_through_mail<TyClock>(
-  api: TestApi<impl ApiRef<DynAuthService>, TyClock, impl ApiRef<MemHammerfestClient<TyClock>>, impl ApiRef<MemMailer>>,
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
 ) where
   TyClock: ApiRef<VirtualClock>,
 {
@@ -265,7 +387,14 @@ async fn register_user_through_mail<TyClock>(
 }
 
 async fn register_user_with_username<TyClock>(
-  api: TestApi<impl ApiRef<DynAuthService>, TyClock, impl ApiRef<MemHammerfestClient<TyClock>>, impl ApiRef<MemMailer>>,
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
 ) where
   TyClock: ApiRef<VirtualClock>,
 {
@@ -308,7 +437,14 @@ async fn register_user_with_username<TyClock>(
 }
 
 async fn register_user_with_username_and_sign_in<TyClock>(
-  api: TestApi<impl ApiRef<DynAuthService>, TyClock, impl ApiRef<MemHammerfestClient<TyClock>>, impl ApiRef<MemMailer>>,
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
 ) where
   TyClock: ApiRef<VirtualClock>,
 {
@@ -361,7 +497,14 @@ async fn register_user_with_username_and_sign_in<TyClock>(
 }
 
 async fn register_user_with_hammerfest<TyClock>(
-  api: TestApi<impl ApiRef<DynAuthService>, TyClock, impl ApiRef<MemHammerfestClient<TyClock>>, impl ApiRef<MemMailer>>,
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
 ) where
   TyClock: ApiRef<VirtualClock>,
 {
@@ -409,3 +552,343 @@ async fn register_user_with_hammerfest<TyClock>(
   };
   assert_eq!(actual, expected);
 }
+
+async fn register_user_with_dinoparc_and_failed_archival<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock>,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  // The preferred exchange recipients do not exist: the archival of the exchange page fails
+  api.dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "3".parse().unwrap(),
+    "alice".parse().unwrap(),
+    DinoparcPassword::new("aaaaa".to_string()),
+  );
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_dinoparc(&DinoparcCredentials {
+      server: DinoparcServer::DinoparcCom,
+      username: "alice".parse().unwrap(),
+      password: DinoparcPassword::new("aaaaa".to_string()),
+    })
+    .await
+    .unwrap();
+  assert_eq!(actual.user.display_name.current.value.as_str(), "alice");
+
+  let expected_target = LoginArchiveTarget::Dinoparc(DinoparcUserIdRef {
+    server: DinoparcServer::DinoparcCom,
+    id: "3".parse().unwrap(),
+  });
+  let mut failures: Vec<LoginArchiveError> = Vec::new();
+  for _ in 0..100 {
+    failures = api.logger.0.lock().unwrap().clone();
+    if !failures.is_empty() {
+      break;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+  }
+  assert!(!failures.is_empty());
+  assert!(failures.iter().all(|failure| failure.target == expected_target));
+}
+
+async fn failed_login_with_dinoparc_does_not_archive<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock>,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  // Another user already archived with the same username: the login fails after the Dinoparc authentication
+  api
+    .dinoparc_store
+    .touch_short_user(&ShortDinoparcUser {
+      server: DinoparcServer::DinoparcCom,
+      id: "2".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  api.dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "3".parse().unwrap(),
+    "alice".parse().unwrap(),
+    DinoparcPassword::new("aaaaa".to_string()),
+  );
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_dinoparc(&DinoparcCredentials {
+      server: DinoparcServer::DinoparcCom,
+      username: "alice".parse().unwrap(),
+      password: DinoparcPassword::new("aaaaa".to_string()),
+    })
+    .await;
+  assert!(actual.is_err());
+
+  // The archival would have failed (see `register_user_with_dinoparc_and_failed_archival`): no failure means it never ran
+  tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+  assert_eq!(api.logger.0.lock().unwrap().clone(), Vec::new());
+}
+
+async fn register_user_with_popotamo<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock> + Clock,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api.popotamo_client.as_ref().create_user(
+    PopotamoServer::PopotamoCom,
+    "123".parse().unwrap(),
+    "alice".parse().unwrap(),
+    PopotamoPassword::new("aaaaa".to_string()),
+  );
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_popotamo(&PopotamoCredentials {
+      server: PopotamoServer::PopotamoCom,
+      username: "alice".parse().unwrap(),
+      password: PopotamoPassword::new("aaaaa".to_string()),
+    })
+    .await
+    .unwrap();
+  let expected = UserAndSession {
+    user: ShortUser {
+      id: actual.user.id,
+      display_name: UserDisplayNameVersions {
+        current: UserDisplayNameVersion {
+          value: "alice".parse().unwrap(),
+        },
+      },
+    },
+    is_administrator: true,
+    session: Session {
+      id: actual.session.id,
+      user: ShortUser {
+        id: actual.user.id,
+        display_name: UserDisplayNameVersions {
+          current: UserDisplayNameVersion {
+            value: "alice".parse().unwrap(),
+          },
+        },
+      },
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    },
+  };
+  assert_eq!(actual, expected);
+
+  let popotamo_user = PopotamoUserIdRef {
+    server: PopotamoServer::PopotamoCom,
+    id: "123".parse().unwrap(),
+  };
+  let stored = api.token_store.get_popotamo(popotamo_user).await.unwrap().unwrap();
+  assert_eq!(stored.user, popotamo_user);
+  assert_eq!(stored.ctime, Utc.ymd(2021, 1, 1).and_hms(0, 0, 1));
+  let session = api
+    .popotamo_client
+    .as_ref()
+    .test_session(PopotamoServer::PopotamoCom, &stored.key)
+    .await
+    .unwrap();
+  assert!(session.is_some());
+}
+
+async fn register_user_with_twinoid_oauth<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock>,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_client
+    .as_ref()
+    .create_user("38".parse().unwrap(), "alice".parse().unwrap());
+  let token = api.twinoid_client.as_ref().create_access_token("38".parse().unwrap());
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_twinoid_oauth(&token)
+    .await
+    .unwrap();
+  let expected = UserAndSession {
+    user: ShortUser {
+      id: actual.user.id,
+      display_name: UserDisplayNameVersions {
+        current: UserDisplayNameVersion {
+          value: "alice".parse().unwrap(),
+        },
+      },
+    },
+    is_administrator: true,
+    session: Session {
+      id: actual.session.id,
+      user: ShortUser {
+        id: actual.user.id,
+        display_name: UserDisplayNameVersions {
+          current: UserDisplayNameVersion {
+            value: "alice".parse().unwrap(),
+          },
+        },
+      },
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    },
+  };
+  assert_eq!(actual, expected);
+}
+
+async fn register_user_with_twinoid_code<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock>,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_client
+    .as_ref()
+    .create_user("38".parse().unwrap(), "alice".parse().unwrap());
+  let code = api
+    .twinoid_oauth_client
+    .create_authorization_code("38".parse().unwrap());
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_twinoid_code(&code)
+    .await
+    .unwrap();
+  assert_eq!(actual.user.display_name.current.value.as_str(), "alice");
+  assert_eq!(actual.session.ctime, Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+
+  // The grant was stored: the access token is available without a new exchange
+  let token = api
+    .auth
+    .as_ref()
+    .get_twinoid_access_token("38".parse::<TwinoidUserId>().unwrap().as_ref())
+    .await
+    .unwrap();
+  let stored = api
+    .token_store
+    .get_twinoid_oauth("38".parse::<TwinoidUserId>().unwrap().as_ref())
+    .await
+    .unwrap();
+  assert_eq!(Some(token), stored.access_token.map(|t| t.key));
+}
+
+async fn register_user_with_expired_twinoid_token<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock>,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_client
+    .as_ref()
+    .create_user("38".parse().unwrap(), "alice".parse().unwrap());
+  let code = api
+    .twinoid_oauth_client
+    .create_authorization_code("38".parse().unwrap());
+  let grant = api.twinoid_oauth_client.exchange_code(&code).await.unwrap();
+  api.twinoid_client.as_ref().expire_access_token(&grant.access_token);
+  api.clock.as_ref().advance_by(Duration::hours(2));
+
+  // The expired token is refreshed through the stored grant instead of failing the login
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_twinoid_oauth(&grant.access_token)
+    .await
+    .unwrap();
+  assert_eq!(actual.user.display_name.current.value.as_str(), "alice");
+
+  let token = api
+    .auth
+    .as_ref()
+    .get_twinoid_access_token("38".parse::<TwinoidUserId>().unwrap().as_ref())
+    .await
+    .unwrap();
+  assert_ne!(token, grant.access_token);
+
+  // The background archival uses the refreshed token: it does not report any failure
+  tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+  assert_eq!(api.logger.0.lock().unwrap().clone(), Vec::new());
+}
+
+async fn register_user_with_invalid_twinoid_token<TyClock>(
+  api: TestApi<
+    impl ApiRef<DynAuthService>,
+    TyClock,
+    impl ApiRef<MemHammerfestClient<TyClock>>,
+    impl ApiRef<MemMailer>,
+    impl ApiRef<MemPopotamoClient<TyClock>>,
+    impl ApiRef<MemTwinoidClient>,
+  >,
+) where
+  TyClock: ApiRef<VirtualClock>,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_client
+    .as_ref()
+    .create_user("38".parse().unwrap(), "alice".parse().unwrap());
+  let token = api.twinoid_client.as_ref().create_access_token("38".parse().unwrap());
+  api.twinoid_client.as_ref().revoke_access_token(&token);
+  let actual = api
+    .auth
+    .as_ref()
+    .register_or_login_with_twinoid_oauth(&token)
+    .await
+    .unwrap_err();
+  assert!(matches!(
+    actual.downcast_ref::<TwinoidOauthLoginError>(),
+    Some(TwinoidOauthLoginError::InvalidToken)
+  ));
+}
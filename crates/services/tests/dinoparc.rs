@@ -0,0 +1,208 @@
+use chrono::{TimeZone, Utc};
+use etwin_core::auth::{AuthContext, AuthScope, GuestAuthContext, UserAuthContext};
+use etwin_core::clock::VirtualClock;
+use etwin_core::dinoparc::{
+  DinoparcArchiveFailure, DinoparcArchiveReport, DinoparcArchiveStep, DinoparcClient, DinoparcCredentials,
+  DinoparcPassword, DinoparcServer, DinoparcStore, DinoparcUserIdRef, GetDinoparcUserOptions, ShortDinoparcUser,
+};
+use etwin_core::link::{LinkStore, TouchLinkOptions};
+use etwin_core::user::{CreateUserOptions, ShortUser, UserIdRef, UserStore};
+use etwin_core::uuid::Uuid4Generator;
+use etwin_dinoparc_client::mem::MemDinoparcClient;
+use etwin_dinoparc_store::mem::MemDinoparcStore;
+use etwin_link_store::mem::MemLinkStore;
+use etwin_services::dinoparc::DinoparcService;
+use etwin_user_store::mem::MemUserStore;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_archive_account() {
+  let uuid = Uuid4Generator;
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let dinoparc_client = MemDinoparcClient::new(&clock);
+  let dinoparc_store = MemDinoparcStore::new(&clock);
+  let link_store = MemLinkStore::new(&clock);
+  let user_store = MemUserStore::new(&clock, &uuid);
+  let dinoparc = DinoparcService::new(&dinoparc_client, &dinoparc_store, &link_store, &user_store);
+
+  dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "1".parse().unwrap(),
+    "alice".parse().unwrap(),
+    DinoparcPassword::new(String::from("aaaaaaaaaa")),
+  );
+  dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "3".parse().unwrap(),
+    "bob".parse().unwrap(),
+    DinoparcPassword::new(String::from("bbbbbbbbbb")),
+  );
+  let session = dinoparc_client
+    .create_session(&DinoparcCredentials {
+      server: DinoparcServer::DinoparcCom,
+      username: "bob".parse().unwrap(),
+      password: DinoparcPassword::new(String::from("bbbbbbbbbb")),
+    })
+    .await
+    .unwrap();
+
+  let actual = dinoparc.archive_account(&session).await.unwrap();
+  let expected = DinoparcArchiveReport {
+    user: ShortDinoparcUser {
+      server: DinoparcServer::DinoparcCom,
+      id: "3".parse().unwrap(),
+      username: "bob".parse().unwrap(),
+    },
+    archived: vec![
+      DinoparcArchiveStep::ExchangeWith {
+        recipient: "1".parse().unwrap(),
+      },
+      DinoparcArchiveStep::Inventory,
+      DinoparcArchiveStep::Collection,
+    ],
+    failures: vec![],
+  };
+  assert_eq!(actual, expected);
+
+  let user = dinoparc_store
+    .get_user(&GetDinoparcUserOptions {
+      server: DinoparcServer::DinoparcCom,
+      id: "3".parse().unwrap(),
+      time: None,
+    })
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(user.inventory.map(|inv| inv.latest.value), Some(HashMap::new()));
+}
+
+#[tokio::test]
+async fn test_archive_account_reports_partial_failures() {
+  let uuid = Uuid4Generator;
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let dinoparc_client = MemDinoparcClient::new(&clock);
+  let dinoparc_store = MemDinoparcStore::new(&clock);
+  let link_store = MemLinkStore::new(&clock);
+  let user_store = MemUserStore::new(&clock, &uuid);
+  let dinoparc = DinoparcService::new(&dinoparc_client, &dinoparc_store, &link_store, &user_store);
+
+  dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "1".parse().unwrap(),
+    "alice".parse().unwrap(),
+    DinoparcPassword::new(String::from("aaaaaaaaaa")),
+  );
+  let session = dinoparc_client
+    .create_session(&DinoparcCredentials {
+      server: DinoparcServer::DinoparcCom,
+      username: "alice".parse().unwrap(),
+      password: DinoparcPassword::new(String::from("aaaaaaaaaa")),
+    })
+    .await
+    .unwrap();
+
+  let actual = dinoparc.archive_account(&session).await.unwrap();
+  assert_eq!(
+    actual.archived,
+    vec![DinoparcArchiveStep::Inventory, DinoparcArchiveStep::Collection]
+  );
+  assert_eq!(
+    actual.failures,
+    vec![DinoparcArchiveFailure {
+      step: DinoparcArchiveStep::ExchangeWith {
+        recipient: "2".parse().unwrap(),
+      },
+      error: String::from("User not found: DinoparcUserId(2)"),
+    }]
+  );
+  assert!(!actual.is_complete());
+}
+
+#[tokio::test]
+async fn test_get_user_owner_only_fields() {
+  let uuid = Uuid4Generator;
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let dinoparc_client = MemDinoparcClient::new(&clock);
+  let dinoparc_store = MemDinoparcStore::new(&clock);
+  let link_store = MemLinkStore::new(&clock);
+  let user_store = MemUserStore::new(&clock, &uuid);
+  let dinoparc = DinoparcService::new(&dinoparc_client, &dinoparc_store, &link_store, &user_store);
+
+  dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "1".parse().unwrap(),
+    "alice".parse().unwrap(),
+    DinoparcPassword::new(String::from("aaaaaaaaaa")),
+  );
+  let session = dinoparc_client
+    .create_session(&DinoparcCredentials {
+      server: DinoparcServer::DinoparcCom,
+      username: "alice".parse().unwrap(),
+      password: DinoparcPassword::new(String::from("aaaaaaaaaa")),
+    })
+    .await
+    .unwrap();
+  dinoparc.archive_account(&session).await.unwrap();
+
+  let alice = user_store
+    .create_user(&CreateUserOptions {
+      display_name: "Alice".parse().unwrap(),
+      email: None,
+      username: Some("alice".parse().unwrap()),
+      password: None,
+    })
+    .await
+    .unwrap();
+  let bob = user_store
+    .create_user(&CreateUserOptions {
+      display_name: "Bob".parse().unwrap(),
+      email: None,
+      username: Some("bob".parse().unwrap()),
+      password: None,
+    })
+    .await
+    .unwrap();
+  link_store
+    .touch_dinoparc_link(&TouchLinkOptions {
+      etwin: UserIdRef { id: alice.id },
+      remote: DinoparcUserIdRef {
+        server: DinoparcServer::DinoparcCom,
+        id: "1".parse().unwrap(),
+      },
+      linked_by: UserIdRef { id: alice.id },
+    })
+    .await
+    .unwrap();
+
+  let options = GetDinoparcUserOptions {
+    server: DinoparcServer::DinoparcCom,
+    id: "1".parse().unwrap(),
+    time: None,
+  };
+
+  let owner = AuthContext::User(UserAuthContext {
+    scope: AuthScope::Default,
+    user: ShortUser::from(alice),
+    is_administrator: false,
+  });
+  let actual = dinoparc.get_user(&owner, &options).await.unwrap().unwrap();
+  assert!(actual.coins.is_some());
+  assert_eq!(actual.inventory.map(|inv| inv.latest.value), Some(HashMap::new()));
+  assert!(actual.collection.is_some());
+
+  let other = AuthContext::User(UserAuthContext {
+    scope: AuthScope::Default,
+    user: ShortUser::from(bob),
+    is_administrator: false,
+  });
+  let guest = AuthContext::Guest(GuestAuthContext {
+    scope: AuthScope::Default,
+  });
+  for acx in [other, guest].iter() {
+    let actual = dinoparc.get_user(acx, &options).await.unwrap().unwrap();
+    assert_eq!(actual.coins, None);
+    assert_eq!(actual.bills, None);
+    assert_eq!(actual.inventory, None);
+    assert_eq!(actual.collection, None);
+  }
+}
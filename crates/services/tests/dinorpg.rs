@@ -0,0 +1,41 @@
+use chrono::{TimeZone, Utc};
+use etwin_core::auth::{AuthContext, AuthScope, GuestAuthContext};
+use etwin_core::clock::VirtualClock;
+use etwin_core::dinorpg::{ArchivedDinorpgUser, DinorpgServer, DinorpgStore, GetDinorpgUserOptions, ShortDinorpgUser};
+use etwin_dinorpg_store::mem::MemDinorpgStore;
+use etwin_services::dinorpg::DinorpgService;
+
+#[tokio::test]
+async fn test_get_user() {
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let dinorpg_store = MemDinorpgStore::new(&clock);
+  let dinorpg = DinorpgService::new(&dinorpg_store);
+  let acx = AuthContext::Guest(GuestAuthContext {
+    scope: AuthScope::Default,
+  });
+  let options = GetDinorpgUserOptions {
+    server: DinorpgServer::DinorpgCom,
+    id: "123".parse().unwrap(),
+    time: None,
+  };
+
+  assert_eq!(dinorpg.get_user(&acx, &options).await.unwrap(), None);
+
+  dinorpg_store
+    .touch_short_user(&ShortDinorpgUser {
+      server: DinorpgServer::DinorpgCom,
+      id: "123".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+
+  let actual = dinorpg.get_user(&acx, &options).await.unwrap();
+  let expected = Some(ArchivedDinorpgUser {
+    server: DinorpgServer::DinorpgCom,
+    id: "123".parse().unwrap(),
+    archived_at: Utc.timestamp(1607531946, 0),
+    display_name: "alice".parse().unwrap(),
+  });
+  assert_eq!(actual, expected);
+}
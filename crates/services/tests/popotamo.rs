@@ -0,0 +1,43 @@
+use chrono::{TimeZone, Utc};
+use etwin_core::auth::{AuthContext, AuthScope, GuestAuthContext};
+use etwin_core::clock::VirtualClock;
+use etwin_core::popotamo::{
+  ArchivedPopotamoUser, GetPopotamoUserOptions, PopotamoServer, PopotamoStore, ShortPopotamoUser,
+};
+use etwin_popotamo_store::mem::MemPopotamoStore;
+use etwin_services::popotamo::PopotamoService;
+
+#[tokio::test]
+async fn test_get_user() {
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let popotamo_store = MemPopotamoStore::new(&clock);
+  let popotamo = PopotamoService::new(&popotamo_store);
+  let acx = AuthContext::Guest(GuestAuthContext {
+    scope: AuthScope::Default,
+  });
+  let options = GetPopotamoUserOptions {
+    server: PopotamoServer::PopotamoCom,
+    id: "123".parse().unwrap(),
+    time: None,
+  };
+
+  assert_eq!(popotamo.get_user(&acx, &options).await.unwrap(), None);
+
+  popotamo_store
+    .touch_short_user(&ShortPopotamoUser {
+      server: PopotamoServer::PopotamoCom,
+      id: "123".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+
+  let actual = popotamo.get_user(&acx, &options).await.unwrap();
+  let expected = Some(ArchivedPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "123".parse().unwrap(),
+    archived_at: Utc.timestamp(1607531946, 0),
+    username: "alice".parse().unwrap(),
+  });
+  assert_eq!(actual, expected);
+}
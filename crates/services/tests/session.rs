@@ -0,0 +1,88 @@
+use chrono::{TimeZone, Utc};
+use etwin_core::clock::{Clock, VirtualClock};
+use etwin_core::dinoparc::{DinoparcClient, DinoparcCredentials, DinoparcPassword, DinoparcServer, DinoparcUserIdRef};
+use etwin_core::hammerfest::{
+  HammerfestClient, HammerfestCredentials, HammerfestPassword, HammerfestServer, HammerfestUserIdRef,
+};
+use etwin_core::token::TokenStore;
+use etwin_dinoparc_client::mem::MemDinoparcClient;
+use etwin_hammerfest_client::MemHammerfestClient;
+use etwin_services::session::SessionService;
+use etwin_token_store::mem::MemTokenStore;
+
+#[tokio::test]
+async fn test_reuse_hammerfest_session() {
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let dinoparc_client = MemDinoparcClient::new(&clock);
+  let hammerfest_client = MemHammerfestClient::new(&clock);
+  let token_store = MemTokenStore::new(&clock);
+  let sessions = SessionService::new(&dinoparc_client, &hammerfest_client, &token_store);
+
+  hammerfest_client.create_user(
+    HammerfestServer::HammerfestFr,
+    "123".parse().unwrap(),
+    "alice".parse().unwrap(),
+    HammerfestPassword::new(String::from("aaaaaaaaaa")),
+  );
+  let user = HammerfestUserIdRef {
+    server: HammerfestServer::HammerfestFr,
+    id: "123".parse().unwrap(),
+  };
+  let credentials = HammerfestCredentials {
+    server: HammerfestServer::HammerfestFr,
+    username: "alice".parse().unwrap(),
+    password: HammerfestPassword::new(String::from("aaaaaaaaaa")),
+  };
+
+  assert_eq!(sessions.get_hammerfest_session(user).await.unwrap(), None);
+
+  let session = sessions.create_hammerfest_session(&credentials).await.unwrap();
+  clock.advance_by(chrono::Duration::seconds(1));
+  let reused = sessions.get_hammerfest_session(user).await.unwrap().unwrap();
+  assert_eq!(reused.key, session.key);
+  assert_eq!(reused.user, session.user);
+  assert_eq!(reused.ctime, session.ctime);
+  assert_eq!(reused.atime, clock.now());
+
+  // Logging in again from elsewhere invalidates the stored session
+  hammerfest_client.create_session(&credentials).await.unwrap();
+  assert_eq!(sessions.get_hammerfest_session(user).await.unwrap(), None);
+  assert_eq!(token_store.get_hammerfest(user).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_reuse_dinoparc_session() {
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let dinoparc_client = MemDinoparcClient::new(&clock);
+  let hammerfest_client = MemHammerfestClient::new(&clock);
+  let token_store = MemTokenStore::new(&clock);
+  let sessions = SessionService::new(&dinoparc_client, &hammerfest_client, &token_store);
+
+  dinoparc_client.create_user(
+    DinoparcServer::DinoparcCom,
+    "3".parse().unwrap(),
+    "bob".parse().unwrap(),
+    DinoparcPassword::new(String::from("bbbbbbbbbb")),
+  );
+  let user = DinoparcUserIdRef {
+    server: DinoparcServer::DinoparcCom,
+    id: "3".parse().unwrap(),
+  };
+  let credentials = DinoparcCredentials {
+    server: DinoparcServer::DinoparcCom,
+    username: "bob".parse().unwrap(),
+    password: DinoparcPassword::new(String::from("bbbbbbbbbb")),
+  };
+
+  assert_eq!(sessions.get_dinoparc_session(user).await.unwrap(), None);
+
+  let session = sessions.create_dinoparc_session(&credentials).await.unwrap();
+  let reused = sessions.get_dinoparc_session(user).await.unwrap().unwrap();
+  assert_eq!(reused.key, session.key);
+  assert_eq!(reused.user, session.user);
+
+  // Logging in again from elsewhere invalidates the stored session
+  dinoparc_client.create_session(&credentials).await.unwrap();
+  assert_eq!(sessions.get_dinoparc_session(user).await.unwrap(), None);
+  assert_eq!(token_store.get_dinoparc(user).await.unwrap(), None);
+}
@@ -0,0 +1,94 @@
+use chrono::{TimeZone, Utc};
+use etwin_core::auth::{AuthContext, AuthScope, GuestAuthContext};
+use etwin_core::clock::VirtualClock;
+use etwin_core::link::{LinkStore, TouchLinkOptions};
+use etwin_core::twinoid::{EtwinTwinoidContact, ShortTwinoidUser, TwinoidContact, TwinoidContactList, TwinoidStore};
+use etwin_core::user::{CreateUserOptions, ShortUser, UserStore};
+use etwin_core::uuid::Uuid4Generator;
+use etwin_link_store::mem::MemLinkStore;
+use etwin_services::twinoid::TwinoidService;
+use etwin_twinoid_store::mem::MemTwinoidStore;
+use etwin_user_store::mem::MemUserStore;
+
+#[tokio::test]
+async fn test_get_linked_contacts() {
+  let clock = VirtualClock::new(Utc.timestamp(1607531946, 0));
+  let link_store = MemLinkStore::new(&clock);
+  let twinoid_store = MemTwinoidStore::new(&clock);
+  let user_store = MemUserStore::new(&clock, Uuid4Generator);
+  let twinoid = TwinoidService::new(&link_store, &twinoid_store, &user_store);
+  let acx = AuthContext::Guest(GuestAuthContext {
+    scope: AuthScope::Default,
+  });
+
+  let alice = user_store
+    .create_user(&CreateUserOptions {
+      display_name: "Alice".parse().unwrap(),
+      email: None,
+      username: None,
+      password: None,
+    })
+    .await
+    .unwrap();
+  let bob = user_store
+    .create_user(&CreateUserOptions {
+      display_name: "Bob".parse().unwrap(),
+      email: None,
+      username: None,
+      password: None,
+    })
+    .await
+    .unwrap();
+
+  assert_eq!(
+    twinoid.get_linked_contacts(&acx, alice.id.into()).await.unwrap(),
+    vec![]
+  );
+
+  let tid_alice = ShortTwinoidUser {
+    id: "1".parse().unwrap(),
+    display_name: "alice".parse().unwrap(),
+  };
+  let tid_bob = ShortTwinoidUser {
+    id: "2".parse().unwrap(),
+    display_name: "bob".parse().unwrap(),
+  };
+  let tid_charlie = ShortTwinoidUser {
+    id: "3".parse().unwrap(),
+    display_name: "charlie".parse().unwrap(),
+  };
+  twinoid_store
+    .touch_contacts(&TwinoidContactList {
+      user: tid_alice.clone(),
+      contacts: vec![
+        TwinoidContact {
+          user: tid_bob.clone(),
+          friend: true,
+        },
+        TwinoidContact {
+          user: tid_charlie,
+          friend: false,
+        },
+      ],
+    })
+    .await
+    .unwrap();
+  for (user, tid_user) in [(&alice, &tid_alice), (&bob, &tid_bob)].iter() {
+    link_store
+      .touch_twinoid_link(&TouchLinkOptions {
+        etwin: user.id.into(),
+        remote: tid_user.id.as_ref(),
+        linked_by: user.id.into(),
+      })
+      .await
+      .unwrap();
+  }
+
+  let actual = twinoid.get_linked_contacts(&acx, alice.id.into()).await.unwrap();
+  let expected = vec![EtwinTwinoidContact {
+    twinoid: tid_bob,
+    friend: true,
+    etwin: ShortUser::from(bob),
+  }];
+  assert_eq!(actual, expected);
+}
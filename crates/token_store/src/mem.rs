@@ -4,7 +4,8 @@ use etwin_core::core::Instant;
 use etwin_core::dinoparc::{DinoparcServer, DinoparcSessionKey, DinoparcUserIdRef, StoredDinoparcSession};
 use etwin_core::hammerfest::{HammerfestServer, HammerfestSessionKey, HammerfestUserIdRef, StoredHammerfestSession};
 use etwin_core::oauth::{RfcOauthAccessTokenKey, RfcOauthRefreshTokenKey, TwinoidAccessToken, TwinoidRefreshToken};
-use etwin_core::token::{TokenStore, TouchOauthTokenOptions, TwinoidOauth};
+use etwin_core::popotamo::{PopotamoServer, PopotamoSessionKey, PopotamoUserIdRef, StoredPopotamoSession};
+use etwin_core::token::{SweepTokensOptions, TokenStore, TokenSweep, TouchOauthTokenOptions, TwinoidOauth};
 use etwin_core::twinoid::{TwinoidUserId, TwinoidUserIdRef};
 use etwin_core::types::EtwinError;
 use std::collections::HashMap;
@@ -41,6 +42,10 @@ impl MemDinoparcServers {
       DinoparcServer::SpDinoparcCom => &mut self.sp_dinoparc_com,
     }
   }
+
+  fn sweep(&mut self, before: Instant) -> u64 {
+    self.dinoparc_com.sweep(before) + self.en_dinoparc_com.sweep(before) + self.sp_dinoparc_com.sweep(before)
+  }
 }
 
 struct MemHammerfestServers {
@@ -73,6 +78,42 @@ impl MemHammerfestServers {
       HammerfestServer::HammerfestEs => &mut self.hammerfest_es,
     }
   }
+
+  fn sweep(&mut self, before: Instant) -> u64 {
+    self.hammerfest_fr.sweep(before) + self.hammerfest_es.sweep(before) + self.hfest_net.sweep(before)
+  }
+}
+
+struct MemPopotamoServers {
+  popotamo_com: MemSessions<StoredPopotamoSession>,
+  en_popotamo_com: MemSessions<StoredPopotamoSession>,
+}
+
+impl MemPopotamoServers {
+  fn new() -> Self {
+    Self {
+      popotamo_com: MemSessions::new(),
+      en_popotamo_com: MemSessions::new(),
+    }
+  }
+
+  fn get(&self, server: PopotamoServer) -> &MemSessions<StoredPopotamoSession> {
+    match server {
+      PopotamoServer::PopotamoCom => &self.popotamo_com,
+      PopotamoServer::EnPopotamoCom => &self.en_popotamo_com,
+    }
+  }
+
+  fn get_mut(&mut self, server: PopotamoServer) -> &mut MemSessions<StoredPopotamoSession> {
+    match server {
+      PopotamoServer::PopotamoCom => &mut self.popotamo_com,
+      PopotamoServer::EnPopotamoCom => &mut self.en_popotamo_com,
+    }
+  }
+
+  fn sweep(&mut self, before: Instant) -> u64 {
+    self.popotamo_com.sweep(before) + self.en_popotamo_com.sweep(before)
+  }
 }
 
 struct MemSessions<Session: MemSession> {
@@ -99,6 +140,20 @@ impl<Session: MemSession> MemSessions<Session> {
   fn revoke(&mut self, key: &Session::SessionKey) {
     revoke_session::<Session>(&mut self.sessions, &mut self.session_by_user, key)
   }
+
+  /// Revokes the sessions last accessed before `before`, returns their count.
+  fn sweep(&mut self, before: Instant) -> u64 {
+    let stale: Vec<Session::SessionKey> = self
+      .sessions
+      .iter()
+      .filter(|(_, session)| session.atime() < before)
+      .map(|(key, _)| key.clone())
+      .collect();
+    for key in stale.iter() {
+      self.revoke(key);
+    }
+    stale.len() as u64
+  }
 }
 
 trait MemSession: Clone {
@@ -109,6 +164,8 @@ trait MemSession: Clone {
 
   fn user_id(&self) -> &Self::UserId;
 
+  fn atime(&self) -> Instant;
+
   fn atime_mut(&mut self) -> &mut Instant;
 }
 
@@ -169,6 +226,10 @@ impl MemSession for StoredHammerfestSession {
     &self.user
   }
 
+  fn atime(&self) -> Instant {
+    self.atime
+  }
+
   fn atime_mut(&mut self) -> &mut Instant {
     &mut self.atime
   }
@@ -191,6 +252,36 @@ impl MemSession for StoredDinoparcSession {
     &self.user
   }
 
+  fn atime(&self) -> Instant {
+    self.atime
+  }
+
+  fn atime_mut(&mut self) -> &mut Instant {
+    &mut self.atime
+  }
+}
+
+impl MemSession for StoredPopotamoSession {
+  type SessionKey = PopotamoSessionKey;
+  type UserId = PopotamoUserIdRef;
+
+  fn new(now: Instant, key: Self::SessionKey, user_id: Self::UserId) -> Self {
+    Self {
+      key,
+      user: user_id,
+      ctime: now,
+      atime: now,
+    }
+  }
+
+  fn user_id(&self) -> &Self::UserId {
+    &self.user
+  }
+
+  fn atime(&self) -> Instant {
+    self.atime
+  }
+
   fn atime_mut(&mut self) -> &mut Instant {
     &mut self.atime
   }
@@ -215,6 +306,7 @@ struct StoreState {
   twinoid_user_to_refresh_token: HashMap<TwinoidUserId, RfcOauthRefreshTokenKey>,
   dinoparc: MemDinoparcServers,
   hammerfest: MemHammerfestServers,
+  popotamo: MemPopotamoServers,
 }
 
 impl StoreState {
@@ -226,6 +318,7 @@ impl StoreState {
       twinoid_user_to_refresh_token: HashMap::new(),
       dinoparc: MemDinoparcServers::new(),
       hammerfest: MemHammerfestServers::new(),
+      popotamo: MemPopotamoServers::new(),
     }
   }
 
@@ -337,6 +430,36 @@ impl StoreState {
       debug_assert_eq!(old.as_ref(), Some(options));
     }
   }
+
+  fn sweep(&mut self, now: Instant, options: &SweepTokensOptions) -> TokenSweep {
+    let expired_access_tokens: Vec<RfcOauthAccessTokenKey> = self
+      .twinoid_access_tokens
+      .values()
+      .filter(|token| now >= token.expires_at)
+      .map(|token| token.key.clone())
+      .collect();
+    for key in expired_access_tokens.iter() {
+      self.revoke_twinoid_access_token(key);
+    }
+    let refresh_before = now - options.max_refresh_token_age;
+    let stale_refresh_tokens: Vec<RfcOauthRefreshTokenKey> = self
+      .twinoid_refresh_tokens
+      .values()
+      .filter(|token| token.accessed_at < refresh_before)
+      .map(|token| token.key.clone())
+      .collect();
+    for key in stale_refresh_tokens.iter() {
+      self.revoke_twinoid_refresh_token(key);
+    }
+    let session_before = now - options.max_session_age;
+    TokenSweep {
+      twinoid_access_tokens: expired_access_tokens.len() as u64,
+      twinoid_refresh_tokens: stale_refresh_tokens.len() as u64,
+      dinoparc_sessions: self.dinoparc.sweep(session_before),
+      hammerfest_sessions: self.hammerfest.sweep(session_before),
+      popotamo_sessions: self.popotamo.sweep(session_before),
+    }
+  }
 }
 
 pub struct MemTokenStore<TyClock: Clock> {
@@ -386,6 +509,14 @@ where
     Ok(state.get_twinoid_oauth(now, options))
   }
 
+  async fn get_twinoid_access_token(
+    &self,
+    key: &RfcOauthAccessTokenKey,
+  ) -> Result<Option<TwinoidAccessToken>, EtwinError> {
+    let state = self.state.read().unwrap();
+    Ok(state.twinoid_access_tokens.get(key).cloned())
+  }
+
   async fn touch_dinoparc(
     &self,
     user: DinoparcUserIdRef,
@@ -433,6 +564,36 @@ where
     let server = state.hammerfest.get(user.server);
     Ok(server.get(user))
   }
+
+  async fn touch_popotamo(
+    &self,
+    user: PopotamoUserIdRef,
+    key: &PopotamoSessionKey,
+  ) -> Result<StoredPopotamoSession, EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let server = state.popotamo.get_mut(user.server);
+    let now = self.clock.now();
+    Ok(server.touch(now, key, user))
+  }
+
+  async fn revoke_popotamo(&self, server: PopotamoServer, key: &PopotamoSessionKey) -> Result<(), EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let server = state.popotamo.get_mut(server);
+    server.revoke(key);
+    Ok(())
+  }
+
+  async fn get_popotamo(&self, user: PopotamoUserIdRef) -> Result<Option<StoredPopotamoSession>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let server = state.popotamo.get(user.server);
+    Ok(server.get(user))
+  }
+
+  async fn sweep(&self, options: &SweepTokensOptions) -> Result<TokenSweep, EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    Ok(state.sweep(now, options))
+  }
 }
 
 #[cfg(feature = "neon")]
@@ -446,10 +607,12 @@ mod test {
   use etwin_core::clock::VirtualClock;
   use etwin_core::dinoparc::DinoparcStore;
   use etwin_core::hammerfest::HammerfestStore;
+  use etwin_core::popotamo::PopotamoStore;
   use etwin_core::token::TokenStore;
   use etwin_core::twinoid::TwinoidStore;
   use etwin_dinoparc_store::mem::MemDinoparcStore;
   use etwin_hammerfest_store::mem::MemHammerfestStore;
+  use etwin_popotamo_store::mem::MemPopotamoStore;
   use etwin_twinoid_store::mem::MemTwinoidStore;
   use std::sync::Arc;
 
@@ -458,12 +621,14 @@ mod test {
     Arc<VirtualClock>,
     Arc<dyn DinoparcStore>,
     Arc<dyn HammerfestStore>,
+    Arc<dyn PopotamoStore>,
     Arc<dyn TokenStore>,
     Arc<dyn TwinoidStore>,
   > {
     let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
     let dinoparc_store: Arc<dyn DinoparcStore> = Arc::new(MemDinoparcStore::new(Arc::clone(&clock)));
     let hammerfest_store: Arc<dyn HammerfestStore> = Arc::new(MemHammerfestStore::new(Arc::clone(&clock)));
+    let popotamo_store: Arc<dyn PopotamoStore> = Arc::new(MemPopotamoStore::new(Arc::clone(&clock)));
     let twinoid_store: Arc<dyn TwinoidStore> = Arc::new(MemTwinoidStore::new(Arc::clone(&clock)));
     let token_store: Arc<dyn TokenStore> = Arc::new(MemTokenStore::new(Arc::clone(&clock)));
 
@@ -471,6 +636,7 @@ mod test {
       clock,
       dinoparc_store,
       hammerfest_store,
+      popotamo_store,
       token_store,
       twinoid_store,
     }
@@ -1,18 +1,81 @@
 use async_trait::async_trait;
+use etwin_config::Config;
 use etwin_core::api::ApiRef;
 use etwin_core::clock::Clock;
-use etwin_core::core::{Instant, Secret};
+use etwin_core::core::{Instant, Secret, SecretKey, SecretKeyring};
 use etwin_core::dinoparc::{DinoparcServer, DinoparcSessionKey, DinoparcUserIdRef, StoredDinoparcSession};
 use etwin_core::hammerfest::{HammerfestServer, HammerfestSessionKey, HammerfestUserIdRef, StoredHammerfestSession};
 use etwin_core::oauth::{RfcOauthAccessTokenKey, RfcOauthRefreshTokenKey, TwinoidAccessToken, TwinoidRefreshToken};
-use etwin_core::token::{TokenStore, TouchOauthTokenOptions, TwinoidOauth};
-use etwin_core::twinoid::TwinoidUserIdRef;
+use etwin_core::popotamo::{PopotamoServer, PopotamoSessionKey, PopotamoUserIdRef, StoredPopotamoSession};
+use etwin_core::token::{SweepTokensOptions, TokenStore, TokenSweep, TouchOauthTokenOptions, TwinoidOauth};
+use etwin_core::twinoid::{TwinoidUserId, TwinoidUserIdRef};
 use etwin_core::types::EtwinError;
 use etwin_populate::dinoparc::populate_dinoparc;
 use etwin_populate::hammerfest::populate_hammerfest;
-use sqlx::PgPool;
+use etwin_populate::popotamo::populate_popotamo;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
 use std::error::Error;
 
+/// Id of the key derived from the main secret, used to encrypt the rows written before the keyring was introduced.
+pub const DEFAULT_KEY_ID: &str = "etwin.secret";
+
+/// Builds the keyring of the token store from `[db]`.
+///
+/// The main secret is always available under the id [DEFAULT_KEY_ID], so the secrets stored before the first
+/// rotation can still be read.
+pub fn token_keyring(config: &Config) -> Result<SecretKeyring, EtwinError> {
+  let default_key = SecretKey {
+    id: DEFAULT_KEY_ID.to_string(),
+    secret: Secret::new(config.etwin.secret.clone()),
+  };
+  let current = match config.db.token_key.as_deref() {
+    None | Some(DEFAULT_KEY_ID) => default_key.clone(),
+    Some(id) => match config.db.token_keys.get(id) {
+      Some(secret) => SecretKey {
+        id: id.to_string(),
+        secret: Secret::new(secret.clone()),
+      },
+      None => return Err(format!("unknown token key {:?}: it is missing from `db.token_keys`", id).into()),
+    },
+  };
+  let keyring = config
+    .db
+    .token_keys
+    .iter()
+    .filter(|(id, _)| id.as_str() != DEFAULT_KEY_ID)
+    .fold(SecretKeyring::new(current), |keyring, (id, secret)| {
+      keyring.with_previous(SecretKey {
+        id: id.clone(),
+        secret: Secret::new(secret.clone()),
+      })
+    });
+  Ok(keyring.with_previous(default_key))
+}
+
+/// Tables storing encrypted secrets, with their encrypted column.
+const ENCRYPTED_COLUMNS: [(&str, &str); 10] = [
+  ("twinoid_access_tokens", "twinoid_access_token"),
+  ("old_twinoid_access_tokens", "twinoid_access_token"),
+  ("twinoid_refresh_tokens", "twinoid_refresh_token"),
+  ("old_twinoid_refresh_tokens", "twinoid_refresh_token"),
+  ("dinoparc_sessions", "dinoparc_session_key"),
+  ("old_dinoparc_sessions", "dinoparc_session_key"),
+  ("hammerfest_sessions", "hammerfest_session_key"),
+  ("old_hammerfest_sessions", "hammerfest_session_key"),
+  ("popotamo_sessions", "popotamo_session_key"),
+  ("old_popotamo_sessions", "popotamo_session_key"),
+];
+
+/// Result of a key rotation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyRotation {
+  /// Number of secrets re-encrypted with the current key
+  pub rotated: u64,
+  /// Number of secrets encrypted with a key missing from the keyring, they can no longer be decrypted
+  pub unknown_key: u64,
+}
+
 pub struct PgTokenStore<TyClock, TyDatabase>
 where
   TyClock: Clock,
@@ -20,33 +83,128 @@ where
 {
   clock: TyClock,
   database: TyDatabase,
-  database_secret: Secret,
+  keyring: SecretKeyring,
+  /// Ids of the keys in `keyring`, bound as an array to find the decryption key of each row
+  key_ids: Vec<String>,
+  /// Secrets of the keys in `keyring`, in the same order as `key_ids`
+  key_secrets: Vec<String>,
 }
 
 fn box_sqlx_error(e: sqlx::Error) -> Box<dyn Error + Send> {
   Box::new(e)
 }
 
+/// Error for a row of `table` encrypted with the key `key_id`, missing from the keyring.
+fn unknown_key_error(table: &str, key_id: &str) -> EtwinError {
+  format!(
+    "a secret in `{}` is encrypted with the key {:?}, missing from the keyring",
+    table, key_id
+  )
+  .into()
+}
+
+/// Deletes the rows of `table` and of its revoked rows (`old_{table}`) where `condition` holds, with `$1` bound to
+/// `time`.
+async fn sweep_table(
+  tx: &mut Transaction<'_, Postgres>,
+  table: &str,
+  condition: &str,
+  time: Instant,
+) -> Result<u64, EtwinError> {
+  let mut deleted = 0;
+  for table in [table.to_string(), format!("old_{}", table)].iter() {
+    let res = sqlx::query(&format!("DELETE FROM {} WHERE {};", table, condition))
+      .bind(time)
+      .execute(&mut *tx)
+      .await?;
+    deleted += res.rows_affected();
+  }
+  Ok(deleted)
+}
+
 impl<TyClock, TyDatabase> PgTokenStore<TyClock, TyDatabase>
 where
   TyClock: Clock,
   TyDatabase: ApiRef<PgPool>,
 {
+  /// Creates a store encrypting the secrets with the main secret, see [DEFAULT_KEY_ID].
   pub async fn new(
     clock: TyClock,
     database: TyDatabase,
     database_secret: Secret,
+  ) -> Result<Self, Box<dyn Error + Send>> {
+    let keyring = SecretKeyring::new(SecretKey {
+      id: DEFAULT_KEY_ID.to_string(),
+      secret: database_secret,
+    });
+    Self::with_keyring(clock, database, keyring).await
+  }
+
+  /// Creates a store encrypting the secrets with the current key of `keyring`.
+  ///
+  /// Reading a secret encrypted with a key missing from `keyring` fails: add the key back to the keyring, or remove
+  /// the secret, to recover.
+  pub async fn with_keyring(
+    clock: TyClock,
+    database: TyDatabase,
+    keyring: SecretKeyring,
   ) -> Result<Self, Box<dyn Error + Send>> {
     let mut tx = database.as_ref().begin().await.map_err(box_sqlx_error)?;
     populate_dinoparc(&mut tx).await?;
     populate_hammerfest(&mut tx).await?;
+    populate_popotamo(&mut tx).await?;
     tx.commit().await.map_err(box_sqlx_error)?;
+    let key_ids = keyring.keys().map(|key| key.id.clone()).collect();
+    let key_secrets = keyring.keys().map(|key| key.secret.as_str().to_string()).collect();
     Ok(Self {
       clock,
       database,
-      database_secret,
+      keyring,
+      key_ids,
+      key_secrets,
     })
   }
+
+  /// Re-encrypts all the stored secrets with the current key, including the revoked ones.
+  ///
+  /// Once no secret uses an old key, it can be removed from the keyring.
+  pub async fn rotate_keys(&self) -> Result<KeyRotation, EtwinError> {
+    let mut tx = self.database.as_ref().begin().await?;
+    let current = self.keyring.current();
+    let mut result = KeyRotation::default();
+    for (table, column) in ENCRYPTED_COLUMNS.iter() {
+      let res = sqlx::query(&format!(
+        r"
+          UPDATE {table} AS t
+          SET {column} = pgp_sym_encrypt(pgp_sym_decrypt(t.{column}, k.secret), $2::TEXT), encryption_key_id = $1::TEXT
+          FROM UNNEST($3::TEXT[], $4::TEXT[]) AS k(key_id, secret)
+          WHERE t.encryption_key_id = k.key_id AND t.encryption_key_id <> $1::TEXT;",
+        table = table,
+        column = column,
+      ))
+      .bind(current.id.as_str())
+      .bind(current.secret.as_str())
+      .bind(&self.key_ids)
+      .bind(&self.key_secrets)
+      .execute(&mut tx)
+      .await?;
+      result.rotated += res.rows_affected();
+
+      let (unknown_key,): (i64,) = sqlx::query_as(&format!(
+        r"
+          SELECT COUNT(*)
+          FROM {table}
+          WHERE encryption_key_id <> $1::TEXT;",
+        table = table,
+      ))
+      .bind(current.id.as_str())
+      .fetch_one(&mut tx)
+      .await?;
+      result.unknown_key += u64::try_from(unknown_key).unwrap_or_default();
+    }
+    tx.commit().await?;
+    Ok(result)
+  }
 }
 
 #[async_trait]
@@ -75,10 +233,10 @@ where
                   AND tat.twinoid_user_id = $3::TWINOID_USER_ID
                 )
               )
-            RETURNING twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, $1::INSTANT AS dtime, expiration_time
+            RETURNING twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, $1::INSTANT AS dtime, expiration_time, encryption_key_id
           )
           INSERT INTO old_twinoid_access_tokens(twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id,
-                                                ctime, atime, dtime, expiration_time)
+                                                ctime, atime, dtime, expiration_time, encryption_key_id)
           SELECT revoked.*
           FROM revoked;",
         )
@@ -107,10 +265,10 @@ where
                   AND trt.twinoid_user_id = $3::TWINOID_USER_ID
                 )
               )
-            RETURNING twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime
+            RETURNING twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime, encryption_key_id
           )
           INSERT INTO old_twinoid_refresh_tokens(twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id,
-                                                ctime, atime, dtime)
+                                                ctime, atime, encryption_key_id, dtime)
           SELECT revoked.*, $1::INSTANT AS dtime
           FROM revoked;",
         )
@@ -128,16 +286,17 @@ where
         // Upsert the access token
         let res = sqlx::query(
           r"
-          INSERT INTO twinoid_access_tokens(twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, expiration_time)
-          VALUES (pgp_sym_encrypt($3::TEXT, $2::TEXT), digest($3::TEXT, 'sha256'), $4::TWINOID_USER_ID, $1::INSTANT, $1::INSTANT, $5::INSTANT)
+          INSERT INTO twinoid_access_tokens(twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, expiration_time, encryption_key_id)
+          VALUES (pgp_sym_encrypt($3::TEXT, $2::TEXT), digest($3::TEXT, 'sha256'), $4::TWINOID_USER_ID, $1::INSTANT, $1::INSTANT, $5::INSTANT, $6::TEXT)
           ON CONFLICT (_twinoid_access_token_hash)
-            DO UPDATE SET atime = $1::INSTANT;",
+            DO UPDATE SET atime = $1::INSTANT, twinoid_access_token = excluded.twinoid_access_token, encryption_key_id = excluded.encryption_key_id;",
         )
           .bind(now)
-        .bind(&self.database_secret.as_str())
+        .bind(self.keyring.current().secret.as_str())
         .bind(&options.access_token)
         .bind(&options.twinoid_user_id)
         .bind(&options.expiration_time)
+        .bind(self.keyring.current().id.as_str())
         .execute(&mut tx)
         .await?;
         // Affected row counts:
@@ -149,16 +308,16 @@ where
         // Upsert the refresh token
         let res = sqlx::query(
           r"
-          INSERT INTO twinoid_refresh_tokens(twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime)
-          VALUES (pgp_sym_encrypt($3::TEXT, $2::TEXT), digest($3::TEXT, 'sha256'), $4::TWINOID_USER_ID, $1::INSTANT, $1::INSTANT)
+          INSERT INTO twinoid_refresh_tokens(twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime, encryption_key_id)
+          VALUES (pgp_sym_encrypt($3::TEXT, $2::TEXT), digest($3::TEXT, 'sha256'), $4::TWINOID_USER_ID, $1::INSTANT, $1::INSTANT, $5::TEXT)
           ON CONFLICT (_twinoid_refresh_token_hash)
-            DO UPDATE SET atime = $1::INSTANT;",
+            DO UPDATE SET atime = $1::INSTANT, twinoid_refresh_token = excluded.twinoid_refresh_token, encryption_key_id = excluded.encryption_key_id;",
         )
           .bind(now)
-        .bind(&self.database_secret.as_str())
+        .bind(self.keyring.current().secret.as_str())
         .bind(&options.refresh_token)
         .bind(&options.twinoid_user_id)
-        .bind(&options.expiration_time)
+        .bind(self.keyring.current().id.as_str())
           .execute(&mut tx)
         .await?;
         // Affected row counts:
@@ -180,10 +339,10 @@ where
         WITH revoked AS (
           DELETE FROM twinoid_access_tokens
             WHERE _twinoid_access_token_hash = digest($2::TEXT, 'sha256')
-            RETURNING twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, expiration_time
+            RETURNING twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, expiration_time, encryption_key_id
         )
         INSERT
-        INTO old_twinoid_access_tokens(twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, expiration_time, dtime)
+        INTO old_twinoid_access_tokens(twinoid_access_token, _twinoid_access_token_hash, twinoid_user_id, ctime, atime, expiration_time, encryption_key_id, dtime)
         SELECT revoked.*, $1::INSTANT AS dtime
         FROM revoked;",
       )
@@ -205,10 +364,10 @@ where
         WITH revoked AS (
           DELETE FROM twinoid_refresh_tokens
             WHERE _twinoid_refresh_token_hash = digest($2::TEXT, 'sha256')
-            RETURNING twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime
+            RETURNING twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime, encryption_key_id
         )
         INSERT
-        INTO old_twinoid_refresh_tokens(twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime, dtime)
+        INTO old_twinoid_refresh_tokens(twinoid_refresh_token, _twinoid_refresh_token_hash, twinoid_user_id, ctime, atime, encryption_key_id, dtime)
         SELECT revoked.*, $1::INSTANT AS dtime
         FROM revoked;",
     )
@@ -231,33 +390,44 @@ where
       let refresh_token = {
         #[derive(Debug, sqlx::FromRow)]
         struct Row {
-          twinoid_refresh_token: RfcOauthRefreshTokenKey,
+          twinoid_refresh_token: Option<RfcOauthRefreshTokenKey>,
+          encryption_key_id: String,
           ctime: Instant,
           atime: Instant,
         }
 
         let row: Option<Row> = sqlx::query_as::<_, Row>(
           r"
-          SELECT pgp_sym_decrypt(twinoid_refresh_token, $1::TEXT) AS twinoid_refresh_token, ctime, atime
+          SELECT pgp_sym_decrypt(twinoid_refresh_token, k.secret) AS twinoid_refresh_token, encryption_key_id, ctime, atime
           FROM twinoid_refresh_tokens
-          WHERE twinoid_user_id = $2::TWINOID_USER_ID;
+            LEFT OUTER JOIN UNNEST($1::TEXT[], $2::TEXT[]) AS k(key_id, secret) ON k.key_id = encryption_key_id
+          WHERE twinoid_user_id = $3::TWINOID_USER_ID;
         ",
         )
-        .bind(self.database_secret.as_str())
+        .bind(&self.key_ids)
+        .bind(&self.key_secrets)
         .bind(options.id)
         .fetch_optional(&mut tx)
         .await?;
-        row.map(|r| TwinoidRefreshToken {
-          key: r.twinoid_refresh_token,
-          created_at: r.ctime,
-          accessed_at: r.atime,
-          twinoid_user_id: options.id,
-        })
+        row
+          .map(|r| -> Result<_, EtwinError> {
+            let encryption_key_id = r.encryption_key_id;
+            Ok(TwinoidRefreshToken {
+              key: r
+                .twinoid_refresh_token
+                .ok_or_else(|| unknown_key_error("twinoid_refresh_tokens", &encryption_key_id))?,
+              created_at: r.ctime,
+              accessed_at: r.atime,
+              twinoid_user_id: options.id,
+            })
+          })
+          .transpose()?
       };
       let access_token = {
         #[derive(Debug, sqlx::FromRow)]
         struct Row {
-          twinoid_access_token: RfcOauthAccessTokenKey,
+          twinoid_access_token: Option<RfcOauthAccessTokenKey>,
+          encryption_key_id: String,
           ctime: Instant,
           atime: Instant,
           expiration_time: Instant,
@@ -265,24 +435,33 @@ where
 
         let row: Option<Row> = sqlx::query_as::<_, Row>(
           r"
-          SELECT pgp_sym_decrypt(twinoid_access_token, $2::TEXT) AS twinoid_access_token, ctime, atime, expiration_time
+          SELECT pgp_sym_decrypt(twinoid_access_token, k.secret) AS twinoid_access_token, encryption_key_id, ctime, atime, expiration_time
           FROM twinoid_access_tokens
-          WHERE twinoid_user_id = $3::TWINOID_USER_ID AND $1::INSTANT < expiration_time;
+            LEFT OUTER JOIN UNNEST($2::TEXT[], $3::TEXT[]) AS k(key_id, secret) ON k.key_id = encryption_key_id
+          WHERE twinoid_user_id = $4::TWINOID_USER_ID AND $1::INSTANT < expiration_time;
         ",
         )
         .bind(now)
-        .bind(self.database_secret.as_str())
+        .bind(&self.key_ids)
+        .bind(&self.key_secrets)
         .bind(options.id)
         .fetch_optional(&mut tx)
         .await?;
 
-        row.map(|row| TwinoidAccessToken {
-          key: row.twinoid_access_token,
-          created_at: row.ctime,
-          accessed_at: row.atime,
-          expires_at: row.expiration_time,
-          twinoid_user_id: options.id,
-        })
+        row
+          .map(|row| -> Result<_, EtwinError> {
+            let encryption_key_id = row.encryption_key_id;
+            Ok(TwinoidAccessToken {
+              key: row
+                .twinoid_access_token
+                .ok_or_else(|| unknown_key_error("twinoid_access_tokens", &encryption_key_id))?,
+              created_at: row.ctime,
+              accessed_at: row.atime,
+              expires_at: row.expiration_time,
+              twinoid_user_id: options.id,
+            })
+          })
+          .transpose()?
       };
 
       TwinoidOauth {
@@ -296,6 +475,39 @@ where
     Ok(result)
   }
 
+  async fn get_twinoid_access_token(
+    &self,
+    key: &RfcOauthAccessTokenKey,
+  ) -> Result<Option<TwinoidAccessToken>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      twinoid_user_id: TwinoidUserId,
+      ctime: Instant,
+      atime: Instant,
+      expiration_time: Instant,
+    }
+
+    // The key is known: the token is found by its hash without decrypting it
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT twinoid_user_id, ctime, atime, expiration_time
+      FROM twinoid_access_tokens
+      WHERE _twinoid_access_token_hash = digest($1::TEXT, 'sha256');
+    ",
+    )
+    .bind(key)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    Ok(row.map(|row| TwinoidAccessToken {
+      key: key.clone(),
+      created_at: row.ctime,
+      accessed_at: row.atime,
+      expires_at: row.expiration_time,
+      twinoid_user_id: row.twinoid_user_id,
+    }))
+  }
+
   async fn touch_dinoparc(
     &self,
     user: DinoparcUserIdRef,
@@ -320,9 +532,9 @@ where
                 AND hs.dinoparc_user_id = $4::DINOPARC_USER_ID
               )
             )
-          RETURNING dinoparc_server, dinoparc_session_key,_dinoparc_session_key_hash, dinoparc_user_id, ctime, atime
+          RETURNING dinoparc_server, dinoparc_session_key,_dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, encryption_key_id
         )
-        INSERT INTO old_dinoparc_sessions(dinoparc_server, dinoparc_session_key, _dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, dtime)
+        INSERT INTO old_dinoparc_sessions(dinoparc_server, dinoparc_session_key, _dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, encryption_key_id, dtime)
         SELECT revoked.*, $1::INSTANT AS dtime
         FROM revoked;",
       )
@@ -347,17 +559,18 @@ where
 
       let row: Row = sqlx::query_as::<_, Row>(
         r"
-        INSERT INTO dinoparc_sessions(dinoparc_server, dinoparc_session_key, _dinoparc_session_key_hash, dinoparc_user_id, ctime, atime)
-        VALUES ($3::DINOPARC_SERVER, pgp_sym_encrypt($4::DINOPARC_SESSION_KEY, $2::TEXT), digest($4::DINOPARC_SESSION_KEY, 'sha256'), $5::DINOPARC_USER_ID, $1::INSTANT, $1::INSTANT)
+        INSERT INTO dinoparc_sessions(dinoparc_server, dinoparc_session_key, _dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, encryption_key_id)
+        VALUES ($3::DINOPARC_SERVER, pgp_sym_encrypt($4::DINOPARC_SESSION_KEY, $2::TEXT), digest($4::DINOPARC_SESSION_KEY, 'sha256'), $5::DINOPARC_USER_ID, $1::INSTANT, $1::INSTANT, $6::TEXT)
         ON CONFLICT (dinoparc_server, _dinoparc_session_key_hash)
-          DO UPDATE SET atime = $1::INSTANT
+          DO UPDATE SET atime = $1::INSTANT, dinoparc_session_key = excluded.dinoparc_session_key, encryption_key_id = excluded.encryption_key_id
         RETURNING ctime, atime;",
       )
         .bind(now)
-        .bind(self.database_secret.as_str())
+        .bind(self.keyring.current().secret.as_str())
         .bind(user.server)
         .bind(key)
         .bind(user.id)
+        .bind(self.keyring.current().id.as_str())
         .fetch_one(&mut tx)
         .await?;
 
@@ -381,9 +594,9 @@ where
         WITH revoked AS (
           DELETE FROM dinoparc_sessions
             WHERE dinoparc_server = $2::DINOPARC_SERVER AND _dinoparc_session_key_hash = digest($3::DINOPARC_SESSION_KEY, 'sha256')
-            RETURNING dinoparc_server, dinoparc_session_key,_dinoparc_session_key_hash, dinoparc_user_id, ctime, atime
+            RETURNING dinoparc_server, dinoparc_session_key,_dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, encryption_key_id
         )
-        INSERT INTO old_dinoparc_sessions(dinoparc_server, dinoparc_session_key, _dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, dtime)
+        INSERT INTO old_dinoparc_sessions(dinoparc_server, dinoparc_session_key, _dinoparc_session_key_hash, dinoparc_user_id, ctime, atime, encryption_key_id, dtime)
         SELECT revoked.*, $1::INSTANT AS dtime
         FROM revoked;",
     )
@@ -402,30 +615,40 @@ where
   async fn get_dinoparc(&self, user: DinoparcUserIdRef) -> Result<Option<StoredDinoparcSession>, EtwinError> {
     #[derive(Debug, sqlx::FromRow)]
     struct Row {
-      dinoparc_session_key: DinoparcSessionKey,
+      dinoparc_session_key: Option<DinoparcSessionKey>,
+      encryption_key_id: String,
       ctime: Instant,
       atime: Instant,
     }
 
     let row: Option<Row> = sqlx::query_as::<_, Row>(
       r"
-          SELECT pgp_sym_decrypt(dinoparc_session_key, $1::TEXT) AS dinoparc_session_key, ctime, atime
+          SELECT pgp_sym_decrypt(dinoparc_session_key, k.secret) AS dinoparc_session_key, encryption_key_id, ctime, atime
           FROM dinoparc_sessions
-          WHERE dinoparc_server = $2::DINOPARC_SERVER AND dinoparc_user_id = $3::DINOPARC_USER_ID;
+            LEFT OUTER JOIN UNNEST($1::TEXT[], $2::TEXT[]) AS k(key_id, secret) ON k.key_id = encryption_key_id
+          WHERE dinoparc_server = $3::DINOPARC_SERVER AND dinoparc_user_id = $4::DINOPARC_USER_ID;
         ",
     )
-    .bind(self.database_secret.as_str())
+    .bind(&self.key_ids)
+    .bind(&self.key_secrets)
     .bind(user.server)
     .bind(user.id)
     .fetch_optional(self.database.as_ref())
     .await?;
 
-    let result = row.map(|r| StoredDinoparcSession {
-      ctime: r.ctime,
-      atime: r.atime,
-      key: r.dinoparc_session_key,
-      user,
-    });
+    let result = row
+      .map(|r| -> Result<_, EtwinError> {
+        let encryption_key_id = r.encryption_key_id;
+        Ok(StoredDinoparcSession {
+          ctime: r.ctime,
+          atime: r.atime,
+          key: r
+            .dinoparc_session_key
+            .ok_or_else(|| unknown_key_error("dinoparc_sessions", &encryption_key_id))?,
+          user,
+        })
+      })
+      .transpose()?;
 
     Ok(result)
   }
@@ -454,9 +677,9 @@ where
                 AND hs.hammerfest_user_id = $4::HAMMERFEST_USER_ID
               )
             )
-          RETURNING hammerfest_server, hammerfest_session_key,_hammerfest_session_key_hash, hammerfest_user_id, ctime, atime
+          RETURNING hammerfest_server, hammerfest_session_key,_hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, encryption_key_id
         )
-        INSERT INTO old_hammerfest_sessions(hammerfest_server, hammerfest_session_key, _hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, dtime)
+        INSERT INTO old_hammerfest_sessions(hammerfest_server, hammerfest_session_key, _hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, encryption_key_id, dtime)
         SELECT revoked.*, $1::INSTANT AS dtime
         FROM revoked;",
       )
@@ -481,17 +704,18 @@ where
 
       let row: Row = sqlx::query_as::<_, Row>(
         r"
-        INSERT INTO hammerfest_sessions(hammerfest_server, hammerfest_session_key, _hammerfest_session_key_hash, hammerfest_user_id, ctime, atime)
-        VALUES ($3::HAMMERFEST_SERVER, pgp_sym_encrypt($4::HAMMERFEST_SESSION_KEY, $2::TEXT), digest($4::HAMMERFEST_SESSION_KEY, 'sha256'), $5::HAMMERFEST_USER_ID, $1::INSTANT, $1::INSTANT)
+        INSERT INTO hammerfest_sessions(hammerfest_server, hammerfest_session_key, _hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, encryption_key_id)
+        VALUES ($3::HAMMERFEST_SERVER, pgp_sym_encrypt($4::HAMMERFEST_SESSION_KEY, $2::TEXT), digest($4::HAMMERFEST_SESSION_KEY, 'sha256'), $5::HAMMERFEST_USER_ID, $1::INSTANT, $1::INSTANT, $6::TEXT)
         ON CONFLICT (hammerfest_server, _hammerfest_session_key_hash)
-          DO UPDATE SET atime = $1::INSTANT
+          DO UPDATE SET atime = $1::INSTANT, hammerfest_session_key = excluded.hammerfest_session_key, encryption_key_id = excluded.encryption_key_id
         RETURNING ctime, atime;",
       )
       .bind(now)
-      .bind(self.database_secret.as_str())
+      .bind(self.keyring.current().secret.as_str())
       .bind(user.server)
       .bind(key)
       .bind(user.id)
+      .bind(self.keyring.current().id.as_str())
       .fetch_one(&mut tx)
       .await?;
 
@@ -515,9 +739,9 @@ where
         WITH revoked AS (
           DELETE FROM hammerfest_sessions
             WHERE hammerfest_server = $2::HAMMERFEST_SERVER AND _hammerfest_session_key_hash = digest($3::HAMMERFEST_SESSION_KEY, 'sha256')
-            RETURNING hammerfest_server, hammerfest_session_key,_hammerfest_session_key_hash, hammerfest_user_id, ctime, atime
+            RETURNING hammerfest_server, hammerfest_session_key,_hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, encryption_key_id
         )
-        INSERT INTO old_hammerfest_sessions(hammerfest_server, hammerfest_session_key, _hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, dtime)
+        INSERT INTO old_hammerfest_sessions(hammerfest_server, hammerfest_session_key, _hammerfest_session_key_hash, hammerfest_user_id, ctime, atime, encryption_key_id, dtime)
         SELECT revoked.*, $1::INSTANT AS dtime
         FROM revoked;",
     )
@@ -536,30 +760,206 @@ where
   async fn get_hammerfest(&self, user: HammerfestUserIdRef) -> Result<Option<StoredHammerfestSession>, EtwinError> {
     #[derive(Debug, sqlx::FromRow)]
     struct Row {
-      hammerfest_session_key: HammerfestSessionKey,
+      hammerfest_session_key: Option<HammerfestSessionKey>,
+      encryption_key_id: String,
       ctime: Instant,
       atime: Instant,
     }
 
     let row: Option<Row> = sqlx::query_as::<_, Row>(
       r"
-          SELECT pgp_sym_decrypt(hammerfest_session_key, $1::TEXT) AS hammerfest_session_key, ctime, atime
+          SELECT pgp_sym_decrypt(hammerfest_session_key, k.secret) AS hammerfest_session_key, encryption_key_id, ctime, atime
           FROM hammerfest_sessions
-          WHERE hammerfest_server = $2::HAMMERFEST_SERVER AND hammerfest_user_id = $3::HAMMERFEST_USER_ID;
+            LEFT OUTER JOIN UNNEST($1::TEXT[], $2::TEXT[]) AS k(key_id, secret) ON k.key_id = encryption_key_id
+          WHERE hammerfest_server = $3::HAMMERFEST_SERVER AND hammerfest_user_id = $4::HAMMERFEST_USER_ID;
         ",
     )
-    .bind(self.database_secret.as_str())
+    .bind(&self.key_ids)
+    .bind(&self.key_secrets)
     .bind(user.server)
     .bind(user.id)
     .fetch_optional(self.database.as_ref())
     .await?;
 
-    let result = row.map(|r| StoredHammerfestSession {
-      ctime: r.ctime,
-      atime: r.atime,
-      key: r.hammerfest_session_key,
-      user,
-    });
+    let result = row
+      .map(|r| -> Result<_, EtwinError> {
+        let encryption_key_id = r.encryption_key_id;
+        Ok(StoredHammerfestSession {
+          ctime: r.ctime,
+          atime: r.atime,
+          key: r
+            .hammerfest_session_key
+            .ok_or_else(|| unknown_key_error("hammerfest_sessions", &encryption_key_id))?,
+          user,
+        })
+      })
+      .transpose()?;
+
+    Ok(result)
+  }
+
+  async fn touch_popotamo(
+    &self,
+    user: PopotamoUserIdRef,
+    key: &PopotamoSessionKey,
+  ) -> Result<StoredPopotamoSession, EtwinError> {
+    let mut tx = self.database.as_ref().begin().await?;
+    let now = self.clock.now();
+
+    {
+      let res = sqlx::query(
+        r"
+          WITH revoked AS (
+          DELETE FROM popotamo_sessions AS hs
+          WHERE hs.popotamo_server = $2::POPOTAMO_SERVER
+            AND (
+              (
+              hs._popotamo_session_key_hash = digest($3::POPOTAMO_SESSION_KEY, 'sha256')
+                AND hs.popotamo_user_id <> $4::POPOTAMO_USER_ID
+              )
+              OR (
+              hs._popotamo_session_key_hash <> digest($3::POPOTAMO_SESSION_KEY, 'sha256')
+                AND hs.popotamo_user_id = $4::POPOTAMO_USER_ID
+              )
+            )
+          RETURNING popotamo_server, popotamo_session_key,_popotamo_session_key_hash, popotamo_user_id, ctime, atime, encryption_key_id
+        )
+        INSERT INTO old_popotamo_sessions(popotamo_server, popotamo_session_key, _popotamo_session_key_hash, popotamo_user_id, ctime, atime, encryption_key_id, dtime)
+        SELECT revoked.*, $1::INSTANT AS dtime
+        FROM revoked;",
+      )
+        .bind(now)
+        .bind(user.server)
+        .bind(key)
+        .bind(user.id)
+        .execute(&mut tx)
+        .await?;
+      // Affected row counts:
+      // +0-1: Session matched a different previous user
+      // +0-1: User matched a different older session
+      assert!((0..=2u64).contains(&res.rows_affected()));
+    }
+
+    let session = {
+      #[derive(Debug, sqlx::FromRow)]
+      struct Row {
+        ctime: Instant,
+        atime: Instant,
+      }
+
+      let row: Row = sqlx::query_as::<_, Row>(
+        r"
+        INSERT INTO popotamo_sessions(popotamo_server, popotamo_session_key, _popotamo_session_key_hash, popotamo_user_id, ctime, atime, encryption_key_id)
+        VALUES ($3::POPOTAMO_SERVER, pgp_sym_encrypt($4::POPOTAMO_SESSION_KEY, $2::TEXT), digest($4::POPOTAMO_SESSION_KEY, 'sha256'), $5::POPOTAMO_USER_ID, $1::INSTANT, $1::INSTANT, $6::TEXT)
+        ON CONFLICT (popotamo_server, _popotamo_session_key_hash)
+          DO UPDATE SET atime = $1::INSTANT, popotamo_session_key = excluded.popotamo_session_key, encryption_key_id = excluded.encryption_key_id
+        RETURNING ctime, atime;",
+      )
+        .bind(now)
+        .bind(self.keyring.current().secret.as_str())
+        .bind(user.server)
+        .bind(key)
+        .bind(user.id)
+        .bind(self.keyring.current().id.as_str())
+        .fetch_one(&mut tx)
+        .await?;
+
+      StoredPopotamoSession {
+        key: key.clone(),
+        user,
+        ctime: row.ctime,
+        atime: row.atime,
+      }
+    };
+
+    tx.commit().await?;
+
+    Ok(session)
+  }
+
+  async fn revoke_popotamo(&self, server: PopotamoServer, key: &PopotamoSessionKey) -> Result<(), EtwinError> {
+    let now = self.clock.now();
+    let res = sqlx::query(
+      r"
+        WITH revoked AS (
+          DELETE FROM popotamo_sessions
+            WHERE popotamo_server = $2::POPOTAMO_SERVER AND _popotamo_session_key_hash = digest($3::POPOTAMO_SESSION_KEY, 'sha256')
+            RETURNING popotamo_server, popotamo_session_key,_popotamo_session_key_hash, popotamo_user_id, ctime, atime, encryption_key_id
+        )
+        INSERT INTO old_popotamo_sessions(popotamo_server, popotamo_session_key, _popotamo_session_key_hash, popotamo_user_id, ctime, atime, encryption_key_id, dtime)
+        SELECT revoked.*, $1::INSTANT AS dtime
+        FROM revoked;",
+    )
+      .bind(now)
+      .bind(server)
+      .bind(key)
+      .execute(self.database.as_ref())
+      .await?;
+    // Affected row counts:
+    // 0: The revoked session key does not exist (or is already revoked)
+    // 1: The session key was revoked
+    assert!((0..=1u64).contains(&res.rows_affected()));
+    Ok(())
+  }
+
+  async fn get_popotamo(&self, user: PopotamoUserIdRef) -> Result<Option<StoredPopotamoSession>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      popotamo_session_key: Option<PopotamoSessionKey>,
+      encryption_key_id: String,
+      ctime: Instant,
+      atime: Instant,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+          SELECT pgp_sym_decrypt(popotamo_session_key, k.secret) AS popotamo_session_key, encryption_key_id, ctime, atime
+          FROM popotamo_sessions
+            LEFT OUTER JOIN UNNEST($1::TEXT[], $2::TEXT[]) AS k(key_id, secret) ON k.key_id = encryption_key_id
+          WHERE popotamo_server = $3::POPOTAMO_SERVER AND popotamo_user_id = $4::POPOTAMO_USER_ID;
+        ",
+    )
+    .bind(&self.key_ids)
+    .bind(&self.key_secrets)
+    .bind(user.server)
+    .bind(user.id)
+    .fetch_optional(self.database.as_ref())
+    .await?;
+
+    let result = row
+      .map(|r| -> Result<_, EtwinError> {
+        let encryption_key_id = r.encryption_key_id;
+        Ok(StoredPopotamoSession {
+          ctime: r.ctime,
+          atime: r.atime,
+          key: r
+            .popotamo_session_key
+            .ok_or_else(|| unknown_key_error("popotamo_sessions", &encryption_key_id))?,
+          user,
+        })
+      })
+      .transpose()?;
+
+    Ok(result)
+  }
+
+  async fn sweep(&self, options: &SweepTokensOptions) -> Result<TokenSweep, EtwinError> {
+    let mut tx = self.database.as_ref().begin().await?;
+    let now = self.clock.now();
+    let refresh_before = now - options.max_refresh_token_age;
+    let session_before = now - options.max_session_age;
+
+    let result = TokenSweep {
+      twinoid_access_tokens: sweep_table(&mut tx, "twinoid_access_tokens", "expiration_time <= $1::INSTANT", now)
+        .await?,
+      twinoid_refresh_tokens: sweep_table(&mut tx, "twinoid_refresh_tokens", "atime < $1::INSTANT", refresh_before)
+        .await?,
+      dinoparc_sessions: sweep_table(&mut tx, "dinoparc_sessions", "atime < $1::INSTANT", session_before).await?,
+      hammerfest_sessions: sweep_table(&mut tx, "hammerfest_sessions", "atime < $1::INSTANT", session_before).await?,
+      popotamo_sessions: sweep_table(&mut tx, "popotamo_sessions", "atime < $1::INSTANT", session_before).await?,
+    };
+
+    tx.commit().await?;
 
     Ok(result)
   }
@@ -575,32 +975,28 @@ where
 
 #[cfg(test)]
 mod test {
-  use super::PgTokenStore;
+  use super::{KeyRotation, PgTokenStore, DEFAULT_KEY_ID};
   use crate::test::TestApi;
   use chrono::{TimeZone, Utc};
   use etwin_core::clock::VirtualClock;
-  use etwin_core::core::Secret;
+  use etwin_core::core::{Secret, SecretKey, SecretKeyring};
   use etwin_core::dinoparc::DinoparcStore;
-  use etwin_core::hammerfest::HammerfestStore;
+  use etwin_core::hammerfest::{HammerfestServer, HammerfestSessionKey, HammerfestStore, ShortHammerfestUser};
+  use etwin_core::popotamo::PopotamoStore;
   use etwin_core::token::TokenStore;
   use etwin_core::twinoid::TwinoidStore;
   use etwin_core::uuid::Uuid4Generator;
   use etwin_db_schema::force_create_latest;
   use etwin_dinoparc_store::pg::PgDinoparcStore;
   use etwin_hammerfest_store::pg::PgHammerfestStore;
+  use etwin_popotamo_store::pg::PgPopotamoStore;
   use etwin_twinoid_store::pg::PgTwinoidStore;
   use serial_test::serial;
   use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
   use sqlx::PgPool;
   use std::sync::Arc;
 
-  async fn make_test_api() -> TestApi<
-    Arc<VirtualClock>,
-    Arc<dyn DinoparcStore>,
-    Arc<dyn HammerfestStore>,
-    Arc<dyn TokenStore>,
-    Arc<dyn TwinoidStore>,
-  > {
+  async fn make_test_database() -> Arc<PgPool> {
     let config = etwin_config::find_config(std::env::current_dir().unwrap()).unwrap();
     let admin_database: PgPool = PgPoolOptions::new()
       .max_connections(5)
@@ -629,8 +1025,18 @@ mod test {
       )
       .await
       .unwrap();
-    let database = Arc::new(database);
+    Arc::new(database)
+  }
 
+  async fn make_test_api() -> TestApi<
+    Arc<VirtualClock>,
+    Arc<dyn DinoparcStore>,
+    Arc<dyn HammerfestStore>,
+    Arc<dyn PopotamoStore>,
+    Arc<dyn TokenStore>,
+    Arc<dyn TwinoidStore>,
+  > {
+    let database = make_test_database().await;
     let clock = Arc::new(VirtualClock::new(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)));
     let uuid_generator = Arc::new(Uuid4Generator);
     let database_secret = Secret::new("dev_secret".to_string());
@@ -649,6 +1055,11 @@ mod test {
       .await
       .unwrap(),
     );
+    let popotamo_store: Arc<dyn PopotamoStore> = Arc::new(
+      PgPopotamoStore::new(Arc::clone(&clock), Arc::clone(&database))
+        .await
+        .unwrap(),
+    );
     let twinoid_store: Arc<dyn TwinoidStore> = Arc::new(PgTwinoidStore::new(Arc::clone(&clock), Arc::clone(&database)));
     let token_store: Arc<dyn TokenStore> = Arc::new(
       PgTokenStore::new(Arc::clone(&clock), Arc::clone(&database), database_secret)
@@ -660,6 +1071,7 @@ mod test {
       clock,
       dinoparc_store,
       hammerfest_store,
+      popotamo_store,
       token_store,
       twinoid_store,
     }
@@ -669,4 +1081,61 @@ mod test {
     #[serial]
     || make_test_api().await
   );
+
+  #[tokio::test]
+  #[serial]
+  async fn test_rotate_keys() {
+    let database = make_test_database().await;
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)));
+    let database_secret = Secret::new("dev_secret".to_string());
+    let hammerfest_store = PgHammerfestStore::new(
+      Arc::clone(&clock),
+      Arc::clone(&database),
+      database_secret.clone(),
+      Uuid4Generator,
+    )
+    .await
+    .unwrap();
+    let alice = ShortHammerfestUser {
+      server: HammerfestServer::HammerfestFr,
+      id: "1".parse().unwrap(),
+      username: "alice".parse().unwrap(),
+    };
+    hammerfest_store.touch_short_user(&alice).await.unwrap();
+    let key: HammerfestSessionKey = "aaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap();
+
+    let old_store = PgTokenStore::new(Arc::clone(&clock), Arc::clone(&database), database_secret.clone())
+      .await
+      .unwrap();
+    old_store.touch_hammerfest(alice.as_ref(), &key).await.unwrap();
+
+    let new_key = SecretKey {
+      id: "2021-06".to_string(),
+      secret: Secret::new("new_secret".to_string()),
+    };
+    let keyring = SecretKeyring::new(new_key.clone()).with_previous(SecretKey {
+      id: DEFAULT_KEY_ID.to_string(),
+      secret: database_secret,
+    });
+    let store = PgTokenStore::with_keyring(Arc::clone(&clock), Arc::clone(&database), keyring)
+      .await
+      .unwrap();
+    let session = store.get_hammerfest(alice.as_ref()).await.unwrap();
+    assert_eq!(session.map(|session| session.key), Some(key.clone()));
+
+    let actual = store.rotate_keys().await.unwrap();
+    let expected = KeyRotation {
+      rotated: 1,
+      unknown_key: 0,
+    };
+    assert_eq!(actual, expected);
+
+    let new_store = PgTokenStore::with_keyring(Arc::clone(&clock), Arc::clone(&database), SecretKeyring::new(new_key))
+      .await
+      .unwrap();
+    let session = new_store.get_hammerfest(alice.as_ref()).await.unwrap();
+    assert_eq!(session.map(|session| session.key), Some(key));
+    // The old key can no longer decrypt the rotated session
+    assert!(old_store.get_hammerfest(alice.as_ref()).await.is_err());
+  }
 }
@@ -8,7 +8,10 @@ use etwin_core::hammerfest::{
   HammerfestServer, HammerfestStore, HammerfestUserIdRef, ShortHammerfestUser, StoredHammerfestSession,
 };
 use etwin_core::oauth::{TwinoidAccessToken, TwinoidRefreshToken};
-use etwin_core::token::{TokenStore, TouchOauthTokenOptions, TwinoidOauth};
+use etwin_core::popotamo::{
+  PopotamoServer, PopotamoStore, PopotamoUserIdRef, ShortPopotamoUser, StoredPopotamoSession,
+};
+use etwin_core::token::{SweepTokensOptions, TokenStore, TokenSweep, TouchOauthTokenOptions, TwinoidOauth};
 use etwin_core::twinoid::{ShortTwinoidUser, TwinoidStore, TwinoidUserId};
 use std::str::FromStr;
 
@@ -18,6 +21,7 @@ macro_rules! test_token_store {
     register_test!($(#[$meta])*, $api, test_touch_twinoid_oauth);
     register_test!($(#[$meta])*, $api, test_touch_twinoid_oauth_twice);
     register_test!($(#[$meta])*, $api, test_revoke_twinoid_access_token);
+    register_test!($(#[$meta])*, $api, test_get_expired_twinoid_access_token);
     register_test!($(#[$meta])*, $api, test_revoke_twinoid_refresh_token);
     register_test!($(#[$meta])*, $api, test_touch_hammerfest_session);
     register_test!($(#[$meta])*, $api, test_touch_hammerfest_session_to_update_atime_but_not_ctime);
@@ -41,6 +45,18 @@ macro_rules! test_token_store {
     register_test!($(#[$meta])*, $api, test_touch_dinoparc_session_again_with_different_user_without_revoking_first);
     register_test!($(#[$meta])*, $api, test_touch_multiple_dinoparc_sessions_with_same_user);
     register_test!($(#[$meta])*, $api, test_touch_dinoparc_session_causing_auto_revocation_of_both_other_key_and_user);
+    register_test!($(#[$meta])*, $api, test_touch_popotamo_session);
+    register_test!($(#[$meta])*, $api, test_touch_popotamo_session_to_update_atime_but_not_ctime);
+    register_test!($(#[$meta])*, $api, test_touch_popotamo_session_and_retrieve_it_without_atime_change);
+    register_test!($(#[$meta])*, $api, test_returns_none_for_session_with_an_unknown_popotamo_user);
+    register_test!($(#[$meta])*, $api, test_returns_none_for_session_with_a_known_unauthenticated_popotamo_user);
+    register_test!($(#[$meta])*, $api, test_returns_none_for_a_revoked_popotamo_session);
+    register_test!($(#[$meta])*, $api, test_touch_revoke_touch_popotamo_session_same_user);
+    register_test!($(#[$meta])*, $api, test_touch_revoke_touch_popotamo_session_different_user);
+    register_test!($(#[$meta])*, $api, test_touch_popotamo_session_again_with_different_user_without_revoking_first);
+    register_test!($(#[$meta])*, $api, test_touch_multiple_popotamo_sessions_with_same_user);
+    register_test!($(#[$meta])*, $api, test_touch_popotamo_session_causing_auto_revocation_of_both_other_key_and_user);
+    register_test!($(#[$meta])*, $api, test_sweep_expired_tokens_and_stale_sessions);
   };
 }
 
@@ -54,17 +70,19 @@ macro_rules! register_test {
   };
 }
 
-pub(crate) struct TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>
+pub(crate) struct TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>
 where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
   pub(crate) clock: TyClock,
   pub(crate) dinoparc_store: TyDinoparcStore,
   pub(crate) hammerfest_store: TyHammerfestStore,
+  pub(crate) popotamo_store: TyPopotamoStore,
   pub(crate) token_store: TyTokenStore,
   pub(crate) twinoid_store: TyTwinoidStore,
 }
@@ -73,14 +91,16 @@ pub(crate) async fn test_touch_twinoid_oauth<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -132,14 +152,16 @@ pub(crate) async fn test_touch_twinoid_oauth_twice<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -202,14 +224,16 @@ pub(crate) async fn test_revoke_twinoid_access_token<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -257,18 +281,85 @@ pub(crate) async fn test_revoke_twinoid_access_token<
   assert_eq!(actual, expected);
 }
 
+pub(crate) async fn test_get_expired_twinoid_access_token<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_store
+    .touch_short_user(&ShortTwinoidUser {
+      id: "1".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_twinoid_oauth(&TouchOauthTokenOptions {
+      access_token: "X6nhMR2zwwfLNOR6EoQ9cM03BI3i66Q6".parse().unwrap(),
+      refresh_token: "HfznfQUg1C2p87ESIp6WRq945ppG6swD".parse().unwrap(),
+      expiration_time: Utc.ymd(2021, 1, 1).and_hms(1, 0, 0),
+      twinoid_user_id: "1".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(2, 0, 0));
+  let actual = api
+    .token_store
+    .get_twinoid_access_token(&"X6nhMR2zwwfLNOR6EoQ9cM03BI3i66Q6".parse().unwrap())
+    .await
+    .unwrap();
+  let expected = Some(TwinoidAccessToken {
+    key: "X6nhMR2zwwfLNOR6EoQ9cM03BI3i66Q6".parse().unwrap(),
+    created_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    accessed_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    expires_at: Utc.ymd(2021, 1, 1).and_hms(1, 0, 0),
+    twinoid_user_id: "1".parse().unwrap(),
+  });
+  assert_eq!(actual, expected);
+
+  api
+    .token_store
+    .revoke_twinoid_access_token(&"X6nhMR2zwwfLNOR6EoQ9cM03BI3i66Q6".parse().unwrap())
+    .await
+    .unwrap();
+  let actual = api
+    .token_store
+    .get_twinoid_access_token(&"X6nhMR2zwwfLNOR6EoQ9cM03BI3i66Q6".parse().unwrap())
+    .await
+    .unwrap();
+  assert_eq!(actual, None);
+}
+
 pub(crate) async fn test_revoke_twinoid_refresh_token<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -321,14 +412,16 @@ pub(crate) async fn test_touch_hammerfest_session<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -364,14 +457,16 @@ pub(crate) async fn test_touch_hammerfest_session_to_update_atime_but_not_ctime<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -419,14 +514,16 @@ pub(crate) async fn test_touch_hammerfest_session_and_retrieve_it_without_atime_
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -464,14 +561,16 @@ pub(crate) async fn test_returns_none_for_session_with_an_unknown_hammerfest_use
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -492,14 +591,16 @@ pub(crate) async fn test_returns_none_for_session_with_a_known_unauthenticated_h
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -520,14 +621,16 @@ pub(crate) async fn test_returns_none_for_a_revoked_hammerfest_session<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -560,14 +663,16 @@ pub(crate) async fn test_touch_revoke_touch_hammerfest_session_same_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -622,14 +727,16 @@ pub(crate) async fn test_touch_revoke_touch_hammerfest_session_different_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -697,14 +804,16 @@ pub(crate) async fn test_touch_hammerfest_session_again_with_different_user_with
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -766,14 +875,16 @@ pub(crate) async fn test_touch_multiple_hammerfest_sessions_with_same_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -822,14 +933,16 @@ pub(crate) async fn test_touch_hammerfest_session_causing_auto_revocation_of_bot
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -897,14 +1010,16 @@ pub(crate) async fn test_touch_dinoparc_session<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -940,14 +1055,16 @@ pub(crate) async fn test_touch_dinoparc_session_to_update_atime_but_not_ctime<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -995,14 +1112,16 @@ pub(crate) async fn test_touch_dinoparc_session_and_retrieve_it_without_atime_ch
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1040,14 +1159,16 @@ pub(crate) async fn test_returns_none_for_session_with_an_unknown_dinoparc_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1068,14 +1189,16 @@ pub(crate) async fn test_returns_none_for_session_with_a_known_unauthenticated_d
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1096,14 +1219,16 @@ pub(crate) async fn test_returns_none_for_a_revoked_dinoparc_session<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1136,14 +1261,16 @@ pub(crate) async fn test_touch_revoke_touch_dinoparc_session_same_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1198,14 +1325,16 @@ pub(crate) async fn test_touch_revoke_touch_dinoparc_session_different_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1273,14 +1402,16 @@ pub(crate) async fn test_touch_dinoparc_session_again_with_different_user_withou
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1342,14 +1473,16 @@ pub(crate) async fn test_touch_multiple_dinoparc_sessions_with_same_user<
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1398,14 +1531,16 @@ pub(crate) async fn test_touch_dinoparc_session_causing_auto_revocation_of_both_
   TyClock,
   TyDinoparcStore,
   TyHammerfestStore,
+  TyPopotamoStore,
   TyTokenStore,
   TyTwinoidStore,
 >(
-  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyTokenStore, TyTwinoidStore>,
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
 ) where
   TyClock: ApiRef<VirtualClock>,
   TyDinoparcStore: DinoparcStore,
   TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
   TyTokenStore: TokenStore,
   TyTwinoidStore: TwinoidStore,
 {
@@ -1468,3 +1603,727 @@ pub(crate) async fn test_touch_dinoparc_session_causing_auto_revocation_of_both_
     assert_eq!(actual, expected);
   }
 }
+
+pub(crate) async fn test_touch_popotamo_session<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .token_store
+    .touch_popotamo(
+      PopotamoUserIdRef {
+        server: PopotamoServer::PopotamoCom,
+        id: "1".parse().unwrap(),
+      },
+      &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    )
+    .await
+    .unwrap();
+  let expected = StoredPopotamoSession {
+    key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    user: alice.as_ref(),
+    ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+  };
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_touch_popotamo_session_to_update_atime_but_not_ctime<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(
+      PopotamoUserIdRef {
+        server: PopotamoServer::PopotamoCom,
+        id: "1".parse().unwrap(),
+      },
+      &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    )
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .token_store
+    .touch_popotamo(
+      PopotamoUserIdRef {
+        server: PopotamoServer::PopotamoCom,
+        id: "1".parse().unwrap(),
+      },
+      &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    )
+    .await
+    .unwrap();
+  let expected = StoredPopotamoSession {
+    key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    user: alice.as_ref(),
+    ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+  };
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_touch_popotamo_session_and_retrieve_it_without_atime_change<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(
+      PopotamoUserIdRef {
+        server: PopotamoServer::PopotamoCom,
+        id: "1".parse().unwrap(),
+      },
+      &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    )
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+  let expected = Some(StoredPopotamoSession {
+    key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+    user: alice.as_ref(),
+    ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+    atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+  });
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_returns_none_for_session_with_an_unknown_popotamo_user<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let actual = api
+    .token_store
+    .get_popotamo(PopotamoUserIdRef {
+      server: PopotamoServer::PopotamoCom,
+      id: "1".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  let expected = None;
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_returns_none_for_session_with_a_known_unauthenticated_popotamo_user<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+  let expected = None;
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_returns_none_for_a_revoked_popotamo_session<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .revoke_popotamo(alice.server, &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+  let expected = None;
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_touch_revoke_touch_popotamo_session_same_user<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .revoke_popotamo(alice.server, &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .token_store
+      .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+      .await
+      .unwrap();
+    let expected = StoredPopotamoSession {
+      key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+      user: alice.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+    let expected = Some(StoredPopotamoSession {
+      key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+      user: alice.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_touch_revoke_touch_popotamo_session_different_user<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let bob = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "2".parse().unwrap(),
+    username: "bob".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&bob).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .revoke_popotamo(alice.server, &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .token_store
+      .touch_popotamo(bob.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+      .await
+      .unwrap();
+    let expected = StoredPopotamoSession {
+      key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+      user: bob.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+    let expected = None;
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(bob.as_ref()).await.unwrap();
+    let expected = Some(StoredPopotamoSession {
+      key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+      user: bob.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_touch_popotamo_session_again_with_different_user_without_revoking_first<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let bob = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "2".parse().unwrap(),
+    username: "bob".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&bob).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .token_store
+      .touch_popotamo(bob.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+      .await
+      .unwrap();
+    let expected = StoredPopotamoSession {
+      key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+      user: bob.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+    let expected = None;
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(bob.as_ref()).await.unwrap();
+    let expected = Some(StoredPopotamoSession {
+      key: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+      user: bob.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 3),
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_touch_multiple_popotamo_sessions_with_same_user<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .token_store
+      .touch_popotamo(alice.as_ref(), &"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap())
+      .await
+      .unwrap();
+    let expected = StoredPopotamoSession {
+      key: "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap(),
+      user: alice.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+    let expected = Some(StoredPopotamoSession {
+      key: "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap(),
+      user: alice.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+    });
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_touch_popotamo_session_causing_auto_revocation_of_both_other_key_and_user<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&alice).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let bob = ShortPopotamoUser {
+    server: PopotamoServer::PopotamoCom,
+    id: "2".parse().unwrap(),
+    username: "bob".parse().unwrap(),
+  };
+  api.popotamo_store.touch_short_user(&bob).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_popotamo(bob.as_ref(), &"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .token_store
+      .touch_popotamo(alice.as_ref(), &"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap())
+      .await
+      .unwrap();
+    let expected = StoredPopotamoSession {
+      key: "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap(),
+      user: alice.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+    };
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(alice.as_ref()).await.unwrap();
+    let expected = Some(StoredPopotamoSession {
+      key: "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap(),
+      user: alice.as_ref(),
+      ctime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+      atime: Utc.ymd(2021, 1, 1).and_hms(0, 0, 4),
+    });
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api.token_store.get_popotamo(bob.as_ref()).await.unwrap();
+    let expected = None;
+    assert_eq!(actual, expected);
+  }
+}
+
+pub(crate) async fn test_sweep_expired_tokens_and_stale_sessions<
+  TyClock,
+  TyDinoparcStore,
+  TyHammerfestStore,
+  TyPopotamoStore,
+  TyTokenStore,
+  TyTwinoidStore,
+>(
+  api: TestApi<TyClock, TyDinoparcStore, TyHammerfestStore, TyPopotamoStore, TyTokenStore, TyTwinoidStore>,
+) where
+  TyClock: ApiRef<VirtualClock>,
+  TyDinoparcStore: DinoparcStore,
+  TyHammerfestStore: HammerfestStore,
+  TyPopotamoStore: PopotamoStore,
+  TyTokenStore: TokenStore,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_store
+    .touch_short_user(&ShortTwinoidUser {
+      id: "1".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  let alice = ShortHammerfestUser {
+    server: HammerfestServer::HammerfestFr,
+    id: "1".parse().unwrap(),
+    username: "alice".parse().unwrap(),
+  };
+  api.hammerfest_store.touch_short_user(&alice).await.unwrap();
+  let bob = ShortHammerfestUser {
+    server: HammerfestServer::HammerfestFr,
+    id: "2".parse().unwrap(),
+    username: "bob".parse().unwrap(),
+  };
+  api.hammerfest_store.touch_short_user(&bob).await.unwrap();
+  let charlie = ShortDinoparcUser {
+    server: DinoparcServer::DinoparcCom,
+    id: "3".parse().unwrap(),
+    username: "charlie".parse().unwrap(),
+  };
+  api.dinoparc_store.touch_short_user(&charlie).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  api
+    .token_store
+    .touch_twinoid_oauth(&TouchOauthTokenOptions {
+      access_token: "X6nhMR2zwwfLNOR6EoQ9cM03BI3i66Q6".parse().unwrap(),
+      refresh_token: "HfznfQUg1C2p87ESIp6WRq945ppG6swD".parse().unwrap(),
+      expiration_time: Utc.ymd(2021, 1, 1).and_hms(1, 0, 0),
+      twinoid_user_id: "1".parse().unwrap(),
+    })
+    .await
+    .unwrap();
+  api
+    .token_store
+    .touch_hammerfest(alice.as_ref(), &"aaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap())
+    .await
+    .unwrap();
+  api
+    .token_store
+    .touch_dinoparc(charlie.as_ref(), &"cccccccccccccccccccccccccccccccc".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 2).and_hms(0, 0, 0));
+  api
+    .token_store
+    .touch_hammerfest(bob.as_ref(), &"bbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap())
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 3).and_hms(0, 0, 0));
+  let options = SweepTokensOptions {
+    max_refresh_token_age: Duration::days(30),
+    max_session_age: Duration::hours(36),
+  };
+  let actual = api.token_store.sweep(&options).await.unwrap();
+  let expected = TokenSweep {
+    twinoid_access_tokens: 1,
+    twinoid_refresh_tokens: 0,
+    dinoparc_sessions: 1,
+    hammerfest_sessions: 1,
+    popotamo_sessions: 0,
+  };
+  assert_eq!(actual, expected);
+  {
+    let actual = api
+      .token_store
+      .get_twinoid_oauth(TwinoidUserId::from_str("1").unwrap().as_ref())
+      .await
+      .unwrap();
+    let expected = TwinoidOauth {
+      access_token: None,
+      refresh_token: Some(TwinoidRefreshToken {
+        key: "HfznfQUg1C2p87ESIp6WRq945ppG6swD".parse().unwrap(),
+        created_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        accessed_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+        twinoid_user_id: "1".parse().unwrap(),
+      }),
+    };
+    assert_eq!(actual, expected);
+  }
+  assert_eq!(api.token_store.get_hammerfest(alice.as_ref()).await.unwrap(), None);
+  assert_eq!(api.token_store.get_dinoparc(charlie.as_ref()).await.unwrap(), None);
+  {
+    let actual = api.token_store.get_hammerfest(bob.as_ref()).await.unwrap();
+    let expected = Some(StoredHammerfestSession {
+      key: "bbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap(),
+      user: bob.as_ref(),
+      ctime: Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+      atime: Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+    });
+    assert_eq!(actual, expected);
+  }
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 2, 1).and_hms(0, 0, 0));
+  let actual = api.token_store.sweep(&options).await.unwrap();
+  let expected = TokenSweep {
+    twinoid_access_tokens: 0,
+    twinoid_refresh_tokens: 1,
+    dinoparc_sessions: 0,
+    hammerfest_sessions: 1,
+    popotamo_sessions: 0,
+  };
+  assert_eq!(actual, expected);
+}
@@ -3,5 +3,5 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum ScraperError {
   #[error("HTTP Error")]
-  HttpError(#[from] reqwest::Error),
+  HttpError(#[from] etwin_http::HttpError),
 }
@@ -1,82 +1,280 @@
 mod errors;
-mod url;
+pub mod reparse;
+pub(crate) mod url;
 
 use crate::http::url::TwinoidUrls;
 use async_trait::async_trait;
 use etwin_core::clock::Clock;
-use etwin_core::core::HtmlFragment;
-use etwin_core::twinoid::{api, TwinoidUserDisplayName};
-use etwin_core::twinoid::{TwinoidApiAuth, TwinoidClient};
+use etwin_core::twinoid::{api, TwinoidUserId};
+use etwin_core::twinoid::{TwinoidApiAuth, TwinoidApiError, TwinoidClient};
 use etwin_core::types::EtwinError;
-use reqwest::Client;
-use std::time::Duration;
+use etwin_http::archive::PageArchive;
+use etwin_http::event::{archive_logged, send_logged, snippet, HttpClientEvent, ParseFailureEvent, RedactedUrl};
+use etwin_http::{new_archive, new_transport, HttpOptions, HttpRequest, HttpTransport};
+use etwin_log::Logger;
+use reqwest::{StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Arc;
 
 const USER_AGENT: &str = "EtwinTwinoidClient";
-const TIMEOUT: Duration = Duration::from_millis(5000);
 
-pub struct HttpTwinoidClient<TyClock> {
-  client: Client,
-  #[allow(unused)]
+pub struct HttpTwinoidClient<TyClock, TyLogger> {
+  transport: Arc<dyn HttpTransport>,
+  archive: Option<Arc<dyn PageArchive>>,
+  urls: TwinoidUrls,
   clock: TyClock,
+  logger: TyLogger,
 }
 
-impl<TyClock> HttpTwinoidClient<TyClock>
+impl<TyClock, TyLogger> HttpTwinoidClient<TyClock, TyLogger>
 where
   TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
 {
-  pub fn new(clock: TyClock) -> Result<Self, EtwinError> {
+  pub fn new(clock: TyClock, logger: TyLogger) -> Result<Self, EtwinError> {
+    Self::with_options(clock, logger, &HttpOptions::default())
+  }
+
+  /// Creates a client using the provided transport options (mode, timeout, rate limit, retries, archive, proxy and
+  /// base URL).
+  pub fn with_options(clock: TyClock, logger: TyLogger, options: &HttpOptions) -> Result<Self, EtwinError> {
     Ok(Self {
-      client: Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(TIMEOUT)
-        .redirect(reqwest::redirect::Policy::none())
-        .build()?,
-      clock,
+      archive: new_archive(options, USER_AGENT)?,
+      urls: TwinoidUrls::from_options(options),
+      ..Self::with_transport(clock, logger, new_transport(options, USER_AGENT)?)
     })
   }
-}
 
-#[async_trait]
-impl<TyClock> TwinoidClient for HttpTwinoidClient<TyClock>
-where
-  TyClock: Clock,
-{
-  async fn get_me<Query: api::UserQuery>(
+  pub fn with_transport(clock: TyClock, logger: TyLogger, transport: Arc<dyn HttpTransport>) -> Self {
+    Self {
+      transport,
+      archive: None,
+      urls: TwinoidUrls::new(),
+      clock,
+      logger,
+    }
+  }
+
+  async fn get_graph<T: DeserializeOwned>(
     &self,
-    auth: TwinoidApiAuth,
-    query: &Query,
-  ) -> Result<Query::Output, EtwinError> {
-    let mut url = TwinoidUrls::new().me();
+    mut url: Url,
+    auth: &TwinoidApiAuth,
+    query: &api::UserQuery,
+  ) -> Result<T, TwinoidApiError> {
     {
       let mut qs = url.query_pairs_mut();
-      if let TwinoidApiAuth::Token(ref token) = &auth {
+      if let TwinoidApiAuth::Token(ref token) = auth {
         qs.append_pair("access_token", token.as_str());
       };
-      qs.append_pair("fields", query.to_fields().as_ref());
+      qs.append_pair("fields", &query.to_fields());
+    }
+
+    let res = send_logged(
+      &*self.transport,
+      &self.logger,
+      USER_AGENT,
+      HttpRequest::get(url.clone()),
+    )
+    .await
+    .map_err(TwinoidApiError::other)?;
+    if let Some(archive) = &self.archive {
+      // The access token is redacted from the archived URL
+      archive_logged(&**archive, &self.logger, USER_AGENT, self.clock.now(), &res);
+    }
+    let result = read_graph_response(res.status(), res.bytes());
+    if let Err(TwinoidApiError::MalformedBody(_)) = &result {
+      // The error already contains the full body, only log a snippet
+      let body = String::from_utf8_lossy(res.bytes());
+      self.logger.log(
+        ParseFailureEvent {
+          client: USER_AGENT,
+          url: &RedactedUrl::new(&url),
+          error: "malformed Twinoid API response",
+          snippet: snippet(&body),
+        }
+        .into(),
+      );
     }
+    result
+  }
+}
 
-    let req = self.client.get(url);
-    let res = req.send().await?;
+#[derive(Debug, Deserialize)]
+struct GraphErrorBody {
+  error: String,
+}
 
-    let body = res.bytes().await?;
+pub(crate) fn read_graph_response<T: DeserializeOwned>(status: StatusCode, body: &[u8]) -> Result<T, TwinoidApiError> {
+  if let Ok(GraphErrorBody { error }) = serde_json::from_slice::<GraphErrorBody>(body) {
+    return Err(match error.as_str() {
+      "invalid_token" => TwinoidApiError::InvalidToken,
+      "expired_token" | "token_expired" => TwinoidApiError::ExpiredToken,
+      "rate_limited" | "too_many_requests" => TwinoidApiError::RateLimited,
+      "not_found" => TwinoidApiError::NotFound,
+      _ => TwinoidApiError::MalformedBody(format!("unknown error code: {}", error)),
+    });
+  }
+  if status == StatusCode::TOO_MANY_REQUESTS {
+    return Err(TwinoidApiError::RateLimited);
+  }
+  if status == StatusCode::NOT_FOUND {
+    return Err(TwinoidApiError::NotFound);
+  }
+  if status.is_server_error() {
+    return Err(TwinoidApiError::ServerError(status.as_u16()));
+  }
+  serde_json::from_slice::<T>(body)
+    .map_err(|_| TwinoidApiError::MalformedBody(String::from_utf8_lossy(body).into_owned()))
+}
 
-    match serde_json::from_slice::<Query::Output>(&body) {
-      Ok(res) => Ok(res),
-      Err(_e) => {
-        // TODO: Handle errors such as `{"error":"invalid_token"}`
-        let body = String::from_utf8_lossy(body.as_ref());
-        Err(body.as_ref().into())
-      }
-    }
+#[async_trait]
+impl<TyClock, TyLogger> TwinoidClient for HttpTwinoidClient<TyClock, TyLogger>
+where
+  TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
+{
+  async fn get_me(&self, auth: TwinoidApiAuth, query: &api::UserQuery) -> Result<api::User, TwinoidApiError> {
+    self.get_graph(self.urls.me(), &auth, query).await
   }
 
-  async fn get_me_short(
+  async fn get_user(
     &self,
     auth: TwinoidApiAuth,
-  ) -> Result<api::User<TwinoidUserDisplayName, HtmlFragment>, EtwinError> {
-    self.get_me(auth, &api::ConstUserQuery::<true, true>).await
+    id: TwinoidUserId,
+    query: &api::UserQuery,
+  ) -> Result<Option<api::User>, TwinoidApiError> {
+    match self.get_graph(self.urls.user(id), &auth, query).await {
+      Ok(user) => Ok(Some(user)),
+      Err(TwinoidApiError::NotFound) => Ok(None),
+      Err(e) => Err(e),
+    }
   }
 }
 
 #[cfg(feature = "neon")]
-impl<TyClock> neon::prelude::Finalize for HttpTwinoidClient<TyClock> where TyClock: Clock {}
+impl<TyClock, TyLogger> neon::prelude::Finalize for HttpTwinoidClient<TyClock, TyLogger>
+where
+  TyClock: Clock,
+  TyLogger: for<'r> Logger<HttpClientEvent<'r>>,
+{
+}
+
+#[cfg(test)]
+mod test {
+  use crate::http::{read_graph_response, HttpTwinoidClient};
+  use async_trait::async_trait;
+  use etwin_core::clock::SystemClock;
+  use etwin_core::oauth::RfcOauthAccessTokenKey;
+  use etwin_core::twinoid::{api, TwinoidApiAuth, TwinoidApiError, TwinoidClient};
+  use etwin_http::event::HttpClientEvent;
+  use etwin_http::{HttpError, HttpRequest, HttpResponse, HttpTransport};
+  use etwin_log::Logger;
+  use reqwest::header::HeaderMap;
+  use reqwest::StatusCode;
+  use std::sync::{Arc, Mutex};
+
+  fn read(status: StatusCode, body: &str) -> Result<api::User, TwinoidApiError> {
+    read_graph_response(status, body.as_bytes())
+  }
+
+  #[test]
+  fn read_user() {
+    let user = read(StatusCode::OK, r#"{"id":38,"name":"Demurgos"}"#).unwrap();
+    assert_eq!(user.id, 38);
+  }
+
+  #[test]
+  fn read_invalid_token() {
+    assert!(matches!(
+      read(StatusCode::OK, r#"{"error":"invalid_token"}"#),
+      Err(TwinoidApiError::InvalidToken)
+    ));
+  }
+
+  #[test]
+  fn read_expired_token() {
+    assert!(matches!(
+      read(StatusCode::OK, r#"{"error":"expired_token"}"#),
+      Err(TwinoidApiError::ExpiredToken)
+    ));
+  }
+
+  #[test]
+  fn read_rate_limited() {
+    assert!(matches!(
+      read(StatusCode::TOO_MANY_REQUESTS, ""),
+      Err(TwinoidApiError::RateLimited)
+    ));
+  }
+
+  #[test]
+  fn read_not_found() {
+    assert!(matches!(
+      read(StatusCode::OK, r#"{"error":"not_found"}"#),
+      Err(TwinoidApiError::NotFound)
+    ));
+  }
+
+  #[test]
+  fn read_server_error() {
+    assert!(matches!(
+      read(StatusCode::BAD_GATEWAY, "<html></html>"),
+      Err(TwinoidApiError::ServerError(502))
+    ));
+  }
+
+  #[test]
+  fn read_malformed_body() {
+    assert!(matches!(
+      read(StatusCode::OK, "not json"),
+      Err(TwinoidApiError::MalformedBody(_))
+    ));
+  }
+
+  /// Logger keeping the debug representation of each event, with all its fields.
+  #[derive(Default)]
+  struct MemLogger(Mutex<Vec<String>>);
+
+  impl<'a> Logger<HttpClientEvent<'a>> for MemLogger {
+    fn log(&self, ev: HttpClientEvent<'a>) {
+      self.0.lock().unwrap().push(format!("{:?}", ev));
+    }
+  }
+
+  /// Transport answering every request with a body that is not valid JSON.
+  struct MalformedTransport;
+
+  #[async_trait]
+  impl HttpTransport for MalformedTransport {
+    async fn send(&self, req: HttpRequest) -> Result<HttpResponse, HttpError> {
+      Ok(HttpResponse {
+        url: req.url,
+        status: StatusCode::OK,
+        headers: HeaderMap::new(),
+        body: b"not json".to_vec(),
+      })
+    }
+  }
+
+  #[tokio::test]
+  async fn access_token_never_logged() {
+    let logger = Arc::new(MemLogger::default());
+    let client = HttpTwinoidClient::with_transport(SystemClock, Arc::clone(&logger), Arc::new(MalformedTransport));
+    let token: RfcOauthAccessTokenKey = "secret_token".parse().unwrap();
+    let res = client
+      .get_me(TwinoidApiAuth::Token(token), &api::UserQuery::new().name())
+      .await;
+    assert!(matches!(res, Err(TwinoidApiError::MalformedBody(_))));
+    let logged = logger.0.lock().unwrap();
+    // Request start, response status and parse failure
+    assert_eq!(logged.len(), 3);
+    for ev in logged.iter() {
+      assert!(
+        !ev.contains("secret_token"),
+        "access token leaked to the logger: {}",
+        ev
+      );
+    }
+  }
+}
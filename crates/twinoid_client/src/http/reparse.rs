@@ -0,0 +1,76 @@
+use super::read_graph_response;
+use super::url::TwinoidUrls;
+use etwin_core::twinoid::{api, TwinoidApiError};
+use etwin_core::types::EtwinError;
+use etwin_http::{path_below, HttpOptions};
+use reqwest::{StatusCode, Url};
+
+/// Archived Graph API response, parsed with the reader matching its URL.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TwinoidArchivedPage {
+  /// User returned by `/graph/me` or `/graph/user/{id}`, only the fields requested by the client are set.
+  User(api::User),
+}
+
+/// Parses an archived response fetched from `url`, on the official host or on the base URL configured in `options`.
+///
+/// Returns `None` if `url` is not a Graph API user endpoint, or if the response is an API error.
+pub fn reparse_page(
+  options: &HttpOptions,
+  url: &Url,
+  status: StatusCode,
+  body: &[u8],
+) -> Result<Option<TwinoidArchivedPage>, EtwinError> {
+  let segments = match path_below(&TwinoidUrls::new().root(), url)
+    .or_else(|| path_below(&TwinoidUrls::from_options(options).root(), url))
+  {
+    Some(segments) => segments,
+    None => return Ok(None),
+  };
+  if !matches!(segments.as_slice(), ["graph", "me"] | ["graph", "user", _]) {
+    return Ok(None);
+  }
+  match read_graph_response::<api::User>(status, body) {
+    Ok(user) => Ok(Some(TwinoidArchivedPage::User(user))),
+    Err(TwinoidApiError::MalformedBody(e)) => Err(e.into()),
+    Err(_) => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{reparse_page, TwinoidArchivedPage};
+  use etwin_http::HttpOptions;
+  use reqwest::{StatusCode, Url};
+
+  #[test]
+  fn reparse_user() {
+    let url = Url::parse("https://twinoid.com/graph/user/38?access_token=REDACTED&fields=id,name").unwrap();
+    let page = reparse_page(
+      &HttpOptions::default(),
+      &url,
+      StatusCode::OK,
+      br#"{"id":38,"name":"alice"}"#,
+    )
+    .unwrap();
+    let user = match page {
+      Some(TwinoidArchivedPage::User(user)) => user,
+      None => panic!("expected a user"),
+    };
+    assert_eq!(user.id, 38);
+    assert_eq!(user.name.unwrap().as_str(), "alice");
+  }
+
+  #[test]
+  fn ignore_api_error() {
+    let url = Url::parse("https://twinoid.com/graph/me?access_token=REDACTED").unwrap();
+    let page = reparse_page(
+      &HttpOptions::default(),
+      &url,
+      StatusCode::OK,
+      br#"{"error":"invalid_token"}"#,
+    )
+    .unwrap();
+    assert_eq!(page, None);
+  }
+}
@@ -1,23 +1,60 @@
+use etwin_core::twinoid::TwinoidUserId;
+use etwin_http::HttpOptions;
 use reqwest::Url;
 
+/// Server name used to configure the Twinoid base URL.
+const SERVER: &str = "twinoid.com";
+
 pub struct TwinoidUrls {
   root: Url,
 }
 
 impl TwinoidUrls {
+  /// URLs of the official Twinoid host.
   pub fn new() -> Self {
-    Self {
-      root: Url::parse("https://twinoid.com/graph").expect("failed to parse twinoid root URL"),
+    Self::with_root(Url::parse("https://twinoid.com/").expect("failed to parse twinoid root URL"))
+  }
+
+  /// URLs of the base URL configured in `options`, or of the official host.
+  pub fn from_options(options: &HttpOptions) -> Self {
+    match options.base_url(SERVER) {
+      Some(root) => Self::with_root(root.clone()),
+      None => Self::new(),
     }
   }
 
+  /// URLs relative to `root`, for mirrors or local test servers.
+  pub fn with_root(root: Url) -> Self {
+    Self { root }
+  }
+
   fn make_url(&self, segments: &[&str]) -> Url {
     let mut url = self.root.clone();
-    url.path_segments_mut().expect("invalid root url").extend(segments);
     url
+      .path_segments_mut()
+      .expect("invalid root url")
+      .pop_if_empty()
+      .extend(segments);
+    url
+  }
+
+  pub fn root(&self) -> Url {
+    self.make_url(&[])
   }
 
   pub fn me(&self) -> Url {
-    self.make_url(&["me"])
+    self.make_url(&["graph", "me"])
+  }
+
+  pub fn user(&self, id: TwinoidUserId) -> Url {
+    self.make_url(&["graph", "user", &id.to_string()])
+  }
+
+  pub fn oauth_authorization(&self) -> Url {
+    self.make_url(&["oauth", "auth"])
+  }
+
+  pub fn oauth_token(&self) -> Url {
+    self.make_url(&["oauth", "token"])
   }
 }
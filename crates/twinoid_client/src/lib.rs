@@ -2,3 +2,5 @@
 pub mod http;
 #[cfg(feature = "mem")]
 pub mod mem;
+#[cfg(feature = "http")]
+pub mod oauth;
@@ -1,24 +1,337 @@
 use async_trait::async_trait;
-use etwin_core::core::HtmlFragment;
+use etwin_core::api::ApiRef;
+use etwin_core::clock::Clock;
+use etwin_core::oauth::{RfcOauthAccessTokenKey, RfcOauthRefreshTokenKey};
+use etwin_core::token::{TokenStore, TouchOauthTokenOptions};
 use etwin_core::twinoid::api::{User, UserQuery};
-use etwin_core::twinoid::{TwinoidApiAuth, TwinoidClient, TwinoidUserDisplayName};
+use etwin_core::twinoid::{
+  TwinoidApiAuth, TwinoidApiError, TwinoidClient, TwinoidOauthClient, TwinoidOauthGrant, TwinoidStore,
+  TwinoidUserDisplayName, TwinoidUserId, TwinoidUserIdRef,
+};
 use etwin_core::types::EtwinError;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::RwLock;
+use url::Url;
 
-pub struct MemTwinoidClient;
+/// Lifetime of the access tokens issued by `MemTwinoidOauthClient`
+const ACCESS_TOKEN_DURATION_SECONDS: i64 = 3600;
+
+#[derive(Clone, Debug)]
+struct MemUser {
+  id: TwinoidUserId,
+  display_name: TwinoidUserDisplayName,
+}
+
+#[derive(Clone, Debug, Default)]
+struct MemState {
+  users: HashMap<TwinoidUserId, MemUser>,
+  access_tokens: HashMap<RfcOauthAccessTokenKey, TwinoidUserId>,
+  expired_access_tokens: HashSet<RfcOauthAccessTokenKey>,
+}
+
+impl MemState {
+  /// The mem client ignores the field selection and always returns the id and name.
+  fn to_api_user(&self, id: TwinoidUserId, _query: &UserQuery) -> Option<User> {
+    let user = self.users.get(&id)?;
+    Some(User {
+      id: user.id.get(),
+      name: Some(user.display_name.clone()),
+      title: None,
+      picture: None,
+      locale: None,
+      gender: None,
+      city: None,
+      contacts: None,
+      sites: None,
+    })
+  }
+}
+
+#[derive(Default)]
+pub struct MemTwinoidClient {
+  state: RwLock<MemState>,
+}
+
+impl MemTwinoidClient {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn create_user(&self, id: TwinoidUserId, display_name: TwinoidUserDisplayName) {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for twinoid client state");
+    match state.users.entry(id) {
+      Entry::Occupied(_) => panic!("TwinoidUserId conflict"),
+      Entry::Vacant(e) => e.insert(MemUser { id, display_name }),
+    };
+  }
+
+  /// Issue a new access token for an existing user, as if they completed the OAuth flow.
+  pub fn create_access_token(&self, user: TwinoidUserId) -> RfcOauthAccessTokenKey {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for twinoid client state");
+    assert!(state.users.contains_key(&user), "TwinoidUserId not found");
+    let key = make_access_token_key();
+    state.access_tokens.insert(key.clone(), user);
+    key
+  }
+
+  pub fn revoke_access_token(&self, key: &RfcOauthAccessTokenKey) {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for twinoid client state");
+    state.access_tokens.remove(key);
+  }
+
+  /// Mark an access token as expired: Twinoid rejects it with `ExpiredToken` instead of `InvalidToken`.
+  pub fn expire_access_token(&self, key: &RfcOauthAccessTokenKey) {
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for twinoid client state");
+    if state.access_tokens.remove(key).is_some() {
+      state.expired_access_tokens.insert(key.clone());
+    }
+  }
+}
 
 #[async_trait]
 impl TwinoidClient for MemTwinoidClient {
-  async fn get_me<Query: UserQuery>(&self, _auth: TwinoidApiAuth, _query: &Query) -> Result<Query::Output, EtwinError>
-  where
-    Self: Sized,
-  {
-    todo!()
+  async fn get_me(&self, auth: TwinoidApiAuth, query: &UserQuery) -> Result<User, TwinoidApiError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for twinoid client state");
+    let user_id = match auth {
+      TwinoidApiAuth::Guest => return Err(TwinoidApiError::InvalidToken),
+      TwinoidApiAuth::Token(key) if state.expired_access_tokens.contains(&key) => {
+        return Err(TwinoidApiError::ExpiredToken)
+      }
+      TwinoidApiAuth::Token(key) => *state.access_tokens.get(&key).ok_or(TwinoidApiError::InvalidToken)?,
+    };
+    state.to_api_user(user_id, query).ok_or(TwinoidApiError::NotFound)
   }
 
-  async fn get_me_short(
+  async fn get_user(
     &self,
     _auth: TwinoidApiAuth,
-  ) -> Result<User<TwinoidUserDisplayName, HtmlFragment>, EtwinError> {
-    todo!()
+    id: TwinoidUserId,
+    query: &UserQuery,
+  ) -> Result<Option<User>, TwinoidApiError> {
+    let state = self
+      .state
+      .read()
+      .expect("failed to acquire read lock for twinoid client state");
+    Ok(state.to_api_user(id, query))
+  }
+}
+
+#[derive(Default)]
+struct MemOauthState {
+  authorization_codes: HashMap<String, TwinoidUserId>,
+  refresh_tokens: HashMap<RfcOauthRefreshTokenKey, TwinoidUserId>,
+}
+
+/// OAuth client issuing the access tokens of a `MemTwinoidClient`
+pub struct MemTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore> {
+  clock: TyClock,
+  token_store: TyTokenStore,
+  twinoid_client: TyTwinoidClient,
+  twinoid_store: TyTwinoidStore,
+  state: RwLock<MemOauthState>,
+}
+
+impl<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+  MemTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+where
+  TyClock: Clock,
+  TyTokenStore: TokenStore,
+  TyTwinoidClient: ApiRef<MemTwinoidClient>,
+  TyTwinoidStore: TwinoidStore,
+{
+  pub fn new(
+    clock: TyClock,
+    token_store: TyTokenStore,
+    twinoid_client: TyTwinoidClient,
+    twinoid_store: TyTwinoidStore,
+  ) -> Self {
+    Self {
+      clock,
+      token_store,
+      twinoid_client,
+      twinoid_store,
+      state: RwLock::new(MemOauthState::default()),
+    }
+  }
+
+  /// Issue an authorization code for an existing user, as if they granted access to Eternal-Twin.
+  pub fn create_authorization_code(&self, user: TwinoidUserId) -> String {
+    let code = make_key();
+    let mut state = self
+      .state
+      .write()
+      .expect("failed to acquire write lock for twinoid oauth client state");
+    state.authorization_codes.insert(code.clone(), user);
+    code
+  }
+
+  /// Issue a new access token for `user` and store it with `refresh_token`.
+  async fn issue(
+    &self,
+    user: TwinoidUserId,
+    refresh_token: RfcOauthRefreshTokenKey,
+  ) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    let access_token = self.twinoid_client.as_ref().create_access_token(user);
+    self.store_token(user, access_token.clone(), refresh_token).await?;
+    Ok(access_token)
+  }
+
+  async fn store_token(
+    &self,
+    user: TwinoidUserId,
+    access_token: RfcOauthAccessTokenKey,
+    refresh_token: RfcOauthRefreshTokenKey,
+  ) -> Result<(), EtwinError> {
+    self
+      .token_store
+      .touch_twinoid_oauth(&TouchOauthTokenOptions {
+        access_token,
+        refresh_token,
+        expiration_time: self.clock.now() + chrono::Duration::seconds(ACCESS_TOKEN_DURATION_SECONDS),
+        twinoid_user_id: user,
+      })
+      .await
+  }
+}
+
+#[async_trait]
+impl<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore> TwinoidOauthClient
+  for MemTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+where
+  TyClock: Clock,
+  TyTokenStore: TokenStore,
+  TyTwinoidClient: ApiRef<MemTwinoidClient>,
+  TyTwinoidStore: TwinoidStore,
+{
+  fn authorization_uri(&self, scope: &str, state: &str) -> Url {
+    let mut url = Url::parse("https://twinoid.com/oauth/auth").unwrap();
+    url
+      .query_pairs_mut()
+      .append_pair("response_type", "code")
+      .append_pair("scope", scope)
+      .append_pair("state", state);
+    url
+  }
+
+  async fn exchange_code(&self, code: &str) -> Result<TwinoidOauthGrant, EtwinError> {
+    let (user, refresh_token) = {
+      let mut state = self
+        .state
+        .write()
+        .expect("failed to acquire write lock for twinoid oauth client state");
+      let user = state
+        .authorization_codes
+        .remove(code)
+        .ok_or("unknown Twinoid authorization code")?;
+      let refresh_token = RfcOauthRefreshTokenKey::from_str(&make_key()).expect("invalid refresh token key");
+      state.refresh_tokens.insert(refresh_token.clone(), user);
+      (user, refresh_token)
+    };
+    let access_token = self.twinoid_client.as_ref().create_access_token(user);
+    let user = self
+      .twinoid_client
+      .as_ref()
+      .get_me_short(TwinoidApiAuth::Token(access_token.clone()))
+      .await?;
+    self.twinoid_store.touch_short_user(&user).await?;
+    self.store_token(user.id, access_token.clone(), refresh_token).await?;
+    Ok(TwinoidOauthGrant { user, access_token })
+  }
+
+  async fn refresh(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    let stored = self.token_store.get_twinoid_oauth(user).await?;
+    let refresh_token = stored
+      .refresh_token
+      .ok_or("no refresh token stored for Twinoid user")?
+      .key;
+    let is_valid = {
+      let state = self
+        .state
+        .read()
+        .expect("failed to acquire read lock for twinoid oauth client state");
+      state.refresh_tokens.get(&refresh_token) == Some(&user.id)
+    };
+    if !is_valid {
+      return Err("invalid Twinoid refresh token".into());
+    }
+    self.issue(user.id, refresh_token).await
+  }
+
+  async fn get_access_token(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    let stored = self.token_store.get_twinoid_oauth(user).await?;
+    match stored.access_token {
+      Some(token) => Ok(token.key),
+      None => self.refresh(user).await,
+    }
   }
+
+  async fn get_me(&self, user: TwinoidUserIdRef, query: &UserQuery) -> Result<User, EtwinError> {
+    let token = self.get_access_token(user).await?;
+    match self
+      .twinoid_client
+      .as_ref()
+      .get_me(TwinoidApiAuth::Token(token.clone()), query)
+      .await
+    {
+      Err(TwinoidApiError::InvalidToken) | Err(TwinoidApiError::ExpiredToken) => {
+        self.token_store.revoke_twinoid_access_token(&token).await?;
+        let token = self.refresh(user).await?;
+        Ok(
+          self
+            .twinoid_client
+            .as_ref()
+            .get_me(TwinoidApiAuth::Token(token), query)
+            .await?,
+        )
+      }
+      res => Ok(res?),
+    }
+  }
+}
+
+fn make_access_token_key() -> RfcOauthAccessTokenKey {
+  RfcOauthAccessTokenKey::from_str(&make_key()).expect("invalid access token key")
+}
+
+fn make_key() -> String {
+  use rand::seq::SliceRandom;
+
+  const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+  let mut rng = rand::thread_rng();
+
+  std::iter::from_fn(|| CHARS.choose(&mut rng).copied())
+    .map(char::from)
+    .take(32)
+    .collect()
+}
+
+#[cfg(feature = "neon")]
+impl neon::prelude::Finalize for MemTwinoidClient {}
+
+#[cfg(feature = "neon")]
+impl<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore> neon::prelude::Finalize
+  for MemTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+where
+  TyClock: Clock,
+  TyTokenStore: TokenStore,
+  TyTwinoidClient: ApiRef<MemTwinoidClient>,
+  TyTwinoidStore: TwinoidStore,
+{
 }
@@ -0,0 +1,419 @@
+use crate::http::url::TwinoidUrls;
+use async_trait::async_trait;
+use etwin_core::clock::Clock;
+use etwin_core::oauth::{RfcOauthAccessTokenKey, RfcOauthRefreshTokenKey, TwinoidRefreshToken};
+use etwin_core::token::{TokenStore, TouchOauthTokenOptions};
+use etwin_core::twinoid::{
+  api, ShortTwinoidUser, TwinoidApiAuth, TwinoidApiError, TwinoidClient, TwinoidOauthClient, TwinoidOauthGrant,
+  TwinoidStore, TwinoidUserId, TwinoidUserIdRef,
+};
+use etwin_core::types::EtwinError;
+use etwin_http::{new_transport, HttpOptions, HttpRequest, HttpTransport};
+use reqwest::Url;
+use serde::Deserialize;
+use std::sync::Arc;
+use thiserror::Error;
+
+const USER_AGENT: &str = "EtwinTwinoidClient";
+
+/// Access tokens expiring within this delay are refreshed before use.
+const EXPIRATION_MARGIN_SECONDS: i64 = 60;
+
+#[derive(Debug, Error)]
+pub enum TwinoidOauthError {
+  #[error("Twinoid token endpoint error: {0}")]
+  TokenEndpoint(String),
+  #[error("Unexpected Twinoid token endpoint response: {0}")]
+  UnexpectedTokenResponse(String),
+  #[error("Missing refresh token in Twinoid authorization code grant response")]
+  MissingRefreshToken,
+  #[error("No refresh token stored for Twinoid user: {0}")]
+  NoRefreshToken(TwinoidUserId),
+  #[error("Missing Twinoid user name")]
+  MissingUserName,
+}
+
+/// Credentials of the Eternal-Twin application registered on Twinoid
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TwinoidOauthClientConfig {
+  pub client_id: String,
+  pub client_secret: String,
+  pub callback_uri: Url,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+  access_token: RfcOauthAccessTokenKey,
+  expires_in: i64,
+  refresh_token: Option<RfcOauthRefreshTokenKey>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+  error: String,
+}
+
+pub struct HttpTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore> {
+  transport: Arc<dyn HttpTransport>,
+  urls: TwinoidUrls,
+  clock: TyClock,
+  config: TwinoidOauthClientConfig,
+  token_store: TyTokenStore,
+  twinoid_client: TyTwinoidClient,
+  twinoid_store: TyTwinoidStore,
+}
+
+impl<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+  HttpTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+where
+  TyClock: Clock,
+  TyTokenStore: TokenStore,
+  TyTwinoidClient: TwinoidClient,
+  TyTwinoidStore: TwinoidStore,
+{
+  pub fn new(
+    clock: TyClock,
+    config: TwinoidOauthClientConfig,
+    token_store: TyTokenStore,
+    twinoid_client: TyTwinoidClient,
+    twinoid_store: TyTwinoidStore,
+  ) -> Result<Self, EtwinError> {
+    Self::with_options(
+      clock,
+      config,
+      token_store,
+      twinoid_client,
+      twinoid_store,
+      &HttpOptions::default(),
+    )
+  }
+
+  /// Creates a client using the provided transport options (mode, timeout, rate limit, retries, proxy and base URL).
+  pub fn with_options(
+    clock: TyClock,
+    config: TwinoidOauthClientConfig,
+    token_store: TyTokenStore,
+    twinoid_client: TyTwinoidClient,
+    twinoid_store: TyTwinoidStore,
+    options: &HttpOptions,
+  ) -> Result<Self, EtwinError> {
+    Ok(Self {
+      urls: TwinoidUrls::from_options(options),
+      ..Self::with_transport(
+        clock,
+        config,
+        token_store,
+        twinoid_client,
+        twinoid_store,
+        new_transport(options, USER_AGENT)?,
+      )
+    })
+  }
+
+  pub fn with_transport(
+    clock: TyClock,
+    config: TwinoidOauthClientConfig,
+    token_store: TyTokenStore,
+    twinoid_client: TyTwinoidClient,
+    twinoid_store: TyTwinoidStore,
+    transport: Arc<dyn HttpTransport>,
+  ) -> Self {
+    Self {
+      transport,
+      urls: TwinoidUrls::new(),
+      clock,
+      config,
+      token_store,
+      twinoid_client,
+      twinoid_store,
+    }
+  }
+
+  /// Use `refresh_token`, read from the token store, to get a new access token for `user`.
+  async fn refresh_with(
+    &self,
+    user: TwinoidUserIdRef,
+    refresh_token: Option<TwinoidRefreshToken>,
+  ) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    let refresh_token = refresh_token.ok_or(TwinoidOauthError::NoRefreshToken(user.id))?.key;
+    let res = self
+      .request_token(&[
+        ("grant_type", "refresh_token"),
+        ("client_id", &self.config.client_id),
+        ("client_secret", &self.config.client_secret),
+        ("refresh_token", refresh_token.as_str()),
+      ])
+      .await?;
+    self
+      .token_store
+      .touch_twinoid_oauth(&TouchOauthTokenOptions {
+        access_token: res.access_token.clone(),
+        refresh_token: res.refresh_token.unwrap_or(refresh_token),
+        expiration_time: self.clock.now() + chrono::Duration::seconds(res.expires_in),
+        twinoid_user_id: user.id,
+      })
+      .await?;
+    Ok(res.access_token)
+  }
+
+  async fn request_token(&self, form: &[(&str, &str)]) -> Result<TokenResponse, EtwinError> {
+    let req = HttpRequest::post(self.urls.oauth_token()).form(form)?;
+    let res = self.transport.send(req).await?;
+    parse_token_response(res.bytes())
+  }
+}
+
+#[async_trait]
+impl<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore> TwinoidOauthClient
+  for HttpTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+where
+  TyClock: Clock,
+  TyTokenStore: TokenStore,
+  TyTwinoidClient: TwinoidClient,
+  TyTwinoidStore: TwinoidStore,
+{
+  /// Offline access is always requested so the grant includes a refresh token.
+  fn authorization_uri(&self, scope: &str, state: &str) -> Url {
+    let mut url = self.urls.oauth_authorization();
+    url
+      .query_pairs_mut()
+      .append_pair("response_type", "code")
+      .append_pair("client_id", &self.config.client_id)
+      .append_pair("redirect_uri", self.config.callback_uri.as_str())
+      .append_pair("scope", scope)
+      .append_pair("state", state)
+      .append_pair("access_type", "offline");
+    url
+  }
+
+  async fn exchange_code(&self, code: &str) -> Result<TwinoidOauthGrant, EtwinError> {
+    let res = self
+      .request_token(&[
+        ("grant_type", "authorization_code"),
+        ("client_id", &self.config.client_id),
+        ("client_secret", &self.config.client_secret),
+        ("redirect_uri", self.config.callback_uri.as_str()),
+        ("code", code),
+      ])
+      .await?;
+    let refresh_token = res.refresh_token.ok_or(TwinoidOauthError::MissingRefreshToken)?;
+
+    let me = self
+      .twinoid_client
+      .get_me(
+        TwinoidApiAuth::Token(res.access_token.clone()),
+        &api::UserQuery::new().name(),
+      )
+      .await?;
+    let user = ShortTwinoidUser {
+      id: TwinoidUserId::new(me.id)?,
+      display_name: me.name.ok_or(TwinoidOauthError::MissingUserName)?,
+    };
+    self.twinoid_store.touch_short_user(&user).await?;
+    self
+      .token_store
+      .touch_twinoid_oauth(&TouchOauthTokenOptions {
+        access_token: res.access_token.clone(),
+        refresh_token,
+        expiration_time: self.clock.now() + chrono::Duration::seconds(res.expires_in),
+        twinoid_user_id: user.id,
+      })
+      .await?;
+
+    Ok(TwinoidOauthGrant {
+      user,
+      access_token: res.access_token,
+    })
+  }
+
+  async fn refresh(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    let stored = self.token_store.get_twinoid_oauth(user).await?;
+    self.refresh_with(user, stored.refresh_token).await
+  }
+
+  async fn get_access_token(&self, user: TwinoidUserIdRef) -> Result<RfcOauthAccessTokenKey, EtwinError> {
+    let stored = self.token_store.get_twinoid_oauth(user).await?;
+    let deadline = self.clock.now() + chrono::Duration::seconds(EXPIRATION_MARGIN_SECONDS);
+    match stored.access_token {
+      Some(token) if token.expires_at > deadline => Ok(token.key),
+      _ => self.refresh_with(user, stored.refresh_token).await,
+    }
+  }
+
+  async fn get_me(&self, user: TwinoidUserIdRef, query: &api::UserQuery) -> Result<api::User, EtwinError> {
+    let token = self.get_access_token(user).await?;
+    match self
+      .twinoid_client
+      .get_me(TwinoidApiAuth::Token(token.clone()), query)
+      .await
+    {
+      Err(TwinoidApiError::InvalidToken) | Err(TwinoidApiError::ExpiredToken) => {
+        self.token_store.revoke_twinoid_access_token(&token).await?;
+        let token = self.refresh(user).await?;
+        Ok(self.twinoid_client.get_me(TwinoidApiAuth::Token(token), query).await?)
+      }
+      res => Ok(res?),
+    }
+  }
+}
+
+fn parse_token_response(body: &[u8]) -> Result<TokenResponse, EtwinError> {
+  if let Ok(res) = serde_json::from_slice::<TokenResponse>(body) {
+    return Ok(res);
+  }
+  match serde_json::from_slice::<TokenErrorResponse>(body) {
+    Ok(res) => Err(TwinoidOauthError::TokenEndpoint(res.error).into()),
+    Err(_) => Err(TwinoidOauthError::UnexpectedTokenResponse(String::from_utf8_lossy(body).into_owned()).into()),
+  }
+}
+
+#[cfg(feature = "neon")]
+impl<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore> neon::prelude::Finalize
+  for HttpTwinoidOauthClient<TyClock, TyTokenStore, TyTwinoidClient, TyTwinoidStore>
+where
+  TyClock: Clock,
+  TyTokenStore: TokenStore,
+  TyTwinoidClient: TwinoidClient,
+  TyTwinoidStore: TwinoidStore,
+{
+}
+
+#[cfg(test)]
+mod test {
+  use crate::http::HttpTwinoidClient;
+  use crate::oauth::{parse_token_response, HttpTwinoidOauthClient, TwinoidOauthClientConfig, TwinoidOauthError};
+  use chrono::{Duration, TimeZone, Utc};
+  use etwin_core::clock::VirtualClock;
+  use etwin_core::token::TokenStore;
+  use etwin_core::twinoid::{GetTwinoidUserOptions, TwinoidOauthClient, TwinoidStore, TwinoidUserId};
+  use etwin_http::replay::ReplayTransport;
+  use etwin_log::NoopLogger;
+  use etwin_token_store::mem::MemTokenStore;
+  use etwin_twinoid_store::mem::MemTwinoidStore;
+  use reqwest::Url;
+  use std::path::Path;
+  use std::sync::Arc;
+
+  type TestClient = HttpTwinoidOauthClient<
+    Arc<VirtualClock>,
+    Arc<MemTokenStore<Arc<VirtualClock>>>,
+    HttpTwinoidClient<Arc<VirtualClock>, NoopLogger>,
+    Arc<MemTwinoidStore<Arc<VirtualClock>>>,
+  >;
+
+  struct TestApi {
+    clock: Arc<VirtualClock>,
+    token_store: Arc<MemTokenStore<Arc<VirtualClock>>>,
+    twinoid_store: Arc<MemTwinoidStore<Arc<VirtualClock>>>,
+    client: TestClient,
+  }
+
+  /// Creates a client replaying the exchanges of `test-resources/http-replay/twinoid/{name}`.
+  fn replay(name: &str) -> TestApi {
+    let dir = Path::new("../../test-resources/http-replay/twinoid").join(name);
+    let transport = Arc::new(ReplayTransport::open(&dir).unwrap());
+    let clock = Arc::new(VirtualClock::new(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)));
+    let token_store = Arc::new(MemTokenStore::new(Arc::clone(&clock)));
+    let twinoid_store = Arc::new(MemTwinoidStore::new(Arc::clone(&clock)));
+    let twinoid_client = HttpTwinoidClient::with_transport(Arc::clone(&clock), NoopLogger, transport.clone());
+    let client = HttpTwinoidOauthClient::with_transport(
+      Arc::clone(&clock),
+      TwinoidOauthClientConfig {
+        client_id: String::from("etwin"),
+        client_secret: String::from("dev_secret"),
+        callback_uri: Url::parse("http://localhost:50320/oauth/callback").unwrap(),
+      },
+      Arc::clone(&token_store),
+      twinoid_client,
+      Arc::clone(&twinoid_store),
+      transport,
+    );
+    TestApi {
+      clock,
+      token_store,
+      twinoid_store,
+      client,
+    }
+  }
+
+  fn alice() -> TwinoidUserId {
+    TwinoidUserId::new(38).unwrap()
+  }
+
+  #[tokio::test]
+  async fn exchange_code() {
+    let api = replay("oauth_exchange_code");
+    let grant = api.client.exchange_code("abc").await.unwrap();
+    assert_eq!(grant.user.id, alice());
+    assert_eq!(grant.user.display_name.as_str(), "alice");
+    assert_eq!(grant.access_token.as_str(), "access_1");
+
+    let stored = api.token_store.get_twinoid_oauth(alice().as_ref()).await.unwrap();
+    assert_eq!(stored.access_token.unwrap().key.as_str(), "access_1");
+    assert_eq!(stored.refresh_token.unwrap().key.as_str(), "refresh_1");
+    let user = api
+      .twinoid_store
+      .get_short_user(&GetTwinoidUserOptions {
+        id: alice(),
+        time: None,
+      })
+      .await
+      .unwrap();
+    assert_eq!(user, Some(grant.user));
+  }
+
+  #[tokio::test]
+  async fn refresh_expired_access_token() {
+    let api = replay("oauth_refresh_on_expiry");
+    api.client.exchange_code("abc").await.unwrap();
+    let token = api.client.get_access_token(alice().as_ref()).await.unwrap();
+    assert_eq!(token.as_str(), "access_1");
+
+    api.clock.advance_by(Duration::hours(1));
+    let token = api.client.get_access_token(alice().as_ref()).await.unwrap();
+    assert_eq!(token.as_str(), "access_2");
+    // The refreshed token is reused: the replay would fail on a second refresh request
+    let token = api.client.get_access_token(alice().as_ref()).await.unwrap();
+    assert_eq!(token.as_str(), "access_2");
+    // Twinoid did not issue a new refresh token, the previous one is kept
+    let stored = api.token_store.get_twinoid_oauth(alice().as_ref()).await.unwrap();
+    assert_eq!(stored.refresh_token.unwrap().key.as_str(), "refresh_1");
+  }
+
+  #[tokio::test]
+  async fn reject_grant_without_refresh_token() {
+    let api = replay("oauth_missing_refresh_token");
+    let err = api.client.exchange_code("abc").await.unwrap_err();
+    assert!(matches!(
+      err.downcast_ref::<TwinoidOauthError>(),
+      Some(TwinoidOauthError::MissingRefreshToken)
+    ));
+
+    // Nothing was stored, so there is no token to refresh
+    let err = api.client.get_access_token(alice().as_ref()).await.unwrap_err();
+    assert!(matches!(
+      err.downcast_ref::<TwinoidOauthError>(),
+      Some(TwinoidOauthError::NoRefreshToken(_))
+    ));
+  }
+
+  #[test]
+  fn parse_authorization_code_grant() {
+    let res = parse_token_response(br#"{"access_token":"abc","expires_in":3600,"refresh_token":"def"}"#).unwrap();
+    assert_eq!(res.access_token.as_str(), "abc");
+    assert_eq!(res.expires_in, 3600);
+    assert_eq!(res.refresh_token.unwrap().as_str(), "def");
+  }
+
+  #[test]
+  fn parse_refresh_grant_without_new_refresh_token() {
+    let res = parse_token_response(br#"{"access_token":"abc","expires_in":3600}"#).unwrap();
+    assert!(res.refresh_token.is_none());
+  }
+
+  #[test]
+  fn parse_token_error() {
+    let err = parse_token_response(br#"{"error":"invalid_grant"}"#).unwrap_err();
+    assert_eq!(err.to_string(), "Twinoid token endpoint error: invalid_grant");
+  }
+}
@@ -1,12 +1,33 @@
 use async_trait::async_trait;
 use etwin_core::clock::Clock;
-use etwin_core::twinoid::{ArchivedTwinoidUser, GetTwinoidUserOptions, ShortTwinoidUser, TwinoidStore, TwinoidUserId};
+use etwin_core::core::Instant;
+use etwin_core::temporal::{LatestTemporal, SnapshotLog};
+use etwin_core::twinoid::{
+  ArchivedTwinoidAchievement, ArchivedTwinoidStat, ArchivedTwinoidUser, GetTwinoidUserOptions, ShortTwinoidUser,
+  TwinoidAchievementKey, TwinoidContact, TwinoidContactList, TwinoidProfile, TwinoidProfileDetails, TwinoidSiteId,
+  TwinoidStatKey, TwinoidStore, TwinoidUserDisplayName, TwinoidUserId,
+};
 use etwin_core::types::EtwinError;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::RwLock;
 
+struct MemUser {
+  archived_at: Instant,
+  display_name: TwinoidUserDisplayName,
+  details: SnapshotLog<TwinoidProfileDetails>,
+  stats: BTreeMap<(TwinoidSiteId, TwinoidStatKey), SnapshotLog<i64>>,
+  achievements: BTreeMap<(TwinoidSiteId, TwinoidAchievementKey), SnapshotLog<Option<u32>>>,
+  contacts: SnapshotLog<Vec<MemContact>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MemContact {
+  user: TwinoidUserId,
+  friend: bool,
+}
+
 struct StoreState {
-  users: HashMap<TwinoidUserId, ArchivedTwinoidUser>,
+  users: HashMap<TwinoidUserId, MemUser>,
 }
 
 impl StoreState {
@@ -14,12 +35,58 @@ impl StoreState {
     Self { users: HashMap::new() }
   }
 
-  fn get_user(&self, id: &TwinoidUserId) -> Option<&ArchivedTwinoidUser> {
-    self.users.get(id)
+  fn get_user(&self, id: TwinoidUserId, time: Instant) -> Option<ArchivedTwinoidUser> {
+    let user = self.users.get(&id)?;
+    if user.archived_at > time {
+      return None;
+    }
+    Some(ArchivedTwinoidUser {
+      id,
+      archived_at: user.archived_at,
+      display_name: user.display_name.clone(),
+      details: user.details.at(time).map(|latest| LatestTemporal {
+        latest: latest.cloned(),
+      }),
+      stats: user
+        .stats
+        .iter()
+        .filter_map(|((site, key), log)| {
+          log.at(time).map(|latest| ArchivedTwinoidStat {
+            site: *site,
+            key: key.clone(),
+            score: LatestTemporal {
+              latest: latest.cloned(),
+            },
+          })
+        })
+        .collect(),
+      achievements: user
+        .achievements
+        .iter()
+        .filter_map(|((site, key), log)| {
+          log.at(time).map(|latest| ArchivedTwinoidAchievement {
+            site: *site,
+            key: key.clone(),
+            points: LatestTemporal {
+              latest: latest.cloned(),
+            },
+          })
+        })
+        .collect(),
+    })
   }
 
-  fn touch_user(&mut self, user: ArchivedTwinoidUser) {
-    self.users.insert(user.id, user);
+  fn touch_user(&mut self, now: Instant, short: &ShortTwinoidUser) -> &mut MemUser {
+    let user = self.users.entry(short.id).or_insert_with(|| MemUser {
+      archived_at: now,
+      display_name: short.display_name.clone(),
+      details: SnapshotLog::new(),
+      stats: BTreeMap::new(),
+      achievements: BTreeMap::new(),
+      contacts: SnapshotLog::new(),
+    });
+    user.display_name = short.display_name.clone();
+    user
   }
 }
 
@@ -47,24 +114,101 @@ where
 {
   async fn get_short_user(&self, options: &GetTwinoidUserOptions) -> Result<Option<ShortTwinoidUser>, EtwinError> {
     let state = self.state.read().unwrap();
-    Ok(state.get_user(&options.id).cloned().map(From::from))
+    Ok(state.users.get(&options.id).map(|user| ShortTwinoidUser {
+      id: options.id,
+      display_name: user.display_name.clone(),
+    }))
   }
 
   async fn get_user(&self, options: &GetTwinoidUserOptions) -> Result<Option<ArchivedTwinoidUser>, EtwinError> {
     let state = self.state.read().unwrap();
-    Ok(state.get_user(&options.id).cloned())
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    Ok(state.get_user(options.id, time))
   }
 
   async fn touch_short_user(&self, short: &ShortTwinoidUser) -> Result<ArchivedTwinoidUser, EtwinError> {
     let mut state = self.state.write().unwrap();
     let now = self.clock.now();
-    let user = ArchivedTwinoidUser {
+    let user = state.touch_user(now, short);
+    Ok(ArchivedTwinoidUser {
       id: short.id,
-      archived_at: now,
-      display_name: short.display_name.clone(),
+      archived_at: user.archived_at,
+      display_name: user.display_name.clone(),
+      details: None,
+      stats: Vec::new(),
+      achievements: Vec::new(),
+    })
+  }
+
+  async fn touch_profile(&self, profile: &TwinoidProfile) -> Result<(), EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    let user = state.touch_user(now, &profile.user);
+    user.details.snapshot(now, profile.details.clone());
+    for site in profile.sites.iter() {
+      for stat in site.stats.iter() {
+        user
+          .stats
+          .entry((site.site, stat.key.clone()))
+          .or_default()
+          .snapshot(now, stat.score);
+      }
+      for achievement in site.achievements.iter() {
+        user
+          .achievements
+          .entry((site.site, achievement.key.clone()))
+          .or_default()
+          .snapshot(now, achievement.points);
+      }
+    }
+    Ok(())
+  }
+
+  async fn get_contacts(
+    &self,
+    options: &GetTwinoidUserOptions,
+  ) -> Result<Option<LatestTemporal<Vec<TwinoidContact>>>, EtwinError> {
+    let state = self.state.read().unwrap();
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    let user = match state.users.get(&options.id) {
+      Some(user) => user,
+      None => return Ok(None),
     };
-    state.touch_user(user.clone());
-    Ok(user)
+    Ok(user.contacts.at(time).map(|latest| LatestTemporal {
+      latest: latest.map(|contacts| {
+        contacts
+          .iter()
+          .map(|contact| TwinoidContact {
+            user: ShortTwinoidUser {
+              id: contact.user,
+              display_name: state.users[&contact.user].display_name.clone(),
+            },
+            friend: contact.friend,
+          })
+          .collect()
+      }),
+    }))
+  }
+
+  async fn touch_contacts(&self, contacts: &TwinoidContactList) -> Result<(), EtwinError> {
+    let mut state = self.state.write().unwrap();
+    let now = self.clock.now();
+    for contact in contacts.contacts.iter() {
+      state.touch_user(now, &contact.user);
+    }
+    let user = state.touch_user(now, &contacts.user);
+    user.contacts.snapshot(
+      now,
+      contacts
+        .contacts
+        .iter()
+        .map(|contact| MemContact {
+          user: contact.user.id,
+          friend: contact.friend,
+        })
+        .collect(),
+    );
+    Ok(())
   }
 }
 
@@ -101,4 +245,19 @@ mod test {
   async fn test_get_missing_user() {
     crate::test::test_get_missing_user(make_test_api()).await;
   }
+
+  #[tokio::test]
+  async fn test_touch_profile() {
+    crate::test::test_touch_profile(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_touch_profile_history() {
+    crate::test::test_touch_profile_history(make_test_api()).await;
+  }
+
+  #[tokio::test]
+  async fn test_touch_contacts() {
+    crate::test::test_touch_contacts(make_test_api()).await;
+  }
 }
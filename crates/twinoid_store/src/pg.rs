@@ -1,12 +1,19 @@
 use async_trait::async_trait;
 use etwin_core::api::ApiRef;
 use etwin_core::clock::Clock;
-use etwin_core::core::Instant;
+use etwin_core::core::{Instant, PeriodLower};
+use etwin_core::pg_num::PgU32;
+use etwin_core::temporal::{ForeignRetrieved, ForeignSnapshot, LatestTemporal};
 use etwin_core::twinoid::{
-  ArchivedTwinoidUser, GetTwinoidUserOptions, ShortTwinoidUser, TwinoidStore, TwinoidUserDisplayName, TwinoidUserId,
+  ArchivedTwinoidAchievement, ArchivedTwinoidStat, ArchivedTwinoidUser, GetTwinoidUserOptions, ShortTwinoidUser,
+  TwinoidAchievement, TwinoidAchievementKey, TwinoidContact, TwinoidContactList, TwinoidProfile, TwinoidProfileDetails,
+  TwinoidSiteId, TwinoidStat, TwinoidStatKey, TwinoidStore, TwinoidUserDisplayName, TwinoidUserId,
 };
 use etwin_core::types::EtwinError;
-use sqlx::PgPool;
+use etwin_postgres_tools::upsert_archive_query;
+use sqlx::postgres::PgQueryResult;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
 
 pub struct PgTwinoidStore<TyClock, TyDatabase>
 where
@@ -58,52 +65,510 @@ where
   }
 
   async fn get_user(&self, options: &GetTwinoidUserOptions) -> Result<Option<ArchivedTwinoidUser>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    let mut tx = self.database.as_ref().begin().await?;
+
     #[derive(Debug, sqlx::FromRow)]
     struct Row {
       twinoid_user_id: TwinoidUserId,
       archived_at: Instant,
       name: TwinoidUserDisplayName,
+      details_period: Option<PeriodLower>,
+      details_retrieved_latest: Option<Instant>,
+      title: Option<String>,
+      picture_url: Option<String>,
+      locale: Option<String>,
+      gender: Option<String>,
+      city: Option<String>,
     }
 
     let row: Option<Row> = sqlx::query_as::<_, Row>(
       r"
-      SELECT twinoid_user_id, name, archived_at
+      WITH latest_twinoid_user_profile AS (
+        SELECT twinoid_user_id, period, retrieved_at, title, picture_url, locale, gender, city
+        FROM twinoid_user_profiles
+        WHERE twinoid_user_id = $1::TWINOID_USER_ID AND lower(period) <= $2::INSTANT
+        ORDER BY lower(period) DESC
+        LIMIT 1
+      )
+      SELECT twinoid_user_id, archived_at, name,
+        profile.period AS details_period, profile.retrieved_at[CARDINALITY(profile.retrieved_at)] AS details_retrieved_latest,
+        profile.title, profile.picture_url, profile.locale, profile.gender, profile.city
       FROM twinoid_users
-      WHERE twinoid_user_id = $1::TWINOID_USER_ID;
+        LEFT OUTER JOIN latest_twinoid_user_profile AS profile USING (twinoid_user_id)
+      WHERE twinoid_user_id = $1::TWINOID_USER_ID AND archived_at <= $2::INSTANT;
     ",
     )
-    .bind(&options.id)
-    .fetch_optional(self.database.as_ref())
+    .bind(options.id)
+    .bind(time)
+    .fetch_optional(&mut tx)
     .await?;
 
-    Ok(row.map(|r| ArchivedTwinoidUser {
-      id: r.twinoid_user_id,
-      archived_at: r.archived_at,
-      display_name: r.name,
+    let row = match row {
+      Some(row) => row,
+      None => return Ok(None),
+    };
+
+    let details = match (row.details_period, row.details_retrieved_latest) {
+      (Some(period), Some(latest)) => Some(LatestTemporal {
+        latest: ForeignSnapshot {
+          period,
+          retrieved: ForeignRetrieved { latest },
+          value: TwinoidProfileDetails {
+            title: row.title,
+            picture: row.picture_url,
+            locale: row.locale,
+            gender: row.gender,
+            city: row.city,
+          },
+        },
+      }),
+      (None, None) => None,
+      _ => unreachable!(),
+    };
+
+    let stats = {
+      #[derive(Debug, sqlx::FromRow)]
+      struct Row {
+        twinoid_site_id: TwinoidSiteId,
+        twinoid_stat_key: TwinoidStatKey,
+        period: PeriodLower,
+        retrieved_latest: Instant,
+        score: i64,
+      }
+
+      let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+        r"
+        SELECT DISTINCT ON (twinoid_site_id, twinoid_stat_key)
+          twinoid_site_id, twinoid_stat_key, period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, score
+        FROM twinoid_site_stats
+        WHERE twinoid_user_id = $1::TWINOID_USER_ID AND lower(period) <= $2::INSTANT
+        ORDER BY twinoid_site_id, twinoid_stat_key, lower(period) DESC;
+      ",
+      )
+      .bind(options.id)
+      .bind(time)
+      .fetch_all(&mut tx)
+      .await?;
+
+      let mut stats: Vec<ArchivedTwinoidStat> = rows
+        .into_iter()
+        .map(|r| ArchivedTwinoidStat {
+          site: r.twinoid_site_id,
+          key: r.twinoid_stat_key,
+          score: LatestTemporal {
+            latest: ForeignSnapshot {
+              period: r.period,
+              retrieved: ForeignRetrieved {
+                latest: r.retrieved_latest,
+              },
+              value: r.score,
+            },
+          },
+        })
+        .collect();
+      stats.sort_by(|l, r| (l.site, &l.key).cmp(&(r.site, &r.key)));
+      stats
+    };
+
+    let achievements = {
+      #[derive(Debug, sqlx::FromRow)]
+      struct Row {
+        twinoid_site_id: TwinoidSiteId,
+        twinoid_achievement_key: TwinoidAchievementKey,
+        period: PeriodLower,
+        retrieved_latest: Instant,
+        points: Option<PgU32>,
+      }
+
+      let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+        r"
+        SELECT DISTINCT ON (twinoid_site_id, twinoid_achievement_key)
+          twinoid_site_id, twinoid_achievement_key, period, retrieved_at[CARDINALITY(retrieved_at)] AS retrieved_latest, points
+        FROM twinoid_site_achievements
+        WHERE twinoid_user_id = $1::TWINOID_USER_ID AND lower(period) <= $2::INSTANT
+        ORDER BY twinoid_site_id, twinoid_achievement_key, lower(period) DESC;
+      ",
+      )
+      .bind(options.id)
+      .bind(time)
+      .fetch_all(&mut tx)
+      .await?;
+
+      let mut achievements: Vec<ArchivedTwinoidAchievement> = rows
+        .into_iter()
+        .map(|r| ArchivedTwinoidAchievement {
+          site: r.twinoid_site_id,
+          key: r.twinoid_achievement_key,
+          points: LatestTemporal {
+            latest: ForeignSnapshot {
+              period: r.period,
+              retrieved: ForeignRetrieved {
+                latest: r.retrieved_latest,
+              },
+              value: r.points.map(u32::from),
+            },
+          },
+        })
+        .collect();
+      achievements.sort_by(|l, r| (l.site, &l.key).cmp(&(r.site, &r.key)));
+      achievements
+    };
+
+    tx.commit().await?;
+
+    Ok(Some(ArchivedTwinoidUser {
+      id: row.twinoid_user_id,
+      archived_at: row.archived_at,
+      display_name: row.name,
+      details,
+      stats,
+      achievements,
     }))
   }
 
   async fn touch_short_user(&self, short: &ShortTwinoidUser) -> Result<ArchivedTwinoidUser, EtwinError> {
     let now = self.clock.now();
-    sqlx::query(
-      r"
-      INSERT INTO twinoid_users(twinoid_user_id, name, archived_at)
-      VALUES ($1::TWINOID_USER_ID, $2::TWINOID_USER_DISPLAY_NAME, $3::INSTANT)
-        ON CONFLICT (twinoid_user_id)
-          DO UPDATE SET name = $2::TWINOID_USER_DISPLAY_NAME;
-    ",
-    )
-    .bind(&short.id)
-    .bind(&short.display_name)
-    .bind(now)
-    .execute(self.database.as_ref())
-    .await?;
+    let mut tx = self.database.as_ref().begin().await?;
+    let archived_at = touch_twinoid_user(&mut tx, now, short).await?;
+    tx.commit().await?;
     Ok(ArchivedTwinoidUser {
       id: short.id,
-      archived_at: now,
+      archived_at,
       display_name: short.display_name.clone(),
+      details: None,
+      stats: Vec::new(),
+      achievements: Vec::new(),
     })
   }
+
+  async fn touch_profile(&self, profile: &TwinoidProfile) -> Result<(), EtwinError> {
+    let now = self.clock.now();
+    let mut tx = self.database.as_ref().begin().await?;
+    let user = profile.user.id;
+    touch_twinoid_user(&mut tx, now, &profile.user).await?;
+    touch_twinoid_user_profile(&mut tx, now, user, &profile.details).await?;
+    for site in profile.sites.iter() {
+      for stat in site.stats.iter() {
+        touch_twinoid_site_stat(&mut tx, now, user, site.site, stat).await?;
+      }
+      for achievement in site.achievements.iter() {
+        touch_twinoid_site_achievement(&mut tx, now, user, site.site, achievement).await?;
+      }
+    }
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn get_contacts(
+    &self,
+    options: &GetTwinoidUserOptions,
+  ) -> Result<Option<LatestTemporal<Vec<TwinoidContact>>>, EtwinError> {
+    let time = options.time.unwrap_or_else(|| self.clock.now());
+    let mut tx = self.database.as_ref().begin().await?;
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      period_start: Instant,
+      period_end: Option<Instant>,
+      retrieved_at: Vec<Instant>,
+      contact_count: PgU32,
+    }
+
+    let row: Option<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT lower(period) AS period_start, upper(period) AS period_end, retrieved_at::TIMESTAMPTZ[] AS retrieved_at,
+        contact_count
+      FROM twinoid_user_contact_lists
+      WHERE twinoid_user_id = $1::TWINOID_USER_ID AND lower(period) <= $2::INSTANT
+      ORDER BY lower(period) DESC
+      LIMIT 1;
+    ",
+    )
+    .bind(options.id)
+    .bind(time)
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let row = match row {
+      Some(row) => row,
+      None => return Ok(None),
+    };
+
+    #[derive(Debug, sqlx::FromRow)]
+    struct ContactRow {
+      period_start: Instant,
+      period_end: Option<Instant>,
+      contact_id: TwinoidUserId,
+      name: TwinoidUserDisplayName,
+      friend: bool,
+    }
+
+    let contact_rows: Vec<ContactRow> = sqlx::query_as::<_, ContactRow>(
+      r"
+      WITH items AS (
+        SELECT DISTINCT ON (offset_in_list) offset_in_list, period, contact_id, friend
+        FROM twinoid_user_contacts
+        WHERE
+          twinoid_user_id = $1::TWINOID_USER_ID
+          AND lower(period) <= $2::INSTANT
+          AND offset_in_list < $3::U32
+        ORDER BY offset_in_list, lower(period) DESC
+      )
+      SELECT lower(period) AS period_start, upper(period) AS period_end, contact_id, twinoid_users.name, friend
+      FROM items
+        INNER JOIN twinoid_users ON (twinoid_users.twinoid_user_id = items.contact_id)
+      ORDER BY offset_in_list;
+    ",
+    )
+    .bind(options.id)
+    .bind(time)
+    .bind(row.contact_count)
+    .fetch_all(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // The list is split across multiple tables: it is valid while all of its rows are valid.
+    let mut start = row.period_start;
+    let mut end = row.period_end;
+    for contact in contact_rows.iter() {
+      start = Instant::max(start, contact.period_start);
+      end = match (end, contact.period_end) {
+        (Some(end), Some(row_end)) => Some(Instant::min(end, row_end)),
+        (end, row_end) => end.or(row_end),
+      };
+    }
+    let latest = row
+      .retrieved_at
+      .iter()
+      .copied()
+      .filter(|t| end.is_none_or(|end| *t < end))
+      .max()
+      .expect("the contact list must be retrieved during its period");
+
+    Ok(Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::new(start, end),
+        retrieved: ForeignRetrieved { latest },
+        value: contact_rows
+          .into_iter()
+          .map(|r| TwinoidContact {
+            user: ShortTwinoidUser {
+              id: r.contact_id,
+              display_name: r.name,
+            },
+            friend: r.friend,
+          })
+          .collect(),
+      },
+    }))
+  }
+
+  async fn touch_contacts(&self, contacts: &TwinoidContactList) -> Result<(), EtwinError> {
+    let now = self.clock.now();
+    let mut tx = self.database.as_ref().begin().await?;
+    let user = contacts.user.id;
+    touch_twinoid_user(&mut tx, now, &contacts.user).await?;
+    for contact in contacts.contacts.iter() {
+      touch_twinoid_user(&mut tx, now, &contact.user).await?;
+    }
+    touch_twinoid_user_contact_list(&mut tx, now, user, contacts.contacts.len()).await?;
+    for (offset, contact) in contacts.contacts.iter().enumerate() {
+      let offset = u32::try_from(offset).unwrap();
+      touch_twinoid_user_contact(&mut tx, now, user, offset, contact).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+  }
+}
+
+/// Inserts or updates a Twinoid user, returns the time when it was first archived.
+async fn touch_twinoid_user(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  short: &ShortTwinoidUser,
+) -> Result<Instant, EtwinError> {
+  #[derive(Debug, sqlx::FromRow)]
+  struct Row {
+    archived_at: Instant,
+  }
+
+  let row: Row = sqlx::query_as::<_, Row>(
+    r"
+    INSERT INTO twinoid_users(twinoid_user_id, name, archived_at)
+    VALUES ($1::TWINOID_USER_ID, $2::TWINOID_USER_DISPLAY_NAME, $3::INSTANT)
+      ON CONFLICT (twinoid_user_id)
+        DO UPDATE SET name = $2::TWINOID_USER_DISPLAY_NAME
+    RETURNING archived_at;
+  ",
+  )
+  .bind(&short.id)
+  .bind(&short.display_name)
+  .bind(now)
+  .fetch_one(tx)
+  .await?;
+
+  Ok(row.archived_at)
+}
+
+async fn touch_twinoid_user_profile(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: TwinoidUserId,
+  details: &TwinoidProfileDetails,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    twinoid_user_profiles(
+      time($1 period, retrieved_at),
+      primary($2 twinoid_user_id::TWINOID_USER_ID),
+      data(
+        $3 title::TEXT,
+        $4 picture_url::VARCHAR,
+        $5 locale::VARCHAR,
+        $6 gender::VARCHAR,
+        $7 city::VARCHAR,
+      ),
+    )
+  ))
+  .bind(now)
+  .bind(user)
+  .bind(&details.title)
+  .bind(&details.picture)
+  .bind(&details.locale)
+  .bind(&details.gender)
+  .bind(&details.city)
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary)
+  assert!((1..=2u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_twinoid_site_stat(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: TwinoidUserId,
+  site: TwinoidSiteId,
+  stat: &TwinoidStat,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    twinoid_site_stats(
+      time($1 period, retrieved_at),
+      primary(
+        $2 twinoid_user_id::TWINOID_USER_ID,
+        $3 twinoid_site_id::TWINOID_SITE_ID,
+        $4 twinoid_stat_key::TWINOID_STAT_KEY,
+      ),
+      data($5 score::I64),
+    )
+  ))
+  .bind(now)
+  .bind(user)
+  .bind(site)
+  .bind(&stat.key)
+  .bind(stat.score)
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary)
+  assert!((1..=2u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_twinoid_site_achievement(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: TwinoidUserId,
+  site: TwinoidSiteId,
+  achievement: &TwinoidAchievement,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    twinoid_site_achievements(
+      time($1 period, retrieved_at),
+      primary(
+        $2 twinoid_user_id::TWINOID_USER_ID,
+        $3 twinoid_site_id::TWINOID_SITE_ID,
+        $4 twinoid_achievement_key::TWINOID_ACHIEVEMENT_KEY,
+      ),
+      data($5 points::U32?),
+    )
+  ))
+  .bind(now)
+  .bind(user)
+  .bind(site)
+  .bind(&achievement.key)
+  .bind(achievement.points.map(PgU32::from))
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary)
+  assert!((1..=2u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_twinoid_user_contact_list(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: TwinoidUserId,
+  contact_count: usize,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    twinoid_user_contact_lists(
+      time($1 period, retrieved_at),
+      primary($2 twinoid_user_id::TWINOID_USER_ID),
+      data($3 contact_count::U32),
+    )
+  ))
+  .bind(now)
+  .bind(user)
+  .bind(PgU32::from(u32::try_from(contact_count).unwrap()))
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary)
+  assert!((1..=2u64).contains(&res.rows_affected()));
+  Ok(())
+}
+
+async fn touch_twinoid_user_contact(
+  tx: &mut Transaction<'_, Postgres>,
+  now: Instant,
+  user: TwinoidUserId,
+  offset: u32,
+  contact: &TwinoidContact,
+) -> Result<(), EtwinError> {
+  let res: PgQueryResult = sqlx::query(upsert_archive_query!(
+    twinoid_user_contacts(
+      time($1 period, retrieved_at),
+      primary($2 twinoid_user_id::TWINOID_USER_ID, $3 offset_in_list::U32),
+      data($4 contact_id::TWINOID_USER_ID, $5 friend::BOOLEAN),
+      unique(contact(twinoid_user_id, contact_id)),
+    )
+  ))
+  .bind(now)
+  .bind(user)
+  .bind(PgU32::from(offset))
+  .bind(contact.user.id)
+  .bind(contact.friend)
+  .execute(&mut *tx)
+  .await?;
+  // Affected row counts:
+  // 1 : 1 updated (matching data)
+  // 1 : 1 inserted (first insert)
+  // 2 : 1 inserted (data change), 1 invalidated (primary + contact)
+  // 3 : 1 inserted (data change), 1 invalidated (primary), 1 invalidated (contact)
+  assert!((1..=3u64).contains(&res.rows_affected()));
+  Ok(())
 }
 
 #[cfg(feature = "neon")]
@@ -181,4 +646,22 @@ mod test {
   async fn test_get_missing_user() {
     crate::test::test_get_missing_user(make_test_api().await).await;
   }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_profile() {
+    crate::test::test_touch_profile(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_profile_history() {
+    crate::test::test_touch_profile_history(make_test_api().await).await;
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_touch_contacts() {
+    crate::test::test_touch_contacts(make_test_api().await).await;
+  }
 }
@@ -1,7 +1,13 @@
 use chrono::{Duration, TimeZone, Utc};
 use etwin_core::api::ApiRef;
 use etwin_core::clock::VirtualClock;
-use etwin_core::twinoid::{ArchivedTwinoidUser, GetTwinoidUserOptions, ShortTwinoidUser, TwinoidStore};
+use etwin_core::core::PeriodLower;
+use etwin_core::temporal::{ForeignRetrieved, ForeignSnapshot, LatestTemporal};
+use etwin_core::twinoid::{
+  ArchivedTwinoidAchievement, ArchivedTwinoidStat, ArchivedTwinoidUser, GetTwinoidUserOptions, ShortTwinoidUser,
+  TwinoidAchievement, TwinoidContact, TwinoidContactList, TwinoidProfile, TwinoidProfileDetails, TwinoidSiteProfile,
+  TwinoidStat, TwinoidStore,
+};
 
 pub(crate) struct TestApi<TyClock, TyTwinoidStore>
 where
@@ -45,6 +51,9 @@ where
       id: "123".parse().unwrap(),
       display_name: "alice".parse().unwrap(),
       archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      details: None,
+      stats: vec![],
+      achievements: vec![],
     };
     assert_eq!(actual, expected);
   }
@@ -77,6 +86,9 @@ where
       id: "123".parse().unwrap(),
       display_name: "alice".parse().unwrap(),
       archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+      details: None,
+      stats: vec![],
+      achievements: vec![],
     });
     assert_eq!(actual, expected);
   }
@@ -113,3 +125,360 @@ where
     assert_eq!(actual, expected);
   }
 }
+
+fn make_profile(score: i64) -> TwinoidProfile {
+  TwinoidProfile {
+    user: ShortTwinoidUser {
+      id: "123".parse().unwrap(),
+      display_name: "alice".parse().unwrap(),
+    },
+    details: TwinoidProfileDetails {
+      title: Some("Explorer".to_string()),
+      picture: Some("https://imgup.motion-twin.com/twinoid/1/2/3.jpg".to_string()),
+      locale: Some("fr".to_string()),
+      gender: Some("female".to_string()),
+      city: None,
+    },
+    sites: vec![TwinoidSiteProfile {
+      site: "1".parse().unwrap(),
+      stats: vec![TwinoidStat {
+        key: "score".parse().unwrap(),
+        score,
+      }],
+      achievements: vec![
+        TwinoidAchievement {
+          key: "first_game".parse().unwrap(),
+          points: Some(10),
+        },
+        TwinoidAchievement {
+          key: "secret".parse().unwrap(),
+          points: None,
+        },
+      ],
+    }],
+  }
+}
+
+pub(crate) async fn test_touch_profile<TyClock, TyTwinoidStore>(api: TestApi<TyClock, TyTwinoidStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api.twinoid_store.touch_profile(&make_profile(100)).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  let actual = api
+    .twinoid_store
+    .get_user(&GetTwinoidUserOptions {
+      id: "123".parse().unwrap(),
+      time: None,
+    })
+    .await
+    .unwrap();
+  let expected = Some(ArchivedTwinoidUser {
+    id: "123".parse().unwrap(),
+    display_name: "alice".parse().unwrap(),
+    archived_at: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+    details: Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+        },
+        value: TwinoidProfileDetails {
+          title: Some("Explorer".to_string()),
+          picture: Some("https://imgup.motion-twin.com/twinoid/1/2/3.jpg".to_string()),
+          locale: Some("fr".to_string()),
+          gender: Some("female".to_string()),
+          city: None,
+        },
+      },
+    }),
+    stats: vec![ArchivedTwinoidStat {
+      site: "1".parse().unwrap(),
+      key: "score".parse().unwrap(),
+      score: LatestTemporal {
+        latest: ForeignSnapshot {
+          period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+          retrieved: ForeignRetrieved {
+            latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+          },
+          value: 100,
+        },
+      },
+    }],
+    achievements: vec![
+      ArchivedTwinoidAchievement {
+        site: "1".parse().unwrap(),
+        key: "first_game".parse().unwrap(),
+        points: LatestTemporal {
+          latest: ForeignSnapshot {
+            period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+            retrieved: ForeignRetrieved {
+              latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+            },
+            value: Some(10),
+          },
+        },
+      },
+      ArchivedTwinoidAchievement {
+        site: "1".parse().unwrap(),
+        key: "secret".parse().unwrap(),
+        points: LatestTemporal {
+          latest: ForeignSnapshot {
+            period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+            retrieved: ForeignRetrieved {
+              latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+            },
+            value: None,
+          },
+        },
+      },
+    ],
+  });
+  assert_eq!(actual, expected);
+}
+
+pub(crate) async fn test_touch_profile_history<TyClock, TyTwinoidStore>(api: TestApi<TyClock, TyTwinoidStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyTwinoidStore: TwinoidStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api.twinoid_store.touch_profile(&make_profile(100)).await.unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1));
+  api.twinoid_store.touch_profile(&make_profile(100)).await.unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2));
+  api.twinoid_store.touch_profile(&make_profile(200)).await.unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .twinoid_store
+      .get_user(&GetTwinoidUserOptions {
+        id: "123".parse().unwrap(),
+        time: Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 1)),
+      })
+      .await
+      .unwrap()
+      .unwrap();
+    let expected = vec![ArchivedTwinoidStat {
+      site: "1".parse().unwrap(),
+      key: "score".parse().unwrap(),
+      score: LatestTemporal {
+        latest: ForeignSnapshot {
+          period: PeriodLower::bounded(
+            Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+            Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+          ),
+          retrieved: ForeignRetrieved {
+            latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 1),
+          },
+          value: 100,
+        },
+      },
+    }];
+    assert_eq!(actual.stats, expected);
+    assert_eq!(
+      actual.details.unwrap().latest.period,
+      PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0))
+    );
+  }
+  {
+    let actual = api
+      .twinoid_store
+      .get_user(&GetTwinoidUserOptions {
+        id: "123".parse().unwrap(),
+        time: None,
+      })
+      .await
+      .unwrap()
+      .unwrap();
+    let expected = vec![ArchivedTwinoidStat {
+      site: "1".parse().unwrap(),
+      key: "score".parse().unwrap(),
+      score: LatestTemporal {
+        latest: ForeignSnapshot {
+          period: PeriodLower::unbounded(Utc.ymd(2021, 1, 1).and_hms(0, 0, 2)),
+          retrieved: ForeignRetrieved {
+            latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 2),
+          },
+          value: 200,
+        },
+      },
+    }];
+    assert_eq!(actual.stats, expected);
+  }
+  {
+    let actual = api
+      .twinoid_store
+      .get_user(&GetTwinoidUserOptions {
+        id: "123".parse().unwrap(),
+        time: Some(Utc.ymd(2020, 12, 31).and_hms(0, 0, 0)),
+      })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+}
+
+pub(crate) async fn test_touch_contacts<TyClock, TyTwinoidStore>(api: TestApi<TyClock, TyTwinoidStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyTwinoidStore: TwinoidStore,
+{
+  let alice = ShortTwinoidUser {
+    id: "1".parse().unwrap(),
+    display_name: "alice".parse().unwrap(),
+  };
+  let bob = ShortTwinoidUser {
+    id: "2".parse().unwrap(),
+    display_name: "bob".parse().unwrap(),
+  };
+  let charlie = ShortTwinoidUser {
+    id: "3".parse().unwrap(),
+    display_name: "charlie".parse().unwrap(),
+  };
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  api
+    .twinoid_store
+    .touch_contacts(&TwinoidContactList {
+      user: alice.clone(),
+      contacts: vec![TwinoidContact {
+        user: charlie.clone(),
+        friend: false,
+      }],
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 2).and_hms(0, 0, 0));
+  api
+    .twinoid_store
+    .touch_contacts(&TwinoidContactList {
+      user: alice.clone(),
+      contacts: vec![
+        TwinoidContact {
+          user: bob.clone(),
+          friend: true,
+        },
+        TwinoidContact {
+          user: charlie.clone(),
+          friend: true,
+        },
+      ],
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 3).and_hms(0, 0, 0));
+  api
+    .twinoid_store
+    .touch_contacts(&TwinoidContactList {
+      user: alice.clone(),
+      contacts: vec![TwinoidContact {
+        user: bob.clone(),
+        friend: true,
+      }],
+    })
+    .await
+    .unwrap();
+  api.clock.as_ref().advance_by(Duration::seconds(1));
+  {
+    let actual = api
+      .twinoid_store
+      .get_contacts(&GetTwinoidUserOptions {
+        id: alice.id,
+        time: None,
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::unbounded(Utc.ymd(2021, 1, 3).and_hms(0, 0, 0)),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 3).and_hms(0, 0, 0),
+        },
+        value: vec![TwinoidContact {
+          user: bob.clone(),
+          friend: true,
+        }],
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .twinoid_store
+      .get_contacts(&GetTwinoidUserOptions {
+        id: alice.id,
+        time: Some(Utc.ymd(2021, 1, 2).and_hms(12, 0, 0)),
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::bounded(
+          Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+          Utc.ymd(2021, 1, 3).and_hms(0, 0, 0),
+        ),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+        },
+        value: vec![
+          TwinoidContact {
+            user: bob.clone(),
+            friend: true,
+          },
+          TwinoidContact {
+            user: charlie.clone(),
+            friend: true,
+          },
+        ],
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .twinoid_store
+      .get_contacts(&GetTwinoidUserOptions {
+        id: alice.id,
+        time: Some(Utc.ymd(2021, 1, 1).and_hms(12, 0, 0)),
+      })
+      .await
+      .unwrap();
+    let expected = Some(LatestTemporal {
+      latest: ForeignSnapshot {
+        period: PeriodLower::bounded(
+          Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+          Utc.ymd(2021, 1, 2).and_hms(0, 0, 0),
+        ),
+        retrieved: ForeignRetrieved {
+          latest: Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+        },
+        value: vec![TwinoidContact {
+          user: charlie,
+          friend: false,
+        }],
+      },
+    });
+    assert_eq!(actual, expected);
+  }
+  {
+    let actual = api
+      .twinoid_store
+      .get_contacts(&GetTwinoidUserOptions {
+        id: alice.id,
+        time: Some(Utc.ymd(2020, 12, 31).and_hms(0, 0, 0)),
+      })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+  {
+    let actual = api
+      .twinoid_store
+      .get_contacts(&GetTwinoidUserOptions { id: bob.id, time: None })
+      .await
+      .unwrap();
+    assert_eq!(actual, None);
+  }
+}
@@ -6,8 +6,8 @@ use etwin_core::password::PasswordHash;
 use etwin_core::temporal::Temporal;
 use etwin_core::types::EtwinError;
 use etwin_core::user::{
-  CompleteSimpleUser, CreateUserOptions, DeleteUserError, GetShortUserOptions, GetUserOptions, GetUserResult,
-  ShortUser, ShortUserWithPassword, SimpleUser, UpdateUserError, UpdateUserOptions, UserDisplayName,
+  CompleteSimpleUser, CreateUserOptions, DeleteUserError, GetShortUserOptions, GetShortUsersOptions, GetUserOptions,
+  GetUserResult, ShortUser, ShortUserWithPassword, SimpleUser, UpdateUserError, UpdateUserOptions, UserDisplayName,
   UserDisplayNameVersion, UserDisplayNameVersions, UserFields, UserId, UserIdRef, UserRef, UserStore, Username,
   USERNAME_LOCK_DURATION, USER_DISPLAY_NAME_LOCK_DURATION, USER_PASSWORD_LOCK_DURATION,
 };
@@ -304,6 +304,18 @@ where
     Ok(mem_user.map(|u| u.at(options.time)).map(ShortUser::from))
   }
 
+  async fn get_short_users(&self, options: &GetShortUsersOptions) -> Result<Vec<ShortUser>, EtwinError> {
+    let state = &self.state.read().unwrap();
+    Ok(
+      options
+        .ids
+        .iter()
+        .filter_map(|id| state.get(&UserRef::Id(*id), options.time))
+        .map(|u| ShortUser::from(u.at(options.time)))
+        .collect(),
+    )
+  }
+
   async fn update_user(&self, options: &UpdateUserOptions) -> Result<CompleteSimpleUser, UpdateUserError> {
     let mut state = self.state.write().unwrap();
     let user = state.update(options, self.clock.now())?;
@@ -6,13 +6,14 @@ use etwin_core::email::{touch_email_address, EmailAddress};
 use etwin_core::password::PasswordHash;
 use etwin_core::types::EtwinError;
 use etwin_core::user::{
-  CompleteSimpleUser, CreateUserOptions, DeleteUserError, GetShortUserOptions, GetUserOptions, GetUserResult,
-  ShortUser, ShortUserWithPassword, UpdateUserError, UpdateUserOptions, UserDisplayName, UserDisplayNameVersion,
-  UserDisplayNameVersions, UserFields, UserId, UserIdRef, UserRef, UserStore, Username, USERNAME_LOCK_DURATION,
-  USER_DISPLAY_NAME_LOCK_DURATION, USER_PASSWORD_LOCK_DURATION,
+  CompleteSimpleUser, CreateUserOptions, DeleteUserError, GetShortUserOptions, GetShortUsersOptions, GetUserOptions,
+  GetUserResult, ShortUser, ShortUserWithPassword, UpdateUserError, UpdateUserOptions, UserDisplayName,
+  UserDisplayNameVersion, UserDisplayNameVersions, UserFields, UserId, UserIdRef, UserRef, UserStore, Username,
+  USERNAME_LOCK_DURATION, USER_DISPLAY_NAME_LOCK_DURATION, USER_PASSWORD_LOCK_DURATION,
 };
 use etwin_core::uuid::UuidGenerator;
 use sqlx::postgres::PgPool;
+use std::collections::HashMap;
 
 pub struct PgUserStore<TyClock, TyDatabase, TyUuidGenerator>
 where
@@ -302,6 +303,44 @@ where
     Ok(Some(user))
   }
 
+  async fn get_short_users(&self, options: &GetShortUsersOptions) -> Result<Vec<ShortUser>, EtwinError> {
+    #[derive(Debug, sqlx::FromRow)]
+    struct Row {
+      user_id: UserId,
+      display_name: UserDisplayName,
+    }
+
+    let ids: Vec<String> = options.ids.iter().map(|r| r.id.to_string()).collect();
+    let rows: Vec<Row> = sqlx::query_as::<_, Row>(
+      r"
+      SELECT user_id, display_name
+      FROM users_current
+      WHERE user_id = ANY($1::TEXT[]::UUID[]);
+      ",
+    )
+    .bind(ids)
+    .fetch_all(self.database.as_ref())
+    .await?;
+
+    let users: HashMap<UserId, UserDisplayName> = rows.into_iter().map(|r| (r.user_id, r.display_name)).collect();
+    Ok(
+      options
+        .ids
+        .iter()
+        .filter_map(|r| {
+          users.get(&r.id).map(|display_name| ShortUser {
+            id: r.id,
+            display_name: UserDisplayNameVersions {
+              current: UserDisplayNameVersion {
+                value: display_name.clone(),
+              },
+            },
+          })
+        })
+        .collect(),
+    )
+  }
+
   async fn update_user(&self, options: &UpdateUserOptions) -> Result<CompleteSimpleUser, UpdateUserError> {
     let now = self.clock.now();
 
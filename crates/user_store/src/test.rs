@@ -2,9 +2,9 @@ use chrono::{Duration, TimeZone, Utc};
 use etwin_core::api::ApiRef;
 use etwin_core::clock::VirtualClock;
 use etwin_core::user::{
-  CompleteSimpleUser, CreateUserOptions, GetUserOptions, GetUserResult, ShortUser, SimpleUser, UpdateUserError,
-  UpdateUserOptions, UpdateUserPatch, UserDisplayNameVersion, UserDisplayNameVersions, UserFields, UserIdRef, UserRef,
-  UserStore, USER_DISPLAY_NAME_LOCK_DURATION,
+  CompleteSimpleUser, CreateUserOptions, GetShortUsersOptions, GetUserOptions, GetUserResult, ShortUser, SimpleUser,
+  UpdateUserError, UpdateUserOptions, UpdateUserPatch, UserDisplayNameVersion, UserDisplayNameVersions, UserFields,
+  UserIdRef, UserRef, UserStore, USER_DISPLAY_NAME_LOCK_DURATION,
 };
 
 #[macro_export]
@@ -14,6 +14,7 @@ macro_rules! test_user_store {
     register_test!($(#[$meta])*, $api, test_register_the_admin_and_retrieve_short);
     register_test!($(#[$meta])*, $api, test_register_the_admin_and_retrieve_default);
     register_test!($(#[$meta])*, $api, test_register_the_admin_and_retrieve_complete);
+    register_test!($(#[$meta])*, $api, test_get_short_users);
     register_test!($(#[$meta])*, $api, test_update_display_name_once);
     register_test!($(#[$meta])*, $api, test_update_locked_display_name);
     register_test!($(#[$meta])*, $api, test_update_display_name_twice);
@@ -188,6 +189,48 @@ pub(crate) async fn test_register_the_admin_and_retrieve_complete<TyClock, TyUse
   assert_eq!(actual, expected);
 }
 
+pub(crate) async fn test_get_short_users<TyClock, TyUserStore>(api: TestApi<TyClock, TyUserStore>)
+where
+  TyClock: ApiRef<VirtualClock>,
+  TyUserStore: UserStore,
+{
+  api.clock.as_ref().advance_to(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+  let alice = api
+    .user_store
+    .create_user(&CreateUserOptions {
+      display_name: "Alice".parse().unwrap(),
+      username: Some("alice".parse().unwrap()),
+      email: None,
+      password: None,
+    })
+    .await
+    .unwrap();
+  let bob = api
+    .user_store
+    .create_user(&CreateUserOptions {
+      display_name: "Bob".parse().unwrap(),
+      username: Some("bob".parse().unwrap()),
+      email: None,
+      password: None,
+    })
+    .await
+    .unwrap();
+  let actual = api
+    .user_store
+    .get_short_users(&GetShortUsersOptions {
+      ids: vec![
+        UserIdRef::new(bob.id),
+        UserIdRef::new("e9c17533-633e-4f60-be9e-72883ae0174a".parse().unwrap()),
+        UserIdRef::new(alice.id),
+      ],
+      time: None,
+    })
+    .await
+    .unwrap();
+  let expected = vec![ShortUser::from(bob), ShortUser::from(alice)];
+  assert_eq!(actual, expected);
+}
+
 pub(crate) async fn test_update_display_name_once<TyClock, TyUserStore>(api: TestApi<TyClock, TyUserStore>)
 where
   TyClock: ApiRef<VirtualClock>,
@@ -0,0 +1,21 @@
+CREATE DOMAIN dinorpg_server AS VARCHAR(15) CHECK (value IN ('www.dinorpg.com', 'en.dinorpg.com', 'es.dinorpg.com'));
+CREATE DOMAIN dinorpg_user_id AS VARCHAR(10) CHECK (value ~ '^[1-9]\d{0,9}$');
+
+-- Known DinoRPG servers
+CREATE TABLE dinorpg_servers (
+  -- Domain name for the DinoRPG server
+  dinorpg_server DINORPG_SERVER PRIMARY KEY NOT NULL
+);
+
+-- Known DinoRPG users
+CREATE TABLE dinorpg_users (
+  -- DinoRPG server
+  dinorpg_server DINORPG_SERVER NOT NULL,
+  -- User ID on the DinoRPG server
+  dinorpg_user_id DINORPG_USER_ID NOT NULL,
+  -- DinoRPG uses the Twinoid display name
+  display_name TWINOID_USER_DISPLAY_NAME NOT NULL,
+  archived_at INSTANT NOT NULL,
+  PRIMARY KEY (dinorpg_server, dinorpg_user_id),
+  CONSTRAINT dinorpg_user__dinorpg_server__fk FOREIGN KEY (dinorpg_server) REFERENCES dinorpg_servers(dinorpg_server) ON DELETE RESTRICT ON UPDATE CASCADE
+);
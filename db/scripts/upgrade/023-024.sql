@@ -0,0 +1,21 @@
+CREATE DOMAIN popotamo_server AS VARCHAR(15) CHECK (value IN ('popotamo.com', 'en.popotamo.com'));
+CREATE DOMAIN popotamo_user_id AS VARCHAR(9) CHECK (value ~ '^[1-9]\d{0,8}$');
+CREATE DOMAIN popotamo_username AS VARCHAR(20) CHECK (value ~ '^[0-9A-Za-z_-]{1,20}$');
+
+-- Known Popotamo servers
+CREATE TABLE popotamo_servers (
+  -- Domain name for the Popotamo server
+  popotamo_server POPOTAMO_SERVER PRIMARY KEY NOT NULL
+);
+
+-- Known Popotamo users
+CREATE TABLE popotamo_users (
+  -- Popotamo server
+  popotamo_server POPOTAMO_SERVER NOT NULL,
+  -- User ID on the Popotamo server
+  popotamo_user_id POPOTAMO_USER_ID NOT NULL,
+  username POPOTAMO_USERNAME NOT NULL,
+  archived_at INSTANT NOT NULL,
+  PRIMARY KEY (popotamo_server, popotamo_user_id),
+  CONSTRAINT popotamo_user__popotamo_server__fk FOREIGN KEY (popotamo_server) REFERENCES popotamo_servers(popotamo_server) ON DELETE RESTRICT ON UPDATE CASCADE
+);
@@ -0,0 +1,62 @@
+CREATE DOMAIN popotamo_session_key AS VARCHAR(32) CHECK (value ~ '^[0-9a-zA-Z]{32}$');
+
+-- Links between Eternal-Twin and Popotamo users
+CREATE TABLE popotamo_user_links (
+  -- Eternal-Twin user id
+  user_id USER_ID NOT NULL,
+  -- Popotamo server
+  popotamo_server POPOTAMO_SERVER NOT NULL,
+  -- User ID on the Popotamo server
+  popotamo_user_id POPOTAMO_USER_ID NOT NULL,
+  -- Link period
+  period PERIOD_LOWER NOT NULL,
+  -- Link creation author
+  linked_by USER_ID NOT NULL,
+  -- Link deletion author
+  unlinked_by USER_ID NULL,
+  PRIMARY KEY (user_id, popotamo_server, popotamo_user_id, period),
+  CHECK ((upper_inf(period) AND unlinked_by IS NULL) OR (NOT upper_inf(period) AND unlinked_by IS NOT NULL)),
+  -- An Eternal-Twin user can only be linked to one Popotamo user per server at a time
+  EXCLUDE USING gist (user_id WITH =, popotamo_server WITH =, period WITH &&),
+  -- A Popotamo user can only be linked to one Eternal-Twin user at a time
+  EXCLUDE USING gist (popotamo_server WITH =, popotamo_user_id WITH =, period WITH &&),
+  CONSTRAINT popotamo_user_link__user__fk FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT popotamo_user_link_linked_by__user__fk FOREIGN KEY (linked_by) REFERENCES users(user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT popotamo_user_link_unlinked_by__user__fk FOREIGN KEY (unlinked_by) REFERENCES users(user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT popotamo_user_link__popotamo_user__fk FOREIGN KEY (popotamo_server, popotamo_user_id) REFERENCES popotamo_users(popotamo_server, popotamo_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Active Popotamo sessions
+CREATE TABLE popotamo_sessions (
+  popotamo_server POPOTAMO_SERVER NOT NULL,
+  popotamo_session_key BYTEA NOT NULL,
+  _popotamo_session_key_hash BYTEA NOT NULL,
+  popotamo_user_id POPOTAMO_USER_ID NOT NULL,
+  -- Session creation time
+  ctime INSTANT NOT NULL,
+  -- Session access time
+  atime INSTANT NOT NULL,
+  CHECK (atime >= ctime),
+  PRIMARY KEY (popotamo_server, _popotamo_session_key_hash),
+  UNIQUE (popotamo_server, popotamo_user_id),
+  CONSTRAINT popotamo_session__popotamo_user__fk FOREIGN KEY (popotamo_server, popotamo_user_id) REFERENCES popotamo_users(popotamo_server, popotamo_user_id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- Revoked Popotamo sessions
+CREATE TABLE old_popotamo_sessions (
+  popotamo_server POPOTAMO_SERVER NOT NULL,
+  popotamo_session_key BYTEA NOT NULL,
+  _popotamo_session_key_hash BYTEA NOT NULL,
+  popotamo_user_id POPOTAMO_USER_ID NOT NULL,
+  -- Session creation time
+  ctime INSTANT NOT NULL,
+  -- Session access time
+  atime INSTANT NOT NULL,
+  -- Session deletion time
+  dtime INSTANT NOT NULL,
+  CHECK (atime >= ctime),
+  CHECK (dtime >= atime),
+  CHECK (dtime > ctime),
+  PRIMARY KEY (popotamo_server, _popotamo_session_key_hash, ctime),
+  CONSTRAINT old_popotamo_session__popotamo_user__fk FOREIGN KEY (popotamo_server, popotamo_user_id) REFERENCES popotamo_users(popotamo_server, popotamo_user_id) ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,47 @@
+CREATE DOMAIN twinoid_site_id AS VARCHAR(6) CHECK (value ~ '^[1-9]\d{0,5}$');
+CREATE DOMAIN twinoid_stat_key AS VARCHAR(100) CHECK (value ~ '^.{1,100}$');
+CREATE DOMAIN twinoid_achievement_key AS VARCHAR(100) CHECK (value ~ '^.{1,100}$');
+
+-- Time-variant Twinoid profile details
+CREATE TABLE twinoid_user_profiles (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  twinoid_user_id TWINOID_USER_ID NOT NULL,
+--
+  title TEXT NULL,
+  picture_url VARCHAR(500) NULL,
+  locale VARCHAR(20) NULL,
+  gender VARCHAR(20) NULL,
+  city VARCHAR(100) NULL,
+  PRIMARY KEY (period, twinoid_user_id),
+  EXCLUDE USING gist (twinoid_user_id WITH =, period WITH &&),
+  CONSTRAINT twinoid_user_profile__user__fk FOREIGN KEY (twinoid_user_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Time-variant Twinoid site stat scores
+CREATE TABLE twinoid_site_stats (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  twinoid_user_id TWINOID_USER_ID NOT NULL,
+  twinoid_site_id TWINOID_SITE_ID NOT NULL,
+  twinoid_stat_key TWINOID_STAT_KEY NOT NULL,
+--
+  score I64 NOT NULL,
+  PRIMARY KEY (period, twinoid_user_id, twinoid_site_id, twinoid_stat_key),
+  EXCLUDE USING gist (twinoid_user_id WITH =, twinoid_site_id WITH =, twinoid_stat_key WITH =, period WITH &&),
+  CONSTRAINT twinoid_site_stat__user__fk FOREIGN KEY (twinoid_user_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Time-variant Twinoid site achievements
+CREATE TABLE twinoid_site_achievements (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  twinoid_user_id TWINOID_USER_ID NOT NULL,
+  twinoid_site_id TWINOID_SITE_ID NOT NULL,
+  twinoid_achievement_key TWINOID_ACHIEVEMENT_KEY NOT NULL,
+--
+  points U32 NOT NULL,
+  PRIMARY KEY (period, twinoid_user_id, twinoid_site_id, twinoid_achievement_key),
+  EXCLUDE USING gist (twinoid_user_id WITH =, twinoid_site_id WITH =, twinoid_achievement_key WITH =, period WITH &&),
+  CONSTRAINT twinoid_site_achievement__user__fk FOREIGN KEY (twinoid_user_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
@@ -0,0 +1,12 @@
+-- Contact relationships between Twinoid users, with the first and last time they were seen
+CREATE TABLE twinoid_user_contacts (
+  twinoid_user_id TWINOID_USER_ID NOT NULL,
+  contact_id TWINOID_USER_ID NOT NULL,
+  friend BOOLEAN NOT NULL,
+  first_seen_at INSTANT NOT NULL,
+  last_seen_at INSTANT NOT NULL,
+  PRIMARY KEY (twinoid_user_id, contact_id),
+  CHECK (first_seen_at <= last_seen_at),
+  CONSTRAINT twinoid_user_contact__user__fk FOREIGN KEY (twinoid_user_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT twinoid_user_contact__contact__fk FOREIGN KEY (contact_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
@@ -0,0 +1,33 @@
+-- Id of the key used to encrypt the token or session key of the row.
+-- Rows encrypted before this version use the main secret (`etwin.secret`).
+ALTER TABLE twinoid_access_tokens
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE old_twinoid_access_tokens
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE twinoid_refresh_tokens
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE old_twinoid_refresh_tokens
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE dinoparc_sessions
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE old_dinoparc_sessions
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE hammerfest_sessions
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE old_hammerfest_sessions
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE popotamo_sessions
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+ALTER TABLE old_popotamo_sessions
+  ADD COLUMN encryption_key_id VARCHAR(64) NOT NULL DEFAULT 'etwin.secret';
+
+ALTER TABLE twinoid_access_tokens ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE old_twinoid_access_tokens ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE twinoid_refresh_tokens ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE old_twinoid_refresh_tokens ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE dinoparc_sessions ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE old_dinoparc_sessions ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE hammerfest_sessions ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE old_hammerfest_sessions ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE popotamo_sessions ALTER COLUMN encryption_key_id DROP DEFAULT;
+ALTER TABLE old_popotamo_sessions ALTER COLUMN encryption_key_id DROP DEFAULT;
@@ -0,0 +1,84 @@
+CREATE DOMAIN dinorpg_dinoz_id AS VARCHAR(10) CHECK (value ~ '^(0|[1-9]\d{0,8})$');
+CREATE DOMAIN dinorpg_dinoz_name AS VARCHAR(50);
+CREATE DOMAIN dinorpg_dinoz_skin AS VARCHAR(30) CHECK (value ~ '^[0-9A-Za-z]{1,30}$');
+CREATE DOMAIN dinorpg_dinoz_race AS VARCHAR(50) CHECK (value IN ('Moueffe', 'Pigmou', 'Winks', 'Planaille', 'Castivore', 'Rocky', 'Pteroz', 'Nuagoz', 'Sirain', 'Hippoclamp', 'Gorilloz', 'Wanwan', 'Santaz', 'Feross', 'Kabuki', 'Mahamuti', 'Toufufu', 'Quetzu', 'Smog', 'Triceragon'));
+CREATE DOMAIN dinorpg_clan_id AS VARCHAR(10) CHECK (value ~ '^(0|[1-9]\d{0,8})$');
+CREATE DOMAIN dinorpg_clan_name AS VARCHAR(50);
+CREATE DOMAIN dinorpg_reward_key AS VARCHAR(30) CHECK (value ~ '^[a-z0-9_]{1,30}$');
+
+-- Known DinoRPG clans
+CREATE TABLE dinorpg_clans (
+  dinorpg_server DINORPG_SERVER NOT NULL,
+  dinorpg_clan_id DINORPG_CLAN_ID NOT NULL,
+  -- Latest known name of the clan
+  name DINORPG_CLAN_NAME NOT NULL,
+  archived_at INSTANT NOT NULL,
+  PRIMARY KEY (dinorpg_server, dinorpg_clan_id),
+  CONSTRAINT dinorpg_clans__server__fk FOREIGN KEY (dinorpg_server) REFERENCES dinorpg_servers(dinorpg_server) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Permanent data for DinoRPG dinoz
+CREATE TABLE dinorpg_dinoz (
+  dinorpg_server DINORPG_SERVER NOT NULL,
+  dinorpg_dinoz_id DINORPG_DINOZ_ID NOT NULL,
+  archived_at INSTANT NOT NULL,
+  PRIMARY KEY (dinorpg_server, dinorpg_dinoz_id),
+  CONSTRAINT dinorpg_dinoz__server__fk FOREIGN KEY (dinorpg_server) REFERENCES dinorpg_servers(dinorpg_server) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Time-variant user data from the profile page <user>
+CREATE TABLE dinorpg_user_profiles (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  dinorpg_server DINORPG_SERVER NOT NULL,
+  dinorpg_user_id DINORPG_USER_ID NOT NULL,
+--
+  dinoz_count U32 NOT NULL,
+  -- Rank and points, both `NULL` when the user is not ranked
+  ranking_rank U32 NULL,
+  ranking_points U32 NULL,
+  dinorpg_clan_id DINORPG_CLAN_ID NULL,
+  -- Number of items in `dinorpg_user_rewards` for this period
+  reward_count U32 NOT NULL,
+  -- Number of items in `dinorpg_user_dinoz` for this period
+  dinoz_list_count U32 NOT NULL,
+  PRIMARY KEY (period, dinorpg_server, dinorpg_user_id),
+  EXCLUDE USING gist (dinorpg_server WITH =, dinorpg_user_id WITH =, period WITH &&),
+  CHECK ((ranking_rank IS NULL) = (ranking_points IS NULL)),
+  CONSTRAINT dinorpg_user_profiles__user__fk FOREIGN KEY (dinorpg_server, dinorpg_user_id) REFERENCES dinorpg_users(dinorpg_server, dinorpg_user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT dinorpg_user_profiles__clan__fk FOREIGN KEY (dinorpg_server, dinorpg_clan_id) REFERENCES dinorpg_clans(dinorpg_server, dinorpg_clan_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Time-variant reward list items <user>
+CREATE TABLE dinorpg_user_rewards (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  dinorpg_server DINORPG_SERVER NOT NULL,
+  dinorpg_user_id DINORPG_USER_ID NOT NULL,
+  offset_in_list U32 NOT NULL,
+--
+  reward_key DINORPG_REWARD_KEY NOT NULL,
+  PRIMARY KEY (period, dinorpg_server, dinorpg_user_id, offset_in_list),
+  EXCLUDE USING gist (dinorpg_server WITH =, dinorpg_user_id WITH =, offset_in_list WITH =, period WITH &&),
+  CONSTRAINT dinorpg_user_rewards__user__fk FOREIGN KEY (dinorpg_server, dinorpg_user_id) REFERENCES dinorpg_users(dinorpg_server, dinorpg_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Time-variant dinoz list items <user>
+CREATE TABLE dinorpg_user_dinoz (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  dinorpg_server DINORPG_SERVER NOT NULL,
+  dinorpg_user_id DINORPG_USER_ID NOT NULL,
+  offset_in_list U32 NOT NULL,
+--
+  dinorpg_dinoz_id DINORPG_DINOZ_ID NOT NULL,
+  name DINORPG_DINOZ_NAME NULL,
+  race DINORPG_DINOZ_RACE NOT NULL,
+  skin DINORPG_DINOZ_SKIN NOT NULL,
+  level U16 NOT NULL,
+  PRIMARY KEY (period, dinorpg_server, dinorpg_user_id, offset_in_list),
+  EXCLUDE USING gist (dinorpg_server WITH =, dinorpg_user_id WITH =, offset_in_list WITH =, period WITH &&),
+  EXCLUDE USING gist (dinorpg_server WITH =, dinorpg_dinoz_id WITH =, period WITH &&),
+  CONSTRAINT dinorpg_user_dinoz__user__fk FOREIGN KEY (dinorpg_server, dinorpg_user_id) REFERENCES dinorpg_users(dinorpg_server, dinorpg_user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT dinorpg_user_dinoz__dinoz__fk FOREIGN KEY (dinorpg_server, dinorpg_dinoz_id) REFERENCES dinorpg_dinoz(dinorpg_server, dinorpg_dinoz_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
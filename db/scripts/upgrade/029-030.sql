@@ -0,0 +1,50 @@
+ALTER TABLE twinoid_user_contacts RENAME TO old_twinoid_user_contacts;
+ALTER INDEX twinoid_user_contacts_pkey RENAME TO old_twinoid_user_contacts_pkey;
+
+-- Time-variant contact list of a Twinoid user
+CREATE TABLE twinoid_user_contact_lists (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  twinoid_user_id TWINOID_USER_ID NOT NULL,
+--
+  -- Number of items in `twinoid_user_contacts` for this period
+  contact_count U32 NOT NULL,
+  PRIMARY KEY (period, twinoid_user_id),
+  EXCLUDE USING gist (twinoid_user_id WITH =, period WITH &&),
+  CONSTRAINT twinoid_user_contact_lists__user__fk FOREIGN KEY (twinoid_user_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- Time-variant contact list items <user>
+CREATE TABLE twinoid_user_contacts (
+  period PERIOD_LOWER NOT NULL,
+  retrieved_at INSTANT_SET NOT NULL,
+  twinoid_user_id TWINOID_USER_ID NOT NULL,
+  offset_in_list U32 NOT NULL,
+--
+  contact_id TWINOID_USER_ID NOT NULL,
+  friend BOOLEAN NOT NULL,
+  PRIMARY KEY (period, twinoid_user_id, offset_in_list),
+  EXCLUDE USING gist (twinoid_user_id WITH =, offset_in_list WITH =, period WITH &&),
+  EXCLUDE USING gist (twinoid_user_id WITH =, contact_id WITH =, period WITH &&),
+  CONSTRAINT twinoid_user_contacts__user__fk FOREIGN KEY (twinoid_user_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE,
+  CONSTRAINT twinoid_user_contacts__contact__fk FOREIGN KEY (contact_id) REFERENCES twinoid_users(twinoid_user_id) ON DELETE RESTRICT ON UPDATE CASCADE
+);
+
+-- The old table only kept the first and last time each contact was seen: keep the contacts present during the latest
+-- retrieval of each list, they form the current list.
+WITH latest_contacts AS (
+  SELECT twinoid_user_id, contact_id, friend, last_seen_at,
+    ROW_NUMBER() OVER (PARTITION BY twinoid_user_id ORDER BY contact_id::INT8) - 1 AS offset_in_list
+  FROM old_twinoid_user_contacts AS c
+  WHERE last_seen_at = (SELECT MAX(last_seen_at) FROM old_twinoid_user_contacts WHERE twinoid_user_id = c.twinoid_user_id)
+)
+INSERT INTO twinoid_user_contacts(period, retrieved_at, twinoid_user_id, offset_in_list, contact_id, friend)
+SELECT PERIOD(last_seen_at, NULL), ARRAY[last_seen_at], twinoid_user_id, offset_in_list, contact_id, friend
+FROM latest_contacts;
+
+INSERT INTO twinoid_user_contact_lists(period, retrieved_at, twinoid_user_id, contact_count)
+SELECT PERIOD(MIN(lower(period)), NULL), ARRAY[MIN(lower(period))], twinoid_user_id, COUNT(*)
+FROM twinoid_user_contacts
+GROUP BY twinoid_user_id;
+
+DROP TABLE old_twinoid_user_contacts;
@@ -0,0 +1,3 @@
+-- `NULL` when the Graph API did not return the points of the achievement
+ALTER TABLE twinoid_site_achievements
+  ALTER COLUMN points DROP NOT NULL;
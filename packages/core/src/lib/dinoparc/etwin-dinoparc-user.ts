@@ -24,6 +24,7 @@ export interface EtwinDinoparcUser {
   archivedAt: Date;
   username: DinoparcUsername;
   coins: NullableLatestTemporal<number>,
+  bills: NullableLatestTemporal<number>,
   dinoz: NullableLatestTemporal<DinoparcDinozIdRef[]>,
   inventory: NullableLatestTemporal<DinoparcItemCounts>,
   collection: NullableLatestTemporal<DinoparcCollection>,
@@ -38,6 +39,7 @@ export const $EtwinDinoparcUser: RecordIoType<EtwinDinoparcUser> = new RecordTyp
     archivedAt: {type: $Date},
     username: {type: $DinoparcUsername},
     coins: {type: $NullableLatestTemporal.apply($Uint32) as IoType<NullableLatestTemporal<number>>},
+    bills: {type: $NullableLatestTemporal.apply($Uint32) as IoType<NullableLatestTemporal<number>>},
     dinoz: {type: $NullableLatestTemporal.apply(new ArrayType({itemType: $DinoparcDinozIdRef, maxLength: 10000})) as IoType<NullableLatestTemporal<DinoparcDinozIdRef[]>>},
     inventory: {type: $NullableLatestTemporal.apply($DinoparcItemCounts) as IoType<NullableLatestTemporal<DinoparcItemCounts>>},
     collection: {type: $NullableLatestTemporal.apply($DinoparcCollection) as IoType<NullableLatestTemporal<DinoparcCollection>>},
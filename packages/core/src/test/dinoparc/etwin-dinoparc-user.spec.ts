@@ -14,6 +14,7 @@ describe("EtwinDinoparcUser", function () {
         archivedAt: new Date("2020-12-18T00:56:12.769Z"),
         username: "demurgos",
         coins: null,
+        bills: null,
         dinoz: null,
         inventory: null,
         collection: null,
@@ -63,6 +64,7 @@ describe("EtwinDinoparcUser", function () {
             value: 3000,
           },
         },
+        bills: null,
         dinoz: {
           latest: {
             period: {
@@ -135,10 +135,11 @@ pub fn get_collection(mut cx: FunctionContext) -> JsResult<JsUndefined> {
 
 pub mod http {
   use crate::clock::get_native_clock;
+  use crate::http::load_http_logger;
   use crate::neon_helpers::NeonNamespace;
   use etwin_core::clock::Clock;
-  use etwin_dinoparc_client::http::{HttpDinoparcClient, HttpDinoparcClientEvent};
-  use etwin_log::{Logger, NoopLogger};
+  use etwin_dinoparc_client::http::{new_logger, HttpDinoparcClient, HttpDinoparcClientEvent};
+  use etwin_log::Logger;
   use neon::prelude::*;
   use std::sync::Arc;
 
@@ -154,7 +155,10 @@ pub mod http {
   pub fn new(mut cx: FunctionContext) -> JsResult<JsHttpDinoparcClient> {
     let clock = cx.argument::<JsValue>(0)?;
     let clock: Arc<dyn Clock> = get_native_clock(&mut cx, clock)?;
-    let logger: Arc<dyn for<'r> Logger<HttpDinoparcClientEvent<'r, &'r [u8]>>> = Arc::new(NoopLogger);
+    let logger = match load_http_logger() {
+      Ok(logger) => new_logger(logger),
+      Err(e) => return cx.throw_error(e.to_string()),
+    };
     #[allow(clippy::type_complexity)]
     let inner: Arc<
       HttpDinoparcClient<Arc<dyn Clock>, Arc<dyn for<'r> Logger<HttpDinoparcClientEvent<'r, &'r [u8]>>>>,
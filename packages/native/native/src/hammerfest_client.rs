@@ -181,9 +181,12 @@ pub fn get_own_shop(mut cx: FunctionContext) -> JsResult<JsUndefined> {
 
 pub mod http {
   use crate::clock::get_native_clock;
+  use crate::http::load_http_logger;
   use crate::neon_helpers::NeonNamespace;
   use etwin_core::clock::Clock;
   use etwin_hammerfest_client::HttpHammerfestClient;
+  use etwin_http::event::{new_logger, HttpClientEvent};
+  use etwin_log::Logger;
   use neon::prelude::*;
   use std::sync::Arc;
 
@@ -193,12 +196,19 @@ pub mod http {
     Ok(ns)
   }
 
-  pub type JsHttpHammerfestClient = JsBox<Arc<HttpHammerfestClient<Arc<dyn Clock>>>>;
+  pub type JsHttpHammerfestClient =
+    JsBox<Arc<HttpHammerfestClient<Arc<dyn Clock>, Arc<dyn for<'r> Logger<HttpClientEvent<'r>>>>>>;
 
   pub fn new(mut cx: FunctionContext) -> JsResult<JsHttpHammerfestClient> {
     let clock = cx.argument::<JsValue>(0)?;
     let clock: Arc<dyn Clock> = get_native_clock(&mut cx, clock)?;
-    let inner: Arc<HttpHammerfestClient<Arc<dyn Clock>>> = Arc::new(HttpHammerfestClient::new(clock).unwrap());
+    let logger = match load_http_logger() {
+      Ok(logger) => new_logger(logger),
+      Err(e) => return cx.throw_error(e.to_string()),
+    };
+    #[allow(clippy::type_complexity)]
+    let inner: Arc<HttpHammerfestClient<Arc<dyn Clock>, Arc<dyn for<'r> Logger<HttpClientEvent<'r>>>>> =
+      Arc::new(HttpHammerfestClient::new(clock, logger).unwrap());
     Ok(cx.boxed(inner))
   }
 }
@@ -0,0 +1,16 @@
+use etwin_config::{FindConfigError, HttpLoggerConfig};
+use etwin_core::types::EtwinError;
+use etwin_http::HttpLogger;
+
+/// Reads the logger of the game HTTP clients from `etwin.toml`, discarding the events if there is no config file.
+pub(crate) fn load_http_logger() -> Result<HttpLogger, EtwinError> {
+  let working_dir = std::env::current_dir()?;
+  match etwin_config::find_config(working_dir) {
+    Ok(config) => Ok(match config.http.map(|http| http.logger).unwrap_or_default() {
+      HttpLoggerConfig::None => HttpLogger::Noop,
+      HttpLoggerConfig::Stderr => HttpLogger::Stderr,
+    }),
+    Err(FindConfigError::NotFound(_)) => Ok(HttpLogger::Noop),
+    Err(e) => Err(format!("failed to read config: {:?}", e).into()),
+  }
+}
@@ -6,6 +6,7 @@ mod dinoparc_client;
 mod dinoparc_store;
 mod hammerfest_client;
 mod hammerfest_store;
+mod http;
 mod link_store;
 mod neon_helpers;
 mod password;
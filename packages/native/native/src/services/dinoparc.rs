@@ -1,9 +1,10 @@
+use crate::dinoparc_client::get_native_dinoparc_client;
 use crate::dinoparc_store::get_native_dinoparc_store;
 use crate::link_store::get_native_link_store;
 use crate::neon_helpers::{resolve_callback_serde, resolve_callback_with, NeonNamespace};
 use crate::user_store::get_native_user_store;
 use etwin_core::auth::AuthContext;
-use etwin_core::dinoparc::{DinoparcStore, GetDinoparcDinozOptions, GetDinoparcUserOptions};
+use etwin_core::dinoparc::{DinoparcClient, DinoparcStore, GetDinoparcDinozOptions, GetDinoparcUserOptions};
 use etwin_core::link::LinkStore;
 use etwin_core::user::UserStore;
 use etwin_services::dinoparc::DinoparcService;
@@ -19,36 +20,46 @@ pub fn create_namespace<'a, C: Context<'a>>(cx: &mut C) -> JsResult<'a, JsObject
 }
 
 pub type JsDinoparcService =
-  JsBox<Arc<DinoparcService<Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>>>;
+  JsBox<Arc<DinoparcService<Arc<dyn DinoparcClient>, Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>>>;
 
 #[allow(clippy::type_complexity)]
 pub fn get_native_dinoparc_service<'a, C: Context<'a>>(
   cx: &mut C,
   value: Handle<JsValue>,
-) -> NeonResult<Arc<DinoparcService<Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>>> {
+) -> NeonResult<
+  Arc<DinoparcService<Arc<dyn DinoparcClient>, Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>>,
+> {
   match value.downcast::<JsDinoparcService, _>(cx) {
     Ok(val) => {
       let val = Arc::clone(&**val);
       Ok(val)
     }
-    Err(_) => cx
-      .throw_type_error::<_, Arc<DinoparcService<Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>>>(
-        "JsDinoparcService".to_string(),
-      ),
+    Err(_) => cx.throw_type_error::<_, Arc<
+      DinoparcService<Arc<dyn DinoparcClient>, Arc<dyn DinoparcStore>, Arc<dyn LinkStore>, Arc<dyn UserStore>>,
+    >>("JsDinoparcService".to_string()),
   }
 }
 
 pub fn new(mut cx: FunctionContext) -> JsResult<JsUndefined> {
-  let dinoparc_store = cx.argument::<JsValue>(0)?;
-  let link_store = cx.argument::<JsValue>(1)?;
-  let user_store = cx.argument::<JsValue>(2)?;
-  let cb = cx.argument::<JsFunction>(3)?.root(&mut cx);
+  let dinoparc_client = cx.argument::<JsValue>(0)?;
+  let dinoparc_store = cx.argument::<JsValue>(1)?;
+  let link_store = cx.argument::<JsValue>(2)?;
+  let user_store = cx.argument::<JsValue>(3)?;
+  let cb = cx.argument::<JsFunction>(4)?.root(&mut cx);
 
+  let dinoparc_client: Arc<dyn DinoparcClient> = get_native_dinoparc_client(&mut cx, dinoparc_client)?;
   let dinoparc_store: Arc<dyn DinoparcStore> = get_native_dinoparc_store(&mut cx, dinoparc_store)?;
   let link_store: Arc<dyn LinkStore> = get_native_link_store(&mut cx, link_store)?;
   let user_store: Arc<dyn UserStore> = get_native_user_store(&mut cx, user_store)?;
 
-  let res = async move { Arc::new(DinoparcService::new(dinoparc_store, link_store, user_store)) };
+  let res = async move {
+    Arc::new(DinoparcService::new(
+      dinoparc_client,
+      dinoparc_store,
+      link_store,
+      user_store,
+    ))
+  };
 
   resolve_callback_with(&mut cx, res, cb, |c: &mut TaskContext, res| Ok(c.boxed(res).upcast()))
 }
@@ -202,8 +202,8 @@ pub mod pg {
   use crate::database::JsPgPool;
   use crate::neon_helpers::{resolve_callback_with, NeonNamespace};
   use etwin_core::clock::Clock;
-  use etwin_core::core::Secret;
-  use etwin_token_store::pg::PgTokenStore;
+  use etwin_core::types::EtwinError;
+  use etwin_token_store::pg::{token_keyring, PgTokenStore};
   use neon::prelude::*;
   use sqlx::PgPool;
   use std::sync::Arc;
@@ -216,22 +216,29 @@ pub mod pg {
 
   pub type JsPgTokenStore = JsBox<Arc<PgTokenStore<Arc<dyn Clock>, Arc<PgPool>>>>;
 
+  /// Creates a store using the token keyring of the Eternal-Twin config, see `token_keyring`.
   pub fn new(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     let clock = cx.argument::<JsValue>(0)?;
     let database = cx.argument::<JsPgPool>(1)?;
-    let database_secret = cx.argument::<JsString>(2)?;
-    let cb = cx.argument::<JsFunction>(3)?.root(&mut cx);
+    let cb = cx.argument::<JsFunction>(2)?.root(&mut cx);
 
     let clock: Arc<dyn Clock> = get_native_clock(&mut cx, clock)?;
     let database = Arc::new(PgPool::clone(&database));
-    let database_secret: String = database_secret.value(&mut cx);
-    let database_secret = Secret::new(database_secret);
     let res = async move {
-      PgTokenStore::new(clock, database, database_secret).await.map(|store| {
-        #[allow(clippy::type_complexity)]
-        let inner: Arc<PgTokenStore<Arc<dyn Clock>, Arc<PgPool>>> = Arc::new(store);
-        inner
-      })
+      let config = std::env::current_dir()
+        .map_err(|e| -> EtwinError { Box::new(e) })
+        .and_then(|dir| {
+          etwin_config::find_config(dir).map_err(|e| -> EtwinError { format!("failed to read config: {:?}", e).into() })
+        })?;
+      let keyring = token_keyring(&config)?;
+      PgTokenStore::with_keyring(clock, database, keyring)
+        .await
+        .map(|store| {
+          #[allow(clippy::type_complexity)]
+          let inner: Arc<PgTokenStore<Arc<dyn Clock>, Arc<PgPool>>> = Arc::new(store);
+          inner
+        })
+        .map_err(|e| -> EtwinError { e.to_string().into() })
     };
 
     resolve_callback_with(&mut cx, res, cb, |c: &mut TaskContext, res| {
@@ -1,7 +1,9 @@
 use crate::neon_helpers::{resolve_callback_serde, NeonNamespace};
 use crate::twinoid_client::http::JsHttpTwinoidClient;
 use etwin_core::oauth::RfcOauthAccessTokenKey;
+use etwin_core::twinoid::api::UserQuery;
 use etwin_core::twinoid::{TwinoidApiAuth, TwinoidClient};
+use etwin_core::types::EtwinError;
 use neon::prelude::*;
 use std::sync::Arc;
 
@@ -33,14 +35,22 @@ pub fn get_me(mut cx: FunctionContext) -> JsResult<JsUndefined> {
 
   let options: RfcOauthAccessTokenKey = serde_json::from_str(&options_json.value(&mut cx)).unwrap();
 
-  let res = async move { inner.get_me_short(TwinoidApiAuth::Token(options)).await };
+  let res = async move {
+    inner
+      .get_me(TwinoidApiAuth::Token(options), &UserQuery::new().name().title())
+      .await
+      .map_err(EtwinError::from)
+  };
   resolve_callback_serde(&mut cx, res, cb)
 }
 
 pub mod http {
   use crate::clock::get_native_clock;
+  use crate::http::load_http_logger;
   use crate::neon_helpers::NeonNamespace;
   use etwin_core::clock::Clock;
+  use etwin_http::event::{new_logger, HttpClientEvent};
+  use etwin_log::Logger;
   use etwin_twinoid_client::http::HttpTwinoidClient;
   use neon::prelude::*;
   use std::sync::Arc;
@@ -51,13 +61,19 @@ pub mod http {
     Ok(ns)
   }
 
-  pub type JsHttpTwinoidClient = JsBox<Arc<HttpTwinoidClient<Arc<dyn Clock>>>>;
+  pub type JsHttpTwinoidClient =
+    JsBox<Arc<HttpTwinoidClient<Arc<dyn Clock>, Arc<dyn for<'r> Logger<HttpClientEvent<'r>>>>>>;
 
   pub fn new(mut cx: FunctionContext) -> JsResult<JsHttpTwinoidClient> {
     let clock = cx.argument::<JsValue>(0)?;
     let clock: Arc<dyn Clock> = get_native_clock(&mut cx, clock)?;
+    let logger = match load_http_logger() {
+      Ok(logger) => new_logger(logger),
+      Err(e) => return cx.throw_error(e.to_string()),
+    };
     #[allow(clippy::type_complexity)]
-    let inner: Arc<HttpTwinoidClient<Arc<dyn Clock>>> = Arc::new(HttpTwinoidClient::new(clock).unwrap());
+    let inner: Arc<HttpTwinoidClient<Arc<dyn Clock>, Arc<dyn for<'r> Logger<HttpClientEvent<'r>>>>> =
+      Arc::new(HttpTwinoidClient::new(clock, logger).unwrap());
     Ok(cx.boxed(inner))
   }
 }
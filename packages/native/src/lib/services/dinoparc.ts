@@ -18,6 +18,7 @@ import { JSON_WRITER } from "kryo-json/json-writer";
 import { promisify } from "util";
 
 import native from "../../native/index.js";
+import { NativeDinoparcClient } from "../dinoparc-client.js";
 import { NativeDinoparcStore } from "../dinoparc-store.js";
 import { NativeLinkStore } from "../link-store.js";
 import { NativeUserStore } from "../user-store.js";
@@ -25,6 +26,7 @@ import { NativeUserStore } from "../user-store.js";
 declare const NativeDinoparcServiceBox: unique symbol;
 
 export interface NativeDinoparcServiceOptions {
+  dinoparcClient: NativeDinoparcClient;
   dinoparcStore: NativeDinoparcStore;
   linkStore: NativeLinkStore;
   userStore: NativeUserStore;
@@ -42,7 +44,7 @@ export class NativeDinoparcService implements DinoparcService {
   }
 
   public static async create(options: Readonly<NativeDinoparcServiceOptions>): Promise<NativeDinoparcService> {
-    const box = await NativeDinoparcService.NEW(options.dinoparcStore.box, options.linkStore.box, options.userStore.box);
+    const box = await NativeDinoparcService.NEW(options.dinoparcClient.box, options.dinoparcStore.box, options.linkStore.box, options.userStore.box);
     return new NativeDinoparcService(box);
   }
 
@@ -132,10 +132,12 @@ export class MemTokenStore extends NativeTokenStore {
   }
 }
 
+/**
+ * The encryption keys are read from the `[db]` section of the Eternal-Twin config (`token_key` and `token_keys`).
+ */
 export interface PgTokenStoreOptions {
   clock: NativeClock;
   database: Database;
-  databaseSecret: string;
 }
 
 export class PgTokenStore extends NativeTokenStore {
@@ -146,7 +148,7 @@ export class PgTokenStore extends NativeTokenStore {
   }
 
   public static async create(options: Readonly<PgTokenStoreOptions>): Promise<PgTokenStore> {
-    const box = await PgTokenStore.NEW(options.clock.box, options.database.box, options.databaseSecret);
+    const box = await PgTokenStore.NEW(options.clock.box, options.database.box);
     return new PgTokenStore(box);
   }
 }
@@ -61,7 +61,7 @@ describe("NativeTokenStore", function () {
       const secretKeyStr: string = config.etwin.secret;
       const hammerfestStore = await PgHammerfestStore.create({clock, database: nativeDatabase, databaseSecret: secretKeyStr, uuidGenerator});
       const twinoidStore = new PgTwinoidStore({clock, database: nativeDatabase});
-      const token = await PgTokenStore.create({clock, database: nativeDatabase, databaseSecret: secretKeyStr});
+      const token = await PgTokenStore.create({clock, database: nativeDatabase});
       try {
         return await fn({hammerfestStore, twinoidStore, token});
       } finally {
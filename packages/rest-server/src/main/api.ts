@@ -104,8 +104,8 @@ export async function createApi(config: Config): Promise<{ api: Api; teardown():
   const forum = new PgForumService(database, uuidGenerator, userStore, forumConfig);
   const announcement = new PgAnnouncementService({database, uuidGenerator, forum});
 
-  const token = await PgTokenStore.create({clock, database: nativeDatabase, databaseSecret: secretKeyStr});
-  const dinoparc = await NativeDinoparcService.create({dinoparcStore, linkStore, userStore});
+  const token = await PgTokenStore.create({clock, database: nativeDatabase});
+  const dinoparc = await NativeDinoparcService.create({dinoparcClient, dinoparcStore, linkStore, userStore});
   const hammerfest = await NativeHammerfestService.create({hammerfestClient, hammerfestStore, linkStore, userStore});
   const twinoid = new DefaultTwinoidService({twinoidStore, link});
   const user = new DefaultUserService({
@@ -92,7 +92,7 @@ export async function withTestServer<R>(isDev: boolean, fn: (server: TestServer)
     const twinoidStore = new PgTwinoidStore({clock, database: nativeDatabase});
     const linkStore = new PgLinkStore({clock, database: nativeDatabase});
     const link = new DefaultLinkService({dinoparcStore, hammerfestStore, linkStore, twinoidStore, userStore});
-    const dinoparc = await NativeDinoparcService.create({dinoparcStore, linkStore, userStore});
+    const dinoparc = await NativeDinoparcService.create({dinoparcClient, dinoparcStore, linkStore, userStore});
     const hammerfest = await NativeHammerfestService.create({hammerfestClient, hammerfestStore, linkStore, userStore});
     const twinoid = new DefaultTwinoidService({twinoidStore, link});
     const oauthProviderStore = new PgOauthProviderStore({
@@ -127,7 +127,7 @@ export async function withTestServer<R>(isDev: boolean, fn: (server: TestServer)
       uuidGenerator
     });
     const koaAuth = new KoaAuth(auth);
-    const token = await PgTokenStore.create({clock, database: nativeDatabase, databaseSecret: secretKeyStr});
+    const token = await PgTokenStore.create({clock, database: nativeDatabase});
     const forumConfig: ForumConfig = {
       postsPerPage: config.forum.postsPerPage,
       threadsPerPage: config.forum.threadsPerPage
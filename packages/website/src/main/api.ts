@@ -184,7 +184,7 @@ async function createApi(config: Config): Promise<{ api: Api; teardown(): Promis
       uuidGenerator
     });
     forum = new PgForumService(database, uuidGenerator, userStore, forumConfig);
-    token = await PgTokenStore.create({clock, database: nativeDatabase, databaseSecret: secretKeyStr});
+    token = await PgTokenStore.create({clock, database: nativeDatabase});
     announcement = new PgAnnouncementService({database, uuidGenerator, forum});
 
     teardown = async function (): Promise<void> {
@@ -192,7 +192,7 @@ async function createApi(config: Config): Promise<{ api: Api; teardown(): Promis
     };
   }
 
-  const dinoparc = await NativeDinoparcService.create({dinoparcStore, linkStore, userStore});
+  const dinoparc = await NativeDinoparcService.create({dinoparcClient, dinoparcStore, linkStore, userStore});
   const hammerfest = await NativeHammerfestService.create({hammerfestClient, hammerfestStore, linkStore, userStore});
   const twinoid = new DefaultTwinoidService({twinoidStore, link});
   const user = new DefaultUserService({